@@ -946,6 +946,10 @@ def external_crates_repository(name, cargo_lockfile, lockfile, sanitizers_enable
             "publicsuffix": crate.spec(
                 version = "^2.2.3",
             ),
+            "quick-xml": crate.spec(
+                version = "^0.26.0",
+                features = ["serialize", "overlapped-lists"],
+            ),
             "quickcheck": crate.spec(
                 version = "^1.0.3",
             ),
@@ -1407,6 +1411,9 @@ def external_crates_repository(name, cargo_lockfile, lockfile, sanitizers_enable
             "wat": crate.spec(
                 version = "1.212.0",
             ),
+            "webpki-roots": crate.spec(
+                version = "^0.26.1",
+            ),
             "wee_alloc": crate.spec(
                 version = "^0.4.3",
             ),
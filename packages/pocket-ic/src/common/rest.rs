@@ -466,6 +466,8 @@ pub struct InstanceConfig {
     pub subnet_config_set: ExtendedSubnetConfigSet,
     pub state_dir: Option<PathBuf>,
     pub nonmainnet_features: bool,
+    pub coverage_instrumentation: bool,
+    pub instruction_profiling: bool,
     pub log_level: Option<String>,
     pub bitcoind_addr: Option<SocketAddr>,
 }
@@ -774,6 +776,9 @@ pub enum CanisterHttpMethod {
     GET,
     POST,
     HEAD,
+    PUT,
+    PATCH,
+    DELETE,
 }
 
 #[derive(
@@ -908,3 +913,111 @@ impl From<MockCanisterHttpResponse> for RawMockCanisterHttpResponse {
         }
     }
 }
+
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+pub struct RawPendingCall {
+    pub subnet_id: RawSubnetId,
+    pub call_id: u64,
+    pub sender: RawCanisterId,
+    pub receiver: RawCanisterId,
+    pub method: String,
+    #[serde(deserialize_with = "base64::deserialize")]
+    #[serde(serialize_with = "base64::serialize")]
+    pub payload: Vec<u8>,
+    pub cycles: u128,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, Hash, Eq, PartialEq, Ord, PartialOrd)]
+pub struct PendingCall {
+    pub subnet_id: Principal,
+    pub call_id: u64,
+    pub sender: Principal,
+    pub receiver: Principal,
+    pub method: String,
+    #[serde(deserialize_with = "base64::deserialize")]
+    #[serde(serialize_with = "base64::serialize")]
+    pub payload: Vec<u8>,
+    pub cycles: u128,
+}
+
+impl From<RawPendingCall> for PendingCall {
+    fn from(raw_pending_call: RawPendingCall) -> Self {
+        Self {
+            subnet_id: raw_pending_call.subnet_id.into(),
+            call_id: raw_pending_call.call_id,
+            sender: raw_pending_call.sender.into(),
+            receiver: raw_pending_call.receiver.into(),
+            method: raw_pending_call.method,
+            payload: raw_pending_call.payload,
+            cycles: raw_pending_call.cycles,
+        }
+    }
+}
+
+impl From<PendingCall> for RawPendingCall {
+    fn from(pending_call: PendingCall) -> Self {
+        Self {
+            subnet_id: pending_call.subnet_id.into(),
+            call_id: pending_call.call_id,
+            sender: pending_call.sender.into(),
+            receiver: pending_call.receiver.into(),
+            method: pending_call.method,
+            payload: pending_call.payload,
+            cycles: pending_call.cycles,
+        }
+    }
+}
+
+#[derive(
+    Clone, Serialize, Deserialize, Debug, Hash, Eq, PartialEq, Ord, PartialOrd, JsonSchema,
+)]
+pub struct CallReject {
+    pub reject_code: u64,
+    pub message: String,
+}
+
+#[derive(
+    Clone, Serialize, Deserialize, Debug, Hash, Eq, PartialEq, Ord, PartialOrd, JsonSchema,
+)]
+pub enum CallResponse {
+    Reply(
+        #[serde(deserialize_with = "base64::deserialize")]
+        #[serde(serialize_with = "base64::serialize")]
+        Vec<u8>,
+    ),
+    Reject(CallReject),
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+pub struct RawMockCallResponse {
+    pub subnet_id: RawSubnetId,
+    pub call_id: u64,
+    pub response: CallResponse,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, Hash, Eq, PartialEq, Ord, PartialOrd)]
+pub struct MockCallResponse {
+    pub subnet_id: Principal,
+    pub call_id: u64,
+    pub response: CallResponse,
+}
+
+impl From<RawMockCallResponse> for MockCallResponse {
+    fn from(raw_mock_call_response: RawMockCallResponse) -> Self {
+        Self {
+            subnet_id: raw_mock_call_response.subnet_id.into(),
+            call_id: raw_mock_call_response.call_id,
+            response: raw_mock_call_response.response,
+        }
+    }
+}
+
+impl From<MockCallResponse> for RawMockCallResponse {
+    fn from(mock_call_response: MockCallResponse) -> Self {
+        Self {
+            subnet_id: mock_call_response.subnet_id.into(),
+            call_id: mock_call_response.call_id,
+            response: mock_call_response.response,
+        }
+    }
+}
@@ -35,8 +35,8 @@
 //!
 use crate::common::rest::{
     BlobCompression, BlobId, CanisterHttpRequest, DtsFlag, ExtendedSubnetConfigSet, HttpsConfig,
-    InstanceId, MockCanisterHttpResponse, RawEffectivePrincipal, RawMessageId, SubnetId,
-    SubnetKind, SubnetSpec, Topology,
+    InstanceId, MockCallResponse, MockCanisterHttpResponse, PendingCall, RawEffectivePrincipal,
+    RawMessageId, SubnetId, SubnetKind, SubnetSpec, Topology,
 };
 use crate::nonblocking::PocketIc as PocketIcAsync;
 use candid::{
@@ -45,7 +45,9 @@ use candid::{
     Principal,
 };
 pub use ic_cdk::api::management_canister::main::CanisterSettings;
-use ic_cdk::api::management_canister::main::{CanisterId, CanisterStatusResponse};
+use ic_cdk::api::management_canister::main::{
+    CanisterId, CanisterLogRecord, CanisterStatusResponse, Snapshot,
+};
 use ic_transport_types::SubnetMetrics;
 use reqwest::Url;
 use schemars::JsonSchema;
@@ -81,8 +83,11 @@ pub struct PocketIcBuilder {
     max_request_time_ms: Option<u64>,
     state_dir: Option<PathBuf>,
     nonmainnet_features: bool,
+    coverage_instrumentation: bool,
+    instruction_profiling: bool,
     log_level: Option<Level>,
     bitcoind_addr: Option<SocketAddr>,
+    exported_canisters: Vec<PathBuf>,
 }
 
 #[allow(clippy::new_without_default)]
@@ -94,8 +99,11 @@ impl PocketIcBuilder {
             max_request_time_ms: Some(DEFAULT_MAX_REQUEST_TIME_MS),
             state_dir: None,
             nonmainnet_features: false,
+            coverage_instrumentation: false,
+            instruction_profiling: false,
             log_level: None,
             bitcoind_addr: None,
+            exported_canisters: vec![],
         }
     }
 
@@ -107,29 +115,48 @@ impl PocketIcBuilder {
 
     pub fn build(self) -> PocketIc {
         let server_url = self.server_url.unwrap_or_else(crate::start_or_reuse_server);
-        PocketIc::from_components(
+        let pic = PocketIc::from_components(
             self.config.unwrap_or_default(),
             server_url,
             self.max_request_time_ms,
             self.state_dir,
             self.nonmainnet_features,
+            self.coverage_instrumentation,
+            self.instruction_profiling,
             self.log_level,
             self.bitcoind_addr,
-        )
+        );
+        for path in self.exported_canisters {
+            pic.create_canister_from_exported_state(&path)
+                .unwrap_or_else(|e| {
+                    panic!("Failed to create canister from {}: {}", path.display(), e)
+                });
+        }
+        pic
     }
 
     pub async fn build_async(self) -> PocketIcAsync {
         let server_url = self.server_url.unwrap_or_else(crate::start_or_reuse_server);
-        PocketIcAsync::from_components(
+        let pic = PocketIcAsync::from_components(
             self.config.unwrap_or_default(),
             server_url,
             self.max_request_time_ms,
             self.state_dir,
             self.nonmainnet_features,
+            self.coverage_instrumentation,
+            self.instruction_profiling,
             self.log_level,
             self.bitcoind_addr,
         )
-        .await
+        .await;
+        for path in self.exported_canisters {
+            pic.create_canister_from_exported_state(&path)
+                .await
+                .unwrap_or_else(|e| {
+                    panic!("Failed to create canister from {}: {}", path.display(), e)
+                });
+        }
+        pic
     }
 
     pub fn with_server_url(mut self, server_url: Url) -> Self {
@@ -152,6 +179,20 @@ impl PocketIcBuilder {
         self
     }
 
+    /// Instrument all canister Wasm modules with basic block counters so that a code coverage
+    /// report can be retrieved via `PocketIc::get_canister_coverage`.
+    pub fn with_coverage_instrumentation(mut self) -> Self {
+        self.coverage_instrumentation = true;
+        self
+    }
+
+    /// Profile the instructions executed by canister Wasm modules so that the profile of the last
+    /// execution of a canister can be retrieved via `PocketIc::get_instruction_profile`.
+    pub fn with_instruction_profiling(mut self) -> Self {
+        self.instruction_profiling = true;
+        self
+    }
+
     pub fn with_log_level(mut self, log_level: Level) -> Self {
         self.log_level = Some(log_level);
         self
@@ -249,6 +290,15 @@ impl PocketIcBuilder {
         self
     }
 
+    /// Add a canister with state exported from a checkpoint (e.g., of an IC mainnet subnet)
+    /// into a directory using `state-tool export_canister`. The canister is created with its original canister ID
+    /// after the PocketIC instance is created (see `PocketIc::create_canister_from_exported_state`).
+    /// Note that the provided path must be accessible for the client.
+    pub fn with_canister_state(mut self, path_to_exported_canister: PathBuf) -> Self {
+        self.exported_canisters.push(path_to_exported_canister);
+        self
+    }
+
     /// Add an empty sns subnet
     pub fn with_sns_subnet(mut self) -> Self {
         let mut config = self.config.unwrap_or_default();
@@ -355,6 +405,8 @@ impl PocketIc {
         max_request_time_ms: Option<u64>,
         state_dir: Option<PathBuf>,
         nonmainnet_features: bool,
+        coverage_instrumentation: bool,
+        instruction_profiling: bool,
         log_level: Option<Level>,
         bitcoind_addr: Option<SocketAddr>,
     ) -> Self {
@@ -375,6 +427,8 @@ impl PocketIc {
                 max_request_time_ms,
                 state_dir,
                 nonmainnet_features,
+                coverage_instrumentation,
+                instruction_profiling,
                 log_level,
                 bitcoind_addr,
             )
@@ -425,6 +479,25 @@ impl PocketIc {
         runtime.block_on(async { self.pocket_ic.get_stable_memory(canister_id).await })
     }
 
+    /// Get the code coverage of a canister in the LCOV format.
+    /// The PocketIC instance must have been created with coverage instrumentation
+    /// (see `PocketIcBuilder::with_coverage_instrumentation`).
+    #[instrument(skip(self), fields(instance_id=self.pocket_ic.instance_id, canister_id = %canister_id.to_string()))]
+    pub fn get_canister_coverage(&self, canister_id: CanisterId) -> String {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async { self.pocket_ic.get_canister_coverage(canister_id).await })
+    }
+
+    /// Get the instruction profile of the last execution of a canister in the folded stack
+    /// format understood by flamegraph tools.
+    /// The PocketIC instance must have been created with instruction profiling
+    /// (see `PocketIcBuilder::with_instruction_profiling`).
+    #[instrument(skip(self), fields(instance_id=self.pocket_ic.instance_id, canister_id = %canister_id.to_string()))]
+    pub fn get_instruction_profile(&self, canister_id: CanisterId) -> String {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async { self.pocket_ic.get_instruction_profile(canister_id).await })
+    }
+
     /// List all instances and their status.
     #[instrument(ret)]
     pub fn list_instances() -> Vec<String> {
@@ -860,6 +933,102 @@ impl PocketIc {
         runtime.block_on(async { self.pocket_ic.delete_canister(canister_id, sender).await })
     }
 
+    /// Fetch the logs of a canister (e.g., produced by `ic0.debug_print`).
+    #[instrument(skip(self), fields(instance_id=self.pocket_ic.instance_id, canister_id = %canister_id.to_string(), sender = %sender.unwrap_or(Principal::anonymous()).to_string()))]
+    pub fn fetch_canister_logs(
+        &self,
+        canister_id: CanisterId,
+        sender: Option<Principal>,
+    ) -> Result<Vec<CanisterLogRecord>, CallError> {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async {
+            self.pocket_ic
+                .fetch_canister_logs(canister_id, sender)
+                .await
+        })
+    }
+
+    /// Take a snapshot of a canister, optionally replacing an existing snapshot.
+    #[instrument(skip(self), fields(instance_id=self.pocket_ic.instance_id, canister_id = %canister_id.to_string(), sender = %sender.unwrap_or(Principal::anonymous()).to_string()))]
+    pub fn take_canister_snapshot(
+        &self,
+        canister_id: CanisterId,
+        sender: Option<Principal>,
+        replace_snapshot: Option<Vec<u8>>,
+    ) -> Result<Snapshot, CallError> {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async {
+            self.pocket_ic
+                .take_canister_snapshot(canister_id, sender, replace_snapshot)
+                .await
+        })
+    }
+
+    /// Load a snapshot of a canister, replacing the canister's current state.
+    #[instrument(skip(self), fields(instance_id=self.pocket_ic.instance_id, canister_id = %canister_id.to_string(), sender = %sender.unwrap_or(Principal::anonymous()).to_string()))]
+    pub fn load_canister_snapshot(
+        &self,
+        canister_id: CanisterId,
+        sender: Option<Principal>,
+        snapshot_id: Vec<u8>,
+    ) -> Result<(), CallError> {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async {
+            self.pocket_ic
+                .load_canister_snapshot(canister_id, sender, snapshot_id)
+                .await
+        })
+    }
+
+    /// List the snapshots of a canister.
+    #[instrument(skip(self), fields(instance_id=self.pocket_ic.instance_id, canister_id = %canister_id.to_string(), sender = %sender.unwrap_or(Principal::anonymous()).to_string()))]
+    pub fn list_canister_snapshots(
+        &self,
+        canister_id: CanisterId,
+        sender: Option<Principal>,
+    ) -> Result<Vec<Snapshot>, CallError> {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async {
+            self.pocket_ic
+                .list_canister_snapshots(canister_id, sender)
+                .await
+        })
+    }
+
+    /// Delete a snapshot of a canister.
+    #[instrument(skip(self), fields(instance_id=self.pocket_ic.instance_id, canister_id = %canister_id.to_string(), sender = %sender.unwrap_or(Principal::anonymous()).to_string()))]
+    pub fn delete_canister_snapshot(
+        &self,
+        canister_id: CanisterId,
+        sender: Option<Principal>,
+        snapshot_id: Vec<u8>,
+    ) -> Result<(), CallError> {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async {
+            self.pocket_ic
+                .delete_canister_snapshot(canister_id, sender, snapshot_id)
+                .await
+        })
+    }
+
+    /// Create a canister from its state exported from a checkpoint (e.g., of an IC mainnet subnet)
+    /// into a directory using `state-tool export_canister`. Note that the provided path must be accessible for the client.
+    ///
+    /// The canister is created with its original canister ID (on a new subnet if no subnet
+    /// of the PocketIC instance contains that canister ID) and its Wasm module, Wasm memory,
+    /// stable memory, Wasm chunk store, controllers, and settings are restored
+    /// from the exported state. The canister receives its exported cycles balance
+    /// before its state is restored and thus ends up with a slightly lower cycles balance.
+    #[instrument(skip(self), fields(instance_id=self.pocket_ic.instance_id, path = %path.display()))]
+    pub fn create_canister_from_exported_state(&self, path: &Path) -> Result<CanisterId, String> {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async {
+            self.pocket_ic
+                .create_canister_from_exported_state(path)
+                .await
+        })
+    }
+
     /// Checks whether the provided canister exists.
     #[instrument(ret(Display), skip(self), fields(instance_id=self.pocket_ic.instance_id, canister_id = %canister_id.to_string()))]
     pub fn canister_exists(&self, canister_id: CanisterId) -> bool {
@@ -933,6 +1102,36 @@ impl PocketIc {
                 .await
         })
     }
+
+    /// Register a canister ID whose incoming calls are to be mocked.
+    /// Calls from canisters on this PocketIC instance to the registered canister ID
+    /// are not delivered, but retained as pending calls (see `PocketIc::get_pending_calls`)
+    /// until the test driver responds to them using `PocketIc::mock_call_response`.
+    /// The canister ID must not belong to any subnet of the PocketIC instance.
+    #[instrument(skip(self), fields(instance_id=self.pocket_ic.instance_id, canister_id = %canister_id.to_string()))]
+    pub fn register_mocked_callee(&self, canister_id: CanisterId) {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async { self.pocket_ic.register_mocked_callee(canister_id).await })
+    }
+
+    /// Get the pending calls to mocked callees (see `PocketIc::register_mocked_callee`).
+    /// Note that a call to a mocked callee only becomes pending once the calling
+    /// canister has executed (e.g., after a `PocketIc::tick`). The ingress message
+    /// triggering the call should thus be submitted using `PocketIc::submit_call`
+    /// and awaited using `PocketIc::await_call` once the call has been mocked.
+    #[instrument(ret, skip(self), fields(instance_id=self.pocket_ic.instance_id))]
+    pub fn get_pending_calls(&self) -> Vec<PendingCall> {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async { self.pocket_ic.get_pending_calls().await })
+    }
+
+    /// Mock a response (reply or reject) to a pending call to a mocked callee.
+    /// Any cycles attached to the call are refunded to the caller.
+    #[instrument(ret, skip(self), fields(instance_id=self.pocket_ic.instance_id))]
+    pub fn mock_call_response(&self, mock_call_response: MockCallResponse) {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async { self.pocket_ic.mock_call_response(mock_call_response).await })
+    }
 }
 
 impl Default for PocketIc {
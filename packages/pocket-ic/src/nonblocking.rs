@@ -1,33 +1,38 @@
 use crate::common::rest::{
     ApiResponse, AutoProgressConfig, BlobCompression, BlobId, CanisterHttpRequest,
     CreateHttpGatewayResponse, CreateInstanceResponse, ExtendedSubnetConfigSet, HttpGatewayBackend,
-    HttpGatewayConfig, HttpGatewayInfo, HttpsConfig, InstanceConfig, InstanceId,
-    MockCanisterHttpResponse, RawAddCycles, RawCanisterCall, RawCanisterHttpRequest, RawCanisterId,
-    RawCanisterResult, RawCycles, RawEffectivePrincipal, RawMessageId, RawMockCanisterHttpResponse,
-    RawSetStableMemory, RawStableMemory, RawSubmitIngressResult, RawSubnetId, RawTime,
-    RawVerifyCanisterSigArg, RawWasmResult, SubnetId, Topology,
+    HttpGatewayConfig, HttpGatewayInfo, HttpsConfig, InstanceConfig, InstanceId, MockCallResponse,
+    MockCanisterHttpResponse, PendingCall, RawAddCycles, RawCanisterCall, RawCanisterHttpRequest,
+    RawCanisterId, RawCanisterResult, RawCycles, RawEffectivePrincipal, RawMessageId,
+    RawMockCallResponse, RawMockCanisterHttpResponse, RawPendingCall, RawSetStableMemory,
+    RawStableMemory, RawSubmitIngressResult, RawSubnetId, RawTime, RawVerifyCanisterSigArg,
+    RawWasmResult, SubnetId, Topology,
 };
 pub use crate::DefaultEffectiveCanisterIdError;
 use crate::{CallError, PocketIcBuilder, UserError, WasmResult};
 use candid::{
-    decode_args, encode_args,
+    decode_args, decode_one, encode_args,
     utils::{ArgumentDecoder, ArgumentEncoder},
     CandidType, Deserialize, Nat, Principal,
 };
 use ic_cdk::api::management_canister::main::{
-    CanisterId, CanisterIdRecord, CanisterInstallMode, CanisterSettings, CanisterStatusResponse,
-    ChunkHash, ClearChunkStoreArgument, InstallChunkedCodeArgument, InstallCodeArgument,
-    SkipPreUpgrade, UpdateSettingsArgument, UploadChunkArgument,
+    CanisterId, CanisterIdRecord, CanisterInstallMode, CanisterLogRecord, CanisterSettings,
+    CanisterStatusResponse, ChunkHash, ClearChunkStoreArgument, DeleteCanisterSnapshotArgs,
+    FetchCanisterLogsResponse, InstallChunkedCodeArgument, InstallCodeArgument,
+    LoadCanisterSnapshotArgs, SkipPreUpgrade, Snapshot, TakeCanisterSnapshotArgs,
+    UpdateSettingsArgument, UploadChunkArgument,
 };
 use ic_transport_types::{ReadStateResponse, SubnetMetrics};
 use reqwest::{StatusCode, Url};
 use serde::{de::DeserializeOwned, Serialize};
+use serde_bytes::ByteBuf;
 use sha2::{Digest, Sha256};
 use slog::Level;
 use std::fs::File;
 use std::future::Future;
+use std::io::Read;
 use std::net::SocketAddr;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::{Duration, SystemTime};
 use tracing::{debug, instrument, warn};
 use tracing_appender::non_blocking::WorkerGuard;
@@ -58,6 +63,17 @@ const INSTALL_CHUNKED_CODE_THRESHOLD: usize = 2_000_000; // 2 MB
 // in the IC protocol.
 const INSTALL_CODE_CHUNK_SIZE: usize = 1 << 20; // 1 MiB
 
+// The maximum size of one slice of data when uploading a canister snapshot.
+// This constant is specified in the IC protocol.
+const SNAPSHOT_DATA_CHUNK_SIZE: usize = 2_000_000; // 2 MB
+
+// The files in a directory written by `state-tool export_canister`.
+const EXPORTED_METADATA_FILE: &str = "metadata.bin";
+const EXPORTED_WASM_MODULE_FILE: &str = "wasm_module.wasm";
+const EXPORTED_WASM_MEMORY_FILE: &str = "wasm_memory.bin";
+const EXPORTED_STABLE_MEMORY_FILE: &str = "stable_memory.bin";
+const EXPORTED_WASM_CHUNKS_DIR: &str = "wasm_chunks";
+
 enum HttpMethod {
     Get,
     Post,
@@ -91,6 +107,8 @@ impl PocketIc {
         max_request_time_ms: Option<u64>,
         state_dir: Option<PathBuf>,
         nonmainnet_features: bool,
+        coverage_instrumentation: bool,
+        instruction_profiling: bool,
         log_level: Option<Level>,
         bitcoind_addr: Option<SocketAddr>,
     ) -> Self {
@@ -104,6 +122,8 @@ impl PocketIc {
             subnet_config_set,
             state_dir,
             nonmainnet_features,
+            coverage_instrumentation,
+            instruction_profiling,
             log_level: log_level.map(|l| l.to_string()),
             bitcoind_addr,
         };
@@ -201,6 +221,41 @@ impl PocketIc {
         blob
     }
 
+    /// Get the code coverage of a canister in the LCOV format.
+    /// The PocketIC instance must have been created with coverage instrumentation
+    /// (see `PocketIcBuilder::with_coverage_instrumentation`).
+    #[instrument(skip(self), fields(instance_id=self.instance_id, canister_id = %canister_id.to_string()))]
+    pub async fn get_canister_coverage(&self, canister_id: CanisterId) -> String {
+        let endpoint = "read/get_canister_coverage";
+        let lcov: Vec<u8> = self
+            .post(
+                endpoint,
+                RawCanisterId {
+                    canister_id: canister_id.as_slice().to_vec(),
+                },
+            )
+            .await;
+        String::from_utf8(lcov).expect("Coverage report is not valid UTF-8")
+    }
+
+    /// Get the instruction profile of the last execution of a canister in the folded stack
+    /// format understood by flamegraph tools.
+    /// The PocketIC instance must have been created with instruction profiling
+    /// (see `PocketIcBuilder::with_instruction_profiling`).
+    #[instrument(skip(self), fields(instance_id=self.instance_id, canister_id = %canister_id.to_string()))]
+    pub async fn get_instruction_profile(&self, canister_id: CanisterId) -> String {
+        let endpoint = "read/get_instruction_profile";
+        let profile: Vec<u8> = self
+            .post(
+                endpoint,
+                RawCanisterId {
+                    canister_id: canister_id.as_slice().to_vec(),
+                },
+            )
+            .await;
+        String::from_utf8(profile).expect("Instruction profile is not valid UTF-8")
+    }
+
     /// List all instances and their status.
     #[instrument(ret)]
     pub async fn list_instances() -> Vec<String> {
@@ -965,6 +1020,281 @@ impl PocketIc {
         .await
     }
 
+    /// Fetch the logs of a canister (e.g., produced by `ic0.debug_print`).
+    #[instrument(skip(self), fields(instance_id=self.instance_id, canister_id = %canister_id.to_string(), sender = %sender.unwrap_or(Principal::anonymous()).to_string()))]
+    pub async fn fetch_canister_logs(
+        &self,
+        canister_id: CanisterId,
+        sender: Option<Principal>,
+    ) -> Result<Vec<CanisterLogRecord>, CallError> {
+        query_candid_as::<(CanisterIdRecord,), (FetchCanisterLogsResponse,)>(
+            self,
+            Principal::management_canister(),
+            sender.unwrap_or(Principal::anonymous()),
+            "fetch_canister_logs",
+            (CanisterIdRecord { canister_id },),
+        )
+        .await
+        .map(|responses| responses.0.canister_log_records)
+    }
+
+    /// Take a snapshot of a canister, optionally replacing an existing snapshot.
+    #[instrument(skip(self), fields(instance_id=self.instance_id, canister_id = %canister_id.to_string(), sender = %sender.unwrap_or(Principal::anonymous()).to_string()))]
+    pub async fn take_canister_snapshot(
+        &self,
+        canister_id: CanisterId,
+        sender: Option<Principal>,
+        replace_snapshot: Option<Vec<u8>>,
+    ) -> Result<Snapshot, CallError> {
+        call_candid_as::<(TakeCanisterSnapshotArgs,), (Snapshot,)>(
+            self,
+            Principal::management_canister(),
+            RawEffectivePrincipal::CanisterId(canister_id.as_slice().to_vec()),
+            sender.unwrap_or(Principal::anonymous()),
+            "take_canister_snapshot",
+            (TakeCanisterSnapshotArgs {
+                canister_id,
+                replace_snapshot,
+            },),
+        )
+        .await
+        .map(|responses| responses.0)
+    }
+
+    /// Load a snapshot of a canister, replacing the canister's current state.
+    #[instrument(skip(self), fields(instance_id=self.instance_id, canister_id = %canister_id.to_string(), sender = %sender.unwrap_or(Principal::anonymous()).to_string()))]
+    pub async fn load_canister_snapshot(
+        &self,
+        canister_id: CanisterId,
+        sender: Option<Principal>,
+        snapshot_id: Vec<u8>,
+    ) -> Result<(), CallError> {
+        call_candid_as::<(LoadCanisterSnapshotArgs,), ()>(
+            self,
+            Principal::management_canister(),
+            RawEffectivePrincipal::CanisterId(canister_id.as_slice().to_vec()),
+            sender.unwrap_or(Principal::anonymous()),
+            "load_canister_snapshot",
+            (LoadCanisterSnapshotArgs {
+                canister_id,
+                snapshot_id,
+                sender_canister_version: None,
+            },),
+        )
+        .await
+    }
+
+    /// List the snapshots of a canister.
+    #[instrument(skip(self), fields(instance_id=self.instance_id, canister_id = %canister_id.to_string(), sender = %sender.unwrap_or(Principal::anonymous()).to_string()))]
+    pub async fn list_canister_snapshots(
+        &self,
+        canister_id: CanisterId,
+        sender: Option<Principal>,
+    ) -> Result<Vec<Snapshot>, CallError> {
+        call_candid_as::<(CanisterIdRecord,), (Vec<Snapshot>,)>(
+            self,
+            Principal::management_canister(),
+            RawEffectivePrincipal::CanisterId(canister_id.as_slice().to_vec()),
+            sender.unwrap_or(Principal::anonymous()),
+            "list_canister_snapshots",
+            (CanisterIdRecord { canister_id },),
+        )
+        .await
+        .map(|responses| responses.0)
+    }
+
+    /// Delete a snapshot of a canister.
+    #[instrument(skip(self), fields(instance_id=self.instance_id, canister_id = %canister_id.to_string(), sender = %sender.unwrap_or(Principal::anonymous()).to_string()))]
+    pub async fn delete_canister_snapshot(
+        &self,
+        canister_id: CanisterId,
+        sender: Option<Principal>,
+        snapshot_id: Vec<u8>,
+    ) -> Result<(), CallError> {
+        call_candid_as::<(DeleteCanisterSnapshotArgs,), ()>(
+            self,
+            Principal::management_canister(),
+            RawEffectivePrincipal::CanisterId(canister_id.as_slice().to_vec()),
+            sender.unwrap_or(Principal::anonymous()),
+            "delete_canister_snapshot",
+            (DeleteCanisterSnapshotArgs {
+                canister_id,
+                snapshot_id,
+            },),
+        )
+        .await
+    }
+
+    /// Create a canister from its state exported from a checkpoint (e.g., of an IC mainnet subnet)
+    /// into a directory using `state-tool export_canister`. Note that the provided path must be accessible for the client.
+    ///
+    /// The canister is created with its original canister ID (on a new subnet if no subnet
+    /// of the PocketIC instance contains that canister ID) and its Wasm module, Wasm memory,
+    /// stable memory, Wasm chunk store, controllers, and settings are restored
+    /// from the exported state. The canister receives its exported cycles balance
+    /// before its state is restored and thus ends up with a slightly lower cycles balance.
+    #[instrument(skip(self), fields(instance_id=self.instance_id, path = %path.display()))]
+    pub async fn create_canister_from_exported_state(
+        &self,
+        path: &Path,
+    ) -> Result<CanisterId, String> {
+        let metadata_path = path.join(EXPORTED_METADATA_FILE);
+        let bytes = std::fs::read(&metadata_path)
+            .map_err(|e| format!("Failed to read {}: {}", metadata_path.display(), e))?;
+        let exported_canister: ExportedCanister = decode_one(&bytes)
+            .map_err(|e| format!("Failed to decode exported canister state: {}", e))?;
+        let canister_id = exported_canister.canister_id;
+        // The canister is set up by the anonymous principal
+        // and its exported controllers are only restored at the very end.
+        let sender = Principal::anonymous();
+        self.create_canister_with_id(Some(sender), None, canister_id)
+            .await?;
+        self.add_cycles(canister_id, exported_canister.cycles_balance)
+            .await;
+
+        let metadata = exported_canister.snapshot_metadata;
+        let wasm_chunk_store = metadata.wasm_chunk_store.clone();
+        let (response,): (UploadCanisterSnapshotMetadataResponse,) = call_candid_as(
+            self,
+            Principal::management_canister(),
+            RawEffectivePrincipal::CanisterId(canister_id.as_slice().to_vec()),
+            sender,
+            "upload_canister_snapshot_metadata",
+            (UploadCanisterSnapshotMetadataArgs {
+                canister_id,
+                replace_snapshot: None,
+                wasm_module_size: metadata.wasm_module_size,
+                exported_globals: metadata.exported_globals,
+                wasm_memory_size: metadata.wasm_memory_size,
+                stable_memory_size: metadata.stable_memory_size,
+                wasm_chunk_store: metadata.wasm_chunk_store,
+                certified_data: metadata.certified_data,
+            },),
+        )
+        .await
+        .map_err(|e| format!("Failed to upload snapshot metadata: {:?}", e))?;
+        let snapshot_id = response.snapshot_id.into_vec();
+
+        // The files are uploaded slice by slice so that they never have to be held in memory at once.
+        // Fresh snapshot memories are zero-initialized and thus zero slices of memories are skipped.
+        self.upload_snapshot_file(
+            canister_id,
+            &snapshot_id,
+            &path.join(EXPORTED_WASM_MODULE_FILE),
+            |offset| CanisterSnapshotDataOffset::WasmModule { offset },
+            false,
+        )
+        .await?;
+        self.upload_snapshot_file(
+            canister_id,
+            &snapshot_id,
+            &path.join(EXPORTED_WASM_MEMORY_FILE),
+            |offset| CanisterSnapshotDataOffset::MainMemory { offset },
+            true,
+        )
+        .await?;
+        self.upload_snapshot_file(
+            canister_id,
+            &snapshot_id,
+            &path.join(EXPORTED_STABLE_MEMORY_FILE),
+            |offset| CanisterSnapshotDataOffset::StableMemory { offset },
+            true,
+        )
+        .await?;
+        for chunk_hash in wasm_chunk_store {
+            let chunk_path = path
+                .join(EXPORTED_WASM_CHUNKS_DIR)
+                .join(format!("{}.bin", hex::encode(&chunk_hash.hash)));
+            let chunk = std::fs::read(&chunk_path)
+                .map_err(|e| format!("Failed to read {}: {}", chunk_path.display(), e))?;
+            self.upload_snapshot_data(
+                canister_id,
+                &snapshot_id,
+                CanisterSnapshotDataOffset::WasmChunk,
+                chunk,
+            )
+            .await?;
+        }
+
+        self.load_canister_snapshot(canister_id, Some(sender), snapshot_id.clone())
+            .await
+            .map_err(|e| format!("Failed to load snapshot: {:?}", e))?;
+        self.delete_canister_snapshot(canister_id, Some(sender), snapshot_id)
+            .await
+            .map_err(|e| format!("Failed to delete snapshot: {:?}", e))?;
+
+        let settings = CanisterSettings {
+            controllers: Some(exported_canister.controllers),
+            compute_allocation: Some(exported_canister.compute_allocation.into()),
+            memory_allocation: Some(exported_canister.memory_allocation.into()),
+            freezing_threshold: Some(exported_canister.freezing_threshold.into()),
+            reserved_cycles_limit: exported_canister.reserved_cycles_limit.map(Nat::from),
+            wasm_memory_limit: exported_canister.wasm_memory_limit.map(Nat::from),
+            ..Default::default()
+        };
+        self.update_canister_settings(canister_id, Some(sender), settings)
+            .await
+            .map_err(|e| format!("Failed to update canister settings: {:?}", e))?;
+
+        Ok(canister_id)
+    }
+
+    /// Uploads the file at `path` into a snapshot of a canister being created from exported state
+    /// in slices of at most `SNAPSHOT_DATA_CHUNK_SIZE` bytes. The slice at offset `offset`
+    /// is uploaded as `kind(offset)` and skipped if `skip_zeros` is set and all its bytes are zero.
+    async fn upload_snapshot_file(
+        &self,
+        canister_id: CanisterId,
+        snapshot_id: &[u8],
+        path: &Path,
+        kind: impl Fn(u64) -> CanisterSnapshotDataOffset,
+        skip_zeros: bool,
+    ) -> Result<(), String> {
+        let mut file =
+            File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+        let mut offset = 0;
+        loop {
+            let mut chunk = Vec::with_capacity(SNAPSHOT_DATA_CHUNK_SIZE);
+            (&mut file)
+                .take(SNAPSHOT_DATA_CHUNK_SIZE as u64)
+                .read_to_end(&mut chunk)
+                .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+            if chunk.is_empty() {
+                return Ok(());
+            }
+            let len = chunk.len() as u64;
+            if !(skip_zeros && chunk.iter().all(|b| *b == 0)) {
+                self.upload_snapshot_data(canister_id, snapshot_id, kind(offset), chunk)
+                    .await?;
+            }
+            offset += len;
+        }
+    }
+
+    async fn upload_snapshot_data(
+        &self,
+        canister_id: CanisterId,
+        snapshot_id: &[u8],
+        kind: CanisterSnapshotDataOffset,
+        chunk: Vec<u8>,
+    ) -> Result<(), String> {
+        call_candid_as::<(UploadCanisterSnapshotDataArgs,), ()>(
+            self,
+            Principal::management_canister(),
+            RawEffectivePrincipal::CanisterId(canister_id.as_slice().to_vec()),
+            Principal::anonymous(),
+            "upload_canister_snapshot_data",
+            (UploadCanisterSnapshotDataArgs {
+                canister_id,
+                snapshot_id: ByteBuf::from(snapshot_id.to_vec()),
+                kind,
+                chunk: ByteBuf::from(chunk),
+            },),
+        )
+        .await
+        .map_err(|e| format!("Failed to upload snapshot data: {:?}", e))
+    }
+
     /// Checks whether the provided canister exists.
     #[instrument(ret(Display), skip(self), fields(instance_id=self.instance_id, canister_id = %canister_id.to_string()))]
     pub async fn canister_exists(&self, canister_id: CanisterId) -> bool {
@@ -1216,6 +1546,44 @@ impl PocketIc {
             mock_canister_http_response.into();
         self.post(endpoint, raw_mock_canister_http_response).await
     }
+
+    /// Register a canister ID whose incoming calls are to be mocked.
+    /// Calls from canisters on this PocketIC instance to the registered canister ID
+    /// are not delivered, but retained as pending calls (see `PocketIc::get_pending_calls`)
+    /// until the test driver responds to them using `PocketIc::mock_call_response`.
+    /// The canister ID must not belong to any subnet of the PocketIC instance.
+    #[instrument(skip(self), fields(instance_id=self.instance_id, canister_id = %canister_id.to_string()))]
+    pub async fn register_mocked_callee(&self, canister_id: CanisterId) {
+        let endpoint = "update/register_mocked_callee";
+        self.post::<(), _>(
+            endpoint,
+            RawCanisterId {
+                canister_id: canister_id.as_slice().to_vec(),
+            },
+        )
+        .await
+    }
+
+    /// Get the pending calls to mocked callees (see `PocketIc::register_mocked_callee`).
+    /// Note that a call to a mocked callee only becomes pending once the calling
+    /// canister has executed (e.g., after a `PocketIc::tick`). The ingress message
+    /// triggering the call should thus be submitted using `PocketIc::submit_call`
+    /// and awaited using `PocketIc::await_call` once the call has been mocked.
+    #[instrument(ret, skip(self), fields(instance_id=self.instance_id))]
+    pub async fn get_pending_calls(&self) -> Vec<PendingCall> {
+        let endpoint = "read/get_pending_calls";
+        let res: Vec<RawPendingCall> = self.get(endpoint).await;
+        res.into_iter().map(|r| r.into()).collect()
+    }
+
+    /// Mock a response (reply or reject) to a pending call to a mocked callee.
+    /// Any cycles attached to the call are refunded to the caller.
+    #[instrument(ret, skip(self), fields(instance_id=self.instance_id))]
+    pub async fn mock_call_response(&self, mock_call_response: MockCallResponse) {
+        let endpoint = "update/mock_call_response";
+        let raw_mock_call_response: RawMockCallResponse = mock_call_response.into();
+        self.post(endpoint, raw_mock_call_response).await
+    }
 }
 
 /// Call a canister candid method, authenticated. The sender can be impersonated (i.e., the
@@ -1372,6 +1740,85 @@ struct ProvisionalCreateCanisterArgument {
     pub amount: Option<Nat>,
 }
 
+/// Candid type of a Wasm global in a canister snapshot.
+#[derive(CandidType, Serialize, Deserialize, Debug, PartialEq, Clone)]
+enum SnapshotGlobal {
+    #[serde(rename = "i32")]
+    I32(i32),
+    #[serde(rename = "i64")]
+    I64(i64),
+    #[serde(rename = "f32")]
+    F32(f32),
+    #[serde(rename = "f64")]
+    F64(f64),
+    #[serde(rename = "v128")]
+    V128(u128),
+}
+
+/// Metadata of a canister snapshot as returned by `read_canister_snapshot_metadata`.
+#[derive(CandidType, Serialize, Deserialize, Debug, PartialEq, Clone)]
+struct SnapshotMetadata {
+    pub taken_at_timestamp: u64,
+    pub canister_version: u64,
+    pub wasm_module_size: u64,
+    pub exported_globals: Vec<SnapshotGlobal>,
+    pub wasm_memory_size: u64,
+    pub stable_memory_size: u64,
+    pub wasm_chunk_store: Vec<ChunkHash>,
+    pub certified_data: ByteBuf,
+}
+
+/// Metadata of a canister exported from a checkpoint by `state-tool export_canister`.
+#[derive(CandidType, Serialize, Deserialize, Debug, PartialEq, Clone)]
+struct ExportedCanister {
+    pub canister_id: Principal,
+    pub controllers: Vec<Principal>,
+    pub cycles_balance: u128,
+    pub compute_allocation: u64,
+    pub memory_allocation: u64,
+    pub freezing_threshold: u64,
+    pub reserved_cycles_limit: Option<u128>,
+    pub wasm_memory_limit: Option<u64>,
+    pub snapshot_metadata: SnapshotMetadata,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Debug, PartialEq, Clone)]
+struct UploadCanisterSnapshotMetadataArgs {
+    pub canister_id: Principal,
+    pub replace_snapshot: Option<ByteBuf>,
+    pub wasm_module_size: u64,
+    pub exported_globals: Vec<SnapshotGlobal>,
+    pub wasm_memory_size: u64,
+    pub stable_memory_size: u64,
+    pub wasm_chunk_store: Vec<ChunkHash>,
+    pub certified_data: ByteBuf,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Debug, PartialEq, Clone)]
+struct UploadCanisterSnapshotMetadataResponse {
+    pub snapshot_id: ByteBuf,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Debug, PartialEq, Clone)]
+enum CanisterSnapshotDataOffset {
+    #[serde(rename = "wasm_module")]
+    WasmModule { offset: u64 },
+    #[serde(rename = "main_memory")]
+    MainMemory { offset: u64 },
+    #[serde(rename = "stable_memory")]
+    StableMemory { offset: u64 },
+    #[serde(rename = "wasm_chunk")]
+    WasmChunk,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Debug, PartialEq, Clone)]
+struct UploadCanisterSnapshotDataArgs {
+    pub canister_id: Principal,
+    pub snapshot_id: ByteBuf,
+    pub kind: CanisterSnapshotDataOffset,
+    pub chunk: ByteBuf,
+}
+
 fn setup_tracing(pid: u32) -> Option<WorkerGuard> {
     use tracing_subscriber::prelude::*;
     match std::env::var(LOG_DIR_PATH_ENV_NAME).map(std::path::PathBuf::from) {
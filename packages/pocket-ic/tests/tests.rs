@@ -5,15 +5,17 @@ use ic_cdk::api::management_canister::http_request::HttpResponse;
 use ic_cdk::api::management_canister::main::{CanisterId, CanisterIdRecord, CanisterSettings};
 use ic_cdk::api::management_canister::provisional::ProvisionalCreateCanisterWithCyclesArgument;
 use pocket_ic::{
+    call_candid_as,
     common::rest::{
-        BlobCompression, CanisterHttpReply, CanisterHttpResponse, MockCanisterHttpResponse,
-        RawEffectivePrincipal, SubnetKind,
+        BlobCompression, CallReject, CallResponse, CanisterHttpReply, CanisterHttpResponse,
+        MockCallResponse, MockCanisterHttpResponse, RawEffectivePrincipal, SubnetKind,
     },
     update_candid, DefaultEffectiveCanisterIdError, ErrorCode, PocketIc, PocketIcBuilder,
     WasmResult,
 };
 use reqwest::blocking::Client;
 use serde::{Deserialize, Serialize};
+use serde_bytes::ByteBuf;
 use sha2::{Digest, Sha256};
 use std::{io::Read, time::SystemTime};
 
@@ -76,6 +78,57 @@ fn call_counter_can(ic: &PocketIc, can_id: CanisterId, method: &str) -> WasmResu
     .expect("Failed to call counter canister")
 }
 
+#[test]
+fn test_canister_coverage() {
+    let pic = PocketIcBuilder::new()
+        .with_application_subnet()
+        .with_coverage_instrumentation()
+        .build();
+
+    let can_id = pic.create_canister();
+    pic.add_cycles(can_id, INIT_CYCLES);
+    pic.install_canister(can_id, counter_wasm(), vec![], None);
+
+    call_counter_can(&pic, can_id, "write");
+    call_counter_can(&pic, can_id, "write");
+    call_counter_can(&pic, can_id, "read");
+
+    // The counter canister has no debug information so its functions are
+    // listed under a pseudo source file with their function index as line.
+    let lcov = pic.get_canister_coverage(can_id);
+    assert!(lcov.contains("SF:<wasm>\n"));
+    assert!(lcov.contains("FNDA:2,write\n"));
+    assert!(lcov.contains("FNDA:3,read\n"));
+    assert!(lcov.contains("FNH:2\n"));
+    assert!(lcov.ends_with("end_of_record\n"));
+}
+
+#[test]
+fn test_instruction_profile() {
+    let pic = PocketIcBuilder::new()
+        .with_application_subnet()
+        .with_instruction_profiling()
+        .build();
+
+    let can_id = pic.create_canister();
+    pic.add_cycles(can_id, INIT_CYCLES);
+    pic.install_canister(can_id, counter_wasm(), vec![], None);
+
+    call_counter_can(&pic, can_id, "write");
+
+    // Every stack is rooted at the executed method and followed by its
+    // instruction count.
+    let profile = pic.get_instruction_profile(can_id);
+    assert!(!profile.is_empty());
+    for line in profile.lines() {
+        let (stack, count) = line.rsplit_once(' ').unwrap();
+        assert!(stack.starts_with("canister_update write"));
+        assert!(count.parse::<u64>().unwrap() > 0);
+    }
+    // The method `write` calls `read`.
+    assert!(profile.lines().any(|line| line.split(';').count() == 3));
+}
+
 #[test]
 fn test_create_canister_with_id() {
     let pic = PocketIcBuilder::new()
@@ -755,6 +808,256 @@ fn test_update_canister_settings() {
     assert_eq!(status.settings.compute_allocation, new_compute_allocation);
 }
 
+#[test]
+fn test_fetch_canister_logs() {
+    let pic = PocketIc::new();
+
+    // Create a canister and charge it with 2T cycles.
+    let can_id = pic.create_canister();
+    pic.add_cycles(can_id, INIT_CYCLES);
+
+    // Install a canister printing a debug message on every update call.
+    let wat = r#"
+    (module
+        (import "ic0" "debug_print" (func $debug_print (param i32 i32)))
+        (import "ic0" "msg_reply" (func $msg_reply))
+        (func $log
+            (call $debug_print (i32.const 0) (i32.const 5))
+            (call $msg_reply))
+        (memory $memory 1)
+        (data (i32.const 0) "hello")
+        (export "canister_update log" (func $log))
+    )"#;
+    pic.install_canister(can_id, wat::parse_str(wat).unwrap(), vec![], None);
+
+    // There are no logs before the canister is called.
+    assert!(pic.fetch_canister_logs(can_id, None).unwrap().is_empty());
+
+    call_counter_can(&pic, can_id, "log");
+    call_counter_can(&pic, can_id, "log");
+
+    let logs = pic.fetch_canister_logs(can_id, None).unwrap();
+    assert_eq!(logs.len(), 2);
+    for log in logs {
+        assert_eq!(log.content, b"hello".to_vec());
+    }
+
+    // Only controllers can fetch the logs by default.
+    let not_a_controller = Principal::from_slice(&[0xFF; 29]);
+    pic.fetch_canister_logs(can_id, Some(not_a_controller))
+        .unwrap_err();
+}
+
+#[test]
+fn test_canister_snapshots() {
+    let pic = PocketIc::new();
+
+    // Create a canister and charge it with 2T cycles.
+    let can_id = pic.create_canister();
+    pic.add_cycles(can_id, INIT_CYCLES);
+
+    // Install the counter canister wasm file on the canister.
+    let counter_wasm = counter_wasm();
+    pic.install_canister(can_id, counter_wasm, vec![], None);
+
+    // There are no snapshots initially.
+    assert!(pic
+        .list_canister_snapshots(can_id, None)
+        .unwrap()
+        .is_empty());
+
+    let reply = call_counter_can(&pic, can_id, "write");
+    assert_eq!(reply, WasmResult::Reply(vec![1, 0, 0, 0]));
+
+    // Take a snapshot of the counter state.
+    let snapshot = pic.take_canister_snapshot(can_id, None, None).unwrap();
+    let snapshots = pic.list_canister_snapshots(can_id, None).unwrap();
+    assert_eq!(snapshots.len(), 1);
+    assert_eq!(snapshots[0].id, snapshot.id);
+
+    let reply = call_counter_can(&pic, can_id, "write");
+    assert_eq!(reply, WasmResult::Reply(vec![2, 0, 0, 0]));
+
+    // Loading the snapshot rolls the counter back.
+    pic.load_canister_snapshot(can_id, None, snapshot.id.clone())
+        .unwrap();
+    let reply = call_counter_can(&pic, can_id, "read");
+    assert_eq!(reply, WasmResult::Reply(vec![1, 0, 0, 0]));
+
+    // Replace the snapshot with a new one.
+    let new_snapshot = pic
+        .take_canister_snapshot(can_id, None, Some(snapshot.id.clone()))
+        .unwrap();
+    let snapshots = pic.list_canister_snapshots(can_id, None).unwrap();
+    assert_eq!(snapshots.len(), 1);
+    assert_eq!(snapshots[0].id, new_snapshot.id);
+
+    // Delete the snapshot.
+    pic.delete_canister_snapshot(can_id, None, new_snapshot.id)
+        .unwrap();
+    assert!(pic
+        .list_canister_snapshots(can_id, None)
+        .unwrap()
+        .is_empty());
+}
+
+#[derive(CandidType, Deserialize)]
+enum SnapshotGlobal {
+    #[serde(rename = "i32")]
+    I32(i32),
+    #[serde(rename = "i64")]
+    I64(i64),
+    #[serde(rename = "f32")]
+    F32(f32),
+    #[serde(rename = "f64")]
+    F64(f64),
+    #[serde(rename = "v128")]
+    V128(u128),
+}
+
+#[derive(CandidType, Deserialize)]
+struct SnapshotChunkHash {
+    hash: ByteBuf,
+}
+
+#[derive(CandidType, Deserialize)]
+struct SnapshotMetadata {
+    taken_at_timestamp: u64,
+    canister_version: u64,
+    wasm_module_size: u64,
+    exported_globals: Vec<SnapshotGlobal>,
+    wasm_memory_size: u64,
+    stable_memory_size: u64,
+    wasm_chunk_store: Vec<SnapshotChunkHash>,
+    certified_data: ByteBuf,
+}
+
+#[derive(CandidType, Deserialize)]
+struct ReadCanisterSnapshotMetadataArgs {
+    canister_id: Principal,
+    snapshot_id: ByteBuf,
+}
+
+#[derive(CandidType, Deserialize)]
+enum CanisterSnapshotDataKind {
+    #[serde(rename = "main_memory")]
+    MainMemory { offset: u64, size: u64 },
+}
+
+#[derive(CandidType, Deserialize)]
+struct ReadCanisterSnapshotDataArgs {
+    canister_id: Principal,
+    snapshot_id: ByteBuf,
+    kind: CanisterSnapshotDataKind,
+}
+
+#[derive(CandidType, Deserialize)]
+struct ReadCanisterSnapshotDataResponse {
+    chunk: ByteBuf,
+}
+
+// The format of the metadata file written by `state-tool export_canister`.
+#[derive(CandidType, Deserialize)]
+struct ExportedCanister {
+    canister_id: Principal,
+    controllers: Vec<Principal>,
+    cycles_balance: u128,
+    compute_allocation: u64,
+    memory_allocation: u64,
+    freezing_threshold: u64,
+    reserved_cycles_limit: Option<u128>,
+    wasm_memory_limit: Option<u64>,
+    snapshot_metadata: SnapshotMetadata,
+}
+
+#[test]
+fn test_canister_from_exported_state() {
+    let pic = PocketIc::new();
+
+    // Create a canister and charge it with 2T cycles.
+    let can_id = pic.create_canister();
+    pic.add_cycles(can_id, INIT_CYCLES);
+
+    // Install the counter canister wasm file on the canister and bump the counter.
+    let counter_wasm = counter_wasm();
+    pic.install_canister(can_id, counter_wasm.clone(), vec![], None);
+    call_counter_can(&pic, can_id, "write");
+    let reply = call_counter_can(&pic, can_id, "write");
+    assert_eq!(reply, WasmResult::Reply(vec![2, 0, 0, 0]));
+
+    // Download the canister state via a snapshot
+    // to assemble a directory in the format produced by `state-tool export_canister`.
+    let snapshot = pic.take_canister_snapshot(can_id, None, None).unwrap();
+    let snapshot_id = ByteBuf::from(snapshot.id);
+    let (snapshot_metadata,): (SnapshotMetadata,) = call_candid_as(
+        &pic,
+        Principal::management_canister(),
+        RawEffectivePrincipal::CanisterId(can_id.as_slice().to_vec()),
+        Principal::anonymous(),
+        "read_canister_snapshot_metadata",
+        (ReadCanisterSnapshotMetadataArgs {
+            canister_id: can_id,
+            snapshot_id: snapshot_id.clone(),
+        },),
+    )
+    .unwrap();
+    let (wasm_memory,): (ReadCanisterSnapshotDataResponse,) = call_candid_as(
+        &pic,
+        Principal::management_canister(),
+        RawEffectivePrincipal::CanisterId(can_id.as_slice().to_vec()),
+        Principal::anonymous(),
+        "read_canister_snapshot_data",
+        (ReadCanisterSnapshotDataArgs {
+            canister_id: can_id,
+            snapshot_id,
+            kind: CanisterSnapshotDataKind::MainMemory {
+                offset: 0,
+                size: snapshot_metadata.wasm_memory_size,
+            },
+        },),
+    )
+    .unwrap();
+
+    // We export the canister under a mainnet canister ID on a fresh PocketIC instance.
+    let canister_id = Principal::from_text("xhy27-fqaaa-aaaao-a2hlq-cai").unwrap();
+    let controller = Principal::from_slice(&[0xFF; 29]);
+    let exported_canister = ExportedCanister {
+        canister_id,
+        controllers: vec![controller],
+        cycles_balance: INIT_CYCLES,
+        compute_allocation: 0,
+        memory_allocation: 0,
+        freezing_threshold: 2_592_000,
+        reserved_cycles_limit: None,
+        wasm_memory_limit: None,
+        snapshot_metadata,
+    };
+    let path = std::env::temp_dir().join(format!("exported_canister_{}", std::process::id()));
+    std::fs::create_dir_all(&path).unwrap();
+    std::fs::write(
+        path.join("metadata.bin"),
+        encode_one(exported_canister).unwrap(),
+    )
+    .unwrap();
+    std::fs::write(path.join("wasm_module.wasm"), counter_wasm).unwrap();
+    std::fs::write(path.join("wasm_memory.bin"), wasm_memory.chunk).unwrap();
+    std::fs::write(path.join("stable_memory.bin"), b"").unwrap();
+
+    let pic = PocketIcBuilder::new()
+        .with_application_subnet()
+        .with_canister_state(path.clone())
+        .build();
+    std::fs::remove_dir_all(path).unwrap();
+
+    // The canister is restored at its original ID with its counter value and controllers.
+    let reply = call_counter_can(&pic, canister_id, "read");
+    assert_eq!(reply, WasmResult::Reply(vec![2, 0, 0, 0]));
+    let reply = call_counter_can(&pic, canister_id, "write");
+    assert_eq!(reply, WasmResult::Reply(vec![3, 0, 0, 0]));
+    let status = pic.canister_status(canister_id, Some(controller)).unwrap();
+    assert_eq!(status.settings.controllers, vec![controller]);
+}
+
 #[test]
 fn test_xnet_call_and_create_canister_with_specified_id() {
     // We start with a PocketIC instance consisting of two application subnets.
@@ -1312,6 +1615,159 @@ fn test_canister_http_with_one_additional_response() {
     pic.mock_canister_http_response(mock_canister_http_response);
 }
 
+#[test]
+fn test_mock_call_response() {
+    let pic = PocketIc::new();
+
+    // Create a canister and charge it with 2T cycles.
+    let can_id = pic.create_canister();
+    pic.add_cycles(can_id, INIT_CYCLES);
+
+    // Install the test canister wasm file on the canister.
+    let test_wasm = test_canister_wasm();
+    pic.install_canister(can_id, test_wasm, vec![], None);
+
+    // Register the ICP ledger canister ID (which does not exist on this instance) as a mocked callee.
+    let ledger_id = Principal::from_text("ryjl3-tyaaa-aaaaa-aaaba-cai").unwrap();
+    pic.register_mocked_callee(ledger_id);
+
+    // Submit an update call to the test canister making a call to the mocked callee.
+    let call_id = pic
+        .submit_call(
+            can_id,
+            Principal::anonymous(),
+            "whois",
+            Encode!(&ledger_id).unwrap(),
+        )
+        .unwrap();
+
+    // We need a tick for the test canister method to make the call.
+    pic.tick();
+    let pending_calls = pic.get_pending_calls();
+    assert_eq!(pending_calls.len(), 1);
+    let pending_call = &pending_calls[0];
+    assert_eq!(pending_call.sender, can_id);
+    assert_eq!(pending_call.receiver, ledger_id);
+    assert_eq!(pending_call.method, "whoami");
+
+    let mock_call_response = MockCallResponse {
+        subnet_id: pending_call.subnet_id,
+        call_id: pending_call.call_id,
+        response: CallResponse::Reply(Encode!(&"ledger".to_string()).unwrap()),
+    };
+    pic.mock_call_response(mock_call_response);
+
+    // Now the test canister will receive the mocked response
+    // and reply to the ingress message from the test driver.
+    let reply = pic.await_call(call_id).unwrap();
+    match reply {
+        WasmResult::Reply(data) => {
+            assert_eq!(Decode!(&data, String).unwrap(), "ledger");
+        }
+        WasmResult::Reject(msg) => panic!("Unexpected reject {}", msg),
+    };
+
+    // There should be no more pending calls.
+    let pending_calls = pic.get_pending_calls();
+    assert_eq!(pending_calls.len(), 0);
+
+    // Mock a reject this time: the test canister traps upon a reject.
+    let call_id = pic
+        .submit_call(
+            can_id,
+            Principal::anonymous(),
+            "whois",
+            Encode!(&ledger_id).unwrap(),
+        )
+        .unwrap();
+    pic.tick();
+    let pending_calls = pic.get_pending_calls();
+    assert_eq!(pending_calls.len(), 1);
+    let mock_call_response = MockCallResponse {
+        subnet_id: pending_calls[0].subnet_id,
+        call_id: pending_calls[0].call_id,
+        response: CallResponse::Reject(CallReject {
+            reject_code: RejectionCode::CanisterReject as u64,
+            message: "mocked reject".to_string(),
+        }),
+    };
+    pic.mock_call_response(mock_call_response);
+    let err = pic.await_call(call_id).unwrap_err();
+    assert_eq!(err.code, ErrorCode::CanisterCalledTrap);
+    assert!(err.description.contains("mocked reject"));
+}
+
+#[test]
+fn test_get_pending_calls_after_mock_call_response() {
+    let pic = PocketIc::new();
+
+    // Create a canister and charge it with 2T cycles.
+    let can_id = pic.create_canister();
+    pic.add_cycles(can_id, INIT_CYCLES);
+
+    // Install the test canister wasm file on the canister.
+    let test_wasm = test_canister_wasm();
+    pic.install_canister(can_id, test_wasm, vec![], None);
+
+    let ledger_id = Principal::from_text("ryjl3-tyaaa-aaaaa-aaaba-cai").unwrap();
+    pic.register_mocked_callee(ledger_id);
+
+    // Make two calls to the mocked callee.
+    let call_ids: Vec<_> = (0..2)
+        .map(|_| {
+            pic.submit_call(
+                can_id,
+                Principal::anonymous(),
+                "whois",
+                Encode!(&ledger_id).unwrap(),
+            )
+            .unwrap()
+        })
+        .collect();
+    pic.tick();
+    let pending_calls = pic.get_pending_calls();
+    assert_eq!(pending_calls.len(), 2);
+
+    // Respond to the second call first: only the first call remains pending
+    // (without executing a round).
+    pic.mock_call_response(MockCallResponse {
+        subnet_id: pending_calls[1].subnet_id,
+        call_id: pending_calls[1].call_id,
+        response: CallResponse::Reply(Encode!(&"second".to_string()).unwrap()),
+    });
+    assert_eq!(pic.get_pending_calls(), vec![pending_calls[0].clone()]);
+
+    // Respond to the first call: no call remains pending.
+    pic.mock_call_response(MockCallResponse {
+        subnet_id: pending_calls[0].subnet_id,
+        call_id: pending_calls[0].call_id,
+        response: CallResponse::Reply(Encode!(&"first".to_string()).unwrap()),
+    });
+    assert!(pic.get_pending_calls().is_empty());
+
+    for (call_id, expected) in call_ids.into_iter().zip(["first", "second"]) {
+        match pic.await_call(call_id).unwrap() {
+            WasmResult::Reply(data) => {
+                assert_eq!(Decode!(&data, String).unwrap(), expected);
+            }
+            WasmResult::Reject(msg) => panic!("Unexpected reject {}", msg),
+        };
+    }
+
+    // A new call to the mocked callee is pending.
+    pic.submit_call(
+        can_id,
+        Principal::anonymous(),
+        "whois",
+        Encode!(&ledger_id).unwrap(),
+    )
+    .unwrap();
+    pic.tick();
+    let pending_calls = pic.get_pending_calls();
+    assert_eq!(pending_calls.len(), 1);
+    assert_eq!(pending_calls[0].receiver, ledger_id);
+}
+
 #[test]
 fn subnet_metrics() {
     const INIT_CYCLES: u128 = 2_000_000_000_000;
@@ -1,5 +1,8 @@
 use crate::{
+    config::RetentionPolicy,
     notification_client::NotificationClient,
+    notification_sink::{AlertKind, Severity},
+    storage::StorageBackend,
     util::{block_on, sleep_secs},
 };
 use anyhow::Context;
@@ -44,7 +47,8 @@ pub(crate) struct BackupHelper {
     pub(crate) downloads_guard: Arc<Mutex<bool>>,
     pub(crate) hot_disk_resource_threshold_percentage: u32,
     pub(crate) cold_disk_resource_threshold_percentage: u32,
-    pub(crate) cold_storage_dir: PathBuf,
+    pub(crate) cold_storage: Box<dyn StorageBackend>,
+    pub(crate) retention: RetentionPolicy,
     pub(crate) versions_hot: usize,
     pub(crate) artifacts_guard: Mutex<bool>,
     pub(crate) daily_replays: usize,
@@ -113,20 +117,6 @@ impl BackupHelper {
         create_if_not_exists(self.root_dir.join(format!("work_dir/{}", self.subnet_id)))
     }
 
-    fn cold_storage_artifacts_dir(&self) -> PathBuf {
-        create_if_not_exists(
-            self.cold_storage_dir
-                .join(format!("{}/artifacts", self.subnet_id)),
-        )
-    }
-
-    fn cold_storage_states_dir(&self) -> PathBuf {
-        create_if_not_exists(
-            self.cold_storage_dir
-                .join(format!("{}/states", self.subnet_id)),
-        )
-    }
-
     fn trash_dir(&self) -> PathBuf {
         create_if_not_exists(self.root_dir.join("trash"))
     }
@@ -218,8 +208,10 @@ impl BackupHelper {
             sleep_secs(10);
         }
         // Without the binaries we can't replay...
-        self.notification_client
-            .report_failure_slack(format!("Couldn't download: {}", binary_name));
+        self.notification_client.report_failure(
+            AlertKind::Download,
+            format!("Couldn't download: {}", binary_name),
+        );
         Err(format!(
             "Binary {} is required for the replica {}",
             binary_name, replica_version
@@ -284,8 +276,10 @@ impl BackupHelper {
             sleep_secs(60);
         }
         warn!(self.log, "Didn't sync any config from host: {}", node_ip);
-        self.notification_client
-            .report_failure_slack("Couldn't pull ic.json5 from the nodes!".to_string());
+        self.notification_client.report_failure(
+            AlertKind::Sync,
+            "Couldn't pull ic.json5 from the nodes!".to_string(),
+        );
     }
 
     fn rsync_remote_cmd(
@@ -329,8 +323,10 @@ impl BackupHelper {
             self.notification_client
                 .push_metrics_sync_time(duration.as_secs() / 60);
         } else {
-            self.notification_client
-                .report_failure_slack("Couldn't pull artifacts from the nodes!".to_string());
+            self.notification_client.report_failure(
+                AlertKind::Sync,
+                "Couldn't pull artifacts from the nodes!".to_string(),
+            );
         }
     }
 
@@ -399,11 +395,14 @@ impl BackupHelper {
             match self.replay_current_version(&current_replica_version) {
                 Ok(ReplayResult::UpgradeRequired(upgrade_version)) => {
                     // replayed the current version, but if there is upgrade try to do it again
-                    self.notification_client.message_slack(format!(
-                        "Replica version upgrade detected (current: {} new: {}): \
-                        upgrading the ic-replay tool to retry... 🤞",
-                        current_replica_version, upgrade_version
-                    ));
+                    self.notification_client.message(
+                        AlertKind::Upgrade,
+                        format!(
+                            "Replica version upgrade detected (current: {} new: {}): \
+                            upgrading the ic-replay tool to retry... 🤞",
+                            current_replica_version, upgrade_version
+                        ),
+                    );
                     current_replica_version = upgrade_version;
                 }
                 Ok(_) => break,
@@ -419,10 +418,15 @@ impl BackupHelper {
             info!(self.log, "[#{}] Replay was successful!", self.thread_id);
 
             if self.archive_state(finish_height).is_ok() {
-                self.notification_client.message_slack(format!(
-                    "✅ Successfully restored the state at height *{}*",
-                    finish_height
-                ));
+                self.notification_client.notify(
+                    Severity::Info,
+                    AlertKind::Replay,
+                    Some(finish_height),
+                    format!(
+                        "✅ Successfully restored the state at height *{}*",
+                        finish_height
+                    ),
+                );
                 let duration = start_time.elapsed();
                 let minutes = duration.as_secs() / 60;
                 self.notification_client.push_metrics_replay_time(minutes);
@@ -431,7 +435,10 @@ impl BackupHelper {
             }
         } else {
             warn!(self.log, "[#{}] No progress in the replay!", self.thread_id);
-            self.notification_client.report_failure_slack(
+            self.notification_client.notify(
+                Severity::Failure,
+                AlertKind::Replay,
+                Some(start_height),
                 "No height progress after the last replay detected!".to_string(),
             );
         }
@@ -592,12 +599,7 @@ impl BackupHelper {
                                 DiskStats::Inodes => "inodes",
                                 DiskStats::Space => "space",
                             };
-                            self.notification_client.report_warning_slack(format!(
-                                "[{}] {} usage is at {}%",
-                                dir.to_str().unwrap_or_default(),
-                                resource,
-                                n
-                            ))
+                            self.notification_client.report_disk_usage(dir, resource, n)
                         }
                         Ok(n)
                     } else {
@@ -630,8 +632,12 @@ impl BackupHelper {
         debug!(self.log, "[#{}] Will execute: {:?}", self.thread_id, cmd);
         if let Err(e) = exec_cmd(&mut cmd) {
             error!(self.log, "Error: {}", e);
-            self.notification_client
-                .report_failure_slack("Couldn't archive the replayed state!".to_string());
+            self.notification_client.notify(
+                Severity::Failure,
+                AlertKind::Archive,
+                Some(last_height),
+                "Couldn't archive the replayed state!".to_string(),
+            );
             return Err(e.to_string());
         }
         // leave only one archived checkpoint
@@ -663,18 +669,20 @@ impl BackupHelper {
 
     pub(crate) fn log_disk_stats(&self, notify_if_exceeds_threshold: bool) -> Result<(), String> {
         let mut stats = Vec::new();
-        for (dir, threshold, storage_type) in [
-            (
-                &self.root_dir,
-                self.hot_disk_resource_threshold_percentage,
-                "hot",
-            ),
-            (
-                &self.cold_storage_dir,
+        let mut dirs = vec![(
+            self.root_dir.as_path(),
+            self.hot_disk_resource_threshold_percentage,
+            "hot",
+        )];
+        // The disk usage of a remote cold storage is not monitored here.
+        if let Some(cold_storage_dir) = self.cold_storage.local_dir() {
+            dirs.push((
+                cold_storage_dir,
                 self.cold_disk_resource_threshold_percentage,
                 "cold",
-            ),
-        ] {
+            ));
+        }
+        for (dir, threshold, storage_type) in dirs {
             let space = self.get_disk_stats(
                 dir,
                 threshold,
@@ -687,7 +695,7 @@ impl BackupHelper {
                 DiskStats::Inodes,
                 notify_if_exceeds_threshold,
             )?;
-            stats.push((dir.as_path(), space, inodes, storage_type));
+            stats.push((dir, space, inodes, storage_type));
         }
         self.notification_client
             .push_metrics_disk_stats(stats.as_slice());
@@ -757,45 +765,20 @@ impl BackupHelper {
 
         if self.do_cold_storage {
             // process moved artifact directories
-            let cold_storage_artifacts_dir = self.cold_storage_artifacts_dir();
-            let work_dir_str = work_dir
-                .clone()
-                .into_os_string()
-                .into_string()
-                .expect("work directory is missing or invalid");
             let pack_dirs = collect_only_dirs(&work_dir)?;
             for pack_dir in pack_dirs {
                 let replica_version = pack_dir
                     .file_name()
                     .into_string()
                     .expect("replica version entry in work directory is missing or invalid");
-                debug!(self.log, "Packing artifacts of {}", replica_version);
-                let timestamp = Utc::now().timestamp();
+                debug!(self.log, "Storing artifacts of {}", replica_version);
                 let (top_height, _) = fetch_top_height(&pack_dir);
-                let packed_file = format!(
-                    "{}/{:010}_{:012}_{}.tgz",
-                    work_dir_str, timestamp, top_height, replica_version
-                );
-                let mut cmd = Command::new("tar");
-                cmd.arg("czvf");
-                cmd.arg(&packed_file);
-                cmd.arg("-C").arg(&work_dir);
-                cmd.arg(&replica_version);
-                debug!(self.log, "Will execute: {:?}", cmd);
-                exec_cmd(&mut cmd).map_err(|err| format!("Error packing artifacts: {:?}", err))?;
-
-                info!(self.log, "Copy packed file of {}", replica_version);
-                let mut cmd2 = Command::new("cp");
-                cmd2.arg(packed_file).arg(&cold_storage_artifacts_dir);
-                debug!(self.log, "Will execute: {:?}", cmd2);
-                exec_cmd(&mut cmd2).map_err(|err| format!("Error copying artifacts: {:?}", err))?;
+                self.cold_storage.store_artifacts(
+                    &pack_dir.path(),
+                    &replica_version,
+                    top_height,
+                )?;
             }
-            ls_path(
-                &self.log,
-                self.cold_storage_dir
-                    .join(format!("{}", self.subnet_id))
-                    .as_path(),
-            )?;
         }
 
         info!(self.log, "Remove leftovers");
@@ -857,14 +840,10 @@ impl BackupHelper {
             }
         });
 
-        for dir in old_state_dirs.values() {
+        for (height, dir) in &old_state_dirs {
             if self.should_cold_store(dir) {
                 info!(self.log, "Will copy to cold storage: {:?}", dir);
-                let mut cmd = Command::new("rsync");
-                cmd.arg("-a");
-                cmd.arg(dir).arg(self.cold_storage_states_dir());
-                debug!(self.log, "Will execute: {:?}", cmd);
-                exec_cmd(&mut cmd).map_err(|err| format!("Error copying states: {:?}", err))?;
+                self.cold_storage.store_state(dir, *height)?;
             }
         }
 
@@ -887,7 +866,7 @@ impl BackupHelper {
             return false;
         }
 
-        let cold_storage_timestamp = match self.cold_storage_newest_state_timestamp() {
+        let cold_storage_timestamp = match self.cold_storage.newest_state_timestamp() {
             Ok(timestamp) => timestamp,
             Err(err) => {
                 error!(
@@ -921,10 +900,46 @@ impl BackupHelper {
             >= 1
     }
 
-    fn cold_storage_newest_state_timestamp(&self) -> anyhow::Result<DateTime<Utc>> {
-        let last_height = last_dir_height(&self.cold_storage_states_dir(), 10);
+    /// Deletes the artifacts and states from the cold storage which are older than allowed by
+    /// the retention policy of the subnet.
+    pub(crate) fn apply_cold_storage_retention(&self) -> Result<(), String> {
+        if !self.do_cold_storage {
+            return Ok(());
+        }
+        self.cold_storage
+            .apply_retention(&self.retention, Utc::now())
+    }
+
+    /// Moves all but the newest archived state to the cold storage if the disk usage of the hot
+    /// storage exceeds its threshold.
+    pub(crate) fn relieve_hot_storage(&self) -> Result<bool, String> {
+        let usage = self.get_disk_stats(
+            &self.root_dir,
+            self.hot_disk_resource_threshold_percentage,
+            DiskStats::Space,
+            /*notify_if_exceeds_threshold=*/ false,
+        )?;
+        if usage < self.hot_disk_resource_threshold_percentage {
+            return Ok(false);
+        }
+
+        let mut heights: Vec<_> = collect_only_dirs(&self.archive_dir())?
+            .iter()
+            .map(|dir_entry| height_from_dir_entry_radix(dir_entry, 10))
+            .collect();
+        heights.sort();
+        if heights.len() < 2 {
+            return Ok(false);
+        }
 
-        state_dir_timestamp(&self.cold_storage_states_dir().join(last_height.to_string()))
+        warn!(
+            self.log,
+            "Hot storage usage is at {}%, moving {} states to the cold storage",
+            usage,
+            heights.len() - 1
+        );
+        self.cold_store_states(heights[heights.len() - 2])
+            .map(|_| true)
     }
 }
 
@@ -990,7 +1005,7 @@ fn height_from_dir_entry(filename: &DirEntry) -> u64 {
     height_from_dir_entry_radix(filename, 16)
 }
 
-fn last_dir_height(dir: &PathBuf, radix: u32) -> u64 {
+pub(crate) fn last_dir_height(dir: &PathBuf, radix: u32) -> u64 {
     if !dir.exists() {
         return 0u64;
     }
@@ -1055,7 +1070,7 @@ pub(crate) fn retrieve_replica_version_last_replayed(
     current_replica_version
 }
 
-fn state_dir_timestamp(dir: &Path) -> anyhow::Result<DateTime<Utc>> {
+pub(crate) fn state_dir_timestamp(dir: &Path) -> anyhow::Result<DateTime<Utc>> {
     let path = dir.join(TIMESTAMP_FILE_NAME);
     let timestamp_str =
         std::fs::read_to_string(&path).context("Failed to read the timestamp file")?;
@@ -1065,7 +1080,7 @@ fn state_dir_timestamp(dir: &Path) -> anyhow::Result<DateTime<Utc>> {
     Ok(timestamp.into())
 }
 
-fn write_timestamp(dir: &Path, timestamp: DateTime<Utc>) -> anyhow::Result<()> {
+pub(crate) fn write_timestamp(dir: &Path, timestamp: DateTime<Utc>) -> anyhow::Result<()> {
     let timestamp_str = format!("{}\n", timestamp.to_rfc2822());
     let mut file =
         File::create(dir.join(TIMESTAMP_FILE_NAME)).context("Error creating timestamp file")?;
@@ -1079,6 +1094,7 @@ fn write_timestamp(dir: &Path, timestamp: DateTime<Utc>) -> anyhow::Result<()> {
 mod tests {
     use std::str::FromStr;
 
+    use crate::{notification_sink::AlertThrottle, storage::LocalStorage};
     use ic_registry_local_store::LocalStoreImpl;
     use ic_test_utilities_tmpdir::tmpdir;
     use ic_types::PrincipalId;
@@ -1158,8 +1174,8 @@ mod tests {
         assert_eq!(max_height, 150);
 
         let cold_storage_dirs = collect_and_sort_dir_entries(
-            &backup_helper
-                .cold_storage_dir
+            &dir.as_ref()
+                .join("cold_storage")
                 .join(FAKE_SUBNET_ID)
                 .join("artifacts"),
         );
@@ -1202,8 +1218,8 @@ mod tests {
             .expect("should execute successfully");
 
         let cold_storage_dirs = collect_and_sort_dir_entries(
-            &backup_helper
-                .cold_storage_dir
+            &dir.as_ref()
+                .join("cold_storage")
                 .join(FAKE_SUBNET_ID)
                 .join("states"),
         );
@@ -1246,8 +1262,8 @@ mod tests {
         assert!(cold_stored_states);
 
         let cold_storage_dirs = collect_and_sort_dir_entries(
-            &backup_helper
-                .cold_storage_dir
+            &dir.as_ref()
+                .join("cold_storage")
                 .join(FAKE_SUBNET_ID)
                 .join("states"),
         );
@@ -1301,6 +1317,54 @@ mod tests {
         );
     }
 
+    #[test]
+    fn apply_cold_storage_retention_test() {
+        let dir = tmpdir("test_dir");
+
+        let mut backup_helper = fake_backup_helper(
+            dir.as_ref(),
+            /*versions_hot=*/ 2,
+            /*daily_replays=*/ 2,
+        );
+        backup_helper.retention = RetentionPolicy {
+            artifacts_days: Some(7),
+            states_days: Some(3),
+        };
+
+        let cold_storage_dir = dir.as_ref().join("cold_storage").join(FAKE_SUBNET_ID);
+        let now = Utc::now();
+        let days_ago = |days| now - chrono::Duration::days(days);
+
+        // The newest state is retained even though it is the oldest one.
+        for (height, days) in [(10, 10), (20, 5), (30, 1), (40, 20)] {
+            let dir = cold_storage_dir.join("states").join(height.to_string());
+            create_dir_all(&dir).unwrap();
+            write_timestamp(&dir, days_ago(days)).unwrap();
+        }
+        let artifacts_dir = cold_storage_dir.join("artifacts");
+        create_dir_all(&artifacts_dir).unwrap();
+        for (height, days) in [(150, 10), (250, 1)] {
+            File::create(artifacts_dir.join(format!(
+                "{:010}_{:012}_replica_version_1.tgz",
+                days_ago(days).timestamp(),
+                height
+            )))
+            .unwrap();
+        }
+
+        backup_helper
+            .apply_cold_storage_retention()
+            .expect("should execute successfully");
+
+        assert_eq!(
+            collect_and_sort_dir_entries(&cold_storage_dir.join("states")),
+            vec!["30".to_string(), "40".to_string()]
+        );
+        let artifacts = collect_and_sort_dir_entries(&artifacts_dir);
+        assert_eq!(artifacts.len(), 1);
+        assert!(artifacts[0].ends_with("_000000000250_replica_version_1.tgz"));
+    }
+
     // Utility functions below
 
     fn create_artifacts_dir_with_heights(replica_version_dir: &Path, heights: Vec<u64>) {
@@ -1333,15 +1397,18 @@ mod tests {
             metrics_urls: vec![],
             network_name: "fake_network_name".into(),
             backup_instance: "fake_backup_instance".into(),
-            slack_token: "fake_slack_token".into(),
+            sinks: Arc::new(vec![]),
+            throttle: Arc::new(Mutex::new(AlertThrottle::new(&Default::default()))),
             subnet: "fake_subnet".into(),
             log: ic_recovery::util::make_logger(),
         };
 
+        let subnet_id = PrincipalId::from_str(FAKE_SUBNET_ID)
+            .map(SubnetId::from)
+            .unwrap();
+
         BackupHelper {
-            subnet_id: PrincipalId::from_str(FAKE_SUBNET_ID)
-                .map(SubnetId::from)
-                .unwrap(),
+            subnet_id,
             initial_replica_version: ReplicaVersion::try_from("fake_replica_version").unwrap(),
             root_dir: temp_dir.join("backup"),
             excluded_dirs: vec![],
@@ -1351,7 +1418,12 @@ mod tests {
             downloads_guard: Mutex::new(true).into(),
             hot_disk_resource_threshold_percentage: 75,
             cold_disk_resource_threshold_percentage: 95,
-            cold_storage_dir: temp_dir.join("cold_storage"),
+            cold_storage: Box::new(LocalStorage::new(
+                temp_dir.join("cold_storage"),
+                subnet_id,
+                ic_recovery::util::make_logger(),
+            )),
+            retention: RetentionPolicy::default(),
             versions_hot,
             artifacts_guard: Mutex::new(true),
             daily_replays,
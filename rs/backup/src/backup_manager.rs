@@ -25,6 +25,9 @@ use crate::{
     cmd::BackupArgs,
     config::{ColdStorage, Config, SubnetConfig},
     notification_client::NotificationClient,
+    notification_sink::{build_sinks, AlertKind, AlertThrottle},
+    object_storage::ObjectStorage,
+    storage::{LocalStorage, StorageBackend},
     util::{block_on, sleep_secs},
 };
 
@@ -61,6 +64,7 @@ impl BackupManager {
         let ColdStorage {
             cold_storage_dir,
             versions_hot,
+            object_storage,
         } = match config.cold_storage {
             Some(cs) => cs,
             None => panic!("Cold storage and cleanup are not configured"),
@@ -108,6 +112,13 @@ impl BackupManager {
 
         let mut backups = Vec::new();
 
+        let sinks = Arc::new(
+            build_sinks(&config.slack_token, &config.notification_sinks)
+                .expect("Notification sinks can't be initialized"),
+        );
+        let throttle = Arc::new(Mutex::new(AlertThrottle::new(
+            &config.notification_throttling,
+        )));
         let downloads = Arc::new(Mutex::new(true));
         let blacklisted = Arc::new(config.blacklisted_nodes.unwrap_or_default());
 
@@ -119,13 +130,32 @@ impl BackupManager {
                 metrics_urls: config.metrics_urls.clone(),
                 network_name: config.network_name.clone(),
                 backup_instance: config.backup_instance.clone(),
-                slack_token: config.slack_token.clone(),
+                sinks: sinks.clone(),
+                throttle: throttle.clone(),
                 subnet: subnet_config.subnet_id.to_string(),
                 log: subnet_log.clone(),
             };
             let daily_replays: usize = SECONDS_IN_DAY
                 .checked_div(subnet_config.replay_period_secs)
                 .unwrap_or(0) as usize;
+            let cold_storage: Box<dyn StorageBackend> = match &object_storage {
+                Some(object_storage) => Box::new(
+                    ObjectStorage::new(
+                        object_storage,
+                        subnet_config.subnet_id,
+                        config
+                            .root_dir
+                            .join(format!("uploads/{}", subnet_config.subnet_id)),
+                        subnet_log.clone(),
+                    )
+                    .expect("Object storage can't be initialized"),
+                ),
+                None => Box::new(LocalStorage::new(
+                    cold_storage_dir.clone(),
+                    subnet_config.subnet_id,
+                    subnet_log.clone(),
+                )),
+            };
             let backup_helper = BackupHelper {
                 subnet_id: subnet_config.subnet_id,
                 initial_replica_version: subnet_config.initial_replica_version,
@@ -139,7 +169,8 @@ impl BackupManager {
                     .hot_disk_resource_threshold_percentage,
                 cold_disk_resource_threshold_percentage: config
                     .cold_disk_resource_threshold_percentage,
-                cold_storage_dir: cold_storage_dir.clone(),
+                cold_storage,
+                retention: subnet_config.retention,
                 versions_hot,
                 artifacts_guard: Mutex::new(true),
                 daily_replays,
@@ -176,6 +207,46 @@ impl BackupManager {
         println!("{}", replica_version)
     }
 
+    pub fn restore_state(
+        log: Logger,
+        config_file: PathBuf,
+        subnet_id: SubnetId,
+        height: u64,
+        target_dir: PathBuf,
+    ) {
+        BackupManager::object_storage(log, config_file, subnet_id)
+            .restore_state(height, &target_dir)
+            .expect("State can't be restored");
+    }
+
+    pub fn restore_artifacts(
+        log: Logger,
+        config_file: PathBuf,
+        subnet_id: SubnetId,
+        replica_version: &str,
+        top_height: u64,
+        target_dir: PathBuf,
+    ) {
+        BackupManager::object_storage(log, config_file, subnet_id)
+            .restore_artifacts(replica_version, top_height, &target_dir)
+            .expect("Artifacts can't be restored");
+    }
+
+    fn object_storage(log: Logger, config_file: PathBuf, subnet_id: SubnetId) -> ObjectStorage {
+        let config = Config::load_config(config_file).expect("Config file can't be loaded");
+        let object_storage = config
+            .cold_storage
+            .and_then(|cold_storage| cold_storage.object_storage)
+            .expect("Object storage is not configured");
+        ObjectStorage::new(
+            &object_storage,
+            subnet_id,
+            config.root_dir.join(format!("uploads/{}", subnet_id)),
+            log,
+        )
+        .expect("Object storage can't be initialized")
+    }
+
     pub fn upgrade(log: Logger, config_file: PathBuf) {
         let config = Config::load_config(config_file.clone()).expect("Config file can't be loaded");
         config
@@ -270,6 +341,7 @@ impl BackupManager {
                 replay_period_secs,
                 thread_id,
                 disable_cold_storage: false,
+                retention: Default::default(),
             })
         }
 
@@ -311,6 +383,7 @@ impl BackupManager {
         config.cold_storage = Some(ColdStorage {
             cold_storage_dir,
             versions_hot,
+            object_storage: None,
         });
 
         config
@@ -464,6 +537,27 @@ fn cold_store(m: Arc<BackupManager>) {
             let b = &m.subnet_backups[i];
 
             let subnet_id = &b.backup_helper.subnet_id;
+            match b.backup_helper.relieve_hot_storage() {
+                Ok(true) => info!(
+                    m.log,
+                    "Moved states of subnet {} to the cold storage to free hot storage", subnet_id
+                ),
+                Ok(false) => {}
+                Err(err) => error!(
+                    m.log,
+                    "Error freeing hot storage of subnet {}: {}", subnet_id, err
+                ),
+            }
+            if let Err(err) = b.backup_helper.apply_cold_storage_retention() {
+                let msg = format!(
+                    "Error applying the cold storage retention for subnet {}: {}",
+                    subnet_id, err
+                );
+                error!(m.log, "{}", msg);
+                b.backup_helper
+                    .notification_client
+                    .report_failure(AlertKind::ColdStorage, msg);
+            }
             match b.backup_helper.need_cold_storage_move() {
                 Ok(need) => {
                     if !need {
@@ -486,7 +580,7 @@ fn cold_store(m: Arc<BackupManager>) {
                 error!(m.log, "{}", msg);
                 b.backup_helper
                     .notification_client
-                    .report_failure_slack(msg);
+                    .report_failure(AlertKind::ColdStorage, msg);
             }
         }
 
@@ -568,10 +662,12 @@ mod tests {
                 replay_period_secs: FAKE_REPLAY_PERIOD_MINS * 60,
                 thread_id: 1,
                 disable_cold_storage: false,
+                retention: Default::default(),
             }],
             cold_storage: Some(ColdStorage {
                 cold_storage_dir: fake_cold_storage_path,
                 versions_hot: FAKE_VERSIONS_HOT,
+                object_storage: None,
             }),
             slack_token: FAKE_SLACK_TOKEN.to_string(),
             ..serde_json::from_str(&fake_input_config).unwrap()
@@ -43,4 +43,24 @@ pub enum SubCommand {
         /// The ID of the target subnet
         subnet_id: ClapSubnetId,
     },
+    /// Restore a state from the object storage
+    RestoreState {
+        /// The ID of the target subnet
+        subnet_id: ClapSubnetId,
+        /// The height of the state
+        height: u64,
+        /// The directory to restore the state to, which must not exist or be empty
+        target_dir: PathBuf,
+    },
+    /// Restore the artifacts of a replica version from the object storage
+    RestoreArtifacts {
+        /// The ID of the target subnet
+        subnet_id: ClapSubnetId,
+        /// The replica version of the artifacts
+        replica_version: String,
+        /// The highest height of the artifacts
+        top_height: u64,
+        /// The directory to restore the artifacts to, which must not exist or be empty
+        target_dir: PathBuf,
+    },
 }
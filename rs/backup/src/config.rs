@@ -17,12 +17,78 @@ pub struct SubnetConfig {
     pub replay_period_secs: u64,
     pub thread_id: u32,
     pub disable_cold_storage: bool,
+    #[serde(default)]
+    pub retention: RetentionPolicy,
+}
+
+/// For how long artifacts and states are kept in the cold storage. `None` keeps them forever.
+/// The newest state in the cold storage is never deleted.
+#[derive(Clone, Eq, PartialEq, Debug, Default, Deserialize, Serialize)]
+pub struct RetentionPolicy {
+    pub artifacts_days: Option<u64>,
+    pub states_days: Option<u64>,
 }
 
 #[derive(Clone, Eq, PartialEq, Debug, Deserialize, Serialize)]
 pub struct ColdStorage {
     pub cold_storage_dir: PathBuf,
     pub versions_hot: usize,
+    /// If set, the cold storage is kept in an S3-compatible object store instead of
+    /// `cold_storage_dir`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub object_storage: Option<ObjectStorageConfig>,
+}
+
+#[derive(Clone, Eq, PartialEq, Debug, Deserialize, Serialize)]
+pub struct ObjectStorageConfig {
+    pub endpoint: Url,
+    pub bucket: String,
+    pub region: String,
+    pub access_key_id: String,
+    pub secret_access_key_file: PathBuf,
+    /// Prefix of all the object keys, e.g. `mainnet/`.
+    #[serde(default)]
+    pub prefix: String,
+}
+
+/// A channel, besides Slack, where the alerts of the backup pod are sent to.
+#[derive(Clone, Eq, PartialEq, Debug, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NotificationSinkConfig {
+    /// Posts every alert as a JSON object to `url`.
+    Webhook { url: Url },
+    /// Sends every alert as an email through the SMTP server at `smtp_server` (`host:port`).
+    /// The connection is upgraded to TLS if the server supports STARTTLS. Credentials are
+    /// never sent over an unencrypted connection.
+    Email {
+        smtp_server: String,
+        from: String,
+        to: Vec<String>,
+        /// A file containing `<username>:<password>` if the server requires authentication.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        credentials_file: Option<PathBuf>,
+    },
+    /// Posts warnings and failures to the Prometheus Alertmanager at the base URL `url`.
+    Alertmanager { url: Url },
+}
+
+/// Limits on the warnings and failures sent to the notification sinks.
+#[derive(Clone, Eq, PartialEq, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct NotificationThrottling {
+    /// Identical alerts of a subnet are sent at most once within this period.
+    pub dedup_window_secs: u64,
+    /// At most this many alerts are sent within an hour.
+    pub max_alerts_per_hour: usize,
+}
+
+impl Default for NotificationThrottling {
+    fn default() -> Self {
+        Self {
+            dedup_window_secs: 60 * 60,
+            max_alerts_per_hour: 30,
+        }
+    }
 }
 
 #[derive(Clone, Eq, PartialEq, Debug, Default, Deserialize, Serialize)]
@@ -39,6 +105,10 @@ pub struct Config {
     pub hot_disk_resource_threshold_percentage: u32,
     pub cold_disk_resource_threshold_percentage: u32,
     pub slack_token: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notification_sinks: Vec<NotificationSinkConfig>,
+    #[serde(default)]
+    pub notification_throttling: NotificationThrottling,
     pub cold_storage: Option<ColdStorage>,
     pub blacklisted_nodes: Option<Vec<IpAddr>>,
     pub subnets: Vec<SubnetConfig>,
@@ -61,6 +131,38 @@ impl ConfigValidate for Config {
         if self.cold_disk_resource_threshold_percentage >= 100 {
             return Err("Cold disk threshold warning should be below 100%".to_string());
         }
+        if let Some(object_storage) = self
+            .cold_storage
+            .as_ref()
+            .and_then(|cs| cs.object_storage.as_ref())
+        {
+            if !object_storage.secret_access_key_file.exists() {
+                return Err(format!(
+                    "Missing object storage credentials file: {:?}",
+                    object_storage.secret_access_key_file
+                ));
+            }
+        }
+        for sink in &self.notification_sinks {
+            if let NotificationSinkConfig::Email {
+                to,
+                credentials_file,
+                ..
+            } = sink
+            {
+                if to.is_empty() {
+                    return Err("No recipient configured for email notifications".to_string());
+                }
+                if let Some(credentials_file) = credentials_file {
+                    if !credentials_file.exists() {
+                        return Err(format!(
+                            "Missing SMTP credentials file: {:?}",
+                            credentials_file
+                        ));
+                    }
+                }
+            }
+        }
         // we accept no subnets in the config at the initial stage only
         if self.subnets.is_empty() && self.slack_token != "<INSERT SLACK TOKEN>" {
             return Err("No subnet configured for backup!".to_string());
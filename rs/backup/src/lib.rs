@@ -3,4 +3,7 @@ pub mod backup_manager;
 pub mod cmd;
 pub mod config;
 mod notification_client;
+mod notification_sink;
+mod object_storage;
+mod storage;
 mod util;
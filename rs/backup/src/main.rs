@@ -27,6 +27,20 @@ use tokio::{runtime::Handle, task::spawn_blocking};
 //     "hot_disk_resource_threshold_percentage": 75,
 //     "cold_disk_resource_threshold_percentage": 95,
 //     "slack_token": "ABCD1234",
+//     "notification_sinks": [
+//         { "type": "webhook", "url": "https://hooks.example.com/backup" },
+//         {
+//             "type": "email",
+//             "smtp_server": "localhost:25",
+//             "from": "backup@example.com",
+//             "to": ["oncall@example.com"]
+//         },
+//         { "type": "alertmanager", "url": "http://localhost:9093/" }
+//     ],
+//     "notification_throttling": {
+//         "dedup_window_secs": 3600,
+//         "max_alerts_per_hour": 30
+//     },
 //     "cold_storage": {
 //         "cold_storage_dir": "/var/cold_storage",
 //         "versions_hot": 2
@@ -80,6 +94,26 @@ async fn main() {
             Some(SubCommand::GetReplicaVersion { subnet_id }) => {
                 BackupManager::get_version(log, args.config_file, subnet_id.0)
             }
+            Some(SubCommand::RestoreState {
+                subnet_id,
+                height,
+                target_dir,
+            }) => {
+                BackupManager::restore_state(log, args.config_file, subnet_id.0, height, target_dir)
+            }
+            Some(SubCommand::RestoreArtifacts {
+                subnet_id,
+                replica_version,
+                top_height,
+                target_dir,
+            }) => BackupManager::restore_artifacts(
+                log,
+                args.config_file,
+                subnet_id.0,
+                &replica_version,
+                top_height,
+                target_dir,
+            ),
             _ => {
                 let bm = BackupManager::new(log, args, &rt);
                 Arc::new(bm).do_backups();
@@ -1,8 +1,13 @@
-use std::path::Path;
+use std::{
+    path::Path,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
 
-use crate::util::block_on;
-use slog::{error, info, Logger};
-use std::time::Duration;
+use crate::notification_sink::{
+    http_post, Alert, AlertKind, AlertThrottle, DiskUsage, NotificationSink, Severity, Verdict,
+};
+use slog::{debug, error, info, warn, Logger};
 use url::Url;
 
 pub struct NotificationClient {
@@ -10,51 +15,106 @@ pub struct NotificationClient {
     pub metrics_urls: Vec<Url>,
     pub network_name: String,
     pub backup_instance: String,
-    pub slack_token: String,
+    pub sinks: Arc<Vec<Box<dyn NotificationSink>>>,
+    pub throttle: Arc<Mutex<AlertThrottle>>,
     pub subnet: String,
     pub log: Logger,
 }
 
 impl NotificationClient {
     fn http_post_request(&self, url: String, content_type: String, data_str: String) {
-        block_on(async {
-            let client = reqwest::Client::new();
-            match client
-                .post(url)
-                .timeout(Duration::from_secs(60))
-                .header(reqwest::header::CONTENT_TYPE, content_type)
-                .body(data_str)
-                .send()
-                .await
-            {
-                Ok(_) => {}
-                Err(err) => error!(self.log, "Http POST failed: {}", err),
+        if let Err(err) = http_post(&url, &content_type, data_str) {
+            error!(self.log, "{}", err);
+        }
+    }
+
+    fn alert(&self, severity: Severity, kind: AlertKind, message: String) -> Alert {
+        Alert {
+            network: self.network_name.clone(),
+            backup_instance: self.backup_instance.clone(),
+            subnet: self.subnet.clone(),
+            severity,
+            kind,
+            message,
+            height: None,
+            disk_usage: None,
+            suppressed: 0,
+        }
+    }
+
+    /// Sends the alert to all the sinks accepting its severity. Warnings and failures are
+    /// deduplicated and rate-limited.
+    fn send(&self, mut alert: Alert) {
+        match alert.severity {
+            Severity::Info => info!(self.log, "{}", alert.message),
+            Severity::Warning => warn!(self.log, "{}", alert.message),
+            Severity::Failure => error!(self.log, "{}", alert.message),
+        }
+        if alert.severity != Severity::Info {
+            let verdict = self
+                .throttle
+                .lock()
+                .expect("notification throttle mutex lock failed")
+                .check(&alert, Instant::now());
+            match verdict {
+                Verdict::Send { suppressed } => alert.suppressed = suppressed,
+                Verdict::Duplicate => {
+                    debug!(self.log, "Not sending a duplicate alert");
+                    return;
+                }
+                Verdict::RateLimited => {
+                    warn!(self.log, "Too many alerts, not sending: {}", alert.message);
+                    return;
+                }
             }
-        });
+        }
+        for sink in self.sinks.iter() {
+            if !sink.accepts(alert.severity) {
+                continue;
+            }
+            if let Err(err) = sink.send(&alert) {
+                error!(self.log, "Error sending alert to {}: {}", sink.name(), err);
+            }
+        }
     }
 
-    pub fn message_slack(&self, message: String) {
-        info!(self.log, "{}", message);
-        let url = format!(
-            "https://hooks.slack.com/services/T43F9UHS5/B027BHAQ1HQ/{}",
-            self.slack_token
-        );
-        let data_str = format!(
-            "{{\"text\":\"[{}, *{}*] {}\"}}",
-            self.backup_instance,
-            &self.subnet[0..5],
-            message
-        );
-        let content_type = "Content-type: application/json".to_string();
-        self.http_post_request(url, content_type, data_str)
+    pub(crate) fn notify(
+        &self,
+        severity: Severity,
+        kind: AlertKind,
+        height: Option<u64>,
+        message: String,
+    ) {
+        let mut alert = self.alert(severity, kind, message);
+        alert.height = height;
+        self.send(alert)
     }
 
-    pub fn report_failure_slack(&self, message: String) {
-        self.message_slack(format!("<!channel> ❌ {}", message))
+    pub(crate) fn message(&self, kind: AlertKind, message: String) {
+        self.notify(Severity::Info, kind, None, message)
     }
 
-    pub fn report_warning_slack(&self, message: String) {
-        self.message_slack(format!("⚠️ {}", message))
+    pub(crate) fn report_failure(&self, kind: AlertKind, message: String) {
+        self.notify(Severity::Failure, kind, None, message)
+    }
+
+    pub(crate) fn report_warning(&self, kind: AlertKind, message: String) {
+        self.notify(Severity::Warning, kind, None, message)
+    }
+
+    pub(crate) fn report_disk_usage(&self, dir: &Path, resource: &str, percentage: u32) {
+        let dir = dir.to_str().unwrap_or_default().to_string();
+        let mut alert = self.alert(
+            Severity::Warning,
+            AlertKind::DiskUsage,
+            format!("[{}] {} usage is at {}%", dir, resource, percentage),
+        );
+        alert.disk_usage = Some(DiskUsage {
+            dir,
+            resource: resource.to_string(),
+            percentage,
+        });
+        self.send(alert)
     }
 
     fn push_metrics(&self, message: String) {
@@ -0,0 +1,745 @@
+use crate::{
+    config::{NotificationSinkConfig, NotificationThrottling},
+    util::block_on,
+};
+use serde::Serialize;
+use serde_json::json;
+use std::{
+    collections::{HashMap, VecDeque},
+    io::{BufRead, BufReader, Read, Write},
+    net::TcpStream,
+    path::Path,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use url::Url;
+
+const SLACK_WEBHOOK_URL: &str = "https://hooks.slack.com/services/T43F9UHS5/B027BHAQ1HQ";
+const HTTP_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum Severity {
+    Info,
+    Warning,
+    Failure,
+}
+
+/// What an alert is about.
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum AlertKind {
+    Download,
+    Sync,
+    Upgrade,
+    Replay,
+    Archive,
+    ColdStorage,
+    DiskUsage,
+}
+
+#[derive(Clone, Eq, PartialEq, Debug, Serialize)]
+pub(crate) struct DiskUsage {
+    pub(crate) dir: String,
+    pub(crate) resource: String,
+    pub(crate) percentage: u32,
+}
+
+#[derive(Clone, Eq, PartialEq, Debug, Serialize)]
+pub(crate) struct Alert {
+    pub(crate) network: String,
+    pub(crate) backup_instance: String,
+    pub(crate) subnet: String,
+    pub(crate) severity: Severity,
+    pub(crate) kind: AlertKind,
+    pub(crate) message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) height: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) disk_usage: Option<DiskUsage>,
+    /// The number of identical alerts which were not sent since this alert was last sent.
+    pub(crate) suppressed: u64,
+}
+
+impl Alert {
+    fn short_subnet(&self) -> &str {
+        self.subnet.get(0..5).unwrap_or(&self.subnet)
+    }
+
+    /// The message followed by the number of suppressed alerts, if any.
+    fn text(&self) -> String {
+        if self.suppressed > 0 {
+            format!(
+                "{} (suppressed {} identical alerts)",
+                self.message, self.suppressed
+            )
+        } else {
+            self.message.clone()
+        }
+    }
+}
+
+/// A channel where the alerts of the backup pod are sent to.
+pub trait NotificationSink: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Returns true if alerts of the given severity should be sent to this sink.
+    fn accepts(&self, _severity: Severity) -> bool {
+        true
+    }
+
+    fn send(&self, alert: &Alert) -> Result<(), String>;
+}
+
+/// Returns the sinks of the backup pod: Slack, if a token is configured, and all the configured
+/// sinks.
+pub(crate) fn build_sinks(
+    slack_token: &str,
+    configs: &[NotificationSinkConfig],
+) -> Result<Vec<Box<dyn NotificationSink>>, String> {
+    let mut sinks: Vec<Box<dyn NotificationSink>> = Vec::new();
+    if !slack_token.is_empty() {
+        sinks.push(Box::new(SlackSink {
+            token: slack_token.to_string(),
+        }));
+    }
+    for config in configs {
+        let sink: Box<dyn NotificationSink> = match config {
+            NotificationSinkConfig::Webhook { url } => Box::new(WebhookSink { url: url.clone() }),
+            NotificationSinkConfig::Email {
+                smtp_server,
+                from,
+                to,
+                credentials_file,
+            } => Box::new(EmailSink {
+                smtp_server: smtp_server.clone(),
+                from: from.clone(),
+                to: to.clone(),
+                credentials: credentials_file
+                    .as_deref()
+                    .map(read_credentials)
+                    .transpose()?,
+            }),
+            NotificationSinkConfig::Alertmanager { url } => Box::new(AlertmanagerSink {
+                url: url
+                    .join("api/v2/alerts")
+                    .map_err(|err| format!("Invalid Alertmanager URL {}: {:?}", url, err))?,
+            }),
+        };
+        sinks.push(sink);
+    }
+    Ok(sinks)
+}
+
+fn read_credentials(path: &Path) -> Result<(String, String), String> {
+    let credentials = std::fs::read_to_string(path)
+        .map_err(|err| format!("Error reading credentials from {:?}: {:?}", path, err))?;
+    credentials
+        .trim()
+        .split_once(':')
+        .map(|(username, password)| (username.to_string(), password.to_string()))
+        .ok_or_else(|| format!("Credentials in {:?} are not <username>:<password>", path))
+}
+
+pub(crate) fn http_post(url: &str, content_type: &str, body: String) -> Result<(), String> {
+    block_on(async {
+        let client = reqwest::Client::new();
+        let response = client
+            .post(url)
+            .timeout(HTTP_TIMEOUT)
+            .header(reqwest::header::CONTENT_TYPE, content_type)
+            .body(body)
+            .send()
+            .await
+            .map_err(|err| format!("Http POST failed: {}", err))?;
+        if !response.status().is_success() {
+            return Err(format!("Http POST failed with {}", response.status()));
+        }
+        Ok(())
+    })
+}
+
+struct SlackSink {
+    token: String,
+}
+
+impl NotificationSink for SlackSink {
+    fn name(&self) -> &'static str {
+        "Slack"
+    }
+
+    fn send(&self, alert: &Alert) -> Result<(), String> {
+        let text = match alert.severity {
+            Severity::Info => alert.text(),
+            Severity::Warning => format!("⚠️ {}", alert.text()),
+            Severity::Failure => format!("<!channel> ❌ {}", alert.text()),
+        };
+        let data = json!({
+            "text": format!("[{}, *{}*] {}", alert.backup_instance, alert.short_subnet(), text)
+        });
+        http_post(
+            &format!("{}/{}", SLACK_WEBHOOK_URL, self.token),
+            "application/json",
+            data.to_string(),
+        )
+    }
+}
+
+/// Posts every alert as a JSON object.
+struct WebhookSink {
+    url: Url,
+}
+
+impl NotificationSink for WebhookSink {
+    fn name(&self) -> &'static str {
+        "webhook"
+    }
+
+    fn send(&self, alert: &Alert) -> Result<(), String> {
+        let body = serde_json::to_string(alert)
+            .map_err(|err| format!("Error serializing alert: {:?}", err))?;
+        http_post(self.url.as_str(), "application/json", body)
+    }
+}
+
+/// Posts warnings and failures to the Alertmanager API. Alertmanager groups and deduplicates
+/// alerts by their labels, so the message is only an annotation.
+struct AlertmanagerSink {
+    url: Url,
+}
+
+fn alertmanager_payload(alert: &Alert) -> serde_json::Value {
+    let severity = match alert.severity {
+        Severity::Info => "info",
+        Severity::Warning => "warning",
+        Severity::Failure => "critical",
+    };
+    let mut annotations = json!({ "summary": alert.text() });
+    if let Some(height) = alert.height {
+        annotations["height"] = json!(height.to_string());
+    }
+    if let Some(disk_usage) = &alert.disk_usage {
+        annotations["disk_usage"] = json!(format!(
+            "{} {}%",
+            disk_usage.resource, disk_usage.percentage
+        ));
+        annotations["dir"] = json!(disk_usage.dir);
+    }
+    json!([{
+        "labels": {
+            "alertname": "BackupPodAlert",
+            "severity": severity,
+            "kind": alert.kind,
+            "ic": alert.network,
+            "instance": alert.backup_instance,
+            "ic_subnet": alert.subnet,
+        },
+        "annotations": annotations,
+    }])
+}
+
+impl NotificationSink for AlertmanagerSink {
+    fn name(&self) -> &'static str {
+        "Alertmanager"
+    }
+
+    fn accepts(&self, severity: Severity) -> bool {
+        severity != Severity::Info
+    }
+
+    fn send(&self, alert: &Alert) -> Result<(), String> {
+        http_post(
+            self.url.as_str(),
+            "application/json",
+            alertmanager_payload(alert).to_string(),
+        )
+    }
+}
+
+struct EmailSink {
+    smtp_server: String,
+    from: String,
+    to: Vec<String>,
+    credentials: Option<(String, String)>,
+}
+
+/// Reads an SMTP reply, which may span multiple lines, checks its code and returns the text of
+/// its lines.
+fn read_smtp_reply(reader: &mut impl BufRead, expected_code: &str) -> Result<Vec<String>, String> {
+    let mut lines = Vec::new();
+    loop {
+        let mut line = String::new();
+        reader
+            .read_line(&mut line)
+            .map_err(|err| format!("Error reading SMTP reply: {:?}", err))?;
+        if !line.starts_with(expected_code) {
+            return Err(format!(
+                "Unexpected SMTP reply (expected {}): {}",
+                expected_code,
+                line.trim_end()
+            ));
+        }
+        lines.push(line.get(4..).unwrap_or_default().trim_end().to_string());
+        // The last line of a reply has a space after the code.
+        if line.as_bytes().get(3) != Some(&b'-') {
+            return Ok(lines);
+        }
+    }
+}
+
+/// An SMTP connection over a plain or an encrypted stream.
+struct SmtpConnection<S: Read + Write> {
+    reader: BufReader<S>,
+}
+
+impl<S: Read + Write> SmtpConnection<S> {
+    fn new(stream: S) -> Self {
+        Self {
+            reader: BufReader::new(stream),
+        }
+    }
+
+    fn command(&mut self, command: &str, expected_code: &str) -> Result<Vec<String>, String> {
+        self.reader
+            .get_mut()
+            .write_all(format!("{}\r\n", command).as_bytes())
+            .map_err(|err| format!("Error writing SMTP command: {:?}", err))?;
+        read_smtp_reply(&mut self.reader, expected_code)
+    }
+
+    /// Returns the stream, e.g. to start a TLS session on it. The server must not have sent
+    /// anything which was not read yet, as it would otherwise be lost.
+    fn into_inner(self) -> Result<S, String> {
+        if !self.reader.buffer().is_empty() {
+            return Err("Unexpected SMTP data before the TLS handshake".to_string());
+        }
+        Ok(self.reader.into_inner())
+    }
+}
+
+/// Starts a TLS session on the stream, verifying that the server certificate is valid for `host`.
+fn start_tls(
+    host: &str,
+    stream: TcpStream,
+) -> Result<rustls::StreamOwned<rustls::ClientConnection, TcpStream>, String> {
+    let root_store = rustls::RootCertStore {
+        roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+    };
+    let config = rustls::ClientConfig::builder_with_provider(Arc::new(
+        rustls::crypto::ring::default_provider(),
+    ))
+    .with_safe_default_protocol_versions()
+    .map_err(|err| format!("Error configuring TLS: {:?}", err))?
+    .with_root_certificates(root_store)
+    .with_no_client_auth();
+    let server_name = rustls::pki_types::ServerName::try_from(host.to_string())
+        .map_err(|err| format!("Invalid SMTP server name {}: {:?}", host, err))?;
+    let connection = rustls::ClientConnection::new(Arc::new(config), server_name)
+        .map_err(|err| format!("Error starting TLS session: {:?}", err))?;
+    Ok(rustls::StreamOwned::new(connection, stream))
+}
+
+impl EmailSink {
+    fn subject(alert: &Alert) -> String {
+        let severity = match alert.severity {
+            Severity::Info => "Info",
+            Severity::Warning => "Warning",
+            Severity::Failure => "Failure",
+        };
+        format!(
+            "[{}] {} on subnet {}: {:?}",
+            alert.backup_instance,
+            severity,
+            alert.short_subnet(),
+            alert.kind
+        )
+    }
+
+    fn body(alert: &Alert) -> String {
+        let mut body = format!(
+            "{}\r\n\r\nNetwork: {}\r\nSubnet: {}\r\n",
+            alert.text(),
+            alert.network,
+            alert.subnet
+        );
+        if let Some(height) = alert.height {
+            body.push_str(&format!("Height: {}\r\n", height));
+        }
+        if let Some(disk_usage) = &alert.disk_usage {
+            body.push_str(&format!(
+                "Disk usage: {} {}% ({})\r\n",
+                disk_usage.resource, disk_usage.percentage, disk_usage.dir
+            ));
+        }
+        // Lines starting with a dot need an additional one, as a single dot ends the message. The
+        // line endings are normalized first, so that lines ending in a bare newline are stuffed too.
+        body.replace("\r\n", "\n")
+            .replace('\r', "\n")
+            .split('\n')
+            .map(|line| {
+                if line.starts_with('.') {
+                    format!(".{}", line)
+                } else {
+                    line.to_string()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\r\n")
+    }
+
+    fn send_mail<S: Read + Write>(
+        &self,
+        connection: &mut SmtpConnection<S>,
+        alert: &Alert,
+    ) -> Result<(), String> {
+        if let Some((username, password)) = &self.credentials {
+            let token = base64::encode(format!("\0{}\0{}", username, password));
+            connection.command(&format!("AUTH PLAIN {}", token), "235")?;
+        }
+        connection.command(&format!("MAIL FROM:<{}>", self.from), "250")?;
+        for to in &self.to {
+            connection.command(&format!("RCPT TO:<{}>", to), "25")?;
+        }
+        connection.command("DATA", "354")?;
+        connection.command(
+            &format!(
+                "From: {}\r\nTo: {}\r\nSubject: {}\r\nDate: {}\r\n\
+                Content-Type: text/plain; charset=utf-8\r\n\r\n{}\r\n.",
+                self.from,
+                self.to.join(", "),
+                Self::subject(alert),
+                chrono::Utc::now().to_rfc2822(),
+                Self::body(alert)
+            ),
+            "250",
+        )?;
+        connection.command("QUIT", "221")?;
+        Ok(())
+    }
+}
+
+impl NotificationSink for EmailSink {
+    fn name(&self) -> &'static str {
+        "email"
+    }
+
+    fn send(&self, alert: &Alert) -> Result<(), String> {
+        let stream = TcpStream::connect(&self.smtp_server)
+            .map_err(|err| format!("Error connecting to {}: {:?}", self.smtp_server, err))?;
+        stream
+            .set_read_timeout(Some(HTTP_TIMEOUT))
+            .and_then(|_| stream.set_write_timeout(Some(HTTP_TIMEOUT)))
+            .map_err(|err| format!("Error setting SMTP timeouts: {:?}", err))?;
+        let mut connection = SmtpConnection::new(stream);
+        read_smtp_reply(&mut connection.reader, "220")?;
+
+        let extensions = connection.command("EHLO backup-pod", "250")?;
+        if extensions
+            .iter()
+            .any(|extension| extension.eq_ignore_ascii_case("STARTTLS"))
+        {
+            connection.command("STARTTLS", "220")?;
+            let host = self
+                .smtp_server
+                .rsplit_once(':')
+                .map_or(self.smtp_server.as_str(), |(host, _)| host);
+            let mut connection = SmtpConnection::new(start_tls(host, connection.into_inner()?)?);
+            connection.command("EHLO backup-pod", "250")?;
+            self.send_mail(&mut connection, alert)
+        } else if self.credentials.is_some() {
+            // Never send the credentials in cleartext.
+            Err(format!(
+                "Refusing to authenticate to {}, as it does not support STARTTLS",
+                self.smtp_server
+            ))
+        } else {
+            self.send_mail(&mut connection, alert)
+        }
+    }
+}
+
+pub(crate) enum Verdict {
+    /// The alert should be sent. `suppressed` identical alerts were not sent since the last
+    /// time it was sent.
+    Send { suppressed: u64 },
+    /// An identical alert was sent recently.
+    Duplicate,
+    /// Too many alerts were sent within the last hour.
+    RateLimited,
+}
+
+/// Deduplicates and rate-limits the alerts of all subnets.
+pub struct AlertThrottle {
+    dedup_window: Duration,
+    max_alerts_per_hour: usize,
+    /// When each alert was last sent and how many identical ones were suppressed since then.
+    last_sent: HashMap<(String, Severity, AlertKind, String), (Instant, u64)>,
+    /// When the alerts within the last hour were sent.
+    sent: VecDeque<Instant>,
+}
+
+impl AlertThrottle {
+    pub(crate) fn new(config: &NotificationThrottling) -> Self {
+        Self {
+            dedup_window: Duration::from_secs(config.dedup_window_secs),
+            max_alerts_per_hour: config.max_alerts_per_hour,
+            last_sent: HashMap::new(),
+            sent: VecDeque::new(),
+        }
+    }
+
+    pub(crate) fn check(&mut self, alert: &Alert, now: Instant) -> Verdict {
+        const HOUR: Duration = Duration::from_secs(60 * 60);
+        while self
+            .sent
+            .front()
+            .is_some_and(|sent| now.duration_since(*sent) >= HOUR)
+        {
+            self.sent.pop_front();
+        }
+        let dedup_window = self.dedup_window;
+        self.last_sent.retain(|_, (sent, suppressed)| {
+            *suppressed > 0 || now.duration_since(*sent) < dedup_window
+        });
+
+        // Disk usage alerts differ in the percentage only, so they are identical if they are
+        // about the same resource.
+        let subject = match &alert.disk_usage {
+            Some(disk_usage) => format!("{} {}", disk_usage.dir, disk_usage.resource),
+            None => alert.message.clone(),
+        };
+        let key = (alert.subnet.clone(), alert.severity, alert.kind, subject);
+        if let Some((sent, suppressed)) = self.last_sent.get_mut(&key) {
+            if now.duration_since(*sent) < dedup_window {
+                *suppressed += 1;
+                return Verdict::Duplicate;
+            }
+        }
+        if self.sent.len() >= self.max_alerts_per_hour {
+            return Verdict::RateLimited;
+        }
+        self.sent.push_back(now);
+        let suppressed = self
+            .last_sent
+            .insert(key, (now, 0))
+            .map_or(0, |(_, suppressed)| suppressed);
+        Verdict::Send { suppressed }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{net::TcpListener, thread};
+
+    fn fake_alert(message: &str) -> Alert {
+        Alert {
+            network: "fake_network_name".into(),
+            backup_instance: "fake_backup_instance".into(),
+            subnet: "gpvux-2ejnk".into(),
+            severity: Severity::Failure,
+            kind: AlertKind::Replay,
+            message: message.into(),
+            height: Some(100),
+            disk_usage: None,
+            suppressed: 0,
+        }
+    }
+
+    #[test]
+    fn throttle_deduplicates_alerts_test() {
+        let mut throttle = AlertThrottle::new(&NotificationThrottling {
+            dedup_window_secs: 60,
+            max_alerts_per_hour: 100,
+        });
+        let start = Instant::now();
+        let stuck = fake_alert("No height progress after the last replay detected!");
+
+        assert!(matches!(
+            throttle.check(&stuck, start),
+            Verdict::Send { suppressed: 0 }
+        ));
+        for secs in [10, 20, 59] {
+            assert!(matches!(
+                throttle.check(&stuck, start + Duration::from_secs(secs)),
+                Verdict::Duplicate
+            ));
+        }
+        // Other alerts are not affected.
+        assert!(matches!(
+            throttle.check(&fake_alert("Couldn't archive the replayed state!"), start),
+            Verdict::Send { suppressed: 0 }
+        ));
+        assert!(matches!(
+            throttle.check(&stuck, start + Duration::from_secs(60)),
+            Verdict::Send { suppressed: 3 }
+        ));
+        assert!(matches!(
+            throttle.check(&stuck, start + Duration::from_secs(200)),
+            Verdict::Send { suppressed: 0 }
+        ));
+    }
+
+    #[test]
+    fn throttle_rate_limits_alerts_test() {
+        let mut throttle = AlertThrottle::new(&NotificationThrottling {
+            dedup_window_secs: 60,
+            max_alerts_per_hour: 2,
+        });
+        let start = Instant::now();
+
+        assert!(matches!(
+            throttle.check(&fake_alert("1"), start),
+            Verdict::Send { .. }
+        ));
+        assert!(matches!(
+            throttle.check(&fake_alert("2"), start + Duration::from_secs(1)),
+            Verdict::Send { .. }
+        ));
+        assert!(matches!(
+            throttle.check(&fake_alert("3"), start + Duration::from_secs(2)),
+            Verdict::RateLimited
+        ));
+        assert!(matches!(
+            throttle.check(&fake_alert("3"), start + Duration::from_secs(60 * 60)),
+            Verdict::Send { .. }
+        ));
+    }
+
+    #[test]
+    fn alertmanager_payload_test() {
+        let mut alert = fake_alert("Hot storage is full");
+        alert.severity = Severity::Warning;
+        alert.kind = AlertKind::DiskUsage;
+        alert.height = None;
+        alert.disk_usage = Some(DiskUsage {
+            dir: "/var/lib/backup".into(),
+            resource: "space".into(),
+            percentage: 91,
+        });
+
+        assert_eq!(
+            alertmanager_payload(&alert),
+            json!([{
+                "labels": {
+                    "alertname": "BackupPodAlert",
+                    "severity": "warning",
+                    "kind": "disk_usage",
+                    "ic": "fake_network_name",
+                    "instance": "fake_backup_instance",
+                    "ic_subnet": "gpvux-2ejnk",
+                },
+                "annotations": {
+                    "summary": "Hot storage is full",
+                    "disk_usage": "space 91%",
+                    "dir": "/var/lib/backup",
+                },
+            }])
+        );
+    }
+
+    /// Runs a fake SMTP server without STARTTLS support, which accepts a single connection and
+    /// returns the received commands and message data.
+    fn fake_smtp_server() -> (String, thread::JoinHandle<(Vec<String>, String)>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let smtp_server = listener.local_addr().unwrap().to_string();
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut writer = stream;
+            let mut commands = Vec::new();
+            let mut data = String::new();
+            writer.write_all(b"220 localhost ESMTP\r\n").unwrap();
+            loop {
+                let mut line = String::new();
+                // The client may close the connection without sending QUIT.
+                if reader.read_line(&mut line).unwrap() == 0 {
+                    return (commands, data);
+                }
+                let line = line.trim_end().to_string();
+                let reply = match line.as_str() {
+                    "EHLO backup-pod" => "250-localhost\r\n250 AUTH PLAIN\r\n",
+                    "DATA" => {
+                        writer.write_all(b"354 Go ahead\r\n").unwrap();
+                        loop {
+                            let mut line = String::new();
+                            reader.read_line(&mut line).unwrap();
+                            if line == ".\r\n" {
+                                break;
+                            }
+                            data.push_str(&line);
+                        }
+                        "250 Queued\r\n"
+                    }
+                    "QUIT" => "221 Bye\r\n",
+                    line if line.starts_with("AUTH PLAIN ") => "235 Authenticated\r\n",
+                    _ => "250 OK\r\n",
+                };
+                writer.write_all(reply.as_bytes()).unwrap();
+                if line == "QUIT" {
+                    return (commands, data);
+                }
+                commands.push(line);
+            }
+        });
+        (smtp_server, server)
+    }
+
+    #[test]
+    fn email_sink_sends_alert_test() {
+        let (smtp_server, server) = fake_smtp_server();
+        let sink = EmailSink {
+            smtp_server,
+            from: "backup@example.com".into(),
+            to: vec!["oncall@example.com".into(), "ops@example.com".into()],
+            credentials: None,
+        };
+        let mut alert = fake_alert("No height progress after the last replay detected!");
+        alert.suppressed = 2;
+        sink.send(&alert).unwrap();
+
+        let (commands, data) = server.join().unwrap();
+        assert_eq!(
+            commands,
+            vec![
+                "EHLO backup-pod".to_string(),
+                "MAIL FROM:<backup@example.com>".to_string(),
+                "RCPT TO:<oncall@example.com>".to_string(),
+                "RCPT TO:<ops@example.com>".to_string(),
+                "DATA".to_string(),
+            ]
+        );
+        assert!(
+            data.contains("Subject: [fake_backup_instance] Failure on subnet gpvux: Replay\r\n")
+        );
+        assert!(data.contains(
+            "No height progress after the last replay detected! \
+            (suppressed 2 identical alerts)\r\n"
+        ));
+        assert!(data.contains("Height: 100\r\n"));
+    }
+
+    #[test]
+    fn email_sink_refuses_auth_without_tls_test() {
+        let (smtp_server, server) = fake_smtp_server();
+        let sink = EmailSink {
+            smtp_server,
+            from: "backup@example.com".into(),
+            to: vec!["oncall@example.com".into()],
+            credentials: Some(("user".into(), "password".into())),
+        };
+        let err = sink.send(&fake_alert("Disk full")).unwrap_err();
+        assert!(err.contains("does not support STARTTLS"), "{}", err);
+
+        let (commands, data) = server.join().unwrap();
+        assert_eq!(commands, vec!["EHLO backup-pod".to_string()]);
+        assert!(data.is_empty());
+    }
+
+    #[test]
+    fn email_body_is_dot_stuffed_test() {
+        let body = EmailSink::body(&fake_alert(".first\n.second\r.third\r\nlast"));
+        assert!(body.starts_with("..first\r\n..second\r\n..third\r\nlast"));
+        assert!(!body.replace("\r\n", "").contains(['\r', '\n']));
+    }
+}
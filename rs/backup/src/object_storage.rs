@@ -0,0 +1,961 @@
+//! Cold storage in an S3-compatible object store.
+//!
+//! Files are stored as content-addressed blobs under `<prefix><subnet_id>/blobs/<sha256>`, so a
+//! file that did not change between two states or two replica versions is uploaded only once.
+//! Every stored directory is described by a manifest mapping the relative paths of its files to
+//! their blobs:
+//! - `<prefix><subnet_id>/states/<timestamp>_<height>.json`
+//! - `<prefix><subnet_id>/artifacts/<timestamp>_<height>_<replica_version>.json`
+//!
+//! A manifest is only written once all of its blobs are uploaded, so an interrupted upload is
+//! resumed by storing the same directory again: existing blobs are skipped, and the IDs of
+//! unfinished multipart uploads are kept in a local journal so that only the missing parts of
+//! large files are uploaded.
+//!
+//! A stored directory is restored by downloading the blobs of its manifest to their paths, after
+//! checking their hashes.
+
+use crate::{
+    backup_helper::state_dir_timestamp,
+    config::{ObjectStorageConfig, RetentionPolicy},
+    storage::{is_expired, StorageBackend},
+    util::block_on,
+};
+use chrono::{DateTime, Utc};
+use ic_crypto_sha2::Sha256;
+use ic_types::SubnetId;
+use reqwest::{Client, Method, StatusCode};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use slog::{debug, info, warn, Logger};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs::{create_dir_all, read_dir, read_to_string, remove_file, write, File},
+    io::{Read, Write},
+    os::unix::fs::FileExt,
+    path::{Component, Path, PathBuf},
+    sync::Mutex,
+};
+use url::Url;
+
+#[cfg(test)]
+mod tests;
+
+/// Files larger than this are uploaded in parts of this size.
+const DEFAULT_PART_SIZE: u64 = 64 * 1024 * 1024;
+/// The maximal number of parts of a multipart upload.
+const MAX_PARTS: u64 = 10_000;
+const EMPTY_PAYLOAD_SHA256: &str =
+    "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+#[derive(Clone, Eq, PartialEq, Debug, Deserialize, Serialize)]
+struct Manifest {
+    /// The archiving time in seconds since the Unix epoch.
+    timestamp: i64,
+    /// The blobs of the files by their paths relative to the stored directory.
+    files: BTreeMap<String, BlobRef>,
+    empty_dirs: Vec<String>,
+}
+
+#[derive(Clone, Eq, PartialEq, Debug, Deserialize, Serialize)]
+struct BlobRef {
+    sha256: String,
+    size: u64,
+}
+
+/// The response to a `ListObjectsV2` request.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ListBucketResult {
+    #[serde(default)]
+    contents: Vec<ObjectSummary>,
+    is_truncated: bool,
+    next_continuation_token: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ObjectSummary {
+    key: String,
+}
+
+/// The response to a `CreateMultipartUpload` request.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct InitiateMultipartUploadResult {
+    upload_id: String,
+}
+
+/// The response to a `ListParts` request.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ListPartsResult {
+    #[serde(default, rename = "Part")]
+    parts: Vec<PartSummary>,
+    is_truncated: bool,
+    next_part_number_marker: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct PartSummary {
+    part_number: u32,
+    #[serde(rename = "ETag")]
+    etag: String,
+}
+
+/// The response to a `CompleteMultipartUpload` request, which is an error document if the
+/// request failed, even with a successful status.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct CompleteMultipartUploadResponse {
+    code: Option<String>,
+    message: Option<String>,
+}
+
+pub(crate) struct ObjectStorage {
+    client: Client,
+    endpoint: Url,
+    bucket: String,
+    region: String,
+    access_key_id: String,
+    secret_access_key: String,
+    /// The prefix of all the keys of the subnet, ending with a slash.
+    prefix: String,
+    /// The directory keeping the IDs of the unfinished multipart uploads.
+    journal_dir: PathBuf,
+    part_size: u64,
+    /// Garbage-collecting blobs while a directory is being stored would delete the blobs which
+    /// are uploaded but not yet referenced by a manifest.
+    guard: Mutex<()>,
+    log: Logger,
+}
+
+impl ObjectStorage {
+    pub(crate) fn new(
+        config: &ObjectStorageConfig,
+        subnet_id: SubnetId,
+        journal_dir: PathBuf,
+        log: Logger,
+    ) -> Result<Self, String> {
+        let secret_access_key = read_to_string(&config.secret_access_key_file)
+            .map_err(|err| {
+                format!(
+                    "Error reading object storage credentials from {:?}: {:?}",
+                    config.secret_access_key_file, err
+                )
+            })?
+            .trim()
+            .to_string();
+        Ok(Self {
+            client: Client::new(),
+            endpoint: config.endpoint.clone(),
+            bucket: config.bucket.clone(),
+            region: config.region.clone(),
+            access_key_id: config.access_key_id.clone(),
+            secret_access_key,
+            prefix: format!("{}{}/", config.prefix, subnet_id),
+            journal_dir,
+            part_size: DEFAULT_PART_SIZE,
+            guard: Mutex::new(()),
+            log,
+        })
+    }
+
+    fn blob_key(&self, sha256: &str) -> String {
+        format!("{}blobs/{}", self.prefix, sha256)
+    }
+
+    async fn store_dir(
+        &self,
+        dir: &Path,
+        manifest_key: &str,
+        timestamp: i64,
+    ) -> Result<(), String> {
+        let mut files = Vec::new();
+        let mut empty_dirs = Vec::new();
+        collect_files(dir, dir, &mut files, &mut empty_dirs)?;
+        let mut manifest = Manifest {
+            timestamp,
+            files: BTreeMap::new(),
+            empty_dirs,
+        };
+        let mut uploaded = 0;
+        for (relative_path, path) in files {
+            let (sha256, size) = hash_file(&path)?;
+            if self.upload_blob(&path, &sha256, size).await? {
+                uploaded += 1;
+            }
+            manifest
+                .files
+                .insert(relative_path, BlobRef { sha256, size });
+        }
+        let manifest_bytes = serde_json::to_vec_pretty(&manifest)
+            .map_err(|err| format!("Error serializing manifest: {:?}", err))?;
+        self.put_object(manifest_key, manifest_bytes).await?;
+        info!(
+            self.log,
+            "Stored {:?} as {}: uploaded {} of {} files",
+            dir,
+            manifest_key,
+            uploaded,
+            manifest.files.len()
+        );
+        Ok(())
+    }
+
+    /// Rebuilds the directory described by the manifest `manifest_key` in `dir`, which must not
+    /// exist or be empty.
+    async fn restore_dir(&self, manifest_key: &str, dir: &Path) -> Result<(), String> {
+        let is_empty = read_dir(dir).map_or(true, |mut entries| entries.next().is_none());
+        if !is_empty {
+            return Err(format!("Target directory {:?} is not empty", dir));
+        }
+        let manifest = self.get_manifest(manifest_key).await?;
+        for empty_dir in &manifest.empty_dirs {
+            let path = restored_path(dir, empty_dir)?;
+            create_dir_all(&path).map_err(|err| format!("Error creating {:?}: {:?}", path, err))?;
+        }
+        for (relative_path, blob) in &manifest.files {
+            let path = restored_path(dir, relative_path)?;
+            if let Some(parent) = path.parent() {
+                create_dir_all(parent)
+                    .map_err(|err| format!("Error creating {:?}: {:?}", parent, err))?;
+            }
+            self.download_blob(blob, &path).await?;
+        }
+        info!(
+            self.log,
+            "Restored {} to {:?}: downloaded {} files",
+            manifest_key,
+            dir,
+            manifest.files.len()
+        );
+        Ok(())
+    }
+
+    /// Downloads the blob to the file at `path` and checks its hash and size.
+    async fn download_blob(&self, blob: &BlobRef, path: &Path) -> Result<(), String> {
+        let key = self.blob_key(&blob.sha256);
+        let mut response = self.send(Method::GET, &key, &[], vec![]).await?;
+        if !response.status().is_success() {
+            return Err(format!(
+                "GET request for {} failed with {}",
+                key,
+                response.status()
+            ));
+        }
+        let mut file =
+            File::create(path).map_err(|err| format!("Error creating {:?}: {:?}", path, err))?;
+        let mut hasher = Sha256::new();
+        let mut size = 0;
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .map_err(|err| format!("Error reading response for {}: {}", key, err))?
+        {
+            hasher.write(&chunk);
+            size += chunk.len() as u64;
+            file.write_all(&chunk)
+                .map_err(|err| format!("Error writing {:?}: {:?}", path, err))?;
+        }
+        if size != blob.size || hex::encode(hasher.finish()) != blob.sha256 {
+            return Err(format!(
+                "Blob {} downloaded to {:?} does not match its hash or size",
+                blob.sha256, path
+            ));
+        }
+        Ok(())
+    }
+
+    /// Uploads the file at `path` unless a blob with the same content exists already.
+    /// Returns true if the file was uploaded.
+    async fn upload_blob(&self, path: &Path, sha256: &str, size: u64) -> Result<bool, String> {
+        let key = self.blob_key(sha256);
+        if self.head_object(&key).await? {
+            debug!(self.log, "Blob of {:?} exists already", path);
+            return Ok(false);
+        }
+        if size <= self.part_size {
+            let bytes = std::fs::read(path)
+                .map_err(|err| format!("Error reading {:?}: {:?}", path, err))?;
+            self.put_object(&key, bytes).await?;
+        } else {
+            self.multipart_upload(path, &key, sha256, size).await?;
+        }
+        Ok(true)
+    }
+
+    async fn multipart_upload(
+        &self,
+        path: &Path,
+        key: &str,
+        sha256: &str,
+        size: u64,
+    ) -> Result<(), String> {
+        let journal = self.journal_dir.join(sha256);
+        let resumed = match read_to_string(&journal) {
+            Ok(upload_id) => {
+                let upload_id = upload_id.trim().to_string();
+                let parts = self.list_parts(key, &upload_id).await?;
+                parts.map(|parts| (upload_id, parts))
+            }
+            Err(_) => None,
+        };
+        let (upload_id, mut parts) = match resumed {
+            Some((upload_id, parts)) => {
+                info!(
+                    self.log,
+                    "Resuming the upload of {:?} with {} parts uploaded",
+                    path,
+                    parts.len()
+                );
+                (upload_id, parts)
+            }
+            None => {
+                let upload_id = self.create_multipart_upload(key).await?;
+                create_dir_all(&self.journal_dir)
+                    .and_then(|_| write(&journal, &upload_id))
+                    .map_err(|err| format!("Error writing upload journal: {:?}", err))?;
+                (upload_id, BTreeMap::new())
+            }
+        };
+
+        // The part size only depends on the file size, so that resumed uploads use the same one.
+        let part_size = self.part_size.max(size.div_ceil(MAX_PARTS));
+        let file =
+            File::open(path).map_err(|err| format!("Error opening {:?}: {:?}", path, err))?;
+        for part_number in 1..=size.div_ceil(part_size) as u32 {
+            if parts.contains_key(&part_number) {
+                continue;
+            }
+            let offset = (part_number as u64 - 1) * part_size;
+            let mut buf = vec![0; part_size.min(size - offset) as usize];
+            file.read_exact_at(&mut buf, offset)
+                .map_err(|err| format!("Error reading {:?}: {:?}", path, err))?;
+            let etag = self.upload_part(key, &upload_id, part_number, buf).await?;
+            parts.insert(part_number, etag);
+        }
+        self.complete_multipart_upload(key, &upload_id, &parts)
+            .await?;
+        let _ = remove_file(journal);
+        Ok(())
+    }
+
+    /// Returns the keys of the state manifests by height.
+    async fn list_states(&self) -> Result<BTreeMap<u64, (i64, String)>, String> {
+        let prefix = format!("{}states/", self.prefix);
+        let mut states = BTreeMap::new();
+        for key in self.list_objects(&prefix).await? {
+            match parse_state_key(&key[prefix.len()..]) {
+                Some((timestamp, height)) => {
+                    states.insert(height, (timestamp, key));
+                }
+                None => warn!(self.log, "Unexpected object in the cold storage: {}", key),
+            }
+        }
+        Ok(states)
+    }
+
+    async fn get_manifest(&self, key: &str) -> Result<Manifest, String> {
+        let bytes = self.get_object(key).await?;
+        serde_json::from_slice(&bytes)
+            .map_err(|err| format!("Error parsing manifest {}: {:?}", key, err))
+    }
+
+    async fn fetch_newest_state_timestamp(&self) -> Result<DateTime<Utc>, String> {
+        let states = self.list_states().await?;
+        let (_, key) = states
+            .values()
+            .next_back()
+            .ok_or_else(|| "No state in the cold storage".to_string())?;
+        let manifest = self.get_manifest(key).await?;
+        DateTime::from_timestamp(manifest.timestamp, 0)
+            .ok_or_else(|| format!("Invalid timestamp in manifest {}", key))
+    }
+
+    /// Restores the state archived at `height` to `dir`.
+    pub(crate) fn restore_state(&self, height: u64, dir: &Path) -> Result<(), String> {
+        block_on(async {
+            let states = self.list_states().await?;
+            let (_, key) = states
+                .get(&height)
+                .ok_or_else(|| format!("No state at height {} in the cold storage", height))?;
+            self.restore_dir(key, dir).await
+        })
+    }
+
+    /// Restores the artifacts of `replica_version` up to `top_height` to `dir`.
+    pub(crate) fn restore_artifacts(
+        &self,
+        replica_version: &str,
+        top_height: u64,
+        dir: &Path,
+    ) -> Result<(), String> {
+        block_on(async {
+            let prefix = format!("{}artifacts/", self.prefix);
+            let suffix = format!("_{:012}_{}.json", top_height, replica_version);
+            let key = self
+                .list_objects(&prefix)
+                .await?
+                .into_iter()
+                .filter(|key| key.ends_with(&suffix))
+                .last()
+                .ok_or_else(|| {
+                    format!(
+                        "No artifacts of {} up to height {} in the cold storage",
+                        replica_version, top_height
+                    )
+                })?;
+            self.restore_dir(&key, dir).await
+        })
+    }
+
+    async fn delete_expired(
+        &self,
+        policy: &RetentionPolicy,
+        now: DateTime<Utc>,
+    ) -> Result<(), String> {
+        let mut expired = Vec::new();
+        let mut retained = Vec::new();
+
+        let mut states = self.list_states().await?;
+        // The newest state is always retained.
+        if let Some((_, (_, key))) = states.pop_last() {
+            retained.push(key);
+        }
+        for (timestamp, key) in states.into_values() {
+            match DateTime::from_timestamp(timestamp, 0) {
+                Some(timestamp) if is_expired(timestamp, policy.states_days, now) => {
+                    expired.push(key)
+                }
+                _ => retained.push(key),
+            }
+        }
+
+        let prefix = format!("{}artifacts/", self.prefix);
+        for key in self.list_objects(&prefix).await? {
+            let timestamp = key[prefix.len()..]
+                .split('_')
+                .next()
+                .and_then(|timestamp| timestamp.parse::<i64>().ok())
+                .and_then(|timestamp| DateTime::from_timestamp(timestamp, 0));
+            match timestamp {
+                Some(timestamp) if is_expired(timestamp, policy.artifacts_days, now) => {
+                    expired.push(key)
+                }
+                Some(_) => retained.push(key),
+                None => warn!(self.log, "Unexpected object in the cold storage: {}", key),
+            }
+        }
+
+        if expired.is_empty() {
+            return Ok(());
+        }
+
+        let mut referenced = BTreeSet::new();
+        for key in &retained {
+            let manifest = self.get_manifest(key).await?;
+            referenced.extend(manifest.files.into_values().map(|blob| blob.sha256));
+        }
+        for key in &expired {
+            info!(self.log, "Deleting expired manifest {}", key);
+            self.delete_object(key).await?;
+        }
+        let prefix = format!("{}blobs/", self.prefix);
+        let mut deleted_blobs = 0;
+        for key in self.list_objects(&prefix).await? {
+            if !referenced.contains(&key[prefix.len()..]) {
+                self.delete_object(&key).await?;
+                deleted_blobs += 1;
+            }
+        }
+        info!(
+            self.log,
+            "Deleted {} expired manifests and {} unreferenced blobs",
+            expired.len(),
+            deleted_blobs
+        );
+        Ok(())
+    }
+
+    /// Sends a request signed with AWS Signature Version 4.
+    async fn send(
+        &self,
+        method: Method,
+        key: &str,
+        query: &[(&str, &str)],
+        body: Vec<u8>,
+    ) -> Result<reqwest::Response, String> {
+        let host = match self.endpoint.port() {
+            Some(port) => format!("{}:{}", self.endpoint.host_str().unwrap_or_default(), port),
+            None => self.endpoint.host_str().unwrap_or_default().to_string(),
+        };
+        let path = if key.is_empty() {
+            format!("/{}", uri_encode(&self.bucket, true))
+        } else {
+            format!(
+                "/{}/{}",
+                uri_encode(&self.bucket, true),
+                uri_encode(key, false)
+            )
+        };
+        let query = canonical_query(query);
+        let payload_sha256 = if body.is_empty() {
+            EMPTY_PAYLOAD_SHA256.to_string()
+        } else {
+            hex::encode(Sha256::hash(&body))
+        };
+        let now = Utc::now();
+        let authorization = sign(
+            &SigningKey {
+                access_key_id: &self.access_key_id,
+                secret_access_key: &self.secret_access_key,
+                region: &self.region,
+            },
+            &CanonicalRequest {
+                method: method.as_str(),
+                host: &host,
+                path: &path,
+                query: &query,
+                payload_sha256: &payload_sha256,
+            },
+            now,
+        );
+
+        let mut url = format!("{}://{}{}", self.endpoint.scheme(), host, path);
+        if !query.is_empty() {
+            url = format!("{}?{}", url, query);
+        }
+        self.client
+            .request(method.clone(), url)
+            .header("x-amz-content-sha256", payload_sha256)
+            .header("x-amz-date", amz_date(now))
+            .header(reqwest::header::AUTHORIZATION, authorization)
+            .body(body)
+            .send()
+            .await
+            .map_err(|err| format!("Error sending {} request for {}: {}", method, path, err))
+    }
+
+    /// Sends a request signed with AWS Signature Version 4 and returns the response.
+    async fn request(
+        &self,
+        method: Method,
+        key: &str,
+        query: &[(&str, &str)],
+        body: Vec<u8>,
+    ) -> Result<(StatusCode, reqwest::header::HeaderMap, Vec<u8>), String> {
+        let response = self.send(method, key, query, body).await?;
+        let status = response.status();
+        let headers = response.headers().clone();
+        let body = response
+            .bytes()
+            .await
+            .map_err(|err| format!("Error reading response for {}: {}", key, err))?
+            .to_vec();
+        Ok((status, headers, body))
+    }
+
+    async fn request_ok(
+        &self,
+        method: Method,
+        key: &str,
+        query: &[(&str, &str)],
+        body: Vec<u8>,
+    ) -> Result<(reqwest::header::HeaderMap, Vec<u8>), String> {
+        let (status, headers, body) = self.request(method.clone(), key, query, body).await?;
+        if !status.is_success() {
+            return Err(format!(
+                "{} request for {} failed with {}: {}",
+                method,
+                key,
+                status,
+                String::from_utf8_lossy(&body)
+            ));
+        }
+        Ok((headers, body))
+    }
+
+    async fn head_object(&self, key: &str) -> Result<bool, String> {
+        let (status, _, _) = self.request(Method::HEAD, key, &[], vec![]).await?;
+        match status {
+            StatusCode::OK => Ok(true),
+            StatusCode::NOT_FOUND => Ok(false),
+            status => Err(format!("HEAD request for {} failed with {}", key, status)),
+        }
+    }
+
+    async fn get_object(&self, key: &str) -> Result<Vec<u8>, String> {
+        let (_, body) = self.request_ok(Method::GET, key, &[], vec![]).await?;
+        Ok(body)
+    }
+
+    async fn put_object(&self, key: &str, body: Vec<u8>) -> Result<(), String> {
+        self.request_ok(Method::PUT, key, &[], body).await?;
+        Ok(())
+    }
+
+    async fn delete_object(&self, key: &str) -> Result<(), String> {
+        self.request_ok(Method::DELETE, key, &[], vec![]).await?;
+        Ok(())
+    }
+
+    async fn list_objects(&self, prefix: &str) -> Result<Vec<String>, String> {
+        let mut keys = Vec::new();
+        let mut continuation_token = None;
+        loop {
+            let mut query = vec![("list-type", "2"), ("prefix", prefix)];
+            if let Some(token) = &continuation_token {
+                query.push(("continuation-token", token.as_str()));
+            }
+            let (_, body) = self.request_ok(Method::GET, "", &query, vec![]).await?;
+            let result: ListBucketResult = parse_xml(&body)?;
+            keys.extend(result.contents.into_iter().map(|object| object.key));
+            if !result.is_truncated {
+                return Ok(keys);
+            }
+            continuation_token = result.next_continuation_token;
+            if continuation_token.is_none() {
+                return Err("Truncated listing without a continuation token".to_string());
+            }
+        }
+    }
+
+    async fn create_multipart_upload(&self, key: &str) -> Result<String, String> {
+        let (_, body) = self
+            .request_ok(Method::POST, key, &[("uploads", "")], vec![])
+            .await?;
+        let result: InitiateMultipartUploadResult = parse_xml(&body)
+            .map_err(|err| format!("No upload ID in the response for {}: {}", key, err))?;
+        Ok(result.upload_id)
+    }
+
+    /// Returns the ETags of the uploaded parts by part number, or `None` if the upload does not
+    /// exist anymore.
+    async fn list_parts(
+        &self,
+        key: &str,
+        upload_id: &str,
+    ) -> Result<Option<BTreeMap<u32, String>>, String> {
+        let mut parts = BTreeMap::new();
+        let mut marker = String::new();
+        loop {
+            let mut query = vec![("uploadId", upload_id)];
+            if !marker.is_empty() {
+                query.push(("part-number-marker", marker.as_str()));
+            }
+            let (status, _, body) = self.request(Method::GET, key, &query, vec![]).await?;
+            if status == StatusCode::NOT_FOUND {
+                return Ok(None);
+            }
+            if !status.is_success() {
+                return Err(format!(
+                    "Listing parts of {} failed with {}: {}",
+                    key,
+                    status,
+                    String::from_utf8_lossy(&body)
+                ));
+            }
+            let result: ListPartsResult = parse_xml(&body)?;
+            parts.extend(
+                result
+                    .parts
+                    .into_iter()
+                    .map(|part| (part.part_number, part.etag)),
+            );
+            if !result.is_truncated {
+                return Ok(Some(parts));
+            }
+            marker = result
+                .next_part_number_marker
+                .ok_or_else(|| "Truncated listing without a part number marker".to_string())?;
+        }
+    }
+
+    async fn upload_part(
+        &self,
+        key: &str,
+        upload_id: &str,
+        part_number: u32,
+        body: Vec<u8>,
+    ) -> Result<String, String> {
+        let part_number = part_number.to_string();
+        let (headers, _) = self
+            .request_ok(
+                Method::PUT,
+                key,
+                &[("partNumber", &part_number), ("uploadId", upload_id)],
+                body,
+            )
+            .await?;
+        headers
+            .get(reqwest::header::ETAG)
+            .and_then(|etag| etag.to_str().ok())
+            .map(String::from)
+            .ok_or_else(|| format!("No ETag for part {} of {}", part_number, key))
+    }
+
+    async fn complete_multipart_upload(
+        &self,
+        key: &str,
+        upload_id: &str,
+        parts: &BTreeMap<u32, String>,
+    ) -> Result<(), String> {
+        let mut body = String::from("<CompleteMultipartUpload>");
+        for (part_number, etag) in parts {
+            body.push_str(&format!(
+                "<Part><PartNumber>{}</PartNumber><ETag>{}</ETag></Part>",
+                part_number,
+                quick_xml::escape::escape(etag)
+            ));
+        }
+        body.push_str("</CompleteMultipartUpload>");
+        let (_, response) = self
+            .request_ok(Method::POST, key, &[("uploadId", upload_id)], body.into())
+            .await?;
+        // The request may fail after the response status has been sent.
+        let response: CompleteMultipartUploadResponse = parse_xml(&response)?;
+        if let Some(code) = response.code {
+            return Err(format!(
+                "Completing the upload of {} failed with {}: {}",
+                key,
+                code,
+                response.message.unwrap_or_default()
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl StorageBackend for ObjectStorage {
+    fn store_artifacts(
+        &self,
+        dir: &Path,
+        replica_version: &str,
+        top_height: u64,
+    ) -> Result<(), String> {
+        let _guard = self.guard.lock().expect("object storage mutex lock failed");
+        let timestamp = Utc::now().timestamp();
+        let key = format!(
+            "{}artifacts/{:010}_{:012}_{}.json",
+            self.prefix, timestamp, top_height, replica_version
+        );
+        block_on(self.store_dir(dir, &key, timestamp))
+    }
+
+    fn store_state(&self, dir: &Path, height: u64) -> Result<(), String> {
+        let _guard = self.guard.lock().expect("object storage mutex lock failed");
+        let timestamp = state_dir_timestamp(dir)
+            .unwrap_or_else(|err| {
+                warn!(
+                    self.log,
+                    "Failed to read the timestamp of the state {:?}: {:?}", dir, err
+                );
+                Utc::now()
+            })
+            .timestamp();
+        let key = format!(
+            "{}states/{:010}_{:012}.json",
+            self.prefix, timestamp, height
+        );
+        block_on(self.store_dir(dir, &key, timestamp))
+    }
+
+    fn newest_state_timestamp(&self) -> anyhow::Result<DateTime<Utc>> {
+        block_on(self.fetch_newest_state_timestamp()).map_err(anyhow::Error::msg)
+    }
+
+    fn apply_retention(&self, policy: &RetentionPolicy, now: DateTime<Utc>) -> Result<(), String> {
+        let _guard = self.guard.lock().expect("object storage mutex lock failed");
+        block_on(self.delete_expired(policy, now))
+    }
+
+    fn local_dir(&self) -> Option<&Path> {
+        None
+    }
+}
+
+/// Parses the name `<timestamp>_<height>.json` of a state manifest.
+fn parse_state_key(name: &str) -> Option<(i64, u64)> {
+    let (timestamp, height) = name.strip_suffix(".json")?.split_once('_')?;
+    Some((timestamp.parse().ok()?, height.parse().ok()?))
+}
+
+/// Collects the regular files and the empty directories below `dir` with their paths relative to
+/// `root`.
+fn collect_files(
+    root: &Path,
+    dir: &Path,
+    files: &mut Vec<(String, PathBuf)>,
+    empty_dirs: &mut Vec<String>,
+) -> Result<(), String> {
+    let relative_path = |path: &Path| {
+        path.strip_prefix(root)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .to_string()
+    };
+    let entries = read_dir(dir)
+        .map_err(|err| format!("Error reading directory {:?}: {:?}", dir, err))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| format!("Error reading directory {:?}: {:?}", dir, err))?;
+    if entries.is_empty() && dir != root {
+        empty_dirs.push(relative_path(dir));
+    }
+    for entry in entries {
+        let path = entry.path();
+        let file_type = entry
+            .file_type()
+            .map_err(|err| format!("Error reading file type of {:?}: {:?}", path, err))?;
+        if file_type.is_dir() {
+            collect_files(root, &path, files, empty_dirs)?;
+        } else if file_type.is_file() {
+            files.push((relative_path(&path), path));
+        }
+    }
+    Ok(())
+}
+
+/// Returns the path of the manifest entry `relative_path` below `dir`, rejecting the paths which
+/// would leave `dir`.
+fn restored_path(dir: &Path, relative_path: &str) -> Result<PathBuf, String> {
+    let path = Path::new(relative_path);
+    if path.as_os_str().is_empty()
+        || !path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+    {
+        return Err(format!("Invalid path {:?} in the manifest", relative_path));
+    }
+    Ok(dir.join(path))
+}
+
+/// Returns the hex-encoded SHA-256 hash and the size of the file at `path`.
+fn hash_file(path: &Path) -> Result<(String, u64), String> {
+    let mut file =
+        File::open(path).map_err(|err| format!("Error opening {:?}: {:?}", path, err))?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0; 1024 * 1024];
+    let mut size = 0;
+    loop {
+        let n = file
+            .read(&mut buf)
+            .map_err(|err| format!("Error reading {:?}: {:?}", path, err))?;
+        if n == 0 {
+            return Ok((hex::encode(hasher.finish()), size));
+        }
+        hasher.write(&buf[..n]);
+        size += n as u64;
+    }
+}
+
+/// Parses an XML response of the object store.
+fn parse_xml<T: DeserializeOwned>(body: &[u8]) -> Result<T, String> {
+    quick_xml::de::from_str(&String::from_utf8_lossy(body)).map_err(|err| {
+        format!(
+            "Error parsing response {}: {}",
+            String::from_utf8_lossy(body),
+            err
+        )
+    })
+}
+
+/// Percent-encodes everything but the unreserved characters, and the slashes unless
+/// `encode_slash` is set.
+fn uri_encode(s: &str, encode_slash: bool) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            b'/' if !encode_slash => "/".to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// Returns the URI-encoded query with its parameters sorted, as required for signing.
+fn canonical_query(query: &[(&str, &str)]) -> String {
+    let mut query: Vec<_> = query
+        .iter()
+        .map(|(name, value)| format!("{}={}", uri_encode(name, true), uri_encode(value, true)))
+        .collect();
+    query.sort();
+    query.join("&")
+}
+
+fn amz_date(now: DateTime<Utc>) -> String {
+    now.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+struct SigningKey<'a> {
+    access_key_id: &'a str,
+    secret_access_key: &'a str,
+    region: &'a str,
+}
+
+/// A request whose path and query are already URI-encoded.
+struct CanonicalRequest<'a> {
+    method: &'a str,
+    host: &'a str,
+    path: &'a str,
+    query: &'a str,
+    payload_sha256: &'a str,
+}
+
+/// Returns the `Authorization` header of the request, signed with AWS Signature Version 4.
+/// The request must also contain the headers `x-amz-date` and `x-amz-content-sha256`.
+fn sign(key: &SigningKey, request: &CanonicalRequest, now: DateTime<Utc>) -> String {
+    const SIGNED_HEADERS: &str = "host;x-amz-content-sha256;x-amz-date";
+    let amz_date = amz_date(now);
+    let date = now.format("%Y%m%d").to_string();
+    let scope = format!("{}/{}/s3/aws4_request", date, key.region);
+    let canonical_request = format!(
+        "{}\n{}\n{}\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+        request.method,
+        request.path,
+        request.query,
+        request.host,
+        request.payload_sha256,
+        amz_date,
+        SIGNED_HEADERS,
+        request.payload_sha256
+    );
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        scope,
+        hex::encode(Sha256::hash(canonical_request.as_bytes()))
+    );
+    let signing_key = [date.as_str(), key.region, "s3", "aws4_request"]
+        .iter()
+        .fold(
+            format!("AWS4{}", key.secret_access_key).into_bytes(),
+            |signing_key, data| hmac_sha256(&signing_key, data.as_bytes()).to_vec(),
+        );
+    format!(
+        "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+        key.access_key_id,
+        scope,
+        SIGNED_HEADERS,
+        hex::encode(hmac_sha256(&signing_key, string_to_sign.as_bytes()))
+    )
+}
+
+/// HMAC-SHA256 as specified in RFC 2104.
+fn hmac_sha256(key: &[u8], data: &[u8]) -> [u8; 32] {
+    const BLOCK_SIZE: usize = 64;
+    let mut block = [0u8; BLOCK_SIZE];
+    if key.len() > BLOCK_SIZE {
+        block[..32].copy_from_slice(&Sha256::hash(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+    let mut inner = Sha256::new();
+    inner.write(&block.map(|b| b ^ 0x36));
+    inner.write(data);
+    let mut outer = Sha256::new();
+    outer.write(&block.map(|b| b ^ 0x5c));
+    outer.write(&inner.finish());
+    outer.finish()
+}
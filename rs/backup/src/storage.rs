@@ -0,0 +1,185 @@
+use crate::{
+    backup_helper::{last_dir_height, ls_path, state_dir_timestamp},
+    config::RetentionPolicy,
+};
+use chrono::{DateTime, Utc};
+use ic_recovery::command_helper::exec_cmd;
+use ic_types::SubnetId;
+use slog::{debug, info, warn, Logger};
+use std::{
+    fs::{create_dir_all, read_dir, remove_dir_all, remove_file},
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// The cold storage of the backup pod, where old artifacts and states of a subnet are kept
+/// after they are removed from the hot storage.
+pub(crate) trait StorageBackend: Send + Sync {
+    /// Stores the artifacts of `replica_version` in the directory `dir`. The highest height of
+    /// the artifacts is `top_height`.
+    fn store_artifacts(
+        &self,
+        dir: &Path,
+        replica_version: &str,
+        top_height: u64,
+    ) -> Result<(), String>;
+
+    /// Stores the archived state at `height` in the directory `dir`.
+    fn store_state(&self, dir: &Path, height: u64) -> Result<(), String>;
+
+    /// Returns the archiving timestamp of the newest state in the storage.
+    fn newest_state_timestamp(&self) -> anyhow::Result<DateTime<Utc>>;
+
+    /// Deletes the artifacts and states which are older than allowed by `policy`.
+    fn apply_retention(&self, policy: &RetentionPolicy, now: DateTime<Utc>) -> Result<(), String>;
+
+    /// Returns the local directory of the storage, if any.
+    fn local_dir(&self) -> Option<&Path>;
+}
+
+/// Returns true if something archived at `timestamp` should be deleted according to the
+/// retention of `days`.
+pub(crate) fn is_expired(timestamp: DateTime<Utc>, days: Option<u64>, now: DateTime<Utc>) -> bool {
+    days.map_or(false, |days| {
+        now.signed_duration_since(timestamp).num_days() >= days as i64
+    })
+}
+
+/// Cold storage in a local directory, e.g. a mounted network drive.
+///
+/// Artifacts are packed into `<dir>/<subnet_id>/artifacts/<timestamp>_<height>_<version>.tgz`
+/// and states are copied to `<dir>/<subnet_id>/states/<height>`.
+pub(crate) struct LocalStorage {
+    dir: PathBuf,
+    subnet_id: SubnetId,
+    log: Logger,
+}
+
+impl LocalStorage {
+    pub(crate) fn new(dir: PathBuf, subnet_id: SubnetId, log: Logger) -> Self {
+        Self {
+            dir,
+            subnet_id,
+            log,
+        }
+    }
+
+    fn subnet_dir(&self) -> PathBuf {
+        self.dir.join(self.subnet_id.to_string())
+    }
+
+    fn artifacts_dir(&self) -> Result<PathBuf, String> {
+        let dir = self.subnet_dir().join("artifacts");
+        create_dir_all(&dir).map_err(|err| format!("Error creating {:?}: {:?}", dir, err))?;
+        Ok(dir)
+    }
+
+    fn states_dir(&self) -> Result<PathBuf, String> {
+        let dir = self.subnet_dir().join("states");
+        create_dir_all(&dir).map_err(|err| format!("Error creating {:?}: {:?}", dir, err))?;
+        Ok(dir)
+    }
+}
+
+impl StorageBackend for LocalStorage {
+    fn store_artifacts(
+        &self,
+        dir: &Path,
+        replica_version: &str,
+        top_height: u64,
+    ) -> Result<(), String> {
+        let work_dir = dir
+            .parent()
+            .ok_or_else(|| format!("Artifacts directory {:?} has no parent", dir))?;
+        let timestamp = Utc::now().timestamp();
+        let packed_file = work_dir.join(format!(
+            "{:010}_{:012}_{}.tgz",
+            timestamp, top_height, replica_version
+        ));
+        let mut cmd = Command::new("tar");
+        cmd.arg("czvf");
+        cmd.arg(&packed_file);
+        cmd.arg("-C").arg(work_dir);
+        cmd.arg(replica_version);
+        debug!(self.log, "Will execute: {:?}", cmd);
+        exec_cmd(&mut cmd).map_err(|err| format!("Error packing artifacts: {:?}", err))?;
+
+        info!(self.log, "Copy packed file of {}", replica_version);
+        let mut cmd2 = Command::new("cp");
+        cmd2.arg(packed_file).arg(self.artifacts_dir()?);
+        debug!(self.log, "Will execute: {:?}", cmd2);
+        exec_cmd(&mut cmd2).map_err(|err| format!("Error copying artifacts: {:?}", err))?;
+
+        ls_path(&self.log, &self.subnet_dir())
+    }
+
+    fn store_state(&self, dir: &Path, _height: u64) -> Result<(), String> {
+        let mut cmd = Command::new("rsync");
+        cmd.arg("-a");
+        cmd.arg(dir).arg(self.states_dir()?);
+        debug!(self.log, "Will execute: {:?}", cmd);
+        exec_cmd(&mut cmd).map_err(|err| format!("Error copying states: {:?}", err))?;
+        Ok(())
+    }
+
+    fn newest_state_timestamp(&self) -> anyhow::Result<DateTime<Utc>> {
+        let states_dir = self.states_dir().map_err(anyhow::Error::msg)?;
+        let last_height = last_dir_height(&states_dir, 10);
+
+        state_dir_timestamp(&states_dir.join(last_height.to_string()))
+    }
+
+    fn apply_retention(&self, policy: &RetentionPolicy, now: DateTime<Utc>) -> Result<(), String> {
+        let artifacts_dir = self.artifacts_dir()?;
+        let packed_files = read_dir(&artifacts_dir)
+            .map_err(|err| format!("Error reading directory {:?}: {:?}", artifacts_dir, err))?;
+        for packed_file in packed_files.flatten() {
+            let file_name = packed_file.file_name().to_string_lossy().to_string();
+            let timestamp = file_name
+                .split('_')
+                .next()
+                .and_then(|timestamp| timestamp.parse::<i64>().ok())
+                .and_then(|timestamp| DateTime::from_timestamp(timestamp, 0));
+            let Some(timestamp) = timestamp else {
+                warn!(
+                    self.log,
+                    "Unexpected file in the cold storage: {}", file_name
+                );
+                continue;
+            };
+            if is_expired(timestamp, policy.artifacts_days, now) {
+                info!(self.log, "Deleting expired artifacts {}", file_name);
+                remove_file(packed_file.path())
+                    .map_err(|err| format!("Error deleting {}: {:?}", file_name, err))?;
+            }
+        }
+
+        let states_dir = self.states_dir()?;
+        let newest_height = last_dir_height(&states_dir, 10);
+        let states = read_dir(&states_dir)
+            .map_err(|err| format!("Error reading directory {:?}: {:?}", states_dir, err))?;
+        for state in states.flatten() {
+            let name = state.file_name().to_string_lossy().to_string();
+            if name.parse::<u64>().ok() == Some(newest_height) {
+                continue;
+            }
+            match state_dir_timestamp(&state.path()) {
+                Ok(timestamp) if is_expired(timestamp, policy.states_days, now) => {
+                    info!(self.log, "Deleting expired state {}", name);
+                    remove_dir_all(state.path())
+                        .map_err(|err| format!("Error deleting state {}: {:?}", name, err))?;
+                }
+                Ok(_) => {}
+                Err(err) => warn!(
+                    self.log,
+                    "Failed to read the timestamp of the state {}: {:?}", name, err
+                ),
+            }
+        }
+        Ok(())
+    }
+
+    fn local_dir(&self) -> Option<&Path> {
+        Some(&self.dir)
+    }
+}
@@ -164,7 +164,8 @@ mod tests {
         messages::{CallContextId, RequestMetadata},
         methods::{FuncRef, WasmMethod},
         time::Time,
-        CanisterTimer, ComputeAllocation, Cycles, MemoryAllocation, NumBytes, NumInstructions,
+        CanisterTimer, ComputeAllocation, Cycles, EnvironmentVariables, MemoryAllocation, NumBytes,
+        NumInstructions, DEFAULT_CANISTER_LOG_BUFFER_SIZE,
     };
     use mockall::*;
     use std::collections::{BTreeMap, BTreeSet};
@@ -221,6 +222,8 @@ mod tests {
             0,
             ic00_aliases,
             SMALL_APP_SUBNET_MAX_SIZE,
+            BTreeMap::new(),
+            false,
             SchedulerConfig::application_subnet().dirty_page_overhead,
             CanisterTimer::Inactive,
             0,
@@ -228,6 +231,8 @@ mod tests {
             RequestMetadata::new(0, Time::from_nanos_since_unix_epoch(0)),
             caller,
             0,
+            NumBytes::new(DEFAULT_CANISTER_LOG_BUFFER_SIZE as u64),
+            EnvironmentVariables::default(),
         )
     }
 
@@ -650,6 +650,7 @@ mod tests {
                 subnet_type: SubnetType::Application,
                 subnet_features: SubnetFeatures::default(),
                 idkg_keys_held: BTreeSet::new(),
+                replica_version: String::new(),
             },
             subnet_test_id(1) => SubnetTopology {
                 public_key: vec![5, 6, 7, 8],
@@ -657,6 +658,7 @@ mod tests {
                 subnet_type: SubnetType::Application,
                 subnet_features: SubnetFeatures::default(),
                 idkg_keys_held: BTreeSet::new(),
+                replica_version: String::new(),
             }
         };
         fn id_range(from: u64, to: u64) -> CanisterIdRange {
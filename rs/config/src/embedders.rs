@@ -1,3 +1,4 @@
+use std::path::PathBuf;
 use std::time::Duration;
 
 use ic_base_types::NumBytes;
@@ -238,6 +239,18 @@ pub struct Config {
 
     /// The maximum size of the stable memory.
     pub max_stable_memory_size: NumBytes,
+
+    /// If this flag is enabled, then every basic block of canister code is
+    /// instrumented with an execution counter that can be turned into a code
+    /// coverage report. Only meant for testing environments.
+    pub coverage_instrumentation: FlagStatus,
+
+    /// If this is set, then every function of canister code is instrumented
+    /// to attribute the instructions it executes to its call stack, and the
+    /// profile of the last execution of each canister is written to this
+    /// directory in the folded stack format of flamegraph tools. Only meant
+    /// for testing environments.
+    pub instruction_profiling_dir: Option<PathBuf>,
 }
 
 impl Config {
@@ -276,6 +289,8 @@ impl Config {
             wasm_max_size: WASM_MAX_SIZE,
             max_wasm_memory_size: NumBytes::new(MAX_WASM_MEMORY_IN_BYTES),
             max_stable_memory_size: NumBytes::new(MAX_STABLE_MEMORY_IN_BYTES),
+            coverage_instrumentation: FlagStatus::Disabled,
+            instruction_profiling_dir: None,
         }
     }
 }
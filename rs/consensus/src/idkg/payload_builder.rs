@@ -1082,6 +1082,7 @@ mod tests {
                         signature: vec![2; 32],
                     })
                 }
+                MasterPublicKeyId::VetKd(_) => panic!("vetKD keys are not supported by IDKG"),
             },
         );
 
@@ -1924,6 +1925,7 @@ mod tests {
                         &mut rng,
                     ))
                 }
+                MasterPublicKeyId::VetKd(_) => panic!("vetKD keys are not supported by IDKG"),
             };
             payload_0.available_pre_signatures.insert(
                 payload_0.uid_generator.next_pre_signature_id(),
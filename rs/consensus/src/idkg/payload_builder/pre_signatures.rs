@@ -398,6 +398,8 @@ fn make_new_pre_signatures_if_needed_helper(
                     blinder_config,
                 ))
             }
+            // vetKD keys don't use pre-signatures.
+            MasterPublicKeyId::VetKd(_) => break,
         };
         new_pre_signatures.insert(uid_generator.next_pre_signature_id(), pre_signature);
     }
@@ -495,6 +497,7 @@ pub(super) mod test_utils {
                     blinder_config_ref,
                 ))
             }
+            MasterPublicKeyId::VetKd(_) => panic!("vetKD keys are not supported by IDKG"),
         };
         let configs = pre_signature
             .iter_transcript_configs_in_creation()
@@ -709,6 +712,7 @@ pub(super) mod tests {
         let expected_transcript_ids = match key_id {
             MasterPublicKeyId::Ecdsa(_) => 2 * expected_pre_signatures_in_creation,
             MasterPublicKeyId::Schnorr(_) => expected_pre_signatures_in_creation,
+            MasterPublicKeyId::VetKd(_) => panic!("vetKD keys are not supported by IDKG"),
         };
         assert_eq!(transcript_ids.len(), expected_transcript_ids);
         assert_eq!(
@@ -1043,6 +1043,7 @@ mod test {
                     MasterPublicKeyId::Schnorr(_) => {
                         SignWithSchnorrReply { signature: vec![] }.encode()
                     }
+                    MasterPublicKeyId::VetKd(_) => panic!("vetKD keys are not supported by IDKG"),
                 }),
             ));
 
@@ -1076,6 +1077,7 @@ mod test {
                 fake_schnorr_master_public_key_id(SchnorrAlgorithm::Ed25519)
             }
             MasterPublicKeyId::Schnorr(_) => fake_ecdsa_master_public_key_id(),
+            MasterPublicKeyId::VetKd(_) => panic!("vetKD keys are not supported by IDKG"),
         };
         // Add a pre-signature for the "wrong_key_id"
         insert_test_sig_inputs(
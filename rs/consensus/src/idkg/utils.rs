@@ -8,7 +8,7 @@ use ic_interfaces::consensus_pool::ConsensusBlockChain;
 use ic_interfaces::idkg::{IDkgChangeAction, IDkgChangeSet, IDkgPool};
 use ic_interfaces_registry::RegistryClient;
 use ic_logger::{warn, ReplicaLogger};
-use ic_management_canister_types::{EcdsaCurve, MasterPublicKeyId, SchnorrAlgorithm};
+use ic_management_canister_types::{EcdsaCurve, MasterPublicKeyId, SchnorrAlgorithm, VetKdCurve};
 use ic_protobuf::registry::subnet::v1 as pb;
 use ic_registry_client_helpers::subnet::SubnetRegistry;
 use ic_registry_subnet_features::ChainKeyConfig;
@@ -443,6 +443,9 @@ pub(crate) fn algorithm_for_key_id(key_id: &MasterPublicKeyId) -> AlgorithmId {
             SchnorrAlgorithm::Bip340Secp256k1 => AlgorithmId::ThresholdSchnorrBip340,
             SchnorrAlgorithm::Ed25519 => AlgorithmId::ThresholdEd25519,
         },
+        MasterPublicKeyId::VetKd(vetkd_key_id) => match vetkd_key_id.curve {
+            VetKdCurve::Bls12_381_G2 => AlgorithmId::ThresBls12_381,
+        },
     }
 }
 
@@ -451,9 +454,14 @@ pub(crate) fn get_chain_key_config_if_enabled(
     registry_version: RegistryVersion,
     registry_client: &dyn RegistryClient,
 ) -> Result<Option<ChainKeyConfig>, RegistryClientError> {
-    if let Some(chain_key_config) =
+    if let Some(mut chain_key_config) =
         registry_client.get_chain_key_config(subnet_id, registry_version)?
     {
+        // vetKD keys are not generated through IDKG and are ignored here
+        chain_key_config
+            .key_configs
+            .retain(|key_config| key_config.key_id.is_idkg_key());
+
         // A key that has `presignatures_to_create_in_advance` set to 0 is not active
         let num_active_key_ids = chain_key_config
             .key_configs
@@ -860,6 +860,7 @@ mod tests {
             MasterPublicKeyId::Schnorr(key_id) => {
                 PreSignatureRef::Schnorr(fake_schnorr_transcript(id, key_id.clone()))
             }
+            MasterPublicKeyId::VetKd(_) => panic!("vetKD keys don't use pre-signatures"),
         }
     }
 
@@ -880,6 +881,7 @@ mod tests {
                         key_id: key_id.clone(),
                     })
                 }
+                MasterPublicKeyId::VetKd(_) => panic!("vetKD keys don't use pre-signatures"),
             },
             derivation_path: vec![],
             pseudo_random_id: [0; 32],
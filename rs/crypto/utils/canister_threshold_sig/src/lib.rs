@@ -1,8 +1,10 @@
+use ic_crypto_internal_bls12_381_vetkd::{DerivationPath, DerivedPublicKey, G2Affine};
 use ic_crypto_internal_threshold_sig_canister_threshold_sig::DeriveThresholdPublicKeyError;
 use ic_types::crypto::canister_threshold_sig::error::CanisterThresholdGetPublicKeyError;
 use ic_types::crypto::canister_threshold_sig::{
     ExtendedDerivationPath, MasterPublicKey, PublicKey,
 };
+use ic_types::crypto::AlgorithmId;
 
 /// Derives the threshold public key from the specified `master_public_key` for
 /// the given `extended_derivation_path`.
@@ -23,3 +25,35 @@ pub fn derive_threshold_public_key(
         }
     })
 }
+
+/// Derives the vetKD public key from the specified `master_public_key` for
+/// the given `extended_derivation_path`.
+///
+/// The master public key must be a BLS12-381 G2 point.
+pub fn derive_vetkd_public_key(
+    master_public_key: &MasterPublicKey,
+    extended_derivation_path: &ExtendedDerivationPath,
+) -> Result<Vec<u8>, CanisterThresholdGetPublicKeyError> {
+    if master_public_key.algorithm_id != AlgorithmId::ThresBls12_381 {
+        return Err(CanisterThresholdGetPublicKeyError::InvalidArgument(
+            format!(
+                "Unsupported algorithm for vetKD: {:?}",
+                master_public_key.algorithm_id
+            ),
+        ));
+    }
+    let master_public_key = G2Affine::deserialize(&master_public_key.public_key).map_err(|_| {
+        CanisterThresholdGetPublicKeyError::InvalidArgument(
+            "Invalid vetKD master public key".to_string(),
+        )
+    })?;
+    let derivation_path = DerivationPath::new(
+        extended_derivation_path.caller.as_slice(),
+        &extended_derivation_path.derivation_path[..],
+    );
+    Ok(
+        DerivedPublicKey::compute_derived_key(&master_public_key, &derivation_path)
+            .serialize()
+            .to_vec(),
+    )
+}
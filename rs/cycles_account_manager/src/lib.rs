@@ -250,6 +250,16 @@ impl CyclesAccountManager {
         )
     }
 
+    /// Returns the fee for performing a xnet call with a payload of the given
+    /// size in [`Cycles`], i.e. the fixed fee and the fee per transmitted
+    /// byte combined.
+    pub fn xnet_call_total_fee(&self, payload_size: NumBytes, subnet_size: usize) -> Cycles {
+        self.scale_cost(
+            self.config.xnet_call_fee + self.config.xnet_byte_transmission_fee * payload_size.get(),
+            subnet_size,
+        )
+    }
+
     // Returns the total idle resource consumption rate in cycles per day.
     pub fn idle_cycles_burned_rate(
         &self,
@@ -752,11 +762,8 @@ impl CyclesAccountManager {
         //   - the fee to send the request (by size)
         //   - the fee for the largest possible response
         //   - the fee for executing the largest allowed response when it eventually arrives.
-        let transmission_fee = self.scale_cost(
-            self.config.xnet_call_fee
-                + self.config.xnet_byte_transmission_fee * request.payload_size_bytes().get(),
-            subnet_size,
-        ) + prepayment_for_response_transmission;
+        let transmission_fee = self.xnet_call_total_fee(request.payload_size_bytes(), subnet_size)
+            + prepayment_for_response_transmission;
 
         let fee = transmission_fee + prepayment_for_response_execution;
 
@@ -1104,11 +1111,36 @@ impl CyclesAccountManager {
         Ok(())
     }
 
+    /// Returns the fee for an HTTP outcall. The fee depends only on the size
+    /// of the request and the response limit, so it is the same for every
+    /// HTTP method.
     pub fn http_request_fee(
         &self,
         request_size: NumBytes,
         response_size_limit: Option<NumBytes>,
         subnet_size: usize,
+    ) -> Cycles {
+        self.http_request_fee_per_node(request_size, response_size_limit, subnet_size)
+            * (subnet_size as u64)
+    }
+
+    /// Returns the fee for a non-replicated HTTP outcall. Since the request is
+    /// made by a single node only, the fee is the share of a single node in
+    /// the fee of a fully replicated outcall.
+    pub fn non_replicated_http_request_fee(
+        &self,
+        request_size: NumBytes,
+        response_size_limit: Option<NumBytes>,
+        subnet_size: usize,
+    ) -> Cycles {
+        self.http_request_fee_per_node(request_size, response_size_limit, subnet_size)
+    }
+
+    fn http_request_fee_per_node(
+        &self,
+        request_size: NumBytes,
+        response_size_limit: Option<NumBytes>,
+        subnet_size: usize,
     ) -> Cycles {
         let response_size = match response_size_limit {
             Some(response_size) => response_size.get(),
@@ -1116,11 +1148,10 @@ impl CyclesAccountManager {
             None => MAX_CANISTER_HTTP_RESPONSE_BYTES,
         };
 
-        (self.config.http_request_linear_baseline_fee
+        self.config.http_request_linear_baseline_fee
             + self.config.http_request_quadratic_baseline_fee * (subnet_size as u64)
             + self.config.http_request_per_byte_fee * request_size.get()
-            + self.config.http_response_per_byte_fee * response_size)
-            * (subnet_size as u64)
+            + self.config.http_response_per_byte_fee * response_size
     }
 
     /// Returns the default value of the reserved balance limit for the case
@@ -1262,6 +1293,23 @@ mod tests {
         );
     }
 
+    #[test]
+    fn non_replicated_http_requests_fee() {
+        let subnet_size: u64 = 34;
+        let request_size = NumBytes::from(17);
+        let cycles_account_manager = create_cycles_account_manager(13);
+
+        // The fee of a single node in a 34-node subnet.
+        assert_eq!(
+            cycles_account_manager.non_replicated_http_request_fee(
+                request_size,
+                None,
+                subnet_size as usize,
+            ),
+            Cycles::from(1_605_046_800u64)
+        );
+    }
+
     #[test]
     fn test_cycles_burn() {
         let subnet_size = 13;
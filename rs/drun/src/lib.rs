@@ -1,6 +1,8 @@
 //! Standalone interface for testing application canisters.
 
-use crate::message::{msg_stream_from_file, Message};
+use crate::message::{
+    load_snapshot_message, msg_stream_from_file, parse_message, take_snapshot_message, Message,
+};
 use hex::encode;
 use ic_config::{subnet_config::SubnetConfig, Config};
 use ic_crypto_test_utils_ni_dkg::dummy_initial_dkg_transcript_with_master_key;
@@ -12,6 +14,7 @@ use ic_interfaces::{
     execution_environment::{IngressHistoryReader, QueryExecutionError},
     messaging::MessageRouting,
 };
+use ic_management_canister_types::{CanisterSnapshotResponse, Payload};
 use ic_messaging::MessageRoutingImpl;
 use ic_metrics::MetricsRegistry;
 use ic_protobuf::registry::{
@@ -46,10 +49,12 @@ use ic_types::{
 use rand::distributions::{Distribution, Uniform};
 use rand::rngs::StdRng;
 use rand::SeedableRng;
+use serde_json::json;
 use slog::{Drain, Logger};
 use std::collections::BTreeMap;
 use std::fs::OpenOptions;
 use std::path::PathBuf;
+use std::str::FromStr;
 use std::sync::{Arc, Mutex};
 use std::{thread::sleep, time::Duration};
 use tower::util::ServiceExt;
@@ -62,6 +67,30 @@ const MAX_BATCHES_UNTIL_RESPONSE: u64 = 100_000;
 // how long to wait between batches
 const WAIT_PER_BATCH: Duration = Duration::from_millis(5);
 
+/// The format in which drun prints the results of the messages.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OutputFormat {
+    /// One line of human-readable text per message.
+    Text,
+    /// One JSON object per line per message.
+    Json,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            _ => Err(format!(
+                "Unknown output format {}, expected text or json",
+                s
+            )),
+        }
+    }
+}
+
 pub struct DrunOptions {
     pub msg_filename: String,
     pub cfg: Config,
@@ -69,22 +98,31 @@ pub struct DrunOptions {
     pub log_file: Option<PathBuf>,
     pub instruction_limit: Option<u64>,
     pub subnet_type: SubnetType,
+    pub output_format: OutputFormat,
 }
 
-/// Deliver a single message to the Message Routing layer
+/// Deliver a single message to the Message Routing layer and return its
+/// result.
 fn deliver_message(
     msg: SignedIngress,
     message_routing: &dyn MessageRouting,
     ingress_hist_reader: &dyn IngressHistoryReader,
     extra_batches: u64,
-) {
+    time_offset: Duration,
+) -> Result<WasmResult, UserError> {
     let message_id = msg.id();
 
-    let _ = execute_ingress_message(message_routing, msg, &message_id, ingress_hist_reader);
+    let _ = execute_ingress_message(
+        message_routing,
+        msg,
+        &message_id,
+        ingress_hist_reader,
+        time_offset,
+    );
     // print result after waiting, to not interleave the result
     // with debug.print messages from subsequent calls. revise after DFN-1269.
-    wait_extra_batches(message_routing, extra_batches);
-    print_ingress_result(&message_id, ingress_hist_reader);
+    wait_extra_batches(message_routing, extra_batches, time_offset);
+    get_ingress_result(&message_id, ingress_hist_reader)
 }
 
 fn setup_logger(log_file: PathBuf) -> Logger {
@@ -168,6 +206,7 @@ pub async fn run_drun(uo: DrunOptions) -> Result<(), String> {
         log_file,
         instruction_limit,
         subnet_type,
+        output_format,
     } = uo;
     // Hardcoded magic values to create a ReplicaConfig that parses.
     let mut subnet_config = SubnetConfig::new(subnet_type);
@@ -268,15 +307,29 @@ pub async fn run_drun(uo: DrunOptions) -> Result<(), String> {
         MaliciousFlags::default(),
     );
 
-    for parse_result in msg_stream {
-        match parse_result? {
+    // The time by which the batch time is ahead of the current time.
+    let mut time_offset = Duration::ZERO;
+    // The result of the last message for `expect` directives.
+    let mut last_result = None;
+    // The last snapshot taken of each canister.
+    let mut snapshots = BTreeMap::new();
+    let mut failed_expectations = 0;
+    for read_result in msg_stream {
+        let (line, text) = read_result?;
+        // Lines are numbered from 1 but nonces from 0.
+        let message = parse_message(&text, line as u64 - 1, time_offset)
+            .map_err(|e| format!("Line {}: {}", line, e))?;
+        match message {
             Message::Install(msg) => {
-                deliver_message(
+                let result = deliver_message(
                     msg,
                     &message_routing,
                     ingress_hist_reader.as_ref(),
                     extra_batches,
+                    time_offset,
                 );
+                print_ingress_result(output_format, line, "install", &result);
+                last_result = Some(result);
             }
 
             Message::Query(q) => {
@@ -293,28 +346,113 @@ pub async fn run_drun(uo: DrunOptions) -> Result<(), String> {
                         panic!("Certified state unavailable for query call.")
                     }
                 };
-                print_query_result(query_result);
+                print_query_result(output_format, line, &query_result);
+                last_result = Some(query_result);
             }
 
             Message::Ingress(msg) => {
-                deliver_message(
+                let result = deliver_message(
                     msg,
                     &message_routing,
                     ingress_hist_reader.as_ref(),
                     extra_batches,
+                    time_offset,
                 );
+                print_ingress_result(output_format, line, "ingress", &result);
+                last_result = Some(result);
             }
 
             Message::Create(msg) => {
-                deliver_message(
+                let result = deliver_message(
                     msg,
                     &message_routing,
                     ingress_hist_reader.as_ref(),
                     extra_batches,
+                    time_offset,
+                );
+                print_ingress_result(output_format, line, "create", &result);
+                last_result = Some(result);
+            }
+
+            Message::SetControllers(msg) => {
+                let result = deliver_message(
+                    msg,
+                    &message_routing,
+                    ingress_hist_reader.as_ref(),
+                    extra_batches,
+                    time_offset,
+                );
+                print_ingress_result(output_format, line, "set_controllers", &result);
+                last_result = Some(result);
+            }
+
+            Message::TakeSnapshot { canister_id, nonce } => {
+                // Only the last snapshot of each canister is kept so that the
+                // number of snapshots per canister is not exceeded.
+                let msg = take_snapshot_message(
+                    canister_id,
+                    snapshots.get(&canister_id).copied(),
+                    nonce,
+                    time_offset,
                 );
+                let result = deliver_message(
+                    msg,
+                    &message_routing,
+                    ingress_hist_reader.as_ref(),
+                    extra_batches,
+                    time_offset,
+                );
+                if let Ok(WasmResult::Reply(payload)) = &result {
+                    let response = CanisterSnapshotResponse::decode(payload).map_err(|e| {
+                        format!("Line {}: Failed to decode snapshot response: {}", line, e)
+                    })?;
+                    snapshots.insert(canister_id, response.snapshot_id());
+                }
+                print_ingress_result(output_format, line, "snapshot", &result);
+                last_result = Some(result);
+            }
+
+            Message::LoadSnapshot { canister_id, nonce } => {
+                let snapshot_id = *snapshots.get(&canister_id).ok_or_else(|| {
+                    format!(
+                        "Line {}: No snapshot of canister {} has been taken",
+                        line, canister_id
+                    )
+                })?;
+                let result = deliver_message(
+                    load_snapshot_message(canister_id, snapshot_id, nonce, time_offset),
+                    &message_routing,
+                    ingress_hist_reader.as_ref(),
+                    extra_batches,
+                    time_offset,
+                );
+                print_ingress_result(output_format, line, "snapshot", &result);
+                last_result = Some(result);
+            }
+
+            Message::Expect(expectation) => {
+                let result = last_result.as_ref().ok_or_else(|| {
+                    format!("Line {}: There is no previous result to check", line)
+                })?;
+                let outcome = expectation.check(result);
+                if outcome.is_err() {
+                    failed_expectations += 1;
+                }
+                print_expectation_result(output_format, line, &outcome);
+            }
+
+            Message::AdvanceTime(duration) => {
+                time_offset += duration;
+            }
+
+            Message::Tick(batches) => {
+                wait_extra_batches(&message_routing, batches, time_offset);
             }
         }
     }
+    if failed_expectations > 0 {
+        return Err(format!("{} expectation(s) failed", failed_expectations));
+    }
     Ok(())
 }
 
@@ -327,42 +465,112 @@ fn disable_dts(subnet_config: &mut SubnetConfig) {
         scheduler_config.max_instructions_per_install_code;
 }
 
-fn print_query_result(res: Result<WasmResult, UserError>) {
-    match res {
-        Ok(payload) => {
-            print!("Ok: ");
-            print_wasm_result(payload);
-        }
-        Err(e) => println!("Err: {}", e),
+fn print_query_result(
+    output_format: OutputFormat,
+    line: usize,
+    res: &Result<WasmResult, UserError>,
+) {
+    match output_format {
+        OutputFormat::Text => match res {
+            Ok(payload) => {
+                print!("Ok: ");
+                print_wasm_result(payload);
+            }
+            Err(e) => println!("Err: {}", e),
+        },
+        OutputFormat::Json => print_json_result(line, "query", res),
     }
 }
 
-fn print_ingress_result(message_id: &MessageId, ingress_hist_reader: &dyn IngressHistoryReader) {
+fn get_ingress_result(
+    message_id: &MessageId,
+    ingress_hist_reader: &dyn IngressHistoryReader,
+) -> Result<WasmResult, UserError> {
     let status = (ingress_hist_reader.get_latest_status())(message_id);
-    print!("ingress ");
     match status {
         IngressStatus::Known {
             state: IngressState::Completed(result),
             ..
-        } => {
-            print!("Completed: ");
-            print_wasm_result(result)
-        }
+        } => Ok(result),
         IngressStatus::Known {
             state: IngressState::Failed(error),
             ..
-        } => println!("Err: {}", error),
+        } => Err(error),
         _ => panic!("Ingress message has not finished processing."),
-    };
+    }
 }
 
-fn print_wasm_result(wasm_result: WasmResult) {
+fn print_ingress_result(
+    output_format: OutputFormat,
+    line: usize,
+    kind: &str,
+    res: &Result<WasmResult, UserError>,
+) {
+    match output_format {
+        OutputFormat::Text => {
+            print!("ingress ");
+            match res {
+                Ok(result) => {
+                    print!("Completed: ");
+                    print_wasm_result(result)
+                }
+                Err(error) => println!("Err: {}", error),
+            }
+        }
+        OutputFormat::Json => print_json_result(line, kind, res),
+    }
+}
+
+fn print_wasm_result(wasm_result: &WasmResult) {
     match wasm_result {
         WasmResult::Reply(v) => println!("Reply: 0x{}", encode(v)),
         WasmResult::Reject(e) => println!("Reject: {}", e),
     }
 }
 
+/// Prints the result of a message as a single-line JSON object, e.g.,
+/// `{"line":3,"message":"ingress","reply":"0x4449444c0000"}`.
+fn print_json_result(line: usize, kind: &str, res: &Result<WasmResult, UserError>) {
+    let output = match res {
+        Ok(WasmResult::Reply(v)) => json!({
+            "line": line,
+            "message": kind,
+            "reply": format!("0x{}", encode(v)),
+        }),
+        Ok(WasmResult::Reject(e)) => json!({ "line": line, "message": kind, "reject": e }),
+        Err(e) => json!({
+            "line": line,
+            "message": kind,
+            "error": {
+                "code": e.code().to_string(),
+                "description": e.description(),
+            },
+        }),
+    };
+    println!("{}", output);
+}
+
+fn print_expectation_result(
+    output_format: OutputFormat,
+    line: usize,
+    outcome: &Result<(), String>,
+) {
+    match (output_format, outcome) {
+        (OutputFormat::Text, Ok(())) => println!("expect Passed"),
+        (OutputFormat::Text, Err(error)) => println!("expect Failed (line {}): {}", line, error),
+        (OutputFormat::Json, Ok(())) => {
+            println!(
+                "{}",
+                json!({ "line": line, "message": "expect", "passed": true })
+            )
+        }
+        (OutputFormat::Json, Err(error)) => println!(
+            "{}",
+            json!({ "line": line, "message": "expect", "passed": false, "error": error })
+        ),
+    }
+}
+
 fn get_random_seed() -> [u8; 32] {
     let step = Uniform::new(0, u8::MAX);
     let mut rng = rand::thread_rng();
@@ -370,7 +578,11 @@ fn get_random_seed() -> [u8; 32] {
     seed.try_into().unwrap()
 }
 
-fn build_batch(message_routing: &dyn MessageRouting, msgs: Vec<SignedIngress>) -> Batch {
+fn build_batch(
+    message_routing: &dyn MessageRouting,
+    msgs: Vec<SignedIngress>,
+    time_offset: Duration,
+) -> Batch {
     Batch {
         batch_number: message_routing.expected_batch_height(),
         batch_summary: None,
@@ -383,7 +595,7 @@ fn build_batch(message_routing: &dyn MessageRouting, msgs: Vec<SignedIngress>) -
         idkg_subnet_public_keys: BTreeMap::new(),
         idkg_pre_signature_ids: BTreeMap::new(),
         registry_version: RegistryVersion::from(1),
-        time: time::current_time(),
+        time: time::current_time() + time_offset,
         consensus_responses: vec![],
         blockmaker_metrics: BlockmakerMetrics::new_for_test(),
     }
@@ -397,8 +609,9 @@ fn execute_ingress_message(
     msg: SignedIngress,
     msg_id: &MessageId,
     ingress_history: &dyn IngressHistoryReader,
+    time_offset: Duration,
 ) -> Result<WasmResult, UserError> {
-    let mut batch = build_batch(message_routing, vec![msg]);
+    let mut batch = build_batch(message_routing, vec![msg], time_offset);
     for _ in 0..MAX_BATCHES_UNTIL_RESPONSE {
         // In the first batch we try to send the ingress message itself. If it fails, we
         // repeat with the same batch.
@@ -409,7 +622,7 @@ fn execute_ingress_message(
         // potential inter-canister messages that the ingress message may have
         // triggered.
         if message_routing.deliver_batch(batch.clone()).is_ok() {
-            batch = build_batch(message_routing, vec![])
+            batch = build_batch(message_routing, vec![], time_offset)
         }
         sleep(WAIT_PER_BATCH);
 
@@ -455,10 +668,14 @@ fn execute_ingress_message(
 ///
 /// This is a temporary measure until DFN-1269 is resolved. In that ticket, we
 /// will actually try to wait until all messages have been executed.
-fn wait_extra_batches(message_routing: &dyn MessageRouting, extra_batches: u64) {
+fn wait_extra_batches(
+    message_routing: &dyn MessageRouting,
+    extra_batches: u64,
+    time_offset: Duration,
+) {
     for _ in 0..extra_batches {
         loop {
-            let batch = build_batch(message_routing, vec![]);
+            let batch = build_batch(message_routing, vec![], time_offset);
             let ok = message_routing.deliver_batch(batch).is_ok();
             sleep(WAIT_PER_BATCH);
             if ok {
@@ -471,6 +688,47 @@ fn wait_extra_batches(message_routing: &dyn MessageRouting, extra_batches: u64)
 #[cfg(test)]
 mod tests {
     use super::*;
+    use ic_config::flag_status::FlagStatus;
+
+    const REPLY_WAT: &str = r#"
+        (module
+            (import "ic0" "msg_reply" (func $msg_reply))
+            (func $go (call $msg_reply))
+            (export "canister_update go" (func $go)))"#;
+
+    #[tokio::test]
+    async fn ingress_after_advance_time_does_not_expire() {
+        let (mut cfg, tmpdir) = Config::temp_config();
+        cfg.hypervisor.canister_sandboxing_flag = FlagStatus::Disabled;
+        let wasm_file = tmpdir.path().join("reply.wasm");
+        std::fs::write(&wasm_file, wat::parse_str(REPLY_WAT).unwrap()).unwrap();
+        let msg_file = tmpdir.path().join("messages.txt");
+        std::fs::write(
+            &msg_file,
+            format!(
+                "create\n\
+                 install rwlgt-iiaaa-aaaaa-aaaaa-cai {} \"\"\n\
+                 advance_time 3600\n\
+                 ingress rwlgt-iiaaa-aaaaa-aaaaa-cai go \"\"\n\
+                 expect reply\n",
+                wasm_file.display()
+            ),
+        )
+        .unwrap();
+
+        run_drun(DrunOptions {
+            msg_filename: msg_file.to_str().unwrap().to_string(),
+            cfg,
+            extra_batches: 0,
+            log_file: None,
+            instruction_limit: None,
+            subnet_type: SubnetType::System,
+            output_format: OutputFormat::Text,
+        })
+        .await
+        .unwrap();
+    }
+
     #[test]
     fn test_get_random_seed() {
         let seed_1 = get_random_seed();
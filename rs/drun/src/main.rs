@@ -5,7 +5,7 @@ use ic_canister_sandbox_backend_lib::{
     RUN_AS_SANDBOX_LAUNCHER_FLAG,
 };
 use ic_config::{flag_status::FlagStatus, Config, ConfigSource};
-use ic_drun::{run_drun, DrunOptions};
+use ic_drun::{run_drun, DrunOptions, OutputFormat};
 use ic_registry_subnet_type::SubnetType;
 use ic_types::NumBytes;
 use std::path::PathBuf;
@@ -18,6 +18,8 @@ const ARG_MESSAGES: &str = "messages";
 const ARG_EXTRA_BATCHES: &str = "extra-batches";
 const ARG_INSTRUCTION_LIMIT: &str = "instruction-limit";
 const ARG_SUBNET_TYPE: &str = "subnet-type";
+const ARG_INSTRUCTION_PROFILE_DIR: &str = "instruction-profile-dir";
+const ARG_FORMAT: &str = "format";
 
 const GB: u64 = 1024 * 1024 * 1024;
 const MAIN_MEMORY_CAPACITY: NumBytes = NumBytes::new(16 * GB);
@@ -68,6 +70,9 @@ async fn drun_main() -> Result<(), String> {
         hypervisor_config.max_canister_memory_size =
             hypervisor_config.embedders_config.max_wasm_memory_size
                 + hypervisor_config.embedders_config.max_stable_memory_size;
+        hypervisor_config.embedders_config.instruction_profiling_dir = matches
+            .get_one::<String>(ARG_INSTRUCTION_PROFILE_DIR)
+            .map(PathBuf::from);
 
         let cfg = Config::load_with_default(&source, default_config).unwrap_or_else(|err| {
             eprintln!("Failed to load config:\n  {}", err);
@@ -103,6 +108,16 @@ async fn drun_main() -> Result<(), String> {
             })
             .unwrap_or(SubnetType::System);
 
+        let output_format = matches
+            .get_one::<String>(ARG_FORMAT)
+            .map(|arg| {
+                arg.parse().unwrap_or_else(|err| {
+                    eprintln!("Failed to parse {}\n  {}", ARG_FORMAT, err);
+                    std::process::exit(1);
+                })
+            })
+            .unwrap_or(OutputFormat::Text);
+
         let uo = DrunOptions {
             msg_filename: matches.get_one::<String>(ARG_MESSAGES).unwrap().clone(),
             cfg,
@@ -110,6 +125,7 @@ async fn drun_main() -> Result<(), String> {
             log_file,
             instruction_limit,
             subnet_type,
+            output_format,
         };
         run_drun(uo).await
     })
@@ -167,5 +183,25 @@ fn get_arg_matches() -> ArgMatches {
                 .value_name("Subnet Type")
                 .num_args(1),
         )
+        .arg(
+            Arg::new(ARG_INSTRUCTION_PROFILE_DIR)
+                .long(ARG_INSTRUCTION_PROFILE_DIR)
+                .value_name("DIR")
+                .help(
+                    "Write the instruction profile of the last execution of each canister \
+                    to <DIR>/<canister_id>.folded in the folded stack format of flamegraph tools.",
+                )
+                .num_args(1),
+        )
+        .arg(
+            Arg::new(ARG_FORMAT)
+                .long(ARG_FORMAT)
+                .value_name("FORMAT")
+                .help(
+                    "Output format: `text` (default) or `json` for one JSON object per line \
+                    per message.",
+                )
+                .num_args(1),
+        )
         .get_matches()
 }
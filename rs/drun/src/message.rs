@@ -1,22 +1,27 @@
 use super::CanisterId;
 
 use hex::decode;
+use ic_error_types::UserError;
 use ic_execution_environment::execution::upgrade::ENHANCED_ORTHOGONAL_PERSISTENCE_SECTION;
 use ic_management_canister_types::{
-    self as ic00, CanisterInstallModeV2, CanisterUpgradeOptions, Payload, WasmMemoryPersistence,
+    self as ic00, CanisterInstallModeV2, CanisterSettingsArgsBuilder, CanisterUpgradeOptions,
+    LoadCanisterSnapshotArgs, Payload, TakeCanisterSnapshotArgs, UpdateSettingsArgs,
+    WasmMemoryPersistence,
 };
 use ic_types::{
+    ingress::WasmResult,
     messages::{Query, QuerySource, SignedIngress},
     time::expiry_time_from_now,
-    PrincipalId, UserId,
+    PrincipalId, SnapshotId, Time, UserId,
 };
 
 use std::{
     fmt,
     fs::File,
     io::{self, Read},
-    str::Chars,
+    str::{Chars, FromStr},
     string::FromUtf8Error,
+    time::Duration,
 };
 
 #[derive(PartialEq, Debug)]
@@ -25,6 +30,59 @@ pub(crate) enum Message {
     Query(Query),
     Install(SignedIngress),
     Create(SignedIngress),
+    SetControllers(SignedIngress),
+    TakeSnapshot { canister_id: CanisterId, nonce: u64 },
+    LoadSnapshot { canister_id: CanisterId, nonce: u64 },
+    Expect(Expectation),
+    AdvanceTime(Duration),
+    Tick(u64),
+}
+
+/// An assertion on the result of the previous message.
+#[derive(PartialEq, Debug)]
+pub(crate) enum Expectation {
+    /// The message was replied to, with the given payload if specified.
+    Reply(Option<Vec<u8>>),
+    /// The message was rejected by the canister or failed, with an error
+    /// message containing the given string if specified.
+    Reject(Option<String>),
+}
+
+impl Expectation {
+    /// Returns an error describing the mismatch if `result` does not meet
+    /// the expectation.
+    pub(crate) fn check(&self, result: &Result<WasmResult, UserError>) -> Result<(), String> {
+        let reject_message = match result {
+            Ok(WasmResult::Reply(payload)) => {
+                return match self {
+                    Expectation::Reply(None) => Ok(()),
+                    Expectation::Reply(Some(expected)) if expected == payload => Ok(()),
+                    Expectation::Reply(Some(expected)) => Err(format!(
+                        "expected reply 0x{}, got reply 0x{}",
+                        hex::encode(expected),
+                        hex::encode(payload)
+                    )),
+                    Expectation::Reject(_) => Err(format!(
+                        "expected reject, got reply 0x{}",
+                        hex::encode(payload)
+                    )),
+                };
+            }
+            Ok(WasmResult::Reject(message)) => message.clone(),
+            Err(error) => error.to_string(),
+        };
+        match self {
+            Expectation::Reject(None) => Ok(()),
+            Expectation::Reject(Some(expected)) if reject_message.contains(expected) => Ok(()),
+            Expectation::Reject(Some(expected)) => Err(format!(
+                "expected reject containing {:?}, got reject {:?}",
+                expected, reject_message
+            )),
+            Expectation::Reply(_) => {
+                Err(format!("expected reply, got reject {:?}", reject_message))
+            }
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -124,9 +182,13 @@ impl<R: Read> Iterator for LineIterator<R> {
     }
 }
 
+/// Returns the non-empty, non-comment lines of the file together with their
+/// (1-based) line numbers. The lines are parsed with [`parse_message`] only
+/// once the preceding messages have been executed, so that the expiry of
+/// ingress messages can take `advance_time` into account.
 pub(crate) fn msg_stream_from_file(
     filename: &str,
-) -> Result<impl Iterator<Item = Result<Message, String>>, String> {
+) -> Result<impl Iterator<Item = Result<(usize, String), String>>, String> {
     let f = File::open(filename).map_err(|e| e.to_string())?;
     let line_iterator = LineIterator::new(f);
 
@@ -138,14 +200,20 @@ pub(crate) fn msg_stream_from_file(
             _ => true,
         })
         .map(|(i, line)| match line {
-            Ok(line) => {
-                parse_message(&line, i as u64).map_err(|e| format!("Line {}: {}", i + 1, e))
-            }
+            Ok(line) => Ok((i + 1, line)),
             Err(e) => Err(format!("Error while reading line {}: {}", i, e)),
         }))
 }
 
-fn parse_message(s: &str, nonce: u64) -> Result<Message, String> {
+/// Returns the expiry time of an ingress message sent when the batch time is
+/// ahead of the current time by `time_offset`.
+fn expiry_time(time_offset: Duration) -> Time {
+    expiry_time_from_now() + time_offset
+}
+
+/// Parses a single line of the messages file. Ingress messages expire
+/// relative to the batch time, i.e., the current time plus `time_offset`.
+pub(crate) fn parse_message(s: &str, nonce: u64, time_offset: Duration) -> Result<Message, String> {
     let s = s.trim_end();
     let tokens: Vec<&str> = s.splitn(4, char::is_whitespace).collect();
 
@@ -165,29 +233,65 @@ fn parse_message(s: &str, nonce: u64) -> Result<Message, String> {
                 .method_name(method_name)
                 .method_payload(method_payload)
                 .nonce(nonce)
+                .expiry_time(expiry_time(time_offset))
                 .build();
             Ok(Message::Ingress(signed_ingress))
         }
         ["query", canister_id, method_name, payload] => Ok(Message::Query(Query {
             source: QuerySource::User {
                 user_id: UserId::from(PrincipalId::new_anonymous()),
-                ingress_expiry: expiry_time_from_now().as_nanos_since_unix_epoch(),
+                ingress_expiry: expiry_time(time_offset).as_nanos_since_unix_epoch(),
                 nonce: Some(nonce.to_le_bytes().to_vec()),
             },
             receiver: parse_canister_id(canister_id)?,
             method_name: validate_method_name(method_name)?,
             method_payload: parse_octet_string(payload)?,
         })),
-        ["create"] => parse_create(nonce),
-        ["install", canister_id, wasm_file, payload] => {
-            parse_install(nonce, canister_id, payload, wasm_file, "install")
-        }
-        ["reinstall", canister_id, wasm_file, payload] => {
-            parse_install(nonce, canister_id, payload, wasm_file, "reinstall")
-        }
-        ["upgrade", canister_id, wasm_file, payload] => {
-            parse_install(nonce, canister_id, payload, wasm_file, "upgrade")
-        }
+        ["create"] => parse_create(nonce, time_offset),
+        ["install", canister_id, wasm_file, payload] => parse_install(
+            nonce,
+            time_offset,
+            canister_id,
+            payload,
+            wasm_file,
+            "install",
+        ),
+        ["reinstall", canister_id, wasm_file, payload] => parse_install(
+            nonce,
+            time_offset,
+            canister_id,
+            payload,
+            wasm_file,
+            "reinstall",
+        ),
+        ["upgrade", canister_id, wasm_file, payload] => parse_install(
+            nonce,
+            time_offset,
+            canister_id,
+            payload,
+            wasm_file,
+            "upgrade",
+        ),
+        ["create_canister", ..] => parse_create_canister(nonce, time_offset, s),
+        ["set_controllers", ..] => parse_set_controllers(nonce, time_offset, s),
+        ["snapshot", "take", canister_id] => Ok(Message::TakeSnapshot {
+            canister_id: parse_canister_id(canister_id)?,
+            nonce,
+        }),
+        ["snapshot", "load", canister_id] => Ok(Message::LoadSnapshot {
+            canister_id: parse_canister_id(canister_id)?,
+            nonce,
+        }),
+        ["expect", ..] => parse_expect(s),
+        ["advance_time", seconds] => seconds
+            .parse()
+            .map(|seconds| Message::AdvanceTime(Duration::from_secs(seconds)))
+            .map_err(|e| format!("Failed to parse number of seconds {}: {}", seconds, e)),
+        ["tick"] => Ok(Message::Tick(1)),
+        ["tick", batches] => batches
+            .parse()
+            .map(Message::Tick)
+            .map_err(|e| format!("Failed to parse number of batches {}: {}", batches, e)),
         _ => Err(format!(
             "Failed to parse line {}, don't have a pattern to match this with",
             s
@@ -196,7 +300,6 @@ fn parse_message(s: &str, nonce: u64) -> Result<Message, String> {
 }
 
 fn parse_canister_id(canister_id: &str) -> Result<CanisterId, String> {
-    use std::str::FromStr;
     match PrincipalId::from_str(canister_id) {
         Ok(id) => Ok(CanisterId::unchecked_from_principal(id)),
         Err(err) => Err(format!(
@@ -206,7 +309,7 @@ fn parse_canister_id(canister_id: &str) -> Result<CanisterId, String> {
     }
 }
 
-fn parse_create(nonce: u64) -> Result<Message, String> {
+fn parse_create(nonce: u64, time_offset: Duration) -> Result<Message, String> {
     use ic_test_utilities_types::messages::SignedIngressBuilder;
 
     let signed_ingress = SignedIngressBuilder::new()
@@ -214,11 +317,154 @@ fn parse_create(nonce: u64) -> Result<Message, String> {
         .canister_id(ic00::IC_00)
         .method_payload(ic00::ProvisionalCreateCanisterWithCyclesArgs::new(None, None).encode())
         .nonce(nonce)
+        .expiry_time(expiry_time(time_offset))
+        .build();
+
+    Ok(Message::Create(signed_ingress))
+}
+
+fn parse_principal_id(principal_id: &str) -> Result<PrincipalId, String> {
+    PrincipalId::from_str(principal_id).map_err(|err| {
+        format!(
+            "Failed to convert {} to principal id with {}",
+            principal_id, err
+        )
+    })
+}
+
+fn parse_controllers(controllers: &str) -> Result<Vec<PrincipalId>, String> {
+    controllers
+        .split(',')
+        .filter(|controller| !controller.is_empty())
+        .map(parse_principal_id)
+        .collect()
+}
+
+/// Parses `create_canister [<setting>=<value> ...]` where the settings are
+/// `controllers` (a comma-separated list of principals), `compute_allocation`,
+/// `memory_allocation`, `freezing_threshold`, and `cycles`.
+fn parse_create_canister(nonce: u64, time_offset: Duration, s: &str) -> Result<Message, String> {
+    use ic_test_utilities_types::messages::SignedIngressBuilder;
+
+    fn parse_number<T: FromStr>(key: &str, value: &str) -> Result<T, String>
+    where
+        T::Err: fmt::Display,
+    {
+        value
+            .parse()
+            .map_err(|e| format!("Failed to parse {} {}: {}", key, value, e))
+    }
+
+    let mut cycles = None;
+    let mut settings = CanisterSettingsArgsBuilder::new();
+    for setting in s.split_whitespace().skip(1) {
+        let (key, value) = setting
+            .split_once('=')
+            .ok_or_else(|| format!("Expected <setting>=<value>, got {}", setting))?;
+        settings = match key {
+            "controllers" => settings.with_controllers(parse_controllers(value)?),
+            "compute_allocation" => settings.with_compute_allocation(parse_number(key, value)?),
+            "memory_allocation" => settings.with_memory_allocation(parse_number(key, value)?),
+            "freezing_threshold" => settings.with_freezing_threshold(parse_number(key, value)?),
+            "cycles" => {
+                cycles = Some(parse_number(key, value)?);
+                settings
+            }
+            _ => return Err(format!("Unknown canister setting {}", key)),
+        };
+    }
+
+    let mut args = ic00::ProvisionalCreateCanisterWithCyclesArgs::new(cycles, None);
+    args.settings = Some(settings.build());
+    let signed_ingress = SignedIngressBuilder::new()
+        .method_name(ic00::Method::ProvisionalCreateCanisterWithCycles)
+        .canister_id(ic00::IC_00)
+        .method_payload(args.encode())
+        .nonce(nonce)
+        .expiry_time(expiry_time(time_offset))
         .build();
 
     Ok(Message::Create(signed_ingress))
 }
 
+/// Parses `set_controllers <canister_id> [<principal> ...]`.
+fn parse_set_controllers(nonce: u64, time_offset: Duration, s: &str) -> Result<Message, String> {
+    use ic_test_utilities_types::messages::SignedIngressBuilder;
+
+    let mut tokens = s.split_whitespace().skip(1);
+    let canister_id = parse_canister_id(
+        tokens
+            .next()
+            .ok_or_else(|| "Missing canister id.".to_string())?,
+    )?;
+    let controllers = tokens.map(parse_principal_id).collect::<Result<_, _>>()?;
+    let settings = CanisterSettingsArgsBuilder::new()
+        .with_controllers(controllers)
+        .build();
+
+    let signed_ingress = SignedIngressBuilder::new()
+        .method_name(ic00::Method::UpdateSettings)
+        .canister_id(ic00::IC_00)
+        .method_payload(UpdateSettingsArgs::new(canister_id, settings).encode())
+        .nonce(nonce)
+        .expiry_time(expiry_time(time_offset))
+        .build();
+
+    Ok(Message::SetControllers(signed_ingress))
+}
+
+/// Parses `expect reply [<payload>]` and `expect reject [<message>]`.
+fn parse_expect(s: &str) -> Result<Message, String> {
+    let tokens: Vec<&str> = s.splitn(3, char::is_whitespace).collect();
+    let expectation = match &tokens[..] {
+        ["expect", "reply"] => Expectation::Reply(None),
+        ["expect", "reply", payload] => Expectation::Reply(Some(parse_octet_string(payload)?)),
+        ["expect", "reject"] => Expectation::Reject(None),
+        ["expect", "reject", message] => Expectation::Reject(Some(
+            String::from_utf8(parse_octet_string(message)?).map_err(|e| e.to_string())?,
+        )),
+        _ => return Err(format!("Failed to parse expectation {}", s)),
+    };
+    Ok(Message::Expect(expectation))
+}
+
+/// Builds the message to take a snapshot of `canister_id`, replacing the
+/// snapshot `replace_snapshot` if specified.
+pub(crate) fn take_snapshot_message(
+    canister_id: CanisterId,
+    replace_snapshot: Option<SnapshotId>,
+    nonce: u64,
+    time_offset: Duration,
+) -> SignedIngress {
+    use ic_test_utilities_types::messages::SignedIngressBuilder;
+
+    SignedIngressBuilder::new()
+        .method_name(ic00::Method::TakeCanisterSnapshot)
+        .canister_id(ic00::IC_00)
+        .method_payload(TakeCanisterSnapshotArgs::new(canister_id, replace_snapshot).encode())
+        .nonce(nonce)
+        .expiry_time(expiry_time(time_offset))
+        .build()
+}
+
+/// Builds the message to load the snapshot `snapshot_id` into `canister_id`.
+pub(crate) fn load_snapshot_message(
+    canister_id: CanisterId,
+    snapshot_id: SnapshotId,
+    nonce: u64,
+    time_offset: Duration,
+) -> SignedIngress {
+    use ic_test_utilities_types::messages::SignedIngressBuilder;
+
+    SignedIngressBuilder::new()
+        .method_name(ic00::Method::LoadCanisterSnapshot)
+        .canister_id(ic00::IC_00)
+        .method_payload(LoadCanisterSnapshotArgs::new(canister_id, snapshot_id, None).encode())
+        .nonce(nonce)
+        .expiry_time(expiry_time(time_offset))
+        .build()
+}
+
 fn contains_icp_private_custom_section(wasm_binary: &[u8], name: &str) -> Result<bool, String> {
     use wasmparser::{Parser, Payload::CustomSection};
 
@@ -236,6 +482,7 @@ fn contains_icp_private_custom_section(wasm_binary: &[u8], name: &str) -> Result
 
 fn parse_install(
     nonce: u64,
+    time_offset: Duration,
     canister_id: &str,
     payload: &str,
     wasm_file: &str,
@@ -285,6 +532,7 @@ fn parse_install(
                 .encode(),
         )
         .nonce(nonce)
+        .expiry_time(expiry_time(time_offset))
         .build();
     Ok(Message::Install(signed_ingress))
 }
@@ -409,7 +657,7 @@ mod tests {
             "ingress {} write \"payload \\x0a\\b00010001\"",
             APP_CANISTER_URL
         );
-        let parsed_message = parse_message(s, 0).unwrap();
+        let parsed_message = parse_message(s, 0, Duration::ZERO).unwrap();
         let expiry_time = match &parsed_message {
             Message::Ingress(signed_ingress) => signed_ingress.expiry_time(),
             _ => panic!(
@@ -432,7 +680,7 @@ mod tests {
     #[test]
     fn test_parse_message_hex_payload_succeeds() {
         let s = &format!("ingress {} write 0x010203", APP_CANISTER_URL);
-        let parsed_message = parse_message(s, 0).unwrap();
+        let parsed_message = parse_message(s, 0, Duration::ZERO).unwrap();
         let expiry_time = match &parsed_message {
             Message::Ingress(signed_ingress) => signed_ingress.expiry_time(),
             _ => panic!(
@@ -453,7 +701,7 @@ mod tests {
 
         let s = &format!("query {} read 0x010203", APP_CANISTER_URL);
         let nonce: u64 = 0;
-        let parsed_message = parse_message(s, 0).unwrap();
+        let parsed_message = parse_message(s, 0, Duration::ZERO).unwrap();
         let ingress_expiry = match &parsed_message {
             Message::Query(query) => match query.source {
                 QuerySource::User { ingress_expiry, .. } => ingress_expiry,
@@ -477,28 +725,137 @@ mod tests {
         assert_eq!(expected, parsed_message);
     }
 
+    #[test]
+    fn test_ingress_expiry_is_relative_to_batch_time() {
+        let s = &format!("ingress {} write 0x010203", APP_CANISTER_URL);
+        let time_offset = Duration::from_secs(3600);
+        let earliest = expiry_time_from_now() + time_offset;
+        let expiry_time = match parse_message(s, 0, time_offset).unwrap() {
+            Message::Ingress(signed_ingress) => signed_ingress.expiry_time(),
+            message => panic!(
+                "parse_message() returned an unexpected message type: {:?}",
+                message
+            ),
+        };
+        assert!(earliest <= expiry_time);
+        assert!(expiry_time <= expiry_time_from_now() + time_offset);
+    }
+
     #[test]
     fn test_parse_message_invalid_escapes_fails() {
         let s = &format!("query {} read \"\\xzz\"", APP_CANISTER_URL);
-        assert!(parse_message(s, 0).is_err());
+        assert!(parse_message(s, 0, Duration::ZERO).is_err());
 
         let s = &format!("query {} read \"\\b01\"", APP_CANISTER_URL);
-        assert!(parse_message(s, 0).is_err());
+        assert!(parse_message(s, 0, Duration::ZERO).is_err());
 
         let s = &format!("query {} read \"\\x1\"", APP_CANISTER_URL);
-        assert!(parse_message(s, 0).is_err());
+        assert!(parse_message(s, 0, Duration::ZERO).is_err());
 
         let s = &format!("query {} read \"\\b2\"", APP_CANISTER_URL);
-        assert!(parse_message(s, 0).is_err());
+        assert!(parse_message(s, 0, Duration::ZERO).is_err());
     }
 
     #[test]
     fn test_illegal_method_name_must_fail() {
         let s = &format!("query {} 0read \"\\xzz\"", APP_CANISTER_URL);
-        assert!(parse_message(s, 0).is_err());
+        assert!(parse_message(s, 0, Duration::ZERO).is_err());
 
         let s = &format!("query {} üread \"\\xzz\"", APP_CANISTER_URL);
-        assert!(parse_message(s, 0).is_err());
+        assert!(parse_message(s, 0, Duration::ZERO).is_err());
+    }
+
+    #[test]
+    fn test_parse_scenario_directives() {
+        assert_eq!(
+            parse_message("advance_time 60", 0, Duration::ZERO).unwrap(),
+            Message::AdvanceTime(Duration::from_secs(60))
+        );
+        assert_eq!(
+            parse_message("tick", 0, Duration::ZERO).unwrap(),
+            Message::Tick(1)
+        );
+        assert_eq!(
+            parse_message("tick 5", 0, Duration::ZERO).unwrap(),
+            Message::Tick(5)
+        );
+        assert_eq!(
+            parse_message(
+                &format!("snapshot take {}", APP_CANISTER_URL),
+                3,
+                Duration::ZERO
+            )
+            .unwrap(),
+            Message::TakeSnapshot {
+                canister_id: canister_test_id(APP_CANISTER_ID),
+                nonce: 3
+            }
+        );
+        assert_eq!(
+            parse_message(
+                &format!("snapshot load {}", APP_CANISTER_URL),
+                4,
+                Duration::ZERO
+            )
+            .unwrap(),
+            Message::LoadSnapshot {
+                canister_id: canister_test_id(APP_CANISTER_ID),
+                nonce: 4
+            }
+        );
+        assert!(parse_message("advance_time soon", 0, Duration::ZERO).is_err());
+        assert!(parse_message("snapshot delete", 0, Duration::ZERO).is_err());
+    }
+
+    #[test]
+    fn test_parse_create_canister_and_set_controllers() {
+        let s = &format!(
+            "create_canister controllers={},2vxsx-fae freezing_threshold=100 cycles=1000",
+            APP_CANISTER_URL
+        );
+        assert!(matches!(
+            parse_message(s, 0, Duration::ZERO),
+            Ok(Message::Create(_))
+        ));
+        assert!(matches!(
+            parse_message("create_canister", 0, Duration::ZERO),
+            Ok(Message::Create(_))
+        ));
+        assert!(parse_message("create_canister freezing_threshold", 0, Duration::ZERO).is_err());
+        assert!(parse_message("create_canister color=blue", 0, Duration::ZERO).is_err());
+
+        let s = &format!("set_controllers {} 2vxsx-fae", APP_CANISTER_URL);
+        assert!(matches!(
+            parse_message(s, 0, Duration::ZERO),
+            Ok(Message::SetControllers(_))
+        ));
+        assert!(parse_message("set_controllers", 0, Duration::ZERO).is_err());
+    }
+
+    #[test]
+    fn test_parse_and_check_expectations() {
+        let reply = Ok(WasmResult::Reply(vec![1, 2]));
+        let reject = Ok(WasmResult::Reject("no such method".to_string()));
+        let error = Err(UserError::new(
+            ic_error_types::ErrorCode::CanisterTrapped,
+            "Canister trapped: unreachable",
+        ));
+
+        let expect = |s: &str| match parse_message(s, 0, Duration::ZERO).unwrap() {
+            Message::Expect(expectation) => expectation,
+            message => panic!("Unexpected message {:?}", message),
+        };
+        assert!(expect("expect reply").check(&reply).is_ok());
+        assert!(expect("expect reply 0x0102").check(&reply).is_ok());
+        assert!(expect("expect reply \"\\x01\\x02\"").check(&reply).is_ok());
+        assert!(expect("expect reply 0x03").check(&reply).is_err());
+        assert!(expect("expect reply").check(&reject).is_err());
+        assert!(expect("expect reject").check(&reply).is_err());
+        assert!(expect("expect reject").check(&reject).is_ok());
+        assert!(expect("expect reject \"no such\"").check(&reject).is_ok());
+        assert!(expect("expect reject \"trapped\"").check(&error).is_ok());
+        assert!(expect("expect reject \"trapped\"").check(&reject).is_err());
+        assert!(parse_message("expect success", 0, Duration::ZERO).is_err());
     }
 
     #[test]
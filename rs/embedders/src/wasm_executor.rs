@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use std::sync::Arc;
 
@@ -16,7 +16,7 @@ use wasmtime::Module;
 use crate::wasmtime_embedder::CanisterMemoryType;
 use crate::{
     wasm_utils::{compile, decoding::decode_wasm, Segments, WasmImportsDetails},
-    wasmtime_embedder::WasmtimeInstance,
+    wasmtime_embedder::{instruction_profiler::INSTRUCTION_PROFILE_EXTENSION, WasmtimeInstance},
     CompilationCache, CompilationResult, SerializedModule, WasmExecutionInput, WasmtimeEmbedder,
 };
 use ic_config::flag_status::FlagStatus;
@@ -565,6 +565,25 @@ pub fn get_wasm_reserved_pages(execution_state: &ExecutionState) -> NumWasmPages
     NumWasmPages::from(0)
 }
 
+/// Writes the instruction profile of the last execution of the canister to
+/// `<dir>/<canister_id>.folded`, replacing the profile of the previous one.
+fn write_instruction_profile(
+    dir: &Path,
+    canister_id: CanisterId,
+    profile: &str,
+    log: &ReplicaLogger,
+) {
+    let path = dir.join(format!("{}.{}", canister_id, INSTRUCTION_PROFILE_EXTENSION));
+    if let Err(err) = std::fs::create_dir_all(dir).and_then(|_| std::fs::write(&path, profile)) {
+        warn!(
+            log,
+            "Failed to write the instruction profile to {}: {}",
+            path.display(),
+            err
+        );
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 #[allow(clippy::type_complexity)]
 pub fn process(
@@ -590,6 +609,7 @@ pub fn process(
     Result<WasmtimeInstance, SystemApiImpl>,
 ) {
     let canister_id = sandbox_safe_system_state.canister_id();
+    let profile_log = logger.clone();
     let modification_tracking = api_type.modification_tracking();
     let timestamp_nanos = api_type.time().as_nanos_since_unix_epoch();
     let system_api = SystemApiImpl::new(
@@ -644,11 +664,23 @@ pub fn process(
     // Set the instruction limit for the first slice.
     instance.set_instruction_counter(first_slice_instruction_limit.get() as i64);
 
+    let profile_root = match &func_ref {
+        FuncRef::Method(method) => method.to_string(),
+        FuncRef::UpdateClosure(_) => "update closure".to_string(),
+        FuncRef::QueryClosure(_) => "query closure".to_string(),
+    };
+
     // Execute Wasm code until it finishes or exceeds the message instruction
     // limit. With deterministic time slicing, this call may execute multiple
     // slices before it returns.
     let run_result = instance.run(func_ref);
 
+    if let Some(dir) = &embedder.config().instruction_profiling_dir {
+        if let Some(profile) = instance.take_instruction_profile(&profile_root) {
+            write_instruction_profile(dir, canister_id, &profile, &profile_log);
+        }
+    }
+
     // Get the executed/remaining instructions for the message and the slice.
     let instruction_counter = instance.instruction_counter();
     let instance_stats = instance.get_stats();
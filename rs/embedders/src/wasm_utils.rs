@@ -19,6 +19,7 @@ use crate::wasmtime_embedder::StoreData;
 use crate::{serialized_module::SerializedModule, CompilationResult, WasmtimeEmbedder};
 use wasmtime::InstancePre;
 
+pub mod coverage;
 pub mod decoding;
 pub mod instrumentation;
 mod system_api_replacements;
@@ -211,6 +212,12 @@ fn validate_and_instrument(
         config.dirty_page_overhead,
         config.max_wasm_memory_size,
         config.max_stable_memory_size,
+        config.coverage_instrumentation,
+        if config.instruction_profiling_dir.is_some() {
+            FlagStatus::Enabled
+        } else {
+            FlagStatus::Disabled
+        },
     )?;
     Ok((wasm_validation_details, instrumentation_output))
 }
@@ -0,0 +1,788 @@
+//! Basic block coverage of canister Wasm modules.
+//!
+//! If [`ic_config::embedders::Config::coverage_instrumentation`] is enabled,
+//! instrumentation splits every function defined in the module into basic
+//! blocks (see [`basic_block_starts`]) and prefixes each block with an
+//! increment of a dedicated counter:
+//!
+//! ```wasm
+//! global.get $counter
+//! i64.const 1
+//! i64.add
+//! global.set $counter
+//! ```
+//!
+//! The counters are mutable `i64` globals exported as
+//! `canister coverage_counter_<n>` after all other exports. Hence they are
+//! persisted as the last exported globals of the canister's execution state
+//! and survive across messages and checkpoints. As for any other global,
+//! increments made by executions whose state changes are discarded (e.g.,
+//! traps or non-replicated queries) are lost and the counters are reset when
+//! the canister's code is (re-)installed or upgraded.
+//!
+//! Given the original Wasm module and the exported globals of the canister,
+//! [`CoverageReport::new`] recovers the counter of every basic block and maps
+//! the blocks to function names using the `name` section and to source lines
+//! using the DWARF `.debug_line` section. The report can be rendered in the
+//! LCOV format understood by most coverage tools.
+
+use ic_replicated_state::Global as PersistedGlobal;
+use ic_wasm_transform::{Global, Module};
+use std::collections::BTreeMap;
+use wasmparser::{
+    Export, ExternalKind, GlobalType, KnownCustom, Name, Operator, Parser, Payload, TypeRef,
+    ValType,
+};
+
+/// The prefix of the names of the exported coverage counter globals.
+///
+/// Canister modules must not export names with this prefix if coverage
+/// instrumentation is enabled.
+pub const COVERAGE_COUNTER_GLOBAL_PREFIX: &str = "canister coverage_counter_";
+
+/// The name of the pseudo source file under which functions without debug
+/// information are listed in LCOV reports.
+pub const UNKNOWN_SOURCE_FILE: &str = "<wasm>";
+
+pub(super) fn coverage_counter_name(index: usize) -> String {
+    format!("{}{}", COVERAGE_COUNTER_GLOBAL_PREFIX, index)
+}
+
+/// Returns the positions of the instructions that start a basic block of a
+/// function body, in increasing order.
+///
+/// A basic block starts at the beginning of the function and after every
+/// instruction that may transfer control elsewhere or that is the target of
+/// a branch.
+pub(super) fn basic_block_starts(code: &[Operator]) -> Vec<usize> {
+    use Operator::*;
+    let mut starts = vec![0];
+    for (position, i) in code.iter().enumerate() {
+        match i {
+            Loop { .. }
+            | If { .. }
+            | Else
+            | Br { .. }
+            | BrIf { .. }
+            | BrTable { .. }
+            | End
+            | Return
+            | Unreachable
+            | ReturnCall { .. }
+            | ReturnCallIndirect { .. } => {
+                if position + 1 < code.len() {
+                    starts.push(position + 1);
+                }
+            }
+            _ => (),
+        }
+    }
+    starts
+}
+
+/// Prefixes every basic block of `code` with an increment of its coverage
+/// counter. The counters are consecutive globals starting at
+/// `first_counter_ix`.
+///
+/// Returns the number of counters used.
+pub(super) fn inject_coverage_counters(code: &mut Vec<Operator>, first_counter_ix: u32) -> u32 {
+    use Operator::*;
+    let starts = basic_block_starts(code);
+    let orig_elems = std::mem::take(code);
+    let mut elems = Vec::with_capacity(orig_elems.len() + 4 * starts.len());
+    let mut next_start = starts.iter().peekable();
+    let mut global_index = first_counter_ix;
+    for (position, i) in orig_elems.into_iter().enumerate() {
+        if next_start.next_if_eq(&&position).is_some() {
+            elems.extend_from_slice(&[
+                GlobalGet { global_index },
+                I64Const { value: 1 },
+                I64Add,
+                GlobalSet { global_index },
+            ]);
+            global_index += 1;
+        }
+        elems.push(i);
+    }
+    *code = elems;
+    starts.len() as u32
+}
+
+/// Appends the `num_counters` coverage counter globals, which must get the
+/// indices starting at `first_counter_ix`, and exports them after all other
+/// exports.
+pub(super) fn export_coverage_counters<'a>(
+    mut module: Module<'a>,
+    first_counter_ix: u32,
+    num_counters: u32,
+    counter_names: &'a mut Vec<String>,
+) -> Module<'a> {
+    debug_assert_eq!(
+        first_counter_ix as usize,
+        module.globals.len()
+            + module
+                .imports
+                .iter()
+                .filter(|imp| matches!(imp.ty, TypeRef::Global(_)))
+                .count()
+    );
+    counter_names.extend((0..num_counters as usize).map(coverage_counter_name));
+    for (ix, name) in counter_names.iter().enumerate() {
+        module.globals.push(Global {
+            ty: GlobalType {
+                content_type: ValType::I64,
+                mutable: true,
+                shared: false,
+            },
+            init_expr: Operator::I64Const { value: 0 },
+        });
+        module.exports.push(Export {
+            name: name.as_str(),
+            kind: ExternalKind::Global,
+            index: first_counter_ix + ix as u32,
+        });
+    }
+    module
+}
+
+/// The coverage of a basic block.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct BlockCoverage {
+    /// Offset of the first instruction of the block relative to the start of
+    /// the code section, as used by DWARF.
+    pub offset: u64,
+    /// How many times the block was entered.
+    pub count: u64,
+}
+
+/// The coverage of a function defined in the module.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct FunctionCoverage {
+    /// Index of the function in the function index space of the module.
+    pub index: u32,
+    /// The demangled name from the `name` section or `func[<index>]`.
+    pub name: String,
+    /// The source file and line of the function entry, if known.
+    pub location: Option<(String, u64)>,
+    /// The basic blocks of the function in code order.
+    pub blocks: Vec<BlockCoverage>,
+}
+
+impl FunctionCoverage {
+    /// How many times the function was called.
+    pub fn count(&self) -> u64 {
+        self.blocks.first().map_or(0, |block| block.count)
+    }
+}
+
+/// The coverage of a canister Wasm module.
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct CoverageReport {
+    pub functions: Vec<FunctionCoverage>,
+    /// Execution counts of source lines keyed by source file and line number.
+    /// Empty if the module has no DWARF line information.
+    pub lines: BTreeMap<String, BTreeMap<u64, u64>>,
+}
+
+impl CoverageReport {
+    /// Builds the coverage report of the (decoded) Wasm module `wasm` from
+    /// the `exported_globals` of a canister running the module instrumented
+    /// for coverage.
+    pub fn new(wasm: &[u8], exported_globals: &[PersistedGlobal]) -> Result<Self, String> {
+        let module = parse_module(wasm).map_err(|err| format!("Failed to parse Wasm: {}", err))?;
+
+        let num_counters: usize = module.functions.iter().map(|f| f.blocks.len()).sum();
+        if exported_globals.len() < num_counters {
+            return Err(format!(
+                "Expected at least {} exported globals, got {}. Is coverage instrumentation enabled?",
+                num_counters,
+                exported_globals.len()
+            ));
+        }
+        let mut counters = exported_globals[exported_globals.len() - num_counters..]
+            .iter()
+            .map(|global| match global {
+                PersistedGlobal::I64(count) => Ok(*count as u64),
+                _ => Err(format!("Unexpected coverage counter {:?}", global)),
+            });
+
+        let mut functions = Vec::with_capacity(module.functions.len());
+        for function in module.functions {
+            let mut blocks = Vec::with_capacity(function.blocks.len());
+            for start in function.blocks {
+                blocks.push(BlockCoverage {
+                    offset: (start - module.code_section_start) as u64,
+                    count: counters.next().unwrap()?,
+                });
+            }
+            let name = match module.function_names.get(&function.index) {
+                Some(name) => crate::wasmtime_embedder::demangle(name),
+                None => format!("func[{}]", function.index),
+            };
+            functions.push(FunctionCoverage {
+                index: function.index,
+                name,
+                location: None,
+                blocks,
+            });
+        }
+
+        // Block ranges relative to the code section with the index of the
+        // function they belong to, in increasing order.
+        let mut block_ranges = vec![];
+        for (function_ix, function) in functions.iter().enumerate() {
+            let ends = function.blocks.iter().skip(1).map(|block| block.offset);
+            let function_end = module.function_ends[function_ix] - module.code_section_start;
+            for (block, end) in function
+                .blocks
+                .iter()
+                .zip(ends.chain(std::iter::once(function_end as u64)))
+            {
+                block_ranges.push((block.offset, end, function_ix, block.count));
+            }
+        }
+
+        let rows = match module.debug_line {
+            Some(debug_line) => {
+                dwarf::parse_line_programs(debug_line, module.debug_str, module.debug_line_str)
+                    .map_err(|err| format!("Failed to parse DWARF line information: {}", err))?
+            }
+            None => vec![],
+        };
+
+        let mut lines: BTreeMap<String, BTreeMap<u64, u64>> = BTreeMap::new();
+        let mut entry_addresses: BTreeMap<usize, u64> = BTreeMap::new();
+        for row in rows.iter().filter(|row| !row.end_sequence && row.line != 0) {
+            let ix = block_ranges.partition_point(|(start, _, _, _)| *start <= row.address);
+            if ix == 0 {
+                continue;
+            }
+            let (_, end, function_ix, count) = block_ranges[ix - 1];
+            if row.address >= end {
+                continue;
+            }
+            let line_count = lines
+                .entry(row.file.clone())
+                .or_default()
+                .entry(row.line)
+                .or_default();
+            *line_count = (*line_count).max(count);
+            // The location of a function is the row with the lowest address
+            // within the function.
+            let entry_address = entry_addresses.entry(function_ix).or_insert(u64::MAX);
+            if row.address < *entry_address {
+                *entry_address = row.address;
+                functions[function_ix].location = Some((row.file.clone(), row.line));
+            }
+        }
+
+        Ok(Self { functions, lines })
+    }
+
+    /// Renders the report in the LCOV tracefile format.
+    ///
+    /// Functions without debug information are listed under the pseudo
+    /// source file [`UNKNOWN_SOURCE_FILE`] with their function index as line
+    /// number.
+    pub fn to_lcov(&self) -> String {
+        let mut files: BTreeMap<&str, (Vec<(u64, &FunctionCoverage)>, BTreeMap<u64, u64>)> =
+            BTreeMap::new();
+        for (file, lines) in &self.lines {
+            files.entry(file.as_str()).or_default().1 = lines.clone();
+        }
+        for function in &self.functions {
+            match &function.location {
+                Some((file, line)) => files
+                    .entry(file.as_str())
+                    .or_default()
+                    .0
+                    .push((*line, function)),
+                None => {
+                    let line = function.index as u64;
+                    let (functions, lines) = files.entry(UNKNOWN_SOURCE_FILE).or_default();
+                    functions.push((line, function));
+                    lines.insert(line, function.count());
+                }
+            }
+        }
+
+        let mut lcov = String::new();
+        for (file, (functions, lines)) in files {
+            lcov.push_str("TN:\n");
+            lcov.push_str(&format!("SF:{}\n", file));
+            for (line, function) in &functions {
+                lcov.push_str(&format!("FN:{},{}\n", line, function.name));
+            }
+            for (_, function) in &functions {
+                lcov.push_str(&format!("FNDA:{},{}\n", function.count(), function.name));
+            }
+            lcov.push_str(&format!("FNF:{}\n", functions.len()));
+            lcov.push_str(&format!(
+                "FNH:{}\n",
+                functions.iter().filter(|(_, f)| f.count() > 0).count()
+            ));
+            for (line, count) in &lines {
+                lcov.push_str(&format!("DA:{},{}\n", line, count));
+            }
+            lcov.push_str(&format!("LF:{}\n", lines.len()));
+            lcov.push_str(&format!(
+                "LH:{}\n",
+                lines.values().filter(|count| **count > 0).count()
+            ));
+            lcov.push_str("end_of_record\n");
+        }
+        lcov
+    }
+}
+
+struct ParsedFunction {
+    index: u32,
+    /// Absolute offsets of the first instructions of the basic blocks.
+    blocks: Vec<usize>,
+}
+
+struct ParsedModule<'a> {
+    code_section_start: usize,
+    functions: Vec<ParsedFunction>,
+    /// Absolute offsets of the ends of the function bodies.
+    function_ends: Vec<usize>,
+    function_names: BTreeMap<u32, &'a str>,
+    debug_line: Option<&'a [u8]>,
+    debug_str: Option<&'a [u8]>,
+    debug_line_str: Option<&'a [u8]>,
+}
+
+fn parse_module(wasm: &[u8]) -> Result<ParsedModule<'_>, wasmparser::BinaryReaderError> {
+    let mut module = ParsedModule {
+        code_section_start: 0,
+        functions: vec![],
+        function_ends: vec![],
+        function_names: BTreeMap::new(),
+        debug_line: None,
+        debug_str: None,
+        debug_line_str: None,
+    };
+    let mut num_imported_functions = 0;
+    for payload in Parser::new(0).parse_all(wasm) {
+        match payload? {
+            Payload::ImportSection(reader) => {
+                for import in reader {
+                    if let TypeRef::Func(_) = import?.ty {
+                        num_imported_functions += 1;
+                    }
+                }
+            }
+            Payload::CodeSectionStart { range, .. } => {
+                module.code_section_start = range.start;
+            }
+            Payload::CodeSectionEntry(body) => {
+                let mut instructions = vec![];
+                let mut offsets = vec![];
+                for op in body.get_operators_reader()?.into_iter_with_offsets() {
+                    let (op, offset) = op?;
+                    instructions.push(op);
+                    offsets.push(offset);
+                }
+                let blocks = basic_block_starts(&instructions)
+                    .into_iter()
+                    .map(|position| offsets[position])
+                    .collect();
+                module.functions.push(ParsedFunction {
+                    index: num_imported_functions + module.functions.len() as u32,
+                    blocks,
+                });
+                module.function_ends.push(body.range().end);
+            }
+            Payload::CustomSection(reader) => match reader.as_known() {
+                KnownCustom::Name(name_section) => {
+                    for subsection in name_section {
+                        if let Name::Function(names) = subsection? {
+                            for naming in names {
+                                let naming = naming?;
+                                module.function_names.insert(naming.index, naming.name);
+                            }
+                        }
+                    }
+                }
+                _ => match reader.name() {
+                    ".debug_line" => module.debug_line = Some(reader.data()),
+                    ".debug_str" => module.debug_str = Some(reader.data()),
+                    ".debug_line_str" => module.debug_line_str = Some(reader.data()),
+                    _ => (),
+                },
+            },
+            _ => (),
+        }
+    }
+    Ok(module)
+}
+
+/// A minimal reader of DWARF line number programs (versions 2 to 5) as
+/// emitted by LLVM for Wasm.
+mod dwarf {
+    /// A row of the line number matrix: the code starting at `address` up to
+    /// the address of the next row belongs to `line` of `file`.
+    pub(super) struct LineRow {
+        pub address: u64,
+        pub file: String,
+        pub line: u64,
+        pub end_sequence: bool,
+    }
+
+    const DW_LNS_COPY: u8 = 1;
+    const DW_LNS_ADVANCE_PC: u8 = 2;
+    const DW_LNS_ADVANCE_LINE: u8 = 3;
+    const DW_LNS_SET_FILE: u8 = 4;
+    const DW_LNS_CONST_ADD_PC: u8 = 8;
+    const DW_LNS_FIXED_ADVANCE_PC: u8 = 9;
+
+    const DW_LNE_END_SEQUENCE: u8 = 1;
+    const DW_LNE_SET_ADDRESS: u8 = 2;
+    const DW_LNE_DEFINE_FILE: u8 = 3;
+
+    const DW_LNCT_PATH: u64 = 1;
+    const DW_LNCT_DIRECTORY_INDEX: u64 = 2;
+
+    const DW_FORM_BLOCK: u64 = 0x09;
+    const DW_FORM_DATA1: u64 = 0x0b;
+    const DW_FORM_DATA2: u64 = 0x05;
+    const DW_FORM_DATA4: u64 = 0x06;
+    const DW_FORM_DATA8: u64 = 0x07;
+    const DW_FORM_DATA16: u64 = 0x1e;
+    const DW_FORM_LINE_STRP: u64 = 0x1f;
+    const DW_FORM_STRING: u64 = 0x08;
+    const DW_FORM_STRP: u64 = 0x0e;
+    const DW_FORM_UDATA: u64 = 0x0f;
+
+    struct Reader<'a> {
+        data: &'a [u8],
+        pos: usize,
+    }
+
+    impl<'a> Reader<'a> {
+        fn new(data: &'a [u8], pos: usize) -> Self {
+            Self { data, pos }
+        }
+
+        fn is_empty(&self) -> bool {
+            self.pos >= self.data.len()
+        }
+
+        fn bytes(&mut self, len: usize) -> Result<&'a [u8], String> {
+            let end = self
+                .pos
+                .checked_add(len)
+                .filter(|end| *end <= self.data.len())
+                .ok_or_else(|| format!("unexpected end of data at offset {}", self.pos))?;
+            let bytes = &self.data[self.pos..end];
+            self.pos = end;
+            Ok(bytes)
+        }
+
+        fn uint(&mut self, len: usize) -> Result<u64, String> {
+            Ok(self
+                .bytes(len)?
+                .iter()
+                .rev()
+                .fold(0, |acc, byte| (acc << 8) | *byte as u64))
+        }
+
+        fn u8(&mut self) -> Result<u8, String> {
+            Ok(self.bytes(1)?[0])
+        }
+
+        fn offset(&mut self, is_64: bool) -> Result<u64, String> {
+            self.uint(if is_64 { 8 } else { 4 })
+        }
+
+        fn uleb(&mut self) -> Result<u64, String> {
+            let mut result = 0;
+            let mut shift = 0;
+            loop {
+                let byte = self.u8()?;
+                if shift < 64 {
+                    result |= ((byte & 0x7f) as u64) << shift;
+                }
+                shift += 7;
+                if byte & 0x80 == 0 {
+                    return Ok(result);
+                }
+            }
+        }
+
+        fn sleb(&mut self) -> Result<i64, String> {
+            let mut result = 0;
+            let mut shift = 0;
+            loop {
+                let byte = self.u8()?;
+                if shift < 64 {
+                    result |= ((byte & 0x7f) as i64) << shift;
+                }
+                shift += 7;
+                if byte & 0x80 == 0 {
+                    if shift < 64 && byte & 0x40 != 0 {
+                        result |= -1 << shift;
+                    }
+                    return Ok(result);
+                }
+            }
+        }
+
+        fn cstr(&mut self) -> Result<String, String> {
+            let len = self.data[self.pos.min(self.data.len())..]
+                .iter()
+                .position(|byte| *byte == 0)
+                .ok_or_else(|| format!("unterminated string at offset {}", self.pos))?;
+            let string = String::from_utf8_lossy(self.bytes(len)?).into_owned();
+            self.pos += 1;
+            Ok(string)
+        }
+    }
+
+    fn string_at(section: Option<&[u8]>, offset: u64) -> Result<String, String> {
+        let section = section.ok_or("missing string section")?;
+        let mut reader = Reader::new(section, offset as usize);
+        if reader.is_empty() {
+            return Err(format!("string offset {} out of bounds", offset));
+        }
+        reader.cstr()
+    }
+
+    struct EntryFormat {
+        content_type: u64,
+        form: u64,
+    }
+
+    /// Reads a directory or file name entry of a DWARF 5 line program header
+    /// and returns its path and directory index.
+    fn read_entry(
+        reader: &mut Reader,
+        formats: &[EntryFormat],
+        is_64: bool,
+        debug_str: Option<&[u8]>,
+        debug_line_str: Option<&[u8]>,
+    ) -> Result<(String, u64), String> {
+        let mut path = String::new();
+        let mut directory_index = 0;
+        for format in formats {
+            let value = match format.form {
+                DW_FORM_STRING => {
+                    path = reader.cstr()?;
+                    continue;
+                }
+                DW_FORM_LINE_STRP => {
+                    path = string_at(debug_line_str, reader.offset(is_64)?)?;
+                    continue;
+                }
+                DW_FORM_STRP => {
+                    path = string_at(debug_str, reader.offset(is_64)?)?;
+                    continue;
+                }
+                DW_FORM_UDATA => reader.uleb()?,
+                DW_FORM_DATA1 => reader.uint(1)?,
+                DW_FORM_DATA2 => reader.uint(2)?,
+                DW_FORM_DATA4 => reader.uint(4)?,
+                DW_FORM_DATA8 => reader.uint(8)?,
+                DW_FORM_DATA16 => {
+                    reader.bytes(16)?;
+                    0
+                }
+                DW_FORM_BLOCK => {
+                    let len = reader.uleb()?;
+                    reader.bytes(len as usize)?;
+                    0
+                }
+                form => return Err(format!("unsupported form {:#x} in line header", form)),
+            };
+            if format.content_type == DW_LNCT_DIRECTORY_INDEX {
+                directory_index = value;
+            }
+        }
+        Ok((path, directory_index))
+    }
+
+    fn read_entry_formats(reader: &mut Reader) -> Result<Vec<EntryFormat>, String> {
+        let count = reader.u8()?;
+        (0..count)
+            .map(|_| {
+                Ok(EntryFormat {
+                    content_type: reader.uleb()?,
+                    form: reader.uleb()?,
+                })
+            })
+            .collect()
+    }
+
+    fn join(directory: &str, file: &str) -> String {
+        if directory.is_empty() || file.starts_with('/') {
+            file.to_string()
+        } else {
+            format!("{}/{}", directory, file)
+        }
+    }
+
+    /// Runs all line number programs of the `.debug_line` section and returns
+    /// the rows of the resulting line number matrix.
+    pub(super) fn parse_line_programs(
+        debug_line: &[u8],
+        debug_str: Option<&[u8]>,
+        debug_line_str: Option<&[u8]>,
+    ) -> Result<Vec<LineRow>, String> {
+        let mut rows = vec![];
+        let mut reader = Reader::new(debug_line, 0);
+        while !reader.is_empty() {
+            let mut unit_length = reader.uint(4)?;
+            let is_64 = unit_length == 0xffff_ffff;
+            if is_64 {
+                unit_length = reader.uint(8)?;
+            }
+            let unit_end = reader.pos.saturating_add(unit_length as usize);
+            let mut unit = Reader::new(&debug_line[..unit_end.min(debug_line.len())], reader.pos);
+            reader.pos = unit_end;
+
+            let version = unit.uint(2)?;
+            if !(2..=5).contains(&version) {
+                return Err(format!("unsupported line table version {}", version));
+            }
+            if version >= 5 {
+                // address_size and segment_selector_size
+                unit.bytes(2)?;
+            }
+            let header_length = unit.offset(is_64)?;
+            let program_start = unit.pos + header_length as usize;
+            let minimum_instruction_length = unit.u8()? as u64;
+            if version >= 4 {
+                // maximum_operations_per_instruction
+                unit.u8()?;
+            }
+            // default_is_stmt
+            unit.u8()?;
+            let line_base = unit.u8()? as i8 as i64;
+            let line_range = unit.u8()? as u64;
+            if line_range == 0 {
+                return Err("line_range must not be zero".to_string());
+            }
+            let opcode_base = unit.u8()?;
+            let standard_opcode_lengths = unit.bytes(opcode_base.saturating_sub(1) as usize)?;
+
+            let mut directories = vec![];
+            let mut files = vec![];
+            if version >= 5 {
+                let formats = read_entry_formats(&mut unit)?;
+                for _ in 0..unit.uleb()? {
+                    let (path, _) =
+                        read_entry(&mut unit, &formats, is_64, debug_str, debug_line_str)?;
+                    directories.push(path);
+                }
+                let formats = read_entry_formats(&mut unit)?;
+                for _ in 0..unit.uleb()? {
+                    let (path, directory_index) =
+                        read_entry(&mut unit, &formats, is_64, debug_str, debug_line_str)?;
+                    let directory = directories
+                        .get(directory_index as usize)
+                        .map_or("", String::as_str);
+                    files.push(join(directory, &path));
+                }
+            } else {
+                // Directory and file indices are 1-based; index 0 refers to
+                // the compilation directory, which is unknown here.
+                directories.push(String::new());
+                loop {
+                    let directory = unit.cstr()?;
+                    if directory.is_empty() {
+                        break;
+                    }
+                    directories.push(directory);
+                }
+                files.push(String::new());
+                loop {
+                    let path = unit.cstr()?;
+                    if path.is_empty() {
+                        break;
+                    }
+                    let directory_index = unit.uleb()?;
+                    // modification time and length
+                    unit.uleb()?;
+                    unit.uleb()?;
+                    let directory = directories
+                        .get(directory_index as usize)
+                        .map_or("", String::as_str);
+                    files.push(join(directory, &path));
+                }
+            }
+
+            unit.pos = program_start;
+            let mut address = 0;
+            let mut file = 1;
+            let mut line: i64 = 1;
+            while !unit.is_empty() {
+                let opcode = unit.u8()?;
+                let mut emit = false;
+                let mut end_sequence = false;
+                if opcode >= opcode_base {
+                    let adjusted = (opcode - opcode_base) as u64;
+                    address += (adjusted / line_range) * minimum_instruction_length;
+                    line += line_base + (adjusted % line_range) as i64;
+                    emit = true;
+                } else if opcode == 0 {
+                    let len = unit.uleb()? as usize;
+                    if len == 0 {
+                        continue;
+                    }
+                    let sub_opcode = unit.u8()?;
+                    match sub_opcode {
+                        DW_LNE_END_SEQUENCE => {
+                            emit = true;
+                            end_sequence = true;
+                        }
+                        DW_LNE_SET_ADDRESS => address = unit.uint(len - 1)?,
+                        DW_LNE_DEFINE_FILE => {
+                            let path = unit.cstr()?;
+                            let directory_index = unit.uleb()?;
+                            unit.uleb()?;
+                            unit.uleb()?;
+                            let directory = directories
+                                .get(directory_index as usize)
+                                .map_or("", String::as_str);
+                            files.push(join(directory, &path));
+                        }
+                        _ => {
+                            unit.bytes(len - 1)?;
+                        }
+                    }
+                } else {
+                    match opcode {
+                        DW_LNS_COPY => emit = true,
+                        DW_LNS_ADVANCE_PC => address += unit.uleb()? * minimum_instruction_length,
+                        DW_LNS_ADVANCE_LINE => line += unit.sleb()?,
+                        DW_LNS_SET_FILE => file = unit.uleb()? as usize,
+                        DW_LNS_CONST_ADD_PC => {
+                            address += ((255 - opcode_base as u64) / line_range)
+                                * minimum_instruction_length
+                        }
+                        DW_LNS_FIXED_ADVANCE_PC => address += unit.uint(2)?,
+                        _ => {
+                            for _ in 0..standard_opcode_lengths[opcode as usize - 1] {
+                                unit.uleb()?;
+                            }
+                        }
+                    }
+                }
+                if emit {
+                    rows.push(LineRow {
+                        address,
+                        file: files.get(file).cloned().unwrap_or_default(),
+                        line: line.max(0) as u64,
+                        end_sequence,
+                    });
+                }
+                if end_sequence {
+                    address = 0;
+                    file = 1;
+                    line = 1;
+                }
+            }
+        }
+        Ok(rows)
+    }
+}
@@ -109,7 +109,40 @@
 //! (memory (export "stable_memory_bytemap") i32 (i64.const STABLE_BYTEMAP_SIZE) (i64.const STABLE_BYTEMAP_SIZE))
 //! ```
 //!
+//! # Coverage
+//!
+//! If coverage instrumentation is enabled, every basic block of the original
+//! code additionally increments an exported counter global as described in
+//! the [`coverage`](super::coverage) module.
+//!
+//! # Instruction profiling
+//!
+//! If instruction profiling is enabled, two more System API functions are
+//! inserted after the others:
+//!
+//! ```wasm
+//! (import "__" "profile_enter" (func (param i32)))
+//! (import "__" "profile_exit" (func))
+//! ```
+//!
+//! and the body of every function of the original code is wrapped as follows:
+//!
+//! ```wasm
+//! i32.const <index of the function in the original module>
+//! call <profile_enter>
+//! block (result <results of the function>)
+//!   ;; original body with `call <profile_exit>` before every `return`
+//! end
+//! call <profile_exit>
+//! ```
+//!
+//! so that branches to the function label also exit through `profile_exit`.
+//! These calls are inserted after metering and are not charged. See the
+//! [`instruction_profiler`](crate::wasmtime_embedder::instruction_profiler)
+//! module for how the instructions are attributed to call stacks.
+//!
 
+use super::coverage::{export_coverage_counters, inject_coverage_counters};
 use super::system_api_replacements::replacement_functions;
 use super::validation::API_VERSION_IC0;
 use super::{InstrumentationOutput, Segments, SystemApiFunc};
@@ -712,6 +745,8 @@ const TRY_GROW_WASM_MEMORY_FUN_NAME: &str = "try_grow_wasm_memory";
 const TRY_GROW_STABLE_MEMORY_FUN_NAME: &str = "try_grow_stable_memory";
 const INTERNAL_TRAP_FUN_NAME: &str = "internal_trap";
 const STABLE_READ_FIRST_ACCESS_NAME: &str = "stable_read_first_access";
+const PROFILE_ENTER_FUN_NAME: &str = "profile_enter";
+const PROFILE_EXIT_FUN_NAME: &str = "profile_exit";
 const TABLE_STR: &str = "table";
 pub(crate) const INSTRUCTIONS_COUNTER_GLOBAL_NAME: &str = "canister counter_instructions";
 pub(crate) const DIRTY_PAGES_COUNTER_GLOBAL_NAME: &str = "canister counter_dirty_pages";
@@ -828,6 +863,7 @@ fn mutate_function_indices(module: &mut Module, f: impl Fn(u32) -> u32) {
 fn inject_helper_functions(
     mut module: Module,
     wasm_native_stable_memory: FlagStatus,
+    instruction_profiling: FlagStatus,
     mem_type: WasmMemoryType,
 ) -> Module {
     // insert types
@@ -854,8 +890,11 @@ fn inject_helper_functions(
     };
 
     let mut old_imports = module.imports;
-    module.imports =
-        Vec::with_capacity(old_imports.len() + InjectedImports::count(wasm_native_stable_memory));
+    module.imports = Vec::with_capacity(
+        old_imports.len()
+            + InjectedImports::count(wasm_native_stable_memory)
+            + profiling_imports_count(instruction_profiling),
+    );
     module.imports.push(ooi_imp);
     module.imports.push(tgwm_imp);
 
@@ -888,10 +927,26 @@ fn inject_helper_functions(
         module.imports.push(fr_imp);
     }
 
+    if instruction_profiling == FlagStatus::Enabled {
+        let pe_type = FuncType::new([ValType::I32], []);
+        let pe_type_idx = add_func_type(&mut module, pe_type);
+        module.imports.push(Import {
+            module: INSTRUMENTED_FUN_MODULE,
+            name: PROFILE_ENTER_FUN_NAME,
+            ty: TypeRef::Func(pe_type_idx),
+        });
+        module.imports.push(Import {
+            module: INSTRUMENTED_FUN_MODULE,
+            name: PROFILE_EXIT_FUN_NAME,
+            ty: TypeRef::Func(ooi_type_idx),
+        });
+    }
+
     module.imports.append(&mut old_imports);
 
-    // now increment all function references by InjectedImports::Count
-    let cnt = InjectedImports::count(wasm_native_stable_memory) as u32;
+    // now increment all function references by the number of injected imports
+    let cnt = (InjectedImports::count(wasm_native_stable_memory)
+        + profiling_imports_count(instruction_profiling)) as u32;
     mutate_function_indices(&mut module, |i| i + cnt);
 
     debug_assert!(
@@ -913,10 +968,24 @@ fn inject_helper_functions(
                 == "stable_read_first_access"
         );
     }
+    if instruction_profiling == FlagStatus::Enabled {
+        let profile_enter_fn = InjectedImports::count(wasm_native_stable_memory);
+        debug_assert!(module.imports[profile_enter_fn].name == "profile_enter");
+        debug_assert!(module.imports[profile_enter_fn + 1].name == "profile_exit");
+    }
 
     module
 }
 
+/// The number of imports injected for instruction profiling. They follow the
+/// imports of [`InjectedImports`].
+fn profiling_imports_count(instruction_profiling: FlagStatus) -> usize {
+    match instruction_profiling {
+        FlagStatus::Enabled => 2,
+        FlagStatus::Disabled => 0,
+    }
+}
+
 /// Indices of functions, globals, etc that will be need in the later parts of
 /// instrumentation.
 #[derive(Default)]
@@ -945,10 +1014,22 @@ pub(super) fn instrument(
     dirty_page_overhead: NumInstructions,
     max_wasm_memory_size: NumBytes,
     max_stable_memory_size: NumBytes,
+    coverage_instrumentation: FlagStatus,
+    instruction_profiling: FlagStatus,
 ) -> Result<InstrumentationOutput, WasmInstrumentationError> {
     let main_memory_type = main_memory_type(&module);
+    let num_original_imported_functions = module
+        .imports
+        .iter()
+        .filter(|imp| matches!(imp.ty, TypeRef::Func(_)))
+        .count() as u32;
     let stable_memory_index;
-    let mut module = inject_helper_functions(module, wasm_native_stable_memory, main_memory_type);
+    let mut module = inject_helper_functions(
+        module,
+        wasm_native_stable_memory,
+        instruction_profiling,
+        main_memory_type,
+    );
     module = export_table(module);
     (module, stable_memory_index) = update_memories(
         module,
@@ -960,6 +1041,7 @@ pub(super) fn instrument(
 
     let mut extra_strs: Vec<String> = Vec::new();
     module = export_mutable_globals(module, &mut extra_strs);
+    let mut coverage_counter_names: Vec<String> = Vec::new();
 
     let mut num_imported_functions = 0;
     let mut num_imported_globals = 0;
@@ -1008,6 +1090,23 @@ pub(super) fn instrument(
         module.start = None;
     }
 
+    // Inject the coverage counters before any other code so that the basic
+    // blocks are those of the original code. The counters are placed after
+    // the globals added by `export_additional_symbols`.
+    let first_coverage_counter_ix = match wasm_native_stable_memory {
+        FlagStatus::Enabled => num_globals + 3,
+        FlagStatus::Disabled => num_globals + 1,
+    };
+    let mut num_coverage_counters = 0;
+    if coverage_instrumentation == FlagStatus::Enabled {
+        for func_body in &mut module.code_sections {
+            num_coverage_counters += inject_coverage_counters(
+                &mut func_body.instructions,
+                first_coverage_counter_ix + num_coverage_counters,
+            );
+        }
+    }
+
     // inject instructions counter decrementation
     for func_body in &mut module.code_sections {
         inject_metering(
@@ -1042,16 +1141,50 @@ pub(super) fn instrument(
     // Inject `try_grow_wasm_memory` after `memory.grow` instructions.
     if !func_types.is_empty() {
         let func_bodies = &mut module.code_sections;
-        for (func_ix, func_type) in func_types.into_iter() {
-            inject_try_grow_wasm_memory(&mut func_bodies[func_ix], &func_type, main_memory_type);
+        for (func_ix, func_type) in func_types.iter() {
+            inject_try_grow_wasm_memory(&mut func_bodies[*func_ix], func_type, main_memory_type);
             if write_barrier == FlagStatus::Enabled {
-                inject_mem_barrier(&mut func_bodies[func_ix], &func_type);
+                inject_mem_barrier(&mut func_bodies[*func_ix], func_type);
             }
         }
     }
 
+    // Inject the profiling calls last so that they are not metered.
+    if instruction_profiling == FlagStatus::Enabled {
+        let profile_enter_fn = InjectedImports::count(wasm_native_stable_memory) as u32;
+        let block_types: Vec<_> = func_types
+            .iter()
+            .map(|(_, func_type)| match func_type.results() {
+                [] => BlockType::Empty,
+                [result] => BlockType::Type(*result),
+                results => BlockType::FuncType(add_func_type(
+                    &mut module,
+                    FuncType::new([], results.iter().copied()),
+                )),
+            })
+            .collect();
+        for ((func_ix, _), block_type) in func_types.iter().zip(block_types) {
+            inject_profiling(
+                &mut module.code_sections[*func_ix].instructions,
+                num_original_imported_functions + *func_ix as u32,
+                block_type,
+                profile_enter_fn,
+                profile_enter_fn + 1,
+            );
+        }
+    }
+
     module = export_additional_symbols(module, &special_indices, wasm_native_stable_memory);
 
+    if coverage_instrumentation == FlagStatus::Enabled {
+        module = export_coverage_counters(
+            module,
+            first_coverage_counter_ix,
+            num_coverage_counters,
+            &mut coverage_counter_names,
+        );
+    }
+
     if wasm_native_stable_memory == FlagStatus::Enabled {
         replace_system_api_functions(
             &mut module,
@@ -1120,6 +1253,43 @@ pub(super) fn instrument(
     })
 }
 
+/// Wraps the body of the function with index `function_index` in the
+/// original module into calls to `profile_enter` and `profile_exit` as
+/// described in the module documentation.
+fn inject_profiling(
+    code: &mut Vec<Operator>,
+    function_index: u32,
+    block_type: BlockType,
+    profile_enter_fn: u32,
+    profile_exit_fn: u32,
+) {
+    use Operator::*;
+    let mut instrumented = Vec::with_capacity(code.len() + 6);
+    instrumented.push(I32Const {
+        value: function_index as i32,
+    });
+    instrumented.push(Call {
+        function_index: profile_enter_fn,
+    });
+    instrumented.push(Block {
+        blockty: block_type,
+    });
+    for op in code.drain(..) {
+        if let Return | ReturnCall { .. } | ReturnCallIndirect { .. } = op {
+            instrumented.push(Call {
+                function_index: profile_exit_fn,
+            });
+        }
+        instrumented.push(op);
+    }
+    // The `end` of the original body now ends the block.
+    instrumented.push(Call {
+        function_index: profile_exit_fn,
+    });
+    instrumented.push(End);
+    *code = instrumented;
+}
+
 fn calculate_api_indexes(module: &Module<'_>) -> BTreeMap<SystemApiFunc, u32> {
     module
         .imports
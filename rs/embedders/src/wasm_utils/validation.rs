@@ -1,7 +1,9 @@
 //! This module is responsible for validating the wasm binaries that are
 //! installed on the Internet Computer.
 
-use super::{Complexity, WasmImportsDetails, WasmValidationDetails};
+use super::{
+    coverage::COVERAGE_COUNTER_GLOBAL_PREFIX, Complexity, WasmImportsDetails, WasmValidationDetails,
+};
 
 use ic_config::{embedders::Config as EmbeddersConfig, flag_status::FlagStatus};
 use ic_replicated_state::canister_state::execution_state::{
@@ -325,6 +327,26 @@ fn get_valid_system_apis_common(I: ValType) -> HashMap<String, HashMap<String, F
                 },
             )],
         ),
+        (
+            "subnet_self_size",
+            vec![(
+                API_VERSION_IC0,
+                FunctionSignature {
+                    param_types: vec![],
+                    return_type: vec![I],
+                },
+            )],
+        ),
+        (
+            "subnet_self_copy",
+            vec![(
+                API_VERSION_IC0,
+                FunctionSignature {
+                    param_types: vec![I, I, I],
+                    return_type: vec![],
+                },
+            )],
+        ),
         // Inter-canister method calls
         (
             "call_new",
@@ -607,6 +629,106 @@ fn get_valid_system_apis_common(I: ValType) -> HashMap<String, HashMap<String, F
                 },
             )],
         ),
+        (
+            "cost_call",
+            vec![(
+                API_VERSION_IC0,
+                FunctionSignature {
+                    param_types: vec![ValType::I64, ValType::I64, I],
+                    return_type: vec![],
+                },
+            )],
+        ),
+        (
+            "cost_create_canister",
+            vec![(
+                API_VERSION_IC0,
+                FunctionSignature {
+                    param_types: vec![I],
+                    return_type: vec![],
+                },
+            )],
+        ),
+        (
+            "cost_http_request",
+            vec![(
+                API_VERSION_IC0,
+                FunctionSignature {
+                    param_types: vec![ValType::I64, ValType::I64, I],
+                    return_type: vec![],
+                },
+            )],
+        ),
+        (
+            "cost_sign_with_ecdsa",
+            vec![(
+                API_VERSION_IC0,
+                FunctionSignature {
+                    param_types: vec![I, I, ValType::I32, I],
+                    return_type: vec![ValType::I32],
+                },
+            )],
+        ),
+        (
+            "cost_sign_with_schnorr",
+            vec![(
+                API_VERSION_IC0,
+                FunctionSignature {
+                    param_types: vec![I, I, ValType::I32, I],
+                    return_type: vec![ValType::I32],
+                },
+            )],
+        ),
+        (
+            "env_var_count",
+            vec![(
+                API_VERSION_IC0,
+                FunctionSignature {
+                    param_types: vec![],
+                    return_type: vec![I],
+                },
+            )],
+        ),
+        (
+            "env_var_name_size",
+            vec![(
+                API_VERSION_IC0,
+                FunctionSignature {
+                    param_types: vec![I],
+                    return_type: vec![I],
+                },
+            )],
+        ),
+        (
+            "env_var_name_copy",
+            vec![(
+                API_VERSION_IC0,
+                FunctionSignature {
+                    param_types: vec![I, I, I, I],
+                    return_type: vec![],
+                },
+            )],
+        ),
+        (
+            "env_var_value_size",
+            vec![(
+                API_VERSION_IC0,
+                FunctionSignature {
+                    param_types: vec![I, I],
+                    return_type: vec![I],
+                },
+            )],
+        ),
+        (
+            "env_var_value_copy",
+            vec![(
+                API_VERSION_IC0,
+                FunctionSignature {
+                    param_types: vec![I, I, I, I, I],
+                    return_type: vec![],
+                },
+            )],
+        ),
         (
             "call_with_best_effort_response",
             vec![(
@@ -862,6 +984,7 @@ fn validate_export_section(
     module: &Module,
     max_number_exported_functions: usize,
     max_sum_exported_function_name_lengths: usize,
+    coverage_instrumentation: FlagStatus,
 ) -> Result<(), WasmValidationError> {
     if !module.exports.is_empty() {
         let imported_function_types: Vec<_> = module
@@ -886,8 +1009,13 @@ fn validate_export_section(
         let mut number_exported_functions = 0;
         let mut sum_exported_function_name_lengths = 0;
         for export in &module.exports {
-            // Verify that the exported symbol's name isn't reserved.
-            if RESERVED_SYMBOLS.contains(&export.name) {
+            // Verify that the exported symbol's name isn't reserved. The
+            // names of the coverage counters are only reserved if coverage
+            // instrumentation exports them.
+            if RESERVED_SYMBOLS.contains(&export.name)
+                || (coverage_instrumentation == FlagStatus::Enabled
+                    && export.name.starts_with(COVERAGE_COUNTER_GLOBAL_PREFIX))
+            {
                 return Err(WasmValidationError::InvalidExportSection(format!(
                     "Exporting reserved symbol {} not allowed.",
                     export.name
@@ -1424,7 +1552,11 @@ pub fn wasmtime_validation_config(embedders_config: &EmbeddersConfig) -> wasmtim
     config.generate_address_map(false);
     // The signal handler uses Posix signals, not Mach ports on MacOS.
     config.macos_use_mach_ports(false);
-    config.wasm_backtrace(embedders_config.feature_flags.canister_backtrace == FlagStatus::Enabled);
+    // Instruction profiling looks up function names in backtraces.
+    config.wasm_backtrace(
+        embedders_config.feature_flags.canister_backtrace == FlagStatus::Enabled
+            || embedders_config.instruction_profiling_dir.is_some(),
+    );
     config.wasm_backtrace_details(wasmtime::WasmBacktraceDetails::Disable);
     config.wasm_bulk_memory(true);
     config.wasm_function_references(false);
@@ -1534,6 +1666,7 @@ pub(super) fn validate_wasm_binary<'a>(
         &module,
         config.max_number_exported_functions,
         config.max_sum_exported_function_name_lengths,
+        config.coverage_instrumentation,
     )?;
     validate_data_section(&module)?;
     validate_global_section(&module, config.max_globals)?;
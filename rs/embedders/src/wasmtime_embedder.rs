@@ -1,4 +1,5 @@
 pub mod host_memory;
+pub mod instruction_profiler;
 mod signal_stack;
 mod system_api;
 pub mod system_api_complexity;
@@ -47,6 +48,7 @@ use crate::{
 use super::InstanceRunResult;
 
 use self::host_memory::{MemoryPageSize, MemoryStart};
+use self::instruction_profiler::InstructionProfiler;
 
 #[cfg(test)]
 mod wasmtime_embedder_tests;
@@ -60,7 +62,7 @@ pub(crate) const STABLE_BYTEMAP_MEMORY_NAME: &str = "stable_bytemap_memory";
 pub(crate) const MAX_STORE_TABLES: usize = 1;
 pub(crate) const MAX_STORE_TABLE_ELEMENTS: u32 = 1_000_000;
 
-fn demangle(func_name: &str) -> String {
+pub(crate) fn demangle(func_name: &str) -> String {
     if let Ok(name) = rustc_demangle::try_demangle(func_name) {
         format!("{:#}", name)
     } else {
@@ -420,6 +422,11 @@ impl WasmtimeEmbedder {
                     .table_elements(MAX_STORE_TABLE_ELEMENTS)
                     .build(),
                 canister_backtrace: self.config.feature_flags.canister_backtrace,
+                instruction_profiler: self
+                    .config
+                    .instruction_profiling_dir
+                    .as_ref()
+                    .map(|_| InstructionProfiler::new()),
             },
         );
         store.limiter(|state| &mut state.limits);
@@ -730,6 +737,8 @@ pub struct StoreData {
     pub num_stable_dirty_pages_from_non_native_writes: NumOsPages,
     pub limits: StoreLimits,
     pub canister_backtrace: FlagStatus,
+    /// Present if instruction profiling is enabled.
+    pub instruction_profiler: Option<InstructionProfiler>,
 }
 
 impl StoreData {
@@ -1308,4 +1317,20 @@ impl WasmtimeInstance {
     pub fn get_stats(&self) -> InstanceStats {
         self.instance_stats.clone()
     }
+
+    /// Returns the instruction profile of the execution so far in the folded
+    /// stack format with `root` as the outermost frame of all stacks, or
+    /// `None` if instruction profiling is disabled.
+    pub fn take_instruction_profile(&mut self, root: &str) -> Option<String> {
+        let profiler = self.store.data_mut().instruction_profiler.take()?;
+        let instruction_counter = self.instruction_counter();
+        let instructions = self
+            .store
+            .data()
+            .system_api()
+            .ok()?
+            .message_instructions_executed(instruction_counter)
+            .get();
+        Some(profiler.finish(root, instructions))
+    }
 }
@@ -0,0 +1,137 @@
+//! Attribution of executed instructions to the call stacks of canister
+//! functions.
+//!
+//! If [`ic_config::embedders::Config::instruction_profiling_dir`] is set,
+//! instrumentation calls `profile_enter` at the entry of every function of
+//! the canister and `profile_exit` right before the function returns. On each
+//! of these events, the instructions executed since the previous event are
+//! charged to the shadow stack of functions at that point. Hence, every
+//! function is only charged for the instructions it executes itself and the
+//! instructions of its callees are charged to the stacks of the callees.
+//!
+//! The profile is rendered in the folded stack format understood by flamegraph
+//! tools (e.g., `flamegraph.pl` or `inferno-flamegraph`) with the executed
+//! method as the root frame of all stacks:
+//!
+//! ```text
+//! canister_update transfer;ledger::transfer;ledger::validate 12345
+//! ```
+//!
+//! Instructions charged outside of canister functions (e.g., for dirty pages
+//! at the end of the execution) are charged to the root frame alone. If the
+//! execution traps, the instructions executed after the last event are charged
+//! to the stack at the time of the trap.
+
+use std::collections::BTreeMap;
+
+/// The file extension of instruction profiles.
+pub const INSTRUCTION_PROFILE_EXTENSION: &str = "folded";
+
+#[derive(Default)]
+pub struct InstructionProfiler {
+    /// The indices of the functions of the shadow stack, outermost first.
+    stack: Vec<u32>,
+    /// The message instructions executed at the time of the last event.
+    last_instructions: u64,
+    /// The instructions charged to each stack.
+    stacks: BTreeMap<Vec<u32>, u64>,
+    /// The names of the functions seen so far.
+    names: BTreeMap<u32, String>,
+}
+
+impl InstructionProfiler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns true if the name of function `function_index` is known.
+    pub fn has_name(&self, function_index: u32) -> bool {
+        self.names.contains_key(&function_index)
+    }
+
+    /// Pushes function `function_index` to the shadow stack after charging
+    /// the instructions executed since the last event to the current stack.
+    pub fn enter(&mut self, function_index: u32, name: Option<String>, instructions: u64) {
+        self.charge(instructions);
+        if let Some(name) = name {
+            self.names.insert(function_index, name);
+        }
+        self.stack.push(function_index);
+    }
+
+    /// Pops the innermost function from the shadow stack after charging the
+    /// instructions executed since the last event to the current stack.
+    pub fn exit(&mut self, instructions: u64) {
+        self.charge(instructions);
+        self.stack.pop();
+    }
+
+    fn charge(&mut self, instructions: u64) {
+        let delta = instructions.saturating_sub(self.last_instructions);
+        self.last_instructions = self.last_instructions.max(instructions);
+        if delta > 0 {
+            *self.stacks.entry(self.stack.clone()).or_default() += delta;
+        }
+    }
+
+    /// Charges the instructions executed since the last event to the current
+    /// stack and renders the profile in the folded stack format with `root` as
+    /// the outermost frame of all stacks.
+    pub fn finish(mut self, root: &str, instructions: u64) -> String {
+        self.charge(instructions);
+        let mut folded = String::new();
+        for (stack, count) in &self.stacks {
+            folded.push_str(&frame_name(root));
+            for function_index in stack {
+                folded.push(';');
+                match self.names.get(function_index) {
+                    Some(name) => folded.push_str(&frame_name(name)),
+                    None => folded.push_str(&format!("func[{}]", function_index)),
+                }
+            }
+            folded.push_str(&format!(" {}\n", count));
+        }
+        folded
+    }
+}
+
+/// Semicolons separate the frames and newlines the stacks of a folded profile,
+/// so they must not appear in frame names (e.g., `[u8; 32]` in demangled Rust
+/// names).
+fn frame_name(name: &str) -> String {
+    name.replace(';', ",").replace('\n', " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn charges_self_instructions_to_stacks() {
+        let mut profiler = InstructionProfiler::new();
+        profiler.enter(3, Some("main".to_string()), 10);
+        profiler.enter(5, Some("helper".to_string()), 30);
+        profiler.exit(100);
+        profiler.enter(5, None, 110);
+        profiler.exit(120);
+        profiler.exit(125);
+        assert_eq!(
+            profiler.finish("canister_update go", 140),
+            "canister_update go 25\n\
+             canister_update go;main 35\n\
+             canister_update go;main;helper 80\n"
+        );
+    }
+
+    #[test]
+    fn charges_remaining_instructions_to_stack_of_trap() {
+        let mut profiler = InstructionProfiler::new();
+        profiler.enter(0, None, 0);
+        profiler.enter(1, Some("<T as [u8; 2]>::f".to_string()), 5);
+        assert_eq!(
+            profiler.finish("canister_query q", 50),
+            "canister_query q;func[0] 5\n\
+             canister_query q;func[0];<T as [u8, 2]>::f 45\n"
+        );
+    }
+}
@@ -1,7 +1,7 @@
 use crate::{
     wasm_utils::instrumentation::WasmMemoryType,
     wasmtime_embedder::{
-        convert_backtrace,
+        convert_backtrace, demangle,
         system_api_complexity::{overhead, overhead_native},
         StoreData, WASM_HEAP_BYTEMAP_MEMORY_NAME, WASM_HEAP_MEMORY_NAME,
     },
@@ -608,6 +608,38 @@ pub(crate) fn syscalls<
         })
         .unwrap();
 
+    linker
+        .func_wrap("ic0", "subnet_self_size", {
+            move |mut caller: Caller<'_, StoreData>| {
+                charge_for_cpu(&mut caller, overhead::SUBNET_SELF_SIZE)?;
+                with_system_api(&mut caller, |s| s.ic0_subnet_self_size()).and_then(|s| {
+                    I::try_from(s).map_err(|e| {
+                        anyhow::Error::msg(format!("ic0_subnet_self_size failed: {}", e))
+                    })
+                })
+            }
+        })
+        .unwrap();
+
+    linker
+        .func_wrap("ic0", "subnet_self_copy", {
+            move |mut caller: Caller<'_, StoreData>, dst: I, offset: I, size: I| {
+                let dst: usize = dst.try_into().expect("Failed to convert I to usize");
+                let offset: usize = offset.try_into().expect("Failed to convert I to usize");
+                let size: usize = size.try_into().expect("Failed to convert I to usize");
+                charge_for_cpu_and_mem(&mut caller, overhead::SUBNET_SELF_COPY, size)?;
+                with_memory_and_system_api(&mut caller, |system_api, memory| {
+                    system_api.ic0_subnet_self_copy(dst, offset, size, memory)
+                })?;
+                if feature_flags.write_barrier == FlagStatus::Enabled {
+                    mark_writes_on_bytemap(&mut caller, dst, size)
+                } else {
+                    Ok(())
+                }
+            }
+        })
+        .unwrap();
+
     linker
         .func_wrap("ic0", "debug_print", {
             move |mut caller: Caller<'_, StoreData>, offset: I, length: I| {
@@ -1035,6 +1067,46 @@ pub(crate) fn syscalls<
         })
         .unwrap();
 
+    linker
+        .func_wrap("__", "profile_enter", {
+            move |mut caller: Caller<'_, StoreData>, function_index: u32| {
+                with_error_handling(&mut caller, |c| {
+                    let instructions = ic0_performance_counter_helper(c, 0)?;
+                    // The innermost frame is the function being entered. Its
+                    // name is only looked up once because capturing a
+                    // backtrace is expensive.
+                    let name = match &c.data().instruction_profiler {
+                        Some(profiler) if !profiler.has_name(function_index) => {
+                            WasmBacktrace::capture(&*c)
+                                .frames()
+                                .first()
+                                .and_then(|frame| frame.func_name().map(demangle))
+                        }
+                        _ => None,
+                    };
+                    if let Some(profiler) = &mut c.data_mut().instruction_profiler {
+                        profiler.enter(function_index, name, instructions);
+                    }
+                    Ok(())
+                })
+            }
+        })
+        .unwrap();
+
+    linker
+        .func_wrap("__", "profile_exit", {
+            move |mut caller: Caller<'_, StoreData>| {
+                with_error_handling(&mut caller, |c| {
+                    let instructions = ic0_performance_counter_helper(c, 0)?;
+                    if let Some(profiler) = &mut c.data_mut().instruction_profiler {
+                        profiler.exit(instructions);
+                    }
+                    Ok(())
+                })
+            }
+        })
+        .unwrap();
+
     match main_memory_type {
         WasmMemoryType::Wasm32 => {
             linker
@@ -1200,6 +1272,195 @@ pub(crate) fn syscalls<
         })
         .unwrap();
 
+    linker
+        .func_wrap("ic0", "cost_call", {
+            move |mut caller: Caller<'_, StoreData>,
+                  method_name_size: u64,
+                  payload_size: u64,
+                  dst: I| {
+                let dst: usize = dst.try_into().expect("Failed to convert I to usize");
+                charge_for_cpu(&mut caller, overhead::COST_CALL)?;
+                with_memory_and_system_api(&mut caller, |s, memory| {
+                    s.ic0_cost_call(method_name_size, payload_size, dst, memory)
+                })?;
+                if feature_flags.write_barrier == FlagStatus::Enabled {
+                    mark_writes_on_bytemap(&mut caller, dst, 16)
+                } else {
+                    Ok(())
+                }
+            }
+        })
+        .unwrap();
+
+    linker
+        .func_wrap("ic0", "cost_create_canister", {
+            move |mut caller: Caller<'_, StoreData>, dst: I| {
+                let dst: usize = dst.try_into().expect("Failed to convert I to usize");
+                charge_for_cpu(&mut caller, overhead::COST_CREATE_CANISTER)?;
+                with_memory_and_system_api(&mut caller, |s, memory| {
+                    s.ic0_cost_create_canister(dst, memory)
+                })?;
+                if feature_flags.write_barrier == FlagStatus::Enabled {
+                    mark_writes_on_bytemap(&mut caller, dst, 16)
+                } else {
+                    Ok(())
+                }
+            }
+        })
+        .unwrap();
+
+    linker
+        .func_wrap("ic0", "cost_http_request", {
+            move |mut caller: Caller<'_, StoreData>,
+                  request_size: u64,
+                  max_res_bytes: u64,
+                  dst: I| {
+                let dst: usize = dst.try_into().expect("Failed to convert I to usize");
+                charge_for_cpu(&mut caller, overhead::COST_HTTP_REQUEST)?;
+                with_memory_and_system_api(&mut caller, |s, memory| {
+                    s.ic0_cost_http_request(request_size, max_res_bytes, dst, memory)
+                })?;
+                if feature_flags.write_barrier == FlagStatus::Enabled {
+                    mark_writes_on_bytemap(&mut caller, dst, 16)
+                } else {
+                    Ok(())
+                }
+            }
+        })
+        .unwrap();
+
+    linker
+        .func_wrap("ic0", "cost_sign_with_ecdsa", {
+            move |mut caller: Caller<'_, StoreData>, src: I, size: I, curve: u32, dst: I| {
+                let src: usize = src.try_into().expect("Failed to convert I to usize");
+                let size: usize = size.try_into().expect("Failed to convert I to usize");
+                let dst: usize = dst.try_into().expect("Failed to convert I to usize");
+                charge_for_cpu_and_mem(&mut caller, overhead::COST_SIGN_WITH_ECDSA, size)?;
+                let result = with_memory_and_system_api(&mut caller, |s, memory| {
+                    s.ic0_cost_sign_with_ecdsa(src, size, curve, dst, memory)
+                })?;
+                if feature_flags.write_barrier == FlagStatus::Enabled {
+                    mark_writes_on_bytemap(&mut caller, dst, 16)?;
+                }
+                Ok(result)
+            }
+        })
+        .unwrap();
+
+    linker
+        .func_wrap("ic0", "cost_sign_with_schnorr", {
+            move |mut caller: Caller<'_, StoreData>, src: I, size: I, algorithm: u32, dst: I| {
+                let src: usize = src.try_into().expect("Failed to convert I to usize");
+                let size: usize = size.try_into().expect("Failed to convert I to usize");
+                let dst: usize = dst.try_into().expect("Failed to convert I to usize");
+                charge_for_cpu_and_mem(&mut caller, overhead::COST_SIGN_WITH_SCHNORR, size)?;
+                let result = with_memory_and_system_api(&mut caller, |s, memory| {
+                    s.ic0_cost_sign_with_schnorr(src, size, algorithm, dst, memory)
+                })?;
+                if feature_flags.write_barrier == FlagStatus::Enabled {
+                    mark_writes_on_bytemap(&mut caller, dst, 16)?;
+                }
+                Ok(result)
+            }
+        })
+        .unwrap();
+
+    linker
+        .func_wrap("ic0", "env_var_count", {
+            move |mut caller: Caller<'_, StoreData>| {
+                charge_for_cpu(&mut caller, overhead::ENV_VAR_COUNT)?;
+                with_system_api(&mut caller, |s| s.ic0_env_var_count()).and_then(|s| {
+                    I::try_from(s).map_err(|e| {
+                        anyhow::Error::msg(format!("ic0::env_var_count failed: {}", e))
+                    })
+                })
+            }
+        })
+        .unwrap();
+
+    linker
+        .func_wrap("ic0", "env_var_name_size", {
+            move |mut caller: Caller<'_, StoreData>, index: I| {
+                let index: usize = index.try_into().expect("Failed to convert I to usize");
+                charge_for_cpu(&mut caller, overhead::ENV_VAR_NAME_SIZE)?;
+                with_system_api(&mut caller, |s| s.ic0_env_var_name_size(index)).and_then(|s| {
+                    I::try_from(s).map_err(|e| {
+                        anyhow::Error::msg(format!("ic0::env_var_name_size failed: {}", e))
+                    })
+                })
+            }
+        })
+        .unwrap();
+
+    linker
+        .func_wrap("ic0", "env_var_name_copy", {
+            move |mut caller: Caller<'_, StoreData>, index: I, dst: I, offset: I, size: I| {
+                let index: usize = index.try_into().expect("Failed to convert I to usize");
+                let dst: usize = dst.try_into().expect("Failed to convert I to usize");
+                let offset: usize = offset.try_into().expect("Failed to convert I to usize");
+                let size: usize = size.try_into().expect("Failed to convert I to usize");
+                charge_for_cpu_and_mem(&mut caller, overhead::ENV_VAR_NAME_COPY, size)?;
+                with_memory_and_system_api(&mut caller, |system_api, memory| {
+                    system_api.ic0_env_var_name_copy(index, dst, offset, size, memory)
+                })?;
+                if feature_flags.write_barrier == FlagStatus::Enabled {
+                    mark_writes_on_bytemap(&mut caller, dst, size)
+                } else {
+                    Ok(())
+                }
+            }
+        })
+        .unwrap();
+
+    linker
+        .func_wrap("ic0", "env_var_value_size", {
+            move |mut caller: Caller<'_, StoreData>, name_src: I, name_size: I| {
+                let name_src: usize = name_src.try_into().expect("Failed to convert I to usize");
+                let name_size: usize = name_size.try_into().expect("Failed to convert I to usize");
+                charge_for_cpu_and_mem(&mut caller, overhead::ENV_VAR_VALUE_SIZE, name_size)?;
+                with_memory_and_system_api(&mut caller, |system_api, memory| {
+                    system_api.ic0_env_var_value_size(name_src, name_size, memory)
+                })
+                .and_then(|s| {
+                    I::try_from(s).map_err(|e| {
+                        anyhow::Error::msg(format!("ic0::env_var_value_size failed: {}", e))
+                    })
+                })
+            }
+        })
+        .unwrap();
+
+    linker
+        .func_wrap("ic0", "env_var_value_copy", {
+            move |mut caller: Caller<'_, StoreData>,
+                  name_src: I,
+                  name_size: I,
+                  dst: I,
+                  offset: I,
+                  size: I| {
+                let name_src: usize = name_src.try_into().expect("Failed to convert I to usize");
+                let name_size: usize = name_size.try_into().expect("Failed to convert I to usize");
+                let dst: usize = dst.try_into().expect("Failed to convert I to usize");
+                let offset: usize = offset.try_into().expect("Failed to convert I to usize");
+                let size: usize = size.try_into().expect("Failed to convert I to usize");
+                charge_for_cpu_and_mem(
+                    &mut caller,
+                    overhead::ENV_VAR_VALUE_COPY,
+                    name_size.saturating_add(size),
+                )?;
+                with_memory_and_system_api(&mut caller, |system_api, memory| {
+                    system_api
+                        .ic0_env_var_value_copy(name_src, name_size, dst, offset, size, memory)
+                })?;
+                if feature_flags.write_barrier == FlagStatus::Enabled {
+                    mark_writes_on_bytemap(&mut caller, dst, size)
+                } else {
+                    Ok(())
+                }
+            }
+        })
+        .unwrap();
+
     linker
         .func_wrap("ic0", "call_with_best_effort_response", {
             move |mut caller: Caller<'_, StoreData>, timeout_seconds: u32| {
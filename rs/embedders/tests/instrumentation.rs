@@ -3,7 +3,12 @@ use ic_config::flag_status::FlagStatus;
 use ic_config::subnet_config::SchedulerConfig;
 use ic_embedders::wasm_utils;
 use ic_embedders::{
-    wasm_utils::{validate_and_instrument_for_testing, validation::RESERVED_SYMBOLS, Segments},
+    wasm_utils::{
+        coverage::{CoverageReport, COVERAGE_COUNTER_GLOBAL_PREFIX},
+        validate_and_instrument_for_testing,
+        validation::RESERVED_SYMBOLS,
+        Segments,
+    },
     WasmtimeEmbedder,
 };
 use ic_logger::replica_logger::no_op_logger;
@@ -12,6 +17,7 @@ use ic_wasm_transform::Module;
 use ic_wasm_types::BinaryEncodedWasm;
 use insta::assert_snapshot;
 use pretty_assertions::assert_eq;
+use std::collections::BTreeMap;
 
 use ic_embedders::wasm_utils::instrumentation::instruction_to_cost;
 use ic_embedders::wasm_utils::instrumentation::WasmMemoryType;
@@ -1330,3 +1336,430 @@ fn test_wasm64_costs_similar_to_wasm32_for_arithmetic_instructions() {
     // Check that the cost in Wasm64 mode is similar to Wasm32 mode.
     assert_eq!(total_cost, total_cost_wasm32);
 }
+
+const COVERAGE_TEST_WAT: &str = r#"
+    (module
+        (func $inc (param i32) (result i32)
+            (i32.add (local.get 0) (i32.const 1))
+        )
+        (func $unused
+            (nop)
+        )
+        (func $test (export "canister_update test")
+            (local $i i32)
+            (loop $loop
+                (local.set $i (call $inc (local.get $i)))
+                (br_if $loop (i32.lt_s (local.get $i) (i32.const 5)))
+            )
+        )
+    )"#;
+
+#[test]
+fn coverage_instrumentation_exports_counters() {
+    let config = EmbeddersConfig {
+        coverage_instrumentation: FlagStatus::Enabled,
+        ..EmbeddersConfig::default()
+    };
+    let embedder = WasmtimeEmbedder::new(config, no_op_logger());
+    let output = validate_and_instrument_for_testing(
+        &embedder,
+        &BinaryEncodedWasm::new(wat::parse_str(COVERAGE_TEST_WAT).unwrap()),
+    )
+    .unwrap()
+    .1;
+    let module = Module::parse(output.binary.as_slice(), false).unwrap();
+    let counters: Vec<_> = module
+        .exports
+        .iter()
+        .filter(|export| export.name.starts_with(COVERAGE_COUNTER_GLOBAL_PREFIX))
+        .map(|export| export.name)
+        .collect();
+    // `inc` and `unused` have a single basic block each, `test` has four:
+    // the function entry, the loop body, after `br_if`, and after the loop.
+    assert_eq!(
+        counters,
+        (0..6)
+            .map(|i| format!("{}{}", COVERAGE_COUNTER_GLOBAL_PREFIX, i))
+            .collect::<Vec<_>>()
+    );
+    // The counters are exported last so that they are the last exported globals.
+    assert!(module.exports.last().unwrap().name.ends_with("_5"));
+}
+
+#[test]
+fn coverage_instrumentation_is_disabled_by_default() {
+    let output = validate_and_instrument_for_testing(
+        &WasmtimeEmbedder::new(EmbeddersConfig::default(), no_op_logger()),
+        &BinaryEncodedWasm::new(wat::parse_str(COVERAGE_TEST_WAT).unwrap()),
+    )
+    .unwrap()
+    .1;
+    let module = Module::parse(output.binary.as_slice(), false).unwrap();
+    assert!(!module
+        .exports
+        .iter()
+        .any(|export| export.name.starts_with(COVERAGE_COUNTER_GLOBAL_PREFIX)));
+}
+
+#[test]
+fn coverage_report_counts_executed_blocks() {
+    let config = EmbeddersConfig {
+        coverage_instrumentation: FlagStatus::Enabled,
+        ..EmbeddersConfig::default()
+    };
+    let mut instance = WasmtimeInstanceBuilder::new()
+        .with_config(config)
+        .with_wat(COVERAGE_TEST_WAT)
+        .with_num_instructions(NumInstructions::new(10_000))
+        .build();
+    let res = instance.run(func_ref("test")).unwrap();
+
+    let wasm = wat::parse_str(COVERAGE_TEST_WAT).unwrap();
+    let report = CoverageReport::new(&wasm, &res.exported_globals).unwrap();
+    let counts: Vec<_> = report
+        .functions
+        .iter()
+        .map(|function| {
+            (
+                function.name.as_str(),
+                function.count(),
+                function.blocks.iter().map(|b| b.count).collect::<Vec<_>>(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        counts,
+        vec![
+            ("inc", 5, vec![5]),
+            ("unused", 0, vec![0]),
+            ("test", 1, vec![1, 5, 1, 1]),
+        ]
+    );
+    // Without DWARF information, functions are reported under a pseudo
+    // source file with their index as line number.
+    assert!(report.lines.is_empty());
+    assert_eq!(
+        report.to_lcov(),
+        "TN:\n\
+         SF:<wasm>\n\
+         FN:0,inc\n\
+         FN:1,unused\n\
+         FN:2,test\n\
+         FNDA:5,inc\n\
+         FNDA:0,unused\n\
+         FNDA:1,test\n\
+         FNF:3\n\
+         FNH:2\n\
+         DA:0,5\n\
+         DA:1,0\n\
+         DA:2,1\n\
+         LF:3\n\
+         LH:2\n\
+         end_of_record\n"
+    );
+}
+
+#[test]
+fn coverage_report_requires_instrumentation() {
+    let mut instance = new_instance(COVERAGE_TEST_WAT, 10_000);
+    let res = instance.run(func_ref("test")).unwrap();
+    let wasm = wat::parse_str(COVERAGE_TEST_WAT).unwrap();
+    assert!(CoverageReport::new(&wasm, &res.exported_globals).is_err());
+}
+
+fn uleb(mut value: u64, out: &mut Vec<u8>) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+fn sleb(mut value: i64, out: &mut Vec<u8>) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if (value == 0 && byte & 0x40 == 0) || (value == -1 && byte & 0x40 != 0) {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+fn append_custom_section(wasm: &mut Vec<u8>, name: &str, data: &[u8]) {
+    let mut payload = vec![];
+    uleb(name.len() as u64, &mut payload);
+    payload.extend_from_slice(name.as_bytes());
+    payload.extend_from_slice(data);
+    wasm.push(0);
+    uleb(payload.len() as u64, wasm);
+    wasm.extend(payload);
+}
+
+/// Builds a DWARF line number program unit of the given `version` whose
+/// `file_table` (the directory and file entries of the header) names
+/// `src/lib.rs` as file 1 and which maps every `(address, line)` of `rows`
+/// to that file. A row with the same address as its predecessor is encoded
+/// as a special opcode.
+fn debug_line_unit(version: u16, file_table: &[u8], rows: &[(u64, i64)], end: u64) -> Vec<u8> {
+    const OPCODE_BASE: u8 = 13;
+    const LINE_BASE: i8 = -5;
+    const LINE_RANGE: u8 = 14;
+
+    let mut header = vec![1]; // minimum_instruction_length
+    if version >= 4 {
+        header.push(1); // maximum_operations_per_instruction
+    }
+    header.extend_from_slice(&[1, LINE_BASE as u8, LINE_RANGE, OPCODE_BASE]);
+    header.extend_from_slice(&[0, 1, 1, 1, 1, 0, 0, 0, 1, 0, 0, 1]);
+    header.extend_from_slice(file_table);
+
+    let mut program = vec![];
+    let set_address = |address: u64, program: &mut Vec<u8>| {
+        program.extend_from_slice(&[0, 5, 2]); // DW_LNE_set_address
+        program.extend_from_slice(&(address as u32).to_le_bytes());
+    };
+    let mut current = (u64::MAX, 1);
+    for (address, line) in rows {
+        if *address == current.0 && line - current.1 == 1 {
+            // Special opcode advancing the line by one.
+            program.push((1 - LINE_BASE) as u8 + OPCODE_BASE);
+        } else {
+            set_address(*address, &mut program);
+            program.push(3); // DW_LNS_advance_line
+            sleb(line - current.1, &mut program);
+            program.push(1); // DW_LNS_copy
+        }
+        current = (*address, *line);
+    }
+    set_address(end, &mut program);
+    program.extend_from_slice(&[0, 1, 1]); // DW_LNE_end_sequence
+
+    let mut unit = version.to_le_bytes().to_vec();
+    if version >= 5 {
+        unit.extend_from_slice(&[4, 0]); // address_size, segment_selector_size
+    }
+    unit.extend_from_slice(&(header.len() as u32).to_le_bytes());
+    unit.extend(header);
+    unit.extend(program);
+    let mut section = (unit.len() as u32).to_le_bytes().to_vec();
+    section.extend(unit);
+    section
+}
+
+/// Runs `COVERAGE_TEST_WAT` instrumented for coverage and builds its
+/// coverage report for the module extended by the custom sections built by
+/// `debug_sections` from the code section relative offsets of its basic
+/// blocks.
+fn coverage_report_with_debug_sections(
+    debug_sections: impl Fn(&[u64]) -> Vec<(&'static str, Vec<u8>)>,
+) -> Result<CoverageReport, String> {
+    let config = EmbeddersConfig {
+        coverage_instrumentation: FlagStatus::Enabled,
+        ..EmbeddersConfig::default()
+    };
+    let mut instance = WasmtimeInstanceBuilder::new()
+        .with_config(config)
+        .with_wat(COVERAGE_TEST_WAT)
+        .with_num_instructions(NumInstructions::new(10_000))
+        .build();
+    let res = instance.run(func_ref("test")).unwrap();
+
+    let mut wasm = wat::parse_str(COVERAGE_TEST_WAT).unwrap();
+    let offsets: Vec<_> = CoverageReport::new(&wasm, &res.exported_globals)
+        .unwrap()
+        .functions
+        .iter()
+        .flat_map(|function| function.blocks.iter().map(|block| block.offset))
+        .collect();
+    for (name, data) in debug_sections(&offsets) {
+        append_custom_section(&mut wasm, name, &data);
+    }
+    CoverageReport::new(&wasm, &res.exported_globals)
+}
+
+/// The lines of the blocks of `COVERAGE_TEST_WAT`: `inc` at line 2, `unused`
+/// at line 5 and the blocks of `test` at lines 8 to 12, with lines 9 and 10
+/// both belonging to the loop body.
+fn coverage_test_rows(offsets: &[u64]) -> Vec<(u64, i64)> {
+    vec![
+        (offsets[0], 2),
+        (offsets[1], 5),
+        (offsets[2], 8),
+        (offsets[3], 9),
+        (offsets[3], 10),
+        (offsets[4], 11),
+        (offsets[5], 12),
+    ]
+}
+
+fn assert_coverage_test_lines(report: &CoverageReport) {
+    let locations: Vec<_> = report
+        .functions
+        .iter()
+        .map(|function| (function.name.as_str(), function.location.clone()))
+        .collect();
+    let location = |line| Some(("src/lib.rs".to_string(), line));
+    assert_eq!(
+        locations,
+        vec![
+            ("inc", location(2)),
+            ("unused", location(5)),
+            ("test", location(8)),
+        ]
+    );
+    assert_eq!(
+        report.lines,
+        BTreeMap::from([(
+            "src/lib.rs".to_string(),
+            BTreeMap::from([(2, 5), (5, 0), (8, 1), (9, 5), (10, 5), (11, 1), (12, 1)])
+        )])
+    );
+    assert_eq!(
+        report.to_lcov(),
+        "TN:\n\
+         SF:src/lib.rs\n\
+         FN:2,inc\n\
+         FN:5,unused\n\
+         FN:8,test\n\
+         FNDA:5,inc\n\
+         FNDA:0,unused\n\
+         FNDA:1,test\n\
+         FNF:3\n\
+         FNH:2\n\
+         DA:2,5\n\
+         DA:5,0\n\
+         DA:8,1\n\
+         DA:9,5\n\
+         DA:10,5\n\
+         DA:11,1\n\
+         DA:12,1\n\
+         LF:7\n\
+         LH:6\n\
+         end_of_record\n"
+    );
+}
+
+#[test]
+fn coverage_report_maps_blocks_to_dwarf_4_lines() {
+    let report = coverage_report_with_debug_sections(|offsets| {
+        // include_directories: "src"; file_names: "lib.rs" in directory 1.
+        let file_table = b"src\0\0lib.rs\0\x01\0\0\0";
+        let end = offsets.last().unwrap() + 1;
+        vec![(
+            ".debug_line",
+            debug_line_unit(4, file_table, &coverage_test_rows(offsets), end),
+        )]
+    })
+    .unwrap();
+    assert_coverage_test_lines(&report);
+}
+
+#[test]
+fn coverage_report_maps_blocks_to_dwarf_5_lines() {
+    let report = coverage_report_with_debug_sections(|offsets| {
+        let mut file_table = vec![];
+        // Directories "/build" and "src" as offsets into `.debug_line_str`
+        // (DW_LNCT_path, DW_FORM_line_strp).
+        file_table.extend_from_slice(&[1, 0x01, 0x1f, 2]);
+        file_table.extend_from_slice(&0u32.to_le_bytes());
+        file_table.extend_from_slice(&7u32.to_le_bytes());
+        // Files "main.rs" in directory 0 and "lib.rs" in directory 1
+        // (DW_LNCT_path, DW_FORM_string; DW_LNCT_directory_index, DW_FORM_udata).
+        file_table.extend_from_slice(&[2, 0x01, 0x08, 0x02, 0x0f, 2]);
+        file_table.extend_from_slice(b"main.rs\0\x00lib.rs\0\x01");
+        let end = offsets.last().unwrap() + 1;
+        vec![
+            (
+                ".debug_line",
+                debug_line_unit(5, &file_table, &coverage_test_rows(offsets), end),
+            ),
+            (".debug_line_str", b"/build\0src\0".to_vec()),
+        ]
+    })
+    .unwrap();
+    assert_coverage_test_lines(&report);
+}
+
+#[test]
+fn coverage_report_rejects_malformed_dwarf() {
+    let err = coverage_report_with_debug_sections(|offsets| {
+        let end = offsets.last().unwrap() + 1;
+        vec![(
+            ".debug_line",
+            debug_line_unit(6, &[], &coverage_test_rows(offsets), end),
+        )]
+    })
+    .unwrap_err();
+    assert!(
+        err.contains("unsupported line table version 6"),
+        "Unexpected error: {}",
+        err
+    );
+}
+
+const PROFILING_TEST_WAT: &str = r#"
+    (module
+        (func $inc (param i32) (result i32)
+            (if (i32.eqz (local.get 0))
+                (then (return (i32.const 1)))
+            )
+            (i32.add (local.get 0) (i32.const 1))
+        )
+        (func $test (export "canister_update test")
+            (local $i i32)
+            (loop $loop
+                (local.set $i (call $inc (local.get $i)))
+                (br_if $loop (i32.lt_s (local.get $i) (i32.const 5)))
+            )
+        )
+    )"#;
+
+#[test]
+fn instruction_profile_charges_call_stacks() {
+    let config = EmbeddersConfig {
+        instruction_profiling_dir: Some(std::env::temp_dir()),
+        ..EmbeddersConfig::default()
+    };
+    let mut instance = WasmtimeInstanceBuilder::new()
+        .with_config(config)
+        .with_wat(PROFILING_TEST_WAT)
+        .with_num_instructions(NumInstructions::new(10_000))
+        .build();
+    instance.run(func_ref("test")).unwrap();
+
+    let profile = instance
+        .take_instruction_profile("canister_update test")
+        .unwrap();
+    let stacks: Vec<_> = profile
+        .lines()
+        .map(|line| {
+            let (stack, count) = line.rsplit_once(' ').unwrap();
+            assert!(count.parse::<u64>().unwrap() > 0);
+            stack
+        })
+        .filter(|stack| *stack != "canister_update test")
+        .collect();
+    assert_eq!(
+        stacks,
+        vec!["canister_update test;test", "canister_update test;test;inc"]
+    );
+    // The profile is only taken once.
+    assert!(instance
+        .take_instruction_profile("canister_update test")
+        .is_none());
+}
+
+#[test]
+fn instruction_profiling_is_disabled_by_default() {
+    let mut instance = new_instance(PROFILING_TEST_WAT, 10_000);
+    instance.run(func_ref("test")).unwrap();
+    assert!(instance
+        .take_instruction_profile("canister_update test")
+        .is_none());
+}
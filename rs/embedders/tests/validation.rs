@@ -155,6 +155,28 @@ fn can_validate_valid_export_section_with_reserved_functions() {
     );
 }
 
+#[test]
+fn can_validate_export_section_with_coverage_counter_names() {
+    use ic_config::flag_status::FlagStatus;
+    let wasm = wat2wasm(
+        r#"(module
+                  (global $g (mut i64) (i64.const 0))
+                  (export "canister coverage_counter_0" (global $g)))"#,
+    )
+    .unwrap();
+    assert!(validate_wasm_binary(&wasm, &EmbeddersConfig::default()).is_ok());
+    let config = EmbeddersConfig {
+        coverage_instrumentation: FlagStatus::Enabled,
+        ..EmbeddersConfig::default()
+    };
+    assert_eq!(
+        validate_wasm_binary(&wasm, &config),
+        Err(WasmValidationError::InvalidExportSection(
+            "Exporting reserved symbol canister coverage_counter_0 not allowed.".to_string()
+        ))
+    );
+}
+
 #[test]
 fn can_validate_canister_init_with_invalid_return() {
     let wasm = wat2wasm(
@@ -940,6 +962,49 @@ fn can_validate_module_cycles_u128_related_imports() {
     );
 }
 
+#[test]
+fn can_validate_module_cost_imports() {
+    let wasm = wat2wasm(
+        r#"(module
+        (import "ic0" "cost_call" (func $ic0_cost_call (param i64 i64 i32)))
+        (import "ic0" "cost_create_canister" (func $ic0_cost_create_canister (param i32)))
+        (import "ic0" "cost_http_request" (func $ic0_cost_http_request (param i64 i64 i32)))
+        (import "ic0" "cost_sign_with_ecdsa"
+            (func $ic0_cost_sign_with_ecdsa (param i32 i32 i32 i32) (result i32)))
+        (import "ic0" "cost_sign_with_schnorr"
+            (func $ic0_cost_sign_with_schnorr (param i32 i32 i32 i32) (result i32)))
+    )"#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        validate_wasm_binary(&wasm, &EmbeddersConfig::default()),
+        Ok(WasmValidationDetails::default())
+    );
+}
+
+#[test]
+fn can_validate_module_env_var_imports() {
+    let wasm = wat2wasm(
+        r#"(module
+        (import "ic0" "env_var_count" (func $ic0_env_var_count (result i32)))
+        (import "ic0" "env_var_name_size" (func $ic0_env_var_name_size (param i32) (result i32)))
+        (import "ic0" "env_var_name_copy"
+            (func $ic0_env_var_name_copy (param i32 i32 i32 i32)))
+        (import "ic0" "env_var_value_size"
+            (func $ic0_env_var_value_size (param i32 i32) (result i32)))
+        (import "ic0" "env_var_value_copy"
+            (func $ic0_env_var_value_copy (param i32 i32 i32 i32 i32)))
+    )"#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        validate_wasm_binary(&wasm, &EmbeddersConfig::default()),
+        Ok(WasmValidationDetails::default())
+    );
+}
+
 #[test]
 fn can_validate_performance_counter_import() {
     let wasm = wat2wasm(
@@ -1,6 +1,8 @@
 use crate::as_round_instructions;
 use crate::canister_settings::{validate_canister_settings, ValidatedCanisterSettings};
-use crate::execution::install_code::{validate_controller, OriginalContext};
+use crate::execution::install_code::{
+    validate_controller, validate_controller_or_operator, OriginalContext,
+};
 use crate::execution::{install::execute_install, upgrade::execute_upgrade};
 use crate::execution_environment::{
     CompilationCostHandling, RoundContext, RoundCounters, RoundLimits,
@@ -8,12 +10,14 @@ use crate::execution_environment::{
 use crate::{
     canister_settings::CanisterSettings,
     hypervisor::Hypervisor,
+    ic00_permissions::Ic00MethodPermissions,
     types::{IngressResponse, Response},
     util::GOVERNANCE_CANISTER_ID,
 };
 use ic_base_types::NumSeconds;
 use ic_config::{
-    execution_environment::MAX_NUMBER_OF_SNAPSHOTS_PER_CANISTER, flag_status::FlagStatus,
+    embedders::WASM_MAX_SIZE, execution_environment::MAX_NUMBER_OF_SNAPSHOTS_PER_CANISTER,
+    flag_status::FlagStatus,
 };
 use ic_cycles_account_manager::{CyclesAccountManager, ResourceSaturation};
 use ic_error_types::{ErrorCode, RejectCode, UserError};
@@ -22,27 +26,34 @@ use ic_interfaces::execution_environment::{
 };
 use ic_logger::{error, fatal, info, ReplicaLogger};
 use ic_management_canister_types::{
-    CanisterChangeDetails, CanisterChangeOrigin, CanisterInstallModeV2, CanisterSnapshotResponse,
+    self as ic00, CanisterChangeDetails, CanisterChangeOrigin, CanisterInstallModeV2,
+    CanisterSnapshotDataKind, CanisterSnapshotDataOffset, CanisterSnapshotResponse,
     CanisterStatusResultV2, CanisterStatusType, ChunkHash, InstallChunkedCodeArgs,
-    InstallCodeArgsV2, Method as Ic00Method, StoredChunksReply, UploadChunkReply,
+    InstallCodeArgsV2, Method as Ic00Method, ReadCanisterSnapshotDataResponse,
+    ReadCanisterSnapshotMetadataResponse, StoredChunksReply, UploadCanisterSnapshotDataArgs,
+    UploadCanisterSnapshotMetadataArgs, UploadCanisterSnapshotMetadataResponse, UploadChunkReply,
+    MAX_SNAPSHOT_DATA_SLICE_SIZE_BYTES,
 };
 use ic_registry_provisional_whitelist::ProvisionalWhitelist;
 use ic_registry_subnet_type::SubnetType;
 use ic_replicated_state::{
-    canister_snapshots::{CanisterSnapshot, CanisterSnapshotError},
+    canister_snapshots::{
+        CanisterSnapshot, CanisterSnapshotError, ExecutionStateSnapshot, PageMemory, SnapshotSource,
+    },
     canister_state::{
         execution_state::Memory,
         system_state::{
-            wasm_chunk_store::{self, WasmChunkStore},
+            wasm_chunk_store::{self, WasmChunkHash, WasmChunkStore},
             CyclesUseCase, ReservationError,
         },
-        NextExecution,
+        NextExecution, WASM_PAGE_SIZE_IN_BYTES,
     },
     metadata_state::subnet_call_context_manager::InstallCodeCallId,
-    page_map::PageAllocatorFileDescriptor,
-    CallOrigin, CanisterState, NetworkTopology, ReplicatedState, SchedulerState, SystemState,
+    page_map::{self, PageAllocatorFileDescriptor},
+    CallOrigin, CanisterState, Global, NetworkTopology, NumWasmPages, PageMap, ReplicatedState,
+    SchedulerState, SystemState,
 };
-use ic_system_api::ExecutionParameters;
+use ic_system_api::{ExecutionParameters, CERTIFIED_DATA_MAX_LENGTH};
 use ic_types::{
     ingress::{IngressState, IngressStatus},
     messages::{
@@ -52,7 +63,8 @@ use ic_types::{
     nominal_cycles::NominalCycles,
     CanisterId, CanisterTimer, ComputeAllocation, Cycles, InvalidComputeAllocationError,
     InvalidMemoryAllocationError, MemoryAllocation, NumBytes, NumInstructions, PrincipalId,
-    SnapshotId, SubnetId, Time,
+    SnapshotId, SubnetId, Time, DEFAULT_CANISTER_LOG_BUFFER_SIZE, MAX_STABLE_MEMORY_IN_BYTES,
+    MAX_WASM_MEMORY_IN_BYTES,
 };
 use ic_wasm_types::{doc_ref, AsErrorHelp, CanisterModule, ErrorHelp, WasmHash};
 use num_traits::cast::ToPrimitive;
@@ -463,6 +475,7 @@ impl CanisterManager {
             | Ok(Ic00Method::ComputeInitialIDkgDealings)
             | Ok(Ic00Method::SchnorrPublicKey)
             | Ok(Ic00Method::SignWithSchnorr)
+            | Ok(Ic00Method::VetKdPublicKey)
             // "DepositCycles" can be called by anyone however as ingress message
             // cannot carry cycles, it does not make sense to allow them from users.
             | Ok(Ic00Method::DepositCycles)
@@ -476,14 +489,16 @@ impl CanisterManager {
             | Ok(Ic00Method::BitcoinSendTransaction)
             | Ok(Ic00Method::BitcoinSendTransactionInternal)
             | Ok(Ic00Method::BitcoinGetCurrentFeePercentiles)
-            | Ok(Ic00Method::NodeMetricsHistory) => Err(UserError::new(
+            | Ok(Ic00Method::NodeMetricsHistory)
+            | Ok(Ic00Method::SubnetInfo) => Err(UserError::new(
                 ErrorCode::CanisterRejectedMessage,
                 format!("Only canisters can call ic00 method {}", method_name),
             )),
 
             // These methods are only valid if they are sent by the controller
-            // of the canister. We assume that the canister always wants to
-            // accept messages from its controller.
+            // of the canister, or by one of its operators if the method allows
+            // operators. We assume that the canister always wants to accept
+            // messages from its controller.
             Ok(Ic00Method::CanisterStatus)
             | Ok(Ic00Method::StartCanister)
             | Ok(Ic00Method::UninstallCode)
@@ -498,14 +513,22 @@ impl CanisterManager {
             | Ok(Ic00Method::TakeCanisterSnapshot)
             | Ok(Ic00Method::LoadCanisterSnapshot)
             | Ok(Ic00Method::ListCanisterSnapshots)
-            | Ok(Ic00Method::DeleteCanisterSnapshot) => {
+            | Ok(Ic00Method::DeleteCanisterSnapshot)
+            | Ok(Ic00Method::ReadCanisterSnapshotMetadata)
+            | Ok(Ic00Method::ReadCanisterSnapshotData)
+            | Ok(Ic00Method::UploadCanisterSnapshotMetadata)
+            | Ok(Ic00Method::UploadCanisterSnapshotData) => {
                 match effective_canister_id {
                     Some(canister_id) => {
                         let canister = state.canister_state(&canister_id).ok_or_else(|| UserError::new(
                             ErrorCode::CanisterNotFound,
                             format!("Canister {} not found", canister_id),
                         ))?;
-                        match canister.controllers().contains(&sender.get()) {
+                        let is_allowed_operator = canister.operators().contains(&sender.get())
+                            && method.as_ref().is_ok_and(|method| {
+                                Ic00MethodPermissions::new(*method).allow_operator_sender()
+                            });
+                        match canister.controllers().contains(&sender.get()) || is_allowed_operator {
                             true => Ok(()),
                             false => Err(UserError::new(
                                 ErrorCode::CanisterInvalidController,
@@ -572,6 +595,7 @@ impl CanisterManager {
             subnet_size,
             Cycles::zero(),
             None,
+            NumBytes::new(DEFAULT_CANISTER_LOG_BUFFER_SIZE as u64),
         )
     }
 
@@ -590,9 +614,20 @@ impl CanisterManager {
                 canister.system_state.controllers.insert(principal);
             }
         }
+        if let Some(operators) = settings.operators() {
+            canister.system_state.operators = operators.into_iter().collect();
+        }
         if let Some(compute_allocation) = settings.compute_allocation() {
             canister.scheduler_state.compute_allocation = compute_allocation;
         }
+        // Apply the log memory limit before checking the memory allocation
+        // because it changes the memory usage.
+        if let Some(log_memory_limit) = settings.log_memory_limit() {
+            canister
+                .system_state
+                .canister_log
+                .set_capacity(log_memory_limit.get() as usize);
+        }
         if let Some(memory_allocation) = settings.memory_allocation() {
             if let MemoryAllocation::Reserved(new_bytes) = memory_allocation {
                 let memory_usage = canister.memory_usage();
@@ -632,6 +667,9 @@ impl CanisterManager {
         if let Some(wasm_memory_limit) = settings.wasm_memory_limit() {
             canister.system_state.wasm_memory_limit = Some(wasm_memory_limit);
         }
+        if let Some(environment_variables) = settings.environment_variables() {
+            canister.system_state.environment_variables = environment_variables.clone();
+        }
     }
 
     /// Tries to apply the requested settings on the canister identified by
@@ -667,9 +705,12 @@ impl CanisterManager {
             subnet_size,
             canister.system_state.reserved_balance(),
             canister.system_state.reserved_balance_limit(),
+            canister.system_state.log_memory_limit(),
         )?;
 
         let is_controllers_change = validated_settings.controllers().is_some();
+        let is_operators_change = validated_settings.operators().is_some();
+        let is_environment_variables_change = validated_settings.environment_variables().is_some();
 
         let old_usage = canister.memory_usage();
         let old_mem = canister.memory_allocation().allocated_bytes(old_usage);
@@ -688,7 +729,8 @@ impl CanisterManager {
                 .saturating_sub(old_compute_allocation - new_compute_allocation);
         }
 
-        let new_usage = old_usage;
+        // A new log memory limit changes the memory usage.
+        let new_usage = canister.memory_usage();
         let new_mem = canister.memory_allocation().allocated_bytes(new_usage);
         if new_mem >= old_mem {
             // Settings were validated before so this should always succeed.
@@ -709,10 +751,26 @@ impl CanisterManager {
             let new_controllers = canister.system_state.controllers.iter().copied().collect();
             canister.system_state.add_canister_change(
                 timestamp_nanos,
-                origin,
+                origin.clone(),
                 CanisterChangeDetails::controllers_change(new_controllers),
             );
         }
+        if is_operators_change {
+            let new_operators = canister.system_state.operators.iter().copied().collect();
+            canister.system_state.add_canister_change(
+                timestamp_nanos,
+                origin.clone(),
+                CanisterChangeDetails::operators_change(new_operators),
+            );
+        }
+        if is_environment_variables_change {
+            let environment_variables_hash = canister.system_state.environment_variables.hash();
+            canister.system_state.add_canister_change(
+                timestamp_nanos,
+                origin,
+                CanisterChangeDetails::environment_variables_change(environment_variables_hash),
+            );
+        }
 
         Ok(())
     }
@@ -1060,7 +1118,11 @@ impl CanisterManager {
             Some(canister) => canister,
         };
 
-        let result = match validate_controller(&canister, stop_context.sender()) {
+        let result = match validate_controller_or_operator(
+            &canister,
+            stop_context.sender(),
+            Ic00Method::StopCanister,
+        ) {
             Err(err) => StopCanisterResult::Failure {
                 error: err,
                 cycles_to_return: stop_context.take_cycles(),
@@ -1094,7 +1156,7 @@ impl CanisterManager {
         sender: PrincipalId,
         canister: &mut CanisterState,
     ) -> Result<Vec<StopCanisterContext>, CanisterManagerError> {
-        validate_controller(canister, &sender)?;
+        validate_controller_or_operator(canister, &sender, Ic00Method::StartCanister)?;
 
         let stop_contexts = canister.system_state.start_canister();
         canister.system_state.canister_version += 1;
@@ -1112,7 +1174,7 @@ impl CanisterManager {
         // Skip the controller check if the canister itself is requesting its
         // own status, as the canister is considered in the same trust domain.
         if sender != canister.canister_id().get() {
-            validate_controller(canister, &sender)?
+            validate_controller_or_operator(canister, &sender, Ic00Method::CanisterStatus)?
         }
 
         let controller = canister.system_state.controller();
@@ -1130,6 +1192,8 @@ impl CanisterManager {
         let reserved_cycles_limit = canister.system_state.reserved_balance_limit();
         let log_visibility = canister.system_state.log_visibility.clone();
         let wasm_memory_limit = canister.system_state.wasm_memory_limit;
+        let log_memory_limit = canister.system_state.log_memory_limit();
+        let operators = canister.operators().iter().copied().collect();
 
         Ok(CanisterStatusResultV2::new(
             canister.status(),
@@ -1167,6 +1231,8 @@ impl CanisterManager {
                 .total_query_stats
                 .egress_payload_size,
             wasm_memory_limit.map(|x| x.get()),
+            log_memory_limit.get(),
+            operators,
         ))
     }
 
@@ -1393,6 +1459,8 @@ impl CanisterManager {
         let scheduler_state = SchedulerState::new(state.metadata.batch_time);
         let mut new_canister = CanisterState::new(system_state, None, scheduler_state);
 
+        let has_operators = settings.operators().is_some();
+        let has_environment_variables = settings.environment_variables().is_some();
         self.do_update_settings(settings, &mut new_canister);
         let new_usage = new_canister.memory_usage();
         let new_mem = new_canister
@@ -1419,9 +1487,30 @@ impl CanisterManager {
             .collect();
         new_canister.system_state.add_canister_change(
             state.time(),
-            origin,
+            origin.clone(),
             CanisterChangeDetails::canister_creation(controllers),
         );
+        if has_operators {
+            let operators = new_canister
+                .system_state
+                .operators
+                .iter()
+                .copied()
+                .collect();
+            new_canister.system_state.add_canister_change(
+                state.time(),
+                origin.clone(),
+                CanisterChangeDetails::operators_change(operators),
+            );
+        }
+        if has_environment_variables {
+            let environment_variables_hash = new_canister.system_state.environment_variables.hash();
+            new_canister.system_state.add_canister_change(
+                state.time(),
+                origin,
+                CanisterChangeDetails::environment_variables_change(environment_variables_hash),
+            );
+        }
 
         // Add new canister to the replicated state.
         state.put_canister_state(new_canister);
@@ -1729,8 +1818,9 @@ impl CanisterManager {
 
     /// Creates a new canister snapshot.
     ///
-    /// A canister snapshot can only be initiated by the controllers.
-    /// In addition, if the `replace_snapshot` parameter is `Some`,
+    /// A canister snapshot can only be initiated by the controllers or operators.
+    /// In addition, if the `replace_snapshot` parameter is `Some`, which only
+    /// controllers may pass,
     /// the system will attempt to identify the snapshot based on the provided ID,
     /// and delete it before creating a new one.
     /// Failure to do so will result in the creation of a new snapshot being unsuccessful.
@@ -1749,59 +1839,25 @@ impl CanisterManager {
         Result<CanisterSnapshotResponse, CanisterManagerError>,
         NumInstructions,
     ) {
-        // Check sender is a controller.
-        if let Err(err) = validate_controller(canister, &sender) {
+        // Check sender is a controller or an operator.
+        if let Err(err) =
+            validate_controller_or_operator(canister, &sender, Ic00Method::TakeCanisterSnapshot)
+        {
             return (Err(err), NumInstructions::new(0));
         };
 
-        let replace_snapshot_size = match replace_snapshot {
-            // Check that replace snapshot ID exists if provided.
-            Some(replace_snapshot) => {
-                match state.canister_snapshots.get(replace_snapshot) {
-                    None => {
-                        // If not found, the operation fails due to invalid parameters.
-                        return (
-                            Err(CanisterManagerError::CanisterSnapshotNotFound {
-                                canister_id: canister.canister_id(),
-                                snapshot_id: replace_snapshot,
-                            }),
-                            NumInstructions::new(0),
-                        );
-                    }
-                    Some(snapshot) => {
-                        // Verify the provided replacement snapshot belongs to this canister.
-                        if snapshot.canister_id() != canister.canister_id() {
-                            return (
-                                Err(CanisterManagerError::CanisterSnapshotInvalidOwnership {
-                                    canister_id: canister.canister_id(),
-                                    snapshot_id: replace_snapshot,
-                                }),
-                                NumInstructions::new(0),
-                            );
-                        }
-                        snapshot.size()
-                    }
-                }
-            }
-            // No replace snapshot ID provided, check whether the maximum number of snapshots
-            // has been reached.
-            None => {
-                if state
-                    .canister_snapshots
-                    .count_by_canister(&canister.canister_id())
-                    >= MAX_NUMBER_OF_SNAPSHOTS_PER_CANISTER
-                {
-                    return (
-                        Err(CanisterManagerError::CanisterSnapshotLimitExceeded {
-                            canister_id: canister.canister_id(),
-                            limit: MAX_NUMBER_OF_SNAPSHOTS_PER_CANISTER,
-                        }),
-                        NumInstructions::new(0),
-                    );
-                }
-                0.into()
+        // Replacing a snapshot deletes it, which only controllers may do.
+        if replace_snapshot.is_some() {
+            if let Err(err) = validate_controller(canister, &sender) {
+                return (Err(err), NumInstructions::new(0));
             }
-        };
+        }
+
+        let replace_snapshot_size =
+            match self.validate_replace_snapshot(canister, replace_snapshot, state) {
+                Ok(size) => size,
+                Err(err) => return (Err(err), NumInstructions::new(0)),
+            };
 
         if self.config.rate_limiting_of_heap_delta == FlagStatus::Enabled
             && canister.scheduler_state.heap_delta_debit >= self.config.heap_delta_rate_limit
@@ -1817,98 +1873,15 @@ impl CanisterManager {
         }
 
         let new_snapshot_size = canister.snapshot_size_bytes();
-        let new_snapshot_increase = NumBytes::from(
-            new_snapshot_size
-                .get()
-                .saturating_sub(replace_snapshot_size.get()),
-        );
-        let new_memory_usage = NumBytes::from(
-            canister
-                .memory_usage()
-                .get()
-                .saturating_add(new_snapshot_size.get())
-                .saturating_sub(replace_snapshot_size.get()),
-        );
-
-        {
-            // Run the following checks on memory usage and return an error
-            // if any fails:
-            // 1. Check new usage will not freeze canister
-            // 2. Check subnet has available memory
-            // 3. Reserve cycles on canister
-            // 4. Actually deduct memory from subnet (asserting it won't fail)
-
-            // Calculate if any cycles will need to be reserved.
-            let reservation_cycles = self.cycles_account_manager.storage_reservation_cycles(
-                new_snapshot_increase,
-                resource_saturation,
-                subnet_size,
-            );
-
-            // Memory usage will increase by the snapshot size.
-            // Check that it doesn't bump the canister over the freezing threshold.
-            let threshold = self.cycles_account_manager.freeze_threshold_cycles(
-                canister.system_state.freeze_threshold,
-                canister.memory_allocation(),
-                new_memory_usage,
-                canister.message_memory_usage(),
-                canister.compute_allocation(),
-                subnet_size,
-                canister.system_state.reserved_balance(),
-            );
-
-            if canister.system_state.balance() < threshold + reservation_cycles {
-                return (
-                    Err(CanisterManagerError::InsufficientCyclesInMemoryGrow {
-                        bytes: new_snapshot_increase,
-                        available: canister.system_state.balance(),
-                        threshold,
-                    }),
-                    NumInstructions::new(0),
-                );
-            }
-            // Verify that the subnet has enough memory for a new snapshot.
-            if let Err(err) = round_limits
-                .subnet_available_memory
-                .check_available_memory(new_snapshot_increase, NumBytes::from(0), NumBytes::from(0))
-                .map_err(
-                    |_| CanisterManagerError::SubnetMemoryCapacityOverSubscribed {
-                        requested: new_snapshot_increase,
-                        available: NumBytes::from(
-                            round_limits
-                                .subnet_available_memory
-                                .get_execution_memory()
-                                .max(0) as u64,
-                        ),
-                    },
-                )
-            {
-                return (Err(err), NumInstructions::new(0));
-            };
-            // Reserve needed cycles if the subnet is becoming saturated.
-            if let Err(err) = canister
-                .system_state
-                .reserve_cycles(reservation_cycles)
-                .map_err(|err| match err {
-                    ReservationError::InsufficientCycles {
-                        requested,
-                        available,
-                    } => CanisterManagerError::InsufficientCyclesInMemoryGrow {
-                        bytes: new_snapshot_increase,
-                        available,
-                        threshold: requested,
-                    },
-                    ReservationError::ReservedLimitExceed { requested, limit } => {
-                        CanisterManagerError::ReservedCyclesLimitExceededInMemoryGrow {
-                            bytes: new_snapshot_increase,
-                            requested,
-                            limit,
-                        }
-                    }
-                })
-            {
-                return (Err(err), NumInstructions::new(0));
-            };
+        if let Err(err) = self.reserve_snapshot_memory(
+            subnet_size,
+            canister,
+            new_snapshot_size,
+            replace_snapshot_size,
+            round_limits,
+            resource_saturation,
+        ) {
+            return (Err(err), NumInstructions::new(0));
         }
 
         // Charge for taking a snapshot of the canister.
@@ -1938,25 +1911,7 @@ impl CanisterManager {
 
         // Delete old snapshot identified by `replace_snapshot` ID.
         if let Some(replace_snapshot) = replace_snapshot {
-            state.canister_snapshots.remove(replace_snapshot);
-            canister.system_state.snapshots_memory_usage = canister
-                .system_state
-                .snapshots_memory_usage
-                .get()
-                .saturating_sub(replace_snapshot_size.get())
-                .into();
-            // Confirm that `snapshots_memory_usage` is updated correctly.
-            debug_assert_eq!(
-                canister.system_state.snapshots_memory_usage,
-                state
-                    .canister_snapshots
-                    .compute_memory_usage_by_canister(canister.canister_id()),
-            );
-            round_limits.subnet_available_memory.increment(
-                replace_snapshot_size,
-                NumBytes::from(0),
-                NumBytes::from(0),
-            );
+            self.remove_replaced_snapshot(canister, replace_snapshot, state, round_limits);
         }
 
         // Actually deduct memory from the subnet. It's safe to unwrap
@@ -2086,9 +2041,14 @@ impl CanisterManager {
 
         let (_old_execution_state, mut system_state, scheduler_state) = canister_clone.into_parts();
 
+        // The Wasm module of a snapshot being uploaded is built here, but only
+        // becomes the module of the snapshot once the load has succeeded, so
+        // that a failed load leaves the snapshot unchanged.
+        let wasm_binary = snapshot.build_wasm_module();
+        let finalize_upload = snapshot.source() == SnapshotSource::MetadataUpload;
         let (instructions_used, new_execution_state) = {
             let execution_snapshot = snapshot.execution_snapshot();
-            let new_wasm_hash = WasmHash::from(&execution_snapshot.wasm_binary);
+            let new_wasm_hash = WasmHash::from(&wasm_binary);
             let compilation_cost_handling = if state
                 .metadata
                 .expected_compiled_wasms
@@ -2100,7 +2060,7 @@ impl CanisterManager {
             };
 
             let (instructions_used, new_execution_state) = self.hypervisor.create_execution_state(
-                execution_snapshot.wasm_binary.clone(),
+                wasm_binary.clone(),
                 "NOT_USED".into(),
                 canister_id,
                 round_limits,
@@ -2167,6 +2127,12 @@ impl CanisterManager {
         state
             .canister_snapshots
             .add_restore_operation(canister_id, snapshot_id);
+        // No more data can be uploaded into an uploaded snapshot once loaded.
+        if finalize_upload {
+            // Already confirmed above that the snapshot exists.
+            Arc::make_mut(state.canister_snapshots.get_mut(snapshot_id).unwrap())
+                .finalize_upload(wasm_binary);
+        }
 
         if self.config.rate_limiting_of_heap_delta == FlagStatus::Enabled {
             new_canister.scheduler_state.heap_delta_debit = new_canister
@@ -2185,15 +2151,16 @@ impl CanisterManager {
     /// Returns the canister snapshots list, or
     /// an error if it failed to retrieve the information.
     ///
-    /// Retrieving the canister snapshots list can only be initiated by the controllers.
+    /// Retrieving the canister snapshots list can only be initiated by the controllers
+    /// or operators.
     pub(crate) fn list_canister_snapshot(
         &self,
         sender: PrincipalId,
         canister: &CanisterState,
         state: &ReplicatedState,
     ) -> Result<Vec<CanisterSnapshotResponse>, CanisterManagerError> {
-        // Check sender is a controller.
-        validate_controller(canister, &sender)?;
+        // Check sender is a controller or an operator.
+        validate_controller_or_operator(canister, &sender, Ic00Method::ListCanisterSnapshots)?;
 
         let mut responses = vec![];
         for (snapshot_id, snapshot) in state
@@ -2267,6 +2234,730 @@ impl CanisterManager {
         );
         Ok(())
     }
+
+    /// Returns the metadata of the specified canister snapshot, or
+    /// an error if it failed to retrieve the information.
+    ///
+    /// Reading the metadata of a canister snapshot can only be initiated by the controllers.
+    pub(crate) fn read_canister_snapshot_metadata(
+        &self,
+        sender: PrincipalId,
+        canister: &CanisterState,
+        snapshot_id: SnapshotId,
+        state: &ReplicatedState,
+    ) -> Result<ReadCanisterSnapshotMetadataResponse, CanisterManagerError> {
+        // Check sender is a controller.
+        validate_controller(canister, &sender)?;
+
+        let snapshot = self.get_canister_snapshot(canister.canister_id(), snapshot_id, state)?;
+        Ok(ReadCanisterSnapshotMetadataResponse {
+            taken_at_timestamp: snapshot.taken_at_timestamp().as_nanos_since_unix_epoch(),
+            canister_version: snapshot.canister_version(),
+            wasm_module_size: snapshot.wasm_module_size() as u64,
+            exported_globals: snapshot
+                .exported_globals()
+                .iter()
+                .map(ic00::Global::from)
+                .collect(),
+            wasm_memory_size: (snapshot.wasm_memory().size.get() * WASM_PAGE_SIZE_IN_BYTES) as u64,
+            stable_memory_size: (snapshot.stable_memory().size.get() * WASM_PAGE_SIZE_IN_BYTES)
+                as u64,
+            wasm_chunk_store: snapshot
+                .chunk_store()
+                .keys()
+                .map(|k| ChunkHash { hash: k.to_vec() })
+                .collect(),
+            certified_data: snapshot.certified_data().clone(),
+        })
+    }
+
+    /// Returns a slice of the data of the specified canister snapshot, or
+    /// an error if it failed to retrieve the data.
+    ///
+    /// Reading the data of a canister snapshot can only be initiated by the controllers.
+    /// The canister is charged for the number of bytes returned.
+    pub(crate) fn read_canister_snapshot_data(
+        &self,
+        subnet_size: usize,
+        sender: PrincipalId,
+        canister: &mut CanisterState,
+        snapshot_id: SnapshotId,
+        kind: CanisterSnapshotDataKind,
+        state: &ReplicatedState,
+    ) -> (
+        Result<ReadCanisterSnapshotDataResponse, CanisterManagerError>,
+        NumInstructions,
+    ) {
+        // Check sender is a controller.
+        if let Err(err) = validate_controller(canister, &sender) {
+            return (Err(err), NumInstructions::new(0));
+        }
+
+        let snapshot = match self.get_canister_snapshot(canister.canister_id(), snapshot_id, state)
+        {
+            Ok(snapshot) => snapshot,
+            Err(err) => return (Err(err), NumInstructions::new(0)),
+        };
+
+        let chunk = match kind {
+            CanisterSnapshotDataKind::WasmModule { offset, size } => {
+                match validate_snapshot_data_slice(offset, size, snapshot.wasm_module_size() as u64)
+                {
+                    Ok(()) => snapshot.read_wasm_module(offset as usize, size as usize),
+                    Err(err) => return (Err(err), NumInstructions::new(0)),
+                }
+            }
+            CanisterSnapshotDataKind::MainMemory { offset, size } => {
+                match read_snapshot_memory(snapshot.wasm_memory(), offset, size) {
+                    Ok(chunk) => chunk,
+                    Err(err) => return (Err(err), NumInstructions::new(0)),
+                }
+            }
+            CanisterSnapshotDataKind::StableMemory { offset, size } => {
+                match read_snapshot_memory(snapshot.stable_memory(), offset, size) {
+                    Ok(chunk) => chunk,
+                    Err(err) => return (Err(err), NumInstructions::new(0)),
+                }
+            }
+            CanisterSnapshotDataKind::WasmChunk { hash } => {
+                let chunk = <[u8; 32]>::try_from(hash.as_slice())
+                    .ok()
+                    .and_then(|hash| snapshot.chunk_store().get_chunk_data(&hash))
+                    .map(|pages| pages.flatten().copied().collect::<Vec<u8>>());
+                match chunk {
+                    Some(chunk) => chunk,
+                    None => {
+                        return (
+                            Err(CanisterManagerError::CanisterSnapshotInvalidData {
+                                message: format!(
+                                    "Wasm chunk with hash {} not found in snapshot {}",
+                                    hex::encode(&hash),
+                                    snapshot_id
+                                ),
+                            }),
+                            NumInstructions::new(0),
+                        );
+                    }
+                }
+            }
+        };
+
+        // Charge for reading the snapshot data.
+        let instructions = NumInstructions::from(chunk.len() as u64);
+        if let Err(err) = self.cycles_account_manager.consume_cycles_for_instructions(
+            &sender,
+            canister,
+            instructions,
+            subnet_size,
+        ) {
+            return (
+                Err(CanisterManagerError::CanisterSnapshotNotEnoughCycles(err)),
+                NumInstructions::new(0),
+            );
+        };
+
+        (Ok(ReadCanisterSnapshotDataResponse { chunk }), instructions)
+    }
+
+    /// Creates a new, empty canister snapshot from the provided metadata.
+    ///
+    /// The contents of the snapshot are filled in by subsequent calls to
+    /// `upload_canister_snapshot_data`. As with `take_canister_snapshot`,
+    /// only the controllers can upload a snapshot and, if `replace_snapshot`
+    /// is provided, the referenced snapshot is deleted.
+    pub(crate) fn upload_canister_snapshot_metadata(
+        &self,
+        subnet_size: usize,
+        sender: PrincipalId,
+        canister: &mut CanisterState,
+        args: &UploadCanisterSnapshotMetadataArgs,
+        state: &mut ReplicatedState,
+        round_limits: &mut RoundLimits,
+        resource_saturation: &ResourceSaturation,
+    ) -> (
+        Result<UploadCanisterSnapshotMetadataResponse, CanisterManagerError>,
+        NumInstructions,
+    ) {
+        // Check sender is a controller.
+        if let Err(err) = validate_controller(canister, &sender) {
+            return (Err(err), NumInstructions::new(0));
+        }
+
+        let replace_snapshot = args.replace_snapshot();
+        let replace_snapshot_size =
+            match self.validate_replace_snapshot(canister, replace_snapshot, state) {
+                Ok(size) => size,
+                Err(err) => return (Err(err), NumInstructions::new(0)),
+            };
+
+        let (wasm_memory_pages, stable_memory_pages, declared_wasm_chunk_hashes) =
+            match self.validate_snapshot_metadata(args) {
+                Ok(metadata) => metadata,
+                Err(err) => return (Err(err), NumInstructions::new(0)),
+            };
+
+        let new_snapshot_size = NumBytes::from(
+            args.wasm_module_size
+                + args.wasm_memory_size
+                + args.stable_memory_size
+                + 8 * args.exported_globals.len() as u64
+                + args.wasm_chunk_store.len() as u64 * wasm_chunk_store::chunk_size().get()
+                + args.certified_data.len() as u64,
+        );
+        if let Err(err) = self.reserve_snapshot_memory(
+            subnet_size,
+            canister,
+            new_snapshot_size,
+            replace_snapshot_size,
+            round_limits,
+            resource_saturation,
+        ) {
+            return (Err(err), NumInstructions::new(0));
+        }
+
+        // Charge for uploading the snapshot metadata.
+        let instructions = self.config.canister_snapshot_baseline_instructions;
+        if let Err(err) = self.cycles_account_manager.consume_cycles_for_instructions(
+            &sender,
+            canister,
+            instructions,
+            subnet_size,
+        ) {
+            return (
+                Err(CanisterManagerError::CanisterSnapshotNotEnoughCycles(err)),
+                0.into(),
+            );
+        };
+
+        // The Wasm module is written into a separate buffer and only built when the
+        // snapshot is loaded or written to a checkpoint.
+        let execution_snapshot = ExecutionStateSnapshot {
+            wasm_binary: CanisterModule::new(vec![]),
+            exported_globals: args.exported_globals.iter().map(Global::from).collect(),
+            stable_memory: PageMemory {
+                page_map: PageMap::new(Arc::clone(&self.fd_factory)),
+                size: stable_memory_pages,
+            },
+            wasm_memory: PageMemory {
+                page_map: PageMap::new(Arc::clone(&self.fd_factory)),
+                size: wasm_memory_pages,
+            },
+        };
+        let mut new_snapshot = CanisterSnapshot::new(
+            SnapshotSource::MetadataUpload,
+            canister.canister_id(),
+            state.time(),
+            canister.system_state.canister_version,
+            args.certified_data.clone(),
+            WasmChunkStore::new(Arc::clone(&self.fd_factory)),
+            execution_snapshot,
+            new_snapshot_size,
+        );
+        new_snapshot
+            .start_wasm_module_upload(args.wasm_module_size as usize, Arc::clone(&self.fd_factory));
+        new_snapshot.set_declared_wasm_chunk_hashes(declared_wasm_chunk_hashes);
+
+        // Delete old snapshot identified by `replace_snapshot` ID.
+        if let Some(replace_snapshot) = replace_snapshot {
+            self.remove_replaced_snapshot(canister, replace_snapshot, state, round_limits);
+        }
+
+        // Actually deduct memory from the subnet. It's safe to unwrap
+        // here because we already checked the available memory above.
+        round_limits.subnet_available_memory
+            .try_decrement(new_snapshot_size, NumBytes::from(0), NumBytes::from(0))
+            .expect("Error: Cannot fail to decrement SubnetAvailableMemory after checking for availability");
+
+        let snapshot_id =
+            SnapshotId::from((canister.canister_id(), canister.new_local_snapshot_id()));
+        state
+            .canister_snapshots
+            .push_uploaded(snapshot_id, Arc::new(new_snapshot));
+        canister.system_state.snapshots_memory_usage = canister
+            .system_state
+            .snapshots_memory_usage
+            .saturating_add(&new_snapshot_size);
+        (
+            Ok(UploadCanisterSnapshotMetadataResponse::new(&snapshot_id)),
+            instructions,
+        )
+    }
+
+    /// Writes a slice of data into the specified canister snapshot.
+    ///
+    /// Uploading data into a canister snapshot can only be initiated by the controllers.
+    /// The data must fit into the layout declared by the snapshot metadata and
+    /// the canister is charged for the number of bytes uploaded.
+    pub(crate) fn upload_canister_snapshot_data(
+        &self,
+        subnet_size: usize,
+        sender: PrincipalId,
+        canister: &mut CanisterState,
+        args: &UploadCanisterSnapshotDataArgs,
+        state: &mut ReplicatedState,
+    ) -> (Result<(), CanisterManagerError>, NumInstructions) {
+        // Check sender is a controller.
+        if let Err(err) = validate_controller(canister, &sender) {
+            return (Err(err), NumInstructions::new(0));
+        }
+
+        if self.config.rate_limiting_of_heap_delta == FlagStatus::Enabled
+            && canister.scheduler_state.heap_delta_debit >= self.config.heap_delta_rate_limit
+        {
+            return (
+                Err(CanisterManagerError::CanisterHeapDeltaRateLimited {
+                    canister_id: canister.canister_id(),
+                    value: canister.scheduler_state.heap_delta_debit,
+                    limit: self.config.heap_delta_rate_limit,
+                }),
+                NumInstructions::new(0),
+            );
+        }
+
+        let snapshot_id = args.get_snapshot_id();
+        let snapshot = match self.get_canister_snapshot(canister.canister_id(), snapshot_id, state)
+        {
+            Ok(snapshot) => snapshot,
+            Err(err) => return (Err(err), NumInstructions::new(0)),
+        };
+
+        // Data can only be uploaded into snapshots created from uploaded
+        // metadata that were not loaded yet.
+        if snapshot.source() != SnapshotSource::MetadataUpload {
+            return (
+                Err(CanisterManagerError::CanisterSnapshotInvalidData {
+                    message: format!(
+                        "Snapshot {} does not accept data: only snapshots created by uploading metadata and not loaded yet do",
+                        snapshot_id
+                    ),
+                }),
+                NumInstructions::new(0),
+            );
+        }
+
+        let chunk_len = args.chunk.len() as u64;
+        let validation = match args.kind {
+            CanisterSnapshotDataOffset::WasmModule { offset } => {
+                validate_snapshot_data_slice(offset, chunk_len, snapshot.wasm_module_size() as u64)
+            }
+            CanisterSnapshotDataOffset::MainMemory { offset } => validate_snapshot_data_slice(
+                offset,
+                chunk_len,
+                (snapshot.wasm_memory().size.get() * WASM_PAGE_SIZE_IN_BYTES) as u64,
+            ),
+            CanisterSnapshotDataOffset::StableMemory { offset } => validate_snapshot_data_slice(
+                offset,
+                chunk_len,
+                (snapshot.stable_memory().size.get() * WASM_PAGE_SIZE_IN_BYTES) as u64,
+            ),
+            CanisterSnapshotDataOffset::WasmChunk => {
+                // Only the chunks declared in the metadata may be uploaded and
+                // the chunk store may only grow up to the declared size.
+                let hash = ic_crypto_sha2::Sha256::hash(&args.chunk);
+                let declared_chunk_store_size = snapshot.size().get().saturating_sub(
+                    (snapshot.wasm_module_size()
+                        + snapshot.wasm_memory().size.get() * WASM_PAGE_SIZE_IN_BYTES
+                        + snapshot.stable_memory().size.get() * WASM_PAGE_SIZE_IN_BYTES
+                        + 8 * snapshot.exported_globals().len()
+                        + snapshot.certified_data().len()) as u64,
+                );
+                let new_chunk_store_size = snapshot.chunk_store().memory_usage().get()
+                    + wasm_chunk_store::chunk_size().get();
+                if !snapshot.declared_wasm_chunk_hashes().contains(&hash) {
+                    Err(CanisterManagerError::CanisterSnapshotInvalidData {
+                        message: format!(
+                            "Wasm chunk with hash {} was not declared in the metadata of snapshot {}",
+                            hex::encode(hash),
+                            snapshot_id
+                        ),
+                    })
+                } else if new_chunk_store_size > declared_chunk_store_size {
+                    Err(CanisterManagerError::CanisterSnapshotInvalidData {
+                        message: format!("Wasm chunk store of snapshot {} is full", snapshot_id),
+                    })
+                } else {
+                    snapshot
+                        .chunk_store()
+                        .can_insert_chunk(self.config.wasm_chunk_store_max_size, &args.chunk)
+                        .map_err(
+                            |message| CanisterManagerError::CanisterSnapshotInvalidData { message },
+                        )
+                }
+            }
+        };
+        if let Err(err) = validation {
+            return (Err(err), NumInstructions::new(0));
+        }
+
+        // Charge for uploading the snapshot data.
+        let instructions = NumInstructions::from(chunk_len);
+        if let Err(err) = self.cycles_account_manager.consume_cycles_for_instructions(
+            &sender,
+            canister,
+            instructions,
+            subnet_size,
+        ) {
+            return (
+                Err(CanisterManagerError::CanisterSnapshotNotEnoughCycles(err)),
+                NumInstructions::new(0),
+            );
+        };
+
+        // Already confirmed above that the snapshot exists.
+        let snapshot = Arc::make_mut(state.canister_snapshots.get_mut(snapshot_id).unwrap());
+        match args.kind {
+            CanisterSnapshotDataOffset::WasmModule { offset } => {
+                snapshot.write_wasm_module(
+                    offset as usize,
+                    &args.chunk,
+                    Arc::clone(&self.fd_factory),
+                );
+            }
+            CanisterSnapshotDataOffset::MainMemory { offset } => {
+                write_snapshot_memory(
+                    &mut snapshot.execution_snapshot_mut().wasm_memory,
+                    offset,
+                    &args.chunk,
+                );
+            }
+            CanisterSnapshotDataOffset::StableMemory { offset } => {
+                write_snapshot_memory(
+                    &mut snapshot.execution_snapshot_mut().stable_memory,
+                    offset,
+                    &args.chunk,
+                );
+            }
+            CanisterSnapshotDataOffset::WasmChunk => {
+                // We checked above that this chunk can be inserted, so the
+                // unwrap here is guaranteed to succeed.
+                snapshot
+                    .chunk_store_mut()
+                    .insert_chunk(self.config.wasm_chunk_store_max_size, &args.chunk)
+                    .expect("Error: Insert chunk cannot fail after checking `can_insert_chunk`");
+            }
+        }
+        state
+            .canister_snapshots
+            .add_upload_data_operation(canister.canister_id(), snapshot_id);
+
+        let heap_delta = NumBytes::from(chunk_len);
+        if self.config.rate_limiting_of_heap_delta == FlagStatus::Enabled {
+            canister.scheduler_state.heap_delta_debit = canister
+                .scheduler_state
+                .heap_delta_debit
+                .saturating_add(&heap_delta);
+        }
+        state.metadata.heap_delta_estimate = state
+            .metadata
+            .heap_delta_estimate
+            .saturating_add(&heap_delta);
+
+        (Ok(()), instructions)
+    }
+
+    /// Returns the snapshot identified by `snapshot_id` if it exists and
+    /// belongs to the given canister.
+    fn get_canister_snapshot<'a>(
+        &self,
+        canister_id: CanisterId,
+        snapshot_id: SnapshotId,
+        state: &'a ReplicatedState,
+    ) -> Result<&'a Arc<CanisterSnapshot>, CanisterManagerError> {
+        match state.canister_snapshots.get(snapshot_id) {
+            // If not found, the operation fails due to invalid parameters.
+            None => Err(CanisterManagerError::CanisterSnapshotNotFound {
+                canister_id,
+                snapshot_id,
+            }),
+            Some(snapshot) => {
+                // Verify the provided snapshot id belongs to this canister.
+                if snapshot.canister_id() != canister_id {
+                    return Err(CanisterManagerError::CanisterSnapshotInvalidOwnership {
+                        canister_id,
+                        snapshot_id,
+                    });
+                }
+                Ok(snapshot)
+            }
+        }
+    }
+
+    /// Checks that the snapshot to be replaced, if any, exists and belongs to
+    /// the canister. Otherwise, checks that the canister has not reached the
+    /// maximum number of snapshots.
+    ///
+    /// Returns the size of the snapshot to be replaced.
+    fn validate_replace_snapshot(
+        &self,
+        canister: &CanisterState,
+        replace_snapshot: Option<SnapshotId>,
+        state: &ReplicatedState,
+    ) -> Result<NumBytes, CanisterManagerError> {
+        match replace_snapshot {
+            // Check that replace snapshot ID exists if provided.
+            Some(replace_snapshot) => self
+                .get_canister_snapshot(canister.canister_id(), replace_snapshot, state)
+                .map(|snapshot| snapshot.size()),
+            // No replace snapshot ID provided, check whether the maximum number of snapshots
+            // has been reached.
+            None => {
+                if state
+                    .canister_snapshots
+                    .count_by_canister(&canister.canister_id())
+                    >= MAX_NUMBER_OF_SNAPSHOTS_PER_CANISTER
+                {
+                    return Err(CanisterManagerError::CanisterSnapshotLimitExceeded {
+                        canister_id: canister.canister_id(),
+                        limit: MAX_NUMBER_OF_SNAPSHOTS_PER_CANISTER,
+                    });
+                }
+                Ok(0.into())
+            }
+        }
+    }
+
+    /// Checks that the uploaded snapshot metadata describes a valid snapshot.
+    ///
+    /// Returns the sizes of the wasm and the stable memory in wasm pages.
+    fn validate_snapshot_metadata(
+        &self,
+        args: &UploadCanisterSnapshotMetadataArgs,
+    ) -> Result<(NumWasmPages, NumWasmPages, BTreeSet<WasmChunkHash>), CanisterManagerError> {
+        let invalid =
+            |message: String| CanisterManagerError::CanisterSnapshotInvalidData { message };
+        if args.wasm_module_size > WASM_MAX_SIZE.get() {
+            return Err(invalid(format!(
+                "Wasm module size {} exceeds the maximum of {}",
+                args.wasm_module_size, WASM_MAX_SIZE
+            )));
+        }
+        if args.wasm_memory_size > MAX_WASM_MEMORY_IN_BYTES {
+            return Err(invalid(format!(
+                "Wasm memory size {} exceeds the maximum of {}",
+                args.wasm_memory_size, MAX_WASM_MEMORY_IN_BYTES
+            )));
+        }
+        if args.stable_memory_size > MAX_STABLE_MEMORY_IN_BYTES {
+            return Err(invalid(format!(
+                "Stable memory size {} exceeds the maximum of {}",
+                args.stable_memory_size, MAX_STABLE_MEMORY_IN_BYTES
+            )));
+        }
+        let page_size = WASM_PAGE_SIZE_IN_BYTES as u64;
+        if args.wasm_memory_size % page_size != 0 || args.stable_memory_size % page_size != 0 {
+            return Err(invalid(format!(
+                "Memory sizes must be multiples of the Wasm page size {}",
+                page_size
+            )));
+        }
+        let chunk_store_size =
+            args.wasm_chunk_store.len() as u64 * wasm_chunk_store::chunk_size().get();
+        if chunk_store_size > self.config.wasm_chunk_store_max_size.get() {
+            return Err(invalid(format!(
+                "Wasm chunk store size {} exceeds the maximum of {}",
+                chunk_store_size, self.config.wasm_chunk_store_max_size
+            )));
+        }
+        let mut declared_wasm_chunk_hashes = BTreeSet::new();
+        for chunk_hash in args.wasm_chunk_store.iter() {
+            let hash = WasmChunkHash::try_from(chunk_hash.hash.as_slice()).map_err(|_| {
+                invalid(format!(
+                    "Wasm chunk hash {} must be 32 bytes long",
+                    hex::encode(&chunk_hash.hash)
+                ))
+            })?;
+            declared_wasm_chunk_hashes.insert(hash);
+        }
+        if args.certified_data.len() > CERTIFIED_DATA_MAX_LENGTH {
+            return Err(invalid(format!(
+                "Certified data length {} exceeds the maximum of {}",
+                args.certified_data.len(),
+                CERTIFIED_DATA_MAX_LENGTH
+            )));
+        }
+        Ok((
+            NumWasmPages::new((args.wasm_memory_size / page_size) as usize),
+            NumWasmPages::new((args.stable_memory_size / page_size) as usize),
+            declared_wasm_chunk_hashes,
+        ))
+    }
+
+    /// Checks that adding a snapshot of `new_snapshot_size` bytes, while
+    /// replacing one of `replace_snapshot_size` bytes, neither freezes the
+    /// canister nor exceeds the subnet memory, and reserves cycles for it.
+    fn reserve_snapshot_memory(
+        &self,
+        subnet_size: usize,
+        canister: &mut CanisterState,
+        new_snapshot_size: NumBytes,
+        replace_snapshot_size: NumBytes,
+        round_limits: &RoundLimits,
+        resource_saturation: &ResourceSaturation,
+    ) -> Result<(), CanisterManagerError> {
+        let new_snapshot_increase = NumBytes::from(
+            new_snapshot_size
+                .get()
+                .saturating_sub(replace_snapshot_size.get()),
+        );
+        let new_memory_usage = NumBytes::from(
+            canister
+                .memory_usage()
+                .get()
+                .saturating_add(new_snapshot_size.get())
+                .saturating_sub(replace_snapshot_size.get()),
+        );
+
+        // Run the following checks on memory usage and return an error
+        // if any fails:
+        // 1. Check new usage will not freeze canister
+        // 2. Check subnet has available memory
+        // 3. Reserve cycles on canister
+
+        // Calculate if any cycles will need to be reserved.
+        let reservation_cycles = self.cycles_account_manager.storage_reservation_cycles(
+            new_snapshot_increase,
+            resource_saturation,
+            subnet_size,
+        );
+
+        // Memory usage will increase by the snapshot size.
+        // Check that it doesn't bump the canister over the freezing threshold.
+        let threshold = self.cycles_account_manager.freeze_threshold_cycles(
+            canister.system_state.freeze_threshold,
+            canister.memory_allocation(),
+            new_memory_usage,
+            canister.message_memory_usage(),
+            canister.compute_allocation(),
+            subnet_size,
+            canister.system_state.reserved_balance(),
+        );
+
+        if canister.system_state.balance() < threshold + reservation_cycles {
+            return Err(CanisterManagerError::InsufficientCyclesInMemoryGrow {
+                bytes: new_snapshot_increase,
+                available: canister.system_state.balance(),
+                threshold,
+            });
+        }
+        // Verify that the subnet has enough memory for a new snapshot.
+        round_limits
+            .subnet_available_memory
+            .check_available_memory(new_snapshot_increase, NumBytes::from(0), NumBytes::from(0))
+            .map_err(
+                |_| CanisterManagerError::SubnetMemoryCapacityOverSubscribed {
+                    requested: new_snapshot_increase,
+                    available: NumBytes::from(
+                        round_limits
+                            .subnet_available_memory
+                            .get_execution_memory()
+                            .max(0) as u64,
+                    ),
+                },
+            )?;
+        // Reserve needed cycles if the subnet is becoming saturated.
+        canister
+            .system_state
+            .reserve_cycles(reservation_cycles)
+            .map_err(|err| match err {
+                ReservationError::InsufficientCycles {
+                    requested,
+                    available,
+                } => CanisterManagerError::InsufficientCyclesInMemoryGrow {
+                    bytes: new_snapshot_increase,
+                    available,
+                    threshold: requested,
+                },
+                ReservationError::ReservedLimitExceed { requested, limit } => {
+                    CanisterManagerError::ReservedCyclesLimitExceededInMemoryGrow {
+                        bytes: new_snapshot_increase,
+                        requested,
+                        limit,
+                    }
+                }
+            })
+    }
+
+    /// Deletes the snapshot identified by `replace_snapshot` and releases
+    /// the memory it used.
+    fn remove_replaced_snapshot(
+        &self,
+        canister: &mut CanisterState,
+        replace_snapshot: SnapshotId,
+        state: &mut ReplicatedState,
+        round_limits: &mut RoundLimits,
+    ) {
+        let replace_snapshot_size = state
+            .canister_snapshots
+            .remove(replace_snapshot)
+            .map_or(NumBytes::from(0), |snapshot| snapshot.size());
+        canister.system_state.snapshots_memory_usage = canister
+            .system_state
+            .snapshots_memory_usage
+            .get()
+            .saturating_sub(replace_snapshot_size.get())
+            .into();
+        // Confirm that `snapshots_memory_usage` is updated correctly.
+        debug_assert_eq!(
+            canister.system_state.snapshots_memory_usage,
+            state
+                .canister_snapshots
+                .compute_memory_usage_by_canister(canister.canister_id()),
+        );
+        round_limits.subnet_available_memory.increment(
+            replace_snapshot_size,
+            NumBytes::from(0),
+            NumBytes::from(0),
+        );
+    }
+}
+
+/// Checks that the slice `[offset, offset + size)` is within the first `len`
+/// bytes of a snapshot part and not larger than the maximum slice size.
+fn validate_snapshot_data_slice(
+    offset: u64,
+    size: u64,
+    len: u64,
+) -> Result<(), CanisterManagerError> {
+    if size > MAX_SNAPSHOT_DATA_SLICE_SIZE_BYTES {
+        return Err(CanisterManagerError::CanisterSnapshotInvalidData {
+            message: format!(
+                "Slice size {} exceeds the maximum of {}",
+                size, MAX_SNAPSHOT_DATA_SLICE_SIZE_BYTES
+            ),
+        });
+    }
+    match offset.checked_add(size) {
+        Some(end) if end <= len => Ok(()),
+        _ => Err(CanisterManagerError::CanisterSnapshotInvalidData {
+            message: format!(
+                "Slice with offset {} and size {} is out of bounds for length {}",
+                offset, size, len
+            ),
+        }),
+    }
+}
+
+fn read_snapshot_memory(
+    memory: &PageMemory,
+    offset: u64,
+    size: u64,
+) -> Result<Vec<u8>, CanisterManagerError> {
+    validate_snapshot_data_slice(
+        offset,
+        size,
+        (memory.size.get() * WASM_PAGE_SIZE_IN_BYTES) as u64,
+    )?;
+    let mut chunk = vec![0; size as usize];
+    page_map::Buffer::new(memory.page_map.clone()).read(&mut chunk, offset as usize);
+    Ok(chunk)
+}
+
+fn write_snapshot_memory(memory: &mut PageMemory, offset: u64, chunk: &[u8]) {
+    let mut buffer = page_map::Buffer::new(memory.page_map.clone());
+    buffer.write(chunk, offset as usize);
+    memory
+        .page_map
+        .update(&buffer.dirty_pages().collect::<Vec<_>>());
 }
 
 #[derive(Eq, PartialEq, Debug)]
@@ -2372,6 +3063,9 @@ pub(crate) enum CanisterManagerError {
         limit: usize,
     },
     CanisterSnapshotNotEnoughCycles(CanisterOutOfCyclesError),
+    CanisterSnapshotInvalidData {
+        message: String,
+    },
     LongExecutionAlreadyInProgress {
         canister_id: CanisterId,
     },
@@ -2542,6 +3236,12 @@ impl AsErrorHelp for CanisterManagerError {
                 suggestion: "".to_string(),
                 doc_link: "".to_string(),
             },
+            CanisterManagerError::CanisterSnapshotInvalidData { .. } => ErrorHelp::UserError {
+                suggestion:
+                    "Use the `read_canister_snapshot_metadata` API to check the snapshot layout."
+                        .to_string(),
+                doc_link: "".to_string(),
+            },
             CanisterManagerError::LongExecutionAlreadyInProgress { .. } => ErrorHelp::UserError {
                 suggestion: "Try waiting for the long execution to complete.".to_string(),
                 doc_link: doc_ref("long-execution-already-in-progress"),
@@ -2856,6 +3556,12 @@ impl From<CanisterManagerError> for UserError {
                     format!("Canister snapshotting failed with `{}`{additional_help}", err),
                 )
             }
+            CanisterSnapshotInvalidData { message } => {
+                Self::new(
+                    ErrorCode::InvalidManagementPayload,
+                    format!("Invalid canister snapshot data: {}{additional_help}", message),
+                )
+            }
             LongExecutionAlreadyInProgress { canister_id } => {
                 Self::new(
                     ErrorCode::CanisterRejectedMessage,
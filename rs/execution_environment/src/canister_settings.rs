@@ -4,10 +4,13 @@ use ic_error_types::{ErrorCode, UserError};
 use ic_interfaces::execution_environment::SubnetAvailableMemory;
 use ic_management_canister_types::{CanisterSettingsArgs, LogVisibilityV2};
 use ic_types::{
-    ComputeAllocation, Cycles, InvalidComputeAllocationError, InvalidMemoryAllocationError,
-    MemoryAllocation, PrincipalId,
+    CanisterLog, ComputeAllocation, Cycles, EnvironmentVariables, InvalidComputeAllocationError,
+    InvalidMemoryAllocationError, MemoryAllocation, PrincipalId, DEFAULT_CANISTER_LOG_BUFFER_SIZE,
+    MAX_CANISTER_LOG_BUFFER_SIZE, MAX_ENVIRONMENT_VARIABLES_COUNT,
+    MAX_ENVIRONMENT_VARIABLE_NAME_LENGTH, MAX_ENVIRONMENT_VARIABLE_VALUE_LENGTH,
 };
 use num_traits::cast::ToPrimitive;
+use std::collections::BTreeMap;
 use std::convert::TryFrom;
 
 use crate::canister_manager::CanisterManagerError;
@@ -27,9 +30,15 @@ pub(crate) struct CanisterSettings {
     pub(crate) reserved_cycles_limit: Option<Cycles>,
     pub(crate) log_visibility: Option<LogVisibilityV2>,
     pub(crate) wasm_memory_limit: Option<NumBytes>,
+    /// Size of the canister log buffer.
+    pub(crate) log_memory_limit: Option<NumBytes>,
+    pub(crate) environment_variables: Option<EnvironmentVariables>,
+    /// Principals allowed to start, stop and snapshot the canister.
+    pub(crate) operators: Option<Vec<PrincipalId>>,
 }
 
 impl CanisterSettings {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         controllers: Option<Vec<PrincipalId>>,
         compute_allocation: Option<ComputeAllocation>,
@@ -39,6 +48,9 @@ impl CanisterSettings {
         reserved_cycles_limit: Option<Cycles>,
         log_visibility: Option<LogVisibilityV2>,
         wasm_memory_limit: Option<NumBytes>,
+        log_memory_limit: Option<NumBytes>,
+        environment_variables: Option<EnvironmentVariables>,
+        operators: Option<Vec<PrincipalId>>,
     ) -> Self {
         Self {
             controllers,
@@ -49,6 +61,9 @@ impl CanisterSettings {
             reserved_cycles_limit,
             log_visibility,
             wasm_memory_limit,
+            log_memory_limit,
+            environment_variables,
+            operators,
         }
     }
 
@@ -83,6 +98,18 @@ impl CanisterSettings {
     pub fn wasm_memory_limit(&self) -> Option<NumBytes> {
         self.wasm_memory_limit
     }
+
+    pub fn log_memory_limit(&self) -> Option<NumBytes> {
+        self.log_memory_limit
+    }
+
+    pub fn environment_variables(&self) -> Option<&EnvironmentVariables> {
+        self.environment_variables.as_ref()
+    }
+
+    pub fn operators(&self) -> Option<Vec<PrincipalId>> {
+        self.operators.clone()
+    }
 }
 
 impl TryFrom<CanisterSettingsArgs> for CanisterSettings {
@@ -146,6 +173,56 @@ impl TryFrom<CanisterSettingsArgs> for CanisterSettings {
             None => None,
         };
 
+        let log_memory_limit = match input.log_memory_limit {
+            Some(limit) => {
+                let limit = limit
+                    .0
+                    .to_u64()
+                    .ok_or(UpdateSettingsError::LogMemoryLimitOutOfRange { provided: limit })?;
+                if limit < DEFAULT_CANISTER_LOG_BUFFER_SIZE as u64
+                    || limit > MAX_CANISTER_LOG_BUFFER_SIZE as u64
+                {
+                    return Err(UpdateSettingsError::LogMemoryLimitOutOfRange {
+                        provided: limit.into(),
+                    });
+                }
+                Some(NumBytes::new(limit))
+            }
+            None => None,
+        };
+
+        let environment_variables = match input.environment_variables {
+            Some(variables) => {
+                if variables.len() > MAX_ENVIRONMENT_VARIABLES_COUNT {
+                    return Err(UpdateSettingsError::TooManyEnvironmentVariables {
+                        provided: variables.len(),
+                    });
+                }
+                let mut environment_variables = BTreeMap::new();
+                for variable in variables {
+                    if variable.name.len() > MAX_ENVIRONMENT_VARIABLE_NAME_LENGTH {
+                        return Err(UpdateSettingsError::EnvironmentVariableNameTooLong {
+                            length: variable.name.len(),
+                        });
+                    }
+                    if variable.value.len() > MAX_ENVIRONMENT_VARIABLE_VALUE_LENGTH {
+                        return Err(UpdateSettingsError::EnvironmentVariableValueTooLong {
+                            name: variable.name,
+                            length: variable.value.len(),
+                        });
+                    }
+                    if environment_variables.contains_key(&variable.name) {
+                        return Err(UpdateSettingsError::DuplicateEnvironmentVariable {
+                            name: variable.name,
+                        });
+                    }
+                    environment_variables.insert(variable.name, variable.value);
+                }
+                Some(EnvironmentVariables::new(environment_variables))
+            }
+            None => None,
+        };
+
         Ok(CanisterSettings::new(
             input
                 .controllers
@@ -157,6 +234,9 @@ impl TryFrom<CanisterSettingsArgs> for CanisterSettings {
             reserved_cycles_limit,
             input.log_visibility,
             wasm_memory_limit,
+            log_memory_limit,
+            environment_variables,
+            input.operators.map(|operators| operators.get().clone()),
         ))
     }
 }
@@ -181,6 +261,9 @@ pub(crate) struct CanisterSettingsBuilder {
     reserved_cycles_limit: Option<Cycles>,
     log_visibility: Option<LogVisibilityV2>,
     wasm_memory_limit: Option<NumBytes>,
+    log_memory_limit: Option<NumBytes>,
+    environment_variables: Option<EnvironmentVariables>,
+    operators: Option<Vec<PrincipalId>>,
 }
 
 #[allow(dead_code)]
@@ -195,6 +278,9 @@ impl CanisterSettingsBuilder {
             reserved_cycles_limit: None,
             log_visibility: None,
             wasm_memory_limit: None,
+            log_memory_limit: None,
+            environment_variables: None,
+            operators: None,
         }
     }
 
@@ -208,6 +294,9 @@ impl CanisterSettingsBuilder {
             reserved_cycles_limit: self.reserved_cycles_limit,
             log_visibility: self.log_visibility,
             wasm_memory_limit: self.wasm_memory_limit,
+            log_memory_limit: self.log_memory_limit,
+            environment_variables: self.environment_variables,
+            operators: self.operators,
         }
     }
 
@@ -266,6 +355,27 @@ impl CanisterSettingsBuilder {
             ..self
         }
     }
+
+    pub fn with_log_memory_limit(self, log_memory_limit: NumBytes) -> Self {
+        Self {
+            log_memory_limit: Some(log_memory_limit),
+            ..self
+        }
+    }
+
+    pub fn with_environment_variables(self, environment_variables: EnvironmentVariables) -> Self {
+        Self {
+            environment_variables: Some(environment_variables),
+            ..self
+        }
+    }
+
+    pub fn with_operators(self, operators: Vec<PrincipalId>) -> Self {
+        Self {
+            operators: Some(operators),
+            ..self
+        }
+    }
 }
 
 pub enum UpdateSettingsError {
@@ -275,6 +385,11 @@ pub enum UpdateSettingsError {
     ReservedCyclesLimitOutOfRange { provided: candid::Nat },
     WasmMemoryLimitOutOfRange { provided: candid::Nat },
     WasmMemoryThresholdOutOfRange { provided: candid::Nat },
+    LogMemoryLimitOutOfRange { provided: candid::Nat },
+    TooManyEnvironmentVariables { provided: usize },
+    EnvironmentVariableNameTooLong { length: usize },
+    EnvironmentVariableValueTooLong { name: String, length: usize },
+    DuplicateEnvironmentVariable { name: String },
 }
 
 impl From<UpdateSettingsError> for UserError {
@@ -324,6 +439,40 @@ impl From<UpdateSettingsError> for UserError {
                     provided
                 ),
             ),
+            UpdateSettingsError::LogMemoryLimitOutOfRange { provided } => UserError::new(
+                ErrorCode::CanisterContractViolation,
+                format!(
+                    "Log memory limit expected to be in the range of [{}..{}], got {}",
+                    DEFAULT_CANISTER_LOG_BUFFER_SIZE, MAX_CANISTER_LOG_BUFFER_SIZE, provided
+                ),
+            ),
+            UpdateSettingsError::TooManyEnvironmentVariables { provided } => UserError::new(
+                ErrorCode::CanisterContractViolation,
+                format!(
+                    "Too many environment variables: {} provided, at most {} are allowed",
+                    provided, MAX_ENVIRONMENT_VARIABLES_COUNT
+                ),
+            ),
+            UpdateSettingsError::EnvironmentVariableNameTooLong { length } => UserError::new(
+                ErrorCode::CanisterContractViolation,
+                format!(
+                    "Environment variable name is {} bytes long, at most {} bytes are allowed",
+                    length, MAX_ENVIRONMENT_VARIABLE_NAME_LENGTH
+                ),
+            ),
+            UpdateSettingsError::EnvironmentVariableValueTooLong { name, length } => {
+                UserError::new(
+                    ErrorCode::CanisterContractViolation,
+                    format!(
+                        "Value of environment variable {} is {} bytes long, at most {} bytes are allowed",
+                        name, length, MAX_ENVIRONMENT_VARIABLE_VALUE_LENGTH
+                    ),
+                )
+            }
+            UpdateSettingsError::DuplicateEnvironmentVariable { name } => UserError::new(
+                ErrorCode::CanisterContractViolation,
+                format!("Environment variable {} is specified more than once", name),
+            ),
         }
     }
 }
@@ -350,6 +499,9 @@ pub(crate) struct ValidatedCanisterSettings {
     reservation_cycles: Cycles,
     log_visibility: Option<LogVisibilityV2>,
     wasm_memory_limit: Option<NumBytes>,
+    log_memory_limit: Option<NumBytes>,
+    environment_variables: Option<EnvironmentVariables>,
+    operators: Option<Vec<PrincipalId>>,
 }
 
 impl ValidatedCanisterSettings {
@@ -388,6 +540,18 @@ impl ValidatedCanisterSettings {
     pub fn wasm_memory_limit(&self) -> Option<NumBytes> {
         self.wasm_memory_limit
     }
+
+    pub fn log_memory_limit(&self) -> Option<NumBytes> {
+        self.log_memory_limit
+    }
+
+    pub fn environment_variables(&self) -> Option<&EnvironmentVariables> {
+        self.environment_variables.as_ref()
+    }
+
+    pub fn operators(&self) -> Option<Vec<PrincipalId>> {
+        self.operators.clone()
+    }
 }
 
 /// Validates the new canisters settings:
@@ -401,6 +565,11 @@ impl ValidatedCanisterSettings {
 ///     - there must be enough cycles to avoid freezing the canister.
 /// - controllers:
 ///     - the number of controllers cannot exceed the given maximum.
+/// - log memory limit:
+///     - the change of the canister memory usage is subject to the same
+///       checks as a change of the memory allocation.
+///     - the new canister memory usage cannot exceed the current memory
+///       allocation if the memory allocation is not changed.
 ///
 /// Keep this function in sync with `do_update_settings()`.
 #[allow(clippy::too_many_arguments)]
@@ -421,10 +590,38 @@ pub(crate) fn validate_canister_settings(
     subnet_size: usize,
     canister_reserved_balance: Cycles,
     canister_reserved_balance_limit: Option<Cycles>,
+    canister_log_memory_limit: NumBytes,
 ) -> Result<ValidatedCanisterSettings, CanisterManagerError> {
     let old_memory_bytes = canister_memory_allocation.allocated_bytes(canister_memory_usage);
-    let new_memory_bytes = match settings.memory_allocation {
+    // The canister log buffer above its default size counts towards the
+    // memory usage, so a new log memory limit changes the memory usage.
+    let log_memory_usage = |limit: NumBytes| {
+        NumBytes::new(CanisterLog::memory_usage_for_capacity(limit.get() as usize) as u64)
+    };
+    let old_canister_memory_usage = canister_memory_usage;
+    let canister_memory_usage = match settings.log_memory_limit {
         None => canister_memory_usage,
+        Some(new_log_memory_limit) => {
+            canister_memory_usage - log_memory_usage(canister_log_memory_limit)
+                + log_memory_usage(new_log_memory_limit)
+        }
+    };
+    let new_memory_bytes = match settings.memory_allocation {
+        None => {
+            // A larger log memory limit cannot grow the memory usage beyond
+            // the current memory allocation.
+            if let MemoryAllocation::Reserved(reserved_bytes) = canister_memory_allocation {
+                if canister_memory_usage > old_canister_memory_usage
+                    && reserved_bytes < canister_memory_usage
+                {
+                    return Err(CanisterManagerError::NotEnoughMemoryAllocationGiven {
+                        memory_allocation_given: canister_memory_allocation,
+                        memory_usage_needed: canister_memory_usage,
+                    });
+                }
+            }
+            canister_memory_usage
+        }
         Some(new_memory_allocation) => {
             // The new memory allocation cannot be lower than the current canister
             // memory usage.
@@ -539,6 +736,15 @@ pub(crate) fn validate_canister_settings(
                 threshold,
             });
         }
+        if canister_memory_usage > old_canister_memory_usage {
+            // Note that the error is produced only if the log memory limit
+            // increases, for the same reason as above.
+            return Err(CanisterManagerError::InsufficientCyclesInMemoryGrow {
+                bytes: canister_memory_usage - old_canister_memory_usage,
+                available: canister_cycles_balance,
+                threshold,
+            });
+        }
     }
 
     let allocated_bytes = if new_memory_bytes > old_memory_bytes {
@@ -595,5 +801,8 @@ pub(crate) fn validate_canister_settings(
         reservation_cycles,
         log_visibility: settings.log_visibility().cloned(),
         wasm_memory_limit: settings.wasm_memory_limit(),
+        log_memory_limit: settings.log_memory_limit(),
+        environment_variables: settings.environment_variables().cloned(),
+        operators: settings.operators(),
     })
 }
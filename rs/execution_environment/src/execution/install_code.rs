@@ -11,7 +11,7 @@ use ic_interfaces::execution_environment::{
 };
 use ic_logger::{error, fatal, info, warn};
 use ic_management_canister_types::{
-    CanisterChangeDetails, CanisterChangeOrigin, CanisterInstallModeV2,
+    CanisterChangeDetails, CanisterChangeOrigin, CanisterInstallModeV2, Method as Ic00Method,
 };
 use ic_replicated_state::canister_state::system_state::ReservationError;
 use ic_replicated_state::metadata_state::subnet_call_context_manager::InstallCodeCallId;
@@ -31,6 +31,7 @@ use crate::{
     },
     canister_settings::{validate_canister_settings, CanisterSettings},
     execution_environment::{log_dirty_pages, RoundContext},
+    ic00_permissions::Ic00MethodPermissions,
     CompilationCostHandling, RoundLimits,
 };
 
@@ -513,6 +514,9 @@ impl InstallCodeHelper {
                 reserved_cycles_limit: None,
                 log_visibility: None,
                 wasm_memory_limit: None,
+                log_memory_limit: None,
+                environment_variables: None,
+                operators: None,
             },
             self.canister.memory_usage(),
             self.canister.message_memory_usage(),
@@ -529,6 +533,7 @@ impl InstallCodeHelper {
             original.subnet_size,
             self.canister.system_state.reserved_balance(),
             self.canister.system_state.reserved_balance_limit(),
+            self.canister.system_state.log_memory_limit(),
         )?;
 
         match original.mode {
@@ -890,6 +895,21 @@ pub(crate) fn validate_controller(
     Ok(())
 }
 
+/// Same as `validate_controller`, but additionally accepts operators of the
+/// canister if they are allowed to call the given management method.
+pub(crate) fn validate_controller_or_operator(
+    canister: &CanisterState,
+    sender: &PrincipalId,
+    method: Ic00Method,
+) -> Result<(), CanisterManagerError> {
+    if Ic00MethodPermissions::new(method).allow_operator_sender()
+        && canister.operators().contains(sender)
+    {
+        return Ok(());
+    }
+    validate_controller(canister, sender)
+}
+
 pub(crate) fn get_wasm_hash(canister: &CanisterState) -> Option<[u8; 32]> {
     canister
         .execution_state
@@ -20,7 +20,9 @@ use candid::Encode;
 use ic_base_types::PrincipalId;
 use ic_config::execution_environment::Config as ExecutionConfig;
 use ic_config::flag_status::FlagStatus;
-use ic_crypto_utils_canister_threshold_sig::derive_threshold_public_key;
+use ic_crypto_utils_canister_threshold_sig::{
+    derive_threshold_public_key, derive_vetkd_public_key,
+};
 use ic_cycles_account_manager::{
     is_delayed_ingress_induction_cost, CyclesAccountManager, IngressInductionCost,
     ResourceSaturation,
@@ -38,9 +40,12 @@ use ic_management_canister_types::{
     EmptyBlob, InstallChunkedCodeArgs, InstallCodeArgsV2, ListCanisterSnapshotArgs,
     LoadCanisterSnapshotArgs, MasterPublicKeyId, Method as Ic00Method, NodeMetricsHistoryArgs,
     Payload as Ic00Payload, ProvisionalCreateCanisterWithCyclesArgs, ProvisionalTopUpCanisterArgs,
-    SchnorrPublicKeyArgs, SchnorrPublicKeyResponse, SetupInitialDKGArgs, SignWithECDSAArgs,
-    SignWithSchnorrArgs, StoredChunksArgs, TakeCanisterSnapshotArgs, UninstallCodeArgs,
-    UpdateSettingsArgs, UploadChunkArgs, IC_00,
+    ReadCanisterSnapshotDataArgs, ReadCanisterSnapshotMetadataArgs, SchnorrPublicKeyArgs,
+    SchnorrPublicKeyResponse, SetupInitialDKGArgs, SignWithECDSAArgs, SignWithSchnorrArgs,
+    StoredChunksArgs, SubnetInfoArgs, SubnetInfoResponse, TakeCanisterSnapshotArgs,
+    UninstallCodeArgs, UpdateSettingsArgs, UploadCanisterSnapshotDataArgs,
+    UploadCanisterSnapshotMetadataArgs, UploadChunkArgs, VetKdPublicKeyArgs, VetKdPublicKeyResult,
+    IC_00,
 };
 use ic_metrics::MetricsRegistry;
 use ic_registry_provisional_whitelist::ProvisionalWhitelist;
@@ -58,7 +63,7 @@ use ic_replicated_state::{
 };
 use ic_system_api::{ExecutionParameters, InstructionLimits};
 use ic_types::{
-    canister_http::CanisterHttpRequestContext,
+    canister_http::{CanisterHttpRequestContext, Replication},
     crypto::{
         canister_threshold_sig::{ExtendedDerivationPath, MasterPublicKey, PublicKey},
         threshold_sig::ni_dkg::NiDkgTargetId,
@@ -72,7 +77,7 @@ use ic_types::{
     },
     methods::SystemMethod,
     nominal_cycles::NominalCycles,
-    CanisterId, Cycles, NumBytes, NumInstructions, SubnetId, Time,
+    CanisterId, Cycles, NodeId, NumBytes, NumInstructions, SubnetId, Time,
 };
 use ic_types::{messages::MessageId, methods::WasmMethod};
 use ic_wasm_types::WasmHash;
@@ -909,87 +914,120 @@ impl ExecutionEnvironment {
                                 response: Err(err),
                                 refund: msg.take_cycles(),
                             },
-                            Ok(args) => match CanisterHttpRequestContext::try_from((
-                                state.time(),
-                                request.as_ref(),
-                                args,
-                            )) {
-                                Err(err) => ExecuteSubnetMessageResult::Finished {
-                                    response: Err(err.into()),
-                                    refund: msg.take_cycles(),
-                                },
-                                Ok(mut canister_http_request_context) => {
-                                    let http_request_fee =
-                                        self.cycles_account_manager.http_request_fee(
-                                            canister_http_request_context.variable_parts_size(),
-                                            canister_http_request_context.max_response_bytes,
-                                            registry_settings.subnet_size,
-                                        );
-                                    // Here we make sure that we do not let upper layers open new
-                                    // http calls while the maximum number of calls is in-flight.
-                                    // Later, in the http adapter we also have a bounded queue of
-                                    // the same size, but this queue alone is not enough as it is
-                                    // used as the interface between DSM and consensus, and the latter
-                                    // consumes requests from this queue upon the request processing
-                                    // start. This means more elements can be added to the queue, while
-                                    // previous requests are still in-flight.
-                                    if state
-                                        .metadata
-                                        .subnet_call_context_manager
-                                        .canister_http_request_contexts
-                                        .len()
-                                        >= self.config.max_canister_http_requests_in_flight
-                                    {
-                                        let err = Err(UserError::new(
-                                            ErrorCode::CanisterRejectedMessage,
-                                            format!("max number ({}) of http requests in-flight reached.", self.config.max_canister_http_requests_in_flight),
-                                        ));
-                                        ExecuteSubnetMessageResult::Finished {
-                                            response: err,
-                                            refund: msg.take_cycles(),
-                                        }
-                                    } else if request.payment < http_request_fee {
-                                        let err = Err(UserError::new(
-                                                        ErrorCode::CanisterRejectedMessage,
-                                                        format!(
-                                                            "http_request request sent with {} cycles, but {} cycles are required.",
-                                                            request.payment, http_request_fee
-                                                        ),
-                                                    ));
-                                        ExecuteSubnetMessageResult::Finished {
-                                            response: err,
-                                            refund: msg.take_cycles(),
+                            Ok(args) => {
+                                let is_replicated = args.is_replicated();
+                                match CanisterHttpRequestContext::try_from((
+                                    state.time(),
+                                    request.as_ref(),
+                                    args,
+                                )) {
+                                    Err(err) => ExecuteSubnetMessageResult::Finished {
+                                        response: Err(err.into()),
+                                        refund: msg.take_cycles(),
+                                    },
+                                    Ok(mut canister_http_request_context) => {
+                                        if !is_replicated {
+                                            // The node making the request is picked at random, so that
+                                            // the calling canister cannot choose the node it has to trust.
+                                            // If the own subnet is unknown, the request falls back to
+                                            // being fully replicated.
+                                            if let Some(node_id) =
+                                                choose_non_replicated_node(&state, rng)
+                                            {
+                                                canister_http_request_context.replication =
+                                                    Replication::NonReplicated(node_id);
+                                            }
                                         }
-                                    } else {
-                                        canister_http_request_context.request.payment -=
-                                            http_request_fee;
-                                        let http_fee = NominalCycles::from(http_request_fee);
-                                        state
-                                            .metadata
-                                            .subnet_metrics
-                                            .consumed_cycles_http_outcalls += http_fee;
-                                        state
+                                        let http_request_fee =
+                                            match canister_http_request_context.replication {
+                                                Replication::FullyReplicated => {
+                                                    self.cycles_account_manager.http_request_fee(
+                                                        canister_http_request_context
+                                                            .variable_parts_size(),
+                                                        canister_http_request_context
+                                                            .max_response_bytes,
+                                                        registry_settings.subnet_size,
+                                                    )
+                                                }
+                                                Replication::NonReplicated(_) => self
+                                                    .cycles_account_manager
+                                                    .non_replicated_http_request_fee(
+                                                        canister_http_request_context
+                                                            .variable_parts_size(),
+                                                        canister_http_request_context
+                                                            .max_response_bytes,
+                                                        registry_settings.subnet_size,
+                                                    ),
+                                            };
+                                        // Here we make sure that we do not let upper layers open new
+                                        // http calls while the maximum number of calls is in-flight.
+                                        // Later, in the http adapter we also have a bounded queue of
+                                        // the same size, but this queue alone is not enough as it is
+                                        // used as the interface between DSM and consensus, and the latter
+                                        // consumes requests from this queue upon the request processing
+                                        // start. This means more elements can be added to the queue, while
+                                        // previous requests are still in-flight.
+                                        if state
                                             .metadata
-                                            .subnet_metrics
-                                            .observe_consumed_cycles_with_use_case(
-                                                CyclesUseCase::HTTPOutcalls,
-                                                http_fee,
+                                            .subnet_call_context_manager
+                                            .canister_http_request_contexts
+                                            .len()
+                                            >= self.config.max_canister_http_requests_in_flight
+                                        {
+                                            let err = Err(UserError::new(
+                                                ErrorCode::CanisterRejectedMessage,
+                                                format!("max number ({}) of http requests in-flight reached.", self.config.max_canister_http_requests_in_flight),
+                                            ));
+                                            ExecuteSubnetMessageResult::Finished {
+                                                response: err,
+                                                refund: msg.take_cycles(),
+                                            }
+                                        } else if request.payment < http_request_fee {
+                                            let err = Err(UserError::new(
+                                                            ErrorCode::CanisterRejectedMessage,
+                                                            format!(
+                                                                "http_request request sent with {} cycles, but {} cycles are required.",
+                                                                request.payment, http_request_fee
+                                                            ),
+                                                        ));
+                                            ExecuteSubnetMessageResult::Finished {
+                                                response: err,
+                                                refund: msg.take_cycles(),
+                                            }
+                                        } else {
+                                            canister_http_request_context.request.payment -=
+                                                http_request_fee;
+                                            let http_fee = NominalCycles::from(http_request_fee);
+                                            state
+                                                .metadata
+                                                .subnet_metrics
+                                                .consumed_cycles_http_outcalls += http_fee;
+                                            state
+                                                .metadata
+                                                .subnet_metrics
+                                                .observe_consumed_cycles_with_use_case(
+                                                    CyclesUseCase::HTTPOutcalls,
+                                                    http_fee,
+                                                );
+                                            state
+                                                .metadata
+                                                .subnet_call_context_manager
+                                                .push_context(
+                                                    SubnetCallContext::CanisterHttpRequest(
+                                                        canister_http_request_context,
+                                                    ),
+                                                );
+                                            self.metrics.observe_message_with_label(
+                                                &request.method_name,
+                                                since.elapsed().as_secs_f64(),
+                                                SUBMITTED_OUTCOME_LABEL.into(),
+                                                SUCCESS_STATUS_LABEL.into(),
                                             );
-                                        state.metadata.subnet_call_context_manager.push_context(
-                                            SubnetCallContext::CanisterHttpRequest(
-                                                canister_http_request_context,
-                                            ),
-                                        );
-                                        self.metrics.observe_message_with_label(
-                                            &request.method_name,
-                                            since.elapsed().as_secs_f64(),
-                                            SUBMITTED_OUTCOME_LABEL.into(),
-                                            SUCCESS_STATUS_LABEL.into(),
-                                        );
-                                        ExecuteSubnetMessageResult::Processing
+                                            ExecuteSubnetMessageResult::Processing
+                                        }
                                     }
                                 }
-                            },
+                            }
                         }
                     }
 
@@ -1224,6 +1262,43 @@ impl ExecutionEnvironment {
                 }
             },
 
+            Ok(Ic00Method::VetKdPublicKey) => {
+                let cycles = msg.take_cycles();
+                match &msg {
+                    CanisterCall::Request(request) => {
+                        let res = match VetKdPublicKeyArgs::decode(request.method_payload()) {
+                            Err(err) => Err(err),
+                            Ok(args) => match get_master_public_key(
+                                idkg_subnet_public_keys,
+                                self.own_subnet_id,
+                                &MasterPublicKeyId::VetKd(args.key_id.clone()),
+                            ) {
+                                Err(err) => Err(err),
+                                Ok(pubkey) => {
+                                    let canister_id = match args.canister_id {
+                                        Some(id) => id.into(),
+                                        None => *msg.sender(),
+                                    };
+                                    self.get_vetkd_public_key(
+                                        pubkey,
+                                        canister_id,
+                                        args.derivation_path.into_inner(),
+                                    )
+                                    .map(|public_key| VetKdPublicKeyResult { public_key }.encode())
+                                }
+                            },
+                        };
+                        ExecuteSubnetMessageResult::Finished {
+                            response: res,
+                            refund: cycles,
+                        }
+                    }
+                    CanisterCall::Ingress(_) => {
+                        self.reject_unexpected_ingress(Ic00Method::VetKdPublicKey)
+                    }
+                }
+            }
+
             Ok(Ic00Method::ProvisionalCreateCanisterWithCycles) => {
                 let res =
                     ProvisionalCreateCanisterWithCyclesArgs::decode(payload).and_then(|args| {
@@ -1378,6 +1453,15 @@ impl ExecutionEnvironment {
                 }
             }
 
+            Ok(Ic00Method::SubnetInfo) => {
+                let res =
+                    SubnetInfoArgs::decode(payload).and_then(|args| self.subnet_info(&state, args));
+                ExecuteSubnetMessageResult::Finished {
+                    response: res,
+                    refund: msg.take_cycles(),
+                }
+            }
+
             Ok(Ic00Method::FetchCanisterLogs) => ExecuteSubnetMessageResult::Finished {
                 response: Err(UserError::new(
                     ErrorCode::CanisterRejectedMessage,
@@ -1507,6 +1591,139 @@ impl ExecutionEnvironment {
                 }
             },
 
+            Ok(Ic00Method::ReadCanisterSnapshotMetadata) => match self.config.canister_snapshots {
+                FlagStatus::Enabled => {
+                    let res = ReadCanisterSnapshotMetadataArgs::decode(payload).and_then(|args| {
+                        self.read_canister_snapshot_metadata(*msg.sender(), &state, args)
+                    });
+                    ExecuteSubnetMessageResult::Finished {
+                        response: res,
+                        refund: msg.take_cycles(),
+                    }
+                }
+                FlagStatus::Disabled => {
+                    let err = Err(UserError::new(
+                        ErrorCode::CanisterContractViolation,
+                        "This API is not enabled on this subnet".to_string(),
+                    ));
+                    ExecuteSubnetMessageResult::Finished {
+                        response: err,
+                        refund: msg.take_cycles(),
+                    }
+                }
+            },
+
+            Ok(Ic00Method::ReadCanisterSnapshotData) => match self.config.canister_snapshots {
+                FlagStatus::Enabled => match ReadCanisterSnapshotDataArgs::decode(payload) {
+                    Err(err) => ExecuteSubnetMessageResult::Finished {
+                        response: Err(err),
+                        refund: msg.take_cycles(),
+                    },
+                    Ok(args) => {
+                        let (result, instructions_used) = self.read_canister_snapshot_data(
+                            registry_settings.subnet_size,
+                            *msg.sender(),
+                            &mut state,
+                            args,
+                        );
+                        let msg_result = ExecuteSubnetMessageResult::Finished {
+                            response: result,
+                            refund: msg.take_cycles(),
+                        };
+
+                        let state =
+                            self.finish_subnet_message_execution(state, msg, msg_result, since);
+                        return (state, Some(instructions_used));
+                    }
+                },
+                FlagStatus::Disabled => {
+                    let err = Err(UserError::new(
+                        ErrorCode::CanisterContractViolation,
+                        "This API is not enabled on this subnet".to_string(),
+                    ));
+                    ExecuteSubnetMessageResult::Finished {
+                        response: err,
+                        refund: msg.take_cycles(),
+                    }
+                }
+            },
+
+            Ok(Ic00Method::UploadCanisterSnapshotMetadata) => {
+                match self.config.canister_snapshots {
+                    FlagStatus::Enabled => {
+                        match UploadCanisterSnapshotMetadataArgs::decode(payload) {
+                            Err(err) => ExecuteSubnetMessageResult::Finished {
+                                response: Err(err),
+                                refund: msg.take_cycles(),
+                            },
+                            Ok(args) => {
+                                let (result, instructions_used) = self
+                                    .upload_canister_snapshot_metadata(
+                                        registry_settings.subnet_size,
+                                        *msg.sender(),
+                                        &mut state,
+                                        args,
+                                        round_limits,
+                                    );
+                                let msg_result = ExecuteSubnetMessageResult::Finished {
+                                    response: result,
+                                    refund: msg.take_cycles(),
+                                };
+
+                                let state = self
+                                    .finish_subnet_message_execution(state, msg, msg_result, since);
+                                return (state, Some(instructions_used));
+                            }
+                        }
+                    }
+                    FlagStatus::Disabled => {
+                        let err = Err(UserError::new(
+                            ErrorCode::CanisterContractViolation,
+                            "This API is not enabled on this subnet".to_string(),
+                        ));
+                        ExecuteSubnetMessageResult::Finished {
+                            response: err,
+                            refund: msg.take_cycles(),
+                        }
+                    }
+                }
+            }
+
+            Ok(Ic00Method::UploadCanisterSnapshotData) => match self.config.canister_snapshots {
+                FlagStatus::Enabled => match UploadCanisterSnapshotDataArgs::decode(payload) {
+                    Err(err) => ExecuteSubnetMessageResult::Finished {
+                        response: Err(err),
+                        refund: msg.take_cycles(),
+                    },
+                    Ok(args) => {
+                        let (result, instructions_used) = self.upload_canister_snapshot_data(
+                            registry_settings.subnet_size,
+                            *msg.sender(),
+                            &mut state,
+                            args,
+                        );
+                        let msg_result = ExecuteSubnetMessageResult::Finished {
+                            response: result,
+                            refund: msg.take_cycles(),
+                        };
+
+                        let state =
+                            self.finish_subnet_message_execution(state, msg, msg_result, since);
+                        return (state, Some(instructions_used));
+                    }
+                },
+                FlagStatus::Disabled => {
+                    let err = Err(UserError::new(
+                        ErrorCode::CanisterContractViolation,
+                        "This API is not enabled on this subnet".to_string(),
+                    ));
+                    ExecuteSubnetMessageResult::Finished {
+                        response: err,
+                        refund: msg.take_cycles(),
+                    }
+                }
+            },
+
             Err(ParseError::VariantNotFound) => {
                 let res = Err(UserError::new(
                     ErrorCode::CanisterMethodNotFound,
@@ -1524,6 +1741,9 @@ impl ExecutionEnvironment {
         //   - `InstallChunkedCode`
         //   - `TakeCanisterSnapshot`
         //   - `LoadCanisterSnapshot`
+        //   - `ReadCanisterSnapshotData`
+        //   - `UploadCanisterSnapshotMetadata`
+        //   - `UploadCanisterSnapshotData`
         //   - `SignWithECDSA`
         // If you modify code below, please also update
         // these cases.
@@ -2201,6 +2421,146 @@ impl ExecutionEnvironment {
         result
     }
 
+    /// Returns the metadata of the specified canister snapshot.
+    fn read_canister_snapshot_metadata(
+        &self,
+        sender: PrincipalId,
+        state: &ReplicatedState,
+        args: ReadCanisterSnapshotMetadataArgs,
+    ) -> Result<Vec<u8>, UserError> {
+        let canister = get_canister(args.get_canister_id(), state)?;
+
+        self.canister_manager
+            .read_canister_snapshot_metadata(sender, canister, args.get_snapshot_id(), state)
+            .map(|response| response.encode())
+            .map_err(UserError::from)
+    }
+
+    /// Returns a slice of the data of the specified canister snapshot.
+    fn read_canister_snapshot_data(
+        &self,
+        subnet_size: usize,
+        sender: PrincipalId,
+        state: &mut ReplicatedState,
+        args: ReadCanisterSnapshotDataArgs,
+    ) -> (Result<Vec<u8>, UserError>, NumInstructions) {
+        let canister_id = args.get_canister_id();
+        // Take canister out.
+        let mut canister = match state.take_canister_state(&canister_id) {
+            None => {
+                return (
+                    Err(UserError::new(
+                        ErrorCode::CanisterNotFound,
+                        format!("Canister {} not found.", &canister_id),
+                    )),
+                    NumInstructions::new(0),
+                )
+            }
+            Some(canister) => canister,
+        };
+
+        let snapshot_id = args.get_snapshot_id();
+        let (result, instructions_used) = self.canister_manager.read_canister_snapshot_data(
+            subnet_size,
+            sender,
+            &mut canister,
+            snapshot_id,
+            args.kind,
+            state,
+        );
+        // Put canister back.
+        state.put_canister_state(canister);
+
+        match result {
+            Ok(response) => (Ok(response.encode()), instructions_used),
+            Err(err) => (Err(err.into()), instructions_used),
+        }
+    }
+
+    /// Creates a new canister snapshot from uploaded metadata and inserts it
+    /// into `ReplicatedState`.
+    fn upload_canister_snapshot_metadata(
+        &self,
+        subnet_size: usize,
+        sender: PrincipalId,
+        state: &mut ReplicatedState,
+        args: UploadCanisterSnapshotMetadataArgs,
+        round_limits: &mut RoundLimits,
+    ) -> (Result<Vec<u8>, UserError>, NumInstructions) {
+        let canister_id = args.get_canister_id();
+        // Take canister out.
+        let mut canister = match state.take_canister_state(&canister_id) {
+            None => {
+                return (
+                    Err(UserError::new(
+                        ErrorCode::CanisterNotFound,
+                        format!("Canister {} not found.", &canister_id),
+                    )),
+                    NumInstructions::new(0),
+                )
+            }
+            Some(canister) => canister,
+        };
+
+        let resource_saturation =
+            self.subnet_memory_saturation(&round_limits.subnet_available_memory);
+        let (result, instructions_used) = self.canister_manager.upload_canister_snapshot_metadata(
+            subnet_size,
+            sender,
+            &mut canister,
+            &args,
+            state,
+            round_limits,
+            &resource_saturation,
+        );
+        // Put canister back.
+        state.put_canister_state(canister);
+
+        match result {
+            Ok(response) => (Ok(response.encode()), instructions_used),
+            Err(err) => (Err(err.into()), instructions_used),
+        }
+    }
+
+    /// Writes a slice of data into the specified canister snapshot.
+    fn upload_canister_snapshot_data(
+        &self,
+        subnet_size: usize,
+        sender: PrincipalId,
+        state: &mut ReplicatedState,
+        args: UploadCanisterSnapshotDataArgs,
+    ) -> (Result<Vec<u8>, UserError>, NumInstructions) {
+        let canister_id = args.get_canister_id();
+        // Take canister out.
+        let mut canister = match state.take_canister_state(&canister_id) {
+            None => {
+                return (
+                    Err(UserError::new(
+                        ErrorCode::CanisterNotFound,
+                        format!("Canister {} not found.", &canister_id),
+                    )),
+                    NumInstructions::new(0),
+                )
+            }
+            Some(canister) => canister,
+        };
+
+        let (result, instructions_used) = self.canister_manager.upload_canister_snapshot_data(
+            subnet_size,
+            sender,
+            &mut canister,
+            &args,
+            state,
+        );
+        // Put canister back.
+        state.put_canister_state(canister);
+
+        match result {
+            Ok(()) => (Ok(EmptyBlob.encode()), instructions_used),
+            Err(err) => (Err(err.into()), instructions_used),
+        }
+    }
+
     fn node_metrics_history(
         &self,
         state: &ReplicatedState,
@@ -2225,6 +2585,35 @@ impl ExecutionEnvironment {
         Ok(Encode!(&result).unwrap())
     }
 
+    fn subnet_info(
+        &self,
+        state: &ReplicatedState,
+        args: SubnetInfoArgs,
+    ) -> Result<Vec<u8>, UserError> {
+        let subnet_id = SubnetId::from(args.subnet_id);
+        let network_topology = &state.metadata.network_topology;
+        let subnet_topology = network_topology.subnets.get(&subnet_id).ok_or_else(|| {
+            UserError::new(
+                ErrorCode::SubnetNotFound,
+                format!("Subnet {} not found.", subnet_id),
+            )
+        })?;
+
+        Ok(SubnetInfoResponse {
+            subnet_type: subnet_topology.subnet_type,
+            subnet_size: subnet_topology.nodes.len() as u64,
+            replica_version: subnet_topology.replica_version.clone(),
+            // Only keys the subnet can sign with, not all keys it holds.
+            key_ids: network_topology
+                .idkg_signing_subnets
+                .iter()
+                .filter(|(_, signing_subnets)| signing_subnets.contains(&subnet_id))
+                .map(|(key_id, _)| key_id.clone())
+                .collect(),
+        }
+        .encode())
+    }
+
     // Executes an inter-canister response.
     //
     // Returns a tuple with the result, along with a flag indicating whether or
@@ -2614,6 +3003,22 @@ impl ExecutionEnvironment {
         .map_err(|err| UserError::new(ErrorCode::CanisterRejectedMessage, format!("{}", err)))
     }
 
+    fn get_vetkd_public_key(
+        &self,
+        subnet_public_key: &MasterPublicKey,
+        caller: PrincipalId,
+        derivation_path: Vec<Vec<u8>>,
+    ) -> Result<Vec<u8>, UserError> {
+        derive_vetkd_public_key(
+            subnet_public_key,
+            &ExtendedDerivationPath {
+                caller,
+                derivation_path,
+            },
+        )
+        .map_err(|err| UserError::new(ErrorCode::CanisterRejectedMessage, format!("{}", err)))
+    }
+
     fn calculate_signature_fee(&self, args: &ThresholdArguments, subnet_size: usize) -> Cycles {
         let cam = &self.cycles_account_manager;
         match args {
@@ -3752,6 +4157,22 @@ pub fn execute_canister(
     )
 }
 
+/// Picks a random node of the own subnet to make a non-replicated canister
+/// http request.
+fn choose_non_replicated_node(state: &ReplicatedState, rng: &mut dyn RngCore) -> Option<NodeId> {
+    let nodes = &state
+        .metadata
+        .network_topology
+        .subnets
+        .get(&state.metadata.own_subnet_id)?
+        .nodes;
+    if nodes.is_empty() {
+        return None;
+    }
+    let index = (rng.next_u64() % nodes.len() as u64) as usize;
+    nodes.iter().nth(index).copied()
+}
+
 fn get_master_public_key<'a>(
     idkg_subnet_public_keys: &'a BTreeMap<MasterPublicKeyId, MasterPublicKey>,
     subnet_id: SubnetId,
@@ -4,11 +4,12 @@ use ic_config::flag_status::FlagStatus;
 use ic_error_types::{ErrorCode, RejectCode, UserError};
 use ic_management_canister_types::{
     self as ic00, BitcoinGetUtxosArgs, BitcoinNetwork, BoundedHttpHeaders, CanisterChange,
-    CanisterHttpRequestArgs, CanisterIdRecord, CanisterStatusResultV2, CanisterStatusType,
-    DerivationPath, EcdsaCurve, EcdsaKeyId, EmptyBlob, FetchCanisterLogsRequest, HttpMethod,
-    LogVisibilityV2, MasterPublicKeyId, Method, Payload as Ic00Payload,
-    ProvisionalCreateCanisterWithCyclesArgs, ProvisionalTopUpCanisterArgs, SchnorrAlgorithm,
-    SchnorrKeyId, TakeCanisterSnapshotArgs, TransformContext, TransformFunc, IC_00,
+    CanisterHttpRequestArgs, CanisterIdRecord, CanisterSnapshotResponse, CanisterStatusResultV2,
+    CanisterStatusType, DerivationPath, EcdsaCurve, EcdsaKeyId, EmptyBlob,
+    FetchCanisterLogsRequest, HttpMethod, LogVisibilityV2, MasterPublicKeyId, Method,
+    Payload as Ic00Payload, ProvisionalCreateCanisterWithCyclesArgs, ProvisionalTopUpCanisterArgs,
+    SchnorrAlgorithm, SchnorrKeyId, TakeCanisterSnapshotArgs, TransformContext, TransformFunc,
+    IC_00,
 };
 use ic_registry_routing_table::{canister_id_into_u64, CanisterIdRange, RoutingTable};
 use ic_registry_subnet_type::SubnetType;
@@ -24,7 +25,7 @@ use ic_test_utilities_execution_environment::{
 };
 use ic_test_utilities_metrics::{fetch_histogram_vec_count, fetch_int_counter, metric_vec};
 use ic_types::{
-    canister_http::{CanisterHttpMethod, Transform},
+    canister_http::{CanisterHttpMethod, Replication, Transform},
     ingress::{IngressState, IngressStatus, WasmResult},
     messages::{
         CallbackId, Payload, RejectContext, RequestOrResponse, Response, MAX_RESPONSE_COUNT_BYTES,
@@ -1612,6 +1613,7 @@ fn http_request_bound_holds() {
         headers: BoundedHttpHeaders::new(vec![]),
         body: None,
         method: HttpMethod::GET,
+        is_replicated: None,
         transform: Some(TransformContext {
             function: TransformFunc(candid::Func {
                 principal: caller_canister.get().0,
@@ -1714,6 +1716,77 @@ fn create_canister_xnet_called_from_nns() {
     };
 }
 
+#[test]
+fn subnet_info_returns_own_subnet_topology() {
+    let own_subnet = subnet_test_id(1);
+    let caller_canister = canister_test_id(1);
+    let key_id = make_ecdsa_key("some_key");
+    let mut test = ExecutionTestBuilder::new()
+        .with_own_subnet_id(own_subnet)
+        .with_caller(own_subnet, caller_canister)
+        .with_idkg_key(key_id.clone())
+        .with_signing_disabled_idkg_key(make_ecdsa_key("disabled_key"))
+        .build();
+    test.state_mut()
+        .metadata
+        .network_topology
+        .subnets
+        .get_mut(&own_subnet)
+        .unwrap()
+        .replica_version = "0.9.0".to_string();
+
+    test.inject_call_to_ic00(
+        Method::SubnetInfo,
+        ic00::SubnetInfoArgs {
+            subnet_id: own_subnet.get(),
+        }
+        .encode(),
+        Cycles::new(0),
+    );
+    test.execute_all();
+    let RequestOrResponse::Response(response) = test.xnet_messages()[0].clone() else {
+        panic!("Type should be RequestOrResponse::Response");
+    };
+    let Payload::Data(data) = &response.response_payload else {
+        panic!("Expected a reply, got {:?}", response.response_payload);
+    };
+    let subnet_topology = &test.state().metadata.network_topology.subnets[&own_subnet];
+    assert_eq!(
+        ic00::SubnetInfoResponse::decode(data).unwrap(),
+        ic00::SubnetInfoResponse {
+            subnet_type: subnet_topology.subnet_type,
+            subnet_size: subnet_topology.nodes.len() as u64,
+            replica_version: "0.9.0".to_string(),
+            key_ids: vec![key_id],
+        }
+    );
+}
+
+#[test]
+fn subnet_info_rejects_unknown_subnet() {
+    let own_subnet = subnet_test_id(1);
+    let caller_canister = canister_test_id(1);
+    let mut test = ExecutionTestBuilder::new()
+        .with_own_subnet_id(own_subnet)
+        .with_caller(own_subnet, caller_canister)
+        .build();
+
+    let unknown_subnet = subnet_test_id(42);
+    test.inject_call_to_ic00(
+        Method::SubnetInfo,
+        ic00::SubnetInfoArgs {
+            subnet_id: unknown_subnet.get(),
+        }
+        .encode(),
+        Cycles::new(0),
+    );
+    test.execute_all();
+    assert_eq!(
+        get_reject_message(test.xnet_messages()[0].clone()),
+        format!("Subnet {} not found.", unknown_subnet)
+    );
+}
+
 #[test]
 fn setup_initial_dkg_sender_on_nns() {
     let own_subnet = subnet_test_id(1);
@@ -2203,6 +2276,7 @@ fn execute_canister_http_request() {
         headers: BoundedHttpHeaders::new(vec![]),
         body: None,
         method: HttpMethod::GET,
+        is_replicated: None,
         transform: Some(TransformContext {
             function: TransformFunc(candid::Func {
                 principal: caller_canister.get().0,
@@ -2238,6 +2312,10 @@ fn execute_canister_http_request() {
     );
     assert_eq!(http_request_context.http_method, CanisterHttpMethod::GET);
     assert_eq!(http_request_context.request.sender, caller_canister);
+    assert_eq!(
+        http_request_context.replication,
+        Replication::FullyReplicated
+    );
     let fee = test.http_request_fee(
         http_request_context.variable_parts_size(),
         Some(NumBytes::from(response_size_limit)),
@@ -2264,6 +2342,112 @@ fn execute_canister_http_request() {
     );
 }
 
+#[test]
+fn execute_canister_http_request_with_put_patch_and_delete() {
+    for (method, expected_method) in [
+        (HttpMethod::PUT, CanisterHttpMethod::PUT),
+        (HttpMethod::PATCH, CanisterHttpMethod::PATCH),
+        (HttpMethod::DELETE, CanisterHttpMethod::DELETE),
+    ] {
+        let own_subnet = subnet_test_id(1);
+        let caller_canister = canister_test_id(10);
+        let mut test = ExecutionTestBuilder::new()
+            .with_own_subnet_id(own_subnet)
+            .with_caller(own_subnet, caller_canister)
+            .build();
+        test.state_mut().metadata.own_subnet_features.http_requests = true;
+
+        let body = b"{\"name\":\"object\"}".to_vec();
+        let args = CanisterHttpRequestArgs {
+            url: "https://example.com/objects/1".to_string(),
+            max_response_bytes: Some(1000),
+            headers: BoundedHttpHeaders::new(vec![]),
+            body: Some(body.clone()),
+            method,
+            is_replicated: None,
+            transform: None,
+        };
+
+        let payment = Cycles::new(1_000_000_000);
+        test.inject_call_to_ic00(Method::HttpRequest, args.encode(), payment);
+        test.execute_all();
+
+        let http_request_context = test
+            .state()
+            .metadata
+            .subnet_call_context_manager
+            .canister_http_request_contexts
+            .get(&CallbackId::from(0))
+            .unwrap();
+        assert_eq!(http_request_context.http_method, expected_method);
+        assert_eq!(http_request_context.body, Some(body));
+        // The fee does not depend on the HTTP method.
+        let fee = test.http_request_fee(
+            http_request_context.variable_parts_size(),
+            Some(NumBytes::from(1000)),
+        );
+        assert_eq!(http_request_context.request.payment, payment - fee);
+    }
+}
+
+#[test]
+fn execute_non_replicated_canister_http_request() {
+    let own_subnet = subnet_test_id(1);
+    let caller_canister = canister_test_id(10);
+    let mut test = ExecutionTestBuilder::new()
+        .with_own_subnet_id(own_subnet)
+        .with_caller(own_subnet, caller_canister)
+        .build();
+    test.state_mut().metadata.own_subnet_features.http_requests = true;
+
+    let response_size_limit = 1000u64;
+    let args = CanisterHttpRequestArgs {
+        url: "https://example.com".to_string(),
+        max_response_bytes: Some(response_size_limit),
+        headers: BoundedHttpHeaders::new(vec![]),
+        body: None,
+        method: HttpMethod::GET,
+        is_replicated: Some(false),
+        transform: None,
+    };
+
+    let payment = Cycles::new(1_000_000_000);
+    test.inject_call_to_ic00(Method::HttpRequest, args.encode(), payment);
+    test.execute_all();
+
+    let http_request_context = test
+        .state()
+        .metadata
+        .subnet_call_context_manager
+        .canister_http_request_contexts
+        .get(&CallbackId::from(0))
+        .unwrap();
+
+    // The request is made by a single node of the own subnet.
+    let Replication::NonReplicated(node_id) = http_request_context.replication else {
+        panic!(
+            "Expected a non-replicated request, got {:?}",
+            http_request_context.replication
+        );
+    };
+    assert!(test.state().metadata.network_topology.subnets[&own_subnet]
+        .nodes
+        .contains(&node_id));
+
+    // The fee is that of a single node, which is lower than for a replicated request.
+    let fee = test.non_replicated_http_request_fee(
+        http_request_context.variable_parts_size(),
+        Some(NumBytes::from(response_size_limit)),
+    );
+    assert!(
+        fee < test.http_request_fee(
+            http_request_context.variable_parts_size(),
+            Some(NumBytes::from(response_size_limit)),
+        )
+    );
+    assert_eq!(http_request_context.request.payment, payment - fee);
+}
+
 #[test]
 fn execute_canister_http_request_disabled() {
     let own_subnet = subnet_test_id(1);
@@ -2282,6 +2466,7 @@ fn execute_canister_http_request_disabled() {
         headers: BoundedHttpHeaders::new(vec![]),
         body: None,
         method: HttpMethod::GET,
+        is_replicated: None,
         transform: Some(TransformContext {
             function: TransformFunc(candid::Func {
                 principal: caller_canister.get().0,
@@ -2608,7 +2793,6 @@ fn test_sign_with_threshold_key_unknown_key_rejected() {
         );
     }
 }
-
 #[test]
 fn test_signing_disabled_vs_unknown_key_on_public_key_and_signing_requests() {
     // Test the disabled key succeeds for public key request but fails for signing,
@@ -3645,6 +3829,156 @@ fn test_fetch_canister_logs_should_accept_ingress_message() {
     );
 }
 
+fn set_operators(
+    test: &mut ExecutionTest,
+    canister_id: CanisterId,
+    operators: Vec<PrincipalId>,
+) -> Result<WasmResult, UserError> {
+    let payload = ic00::UpdateSettingsArgs {
+        canister_id: canister_id.into(),
+        settings: ic00::CanisterSettingsArgsBuilder::new()
+            .with_operators(operators)
+            .build(),
+        sender_canister_version: None,
+    }
+    .encode();
+    test.subnet_message(Method::UpdateSettings, payload)
+}
+
+#[test]
+fn test_operators_are_reported_in_canister_status() {
+    // Arrange.
+    let mut test = ExecutionTestBuilder::new().build();
+    let canister_id = test.universal_canister().unwrap();
+    let operator = user_test_id(42).get();
+    // Act.
+    set_operators(&mut test, canister_id, vec![operator]).unwrap();
+    let result = test.canister_status(canister_id);
+    let canister_status = CanisterStatusResultV2::decode(&get_reply(result)).unwrap();
+    // Assert.
+    assert_eq!(canister_status.operators(), vec![operator]);
+    assert_eq!(canister_status.controllers(), vec![test.user_id().get()]);
+}
+
+#[test]
+fn test_operator_can_stop_start_and_snapshot_canister() {
+    // Arrange.
+    let mut test = ExecutionTestBuilder::new().build();
+    let canister_id = test.universal_canister().unwrap();
+    let operator = user_test_id(42);
+    set_operators(&mut test, canister_id, vec![operator.get()]).unwrap();
+    test.set_user_id(operator);
+    // Act & Assert.
+    test.stop_canister(canister_id);
+    test.process_stopping_canisters();
+    assert_eq!(
+        test.canister_state(canister_id).status(),
+        CanisterStatusType::Stopped
+    );
+    test.start_canister(canister_id).unwrap();
+    assert_eq!(
+        test.canister_state(canister_id).status(),
+        CanisterStatusType::Running
+    );
+    test.subnet_message(
+        Method::TakeCanisterSnapshot,
+        TakeCanisterSnapshotArgs::new(canister_id, None).encode(),
+    )
+    .unwrap();
+    test.subnet_message(
+        Method::ListCanisterSnapshots,
+        CanisterIdRecord::from(canister_id).encode(),
+    )
+    .unwrap();
+    test.canister_status(canister_id).unwrap();
+}
+
+#[test]
+fn test_operator_cannot_replace_snapshot() {
+    // Arrange.
+    let mut test = ExecutionTestBuilder::new().build();
+    let canister_id = test.universal_canister().unwrap();
+    let operator = user_test_id(42);
+    set_operators(&mut test, canister_id, vec![operator.get()]).unwrap();
+    let result = test
+        .subnet_message(
+            Method::TakeCanisterSnapshot,
+            TakeCanisterSnapshotArgs::new(canister_id, None).encode(),
+        )
+        .unwrap();
+    let snapshot_id = CanisterSnapshotResponse::decode(&result.bytes())
+        .unwrap()
+        .snapshot_id();
+    test.set_user_id(operator);
+    // Act.
+    let err = test
+        .subnet_message(
+            Method::TakeCanisterSnapshot,
+            TakeCanisterSnapshotArgs::new(canister_id, Some(snapshot_id)).encode(),
+        )
+        .unwrap_err();
+    // Assert.
+    assert_eq!(err.code(), ErrorCode::CanisterInvalidController);
+    assert!(test.state().canister_snapshots.get(snapshot_id).is_some());
+    assert_eq!(
+        test.state()
+            .canister_snapshots
+            .list_snapshots(canister_id)
+            .len(),
+        1
+    );
+}
+
+#[test]
+fn test_operator_cannot_change_code_or_settings() {
+    // Arrange.
+    let mut test = ExecutionTestBuilder::new().build();
+    let canister_id = test.universal_canister().unwrap();
+    let operator = user_test_id(42);
+    set_operators(&mut test, canister_id, vec![operator.get()]).unwrap();
+    test.set_user_id(operator);
+    // Act & Assert.
+    let err = set_operators(&mut test, canister_id, vec![]).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::CanisterInvalidController);
+    let err = test
+        .upgrade_canister(canister_id, UNIVERSAL_CANISTER_WASM.to_vec())
+        .unwrap_err();
+    assert_eq!(err.code(), ErrorCode::CanisterInvalidController);
+    let err = test.uninstall_code(canister_id).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::CanisterInvalidController);
+    assert!(test
+        .canister_state(canister_id)
+        .operators()
+        .contains(&operator.get()));
+}
+
+#[test]
+fn test_should_accept_ingress_message_from_operator() {
+    // Arrange.
+    let mut test = ExecutionTestBuilder::new().build();
+    let canister_id = test.universal_canister().unwrap();
+    let operator = user_test_id(42);
+    set_operators(&mut test, canister_id, vec![operator.get()]).unwrap();
+    // Act.
+    test.set_user_id(operator);
+    let stop_result = test.should_accept_ingress_message(
+        IC_00,
+        Method::StopCanister,
+        CanisterIdRecord::from(canister_id).encode(),
+    );
+    let uninstall_result = test.should_accept_ingress_message(
+        IC_00,
+        Method::UninstallCode,
+        CanisterIdRecord::from(canister_id).encode(),
+    );
+    // Assert.
+    assert_eq!(stop_result, Ok(()));
+    assert_eq!(
+        uninstall_result.unwrap_err().code(),
+        ErrorCode::CanisterInvalidController
+    );
+}
+
 #[test]
 fn test_sign_with_schnorr_api_is_enabled() {
     // TODO(EXC-1629): upgrade to more of e2e test with mocking the response
@@ -1,20 +1,26 @@
 use assert_matches::assert_matches;
 use candid::{Decode, Encode};
 use ic_base_types::NumBytes;
+use ic_config::embedders::WASM_MAX_SIZE;
 use ic_config::flag_status::FlagStatus;
 use ic_config::subnet_config::SubnetConfig;
 use ic_cycles_account_manager::ResourceSaturation;
 use ic_error_types::{ErrorCode, RejectCode};
 use ic_management_canister_types::{
-    self as ic00, CanisterChange, CanisterChangeDetails, CanisterSnapshotResponse,
-    ClearChunkStoreArgs, DeleteCanisterSnapshotArgs, ListCanisterSnapshotArgs,
-    LoadCanisterSnapshotArgs, Method, Payload as Ic00Payload, TakeCanisterSnapshotArgs,
-    UploadChunkArgs,
+    self as ic00, CanisterChange, CanisterChangeDetails, CanisterSnapshotDataKind,
+    CanisterSnapshotDataOffset, CanisterSnapshotResponse, ChunkHash, ClearChunkStoreArgs,
+    DeleteCanisterSnapshotArgs, ListCanisterSnapshotArgs, LoadCanisterSnapshotArgs, Method,
+    Payload as Ic00Payload, ReadCanisterSnapshotDataArgs, ReadCanisterSnapshotDataResponse,
+    ReadCanisterSnapshotMetadataArgs, ReadCanisterSnapshotMetadataResponse,
+    TakeCanisterSnapshotArgs, UploadCanisterSnapshotDataArgs, UploadCanisterSnapshotMetadataArgs,
+    UploadCanisterSnapshotMetadataResponse, UploadChunkArgs,
 };
 use ic_registry_subnet_type::SubnetType;
 use ic_replicated_state::{
-    canister_snapshots::SnapshotOperation,
-    canister_state::{execution_state::WasmBinary, system_state::CyclesUseCase},
+    canister_snapshots::{SnapshotOperation, SnapshotSource},
+    canister_state::{
+        execution_state::WasmBinary, system_state::CyclesUseCase, WASM_PAGE_SIZE_IN_BYTES,
+    },
     CanisterState, ExecutionState, SchedulerState,
 };
 use ic_test_utilities_execution_environment::{
@@ -1919,6 +1925,447 @@ fn snapshot_must_include_globals() {
     assert_eq!(result, WasmResult::Reply(vec![1, 0, 0, 0]));
 }
 
+#[test]
+fn read_canister_snapshot_data_decode_round_trip() {
+    let canister_id = canister_test_id(4);
+    let snapshot_id = SnapshotId::from((canister_id, 6));
+    let args = ReadCanisterSnapshotDataArgs::new(
+        canister_id,
+        snapshot_id,
+        CanisterSnapshotDataKind::StableMemory {
+            offset: 10,
+            size: 20,
+        },
+    );
+    let encoded_args = args.encode();
+    assert_eq!(
+        args,
+        ReadCanisterSnapshotDataArgs::decode(encoded_args.as_slice()).unwrap()
+    );
+
+    let args = UploadCanisterSnapshotDataArgs::new(
+        canister_id,
+        snapshot_id,
+        CanisterSnapshotDataOffset::WasmModule { offset: 5 },
+        vec![1, 2, 3],
+    );
+    let encoded_args = args.encode();
+    assert_eq!(
+        args,
+        UploadCanisterSnapshotDataArgs::decode(encoded_args.as_slice()).unwrap()
+    );
+}
+
+#[test]
+fn canister_snapshot_can_be_downloaded_and_uploaded_to_another_canister() {
+    let wat = r#"
+    (module
+        (import "ic0" "msg_reply" (func $msg_reply))
+        (import "ic0" "msg_reply_data_append"
+          (func $msg_reply_data_append (param i32 i32)))
+
+        (func $read_global
+          (i32.store
+            (i32.const 0)
+            (global.get 0)
+          )
+          (call $msg_reply_data_append
+            (i32.const 0)
+            (i32.const 4))
+          (call $msg_reply)
+        )
+
+        (func $increase_global
+          (global.set 0
+            (i32.add
+              (global.get 0)
+              (i32.const 1)
+            )
+          )
+          (call $msg_reply)
+        )
+
+        (memory $memory 1)
+        (export "memory" (memory $memory))
+        (global (export "counter") (mut i32) (i32.const 0))
+        (export "canister_query read_global" (func $read_global))
+        (export "canister_update increase_global" (func $increase_global))
+      )"#;
+    let wasm = wat::parse_str(wat).unwrap();
+
+    let mut test = ExecutionTestBuilder::new()
+        .with_snapshots(FlagStatus::Enabled)
+        .build();
+
+    let source_canister_id = test.canister_from_binary(wasm.clone()).unwrap();
+    let target_canister_id = test.canister_from_binary(wasm).unwrap();
+
+    // Increase the global of the source canister to 1 and take a snapshot.
+    test.ingress(source_canister_id, "increase_global", vec![])
+        .unwrap();
+    let args = TakeCanisterSnapshotArgs::new(source_canister_id, None);
+    let result = test
+        .subnet_message("take_canister_snapshot", args.encode())
+        .unwrap();
+    let snapshot_id = CanisterSnapshotResponse::decode(&result.bytes())
+        .unwrap()
+        .snapshot_id();
+
+    // Download the snapshot.
+    let args = ReadCanisterSnapshotMetadataArgs::new(source_canister_id, snapshot_id);
+    let result = test
+        .subnet_message("read_canister_snapshot_metadata", args.encode())
+        .unwrap();
+    let metadata = ReadCanisterSnapshotMetadataResponse::decode(&result.bytes()).unwrap();
+    assert_eq!(metadata.wasm_memory_size, WASM_PAGE_SIZE_IN_BYTES as u64);
+    assert_eq!(metadata.stable_memory_size, 0);
+
+    let read_data = |test: &mut ExecutionTest, kind: CanisterSnapshotDataKind| {
+        let args = ReadCanisterSnapshotDataArgs::new(source_canister_id, snapshot_id, kind);
+        let result = test
+            .subnet_message("read_canister_snapshot_data", args.encode())
+            .unwrap();
+        ReadCanisterSnapshotDataResponse::decode(&result.bytes())
+            .unwrap()
+            .chunk
+    };
+    let wasm_module = read_data(
+        &mut test,
+        CanisterSnapshotDataKind::WasmModule {
+            offset: 0,
+            size: metadata.wasm_module_size,
+        },
+    );
+    let wasm_memory = read_data(
+        &mut test,
+        CanisterSnapshotDataKind::MainMemory {
+            offset: 0,
+            size: metadata.wasm_memory_size,
+        },
+    );
+    assert_eq!(wasm_module.len() as u64, metadata.wasm_module_size);
+    assert_eq!(wasm_memory.len() as u64, metadata.wasm_memory_size);
+
+    // Upload the snapshot to the target canister.
+    let args = UploadCanisterSnapshotMetadataArgs {
+        canister_id: target_canister_id.get(),
+        replace_snapshot: None,
+        wasm_module_size: metadata.wasm_module_size,
+        exported_globals: metadata.exported_globals.clone(),
+        wasm_memory_size: metadata.wasm_memory_size,
+        stable_memory_size: metadata.stable_memory_size,
+        wasm_chunk_store: metadata.wasm_chunk_store.clone(),
+        certified_data: metadata.certified_data.clone(),
+    };
+    let result = test
+        .subnet_message("upload_canister_snapshot_metadata", args.encode())
+        .unwrap();
+    let uploaded_snapshot_id = UploadCanisterSnapshotMetadataResponse::decode(&result.bytes())
+        .unwrap()
+        .get_snapshot_id();
+    assert_eq!(
+        test.state_mut().canister_snapshots.take_unflushed_changes(),
+        vec![
+            SnapshotOperation::Backup(source_canister_id, snapshot_id),
+            SnapshotOperation::Upload(target_canister_id, uploaded_snapshot_id),
+        ]
+    );
+
+    for (kind, chunk) in [
+        (
+            CanisterSnapshotDataOffset::WasmModule { offset: 0 },
+            wasm_module,
+        ),
+        (
+            CanisterSnapshotDataOffset::MainMemory { offset: 0 },
+            wasm_memory,
+        ),
+    ] {
+        let args = UploadCanisterSnapshotDataArgs::new(
+            target_canister_id,
+            uploaded_snapshot_id,
+            kind,
+            chunk,
+        );
+        test.subnet_message("upload_canister_snapshot_data", args.encode())
+            .unwrap();
+    }
+
+    // Load the uploaded snapshot and check that the global was restored.
+    let args = LoadCanisterSnapshotArgs::new(target_canister_id, uploaded_snapshot_id, None);
+    test.subnet_message("load_canister_snapshot", args.encode())
+        .unwrap();
+    let result = test
+        .non_replicated_query(target_canister_id, "read_global", vec![])
+        .unwrap();
+    assert_eq!(result, WasmResult::Reply(vec![1, 0, 0, 0]));
+
+    // Neither the loaded snapshot nor the snapshot taken from the source
+    // canister accept uploaded data.
+    assert_eq!(
+        test.state()
+            .canister_snapshots
+            .get(uploaded_snapshot_id)
+            .unwrap()
+            .source(),
+        SnapshotSource::UploadFinalized
+    );
+    for (canister_id, snapshot_id) in [
+        (target_canister_id, uploaded_snapshot_id),
+        (source_canister_id, snapshot_id),
+    ] {
+        let args = UploadCanisterSnapshotDataArgs::new(
+            canister_id,
+            snapshot_id,
+            CanisterSnapshotDataOffset::MainMemory { offset: 0 },
+            vec![1, 2, 3],
+        );
+        let err = test
+            .subnet_message("upload_canister_snapshot_data", args.encode())
+            .unwrap_err();
+        assert_eq!(err.code(), ErrorCode::InvalidManagementPayload);
+        assert!(err.description().contains("does not accept data"));
+    }
+}
+
+#[test]
+fn failed_load_of_uploaded_snapshot_leaves_snapshot_unchanged() {
+    const CYCLES: Cycles = Cycles::new(1_000_000_000_000_000);
+    let mut test = ExecutionTestBuilder::new()
+        .with_snapshots(FlagStatus::Enabled)
+        .build();
+    let canister_id = test
+        .canister_from_cycles_and_binary(CYCLES, UNIVERSAL_CANISTER_WASM.into())
+        .unwrap();
+
+    let args = UploadCanisterSnapshotMetadataArgs {
+        canister_id: canister_id.get(),
+        wasm_module_size: 10,
+        ..Default::default()
+    };
+    let result = test
+        .subnet_message("upload_canister_snapshot_metadata", args.encode())
+        .unwrap();
+    let snapshot_id = UploadCanisterSnapshotMetadataResponse::decode(&result.bytes())
+        .unwrap()
+        .get_snapshot_id();
+    let args = UploadCanisterSnapshotDataArgs::new(
+        canister_id,
+        snapshot_id,
+        CanisterSnapshotDataOffset::WasmModule { offset: 0 },
+        vec![1, 2, 3],
+    );
+    test.subnet_message("upload_canister_snapshot_data", args.encode())
+        .unwrap();
+
+    // The module is not a valid Wasm module, so loading the snapshot fails.
+    let canister_version = test
+        .canister_state(canister_id)
+        .system_state
+        .canister_version;
+    let args = LoadCanisterSnapshotArgs::new(canister_id, snapshot_id, None);
+    test.subnet_message("load_canister_snapshot", args.encode())
+        .unwrap_err();
+    assert_eq!(
+        test.canister_state(canister_id)
+            .system_state
+            .canister_version,
+        canister_version
+    );
+    let snapshot = test.state().canister_snapshots.get(snapshot_id).unwrap();
+    assert!(snapshot.is_uploading_wasm_module());
+    assert_eq!(snapshot.source(), SnapshotSource::MetadataUpload);
+
+    // The upload can be completed after the failed load.
+    let args = UploadCanisterSnapshotDataArgs::new(
+        canister_id,
+        snapshot_id,
+        CanisterSnapshotDataOffset::WasmModule { offset: 3 },
+        vec![4, 5, 6],
+    );
+    test.subnet_message("upload_canister_snapshot_data", args.encode())
+        .unwrap();
+}
+
+#[test]
+fn upload_canister_snapshot_data_fails_out_of_bounds() {
+    let mut test = ExecutionTestBuilder::new()
+        .with_snapshots(FlagStatus::Enabled)
+        .build();
+    let canister_id = test
+        .canister_from_cycles_and_binary(
+            Cycles::new(1_000_000_000_000_000),
+            UNIVERSAL_CANISTER_WASM.into(),
+        )
+        .unwrap();
+
+    let args = UploadCanisterSnapshotMetadataArgs {
+        canister_id: canister_id.get(),
+        wasm_module_size: 10,
+        ..Default::default()
+    };
+    let result = test
+        .subnet_message("upload_canister_snapshot_metadata", args.encode())
+        .unwrap();
+    let snapshot_id = UploadCanisterSnapshotMetadataResponse::decode(&result.bytes())
+        .unwrap()
+        .get_snapshot_id();
+
+    let args = UploadCanisterSnapshotDataArgs::new(
+        canister_id,
+        snapshot_id,
+        CanisterSnapshotDataOffset::WasmModule { offset: 8 },
+        vec![1, 2, 3, 4],
+    );
+    let err = test
+        .subnet_message("upload_canister_snapshot_data", args.encode())
+        .unwrap_err();
+    assert_eq!(err.code(), ErrorCode::InvalidManagementPayload);
+
+    // Memory sizes must be multiples of the Wasm page size.
+    let args = UploadCanisterSnapshotMetadataArgs {
+        canister_id: canister_id.get(),
+        wasm_memory_size: 100,
+        ..Default::default()
+    };
+    let err = test
+        .subnet_message("upload_canister_snapshot_metadata", args.encode())
+        .unwrap_err();
+    assert_eq!(err.code(), ErrorCode::InvalidManagementPayload);
+
+    // The Wasm module may not exceed the maximum module size.
+    let args = UploadCanisterSnapshotMetadataArgs {
+        canister_id: canister_id.get(),
+        wasm_module_size: WASM_MAX_SIZE.get() + 1,
+        ..Default::default()
+    };
+    let err = test
+        .subnet_message("upload_canister_snapshot_metadata", args.encode())
+        .unwrap_err();
+    assert_eq!(err.code(), ErrorCode::InvalidManagementPayload);
+}
+
+#[test]
+fn upload_canister_snapshot_data_rejects_undeclared_wasm_chunks() {
+    const CYCLES: Cycles = Cycles::new(1_000_000_000_000_000);
+    let mut test = ExecutionTestBuilder::new()
+        .with_snapshots(FlagStatus::Enabled)
+        .build();
+    let canister_id = test
+        .canister_from_cycles_and_binary(CYCLES, UNIVERSAL_CANISTER_WASM.into())
+        .unwrap();
+
+    // Declared chunk hashes must be valid hashes.
+    let args = UploadCanisterSnapshotMetadataArgs {
+        canister_id: canister_id.get(),
+        wasm_chunk_store: vec![ChunkHash {
+            hash: vec![1, 2, 3],
+        }],
+        ..Default::default()
+    };
+    let err = test
+        .subnet_message("upload_canister_snapshot_metadata", args.encode())
+        .unwrap_err();
+    assert_eq!(err.code(), ErrorCode::InvalidManagementPayload);
+
+    let declared_chunk = vec![1, 2, 3];
+    let args = UploadCanisterSnapshotMetadataArgs {
+        canister_id: canister_id.get(),
+        wasm_chunk_store: vec![ChunkHash {
+            hash: ic_crypto_sha2::Sha256::hash(&declared_chunk).to_vec(),
+        }],
+        ..Default::default()
+    };
+    let result = test
+        .subnet_message("upload_canister_snapshot_metadata", args.encode())
+        .unwrap();
+    let snapshot_id = UploadCanisterSnapshotMetadataResponse::decode(&result.bytes())
+        .unwrap()
+        .get_snapshot_id();
+
+    // A chunk which was not declared in the metadata is rejected.
+    let args = UploadCanisterSnapshotDataArgs::new(
+        canister_id,
+        snapshot_id,
+        CanisterSnapshotDataOffset::WasmChunk,
+        vec![4, 5, 6],
+    );
+    let err = test
+        .subnet_message("upload_canister_snapshot_data", args.encode())
+        .unwrap_err();
+    assert_eq!(err.code(), ErrorCode::InvalidManagementPayload);
+    assert!(err.description().contains("was not declared"));
+
+    let args = UploadCanisterSnapshotDataArgs::new(
+        canister_id,
+        snapshot_id,
+        CanisterSnapshotDataOffset::WasmChunk,
+        declared_chunk,
+    );
+    test.subnet_message("upload_canister_snapshot_data", args.encode())
+        .unwrap();
+    let snapshot = test.state().canister_snapshots.get(snapshot_id).unwrap();
+    assert_eq!(snapshot.chunk_store().keys().count(), 1);
+}
+
+#[test]
+fn upload_canister_snapshot_wasm_module_in_chunks() {
+    const CYCLES: Cycles = Cycles::new(1_000_000_000_000_000);
+    let mut test = ExecutionTestBuilder::new()
+        .with_snapshots(FlagStatus::Enabled)
+        .build();
+    let canister_id = test
+        .canister_from_cycles_and_binary(CYCLES, UNIVERSAL_CANISTER_WASM.into())
+        .unwrap();
+
+    let args = UploadCanisterSnapshotMetadataArgs {
+        canister_id: canister_id.get(),
+        wasm_module_size: 10,
+        ..Default::default()
+    };
+    let result = test
+        .subnet_message("upload_canister_snapshot_metadata", args.encode())
+        .unwrap();
+    let snapshot_id = UploadCanisterSnapshotMetadataResponse::decode(&result.bytes())
+        .unwrap()
+        .get_snapshot_id();
+
+    for (offset, chunk) in [(0, vec![1, 2, 3]), (9, vec![9]), (1, vec![4])] {
+        let args = UploadCanisterSnapshotDataArgs::new(
+            canister_id,
+            snapshot_id,
+            CanisterSnapshotDataOffset::WasmModule { offset },
+            chunk,
+        );
+        test.subnet_message("upload_canister_snapshot_data", args.encode())
+            .unwrap();
+    }
+
+    // The module is only built when it is needed.
+    let snapshot = test.state().canister_snapshots.get(snapshot_id).unwrap();
+    assert!(snapshot.is_uploading_wasm_module());
+    assert_eq!(snapshot.wasm_module_size(), 10);
+
+    // Bytes which were not uploaded yet are zero.
+    let args = ReadCanisterSnapshotDataArgs::new(
+        canister_id,
+        snapshot_id,
+        CanisterSnapshotDataKind::WasmModule {
+            offset: 0,
+            size: 10,
+        },
+    );
+    let result = test
+        .subnet_message("read_canister_snapshot_data", args.encode())
+        .unwrap();
+    assert_eq!(
+        ReadCanisterSnapshotDataResponse::decode(&result.bytes())
+            .unwrap()
+            .chunk,
+        vec![1, 4, 3, 0, 0, 0, 0, 0, 0, 9]
+    );
+}
+
 /// Early warning system / stumbling block forcing the authors of changes adding
 /// or removing canister state fields to think about and/or ask the Execution
 /// team to think about any repercussions to the canister snapshot logic.
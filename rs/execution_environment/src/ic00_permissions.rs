@@ -13,6 +13,10 @@ pub(crate) struct Ic00MethodPermissions {
     allow_remote_subnet_sender: bool,
     /// Call initiated only by the NNS subnet.
     allow_only_nns_subnet_sender: bool,
+    /// Call initiated by an operator of the target canister. Operators may
+    /// only call methods that neither change the code nor the settings of
+    /// the canister; all other methods remain restricted to controllers.
+    allow_operator_sender: bool,
 }
 
 impl Ic00MethodPermissions {
@@ -22,136 +26,175 @@ impl Ic00MethodPermissions {
                 method,
                 allow_remote_subnet_sender: true,
                 allow_only_nns_subnet_sender: false,
+                allow_operator_sender: false,
             },
             Ic00Method::CanisterStatus => Self {
                 method,
                 allow_remote_subnet_sender: true,
                 allow_only_nns_subnet_sender: false,
+                allow_operator_sender: true,
             },
             Ic00Method::CanisterInfo => Self {
                 method,
                 allow_remote_subnet_sender: true,
                 allow_only_nns_subnet_sender: false,
+                allow_operator_sender: false,
             },
             Ic00Method::CreateCanister => Self {
                 method,
                 allow_remote_subnet_sender: false,
                 allow_only_nns_subnet_sender: false,
+                allow_operator_sender: false,
             },
             Ic00Method::DeleteCanister => Self {
                 method,
                 allow_remote_subnet_sender: true,
                 allow_only_nns_subnet_sender: false,
+                allow_operator_sender: false,
             },
             Ic00Method::DepositCycles => Self {
                 method,
                 allow_remote_subnet_sender: true,
                 allow_only_nns_subnet_sender: false,
+                allow_operator_sender: false,
             },
             Ic00Method::HttpRequest => Self {
                 method,
                 allow_remote_subnet_sender: false,
                 allow_only_nns_subnet_sender: false,
+                allow_operator_sender: false,
             },
             Ic00Method::ECDSAPublicKey => Self {
                 method,
                 allow_remote_subnet_sender: true,
                 allow_only_nns_subnet_sender: false,
+                allow_operator_sender: false,
             },
             Ic00Method::InstallCode => Self {
                 method,
                 allow_remote_subnet_sender: true,
                 allow_only_nns_subnet_sender: false,
+                allow_operator_sender: false,
             },
             Ic00Method::InstallChunkedCode => Self {
                 method,
                 allow_remote_subnet_sender: true,
                 allow_only_nns_subnet_sender: false,
+                allow_operator_sender: false,
             },
             Ic00Method::RawRand => Self {
                 method,
                 allow_remote_subnet_sender: false,
                 allow_only_nns_subnet_sender: false,
+                allow_operator_sender: false,
             },
             Ic00Method::SetupInitialDKG => Self {
                 method,
                 allow_remote_subnet_sender: true,
                 allow_only_nns_subnet_sender: true,
+                allow_operator_sender: false,
             },
             Ic00Method::StartCanister => Self {
                 method,
                 allow_remote_subnet_sender: true,
                 allow_only_nns_subnet_sender: false,
+                allow_operator_sender: true,
             },
             Ic00Method::StopCanister => Self {
                 method,
                 allow_remote_subnet_sender: true,
                 allow_only_nns_subnet_sender: false,
+                allow_operator_sender: true,
             },
             Ic00Method::UninstallCode => Self {
                 method,
                 allow_remote_subnet_sender: true,
                 allow_only_nns_subnet_sender: false,
+                allow_operator_sender: false,
             },
             Ic00Method::UpdateSettings => Self {
                 method,
                 allow_remote_subnet_sender: true,
                 allow_only_nns_subnet_sender: false,
+                allow_operator_sender: false,
             },
             Ic00Method::ComputeInitialIDkgDealings => Self {
                 method,
                 allow_remote_subnet_sender: true,
                 allow_only_nns_subnet_sender: true,
+                allow_operator_sender: false,
             },
             Ic00Method::SchnorrPublicKey => Self {
                 method,
                 allow_remote_subnet_sender: true,
                 allow_only_nns_subnet_sender: false,
+                allow_operator_sender: false,
             },
             Ic00Method::SignWithSchnorr => Self {
                 method,
                 allow_remote_subnet_sender: true,
                 allow_only_nns_subnet_sender: false,
+                allow_operator_sender: false,
+            },
+            Ic00Method::VetKdPublicKey => Self {
+                method,
+                allow_remote_subnet_sender: true,
+                allow_only_nns_subnet_sender: false,
+                allow_operator_sender: false,
             },
             Ic00Method::BitcoinGetBalance => Self {
                 method,
                 allow_remote_subnet_sender: true,
                 allow_only_nns_subnet_sender: false,
+                allow_operator_sender: false,
             },
             Ic00Method::BitcoinGetUtxos => Self {
                 method,
                 allow_remote_subnet_sender: true,
                 allow_only_nns_subnet_sender: false,
+                allow_operator_sender: false,
             },
             Ic00Method::BitcoinGetBlockHeaders => Self {
                 method,
                 allow_remote_subnet_sender: true,
                 allow_only_nns_subnet_sender: false,
+                allow_operator_sender: false,
             },
             Ic00Method::BitcoinSendTransaction => Self {
                 method,
                 allow_remote_subnet_sender: true,
                 allow_only_nns_subnet_sender: false,
+                allow_operator_sender: false,
             },
             Ic00Method::BitcoinGetCurrentFeePercentiles => Self {
                 method,
                 allow_remote_subnet_sender: true,
                 allow_only_nns_subnet_sender: false,
+                allow_operator_sender: false,
             },
             Ic00Method::BitcoinSendTransactionInternal => Self {
                 method,
                 allow_remote_subnet_sender: true,
                 allow_only_nns_subnet_sender: false,
+                allow_operator_sender: false,
             },
             Ic00Method::BitcoinGetSuccessors => Self {
                 method,
                 allow_remote_subnet_sender: true,
                 allow_only_nns_subnet_sender: false,
+                allow_operator_sender: false,
             },
             Ic00Method::NodeMetricsHistory => Self {
                 method,
                 allow_remote_subnet_sender: true,
                 allow_only_nns_subnet_sender: false,
+                allow_operator_sender: false,
+            },
+            Ic00Method::SubnetInfo => Self {
+                method,
+                allow_remote_subnet_sender: true,
+                allow_only_nns_subnet_sender: false,
+                allow_operator_sender: false,
             },
             Ic00Method::FetchCanisterLogs => Self {
                 method,
@@ -159,35 +202,54 @@ impl Ic00MethodPermissions {
                 // all inter-canister call permissions are irrelevant and therefore set to false.
                 allow_remote_subnet_sender: false,
                 allow_only_nns_subnet_sender: false,
+                allow_operator_sender: false,
             },
             Ic00Method::ProvisionalCreateCanisterWithCycles => Self {
                 method,
                 allow_remote_subnet_sender: true,
                 allow_only_nns_subnet_sender: false,
+                allow_operator_sender: false,
             },
             Ic00Method::ProvisionalTopUpCanister => Self {
                 method,
                 allow_remote_subnet_sender: true,
                 allow_only_nns_subnet_sender: false,
+                allow_operator_sender: false,
             },
             Ic00Method::UploadChunk | Ic00Method::StoredChunks | Ic00Method::ClearChunkStore => {
                 Self {
                     method,
                     allow_remote_subnet_sender: true,
                     allow_only_nns_subnet_sender: false,
+                    allow_operator_sender: false,
                 }
             }
-            Ic00Method::TakeCanisterSnapshot
-            | Ic00Method::LoadCanisterSnapshot
-            | Ic00Method::ListCanisterSnapshots
-            | Ic00Method::DeleteCanisterSnapshot => Self {
+            Ic00Method::TakeCanisterSnapshot | Ic00Method::ListCanisterSnapshots => Self {
+                method,
+                allow_remote_subnet_sender: true,
+                allow_only_nns_subnet_sender: false,
+                allow_operator_sender: true,
+            },
+            Ic00Method::LoadCanisterSnapshot
+            | Ic00Method::DeleteCanisterSnapshot
+            | Ic00Method::ReadCanisterSnapshotMetadata
+            | Ic00Method::ReadCanisterSnapshotData
+            | Ic00Method::UploadCanisterSnapshotMetadata
+            | Ic00Method::UploadCanisterSnapshotData => Self {
                 method,
                 allow_remote_subnet_sender: true,
                 allow_only_nns_subnet_sender: false,
+                allow_operator_sender: false,
             },
         }
     }
 
+    /// Returns whether operators of the target canister are allowed to call
+    /// the management method in addition to its controllers.
+    pub fn allow_operator_sender(&self) -> bool {
+        self.allow_operator_sender
+    }
+
     /// Verifies all the rules defined for a management method.
     pub fn verify(&self, msg: &CanisterCall, state: &ReplicatedState) -> Result<(), UserError> {
         match msg {
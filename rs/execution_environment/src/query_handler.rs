@@ -333,6 +333,7 @@ fn fetch_canister_logs(
             .canister_log
             .records()
             .iter()
+            .filter(|record| args.filter.map_or(true, |filter| filter.matches(record)))
             .cloned()
             .collect(),
     };
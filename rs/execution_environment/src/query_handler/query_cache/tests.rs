@@ -1479,11 +1479,21 @@ fn query_cache_future_proof_test() {
         | SystemApiCallId::CanisterStatus
         | SystemApiCallId::CanisterVersion
         | SystemApiCallId::CertifiedDataSet
+        | SystemApiCallId::CostCall
+        | SystemApiCallId::CostCreateCanister
+        | SystemApiCallId::CostHttpRequest
+        | SystemApiCallId::CostSignWithEcdsa
+        | SystemApiCallId::CostSignWithSchnorr
         | SystemApiCallId::CyclesBurn128
         | SystemApiCallId::DataCertificateCopy
         | SystemApiCallId::DataCertificatePresent
         | SystemApiCallId::DataCertificateSize
         | SystemApiCallId::DebugPrint
+        | SystemApiCallId::EnvVarCount
+        | SystemApiCallId::EnvVarNameCopy
+        | SystemApiCallId::EnvVarNameSize
+        | SystemApiCallId::EnvVarValueCopy
+        | SystemApiCallId::EnvVarValueSize
         | SystemApiCallId::GlobalTimerSet
         | SystemApiCallId::InReplicatedExecution
         | SystemApiCallId::IsController
@@ -1517,6 +1527,8 @@ fn query_cache_future_proof_test() {
         | SystemApiCallId::StableRead
         | SystemApiCallId::StableSize
         | SystemApiCallId::StableWrite
+        | SystemApiCallId::SubnetSelfCopy
+        | SystemApiCallId::SubnetSelfSize
         | SystemApiCallId::Time
         | SystemApiCallId::Trap
         | SystemApiCallId::TryGrowWasmMemory => {
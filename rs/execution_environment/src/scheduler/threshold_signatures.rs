@@ -140,6 +140,7 @@ mod tests {
                 key_id: key_id.clone(),
                 message: Arc::new(vec![1; 64]),
             }),
+            MasterPublicKeyId::VetKd(_) => panic!("vetKD keys are not supported by IDKG"),
         };
         let context = SignWithThresholdContext {
             request: RequestBuilder::new().build(),
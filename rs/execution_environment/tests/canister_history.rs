@@ -8,15 +8,15 @@ use ic_error_types::{ErrorCode, UserError};
 use ic_management_canister_types::CanisterInstallMode::{Install, Reinstall, Upgrade};
 use ic_management_canister_types::{
     self as ic00, CanisterChange, CanisterChangeDetails, CanisterChangeOrigin, CanisterIdRecord,
-    CanisterInfoRequest, CanisterInfoResponse, CreateCanisterArgs, InstallCodeArgs, Method,
-    Payload, UpdateSettingsArgs,
+    CanisterInfoRequest, CanisterInfoResponse, CreateCanisterArgs, EnvironmentVariable,
+    InstallCodeArgs, Method, Payload, UpdateSettingsArgs,
 };
 use ic_registry_subnet_type::SubnetType;
 use ic_replicated_state::canister_state::system_state::{
     CanisterHistory, MAX_CANISTER_HISTORY_CHANGES,
 };
 use ic_state_machine_tests::{PrincipalId, StateMachine, StateMachineBuilder, StateMachineConfig};
-use ic_types::{ingress::WasmResult, CanisterId, Cycles};
+use ic_types::{ingress::WasmResult, CanisterId, Cycles, EnvironmentVariables};
 use ic_types_test_utils::ids::user_test_id;
 use ic_universal_canister::{
     call_args, wasm, UNIVERSAL_CANISTER_WASM, UNIVERSAL_CANISTER_WASM_SHA256,
@@ -501,6 +501,205 @@ fn canister_history_tracks_controllers_change() {
     }
 }
 
+#[test]
+fn canister_history_tracks_environment_variables_change() {
+    let mut now = std::time::SystemTime::now();
+    let (env, _test_canister, _test_canister_sha256) = test_setup(SubnetType::Application, now);
+
+    let user_id1 = user_test_id(7).get();
+    let environment_variable = |name: &str, value: &str| EnvironmentVariable {
+        name: name.to_string(),
+        value: value.to_string(),
+    };
+
+    // create canister with environment variables via ingress from user_id1
+    let wasm_result = env
+        .execute_ingress_as(
+            user_id1,
+            ic00::IC_00,
+            ic00::Method::ProvisionalCreateCanisterWithCycles,
+            ic00::ProvisionalCreateCanisterWithCyclesArgs {
+                amount: Some(candid::Nat::from(INITIAL_CYCLES_BALANCE.get())),
+                settings: Some(
+                    CanisterSettingsArgsBuilder::new()
+                        .with_controllers(vec![user_id1])
+                        .with_environment_variables(vec![environment_variable("NETWORK", "test")])
+                        .build(),
+                ),
+                specified_id: None,
+                sender_canister_version: None,
+            }
+            .encode(),
+        )
+        .expect("failed to create canister");
+    let canister_id = match wasm_result {
+        WasmResult::Reply(bytes) => CanisterIdRecord::decode(&bytes[..])
+            .expect("failed to decode canister ID record")
+            .get_canister_id(),
+        WasmResult::Reject(reason) => panic!("create_canister call rejected: {}", reason),
+    };
+    let creation_timestamp_nanos = now.duration_since(UNIX_EPOCH).unwrap().as_nanos() as u64;
+    let initial_environment_variables =
+        EnvironmentVariables::new([("NETWORK".to_string(), "test".to_string())].into());
+    let mut reference_change_entries: Vec<CanisterChange> = vec![
+        CanisterChange::new(
+            creation_timestamp_nanos,
+            0,
+            CanisterChangeOrigin::from_user(user_id1),
+            CanisterChangeDetails::canister_creation(vec![user_id1]),
+        ),
+        CanisterChange::new(
+            creation_timestamp_nanos,
+            0,
+            CanisterChangeOrigin::from_user(user_id1),
+            CanisterChangeDetails::environment_variables_change(
+                initial_environment_variables.hash(),
+            ),
+        ),
+    ];
+
+    // update the environment variables via ingress from user_id1
+    now += Duration::from_secs(5);
+    env.set_time(now);
+    env.execute_ingress_as(
+        user_id1,
+        ic00::IC_00,
+        Method::UpdateSettings,
+        UpdateSettingsArgs {
+            canister_id: canister_id.into(),
+            settings: CanisterSettingsArgsBuilder::new()
+                .with_environment_variables(vec![
+                    environment_variable("NETWORK", "staging"),
+                    environment_variable("BACKEND", "rrkah-fqaaa-aaaaa-aaaaq-cai"),
+                ])
+                .build(),
+            sender_canister_version: None,
+        }
+        .encode(),
+    )
+    .unwrap();
+    let new_environment_variables = EnvironmentVariables::new(
+        [
+            ("NETWORK".to_string(), "staging".to_string()),
+            (
+                "BACKEND".to_string(),
+                "rrkah-fqaaa-aaaaa-aaaaq-cai".to_string(),
+            ),
+        ]
+        .into(),
+    );
+    reference_change_entries.push(CanisterChange::new(
+        now.duration_since(UNIX_EPOCH).unwrap().as_nanos() as u64,
+        1,
+        CanisterChangeOrigin::from_user(user_id1),
+        CanisterChangeDetails::environment_variables_change(new_environment_variables.hash()),
+    ));
+
+    // check canister history and the stored environment variables
+    let history = get_canister_history(&env, canister_id);
+    assert_eq!(
+        history
+            .get_changes(history.get_total_num_changes() as usize)
+            .map(|c| (**c).clone())
+            .collect::<Vec<CanisterChange>>(),
+        reference_change_entries
+    );
+    assert_eq!(
+        env.get_latest_state()
+            .canister_state(&canister_id)
+            .unwrap()
+            .system_state
+            .environment_variables,
+        new_environment_variables
+    );
+}
+
+#[test]
+fn canister_history_tracks_operators_change() {
+    let mut now = std::time::SystemTime::now();
+    let (env, _test_canister, _test_canister_sha256) = test_setup(SubnetType::Application, now);
+
+    let user_id1 = user_test_id(7).get();
+    let operator1 = user_test_id(8).get();
+    let operator2 = user_test_id(9).get();
+
+    // create canister with an operator via ingress from user_id1
+    let wasm_result = env
+        .execute_ingress_as(
+            user_id1,
+            ic00::IC_00,
+            ic00::Method::ProvisionalCreateCanisterWithCycles,
+            ic00::ProvisionalCreateCanisterWithCyclesArgs {
+                amount: Some(candid::Nat::from(INITIAL_CYCLES_BALANCE.get())),
+                settings: Some(
+                    CanisterSettingsArgsBuilder::new()
+                        .with_controllers(vec![user_id1])
+                        .with_operators(vec![operator1])
+                        .build(),
+                ),
+                specified_id: None,
+                sender_canister_version: None,
+            }
+            .encode(),
+        )
+        .expect("failed to create canister");
+    let canister_id = match wasm_result {
+        WasmResult::Reply(bytes) => CanisterIdRecord::decode(&bytes[..])
+            .expect("failed to decode canister ID record")
+            .get_canister_id(),
+        WasmResult::Reject(reason) => panic!("create_canister call rejected: {}", reason),
+    };
+    let creation_timestamp_nanos = now.duration_since(UNIX_EPOCH).unwrap().as_nanos() as u64;
+    let mut reference_change_entries: Vec<CanisterChange> = vec![
+        CanisterChange::new(
+            creation_timestamp_nanos,
+            0,
+            CanisterChangeOrigin::from_user(user_id1),
+            CanisterChangeDetails::canister_creation(vec![user_id1]),
+        ),
+        CanisterChange::new(
+            creation_timestamp_nanos,
+            0,
+            CanisterChangeOrigin::from_user(user_id1),
+            CanisterChangeDetails::operators_change(vec![operator1]),
+        ),
+    ];
+
+    // replace the operators via ingress from user_id1
+    now += Duration::from_secs(5);
+    env.set_time(now);
+    env.execute_ingress_as(
+        user_id1,
+        ic00::IC_00,
+        Method::UpdateSettings,
+        UpdateSettingsArgs {
+            canister_id: canister_id.into(),
+            settings: CanisterSettingsArgsBuilder::new()
+                .with_operators(vec![operator1, operator2])
+                .build(),
+            sender_canister_version: None,
+        }
+        .encode(),
+    )
+    .unwrap();
+    reference_change_entries.push(CanisterChange::new(
+        now.duration_since(UNIX_EPOCH).unwrap().as_nanos() as u64,
+        1,
+        CanisterChangeOrigin::from_user(user_id1),
+        CanisterChangeDetails::operators_change(vec![operator1, operator2]),
+    ));
+
+    // check canister history
+    let history = get_canister_history(&env, canister_id);
+    assert_eq!(
+        history
+            .get_changes(history.get_total_num_changes() as usize)
+            .map(|c| (**c).clone())
+            .collect::<Vec<CanisterChange>>(),
+        reference_change_entries
+    );
+}
+
 #[test]
 fn canister_history_cleared_if_canister_out_of_cycles() {
     let mut now = std::time::SystemTime::now();
@@ -3,7 +3,8 @@ use ic_config::subnet_config::SubnetConfig;
 use ic_management_canister_types::{
     self as ic00, BoundedAllowedViewers, CanisterIdRecord, CanisterInstallMode, CanisterLogRecord,
     CanisterSettingsArgs, CanisterSettingsArgsBuilder, DataSize, EmptyBlob,
-    FetchCanisterLogsRequest, FetchCanisterLogsResponse, LogVisibilityV2, Payload,
+    FetchCanisterLogsFilter, FetchCanisterLogsRange, FetchCanisterLogsRequest,
+    FetchCanisterLogsResponse, LogVisibilityV2, Payload,
 };
 use ic_registry_subnet_type::SubnetType;
 use ic_state_machine_tests::{
@@ -14,7 +15,8 @@ use ic_test_utilities::universal_canister::{call_args, wasm, UNIVERSAL_CANISTER_
 use ic_test_utilities_execution_environment::{get_reply, wat_canister, wat_fn};
 use ic_test_utilities_metrics::{fetch_histogram_stats, fetch_histogram_vec_stats, labels};
 use ic_types::{
-    ingress::WasmResult, CanisterId, Cycles, NumInstructions, MAX_ALLOWED_CANISTER_LOG_BUFFER_SIZE,
+    ingress::WasmResult, CanisterId, Cycles, NumInstructions, DEFAULT_CANISTER_LOG_BUFFER_SIZE,
+    MAX_CANISTER_LOG_BUFFER_SIZE,
 };
 use more_asserts::{assert_le, assert_lt};
 use proptest::{prelude::ProptestConfig, prop_assume};
@@ -107,6 +109,22 @@ fn fetch_canister_logs(
     )
 }
 
+fn fetch_canister_logs_with_filter(
+    env: &StateMachine,
+    sender: PrincipalId,
+    canister_id: CanisterId,
+    filter: FetchCanisterLogsFilter,
+) -> Result<WasmResult, UserError> {
+    env.query_as(
+        sender,
+        CanisterId::ic_00(),
+        "fetch_canister_logs",
+        FetchCanisterLogsRequest::new(canister_id)
+            .with_filter(filter)
+            .encode(),
+    )
+}
+
 #[test]
 fn test_fetch_canister_logs_via_submit_ingress() {
     let (env, canister_id) = setup_and_install_wasm(
@@ -523,7 +541,7 @@ fn test_canister_log_stays_within_limit() {
         wat_canister()
             .update(
                 "test",
-                wat_fn().debug_print(&[42; MAX_ALLOWED_CANISTER_LOG_BUFFER_SIZE]),
+                wat_fn().debug_print(&[42; DEFAULT_CANISTER_LOG_BUFFER_SIZE]),
             )
             .build_wasm(),
     );
@@ -540,7 +558,7 @@ fn test_canister_log_stays_within_limit() {
             .iter()
             .map(|r| r.data_size())
             .sum::<usize>(),
-        MAX_ALLOWED_CANISTER_LOG_BUFFER_SIZE
+        DEFAULT_CANISTER_LOG_BUFFER_SIZE
     );
 }
 
@@ -554,8 +572,8 @@ fn test_canister_log_in_state_stays_within_limit() {
             .update(
                 "test",
                 wat_fn()
-                    .debug_print(&[b'd'; MAX_ALLOWED_CANISTER_LOG_BUFFER_SIZE])
-                    .trap_with_blob(&[b't'; MAX_ALLOWED_CANISTER_LOG_BUFFER_SIZE]),
+                    .debug_print(&[b'd'; DEFAULT_CANISTER_LOG_BUFFER_SIZE])
+                    .trap_with_blob(&[b't'; DEFAULT_CANISTER_LOG_BUFFER_SIZE]),
             )
             .build_wasm(),
     );
@@ -565,7 +583,188 @@ fn test_canister_log_in_state_stays_within_limit() {
     // Expect that the total size of the log in canister state is not zero and less than the limit.
     let log_size = env.canister_log(canister_id).used_space();
     assert_lt!(0, log_size);
-    assert_le!(log_size, MAX_ALLOWED_CANISTER_LOG_BUFFER_SIZE);
+    assert_le!(log_size, DEFAULT_CANISTER_LOG_BUFFER_SIZE);
+}
+
+#[test]
+fn test_canister_log_with_log_memory_limit_stays_within_limit() {
+    // Test that a bigger log buffer keeps more records, stays within the
+    // configured limit and survives a checkpoint and node restart.
+    const MESSAGES_NUMBER: usize = 10;
+    let log_memory_limit = 4 * DEFAULT_CANISTER_LOG_BUFFER_SIZE;
+    let controller = PrincipalId::new_user_test_id(42);
+    let (env, canister_id) = setup_and_install_wasm(
+        CanisterSettingsArgsBuilder::new()
+            .with_log_visibility(LogVisibilityV2::Controllers)
+            .with_controllers(vec![controller])
+            .with_log_memory_limit(log_memory_limit as u64)
+            .build(),
+        wat_canister()
+            .update(
+                "test",
+                wat_fn().debug_print(&[42; DEFAULT_CANISTER_LOG_BUFFER_SIZE]),
+            )
+            .build_wasm(),
+    );
+    env.set_checkpoints_enabled(true);
+    for _ in 0..MESSAGES_NUMBER {
+        let _ = env.execute_ingress(canister_id, "test", vec![]);
+    }
+    let env = restart_node(env);
+
+    let result = fetch_canister_logs(&env, controller, canister_id);
+    let response = FetchCanisterLogsResponse::decode(&get_reply(result)).unwrap();
+    assert_lt!(1, response.canister_log_records.len());
+    assert_le!(
+        response
+            .canister_log_records
+            .iter()
+            .map(|r| r.data_size())
+            .sum::<usize>(),
+        log_memory_limit
+    );
+    assert_eq!(env.canister_log(canister_id).capacity(), log_memory_limit);
+
+    // Expect the log buffer above the default size to be reported and charged.
+    let status = env
+        .canister_status_as(controller, canister_id)
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        status.settings().log_memory_limit(),
+        candid::Nat::from(log_memory_limit as u64)
+    );
+    assert_le!(
+        (log_memory_limit - DEFAULT_CANISTER_LOG_BUFFER_SIZE) as u64,
+        status.memory_size().get()
+    );
+}
+
+#[test]
+fn test_update_settings_rejects_log_memory_limit_out_of_range() {
+    let (env, canister_id, _controller) = setup_with_controller(wat_canister().build_wasm());
+    for log_memory_limit in [
+        DEFAULT_CANISTER_LOG_BUFFER_SIZE - 1,
+        MAX_CANISTER_LOG_BUFFER_SIZE + 1,
+    ] {
+        let result = env.update_settings(
+            &canister_id,
+            CanisterSettingsArgsBuilder::new()
+                .with_log_memory_limit(log_memory_limit as u64)
+                .build(),
+        );
+        assert_eq!(
+            result.unwrap_err().code(),
+            ErrorCode::CanisterContractViolation
+        );
+    }
+}
+
+#[test]
+fn test_update_settings_rejects_log_memory_limit_above_memory_allocation() {
+    const KIB: u64 = 1024;
+    const MIB: u64 = 1024 * KIB;
+    let (env, canister_id) = setup_and_install_wasm(
+        CanisterSettingsArgsBuilder::new()
+            .with_controllers(vec![PrincipalId::new_user_test_id(42)])
+            .with_memory_allocation(MIB)
+            .build(),
+        wat_canister().build_wasm(),
+    );
+
+    // The log buffer above its default size would exceed the memory allocation.
+    let result = env.update_settings(
+        &canister_id,
+        CanisterSettingsArgsBuilder::new()
+            .with_log_memory_limit(MAX_CANISTER_LOG_BUFFER_SIZE as u64)
+            .build(),
+    );
+    assert_eq!(
+        result.unwrap_err().code(),
+        ErrorCode::InsufficientMemoryAllocation
+    );
+    assert_eq!(
+        env.canister_log(canister_id).capacity(),
+        DEFAULT_CANISTER_LOG_BUFFER_SIZE
+    );
+
+    // A log buffer within the memory allocation is accepted.
+    env.update_settings(
+        &canister_id,
+        CanisterSettingsArgsBuilder::new()
+            .with_log_memory_limit(64 * KIB)
+            .build(),
+    )
+    .unwrap();
+    assert_eq!(env.canister_log(canister_id).capacity(), 64 * KIB as usize);
+
+    // So is a larger log buffer together with a larger memory allocation.
+    env.update_settings(
+        &canister_id,
+        CanisterSettingsArgsBuilder::new()
+            .with_memory_allocation(4 * MIB)
+            .with_log_memory_limit(MAX_CANISTER_LOG_BUFFER_SIZE as u64)
+            .build(),
+    )
+    .unwrap();
+    assert_eq!(
+        env.canister_log(canister_id).capacity(),
+        MAX_CANISTER_LOG_BUFFER_SIZE
+    );
+}
+
+#[test]
+fn test_fetch_canister_logs_with_filter() {
+    let (env, canister_id, controller) = setup_with_controller(
+        wat_canister()
+            .update(
+                "test1",
+                wat_fn().debug_print(b"message 0").debug_print(b"message 1"),
+            )
+            .update(
+                "test2",
+                wat_fn().debug_print(b"message 2").debug_print(b"message 3"),
+            )
+            .build_wasm(),
+    );
+    let timestamp_01 = system_time_to_nanos(env.time_of_next_round());
+    let _ = env.execute_ingress(canister_id, "test1", vec![]);
+    env.advance_time(TIME_STEP);
+    let timestamp_23 = system_time_to_nanos(env.time_of_next_round());
+    let _ = env.execute_ingress(canister_id, "test2", vec![]);
+
+    // Filter by index, the end of the range is excluded.
+    let result = fetch_canister_logs_with_filter(
+        &env,
+        controller,
+        canister_id,
+        FetchCanisterLogsFilter::ByIdx(FetchCanisterLogsRange { start: 1, end: 3 }),
+    );
+    assert_eq!(
+        FetchCanisterLogsResponse::decode(&get_reply(result)).unwrap(),
+        canister_log_response(vec![
+            (1, timestamp_01, b"message 1".to_vec()),
+            (2, timestamp_23, b"message 2".to_vec()),
+        ])
+    );
+
+    // Filter by timestamp.
+    let result = fetch_canister_logs_with_filter(
+        &env,
+        controller,
+        canister_id,
+        FetchCanisterLogsFilter::ByTimestampNanos(FetchCanisterLogsRange {
+            start: timestamp_23,
+            end: u64::MAX,
+        }),
+    );
+    assert_eq!(
+        FetchCanisterLogsResponse::decode(&get_reply(result)).unwrap(),
+        canister_log_response(vec![
+            (2, timestamp_23, b"message 2".to_vec()),
+            (3, timestamp_23, b"message 3".to_vec()),
+        ])
+    );
 }
 
 #[test]
@@ -10,8 +10,10 @@ use ic_config::{
 };
 use ic_management_canister_types::{
     CanisterIdRecord, CanisterInfoRequest, CanisterInstallModeV2, CanisterSettingsArgsBuilder,
-    DeleteCanisterSnapshotArgs, EmptyBlob, InstallCodeArgs, ListCanisterSnapshotArgs, Method,
-    Payload, StoredChunksArgs, UninstallCodeArgs, IC_00,
+    CanisterSnapshotDataKind, CanisterSnapshotDataOffset, DeleteCanisterSnapshotArgs, EmptyBlob,
+    InstallCodeArgs, ListCanisterSnapshotArgs, Method, Payload, ReadCanisterSnapshotDataArgs,
+    ReadCanisterSnapshotMetadataArgs, StoredChunksArgs, UninstallCodeArgs,
+    UploadCanisterSnapshotDataArgs, UploadCanisterSnapshotMetadataArgs, IC_00,
 };
 use ic_registry_subnet_type::SubnetType;
 use ic_replicated_state::canister_state::{execution_state::NextScheduledMethod, NextExecution};
@@ -1070,7 +1072,11 @@ fn dts_aborted_execution_does_not_block_subnet_messages() {
         }
 
         let (method, args) = f(aborted_canister_id);
-        if method == Method::DeleteCanisterSnapshot {
+        if method == Method::DeleteCanisterSnapshot
+            || method == Method::ReadCanisterSnapshotMetadata
+            || method == Method::ReadCanisterSnapshotData
+            || method == Method::UploadCanisterSnapshotData
+        {
             env.take_canister_snapshot(TakeCanisterSnapshotArgs::new(aborted_canister_id, None))
                 .unwrap();
         }
@@ -1157,6 +1163,7 @@ fn dts_aborted_execution_does_not_block_subnet_messages() {
             | Method::ComputeInitialIDkgDealings
             | Method::SchnorrPublicKey
             | Method::SignWithSchnorr
+            | Method::VetKdPublicKey
             | Method::BitcoinGetBalance
             | Method::BitcoinGetUtxos
             | Method::BitcoinGetBlockHeaders
@@ -1165,6 +1172,7 @@ fn dts_aborted_execution_does_not_block_subnet_messages() {
             | Method::BitcoinSendTransactionInternal
             | Method::BitcoinGetSuccessors
             | Method::NodeMetricsHistory
+            | Method::SubnetInfo
             | Method::ProvisionalCreateCanisterWithCycles
             | Method::ProvisionalTopUpCanister => {}
             // Unsupported methods accepting just one argument.
@@ -1267,6 +1275,41 @@ fn dts_aborted_execution_does_not_block_subnet_messages() {
                 .encode();
                 (method, call_args().other_side(args))
             }),
+            Method::ReadCanisterSnapshotMetadata => test_supported(|aborted_canister_id| {
+                let args = ReadCanisterSnapshotMetadataArgs::new(
+                    aborted_canister_id,
+                    (aborted_canister_id, 0).into(),
+                )
+                .encode();
+                (method, call_args().other_side(args))
+            }),
+            Method::ReadCanisterSnapshotData => test_supported(|aborted_canister_id| {
+                let args = ReadCanisterSnapshotDataArgs::new(
+                    aborted_canister_id,
+                    (aborted_canister_id, 0).into(),
+                    CanisterSnapshotDataKind::WasmModule { offset: 0, size: 0 },
+                )
+                .encode();
+                (method, call_args().other_side(args))
+            }),
+            Method::UploadCanisterSnapshotMetadata => test_supported(|aborted_canister_id| {
+                let args = UploadCanisterSnapshotMetadataArgs {
+                    canister_id: aborted_canister_id.get(),
+                    ..Default::default()
+                }
+                .encode();
+                (method, call_args().other_side(args))
+            }),
+            Method::UploadCanisterSnapshotData => test_supported(|aborted_canister_id| {
+                let args = UploadCanisterSnapshotDataArgs::new(
+                    aborted_canister_id,
+                    (aborted_canister_id, 0).into(),
+                    CanisterSnapshotDataOffset::WasmModule { offset: 0 },
+                    vec![],
+                )
+                .encode();
+                (method, call_args().other_side(args))
+            }),
         }
     }
 }
@@ -2,7 +2,7 @@ use candid::Decode;
 use ic_management_canister_types::{
     self as ic00, CanisterInstallMode, DerivationPath, ECDSAPublicKeyResponse, EcdsaCurve,
     EcdsaKeyId, MasterPublicKeyId, Method, Payload as Ic00Payload, SchnorrAlgorithm, SchnorrKeyId,
-    SchnorrPublicKeyResponse, SignWithECDSAReply, SignWithSchnorrReply,
+    SchnorrPublicKeyResponse, SignWithECDSAReply, SignWithSchnorrReply, VetKdCurve, VetKdKeyId,
 };
 use ic_registry_subnet_type::SubnetType;
 use ic_state_machine_tests::{PrincipalId, StateMachine, StateMachineBuilder, UserError};
@@ -721,3 +721,49 @@ fn test_sign_with_threshold_key_queue_fills_up() {
         );
     }
 }
+
+#[test]
+fn test_vetkd_public_key() {
+    use ic_crypto_internal_bls12_381_vetkd::DerivedPublicKey;
+
+    let key_id = VetKdKeyId {
+        curve: VetKdCurve::Bls12_381_G2,
+        name: "some_key".to_string(),
+    };
+    let env = StateMachineBuilder::new()
+        .with_checkpoints_enabled(false)
+        .with_idkg_key(MasterPublicKeyId::VetKd(key_id.clone()))
+        .build();
+    let canister_id = create_universal_canister(&env);
+
+    let get_public_key = |derivation_path: Vec<Vec<u8>>| {
+        let result = env.execute_ingress(
+            canister_id,
+            "update",
+            wasm()
+                .call_simple(
+                    ic00::IC_00,
+                    Method::VetKdPublicKey,
+                    call_args()
+                        .other_side(
+                            ic00::VetKdPublicKeyArgs {
+                                canister_id: None,
+                                derivation_path: DerivationPath::new(
+                                    derivation_path.into_iter().map(Into::into).collect(),
+                                ),
+                                key_id: key_id.clone(),
+                            }
+                            .encode(),
+                        )
+                        .on_reject(wasm().reject_message().reject()),
+                )
+                .build(),
+        );
+        expect_reply::<ic00::VetKdPublicKeyResult>(result).public_key
+    };
+
+    let public_key = get_public_key(vec![b"path".to_vec()]);
+    assert!(DerivedPublicKey::deserialize(&public_key).is_ok());
+    assert_eq!(public_key, get_public_key(vec![b"path".to_vec()]));
+    assert_ne!(public_key, get_public_key(vec![b"other path".to_vec()]));
+}
@@ -35,7 +35,7 @@ use ic_types::{
     canister_http::{
         CanisterHttpMethod, CanisterHttpRequestContext, CanisterHttpResponse,
         CanisterHttpResponseContent, CanisterHttpResponseDivergence, CanisterHttpResponseMetadata,
-        CanisterHttpResponseShare, CanisterHttpResponseWithConsensus,
+        CanisterHttpResponseShare, CanisterHttpResponseWithConsensus, Replication,
         CANISTER_HTTP_MAX_RESPONSES_PER_BLOCK, CANISTER_HTTP_TIMEOUT_INTERVAL,
     },
     consensus::get_faults_tolerated,
@@ -338,6 +338,7 @@ fn timeout_priority() {
                     transform: None,
                     // this is the important one
                     time: UNIX_EPOCH,
+                    replication: Replication::FullyReplicated,
                 };
                 init_state
                     .metadata
@@ -454,6 +455,123 @@ fn divergence_response_inclusion_test() {
     });
 }
 
+/// Check that a response to a non-replicated request is included with the share
+/// of the designated node only, and that it only validates if signed by that node
+#[test]
+fn non_replicated_request_test() {
+    let mut init_state = ic_test_utilities_state::get_initial_state(0, 0);
+
+    test_config_with_http_feature(true, 4, |mut payload_builder, canister_http_pool| {
+        {
+            // Request 0 is made by this node, request 1 by another node
+            for (callback_id, node_id) in [(0, 0), (1, 1)] {
+                let context = CanisterHttpRequestContext {
+                    request: RequestBuilder::default().build(),
+                    url: String::new(),
+                    max_response_bytes: None,
+                    headers: vec![],
+                    body: None,
+                    http_method: CanisterHttpMethod::GET,
+                    transform: None,
+                    time: UNIX_EPOCH,
+                    replication: Replication::NonReplicated(node_test_id(node_id)),
+                };
+                init_state
+                    .metadata
+                    .subnet_call_context_manager
+                    .canister_http_request_contexts
+                    .insert(CallbackId::from(callback_id), context);
+            }
+
+            let state_manager = Arc::new(RefMockStateManager::default());
+            state_manager
+                .get_mut()
+                .expect_get_state_at()
+                .return_const(Ok(ic_interfaces_state_manager::Labeled::new(
+                    Height::new(0),
+                    Arc::new(init_state),
+                )));
+            payload_builder.state_reader = state_manager;
+        }
+
+        let (response, metadata) = test_response_and_metadata(0);
+        {
+            let mut pool_access = canister_http_pool.write().unwrap();
+            add_own_share_to_pool(
+                pool_access.deref_mut(),
+                &metadata_to_share(0, &metadata),
+                &response,
+            );
+
+            // Only the designated node holds the content of the other response
+            let (_, other_metadata) = test_response_and_metadata(1);
+            add_received_shares_to_pool(
+                pool_access.deref_mut(),
+                vec![metadata_to_share(1, &other_metadata)],
+            );
+        }
+
+        let context = default_validation_context();
+        let payload = payload_builder.build_payload(
+            Height::new(1),
+            NumBytes::new(4 * 1024 * 1024),
+            &[],
+            &context,
+        );
+
+        let parsed_payload = bytes_to_payload(&payload).expect("Failed to parse the payload");
+        assert_eq!(parsed_payload.num_responses(), 1);
+        assert_eq!(parsed_payload.responses[0].content, response);
+        assert_eq!(
+            parsed_payload.responses[0]
+                .proof
+                .signature
+                .signatures_map
+                .keys()
+                .cloned()
+                .collect::<Vec<_>>(),
+            vec![node_test_id(0)]
+        );
+
+        assert!(payload_builder
+            .validate_payload(
+                Height::new(1),
+                &test_proposal_context(&context),
+                &payload,
+                &[],
+            )
+            .is_ok());
+
+        // A response signed by a node other than the designated one is rejected
+        let mut invalid_response = response_and_metadata_to_proof(&response, &metadata);
+        invalid_response
+            .proof
+            .signature
+            .signatures_map
+            .insert(node_test_id(1), BasicSigOf::new(BasicSig(vec![])));
+        let payload = CanisterHttpPayload {
+            responses: vec![invalid_response],
+            timeouts: vec![],
+            divergence_responses: vec![],
+        };
+        let payload = payload_to_bytes(&payload, NumBytes::new(4 * 1024 * 1024));
+
+        match payload_builder.validate_payload(
+            Height::new(1),
+            &test_proposal_context(&context),
+            &payload,
+            &[],
+        ) {
+            Err(ValidationError::InvalidArtifact(
+                InvalidPayloadReason::InvalidCanisterHttpPayload(
+                    InvalidCanisterHttpPayloadReason::InvalidNonReplicatedSigners { .. },
+                ),
+            )) => (),
+            x => panic!("Expected InvalidNonReplicatedSigners, got {:?}", x),
+        }
+    });
+}
+
 /// Submit a very large number of valid responses, then check that the
 /// payload builder does not process all of them but only CANISTER_HTTP_RESPONSES_PER_BLOCK
 #[test]
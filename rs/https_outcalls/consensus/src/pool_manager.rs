@@ -182,6 +182,12 @@ impl CanisterHttpPoolManagerImpl {
             .collect();
 
         for (id, context) in http_requests {
+            // Non-replicated requests are only made by the designated node.
+            if let Replication::NonReplicated(node_id) = context.replication {
+                if node_id != self.replica_config.node_id {
+                    continue;
+                }
+            }
             if !request_ids_already_made.contains(&id) {
                 let timeout = context.time + Duration::from_secs(5 * 60);
                 if let Err(err) = self
@@ -285,7 +291,12 @@ impl CanisterHttpPoolManagerImpl {
             return Vec::new();
         };
 
-        let active_callback_ids = self.active_callback_ids();
+        let state = self.state_reader.get_latest_state();
+        let http_contexts = &state
+            .get_ref()
+            .metadata
+            .subnet_call_context_manager
+            .canister_http_request_contexts;
         let next_callback_id = self.next_callback_id();
 
         let key_from_share =
@@ -308,9 +319,9 @@ impl CanisterHttpPoolManagerImpl {
                     ));
                 }
 
-                if !active_callback_ids.contains(&share.content.id) {
+                let Some(context) = http_contexts.get(&share.content.id) else {
                     return Some(CanisterHttpChangeAction::RemoveUnvalidated(share.clone()));
-                }
+                };
 
                 let node_is_in_committee = self
                     .membership
@@ -335,6 +346,15 @@ impl CanisterHttpPoolManagerImpl {
                             .to_string(),
                     ));
                 }
+                if let Replication::NonReplicated(node_id) = context.replication {
+                    if share.signature.signer != node_id {
+                        return Some(CanisterHttpChangeAction::HandleInvalid(
+                            share.clone(),
+                            "Share for a non-replicated request signed by a node other than the designated one"
+                                .to_string(),
+                        ));
+                    }
+                }
                 // TODO: more precise error handling
                 if let Err(err) = self.crypto.verify(share, registry_version) {
                     error!(self.log, "Unable to verify signature of share, {}", err);
@@ -449,7 +469,7 @@ pub mod test {
     use ic_registry_subnet_type::SubnetType;
     use ic_replicated_state::metadata_state::subnet_call_context_manager::SubnetCallContext;
     use ic_test_utilities_logger::with_test_replica_logger;
-    use ic_test_utilities_types::ids::subnet_test_id;
+    use ic_test_utilities_types::ids::{node_test_id, subnet_test_id};
     use ic_types::{
         crypto::{CryptoHash, CryptoHashOf},
         messages::CallbackId,
@@ -526,6 +546,7 @@ pub mod test {
                     http_method: CanisterHttpMethod::GET,
                     transform: None,
                     time: ic_types::Time::from_nanos_since_unix_epoch(10),
+                    replication: Replication::FullyReplicated,
                 };
 
                 state_manager
@@ -623,6 +644,7 @@ pub mod test {
                     http_method: CanisterHttpMethod::GET,
                     transform: None,
                     time: ic_types::Time::from_nanos_since_unix_epoch(10),
+                    replication: Replication::FullyReplicated,
                 };
 
                 state_manager
@@ -747,6 +769,7 @@ pub mod test {
                     http_method: CanisterHttpMethod::GET,
                     transform: None,
                     time: ic_types::Time::from_nanos_since_unix_epoch(10),
+                    replication: Replication::FullyReplicated,
                 };
 
                 state_manager
@@ -891,6 +914,7 @@ pub mod test {
                     http_method: CanisterHttpMethod::GET,
                     transform: None,
                     time: ic_types::Time::from_nanos_since_unix_epoch(10),
+                    replication: Replication::FullyReplicated,
                 };
 
                 // Expect times to be called exactly once to check that already
@@ -966,4 +990,182 @@ pub mod test {
             });
         });
     }
+
+    #[test]
+    pub fn test_non_replicated_requests_only_made_by_designated_node() {
+        ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
+            with_test_replica_logger(|log| {
+                let Dependencies {
+                    pool,
+                    replica_config,
+                    crypto,
+                    state_manager,
+                    registry,
+                    ..
+                } = dependencies(pool_config.clone(), 4);
+                let mut shim_mock = MockNonBlockingChannel::<CanisterHttpRequest>::new();
+                shim_mock
+                    .expect_try_receive()
+                    .return_const(Err(TryReceiveError::Empty));
+
+                let request = |node_id| CanisterHttpRequestContext {
+                    request: ic_test_utilities_types::messages::RequestBuilder::new().build(),
+                    url: "".to_string(),
+                    max_response_bytes: None,
+                    headers: vec![],
+                    body: None,
+                    http_method: CanisterHttpMethod::GET,
+                    transform: None,
+                    time: ic_types::Time::from_nanos_since_unix_epoch(10),
+                    replication: Replication::NonReplicated(node_id),
+                };
+                let own_request = request(replica_config.node_id);
+
+                // Only the request designated to this node is sent to the adapter.
+                shim_mock
+                    .expect_send()
+                    .with(eq(CanisterHttpRequest {
+                        id: CallbackId::from(7),
+                        timeout: ic_types::Time::from_nanos_since_unix_epoch(10)
+                            + Duration::from_secs(60 * 5),
+                        context: own_request.clone(),
+                    }))
+                    .times(1)
+                    .return_const(Ok(()));
+
+                let shim: Arc<Mutex<CanisterHttpAdapterClient>> =
+                    Arc::new(Mutex::new(Box::new(shim_mock)));
+
+                state_manager
+                    .get_mut()
+                    .expect_get_latest_state()
+                    .return_const(Labeled::new(
+                        Height::from(1),
+                        Arc::new(state_with_pending_http_calls(BTreeMap::from([
+                            (CallbackId::from(7), own_request),
+                            (CallbackId::from(8), request(node_test_id(1))),
+                        ]))),
+                    ));
+
+                let pool_manager = CanisterHttpPoolManagerImpl::new(
+                    state_manager,
+                    shim,
+                    crypto,
+                    pool.get_cache(),
+                    replica_config,
+                    Arc::clone(&registry) as Arc<_>,
+                    MetricsRegistry::new(),
+                    log,
+                );
+                let canister_http_pool =
+                    CanisterHttpPoolImpl::new(MetricsRegistry::new(), no_op_logger());
+                let change_set = pool_manager.generate_change_set(&canister_http_pool);
+                assert_eq!(change_set.len(), 0);
+            });
+        });
+    }
+
+    #[test]
+    pub fn test_invalidation_of_non_replicated_shares_from_other_nodes() {
+        ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
+            with_test_replica_logger(|log| {
+                let Dependencies {
+                    pool,
+                    replica_config,
+                    crypto,
+                    state_manager,
+                    registry,
+                    ..
+                } = dependencies(pool_config.clone(), 5);
+                let mut shim_mock = MockNonBlockingChannel::<CanisterHttpRequest>::new();
+                shim_mock
+                    .expect_try_receive()
+                    .return_const(Err(TryReceiveError::Empty));
+
+                let request = CanisterHttpRequestContext {
+                    request: ic_test_utilities_types::messages::RequestBuilder::new().build(),
+                    url: "".to_string(),
+                    max_response_bytes: None,
+                    headers: vec![],
+                    body: None,
+                    http_method: CanisterHttpMethod::GET,
+                    transform: None,
+                    time: ic_types::Time::from_nanos_since_unix_epoch(10),
+                    replication: Replication::NonReplicated(node_test_id(1)),
+                };
+
+                state_manager
+                    .get_mut()
+                    .expect_get_latest_state()
+                    .return_const(Labeled::new(
+                        Height::from(1),
+                        Arc::new(state_with_pending_http_calls(BTreeMap::from([(
+                            CallbackId::from(0),
+                            request,
+                        )]))),
+                    ));
+
+                let response_metadata = CanisterHttpResponseMetadata {
+                    id: CallbackId::from(0),
+                    timeout: ic_types::Time::from_nanos_since_unix_epoch(10),
+                    registry_version: RegistryVersion::from(1),
+                    content_hash: CryptoHashOf::new(CryptoHash(vec![])),
+                };
+
+                let mut canister_http_pool =
+                    CanisterHttpPoolImpl::new(MetricsRegistry::new(), no_op_logger());
+
+                // Insert a share signed by a committee member that is not the designated node.
+                {
+                    let signature = crypto
+                        .sign(
+                            &response_metadata,
+                            replica_config.node_id,
+                            RegistryVersion::from(1),
+                        )
+                        .unwrap();
+
+                    let share = Signed {
+                        content: response_metadata.clone(),
+                        signature,
+                    };
+                    canister_http_pool.insert(UnvalidatedArtifact {
+                        message: share,
+                        peer_id: replica_config.node_id,
+                        timestamp: UNIX_EPOCH,
+                    });
+                }
+
+                let shim: Arc<Mutex<CanisterHttpAdapterClient>> =
+                    Arc::new(Mutex::new(Box::new(shim_mock)));
+
+                let pool_manager = CanisterHttpPoolManagerImpl::new(
+                    state_manager as Arc<_>,
+                    shim,
+                    crypto,
+                    pool.get_cache(),
+                    replica_config,
+                    Arc::clone(&registry) as Arc<_>,
+                    MetricsRegistry::new(),
+                    log,
+                );
+
+                let changes = pool_manager.validate_shares(
+                    pool.get_cache().as_ref(),
+                    &canister_http_pool,
+                    Height::from(0),
+                );
+
+                assert_eq!(changes.len(), 1);
+                if let CanisterHttpChangeAction::HandleInvalid(_, err) = &changes[0] {
+                    assert_eq!(
+                        err,
+                        "Share for a non-replicated request signed by a node other than the designated one"
+                    );
+                } else {
+                    panic!("unexpected change action");
+                }
+            })
+        });
+    }
 }
@@ -64,6 +64,12 @@ pub enum InvalidCanisterHttpPayloadReason {
         signers: Vec<NodeId>,
         expected_threshold: Threshold,
     },
+    /// A response to a non-replicated request was not signed by exactly the
+    /// node designated to make the request
+    InvalidNonReplicatedSigners {
+        designated_node: NodeId,
+        signers: Vec<NodeId>,
+    },
     /// The payload contains a duplicate response
     DuplicateResponse(CallbackId),
     DivergenceProofContainsMultipleCallbackIds,
@@ -155,6 +155,16 @@ pub enum SystemApiCallId {
     CanisterVersion,
     /// Tracker for `ic0.certified_data_set()`
     CertifiedDataSet,
+    /// Tracker for `ic0.cost_call()`
+    CostCall,
+    /// Tracker for `ic0.cost_create_canister()`
+    CostCreateCanister,
+    /// Tracker for `ic0.cost_http_request()`
+    CostHttpRequest,
+    /// Tracker for `ic0.cost_sign_with_ecdsa()`
+    CostSignWithEcdsa,
+    /// Tracker for `ic0.cost_sign_with_schnorr()`
+    CostSignWithSchnorr,
     /// Tracker for `ic0.cycles_burn128()`
     CyclesBurn128,
     /// Tracker for `ic0.data_certificate_copy()`
@@ -165,6 +175,16 @@ pub enum SystemApiCallId {
     DataCertificateSize,
     /// Tracker for `ic0.debug_print()`
     DebugPrint,
+    /// Tracker for `ic0.env_var_count()`
+    EnvVarCount,
+    /// Tracker for `ic0.env_var_name_copy()`
+    EnvVarNameCopy,
+    /// Tracker for `ic0.env_var_name_size()`
+    EnvVarNameSize,
+    /// Tracker for `ic0.env_var_value_copy()`
+    EnvVarValueCopy,
+    /// Tracker for `ic0.env_var_value_size()`
+    EnvVarValueSize,
     /// Tracker for `ic0.global_timer_set()`
     GlobalTimerSet,
     /// Tracker for `ic0.in_replicated_execution()`
@@ -231,6 +251,10 @@ pub enum SystemApiCallId {
     StableSize,
     /// Tracker for `ic0.stable_write())`
     StableWrite,
+    /// Tracker for `ic0.subnet_self_copy()`
+    SubnetSelfCopy,
+    /// Tracker for `ic0.subnet_self_size()`
+    SubnetSelfSize,
     /// Tracker for `ic0.time()`
     Time,
     /// Tracker for `ic0.trap()`
@@ -712,6 +736,20 @@ pub trait SystemApi {
         heap: &mut [u8],
     ) -> HypervisorResult<()>;
 
+    /// Returns the size of the blob corresponding to the id of the subnet
+    /// the canister is running on.
+    fn ic0_subnet_self_size(&self) -> HypervisorResult<usize>;
+
+    /// Copies `size` bytes starting from `offset` in the id blob of the
+    /// subnet the canister is running on to heap[dst..dst+size].
+    fn ic0_subnet_self_copy(
+        &mut self,
+        dst: usize,
+        offset: usize,
+        size: usize,
+        heap: &mut [u8],
+    ) -> HypervisorResult<()>;
+
     /// Outputs the specified bytes on the heap as a string on STDOUT.
     fn ic0_debug_print(&self, src: usize, size: usize, heap: &[u8]) -> HypervisorResult<()>;
 
@@ -1151,6 +1189,124 @@ pub trait SystemApi {
         dst: usize,
         heap: &mut [u8],
     ) -> HypervisorResult<()>;
+
+    /// Returns the amount of cycles that the canister is charged for
+    /// performing an inter-canister call with a method name and payload of
+    /// the given sizes, including the prepayment for the response.
+    ///
+    /// The amount of cycles is represented by a 128-bit value
+    /// and is copied in the canister memory starting
+    /// at the location `dst`.
+    fn ic0_cost_call(
+        &self,
+        method_name_size: u64,
+        payload_size: u64,
+        dst: usize,
+        heap: &mut [u8],
+    ) -> HypervisorResult<()>;
+
+    /// Returns the amount of cycles that must be attached to a call to the
+    /// management canister's `create_canister` method on this subnet.
+    ///
+    /// The amount of cycles is represented by a 128-bit value
+    /// and is copied in the canister memory starting
+    /// at the location `dst`.
+    fn ic0_cost_create_canister(&self, dst: usize, heap: &mut [u8]) -> HypervisorResult<()>;
+
+    /// Returns the amount of cycles that must be attached to a call to the
+    /// management canister's `http_request` method with a request of the
+    /// given size and the given `max_response_bytes`.
+    ///
+    /// The amount of cycles is represented by a 128-bit value
+    /// and is copied in the canister memory starting
+    /// at the location `dst`.
+    fn ic0_cost_http_request(
+        &self,
+        request_size: u64,
+        max_res_bytes: u64,
+        dst: usize,
+        heap: &mut [u8],
+    ) -> HypervisorResult<()>;
+
+    /// Returns the amount of cycles that must be attached to a call to the
+    /// management canister's `sign_with_ecdsa` method with the key whose
+    /// name is given by `src`/`size` and the given curve.
+    ///
+    /// On success the 128-bit amount is copied to `dst` and `0` is returned.
+    /// Returns `1` if the curve is unknown and `2` if no subnet is enabled
+    /// to sign with the key, in which case nothing is copied.
+    fn ic0_cost_sign_with_ecdsa(
+        &self,
+        src: usize,
+        size: usize,
+        curve: u32,
+        dst: usize,
+        heap: &mut [u8],
+    ) -> HypervisorResult<u32>;
+
+    /// Returns the amount of cycles that must be attached to a call to the
+    /// management canister's `sign_with_schnorr` method with the key whose
+    /// name is given by `src`/`size` and the given algorithm.
+    ///
+    /// On success the 128-bit amount is copied to `dst` and `0` is returned.
+    /// Returns `1` if the algorithm is unknown and `2` if no subnet is enabled
+    /// to sign with the key, in which case nothing is copied.
+    fn ic0_cost_sign_with_schnorr(
+        &self,
+        src: usize,
+        size: usize,
+        algorithm: u32,
+        dst: usize,
+        heap: &mut [u8],
+    ) -> HypervisorResult<u32>;
+
+    /// Returns the number of environment variables of the canister.
+    fn ic0_env_var_count(&self) -> HypervisorResult<usize>;
+
+    /// Returns the size of the name of the environment variable at `index`.
+    /// Environment variables are ordered by name.
+    ///
+    /// Traps if `index` is not smaller than `ic0_env_var_count()`.
+    fn ic0_env_var_name_size(&self, index: usize) -> HypervisorResult<usize>;
+
+    /// Copies `size` bytes starting from `offset` of the name of the
+    /// environment variable at `index` to the heap starting at `dst`.
+    ///
+    /// Traps if `index` is not smaller than `ic0_env_var_count()`.
+    fn ic0_env_var_name_copy(
+        &self,
+        index: usize,
+        dst: usize,
+        offset: usize,
+        size: usize,
+        heap: &mut [u8],
+    ) -> HypervisorResult<()>;
+
+    /// Returns the size of the value of the environment variable whose name
+    /// is given by `name_src`/`name_size`.
+    ///
+    /// Traps if the canister has no environment variable with that name.
+    fn ic0_env_var_value_size(
+        &self,
+        name_src: usize,
+        name_size: usize,
+        heap: &[u8],
+    ) -> HypervisorResult<usize>;
+
+    /// Copies `size` bytes starting from `offset` of the value of the
+    /// environment variable whose name is given by `name_src`/`name_size` to
+    /// the heap starting at `dst`.
+    ///
+    /// Traps if the canister has no environment variable with that name.
+    fn ic0_env_var_value_copy(
+        &self,
+        name_src: usize,
+        name_size: usize,
+        dst: usize,
+        offset: usize,
+        size: usize,
+        heap: &mut [u8],
+    ) -> HypervisorResult<()>;
 }
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
@@ -896,6 +896,7 @@ impl BatchProcessorImpl {
                 })
                 .transpose()?
                 .unwrap_or_default();
+            let replica_version = subnet_record.replica_version_id;
 
             subnets.insert(
                 *subnet_id,
@@ -905,6 +906,7 @@ impl BatchProcessorImpl {
                     subnet_type,
                     subnet_features,
                     idkg_keys_held,
+                    replica_version,
                 },
             );
         }
@@ -116,6 +116,7 @@ fn test_fixture(provided_batch: &Batch) -> StateMachineTestFixture {
             subnet_type: SubnetType::Application,
             subnet_features: SubnetFeatures::default(),
             idkg_keys_held: BTreeSet::new(),
+            replica_version: String::new(),
         },
     );
 
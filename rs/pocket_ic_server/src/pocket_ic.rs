@@ -56,25 +56,28 @@ use ic_types::{
     crypto::{BasicSig, BasicSigOf, CryptoResult, Signable},
     messages::{
         CertificateDelegation, HttpCallContent, HttpRequestEnvelope, MessageId as OtherMessageId,
-        QueryResponseHash, ReplicaHealthStatus, SignedIngress,
+        Payload as MsgPayload, QueryResponseHash, RejectContext, ReplicaHealthStatus,
+        RequestOrResponse, Response as CanisterResponse, SignedIngress,
     },
     time::GENESIS,
+    xnet::StreamIndex,
     CanisterId, Height, NodeId, NumInstructions, PrincipalId, RegistryVersion, SubnetId,
 };
 use ic_validator_ingress_message::StandaloneIngressSigVerifier;
 use itertools::Itertools;
 use pocket_ic::common::rest::{
-    self, BinaryBlob, BlobCompression, CanisterHttpHeader, CanisterHttpMethod, CanisterHttpRequest,
-    CanisterHttpResponse, DtsFlag, ExtendedSubnetConfigSet, MockCanisterHttpResponse, RawAddCycles,
-    RawCanisterCall, RawCanisterId, RawEffectivePrincipal, RawMessageId, RawSetStableMemory,
-    SubnetInstructionConfig, SubnetKind, SubnetSpec, Topology,
+    self, BinaryBlob, BlobCompression, CallResponse, CanisterHttpHeader, CanisterHttpMethod,
+    CanisterHttpRequest, CanisterHttpResponse, DtsFlag, ExtendedSubnetConfigSet, MockCallResponse,
+    MockCanisterHttpResponse, PendingCall, RawAddCycles, RawCanisterCall, RawCanisterId,
+    RawEffectivePrincipal, RawMessageId, RawSetStableMemory, SubnetInstructionConfig, SubnetKind,
+    SubnetSpec, Topology,
 };
 use serde::{Deserialize, Serialize};
 use std::hash::Hash;
 use std::str::FromStr;
 use std::{
     cmp::max,
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, BTreeSet, HashMap},
     fs::{remove_file, File},
     io::{BufReader, Write},
     net::SocketAddr,
@@ -131,6 +134,10 @@ fn compute_subnet_seed(
 struct RawTopologyInternal {
     pub subnet_configs: BTreeMap<String, RawSubnetConfigInternal>,
     pub default_effective_canister_id: RawCanisterId,
+    #[serde(default)]
+    pub mocked_callees: BTreeSet<CanisterId>,
+    #[serde(default)]
+    pub answered_mock_calls: BTreeSet<(SubnetId, StreamIndex)>,
 }
 
 #[derive(Clone, Deserialize, Serialize)]
@@ -227,6 +234,13 @@ pub struct PocketIc {
     subnets: Arc<RwLock<BTreeMap<SubnetId, Arc<StateMachine>>>>,
     canister_http_adapters: CanisterHttpAdapters,
     routing_table: RoutingTable,
+    /// Canister IDs routed to the mock subnet whose incoming calls are mocked.
+    mocked_callees: BTreeSet<CanisterId>,
+    /// Calls to mocked callees (identified by the caller's subnet ID and
+    /// the index in the stream to the mock subnet) that have been responded to,
+    /// but are still retained in the stream because an earlier call in that
+    /// stream is pending.
+    answered_mock_calls: BTreeSet<(SubnetId, StreamIndex)>,
     /// Created on initialization and updated if a new subnet is created.
     topology: TopologyInternal,
     // The initial state hash used for computing the state label
@@ -237,6 +251,8 @@ pub struct PocketIc {
     registry_data_provider: Arc<ProtoRegistryDataProvider>,
     runtime: Arc<Runtime>,
     nonmainnet_features: bool,
+    coverage_instrumentation: bool,
+    instruction_profiling_dir: Option<TempDir>,
     log_level: Option<Level>,
     bitcoind_addr: Option<SocketAddr>,
     _bitcoin_adapter_parts: Option<BitcoinAdapterParts>,
@@ -271,6 +287,8 @@ impl Drop for PocketIc {
             let raw_topology: RawTopologyInternal = RawTopologyInternal {
                 subnet_configs,
                 default_effective_canister_id: self.topology.default_effective_canister_id.into(),
+                mocked_callees: self.mocked_callees.clone(),
+                answered_mock_calls: self.answered_mock_calls.clone(),
             };
             let topology_json = serde_json::to_string(&raw_topology).unwrap();
             topology_file.write_all(topology_json.as_bytes()).unwrap();
@@ -332,6 +350,8 @@ impl PocketIc {
         registry_data_provider: Arc<ProtoRegistryDataProvider>,
         time: SystemTime,
         nonmainnet_features: bool,
+        coverage_instrumentation: bool,
+        instruction_profiling_dir: Option<PathBuf>,
         log_level: Option<Level>,
         bitcoin_adapter_uds_path: Option<PathBuf>,
     ) -> StateMachineBuilder {
@@ -365,6 +385,10 @@ impl PocketIc {
             .embedders_config
             .feature_flags
             .rate_limiting_of_debug_prints = FlagStatus::Disabled;
+        if coverage_instrumentation {
+            hypervisor_config.embedders_config.coverage_instrumentation = FlagStatus::Enabled;
+        }
+        hypervisor_config.embedders_config.instruction_profiling_dir = instruction_profiling_dir;
         let state_machine_config = StateMachineConfig::new(subnet_config, hypervisor_config);
         let t = time
             .duration_since(SystemTime::UNIX_EPOCH)
@@ -389,9 +413,13 @@ impl PocketIc {
         subnet_configs: ExtendedSubnetConfigSet,
         state_dir: Option<PathBuf>,
         nonmainnet_features: bool,
+        coverage_instrumentation: bool,
+        instruction_profiling: bool,
         log_level: Option<Level>,
         bitcoind_addr: Option<SocketAddr>,
     ) -> Self {
+        let instruction_profiling_dir = instruction_profiling
+            .then(|| TempDir::new().expect("failed to create a temporary directory"));
         let mut range_gen = RangeGen::new();
         let mut routing_table = RoutingTable::new();
         let mut nns_subnet_id = subnet_configs.nns.as_ref().and_then(|x| {
@@ -409,6 +437,16 @@ impl PocketIc {
             None
         };
 
+        let (mocked_callees, answered_mock_calls) = topology
+            .as_ref()
+            .map(|topology| {
+                (
+                    topology.mocked_callees.clone(),
+                    topology.answered_mock_calls.clone(),
+                )
+            })
+            .unwrap_or_default();
+
         let subnet_config_info: Vec<SubnetConfigInfo> = if let Some(topology) = topology {
             topology
                 .subnet_configs
@@ -534,6 +572,10 @@ impl PocketIc {
                 registry_data_provider.clone(),
                 time,
                 nonmainnet_features,
+                coverage_instrumentation,
+                instruction_profiling_dir
+                    .as_ref()
+                    .map(|dir| dir.path().to_path_buf()),
                 log_level,
                 bitcoin_adapter_uds_path.clone(),
             );
@@ -609,6 +651,15 @@ impl PocketIc {
             subnet_configs.insert(subnet_seed, subnet_config_internal);
         }
 
+        // Route the mocked callees restored from the state directory to the mock subnet.
+        for canister_id in &mocked_callees {
+            let range = CanisterIdRange {
+                start: *canister_id,
+                end: *canister_id,
+            };
+            routing_table.insert(range, mock_subnet_id()).unwrap();
+        }
+
         // Finalize registry with subnet IDs that are only available now that we created
         // all the StateMachines.
         let subnet_list = subnet_configs
@@ -659,6 +710,7 @@ impl PocketIc {
         let initial_state_hash = compute_state_label(
             &hasher.finish(),
             subnets.read().unwrap().values().cloned().collect(),
+            &mocked_callees,
         )
         .0;
 
@@ -701,12 +753,16 @@ impl PocketIc {
             subnets,
             canister_http_adapters,
             routing_table,
+            mocked_callees,
+            answered_mock_calls,
             topology,
             initial_state_hash,
             range_gen,
             registry_data_provider,
             runtime,
             nonmainnet_features,
+            coverage_instrumentation,
+            instruction_profiling_dir,
             log_level,
             bitcoind_addr,
             _bitcoin_adapter_parts,
@@ -715,7 +771,8 @@ impl PocketIc {
 
     fn try_route_canister(&self, canister_id: CanisterId) -> Option<Arc<StateMachine>> {
         let subnet_id = self.routing_table.route(canister_id.into());
-        subnet_id.map(|subnet_id| self.get_subnet_with_id(subnet_id).unwrap())
+        // Mocked callees are routed to the mock subnet which has no `StateMachine`.
+        subnet_id.and_then(|subnet_id| self.get_subnet_with_id(subnet_id))
     }
 
     fn any_subnet(&self) -> Arc<StateMachine> {
@@ -777,9 +834,15 @@ impl Default for PocketIc {
 fn compute_state_label(
     initial_state_hash: &[u8; 32],
     subnets: Vec<Arc<StateMachine>>,
+    mocked_callees: &BTreeSet<CanisterId>,
 ) -> StateLabel {
     let mut hasher = Sha256::new();
     hasher.write(initial_state_hash);
+    // Registering a mocked callee only changes the routing table
+    // and thus does not affect the subnet states.
+    for canister_id in mocked_callees {
+        hasher.write(canister_id.get_ref().as_slice());
+    }
     for subnet in subnets {
         let subnet_state_hash = subnet
             .state_manager
@@ -798,6 +861,7 @@ impl HasStateLabel for PocketIc {
         compute_state_label(
             &self.initial_state_hash,
             self.subnets.read().unwrap().values().cloned().collect(),
+            &self.mocked_callees,
         )
     }
 }
@@ -1020,6 +1084,9 @@ fn http_method_from(
         ic_types::canister_http::CanisterHttpMethod::GET => CanisterHttpMethod::GET,
         ic_types::canister_http::CanisterHttpMethod::POST => CanisterHttpMethod::POST,
         ic_types::canister_http::CanisterHttpMethod::HEAD => CanisterHttpMethod::HEAD,
+        ic_types::canister_http::CanisterHttpMethod::PUT => CanisterHttpMethod::PUT,
+        ic_types::canister_http::CanisterHttpMethod::PATCH => CanisterHttpMethod::PATCH,
+        ic_types::canister_http::CanisterHttpMethod::DELETE => CanisterHttpMethod::DELETE,
     }
 }
 
@@ -1256,6 +1323,181 @@ impl Operation for MockCanisterHttp {
     }
 }
 
+/// Returns the ID of the (fake) subnet that mocked callees are routed to.
+/// No `StateMachine` exists for this subnet and thus calls to mocked callees
+/// are retained in the streams from the callers' subnets to this subnet.
+fn mock_subnet_id() -> SubnetId {
+    SubnetId::new(PrincipalId::new_self_authenticating(
+        b"pocket_ic_mock_subnet",
+    ))
+}
+
+#[derive(Clone, Debug)]
+pub struct RegisterMockedCallee {
+    pub canister_id: CanisterId,
+}
+
+impl Operation for RegisterMockedCallee {
+    fn compute(&self, pic: &mut PocketIc) -> OpOut {
+        let canister_id = self.canister_id;
+        if pic.mocked_callees.contains(&canister_id) {
+            return OpOut::NoOutput;
+        }
+        if let Some(subnet_id) = pic.routing_table.route(canister_id.get()) {
+            return OpOut::Error(PocketIcError::InvalidMockedCallee(format!(
+                "Canister {canister_id} belongs to subnet {subnet_id} and thus cannot be mocked."
+            )));
+        }
+        let range = CanisterIdRange {
+            start: canister_id,
+            end: canister_id,
+        };
+        pic.routing_table.insert(range, mock_subnet_id()).unwrap();
+        pic.mocked_callees.insert(canister_id);
+        // We update the registry by creating a new registry version
+        // and inserting the new routing table at that new registry version.
+        let registry_version = pic.registry_data_provider.latest_version().increment();
+        let pb_routing_table = PbRoutingTable::from(pic.routing_table.clone());
+        pic.registry_data_provider
+            .add(
+                &make_routing_table_record_key(),
+                registry_version,
+                Some(pb_routing_table),
+            )
+            .unwrap();
+        for subnet in pic.subnets.read().unwrap().values() {
+            // Reload registry on the state machines to make sure
+            // all the state machines have a consistent view of the registry.
+            subnet.reload_registry();
+        }
+        // Update the registry file on disk.
+        if let Some(ref state_dir) = pic.state_dir {
+            let registry_proto_path = PathBuf::from(state_dir).join("registry.proto");
+            pic.registry_data_provider
+                .write_to_file(registry_proto_path);
+        }
+        OpOut::NoOutput
+    }
+
+    fn id(&self) -> OpId {
+        OpId(format!("register_mocked_callee({})", self.canister_id))
+    }
+}
+
+fn get_pending_calls(pic: &PocketIc) -> Vec<PendingCall> {
+    let mut pending_calls = vec![];
+    for subnet in pic.subnets.read().unwrap().values() {
+        let subnet_id = subnet.get_subnet_id();
+        let state = subnet.get_latest_state();
+        let Some(stream) = state.get_stream(&mock_subnet_id()) else {
+            continue;
+        };
+        for (index, msg) in stream.messages().iter() {
+            if pic.answered_mock_calls.contains(&(subnet_id, index)) {
+                continue;
+            }
+            if let RequestOrResponse::Request(req) = msg {
+                pending_calls.push(PendingCall {
+                    subnet_id: subnet_id.get().0,
+                    call_id: index.get(),
+                    sender: req.sender.get().0,
+                    receiver: req.receiver.get().0,
+                    method: req.method_name.clone(),
+                    payload: req.method_payload.clone(),
+                    cycles: req.payment.get(),
+                });
+            }
+        }
+    }
+    pending_calls
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct GetPendingCalls;
+
+impl Operation for GetPendingCalls {
+    fn compute(&self, pic: &mut PocketIc) -> OpOut {
+        OpOut::PendingCalls(get_pending_calls(pic))
+    }
+
+    fn id(&self) -> OpId {
+        OpId("get_pending_calls".into())
+    }
+}
+
+fn process_mock_call_response(pic: &mut PocketIc, mock_call_response: &MockCallResponse) -> OpOut {
+    let subnet_id = SubnetId::new(PrincipalId(mock_call_response.subnet_id));
+    let Some(subnet) = pic.get_subnet_with_id(subnet_id) else {
+        return OpOut::Error(PocketIcError::SubnetNotFound(mock_call_response.subnet_id));
+    };
+    let index = StreamIndex::from(mock_call_response.call_id);
+    let invalid_call_id = OpOut::Error(PocketIcError::InvalidCallId((
+        subnet_id,
+        mock_call_response.call_id,
+    )));
+    if pic.answered_mock_calls.contains(&(subnet_id, index)) {
+        return invalid_call_id;
+    }
+    let state = subnet.get_latest_state();
+    let Some(RequestOrResponse::Request(req)) = state
+        .get_stream(&mock_subnet_id())
+        .and_then(|stream| stream.messages().get(index))
+    else {
+        return invalid_call_id;
+    };
+    let response_payload = match &mock_call_response.response {
+        CallResponse::Reply(data) => MsgPayload::Data(data.clone()),
+        CallResponse::Reject(reject) => {
+            let Ok(reject_code) = RejectCode::try_from(reject.reject_code) else {
+                return OpOut::Error(PocketIcError::InvalidRejectCode(reject.reject_code));
+            };
+            MsgPayload::Reject(RejectContext::new(reject_code, reject.message.clone()))
+        }
+    };
+    let response = CanisterResponse {
+        originator: req.sender,
+        respondent: req.receiver,
+        originator_reply_callback: req.sender_reply_callback,
+        refund: req.payment,
+        response_payload,
+        deadline: req.deadline,
+    };
+    if let Err(err) = subnet.push_input(RequestOrResponse::Response(Arc::new(response))) {
+        return OpOut::Error(PocketIcError::RequestRoutingError(err));
+    }
+    pic.answered_mock_calls.insert((subnet_id, index));
+    // Calls that have been responded to are garbage collected from the stream
+    // (as if the mock subnet had accepted them) once all earlier calls in that
+    // stream have been responded to as well.
+    let messages_begin = state
+        .get_stream(&mock_subnet_id())
+        .unwrap()
+        .messages_begin();
+    let mut new_begin = messages_begin;
+    while pic.answered_mock_calls.remove(&(subnet_id, new_begin)) {
+        new_begin.inc_assign();
+    }
+    if new_begin > messages_begin {
+        subnet.discard_stream_messages_before(mock_subnet_id(), new_begin);
+    }
+    OpOut::NoOutput
+}
+
+#[derive(Clone, Debug)]
+pub struct MockCall {
+    pub mock_call_response: MockCallResponse,
+}
+
+impl Operation for MockCall {
+    fn compute(&self, pic: &mut PocketIc) -> OpOut {
+        process_mock_call_response(pic, &self.mock_call_response)
+    }
+
+    fn id(&self) -> OpId {
+        OpId(format!("mock_call({:?})", self.mock_call_response))
+    }
+}
+
 #[derive(Copy, Clone, Debug)]
 pub struct PubKey {
     pub subnet_id: SubnetId,
@@ -2205,6 +2447,48 @@ impl Operation for GetStableMemory {
     }
 }
 
+#[derive(Clone, Debug)]
+pub struct GetCanisterCoverage {
+    pub canister_id: CanisterId,
+}
+
+impl Operation for GetCanisterCoverage {
+    fn compute(&self, pic: &mut PocketIc) -> OpOut {
+        match pic.try_route_canister(self.canister_id) {
+            Some(subnet) => match subnet.canister_coverage(self.canister_id) {
+                Ok(report) => OpOut::Bytes(report.to_lcov().into_bytes()),
+                Err(err) => OpOut::Error(PocketIcError::CanisterCoverageUnavailable(err)),
+            },
+            None => OpOut::Error(PocketIcError::CanisterNotFound(self.canister_id)),
+        }
+    }
+
+    fn id(&self) -> OpId {
+        OpId(format!("get_canister_coverage({})", self.canister_id))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct GetInstructionProfile {
+    pub canister_id: CanisterId,
+}
+
+impl Operation for GetInstructionProfile {
+    fn compute(&self, pic: &mut PocketIc) -> OpOut {
+        match pic.try_route_canister(self.canister_id) {
+            Some(subnet) => match subnet.instruction_profile(self.canister_id) {
+                Ok(profile) => OpOut::Bytes(profile.into_bytes()),
+                Err(err) => OpOut::Error(PocketIcError::InstructionProfileUnavailable(err)),
+            },
+            None => OpOut::Error(PocketIcError::CanisterNotFound(self.canister_id)),
+        }
+    }
+
+    fn id(&self) -> OpId {
+        OpId(format!("get_instruction_profile({})", self.canister_id))
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct GetCyclesBalance {
     pub canister_id: CanisterId,
@@ -2355,6 +2639,11 @@ fn route(
         EffectivePrincipal::CanisterId(canister_id) => match pic.try_route_canister(canister_id) {
             Some(subnet) => Ok(subnet),
             None => {
+                if pic.mocked_callees.contains(&canister_id) {
+                    return Err(format!(
+                        "Canister {canister_id} is a mocked callee and can only be called by canisters."
+                    ));
+                }
                 if is_provisional_create_canister {
                     // We retrieve the PocketIC instace time (consistent across all subnets) from one subnet.
                     let time = pic.subnets.read().unwrap().values().next().unwrap().time();
@@ -2389,6 +2678,13 @@ fn route(
                         start: CanisterId::from_u64(canister_id_u64 & 0xFFFFFFFFFFF00000),
                         end: CanisterId::from_u64(canister_id_u64 | 0xFFFFF),
                     };
+                    // The canister range of the new subnet must not contain any mocked callee
+                    // since mocked callees are already routed to the mock subnet.
+                    if let Some(mocked_callee) =
+                        pic.mocked_callees.range(range.start..=range.end).next()
+                    {
+                        return Err(format!("The canister range of the effective canister ID {canister_id} contains the mocked callee {mocked_callee}."));
+                    }
                     // The canister allocation range must be disjoint from the canister ranges on the IC mainnet
                     // and all existing canister ranges within the PocketIC instance and thus we use
                     // `RangeGen::next_range()` to produce such a canister range.
@@ -2414,6 +2710,10 @@ fn route(
                         pic.registry_data_provider.clone(),
                         time,
                         pic.nonmainnet_features,
+                        pic.coverage_instrumentation,
+                        pic.instruction_profiling_dir
+                            .as_ref()
+                            .map(|dir| dir.path().to_path_buf()),
                         pic.log_level,
                         bitcoin_adapter_uds_path.clone(),
                     );
@@ -2757,6 +3057,8 @@ mod tests {
             },
             None,
             false,
+            false,
+            false,
             None,
             None,
         );
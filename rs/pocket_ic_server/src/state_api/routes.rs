@@ -7,8 +7,9 @@
 use super::state::{ApiState, OpOut, PocketIcError, StateLabel, UpdateReply};
 use crate::pocket_ic::{
     AddCycles, AwaitIngressMessage, CallRequest, CallRequestVersion, CanisterReadStateRequest,
-    DashboardRequest, ExecuteIngressMessage, GetCanisterHttp, GetCyclesBalance, GetStableMemory,
-    GetSubnet, GetTime, GetTopology, MockCanisterHttp, PubKey, Query, QueryRequest,
+    DashboardRequest, ExecuteIngressMessage, GetCanisterCoverage, GetCanisterHttp,
+    GetCyclesBalance, GetInstructionProfile, GetPendingCalls, GetStableMemory, GetSubnet, GetTime,
+    GetTopology, MockCall, MockCanisterHttp, PubKey, Query, QueryRequest, RegisterMockedCallee,
     SetStableMemory, SetTime, StatusRequest, SubmitIngressMessage, SubnetReadStateRequest, Tick,
 };
 use crate::{async_trait, pocket_ic::PocketIc, BlobStore, InstanceId, OpId, Operation};
@@ -36,10 +37,11 @@ use ic_state_machine_tests::Level;
 use ic_types::{CanisterId, SubnetId};
 use pocket_ic::common::rest::{
     self, ApiResponse, AutoProgressConfig, ExtendedSubnetConfigSet, HttpGatewayConfig,
-    HttpGatewayDetails, InstanceConfig, MockCanisterHttpResponse, RawAddCycles, RawCanisterCall,
-    RawCanisterHttpRequest, RawCanisterId, RawCanisterResult, RawCycles, RawMessageId,
-    RawMockCanisterHttpResponse, RawSetStableMemory, RawStableMemory, RawSubmitIngressResult,
-    RawSubnetId, RawTime, RawWasmResult, Topology,
+    HttpGatewayDetails, InstanceConfig, MockCallResponse, MockCanisterHttpResponse, RawAddCycles,
+    RawCanisterCall, RawCanisterHttpRequest, RawCanisterId, RawCanisterResult, RawCycles,
+    RawMessageId, RawMockCallResponse, RawMockCanisterHttpResponse, RawPendingCall,
+    RawSetStableMemory, RawStableMemory, RawSubmitIngressResult, RawSubnetId, RawTime,
+    RawWasmResult, Topology,
 };
 use pocket_ic::WasmResult;
 use serde::Serialize;
@@ -74,8 +76,17 @@ where
         .directory_route("/topology", get(handler_topology))
         .directory_route("/get_time", get(handler_get_time))
         .directory_route("/get_canister_http", get(handler_get_canister_http))
+        .directory_route("/get_pending_calls", get(handler_get_pending_calls))
         .directory_route("/get_cycles", post(handler_get_cycles))
         .directory_route("/get_stable_memory", post(handler_get_stable_memory))
+        .directory_route(
+            "/get_canister_coverage",
+            post(handler_get_canister_coverage),
+        )
+        .directory_route(
+            "/get_instruction_profile",
+            post(handler_get_instruction_profile),
+        )
         .directory_route("/get_subnet", post(handler_get_subnet))
         .directory_route("/pub_key", post(handler_pub_key))
 }
@@ -103,6 +114,11 @@ where
         .directory_route("/set_stable_memory", post(handler_set_stable_memory))
         .directory_route("/tick", post(handler_tick))
         .directory_route("/mock_canister_http", post(handler_mock_canister_http))
+        .directory_route(
+            "/register_mocked_callee",
+            post(handler_register_mocked_callee),
+        )
+        .directory_route("/mock_call_response", post(handler_mock_call_response))
 }
 
 pub fn instance_api_v2_routes<S>() -> ApiRouter<S>
@@ -480,6 +496,18 @@ impl TryFrom<OpOut> for Vec<RawCanisterHttpRequest> {
     }
 }
 
+impl TryFrom<OpOut> for Vec<RawPendingCall> {
+    type Error = OpConversionError;
+    fn try_from(value: OpOut) -> Result<Self, Self::Error> {
+        match value {
+            OpOut::PendingCalls(pending_calls) => {
+                Ok(pending_calls.into_iter().map(|c| c.into()).collect())
+            }
+            _ => Err(OpConversionError),
+        }
+    }
+}
+
 #[async_trait]
 impl FromOpOut for PocketHttpResponse {
     async fn from(value: OpOut) -> (StatusCode, ApiResponse<PocketHttpResponse>) {
@@ -582,6 +610,52 @@ pub async fn handler_mock_canister_http(
     (code, Json(response))
 }
 
+pub async fn handler_get_pending_calls(
+    State(AppState { api_state, .. }): State<AppState>,
+    headers: HeaderMap,
+    Path(instance_id): Path<InstanceId>,
+) -> (StatusCode, Json<ApiResponse<Vec<RawPendingCall>>>) {
+    let timeout = timeout_or_default(headers);
+    let op = GetPendingCalls {};
+    let (code, response) = run_operation(api_state, instance_id, timeout, op).await;
+    (code, Json(response))
+}
+
+pub async fn handler_register_mocked_callee(
+    State(AppState { api_state, .. }): State<AppState>,
+    headers: HeaderMap,
+    Path(instance_id): Path<InstanceId>,
+    axum::extract::Json(raw_canister_id): axum::extract::Json<RawCanisterId>,
+) -> (StatusCode, Json<ApiResponse<()>>) {
+    let timeout = timeout_or_default(headers);
+    match CanisterId::try_from(raw_canister_id.canister_id) {
+        Ok(canister_id) => {
+            let op = RegisterMockedCallee { canister_id };
+            let (code, response) = run_operation(api_state, instance_id, timeout, op).await;
+            (code, Json(response))
+        }
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::Error {
+                message: format!("{:?}", e),
+            }),
+        ),
+    }
+}
+
+pub async fn handler_mock_call_response(
+    State(AppState { api_state, .. }): State<AppState>,
+    headers: HeaderMap,
+    Path(instance_id): Path<InstanceId>,
+    axum::extract::Json(raw_mock_call_response): axum::extract::Json<RawMockCallResponse>,
+) -> (StatusCode, Json<ApiResponse<()>>) {
+    let timeout = timeout_or_default(headers);
+    let mock_call_response: MockCallResponse = raw_mock_call_response.into();
+    let op = MockCall { mock_call_response };
+    let (code, response) = run_operation(api_state, instance_id, timeout, op).await;
+    (code, Json(response))
+}
+
 pub async fn handler_get_cycles(
     State(AppState { api_state, .. }): State<AppState>,
     Path(instance_id): Path<InstanceId>,
@@ -626,6 +700,50 @@ pub async fn handler_get_stable_memory(
     }
 }
 
+pub async fn handler_get_canister_coverage(
+    State(AppState { api_state, .. }): State<AppState>,
+    Path(instance_id): Path<InstanceId>,
+    headers: HeaderMap,
+    axum::extract::Json(raw_canister_id): axum::extract::Json<RawCanisterId>,
+) -> (StatusCode, Json<ApiResponse<Vec<u8>>>) {
+    let timeout = timeout_or_default(headers);
+    match CanisterId::try_from(raw_canister_id.canister_id) {
+        Ok(canister_id) => {
+            let op = GetCanisterCoverage { canister_id };
+            let (code, response) = run_operation(api_state, instance_id, timeout, op).await;
+            (code, Json(response))
+        }
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::Error {
+                message: format!("{:?}", e),
+            }),
+        ),
+    }
+}
+
+pub async fn handler_get_instruction_profile(
+    State(AppState { api_state, .. }): State<AppState>,
+    Path(instance_id): Path<InstanceId>,
+    headers: HeaderMap,
+    axum::extract::Json(raw_canister_id): axum::extract::Json<RawCanisterId>,
+) -> (StatusCode, Json<ApiResponse<Vec<u8>>>) {
+    let timeout = timeout_or_default(headers);
+    match CanisterId::try_from(raw_canister_id.canister_id) {
+        Ok(canister_id) => {
+            let op = GetInstructionProfile { canister_id };
+            let (code, response) = run_operation(api_state, instance_id, timeout, op).await;
+            (code, Json(response))
+        }
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::Error {
+                message: format!("{:?}", e),
+            }),
+        ),
+    }
+}
+
 pub async fn handler_get_subnet(
     State(AppState { api_state, .. }): State<AppState>,
     Path(instance_id): Path<InstanceId>,
@@ -840,6 +958,13 @@ async fn op_out_to_response(op_out: OpOut) -> Response {
             )),
         )
             .into_response(),
+        opout @ OpOut::PendingCalls(_) => (
+            StatusCode::OK,
+            Json(ApiResponse::Success(
+                Vec::<RawPendingCall>::try_from(opout).unwrap(),
+            )),
+        )
+            .into_response(),
         OpOut::RawResponse(fut) => {
             let (status, headers, bytes) = fut.await;
             let code = StatusCode::from_u16(status).unwrap();
@@ -1119,6 +1244,8 @@ pub async fn create_instance(
             subnet_configs,
             instance_config.state_dir,
             instance_config.nonmainnet_features,
+            instance_config.coverage_instrumentation,
+            instance_config.instruction_profiling,
             log_level,
             instance_config.bitcoind_addr,
         )
@@ -47,7 +47,7 @@ use ic_types::{
 use pocket_ic::common::rest::{
     CanisterHttpHeader, CanisterHttpMethod, CanisterHttpReject, CanisterHttpReply,
     CanisterHttpRequest, CanisterHttpResponse, HttpGatewayBackend, HttpGatewayConfig,
-    HttpGatewayDetails, HttpGatewayInfo, MockCanisterHttpResponse, Topology,
+    HttpGatewayDetails, HttpGatewayInfo, MockCanisterHttpResponse, PendingCall, Topology,
 };
 use pocket_ic::{ErrorCode, UserError, WasmResult};
 use serde::{Deserialize, Serialize};
@@ -214,6 +214,7 @@ pub enum OpOut {
     MessageId((EffectivePrincipal, Vec<u8>)),
     Topology(Topology),
     CanisterHttp(Vec<CanisterHttpRequest>),
+    PendingCalls(Vec<PendingCall>),
 }
 
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Debug, Deserialize, Serialize)]
@@ -224,6 +225,11 @@ pub enum PocketIcError {
     RequestRoutingError(String),
     InvalidCanisterHttpRequestId((SubnetId, CanisterHttpRequestId)),
     InvalidMockCanisterHttpResponses((usize, usize)),
+    InvalidCallId((SubnetId, u64)),
+    InvalidMockedCallee(String),
+    InvalidRejectCode(u64),
+    CanisterCoverageUnavailable(String),
+    InstructionProfileUnavailable(String),
 }
 
 impl From<Result<ic_state_machine_tests::WasmResult, ic_state_machine_tests::UserError>> for OpOut {
@@ -299,6 +305,21 @@ impl std::fmt::Debug for OpOut {
                     actual, expected
                 )
             }
+            OpOut::Error(PocketIcError::InvalidCallId((subnet_id, call_id))) => {
+                write!(f, "InvalidCallId({},{})", subnet_id, call_id)
+            }
+            OpOut::Error(PocketIcError::InvalidMockedCallee(msg)) => {
+                write!(f, "InvalidMockedCallee({:?})", msg)
+            }
+            OpOut::Error(PocketIcError::InvalidRejectCode(code)) => {
+                write!(f, "InvalidRejectCode({})", code)
+            }
+            OpOut::Error(PocketIcError::CanisterCoverageUnavailable(msg)) => {
+                write!(f, "CanisterCoverageUnavailable({:?})", msg)
+            }
+            OpOut::Error(PocketIcError::InstructionProfileUnavailable(msg)) => {
+                write!(f, "InstructionProfileUnavailable({:?})", msg)
+            }
             OpOut::Bytes(bytes) => write!(f, "Bytes({})", base64::encode(bytes)),
             OpOut::StableMemBytes(bytes) => write!(f, "StableMemory({})", base64::encode(bytes)),
             OpOut::MaybeSubnetId(Some(subnet_id)) => write!(f, "SubnetId({})", subnet_id),
@@ -326,6 +347,9 @@ impl std::fmt::Debug for OpOut {
             OpOut::CanisterHttp(canister_http_reqeusts) => {
                 write!(f, "CanisterHttp({:?})", canister_http_reqeusts)
             }
+            OpOut::PendingCalls(pending_calls) => {
+                write!(f, "PendingCalls({:?})", pending_calls)
+            }
         }
     }
 }
@@ -1075,6 +1099,9 @@ impl ApiState {
                 CanisterHttpMethod::GET => HttpMethod::Get.into(),
                 CanisterHttpMethod::POST => HttpMethod::Post.into(),
                 CanisterHttpMethod::HEAD => HttpMethod::Head.into(),
+                CanisterHttpMethod::PUT => HttpMethod::Put.into(),
+                CanisterHttpMethod::PATCH => HttpMethod::Patch.into(),
+                CanisterHttpMethod::DELETE => HttpMethod::Delete.into(),
             },
             max_response_size_bytes: canister_http_request
                 .max_response_bytes
@@ -102,6 +102,8 @@ fn test_creation_of_instance_extended() {
         .into(),
         state_dir: None,
         nonmainnet_features: false,
+        coverage_instrumentation: false,
+        instruction_profiling: false,
         log_level: None,
         bitcoind_addr: None,
     };
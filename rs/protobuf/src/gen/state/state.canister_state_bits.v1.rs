@@ -437,6 +437,16 @@ pub struct CanisterLoadSnapshot {
     pub snapshot_id: ::prost::alloc::vec::Vec<u8>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CanisterEnvironmentVariablesChange {
+    #[prost(bytes = "vec", tag = "1")]
+    pub environment_variables_hash: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CanisterOperatorsChange {
+    #[prost(message, repeated, tag = "1")]
+    pub operators: ::prost::alloc::vec::Vec<super::super::super::types::v1::PrincipalId>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CanisterChange {
     #[prost(uint64, tag = "1")]
     pub timestamp_nanos: u64,
@@ -444,7 +454,7 @@ pub struct CanisterChange {
     pub canister_version: u64,
     #[prost(oneof = "canister_change::ChangeOrigin", tags = "3, 4")]
     pub change_origin: ::core::option::Option<canister_change::ChangeOrigin>,
-    #[prost(oneof = "canister_change::ChangeDetails", tags = "5, 6, 7, 8, 9")]
+    #[prost(oneof = "canister_change::ChangeDetails", tags = "5, 6, 7, 8, 9, 10, 11")]
     pub change_details: ::core::option::Option<canister_change::ChangeDetails>,
 }
 /// Nested message and enum types in `CanisterChange`.
@@ -468,6 +478,10 @@ pub mod canister_change {
         CanisterControllersChange(super::CanisterControllersChange),
         #[prost(message, tag = "9")]
         CanisterLoadSnapshot(super::CanisterLoadSnapshot),
+        #[prost(message, tag = "10")]
+        CanisterEnvironmentVariablesChange(super::CanisterEnvironmentVariablesChange),
+        #[prost(message, tag = "11")]
+        CanisterOperatorsChange(super::CanisterOperatorsChange),
     }
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -546,6 +560,13 @@ pub struct SnapshotId {
     pub content: ::prost::alloc::vec::Vec<u8>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EnvironmentVariable {
+    #[prost(string, tag = "1")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub value: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CanisterStateBits {
     #[prost(uint64, tag = "2")]
     pub last_full_execution_round: u64,
@@ -650,6 +671,16 @@ pub struct CanisterStateBits {
     pub wasm_memory_threshold: ::core::option::Option<u64>,
     #[prost(enumeration = "OnLowWasmMemoryHookStatus", optional, tag = "53")]
     pub on_low_wasm_memory_hook_status: ::core::option::Option<i32>,
+    /// The size of the canister log buffer in bytes. The default buffer size
+    /// is used if unset.
+    #[prost(uint64, optional, tag = "54")]
+    pub log_memory_limit: ::core::option::Option<u64>,
+    /// The environment variables of the canister, sorted by name.
+    #[prost(message, repeated, tag = "55")]
+    pub environment_variables: ::prost::alloc::vec::Vec<EnvironmentVariable>,
+    /// Principals holding the operator role of the canister.
+    #[prost(message, repeated, tag = "56")]
+    pub operators: ::prost::alloc::vec::Vec<super::super::super::types::v1::PrincipalId>,
     #[prost(oneof = "canister_state_bits::CanisterStatus", tags = "11, 12, 13")]
     pub canister_status: ::core::option::Option<canister_state_bits::CanisterStatus>,
 }
@@ -28,6 +28,8 @@ pub struct SubnetTopology {
     #[prost(message, repeated, tag = "6")]
     pub idkg_keys_held:
         ::prost::alloc::vec::Vec<super::super::super::registry::crypto::v1::MasterPublicKeyId>,
+    #[prost(string, tag = "7")]
+    pub replica_version: ::prost::alloc::string::String,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct SubnetsEntry {
@@ -168,6 +170,9 @@ pub struct CanisterHttpRequestContext {
     pub max_response_bytes: ::core::option::Option<u64>,
     #[prost(message, optional, tag = "10")]
     pub transform_context: ::core::option::Option<::prost::alloc::vec::Vec<u8>>,
+    /// Set if the request is made by the given node only.
+    #[prost(message, optional, tag = "11")]
+    pub non_replicated_node: ::core::option::Option<super::super::super::types::v1::NodeId>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CanisterHttpRequestContextTree {
@@ -492,6 +497,9 @@ pub enum HttpMethod {
     Get = 1,
     Post = 2,
     Head = 3,
+    Put = 4,
+    Patch = 5,
+    Delete = 6,
 }
 impl HttpMethod {
     /// String value of the enum field names used in the ProtoBuf definition.
@@ -504,6 +512,9 @@ impl HttpMethod {
             Self::Get => "HTTP_METHOD_GET",
             Self::Post => "HTTP_METHOD_POST",
             Self::Head => "HTTP_METHOD_HEAD",
+            Self::Put => "HTTP_METHOD_PUT",
+            Self::Patch => "HTTP_METHOD_PATCH",
+            Self::Delete => "HTTP_METHOD_DELETE",
         }
     }
     /// Creates an enum from field names used in the ProtoBuf definition.
@@ -513,6 +524,9 @@ impl HttpMethod {
             "HTTP_METHOD_GET" => Some(Self::Get),
             "HTTP_METHOD_POST" => Some(Self::Post),
             "HTTP_METHOD_HEAD" => Some(Self::Head),
+            "HTTP_METHOD_PUT" => Some(Self::Put),
+            "HTTP_METHOD_PATCH" => Some(Self::Patch),
+            "HTTP_METHOD_DELETE" => Some(Self::Delete),
             _ => None,
         }
     }
@@ -170,6 +170,35 @@ impl AppSubnetRecovery {
         )
         .expect_graceful("Failed to init recovery");
 
+        Self::with_recovery(logger, recovery_args, neuron_args, subnet_args, recovery)
+    }
+
+    /// Creates the recovery only for planning its steps, see [Recovery::new_for_plan].
+    pub fn new_for_plan(
+        logger: Logger,
+        recovery_args: RecoveryArgs,
+        neuron_args: Option<NeuronArgs>,
+        subnet_args: AppSubnetRecoveryArgs,
+    ) -> Self {
+        let recovery = Recovery::new_for_plan(
+            logger.clone(),
+            recovery_args.clone(),
+            neuron_args.clone(),
+            recovery_args.nns_url.clone(),
+            RegistryPollingStrategy::OnlyOnInit,
+        )
+        .expect_graceful("Failed to init recovery");
+
+        Self::with_recovery(logger, recovery_args, neuron_args, subnet_args, recovery)
+    }
+
+    fn with_recovery(
+        logger: Logger,
+        recovery_args: RecoveryArgs,
+        neuron_args: Option<NeuronArgs>,
+        subnet_args: AppSubnetRecoveryArgs,
+        recovery: Recovery,
+    ) -> Self {
         Self {
             step_iterator: StepType::iter().peekable(),
             params: subnet_args,
@@ -281,6 +310,40 @@ impl RecoveryIterator<StepType, StepTypeIter> for AppSubnetRecovery {
         }
     }
 
+    fn get_missing_inputs(&self, step_type: StepType) -> Vec<&'static str> {
+        let mut inputs = vec![];
+        match step_type {
+            StepType::Halt if self.params.pub_key.is_none() => inputs.push("--pub-key"),
+            StepType::DownloadState => {
+                if self.params.download_node.is_none() {
+                    inputs.push("--download-node");
+                }
+                if self.params.keep_downloaded_state.is_none() {
+                    inputs.push("--keep-downloaded-state");
+                }
+            }
+            StepType::ICReplay if self.params.replay_until_height.is_none() => {
+                inputs.push("--replay-until-height")
+            }
+            StepType::BlessVersion if self.params.upgrade_version.is_none() => {
+                inputs.push("--upgrade-version")
+            }
+            StepType::ProposeCup => {
+                if self.params.replacement_nodes.is_none() {
+                    inputs.push("--replacement-nodes");
+                }
+                if self.params.chain_key_subnet_id.is_none() {
+                    inputs.push("--chain-key-subnet-id");
+                }
+            }
+            StepType::UploadState if self.params.upload_node.is_none() => {
+                inputs.push("--upload-node")
+            }
+            _ => {}
+        }
+        inputs
+    }
+
     fn get_step_impl(&self, step_type: StepType) -> RecoveryResult<Box<dyn Step>> {
         match step_type {
             StepType::Halt => {
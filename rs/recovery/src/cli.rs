@@ -2,7 +2,8 @@
 use crate::{
     app_subnet_recovery::{AppSubnetRecovery, AppSubnetRecoveryArgs},
     args_merger::merge,
-    error::GracefulExpect,
+    cmd::SubCommand,
+    error::{GracefulExpect, RecoveryError},
     get_node_heights_from_metrics,
     nns_recovery_failover_nodes::{NNSRecoveryFailoverNodes, NNSRecoveryFailoverNodesArgs},
     nns_recovery_same_nodes::{NNSRecoverySameNodes, NNSRecoverySameNodesArgs},
@@ -156,6 +157,52 @@ pub fn execute_steps<
     }
 }
 
+/// Prints the plan of the given recovery as JSON to stdout, starting from its next step.
+pub fn print_plan(logger: &Logger, state: RecoveryState<SubCommand>) {
+    match state.subcommand_args {
+        SubCommand::AppSubnetRecovery(subnet_recovery_args) => {
+            print_steps_plan(AppSubnetRecovery::new_for_plan(
+                logger.clone(),
+                state.recovery_args,
+                state.neuron_args,
+                subnet_recovery_args,
+            ))
+        }
+        SubCommand::NNSRecoverySameNodes(nns_recovery_args) => {
+            print_steps_plan(NNSRecoverySameNodes::new_for_plan(
+                logger.clone(),
+                state.recovery_args,
+                nns_recovery_args,
+            ))
+        }
+        SubCommand::NNSRecoveryFailoverNodes(nns_recovery_args) => {
+            print_steps_plan(NNSRecoveryFailoverNodes::new_for_plan(
+                logger.clone(),
+                state.recovery_args,
+                state.neuron_args,
+                nns_recovery_args,
+            ))
+        }
+    }
+}
+
+fn print_steps_plan<
+    StepType: Copy + Debug + PartialEq + EnumMessage,
+    I: Iterator<Item = StepType>,
+    Steps: HasRecoveryState<StepType = StepType> + RecoveryIterator<StepType, I>,
+>(
+    mut steps: Steps,
+) {
+    if let Some(next_step) = steps.get_next_step() {
+        steps.resume(next_step);
+    }
+
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&steps.plan()).expect("Failed to stringify the recovery plan")
+    );
+}
+
 fn execute_step_after_consent(logger: &Logger, skip_prompts: bool, step: Box<dyn Step>) {
     info!(logger, "{}", step.descr());
     if !skip_prompts && !consent_given(logger, "Execute now?") {
@@ -327,6 +374,66 @@ pub fn read_and_maybe_update_state<T: Serialize + DeserializeOwned + Clone + Par
     }
 }
 
+/// Reads the recovery state saved in the working directory and sets it to continue from the
+/// given step. New arguments passed in this run are merged like when resuming interactively.
+pub fn resume_from_step(
+    logger: &Logger,
+    recovery_args: RecoveryArgs,
+    subcommand_args: Option<SubCommand>,
+    step: &str,
+) -> RecoveryState<SubCommand> {
+    let state = read_saved_state(&recovery_args);
+    let mut state = maybe_update_state(logger, state, &recovery_args, &subcommand_args);
+    state
+        .subcommand_args
+        .set_next_step(step)
+        .expect_graceful("Failed to resume the recovery");
+    if let Err(e) = state.save() {
+        warn!(logger, "Failed to save the recovery state: {}", e);
+    }
+
+    state
+}
+
+/// Returns the recovery to plan without asking for any input. The subcommand passed in this
+/// run takes precedence over the recovery state saved in the working directory. Nothing is
+/// written to the disk.
+pub fn read_state_for_plan(
+    recovery_args: RecoveryArgs,
+    subcommand_args: Option<SubCommand>,
+    resume_from: Option<String>,
+) -> RecoveryState<SubCommand> {
+    let mut state = match subcommand_args {
+        Some(subcommand_args) => RecoveryState {
+            recovery_args,
+            subcommand_args,
+            neuron_args: None,
+        },
+        None => read_saved_state(&recovery_args),
+    };
+    if let Some(step) = resume_from {
+        state
+            .subcommand_args
+            .set_next_step(&step)
+            .expect_graceful("Failed to plan the recovery");
+    }
+
+    state
+}
+
+fn read_saved_state(recovery_args: &RecoveryArgs) -> RecoveryState<SubCommand> {
+    RecoveryState::<SubCommand>::read(&recovery_args.dir)
+        .and_then(|state| {
+            state.ok_or_else(|| {
+                RecoveryError::UnexpectedError(format!(
+                    "No recovery state file found in {}",
+                    recovery_args.dir.display()
+                ))
+            })
+        })
+        .expect_graceful("Failed to read the recovery state file")
+}
+
 /// Checks if there are any differences between the arguments passed to the tool in this run
 /// compared to the last run. If there are, asks user whether to use the new arguments.
 fn maybe_update_state<T: Serialize + DeserializeOwned + Clone + PartialEq>(
@@ -1,13 +1,14 @@
 use clap::Parser;
 use ic_types::ReplicaVersion;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::{path::PathBuf, str::FromStr};
 use url::Url;
 
 use crate::{
-    app_subnet_recovery::AppSubnetRecoveryArgs,
-    nns_recovery_failover_nodes::NNSRecoveryFailoverNodesArgs,
-    nns_recovery_same_nodes::NNSRecoverySameNodesArgs,
+    app_subnet_recovery::{self, AppSubnetRecoveryArgs},
+    error::{RecoveryError, RecoveryResult},
+    nns_recovery_failover_nodes::{self, NNSRecoveryFailoverNodesArgs},
+    nns_recovery_same_nodes::{self, NNSRecoverySameNodesArgs},
 };
 
 /// Subcommands for recovery procedures (application subnets, NNS with failover nodes, etc...)
@@ -21,6 +22,31 @@ pub enum SubCommand {
     NNSRecoverySameNodes(NNSRecoverySameNodesArgs),
 }
 
+impl SubCommand {
+    /// Sets the step the recovery should continue from. The step is parsed according to the
+    /// steps of the subcommand.
+    pub fn set_next_step(&mut self, step: &str) -> RecoveryResult<()> {
+        fn parse<T: FromStr>(step: &str) -> RecoveryResult<T> {
+            T::from_str(step).map_err(|_| {
+                RecoveryError::UnexpectedError(format!("Unknown recovery step: {}", step))
+            })
+        }
+
+        match self {
+            SubCommand::AppSubnetRecovery(args) => {
+                args.next_step = Some(parse::<app_subnet_recovery::StepType>(step)?)
+            }
+            SubCommand::NNSRecoveryFailoverNodes(args) => {
+                args.next_step = Some(parse::<nns_recovery_failover_nodes::StepType>(step)?)
+            }
+            SubCommand::NNSRecoverySameNodes(args) => {
+                args.next_step = Some(parse::<nns_recovery_same_nodes::StepType>(step)?)
+            }
+        }
+        Ok(())
+    }
+}
+
 #[derive(Parser)]
 #[clap(version = "1.0")]
 pub struct RecoveryToolArgs {
@@ -54,6 +80,16 @@ pub struct RecoveryToolArgs {
     #[clap(long)]
     pub skip_prompts: bool,
 
+    /// Print the ordered list of all recovery steps as JSON, including their commands, target
+    /// nodes, touched files and required inputs, without executing anything.
+    #[clap(long)]
+    pub plan: bool,
+
+    /// Resume the recovery saved in the recovery state file of the working directory from the
+    /// given step.
+    #[clap(long)]
+    pub resume_from: Option<String>,
+
     #[clap(subcommand)]
     pub subcmd: Option<SubCommand>,
 }
@@ -140,6 +140,43 @@ impl Recovery {
         neuron_args: Option<NeuronArgs>,
         registry_nns_url: Url,
         registry_polling_strategy: RegistryPollingStrategy,
+    ) -> RecoveryResult<Self> {
+        Self::init(
+            logger,
+            args,
+            neuron_args,
+            registry_nns_url,
+            registry_polling_strategy,
+            /*dry_run=*/ false,
+        )
+    }
+
+    /// Creates a recovery instance which is only used to plan the recovery steps. No directories
+    /// are created, no binaries are downloaded and the user isn't asked for any confirmation.
+    pub fn new_for_plan(
+        logger: Logger,
+        args: RecoveryArgs,
+        neuron_args: Option<NeuronArgs>,
+        registry_nns_url: Url,
+        registry_polling_strategy: RegistryPollingStrategy,
+    ) -> RecoveryResult<Self> {
+        Self::init(
+            logger,
+            args,
+            neuron_args,
+            registry_nns_url,
+            registry_polling_strategy,
+            /*dry_run=*/ true,
+        )
+    }
+
+    fn init(
+        logger: Logger,
+        args: RecoveryArgs,
+        neuron_args: Option<NeuronArgs>,
+        registry_nns_url: Url,
+        registry_polling_strategy: RegistryPollingStrategy,
+        dry_run: bool,
     ) -> RecoveryResult<Self> {
         let ssh_confirmation = !args.test_mode;
         let recovery_dir = args.dir.join(RECOVERY_DIRECTORY_NAME);
@@ -149,22 +186,24 @@ impl Recovery {
         let local_store_path = work_dir.join("data").join(IC_REGISTRY_LOCAL_STORE);
         let nns_pem = recovery_dir.join("nns.pem");
 
-        match Recovery::create_dirs(&[&binary_dir, &data_dir, &work_dir, &local_store_path]) {
-            Err(RecoveryError::IoError(s, err)) => match err.kind() {
-                ErrorKind::PermissionDenied => Err(RecoveryError::IoError(
-                    format!(
-                        "No permission to create recovery directory. Consider manually \
-                        creating the directory with the right permissions by running:\n\n  \
-                        sudo mkdir -p {} && sudo chown $USER {}\n",
-                        recovery_dir.display(),
-                        recovery_dir.display()
-                    ),
-                    err,
-                )),
-                _ => Err(RecoveryError::IoError(s, err)),
-            },
-            x => x,
-        }?;
+        if !dry_run {
+            match Recovery::create_dirs(&[&binary_dir, &data_dir, &work_dir, &local_store_path]) {
+                Err(RecoveryError::IoError(s, err)) => match err.kind() {
+                    ErrorKind::PermissionDenied => Err(RecoveryError::IoError(
+                        format!(
+                            "No permission to create recovery directory. Consider manually \
+                            creating the directory with the right permissions by running:\n\n  \
+                            sudo mkdir -p {} && sudo chown $USER {}\n",
+                            recovery_dir.display(),
+                            recovery_dir.display()
+                        ),
+                        err,
+                    )),
+                    _ => Err(RecoveryError::IoError(s, err)),
+                },
+                x => x,
+            }?;
+        }
 
         let registry_helper = RegistryHelper::new(
             logger.clone(),
@@ -174,11 +213,16 @@ impl Recovery {
             registry_polling_strategy,
         );
 
-        if ssh_confirmation {
+        if ssh_confirmation && !dry_run {
             wait_for_confirmation(&logger);
         }
 
-        if !binary_dir.join("ic-admin").exists() {
+        if dry_run {
+            info!(
+                logger,
+                "Planning the recovery, skipping download of ic-admin."
+            );
+        } else if !binary_dir.join("ic-admin").exists() {
             if let Some(version) = args.replica_version {
                 block_on(download_binary(
                     &logger,
@@ -36,7 +36,16 @@ fn main() {
         skip_prompts: args.skip_prompts,
     };
 
-    let recovery_state = cli::read_and_maybe_update_state(&logger, recovery_args, args.subcmd);
+    if args.plan {
+        let recovery_state = cli::read_state_for_plan(recovery_args, args.subcmd, args.resume_from);
+        cli::print_plan(&logger, recovery_state);
+        return;
+    }
+
+    let recovery_state = match args.resume_from {
+        Some(step) => cli::resume_from_step(&logger, recovery_args, args.subcmd, &step),
+        None => cli::read_and_maybe_update_state(&logger, recovery_args, args.subcmd),
+    };
 
     match recovery_state.subcommand_args {
         SubCommand::AppSubnetRecovery(subnet_recovery_args) => cli::app_subnet_recovery(
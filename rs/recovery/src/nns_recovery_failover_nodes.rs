@@ -123,6 +123,35 @@ impl NNSRecoveryFailoverNodes {
         )
         .expect_graceful("Failed to init recovery");
 
+        Self::with_recovery(logger, recovery_args, neuron_args, subnet_args, recovery)
+    }
+
+    /// Creates the recovery only for planning its steps, see [Recovery::new_for_plan].
+    pub fn new_for_plan(
+        logger: Logger,
+        recovery_args: RecoveryArgs,
+        neuron_args: Option<NeuronArgs>,
+        subnet_args: NNSRecoveryFailoverNodesArgs,
+    ) -> Self {
+        let recovery = Recovery::new_for_plan(
+            logger.clone(),
+            recovery_args.clone(),
+            neuron_args.clone(),
+            subnet_args.validate_nns_url.clone(),
+            RegistryPollingStrategy::OnlyOnInit,
+        )
+        .expect_graceful("Failed to init recovery");
+
+        Self::with_recovery(logger, recovery_args, neuron_args, subnet_args, recovery)
+    }
+
+    fn with_recovery(
+        logger: Logger,
+        recovery_args: RecoveryArgs,
+        neuron_args: Option<NeuronArgs>,
+        subnet_args: NNSRecoveryFailoverNodesArgs,
+        recovery: Recovery,
+    ) -> Self {
         let new_registry_local_store = recovery.work_dir.join(IC_REGISTRY_LOCAL_STORE);
         Self {
             step_iterator: StepType::iter().peekable(),
@@ -239,6 +268,47 @@ impl RecoveryIterator<StepType, StepTypeIter> for NNSRecoveryFailoverNodes {
         }
     }
 
+    fn get_missing_inputs(&self, step_type: StepType) -> Vec<&'static str> {
+        let mut inputs = vec![];
+        match step_type {
+            StepType::StopReplica if self.params.download_node.is_none() => {
+                inputs.push("--download-node")
+            }
+            StepType::ProposeToCreateSubnet => {
+                if self.params.replica_version.is_none() {
+                    inputs.push("--replica-version");
+                }
+                if self.params.replacement_nodes.is_none() {
+                    inputs.push("--replacement-nodes");
+                }
+            }
+            StepType::DownloadParentNNSStore if self.params.parent_nns_host_ip.is_none() => {
+                inputs.push("--parent-nns-host-ip")
+            }
+            StepType::ICReplayWithRegistryContent if self.params.replay_until_height.is_none() => {
+                inputs.push("--replay-until-height")
+            }
+            StepType::UploadAndHostTar => {
+                if self.params.aux_user.is_none() {
+                    inputs.push("--aux-user");
+                }
+                if self.params.aux_ip.is_none() {
+                    inputs.push("--aux-ip");
+                }
+                if (self.params.aux_user.is_none() || self.params.aux_ip.is_none())
+                    && self.params.registry_url.is_none()
+                {
+                    inputs.push("--registry-url");
+                }
+            }
+            StepType::WaitForCUP if self.params.upload_node.is_none() => {
+                inputs.push("--upload-node")
+            }
+            _ => {}
+        }
+        inputs
+    }
+
     fn get_step_impl(&self, step_type: StepType) -> RecoveryResult<Box<dyn Step>> {
         match step_type {
             StepType::StopReplica => {
@@ -98,8 +98,37 @@ impl NNSRecoverySameNodes {
         )
         .expect_graceful("Failed to init recovery");
 
+        let nns_recovery = Self::with_recovery(logger, recovery_args, subnet_args, recovery);
+        create_dir(&nns_recovery.new_state_dir)
+            .expect_graceful("Failed to create state directory for upload.");
+        nns_recovery
+    }
+
+    /// Creates the recovery only for planning its steps, see [Recovery::new_for_plan].
+    pub fn new_for_plan(
+        logger: Logger,
+        recovery_args: RecoveryArgs,
+        subnet_args: NNSRecoverySameNodesArgs,
+    ) -> Self {
+        let recovery = Recovery::new_for_plan(
+            logger.clone(),
+            recovery_args.clone(),
+            /*neuron_args=*/ None,
+            recovery_args.nns_url.clone(),
+            RegistryPollingStrategy::OnlyOnInit,
+        )
+        .expect_graceful("Failed to init recovery");
+
+        Self::with_recovery(logger, recovery_args, subnet_args, recovery)
+    }
+
+    fn with_recovery(
+        logger: Logger,
+        recovery_args: RecoveryArgs,
+        subnet_args: NNSRecoverySameNodesArgs,
+        recovery: Recovery,
+    ) -> Self {
         let new_state_dir = recovery.work_dir.join("new_ic_state");
-        create_dir(&new_state_dir).expect_graceful("Failed to create state directory for upload.");
         Self {
             step_iterator: StepType::iter().peekable(),
             params: subnet_args,
@@ -167,6 +196,28 @@ impl RecoveryIterator<StepType, StepTypeIter> for NNSRecoverySameNodes {
         }
     }
 
+    fn get_missing_inputs(&self, step_type: StepType) -> Vec<&'static str> {
+        let mut inputs = vec![];
+        match step_type {
+            StepType::StopReplica if self.params.download_node.is_none() => {
+                inputs.push("--download-node")
+            }
+            StepType::ICReplay => {
+                if self.params.upgrade_version.is_none() {
+                    inputs.push("--upgrade-version");
+                }
+                if self.params.replay_until_height.is_none() {
+                    inputs.push("--replay-until-height");
+                }
+            }
+            StepType::WaitForCUP if self.params.upload_node.is_none() => {
+                inputs.push("--upload-node")
+            }
+            _ => {}
+        }
+        inputs
+    }
+
     fn get_step_impl(&self, step_type: StepType) -> RecoveryResult<Box<dyn Step>> {
         match step_type {
             StepType::StopReplica => {
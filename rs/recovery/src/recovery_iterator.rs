@@ -4,10 +4,35 @@ use crate::{
     nns_recovery_same_nodes, nns_recovery_same_nodes::NNSRecoverySameNodes, steps::Step,
     RecoveryResult,
 };
+use serde::Serialize;
 use slog::{info, warn, Logger};
 use strum::EnumMessage;
 
-use std::{fmt::Debug, iter::Peekable};
+use std::{fmt::Debug, iter::Peekable, net::IpAddr};
+
+/// Description of a single recovery step, as printed by the `--plan` mode.
+#[derive(Clone, PartialEq, Debug, Serialize)]
+pub struct StepPlan {
+    /// Name of the step, which can be passed to `--resume-from`.
+    pub step: String,
+    /// Explanation of the step, if any.
+    pub explanation: Option<String>,
+    /// The command, or the description of the action, which the step executes. Missing if the
+    /// step is skipped or can't be determined before the previous steps are executed.
+    pub command: Option<String>,
+    /// Nodes the step connects to.
+    pub target_nodes: Vec<IpAddr>,
+    /// Local paths and remote locations read or written by the step.
+    pub files: Vec<String>,
+    /// Arguments which the step requires but which weren't provided. In the interactive mode the
+    /// user is asked for them before the step is executed.
+    pub required_inputs: Vec<String>,
+    /// Whether the step will be skipped with the current arguments.
+    pub skipped: bool,
+    /// Why the step couldn't be fully planned, e.g. because it depends on the output of the
+    /// previous steps.
+    pub unresolved: Option<String>,
+}
 
 pub trait RecoveryIterator<
     StepType: Copy + Debug + PartialEq + EnumMessage,
@@ -42,6 +67,48 @@ pub trait RecoveryIterator<
         vec![]
     }
 
+    /// Returns the names of the arguments which the given step requires but which haven't been
+    /// provided yet, i.e. the arguments [RecoveryIterator::read_step_params] would ask for.
+    fn get_missing_inputs(&self, _step_type: StepType) -> Vec<&'static str> {
+        vec![]
+    }
+
+    /// Describes all remaining steps without executing them or asking for any input.
+    fn plan(&mut self) -> Vec<StepPlan> {
+        let skipped_steps = self.get_skipped_steps();
+        let mut plan = vec![];
+        while let Some(step_type) = self.get_step_iterator().next() {
+            if skipped_steps.contains(&step_type) {
+                continue;
+            }
+            let mut step_plan = StepPlan {
+                step: format!("{:?}", step_type),
+                explanation: step_type.get_documentation().map(String::from),
+                command: None,
+                target_nodes: vec![],
+                files: vec![],
+                required_inputs: self
+                    .get_missing_inputs(step_type)
+                    .into_iter()
+                    .map(String::from)
+                    .collect(),
+                skipped: false,
+                unresolved: None,
+            };
+            match self.get_step_impl(step_type) {
+                Ok(step) => {
+                    step_plan.command = Some(step.descr());
+                    step_plan.target_nodes = step.target_nodes();
+                    step_plan.files = step.files();
+                }
+                Err(RecoveryError::StepSkipped) => step_plan.skipped = true,
+                Err(e) => step_plan.unresolved = Some(e.to_string()),
+            }
+            plan.push(step_plan);
+        }
+        plan
+    }
+
     fn next_step(&mut self) -> Option<(StepType, Box<dyn Step>)> {
         let skipped_steps = self.get_skipped_steps();
         let result = if let Some(current_step) = self.get_step_iterator().next() {
@@ -224,6 +291,25 @@ mod tests {
         assert_eq!(Some(FakeStep::P2), fake_recovery_iterator.next_step);
     }
 
+    #[test]
+    fn plan_describes_remaining_steps_without_reading_params() {
+        let mut fake_recovery_iterator = FakeRecoveryIterator::new(/*interactive=*/ true);
+
+        fake_recovery_iterator.resume(FakeStep::P8);
+        let plan = fake_recovery_iterator.plan();
+
+        assert_eq!(
+            plan.iter()
+                .map(|step| step.step.as_str())
+                .collect::<Vec<_>>(),
+            vec!["P8", "P9"]
+        );
+        assert!(plan
+            .iter()
+            .all(|step| !step.skipped && step.command.as_deref() == Some("Fake Step Description")));
+        assert!(!fake_recovery_iterator.read_step_params_called);
+    }
+
     #[test]
     fn next_step_reads_params_only_when_interactive() {
         for &interactive in &[false, true] {
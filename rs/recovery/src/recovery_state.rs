@@ -168,6 +168,25 @@ mod tests {
         assert_eq!(RecoveryState::<SubCommand>::read(tmp.path()).unwrap(), None);
     }
 
+    #[test]
+    fn set_next_step_parses_steps_of_the_subcommand() {
+        let tmp = tempdir().expect("Couldn't create a temp test directory");
+        let mut subcommand_args = fake_recovery_state(tmp.path()).subcommand_args;
+
+        assert!(subcommand_args.set_next_step("ProposeCup").is_ok());
+        assert!(subcommand_args
+            .set_next_step("UploadCUPandRegistry")
+            .is_err());
+
+        let SubCommand::AppSubnetRecovery(args) = subcommand_args else {
+            panic!("Unexpected subcommand");
+        };
+        assert_eq!(
+            args.next_step,
+            Some(crate::app_subnet_recovery::StepType::ProposeCup)
+        );
+    }
+
     fn fake_recovery_state(dir: &Path) -> RecoveryState<SubCommand> {
         RecoveryState::<SubCommand> {
             recovery_args: RecoveryArgs {
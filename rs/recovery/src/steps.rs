@@ -8,8 +8,9 @@ use crate::{
     replay_helper,
     ssh_helper::SshHelper,
     util::{block_on, parse_hex_str},
-    Recovery, ADMIN, CHECKPOINTS, IC_CERTIFICATIONS_PATH, IC_CHECKPOINTS_PATH, IC_DATA_PATH,
-    IC_JSON5_PATH, IC_REGISTRY_LOCAL_STORE, IC_STATE, IC_STATE_EXCLUDES, NEW_IC_STATE, READONLY,
+    Recovery, ADMIN, CHECKPOINTS, CUPS_DIR, IC_CERTIFICATIONS_PATH, IC_CHECKPOINTS_PATH,
+    IC_DATA_PATH, IC_JSON5_PATH, IC_REGISTRY_LOCAL_STORE, IC_STATE, IC_STATE_EXCLUDES,
+    NEW_IC_STATE, READONLY,
 };
 use ic_artifact_pool::certification_pool::CertificationPoolImpl;
 use ic_base_types::{CanisterId, NodeId, PrincipalId};
@@ -31,6 +32,17 @@ use std::{collections::HashMap, net::IpAddr, path::PathBuf, process::Command, th
 pub trait Step {
     fn descr(&self) -> String;
     fn exec(&self) -> RecoveryResult<()>;
+
+    /// Nodes this step connects to. Nodes which are only looked up in the registry during the
+    /// execution of the step are not listed.
+    fn target_nodes(&self) -> Vec<IpAddr> {
+        vec![]
+    }
+
+    /// Local paths and remote locations read or written by this step.
+    fn files(&self) -> Vec<String> {
+        vec![]
+    }
 }
 
 impl<T: Step + 'static> From<T> for Box<dyn Step> {
@@ -112,6 +124,13 @@ impl Step for DownloadCertificationsStep {
             Ok(())
         }
     }
+
+    fn files(&self) -> Vec<String> {
+        vec![
+            format!("{IC_DATA_PATH}/{IC_CERTIFICATIONS_PATH}"),
+            self.work_dir.join("certifications").display().to_string(),
+        ]
+    }
 }
 
 pub struct MergeCertificationPoolsStep {
@@ -254,6 +273,16 @@ impl Step for MergeCertificationPoolsStep {
 
         Ok(())
     }
+
+    fn files(&self) -> Vec<String> {
+        vec![
+            self.work_dir.join("certifications").display().to_string(),
+            self.work_dir
+                .join("data/ic_consensus_pool")
+                .display()
+                .to_string(),
+        ]
+    }
 }
 
 pub struct DownloadIcStateStep {
@@ -380,6 +409,22 @@ impl Step for DownloadIcStateStep {
 
         Ok(())
     }
+
+    fn target_nodes(&self) -> Vec<IpAddr> {
+        vec![self.node_ip]
+    }
+
+    fn files(&self) -> Vec<String> {
+        let mut files = vec![
+            format!("[{}]:{}", self.node_ip, IC_DATA_PATH),
+            format!("[{}]:{}", self.node_ip, IC_JSON5_PATH),
+            self.working_dir.clone(),
+        ];
+        if self.keep_downloaded_state {
+            files.push(self.target.clone());
+        }
+        files
+    }
 }
 
 pub struct ReplaySubCmd {
@@ -451,6 +496,18 @@ impl Step for ReplayStep {
 
         Ok(())
     }
+
+    fn files(&self) -> Vec<String> {
+        vec![
+            self.work_dir
+                .join("data")
+                .join(IC_CHECKPOINTS_PATH)
+                .display()
+                .to_string(),
+            self.config.display().to_string(),
+            self.result.display().to_string(),
+        ]
+    }
 }
 
 pub struct ValidateReplayStep {
@@ -510,6 +567,14 @@ impl Step for ValidateReplayStep {
 
         Ok(())
     }
+
+    fn files(&self) -> Vec<String> {
+        vec![self
+            .work_dir
+            .join(replay_helper::OUTPUT_FILE_NAME)
+            .display()
+            .to_string()]
+    }
 }
 
 pub struct UploadAndRestartStep {
@@ -631,6 +696,17 @@ impl Step for UploadAndRestartStep {
         ssh_helper.ssh(replace_state)?;
         Ok(())
     }
+
+    fn target_nodes(&self) -> Vec<IpAddr> {
+        vec![self.node_ip]
+    }
+
+    fn files(&self) -> Vec<String> {
+        vec![
+            self.data_src.display().to_string(),
+            format!("[{}]:{}/{}", self.node_ip, IC_DATA_PATH, IC_STATE),
+        ]
+    }
 }
 
 pub struct WaitForCUPStep {
@@ -659,6 +735,18 @@ impl Step for WaitForCUPStep {
             state_params.hash,
         )
     }
+
+    fn target_nodes(&self) -> Vec<IpAddr> {
+        vec![self.node_ip]
+    }
+
+    fn files(&self) -> Vec<String> {
+        vec![self
+            .work_dir
+            .join(replay_helper::OUTPUT_FILE_NAME)
+            .display()
+            .to_string()]
+    }
 }
 
 pub struct CleanupStep {
@@ -673,6 +761,10 @@ impl Step for CleanupStep {
     fn exec(&self) -> RecoveryResult<()> {
         remove_dir(&self.recovery_dir)
     }
+
+    fn files(&self) -> Vec<String> {
+        vec![self.recovery_dir.display().to_string()]
+    }
 }
 
 pub struct StopReplicaStep {
@@ -698,6 +790,10 @@ impl Step for StopReplicaStep {
         ssh_helper.ssh("sudo systemctl stop ic-replica".to_string())?;
         Ok(())
     }
+
+    fn target_nodes(&self) -> Vec<IpAddr> {
+        vec![self.node_ip]
+    }
 }
 
 pub struct UpdateLocalStoreStep {
@@ -724,6 +820,17 @@ impl Step for UpdateLocalStoreStep {
         ))?;
         Ok(())
     }
+
+    fn files(&self) -> Vec<String> {
+        vec![
+            self.work_dir.join("ic.json5").display().to_string(),
+            self.work_dir
+                .join("data")
+                .join(IC_REGISTRY_LOCAL_STORE)
+                .display()
+                .to_string(),
+        ]
+    }
 }
 
 pub struct GetRecoveryCUPStep {
@@ -765,6 +872,14 @@ impl Step for GetRecoveryCUPStep {
         ))?;
         Ok(())
     }
+
+    fn files(&self) -> Vec<String> {
+        vec![
+            self.config.display().to_string(),
+            self.work_dir.join("cup.proto").display().to_string(),
+            self.result.display().to_string(),
+        ]
+    }
 }
 
 pub struct CreateTarsStep {
@@ -812,6 +927,13 @@ impl Step for CopyIcStateStep {
         )?;
         Ok(())
     }
+
+    fn files(&self) -> Vec<String> {
+        vec![
+            self.work_dir.display().to_string(),
+            self.new_state_dir.display().to_string(),
+        ]
+    }
 }
 
 pub struct UploadCUPAndTar {
@@ -914,6 +1036,18 @@ impl Step for UploadCUPAndTar {
 
         Ok(())
     }
+
+    fn files(&self) -> Vec<String> {
+        vec![
+            self.work_dir.join("cup.proto").display().to_string(),
+            self.work_dir
+                .join(format!("{}.tar.zst", IC_REGISTRY_LOCAL_STORE))
+                .display()
+                .to_string(),
+            format!("{}/{}", IC_DATA_PATH, IC_REGISTRY_LOCAL_STORE),
+            format!("{}/{}", IC_DATA_PATH, CUPS_DIR),
+        ]
+    }
 }
 
 pub struct DownloadRegistryStoreStep {
@@ -987,6 +1121,23 @@ impl Step for DownloadRegistryStoreStep {
 
         Ok(())
     }
+
+    fn target_nodes(&self) -> Vec<IpAddr> {
+        vec![self.node_ip]
+    }
+
+    fn files(&self) -> Vec<String> {
+        vec![
+            format!(
+                "[{}]:{}/{}",
+                self.node_ip, IC_DATA_PATH, IC_REGISTRY_LOCAL_STORE
+            ),
+            self.work_dir
+                .join(IC_REGISTRY_LOCAL_STORE)
+                .display()
+                .to_string(),
+        ]
+    }
 }
 
 pub struct UploadAndHostTarStep {
@@ -1037,6 +1188,17 @@ impl Step for UploadAndHostTarStep {
 
         Ok(())
     }
+
+    fn target_nodes(&self) -> Vec<IpAddr> {
+        vec![self.aux_ip]
+    }
+
+    fn files(&self) -> Vec<String> {
+        vec![
+            self.tar.display().to_string(),
+            format!("[{}]:/tmp/recovery_registry", self.aux_ip),
+        ]
+    }
 }
 
 #[cfg(test)]
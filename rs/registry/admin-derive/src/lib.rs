@@ -27,7 +27,9 @@ fn impl_proposal_metadata(ast: &syn::DeriveInput) -> TokenStream {
             }
 
             fn proposer_and_sender(&self, sender: Sender) -> (NeuronId, Sender) {
-                let use_test_neuron = self.test_neuron_proposer || (self.dry_run && matches!(sender, Sender::Anonymous));
+                let use_test_neuron = self.test_neuron_proposer
+                    || ((self.dry_run || self.output_payload.is_some())
+                        && matches!(sender, Sender::Anonymous));
                 get_proposer_and_sender(self.proposer.clone(), sender, use_test_neuron)
             }
 
@@ -38,6 +40,10 @@ fn impl_proposal_metadata(ast: &syn::DeriveInput) -> TokenStream {
             fn is_json(&self) -> bool {
                 self.json
             }
+
+            fn output_payload(&self) -> Option<PathBuf> {
+                self.output_payload.clone()
+            }
         }
     };
     gen.into()
@@ -94,6 +100,13 @@ pub fn derive_common_proposal_fields(_: TokenStream, item: TokenStream) -> Token
                             /// If set, JSON output will be printed for --dry-run
                             #[clap(long)]
                             pub json: bool,
+
+                            /// If set, the proposal will not be submitted. Instead, the
+                            /// Candid-encoded MakeProposalRequest is written to this file and a
+                            /// human-readable summary to the same path with a `.summary.txt`
+                            /// suffix, so that it can be checked with `validate-payload`.
+                            #[clap(long)]
+                            pub output_payload: Option<PathBuf>,
                     });
                     stream.extend(gen);
                     stream.extend(group.stream());
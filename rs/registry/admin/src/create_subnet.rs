@@ -327,6 +327,7 @@ mod tests {
             test_neuron_proposer: false,
             dry_run: false,
             json: true,
+            output_payload: None,
             start_as_nns: false,
             is_halted: false,
             node_ids: vec![],
@@ -124,6 +124,7 @@ use ic_types::{
 use indexmap::IndexMap;
 use itertools::izip;
 use maplit::hashmap;
+use proposal_file::{validate_payload, write_proposal_to_file, ValidatePayloadCmd};
 use prost::Message;
 use recover_subnet::ProposeToUpdateRecoveryCupCmd;
 use registry_canister::mutations::{
@@ -179,6 +180,7 @@ extern crate chrono;
 
 mod create_subnet;
 mod helpers;
+mod proposal_file;
 mod recover_subnet;
 mod types;
 mod update_subnet;
@@ -336,6 +338,9 @@ enum SubCommand {
     GetNodeOperatorList,
     /// Update local registry store by pulling from remote URL
     UpdateRegistryLocalStore(UpdateRegistryLocalStoreCmd),
+    /// Validate a proposal written with `--output-payload` against a local
+    /// registry store, without submitting it
+    ValidatePayload(ValidatePayloadCmd),
     /// Update the whitelist of principals that can access the provisional_*
     /// APIs in the management canister.
     ProposeToClearProvisionalWhitelist(ProposeToClearProvisionalWhitelistCmd),
@@ -2869,6 +2874,7 @@ impl TryFrom<ProposeToCreateServiceNervousSystemCmd> for CreateServiceNervousSys
             summary_file: _,
             dry_run: _,
             json: _,
+            output_payload: _,
         } = cmd;
 
         let name = Some(name);
@@ -3101,6 +3107,11 @@ async fn propose_to_create_service_nervous_system(
     };
     print_proposal(&proposal, &cmd);
 
+    if let Some(payload_file) = cmd.output_payload() {
+        write_proposal_to_file(&proposal, &payload_file);
+        return;
+    }
+
     if is_dry_run {
         return;
     }
@@ -3497,6 +3508,12 @@ async fn find_reachable_nns_urls(nns_urls: Vec<Url>) -> Vec<Url> {
 async fn main() {
     let opts: Opts = Opts::parse();
 
+    // Validating a proposal payload works offline, so it does not need any NNS URLs.
+    if let SubCommand::ValidatePayload(cmd) = opts.subcmd {
+        validate_payload(cmd);
+        return;
+    }
+
     let reachable_nns_urls = find_reachable_nns_urls(opts.nns_urls.clone()).await;
 
     if reachable_nns_urls.is_empty() {
@@ -5047,6 +5064,18 @@ async fn propose_external_proposal_from_command<
 
     print_proposal(&payload, &cmd);
 
+    if let Some(payload_file) = cmd.output_payload() {
+        let proposal = create_external_update_proposal_candid(
+            &cmd.title(),
+            &cmd.summary(),
+            &cmd.url(),
+            nns_function,
+            payload,
+        );
+        write_proposal_to_file(&proposal, &payload_file);
+        return;
+    }
+
     if cmd.is_dry_run() {
         return;
     }
@@ -5090,6 +5119,17 @@ where
 
     print_proposal(&Action::from(action.clone()), &cmd);
 
+    if let Some(payload_file) = cmd.output_payload() {
+        let proposal = MakeProposalRequest {
+            title: Some(cmd.title()),
+            summary: cmd.summary(),
+            url: cmd.url(),
+            action: Some(action),
+        };
+        write_proposal_to_file(&proposal, &payload_file);
+        return;
+    }
+
     if cmd.is_dry_run() {
         return;
     }
@@ -5597,11 +5637,22 @@ async fn propose_to_add_or_remove_node_provider(
     let payload = AddOrRemoveNodeProvider { change };
     print_proposal(&payload, &cmd);
 
+    let summary = cmd.summary.clone().unwrap_or(default_summary);
+    if let Some(payload_file) = cmd.output_payload() {
+        let proposal = MakeProposalRequest {
+            title: Some(title),
+            summary,
+            url: parse_proposal_url(cmd.proposal_url),
+            action: Some(ProposalActionRequest::AddOrRemoveNodeProvider(payload)),
+        };
+        write_proposal_to_file(&proposal, &payload_file);
+        return;
+    }
+
     if cmd.is_dry_run() {
         return;
     }
 
-    let summary = cmd.summary.unwrap_or(default_summary);
     let response = canister_client
         .submit_add_or_remove_node_provider_proposal(
             payload,
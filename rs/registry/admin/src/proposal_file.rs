@@ -0,0 +1,402 @@
+//! Support for writing proposals to a file instead of submitting them, and for
+//! validating such files offline against a local registry store.
+use candid::{CandidType, Decode, Encode, IDLArgs};
+use clap::Parser;
+use ic_nns_governance_api::pb::v1::{
+    ExecuteNnsFunction, MakeProposalRequest, NnsFunction, ProposalActionRequest,
+};
+use ic_registry_local_store::{LocalStoreImpl, LocalStoreReader};
+use ic_registry_transport::{delete, pb::v1::RegistryAtomicMutateRequest, upsert};
+use ic_types::RegistryVersion;
+use prost::Message;
+use registry_canister::{
+    pb::v1::{registry_stable_storage, ChangelogEntry, RegistryStableStorage},
+    registry::Registry,
+};
+use serde::de::DeserializeOwned;
+use std::{
+    fs::{read, write},
+    panic::{catch_unwind, AssertUnwindSafe},
+    path::{Path, PathBuf},
+    process::exit,
+};
+
+/// Sub-command to validate a proposal written with `--output-payload` against a
+/// local registry store, without talking to the NNS.
+#[derive(Parser)]
+pub(crate) struct ValidatePayloadCmd {
+    /// The file containing the Candid-encoded `MakeProposalRequest`.
+    pub payload_file: PathBuf,
+
+    #[clap(long, required = true)]
+    /// The local registry store the proposal is validated against, e.g. as
+    /// created by `update-registry-local-store`.
+    pub local_store: PathBuf,
+}
+
+/// Returns the path of the human-readable summary written next to `payload_file`.
+fn summary_file_path(payload_file: &Path) -> PathBuf {
+    let mut path = payload_file.as_os_str().to_owned();
+    path.push(".summary.txt");
+    PathBuf::from(path)
+}
+
+/// Writes the Candid-encoded `proposal` to `payload_file` and a human-readable
+/// summary of it next to it.
+pub(crate) fn write_proposal_to_file(proposal: &MakeProposalRequest, payload_file: &Path) {
+    let bytes = Encode!(proposal).expect("Cannot candid-serialize the MakeProposalRequest");
+    write(payload_file, bytes)
+        .unwrap_or_else(|e| panic!("Cannot write the payload to {:?}: {}", payload_file, e));
+
+    let summary_file = summary_file_path(payload_file);
+    write(&summary_file, describe_proposal(proposal))
+        .unwrap_or_else(|e| panic!("Cannot write the summary to {:?}: {}", summary_file, e));
+
+    eprintln!(
+        "Wrote the proposal payload to {:?} and its summary to {:?}",
+        payload_file, summary_file
+    );
+}
+
+/// Renders the proposal in a human-readable form. The payloads of NNS functions
+/// are decoded to their Candid text representation.
+fn describe_proposal(proposal: &MakeProposalRequest) -> String {
+    let mut description = format!(
+        "Title: {}\nURL: {}\n\nSummary:\n{}\n\n",
+        proposal.title.clone().unwrap_or_default(),
+        proposal.url,
+        proposal.summary
+    );
+    match &proposal.action {
+        Some(ProposalActionRequest::ExecuteNnsFunction(ExecuteNnsFunction {
+            nns_function,
+            payload,
+        })) => {
+            let nns_function = NnsFunction::try_from(*nns_function)
+                .map(|f| format!("{:?}", f))
+                .unwrap_or_else(|_| format!("unknown NNS function {}", nns_function));
+            let payload = IDLArgs::from_bytes(payload)
+                .map(|args| args.to_string())
+                .unwrap_or_else(|e| format!("<cannot decode the payload: {}>", e));
+            description.push_str(&format!(
+                "Action: ExecuteNnsFunction({})\n\nPayload:\n{}\n",
+                nns_function, payload
+            ));
+        }
+        Some(action) => description.push_str(&format!("Action: {:#?}\n", action)),
+        None => description.push_str("Action: none\n"),
+    }
+    description
+}
+
+/// Decodes the proposal in `cmd.payload_file` and checks it against the
+/// registry in `cmd.local_store` by running the mutation of the registry
+/// canister, including its global invariant checks, on a local copy.
+pub(crate) fn validate_payload(cmd: ValidatePayloadCmd) {
+    let bytes = read(&cmd.payload_file)
+        .unwrap_or_else(|e| panic!("Cannot read {:?}: {}", cmd.payload_file, e));
+    let proposal = Decode!(&bytes, MakeProposalRequest).unwrap_or_else(|e| {
+        eprintln!(
+            "{:?} does not contain a Candid-encoded MakeProposalRequest: {}",
+            cmd.payload_file, e
+        );
+        exit(1);
+    });
+    println!("{}", describe_proposal(&proposal));
+
+    let Some(ProposalActionRequest::ExecuteNnsFunction(ExecuteNnsFunction {
+        nns_function,
+        payload,
+    })) = proposal.action
+    else {
+        println!("The proposal does not mutate the registry, nothing to validate.");
+        return;
+    };
+    let Ok(nns_function) = NnsFunction::try_from(nns_function) else {
+        eprintln!("Unknown NNS function: {}", nns_function);
+        exit(1);
+    };
+
+    let mut registry = registry_from_local_store(&cmd.local_store);
+    let version = registry.latest_version();
+    match apply_to_registry(&mut registry, nns_function, &payload) {
+        None => println!(
+            "{:?} is not handled by the registry canister or cannot be validated offline.",
+            nns_function
+        ),
+        Some(Ok(())) => println!(
+            "The proposal passes the registry canister validation at registry version {}.",
+            version
+        ),
+        Some(Err(e)) => {
+            eprintln!(
+                "The proposal fails the registry canister validation at registry version {}: {}",
+                version, e
+            );
+            exit(1);
+        }
+    }
+}
+
+/// Builds an in-memory registry canister state from the content of a local store.
+fn registry_from_local_store(path: &Path) -> Registry {
+    let changelog = LocalStoreImpl::new(path)
+        .get_changelog_since_version(RegistryVersion::from(0))
+        .unwrap_or_else(|e| panic!("Cannot read the local store at {:?}: {}", path, e));
+    if changelog.is_empty() {
+        eprintln!("The local store at {:?} is empty", path);
+        exit(1);
+    }
+
+    let changelog = changelog
+        .into_iter()
+        .enumerate()
+        .map(|(i, key_mutations)| {
+            let mutations = key_mutations
+                .into_iter()
+                .map(|m| match m.value {
+                    Some(value) => upsert(m.key, value),
+                    None => delete(m.key),
+                })
+                .collect();
+            ChangelogEntry {
+                version: i as u64 + 1,
+                encoded_mutation: RegistryAtomicMutateRequest {
+                    mutations,
+                    preconditions: vec![],
+                }
+                .encode_to_vec(),
+            }
+        })
+        .collect();
+
+    let mut registry = Registry::new();
+    registry.from_serializable_form(RegistryStableStorage {
+        version: registry_stable_storage::Version::Version1 as i32,
+        deltas: vec![],
+        changelog,
+    });
+    registry
+}
+
+fn decode_payload<T: CandidType + DeserializeOwned>(payload: &[u8]) -> Result<T, String> {
+    Decode!(payload, T).map_err(|e| {
+        format!(
+            "Cannot decode the payload as {}: {}",
+            std::any::type_name::<T>(),
+            e
+        )
+    })
+}
+
+/// Applies the NNS function to `registry` the way the registry canister would.
+/// Returns `None` if the function is not executed by the registry canister, or
+/// depends on calls to other canisters and therefore cannot be run offline.
+fn apply_to_registry(
+    registry: &mut Registry,
+    nns_function: NnsFunction,
+    payload: &[u8],
+) -> Option<Result<(), String>> {
+    let apply = || -> Result<bool, String> {
+        match nns_function {
+            NnsFunction::AssignNoid => registry.do_add_node_operator(decode_payload(payload)?),
+            NnsFunction::AddNodeToSubnet => {
+                registry.do_add_nodes_to_subnet(decode_payload(payload)?)
+            }
+            NnsFunction::RemoveNodesFromSubnet => {
+                registry.do_remove_nodes_from_subnet(decode_payload(payload)?)
+            }
+            NnsFunction::ChangeSubnetMembership => {
+                registry.do_change_subnet_membership(decode_payload(payload)?)
+            }
+            NnsFunction::ReviseElectedGuestosVersions => {
+                registry.do_revise_elected_guestos_versions(decode_payload(payload)?)
+            }
+            NnsFunction::UpdateNodeOperatorConfig => {
+                registry.do_update_node_operator_config(decode_payload(payload)?)
+            }
+            NnsFunction::DeployGuestosToAllSubnetNodes => {
+                registry.do_deploy_guestos_to_all_subnet_nodes(decode_payload(payload)?)
+            }
+            NnsFunction::ReviseElectedHostosVersions => {
+                registry.do_revise_elected_hostos_versions(decode_payload(payload)?)
+            }
+            NnsFunction::DeployHostosToSomeNodes => {
+                registry.do_deploy_hostos_to_some_nodes(decode_payload(payload)?)
+            }
+            NnsFunction::UpdateConfigOfSubnet => {
+                registry.do_update_subnet(decode_payload(payload)?)
+            }
+            NnsFunction::ClearProvisionalWhitelist => registry.do_clear_provisional_whitelist(),
+            NnsFunction::SetFirewallConfig => {
+                registry.do_set_firewall_config(decode_payload(payload)?)
+            }
+            NnsFunction::AddFirewallRules => {
+                registry.do_add_firewall_rules(decode_payload(payload)?)
+            }
+            NnsFunction::RemoveFirewallRules => {
+                registry.do_remove_firewall_rules(decode_payload(payload)?)
+            }
+            NnsFunction::UpdateFirewallRules => {
+                registry.do_update_firewall_rules(decode_payload(payload)?)
+            }
+            NnsFunction::RemoveNodes => registry.do_remove_nodes(decode_payload(payload)?),
+            NnsFunction::UpdateNodeRewardsTable => {
+                registry.do_update_node_rewards_table(decode_payload(payload)?)
+            }
+            NnsFunction::AddOrRemoveDataCenters => {
+                registry.do_add_or_remove_data_centers(decode_payload(payload)?)
+            }
+            NnsFunction::UpdateUnassignedNodesConfig => {
+                registry.do_update_unassigned_nodes_config(decode_payload(payload)?)
+            }
+            NnsFunction::RemoveNodeOperators => {
+                registry.do_remove_node_operators(decode_payload(payload)?)
+            }
+            NnsFunction::RerouteCanisterRanges => {
+                registry.reroute_canister_ranges(decode_payload(payload)?)?
+            }
+            NnsFunction::PrepareCanisterMigration => registry
+                .prepare_canister_migration(decode_payload(payload)?)
+                .map_err(|e| e.to_string())?,
+            NnsFunction::CompleteCanisterMigration => {
+                registry.complete_canister_migration(decode_payload(payload)?)?
+            }
+            NnsFunction::AddApiBoundaryNodes => {
+                registry.do_add_api_boundary_nodes(decode_payload(payload)?)
+            }
+            NnsFunction::RemoveApiBoundaryNodes => {
+                registry.do_remove_api_boundary_nodes(decode_payload(payload)?)
+            }
+            NnsFunction::DeployGuestosToSomeApiBoundaryNodes => {
+                registry.do_deploy_guestos_to_some_api_boundary_nodes(decode_payload(payload)?)
+            }
+            NnsFunction::DeployGuestosToAllUnassignedNodes => {
+                registry.do_deploy_guestos_to_all_unassigned_nodes(decode_payload(payload)?)
+            }
+            NnsFunction::UpdateSshReadonlyAccessForAllUnassignedNodes => registry
+                .do_update_ssh_readonly_access_for_all_unassigned_nodes(decode_payload(payload)?),
+            // CreateSubnet and RecoverSubnet call the management canister, the
+            // rest is executed by other canisters.
+            _ => return Ok(false),
+        };
+        Ok(true)
+    };
+
+    // The registry canister traps when a mutation violates one of its
+    // invariants, which is a panic when running it natively.
+    match catch_unwind(AssertUnwindSafe(apply)) {
+        Ok(Ok(true)) => Some(Ok(())),
+        Ok(Ok(false)) => None,
+        Ok(Err(e)) => Some(Err(e)),
+        Err(panic) => Some(Err(panic
+            .downcast_ref::<String>()
+            .cloned()
+            .or_else(|| panic.downcast_ref::<&str>().map(|s| s.to_string()))
+            .unwrap_or_else(|| "the registry canister panicked".to_string()))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ic_registry_local_store::{KeyMutation, LocalStoreWriter};
+    use ic_types::{NodeId, PrincipalId};
+    use registry_canister::mutations::do_remove_nodes_from_subnet::RemoveNodesFromSubnetPayload;
+
+    use super::*;
+    use ic_nns_governance_api::proposal_submission_helpers::create_external_update_proposal_candid;
+
+    fn registry_with_one_key(key: &str, value: &[u8]) -> Registry {
+        let dir = tempfile::tempdir().unwrap();
+        LocalStoreImpl::new(dir.path())
+            .store(
+                RegistryVersion::from(1),
+                vec![KeyMutation {
+                    key: key.to_string(),
+                    value: Some(value.to_vec()),
+                }],
+            )
+            .unwrap();
+        registry_from_local_store(dir.path())
+    }
+
+    #[test]
+    fn registry_is_loaded_from_local_store() {
+        let registry = registry_with_one_key("some_key", b"some_value");
+
+        assert_eq!(registry.latest_version(), 1);
+        assert_eq!(
+            registry.get(b"some_key", 1).map(|v| v.value.clone()),
+            Some(b"some_value".to_vec())
+        );
+    }
+
+    #[test]
+    fn proposal_is_written_with_summary() {
+        let dir = tempfile::tempdir().unwrap();
+        let payload_file = dir.path().join("proposal.bin");
+        let proposal = create_external_update_proposal_candid(
+            "Remove a node",
+            "Removes a node from its subnet.",
+            "https://forum.dfinity.org",
+            NnsFunction::RemoveNodesFromSubnet,
+            RemoveNodesFromSubnetPayload {
+                node_ids: vec![NodeId::from(PrincipalId::new_node_test_id(1))],
+            },
+        );
+
+        write_proposal_to_file(&proposal, &payload_file);
+
+        let bytes = read(&payload_file).unwrap();
+        assert_eq!(Decode!(&bytes, MakeProposalRequest).unwrap(), proposal);
+        let summary = std::fs::read_to_string(dir.path().join("proposal.bin.summary.txt")).unwrap();
+        assert!(summary.contains("Title: Remove a node"));
+        assert!(summary.contains("Action: ExecuteNnsFunction(RemoveNodesFromSubnet)"));
+    }
+
+    #[test]
+    fn invariant_violations_are_reported() {
+        let mut registry = registry_with_one_key("some_key", b"some_value");
+        let payload = Encode!(&RemoveNodesFromSubnetPayload {
+            node_ids: vec![NodeId::from(PrincipalId::new_node_test_id(1))],
+        })
+        .unwrap();
+
+        let result = apply_to_registry(&mut registry, NnsFunction::RemoveNodesFromSubnet, &payload);
+
+        assert!(result
+            .unwrap()
+            .unwrap_err()
+            .contains("subnet list record not found"));
+        assert_eq!(registry.latest_version(), 1);
+    }
+
+    #[test]
+    fn undecodable_payloads_are_reported() {
+        let mut registry = registry_with_one_key("some_key", b"some_value");
+
+        let result = apply_to_registry(
+            &mut registry,
+            NnsFunction::UpdateConfigOfSubnet,
+            &Encode!(&"not a payload").unwrap(),
+        );
+
+        assert!(result
+            .unwrap()
+            .unwrap_err()
+            .contains("Cannot decode the payload"));
+    }
+
+    #[test]
+    fn functions_of_other_canisters_are_not_validated() {
+        let mut registry = registry_with_one_key("some_key", b"some_value");
+
+        let result = apply_to_registry(
+            &mut registry,
+            NnsFunction::IcpXdrConversionRate,
+            &Encode!(&()).unwrap(),
+        );
+
+        assert_eq!(result, None);
+    }
+}
@@ -258,6 +258,7 @@ mod tests {
             test_neuron_proposer: false,
             dry_run: false,
             json: true,
+            output_payload: None,
             height,
             time_ns,
             state_hash,
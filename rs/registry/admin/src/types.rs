@@ -21,6 +21,7 @@ use serde::{Deserialize, Serialize};
 use std::{
     convert::{From, TryFrom, TryInto},
     net::{Ipv4Addr, Ipv6Addr},
+    path::PathBuf,
     str::FromStr,
 };
 use strum_macros::EnumString;
@@ -202,6 +203,7 @@ pub trait ProposalMetadata {
     fn proposer_and_sender(&self, sender: Sender) -> (NeuronId, Sender);
     fn is_dry_run(&self) -> bool;
     fn is_json(&self) -> bool;
+    fn output_payload(&self) -> Option<PathBuf>;
 }
 
 /// A description of a subnet, either by index, or by id.
@@ -399,6 +399,7 @@ mod tests {
             test_neuron_proposer: false,
             dry_run: true,
             json: true,
+            output_payload: None,
             proposer: None,
             proposal_url: None,
             proposal_title: None,
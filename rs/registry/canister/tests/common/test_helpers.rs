@@ -346,6 +346,12 @@ pub async fn wait_for_chain_key_setup(
         MasterPublicKeyId::Schnorr(key_id) => {
             wait_for_schnorr_setup(runtime, calling_canister, key_id).await;
         }
+        MasterPublicKeyId::VetKd(key_id) => {
+            panic!(
+                "Waiting for the setup of vetKD key {} is not supported",
+                key_id
+            );
+        }
     }
 }
 
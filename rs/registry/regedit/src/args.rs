@@ -38,6 +38,26 @@ pub enum CommandArg {
         /// Path to the local store (may not be specified together with --url).
         local_store_path: PathBuf,
     },
+    /// Prints the changes of the registry records between two registry
+    /// versions, grouped by the record type.
+    Log {
+        /// The registry version after which the log starts. (default: 0)
+        #[clap(short, long)]
+        from: Option<u64>,
+
+        /// The last registry version included in the log. (default: latest
+        /// available version.)
+        #[clap(short, long, allow_hyphen_values = true)]
+        to: Option<i64>,
+
+        /// Comma-separated list of key prefixes. If provided, only changes of
+        /// records with these prefixes are shown.
+        #[clap(short, long)]
+        keys: Option<String>,
+
+        /// Path to the local store.
+        local_store_path: PathBuf,
+    },
     CanisterToProto {
         /// Url to a node hosting the registry canister (may not be specified
         /// together with --local-store).
@@ -147,6 +167,17 @@ impl CliArgs {
                     projection,
                 }
             }
+            CommandArg::Log {
+                from,
+                to,
+                keys,
+                local_store_path,
+            } => Command::Log {
+                source: SourceSpec::LocalStore(Self::is_dir(local_store_path)?),
+                from: from.unwrap_or_default().into(),
+                to: to.into(),
+                projection: Self::keys_to_projection(keys),
+            },
             CommandArg::CanisterToProto {
                 start_version,
                 latest_version,
@@ -304,6 +335,12 @@ pub enum Command {
         registry_spec: RegistrySpec,
         projection: Projection,
     },
+    Log {
+        source: SourceSpec,
+        from: RegistryVersion,
+        to: VersionSpec,
+        projection: Projection,
+    },
     CanisterToProto {
         start_version: RegistryVersion,
         latest_version: Option<RegistryVersion>,
@@ -0,0 +1,485 @@
+use crate::{
+    args::{Projection, VersionSpec},
+    json,
+    normalization::{self, PRINCIPAL_ID},
+    protobuf::raw_data_to_value,
+    snapshot::resolve_version,
+    source::Changelog,
+};
+use anyhow::Result;
+use ic_registry_keys::{
+    make_blessed_replica_versions_key, make_canister_migrations_record_key,
+    make_routing_table_record_key, make_subnet_list_record_key, CRYPTO_RECORD_KEY_PREFIX,
+    CRYPTO_THRESHOLD_SIGNING_KEY_PREFIX, CRYPTO_TLS_CERT_KEY_PREFIX, DATA_CENTER_KEY_PREFIX,
+    NODE_OPERATOR_RECORD_KEY_PREFIX, NODE_RECORD_KEY_PREFIX, REPLICA_VERSION_KEY_PREFIX,
+    SUBNET_RECORD_KEY_PREFIX,
+};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+
+const MEMBERSHIP_FIELD: &str = "membership";
+
+/// The type of a registry record, by which the changes are grouped.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordType {
+    Subnet,
+    SubnetList,
+    Node,
+    NodeOperator,
+    RoutingTable,
+    CanisterMigrations,
+    ReplicaVersion,
+    CatchUpPackage,
+    Crypto,
+    Firewall,
+    DataCenter,
+    Other,
+}
+
+/// A single change of a registry record.
+#[derive(Clone, Eq, PartialEq, Debug, Serialize)]
+pub struct Change {
+    pub version: u64,
+    pub key: String,
+    pub summary: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub deltas: Vec<FieldDelta>,
+}
+
+/// The change of a single field of a record. Nested fields are addressed by their
+/// dot-separated path.
+#[derive(Clone, Eq, PartialEq, Debug, Serialize)]
+pub struct FieldDelta {
+    pub field: String,
+    pub old: Value,
+    pub new: Value,
+}
+
+#[derive(Serialize)]
+struct Log {
+    from: u64,
+    to: u64,
+    changes: BTreeMap<RecordType, Vec<Change>>,
+}
+
+/// Returns the changes of all records matching `projection` after the registry version `from`
+/// up to (and including) the version `to`, grouped by the record type.
+pub fn changelog_to_log(
+    changelog: Changelog,
+    from: u64,
+    to: VersionSpec,
+    projection: &Projection,
+) -> Result<Value> {
+    let (mut changelog, latest_version) = changelog;
+    let to = resolve_version(to, latest_version)?;
+
+    changelog.retain(|r| r.version.get() <= to && projection.iter().any(|p| r.key.starts_with(p)));
+    changelog.sort_by_key(|r| r.version);
+
+    let mut records = BTreeMap::<String, Value>::default();
+    let mut changes = BTreeMap::<RecordType, Vec<Change>>::default();
+    for entry in changelog {
+        let new_value = entry.value.map(|data| {
+            let (normalized, _) = normalization::normalize(raw_data_to_value(&entry.key, &data));
+            normalized.0
+        });
+        let old_value = match &new_value {
+            Some(value) => records.insert(entry.key.clone(), value.clone()),
+            None => records.remove(&entry.key),
+        };
+        if entry.version.get() <= from {
+            continue;
+        }
+
+        let record_changes = record_changes(
+            entry.version.get(),
+            &entry.key,
+            old_value.as_ref(),
+            new_value.as_ref(),
+        );
+        if !record_changes.is_empty() {
+            changes
+                .entry(record_type(&entry.key))
+                .or_default()
+                .extend(record_changes);
+        }
+    }
+
+    Ok(json::assert_to_value(Log { from, to, changes }))
+}
+
+fn record_type(key: &str) -> RecordType {
+    if key.starts_with(SUBNET_RECORD_KEY_PREFIX) {
+        RecordType::Subnet
+    } else if key == make_subnet_list_record_key() {
+        RecordType::SubnetList
+    } else if key.starts_with(NODE_RECORD_KEY_PREFIX) {
+        RecordType::Node
+    } else if key.starts_with(NODE_OPERATOR_RECORD_KEY_PREFIX) {
+        RecordType::NodeOperator
+    } else if key == make_routing_table_record_key() {
+        RecordType::RoutingTable
+    } else if key == make_canister_migrations_record_key() {
+        RecordType::CanisterMigrations
+    } else if key.starts_with(REPLICA_VERSION_KEY_PREFIX)
+        || key == make_blessed_replica_versions_key()
+    {
+        RecordType::ReplicaVersion
+    } else if key.starts_with("catch_up_package_contents_") {
+        RecordType::CatchUpPackage
+    } else if key.starts_with(CRYPTO_RECORD_KEY_PREFIX)
+        || key.starts_with(CRYPTO_TLS_CERT_KEY_PREFIX)
+        || key.starts_with(CRYPTO_THRESHOLD_SIGNING_KEY_PREFIX)
+    {
+        RecordType::Crypto
+    } else if key.starts_with("firewall_") {
+        RecordType::Firewall
+    } else if key.starts_with(DATA_CENTER_KEY_PREFIX) {
+        RecordType::DataCenter
+    } else {
+        RecordType::Other
+    }
+}
+
+/// Describes the change of the record `key` from `old` to `new` at the given version. A missing
+/// value means that the record doesn't exist.
+fn record_changes(
+    version: u64,
+    key: &str,
+    old: Option<&Value>,
+    new: Option<&Value>,
+) -> Vec<Change> {
+    match record_type(key) {
+        RecordType::Subnet => subnet_changes(version, key, old, new),
+        RecordType::RoutingTable => routing_table_changes(version, key, old, new),
+        _ => {
+            let deltas = record_deltas(old.cloned(), new.cloned());
+            if deltas.is_empty() {
+                return vec![];
+            }
+            let summary = match (old, new) {
+                (None, _) => format!("{} created", key),
+                (_, None) => format!("{} deleted", key),
+                _ => format!("{} changed: {}", key, changed_fields(&deltas)),
+            };
+            vec![Change {
+                version,
+                key: key.to_string(),
+                summary,
+                deltas,
+            }]
+        }
+    }
+}
+
+fn subnet_changes(
+    version: u64,
+    key: &str,
+    old: Option<&Value>,
+    new: Option<&Value>,
+) -> Vec<Change> {
+    let subnet_id = key.strip_prefix(SUBNET_RECORD_KEY_PREFIX).unwrap_or(key);
+    let change = |summary: String, deltas: Vec<FieldDelta>| Change {
+        version,
+        key: key.to_string(),
+        summary,
+        deltas,
+    };
+    let mut changes = vec![];
+
+    let without_membership = |value: Option<&Value>| {
+        value.cloned().map(|mut value| {
+            if let Some(fields) = value.as_object_mut() {
+                fields.remove(MEMBERSHIP_FIELD);
+            }
+            value
+        })
+    };
+    let deltas = record_deltas(without_membership(old), without_membership(new));
+    match (old, new) {
+        (None, Some(_)) => changes.push(change(format!("subnet {} created", subnet_id), deltas)),
+        (Some(_), None) => changes.push(change(format!("subnet {} deleted", subnet_id), deltas)),
+        _ if !deltas.is_empty() => changes.push(change(
+            format!(
+                "subnet {} config changed: {}",
+                subnet_id,
+                changed_fields(&deltas)
+            ),
+            deltas,
+        )),
+        _ => {}
+    }
+
+    let old_members = subnet_members(old);
+    let new_members = subnet_members(new);
+    for node_id in new_members.difference(&old_members) {
+        changes.push(change(
+            format!("node {} added to subnet {}", node_id, subnet_id),
+            vec![],
+        ));
+    }
+    for node_id in old_members.difference(&new_members) {
+        changes.push(change(
+            format!("node {} removed from subnet {}", node_id, subnet_id),
+            vec![],
+        ));
+    }
+
+    changes
+}
+
+fn routing_table_changes(
+    version: u64,
+    key: &str,
+    old: Option<&Value>,
+    new: Option<&Value>,
+) -> Vec<Change> {
+    let old_ranges = routing_table_ranges(old);
+    let new_ranges = routing_table_ranges(new);
+    let change =
+        |(start, end): &(String, String), summary: String, old: Value, new: Value| Change {
+            version,
+            key: key.to_string(),
+            summary: format!("canister range {}:{} {}", start, end, summary),
+            deltas: vec![FieldDelta {
+                field: "subnet_id".to_string(),
+                old,
+                new,
+            }],
+        };
+    let mut changes = vec![];
+
+    for (range, subnet_id) in new_ranges.iter() {
+        match old_ranges.get(range) {
+            Some(old_subnet_id) if old_subnet_id == subnet_id => {}
+            Some(old_subnet_id) => changes.push(change(
+                range,
+                format!(
+                    "moved from subnet {} to subnet {}",
+                    old_subnet_id, subnet_id
+                ),
+                json::assert_to_value(old_subnet_id),
+                json::assert_to_value(subnet_id),
+            )),
+            None => changes.push(change(
+                range,
+                format!("assigned to subnet {}", subnet_id),
+                Value::Null,
+                json::assert_to_value(subnet_id),
+            )),
+        }
+    }
+    for (range, subnet_id) in old_ranges.iter() {
+        if !new_ranges.contains_key(range) {
+            changes.push(change(
+                range,
+                format!("removed from subnet {}", subnet_id),
+                json::assert_to_value(subnet_id),
+                Value::Null,
+            ));
+        }
+    }
+
+    changes
+}
+
+/// Returns the field-level differences between two versions of a record.
+fn record_deltas(old: Option<Value>, new: Option<Value>) -> Vec<FieldDelta> {
+    let empty = || Value::Object(Default::default());
+    let mut deltas = vec![];
+    field_deltas(
+        "",
+        &old.unwrap_or_else(empty),
+        &new.unwrap_or_else(empty),
+        &mut deltas,
+    );
+    deltas
+}
+
+fn field_deltas(path: &str, old: &Value, new: &Value, deltas: &mut Vec<FieldDelta>) {
+    match (old, new) {
+        (Value::Object(old_fields), Value::Object(new_fields)) => {
+            let fields: BTreeSet<&String> = old_fields.keys().chain(new_fields.keys()).collect();
+            for field in fields {
+                let field_path = if path.is_empty() {
+                    field.clone()
+                } else {
+                    format!("{}.{}", path, field)
+                };
+                field_deltas(
+                    &field_path,
+                    old_fields.get(field).unwrap_or(&Value::Null),
+                    new_fields.get(field).unwrap_or(&Value::Null),
+                    deltas,
+                );
+            }
+        }
+        _ if old != new => deltas.push(FieldDelta {
+            field: path.to_string(),
+            old: old.clone(),
+            new: new.clone(),
+        }),
+        _ => {}
+    }
+}
+
+fn changed_fields(deltas: &[FieldDelta]) -> String {
+    deltas
+        .iter()
+        .map(|delta| delta.field.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn subnet_members(record: Option<&Value>) -> BTreeSet<String> {
+    record
+        .and_then(|record| record.get(MEMBERSHIP_FIELD))
+        .and_then(Value::as_array)
+        .map(|members| members.iter().map(principal_id).collect())
+        .unwrap_or_default()
+}
+
+/// Returns the subnet ids of the canister ranges of the routing table, indexed by the first and
+/// the last canister id of the range.
+fn routing_table_ranges(record: Option<&Value>) -> BTreeMap<(String, String), String> {
+    record
+        .and_then(|record| record.get("entries"))
+        .and_then(Value::as_array)
+        .map(|entries| {
+            entries
+                .iter()
+                .map(|entry| {
+                    let range = &entry["range"];
+                    (
+                        (
+                            principal_id(&range["start_canister_id"]),
+                            principal_id(&range["end_canister_id"]),
+                        ),
+                        principal_id(&entry["subnet_id"]),
+                    )
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Returns the textual representation of a normalized principal id, which is either a plain
+/// principal id or a message wrapping it, like a subnet or canister id.
+fn principal_id(value: &Value) -> String {
+    if let Some(s) = value.as_str() {
+        return s.strip_prefix(PRINCIPAL_ID).unwrap_or(s).to_string();
+    }
+    match value.get("principal_id").and_then(|p| p.get("raw")) {
+        Some(raw) => principal_id(raw),
+        None => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const SUBNET_KEY: &str = "subnet_record_aaaaa-aa";
+
+    fn principal(id: &str) -> Value {
+        json!({ "principal_id": { "raw": format!("{}{}", PRINCIPAL_ID, id) } })
+    }
+
+    fn routing_table(entries: &[(&str, &str, &str)]) -> Value {
+        let entries: Vec<_> = entries
+            .iter()
+            .map(|(start, end, subnet_id)| {
+                json!({
+                    "range": {
+                        "start_canister_id": principal(start),
+                        "end_canister_id": principal(end),
+                    },
+                    "subnet_id": principal(subnet_id),
+                })
+            })
+            .collect();
+        json!({ "entries": entries })
+    }
+
+    #[test]
+    fn subnet_changes_report_members_and_field_deltas() {
+        let old = json!({
+            "membership": ["(principal-id)node-1", "(principal-id)node-2"],
+            "max_ingress_bytes_per_message": 2097152,
+            "features": { "http_requests": true },
+        });
+        let new = json!({
+            "membership": ["(principal-id)node-2", "(principal-id)node-3"],
+            "max_ingress_bytes_per_message": 4194304,
+            "features": { "http_requests": true },
+        });
+
+        let changes = record_changes(7, SUBNET_KEY, Some(&old), Some(&new));
+        let summaries: Vec<_> = changes.iter().map(|c| c.summary.as_str()).collect();
+
+        assert_eq!(
+            summaries,
+            vec![
+                "subnet aaaaa-aa config changed: max_ingress_bytes_per_message",
+                "node node-3 added to subnet aaaaa-aa",
+                "node node-1 removed from subnet aaaaa-aa",
+            ]
+        );
+        assert_eq!(
+            changes[0].deltas,
+            vec![FieldDelta {
+                field: "max_ingress_bytes_per_message".to_string(),
+                old: json!(2097152),
+                new: json!(4194304),
+            }]
+        );
+        assert!(changes
+            .iter()
+            .all(|c| c.version == 7 && c.key == SUBNET_KEY));
+    }
+
+    #[test]
+    fn nested_fields_are_reported_by_path() {
+        let old = json!({ "config": { "a": 1, "b": { "c": 2 } } });
+        let new = json!({ "config": { "a": 1, "b": { "c": 3, "d": 4 } } });
+
+        let deltas = record_deltas(Some(old), Some(new));
+
+        assert_eq!(
+            deltas
+                .iter()
+                .map(|d| (d.field.as_str(), d.old.clone(), d.new.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("config.b.c", json!(2), json!(3)),
+                ("config.b.d", Value::Null, json!(4)),
+            ]
+        );
+    }
+
+    #[test]
+    fn routing_table_changes_report_moved_ranges() {
+        let old = routing_table(&[("c-1", "c-2", "subnet-a"), ("c-3", "c-4", "subnet-a")]);
+        let new = routing_table(&[("c-1", "c-2", "subnet-a"), ("c-3", "c-4", "subnet-b")]);
+
+        let changes = record_changes(3, "routing_table", Some(&old), Some(&new));
+
+        assert_eq!(changes.len(), 1);
+        assert_eq!(
+            changes[0].summary,
+            "canister range c-3:c-4 moved from subnet subnet-a to subnet subnet-b"
+        );
+        assert_eq!(changes[0].deltas[0].new, json!("subnet-b"));
+    }
+
+    #[test]
+    fn unchanged_records_are_not_reported() {
+        let value = json!({ "a": 1 });
+
+        assert!(record_changes(1, "node_record_x", Some(&value), Some(&value)).is_empty());
+        assert!(record_changes(1, SUBNET_KEY, Some(&value), Some(&value)).is_empty());
+    }
+}
@@ -1,4 +1,5 @@
 pub mod args;
+mod change_log;
 mod diff;
 mod json;
 mod normalization;
@@ -74,6 +75,15 @@ pub fn execute_command(cmd: Command) -> Result<Value> {
             let (normalized_snapshot, _) = normalization::normalize(snapshot.0);
             projection::project(normalized_snapshot.0, projection)
         }
+        Command::Log {
+            source,
+            from,
+            to,
+            projection,
+        } => {
+            let cl = source::get_changelog(source)?;
+            change_log::changelog_to_log(cl, from.get(), to, &projection)?
+        }
         Command::CanisterToProto {
             start_version,
             latest_version,
@@ -6,7 +6,7 @@ use std::{collections::BTreeMap, convert::TryFrom, ops::Range, str::FromStr};
 
 const LARGE_ARRAY_MIN_SIZE: usize = 33;
 const BIN_DATA_SHA256: &str = "(binary-data|sha256)";
-const PRINCIPAL_ID: &str = "(principal-id)";
+pub(crate) const PRINCIPAL_ID: &str = "(principal-id)";
 const BIN_DATA: &str = "(binary-data)";
 
 #[derive(Clone, Eq, PartialEq, Debug)]
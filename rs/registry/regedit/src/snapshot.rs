@@ -1,5 +1,6 @@
 use crate::{args::VersionSpec, json, protobuf::raw_data_to_value, source::Changelog};
 use anyhow::{bail, Result};
+use ic_registry_client::client::RegistryVersion;
 use serde_json::Value;
 use std::collections::BTreeMap;
 use thiserror::Error;
@@ -12,19 +13,7 @@ pub struct Snapshot(pub Value);
 
 pub fn changelog_to_snapshot(changelog: Changelog, version: VersionSpec) -> Result<Snapshot> {
     let (mut changelog, v) = changelog;
-    let bound = match version {
-        VersionSpec::RelativeToLatest(r) => {
-            if r > v.get() {
-                bail!(SnapshotCreationError::RelativeVersionTooOld {
-                    latest_version: v.get(),
-                    relative_version: -(r as i64)
-                });
-            } else {
-                v.get() - r
-            }
-        }
-        VersionSpec::Absolute(v) => v.get(),
-    };
+    let bound = resolve_version(version, v)?;
 
     changelog.retain(|x| x.version.get() <= bound);
     changelog.sort_by_key(|x| x.version);
@@ -53,6 +42,23 @@ pub fn changelog_to_snapshot(changelog: Changelog, version: VersionSpec) -> Resu
     Ok(Snapshot(json_val))
 }
 
+/// Returns the absolute registry version specified by `version`, given the latest version
+/// `latest_version` of the registry.
+pub fn resolve_version(version: VersionSpec, latest_version: RegistryVersion) -> Result<u64> {
+    match version {
+        VersionSpec::RelativeToLatest(r) => {
+            if r > latest_version.get() {
+                bail!(SnapshotCreationError::RelativeVersionTooOld {
+                    latest_version: latest_version.get(),
+                    relative_version: -(r as i64)
+                });
+            }
+            Ok(latest_version.get() - r)
+        }
+        VersionSpec::Absolute(v) => Ok(v.get()),
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum SnapshotCreationError {
     #[error(
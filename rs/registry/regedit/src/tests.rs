@@ -111,6 +111,61 @@ fn adding_deleting_values_shows_up_in_diff() {
     assert_eq!(expected_snapshot.0, final_snapshot);
 }
 
+#[test]
+fn log_shows_field_deltas_of_changed_subnet_record() {
+    let (_guard, ic_prep_dir) = run_ic_prep();
+    let registry_spec = local_store_latest_snapshot(ic_prep_dir.registry_local_store_path());
+
+    let mut snapshot = execute_command(Command::Snapshot {
+        registry_spec,
+        projection: universal_projection(),
+    })
+    .unwrap();
+    let subnet_key = snapshot
+        .as_object()
+        .unwrap()
+        .keys()
+        .find(|k| k.starts_with("subnet_record_"))
+        .unwrap()
+        .clone();
+    let subnet_record = snapshot.get_mut(&subnet_key).unwrap();
+    let old_value = subnet_record["max_ingress_bytes_per_message"].clone();
+    let new_value = serde_json::to_value(old_value.as_u64().unwrap() + 1).unwrap();
+    subnet_record["max_ingress_bytes_per_message"] = new_value.clone();
+
+    execute_command(Command::ApplyUpdate {
+        local_store_path: ic_prep_dir.registry_local_store_path(),
+        snapshot,
+        amend: false,
+    })
+    .unwrap();
+
+    let log = execute_command(Command::Log {
+        source: SourceSpec::LocalStore(ic_prep_dir.registry_local_store_path()),
+        from: 1.into(),
+        to: VersionSpec::RelativeToLatest(0),
+        projection: vec![subnet_key.clone()],
+    })
+    .unwrap();
+
+    assert_eq!(log["from"], 1);
+    assert_eq!(log["to"], 2);
+    let changes = log["changes"].as_object().unwrap();
+    assert_eq!(changes.keys().collect::<Vec<_>>(), vec!["subnet"]);
+    let subnet_changes = changes["subnet"].as_array().unwrap();
+    assert_eq!(subnet_changes.len(), 1);
+    assert_eq!(subnet_changes[0]["version"], 2);
+    assert_eq!(subnet_changes[0]["key"], subnet_key.as_str());
+    assert_eq!(
+        subnet_changes[0]["deltas"],
+        serde_json::json!([{
+            "field": "max_ingress_bytes_per_message",
+            "old": old_value,
+            "new": new_value,
+        }])
+    );
+}
+
 pub fn local_store_latest_snapshot(path: PathBuf) -> RegistrySpec {
     let source = SourceSpec::LocalStore(path);
     let version = VersionSpec::RelativeToLatest(0);
@@ -40,6 +40,10 @@ pub struct ReplayToolArgs {
     #[clap(long)]
     /// The replay will stop at this height and make a checkpoint.
     pub replay_until_height: Option<u64>,
+
+    /// Write a trace of every replayed height to `<TRACE>/<height>.json`.
+    #[clap(long)]
+    pub trace: Option<PathBuf>,
 }
 
 #[derive(Clone, Subcommand)]
@@ -64,6 +68,10 @@ pub enum SubCommand {
     /// Restore from the backup.
     RestoreFromBackup(RestoreFromBackupCmd),
 
+    /// Replay a block range from the backup with the Wasm module of a canister
+    /// replaced and report how the execution differs from the original one.
+    WhatIf(WhatIfCmd),
+
     /// The replay will add a test Neuron to the Governance canister
     /// and the corresponding account in the ledger.
     WithNeuronForTests(WithNeuronCmd),
@@ -117,6 +125,26 @@ pub struct RestoreFromBackupCmd {
     pub start_height: u64,
 }
 
+#[derive(Clone, Parser)]
+pub struct WhatIfCmd {
+    /// Registry local store path
+    pub registry_local_store_path: PathBuf,
+    /// Backup spool path
+    pub backup_spool_path: PathBuf,
+    /// The replica version to be restored
+    pub replica_version: String,
+    /// Height of the checkpoint from which the replay should happen
+    pub start_height: u64,
+    /// Height up to which blocks are replayed
+    pub end_height: u64,
+    /// The canister whose Wasm module is replaced
+    #[clap(long)]
+    pub canister_id: PrincipalId,
+    /// Path to the Wasm module to execute instead of the installed one
+    #[clap(long)]
+    pub wasm: PathBuf,
+}
+
 #[derive(Clone, Parser)]
 pub struct RestoreFromBackup2Cmd {
     /// Registry local store path
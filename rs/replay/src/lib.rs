@@ -33,7 +33,9 @@ pub mod ingress;
 mod mocks;
 pub mod player;
 mod registry_helper;
+pub mod trace;
 mod validator;
+mod what_if;
 
 /// Replays the past blocks and creates a checkpoint of the latest state.
 /// # An example of how to set the arguments
@@ -53,6 +55,7 @@ mod validator;
 ///     config: Some(PathBuf::from("/path/to/ic.json5")),
 ///     canister_caller_id: None,
 ///     replay_until_height: None,
+///     trace: None,
 ///     data_root: None,
 ///     subcmd: Some(SubCommand::RestoreFromBackup(RestoreFromBackupCmd {
 ///         registry_local_store_path: PathBuf::from("/path/to/ic_registry_local_store"),
@@ -99,6 +102,17 @@ pub fn replay(args: ReplayToolArgs) -> ReplayResult {
             .0;
 
         let target_height = args.replay_until_height;
+        let trace_dir = args.trace;
+        if let Some(trace_dir) = &trace_dir {
+            std::fs::create_dir_all(trace_dir).unwrap_or_else(|err| {
+                println!(
+                    "Failed to create trace directory {}: {}",
+                    trace_dir.display(),
+                    err
+                );
+                std::process::exit(1);
+            });
+        }
         if let Some(h) = target_height {
             let question = format!("The checkpoint created at height {} ", h)
                 + "cannot be used for deterministic state computation if it is not a CUP height.\n"
@@ -120,11 +134,18 @@ pub fn replay(args: ReplayToolArgs) -> ReplayResult {
                 subnet_id,
                 cmd.start_height,
             )
-            .with_replay_target_height(target_height);
+            .with_replay_target_height(target_height)
+            .with_trace_dir(trace_dir);
             *res_clone.borrow_mut() = player.restore(cmd.start_height + 1);
             return;
         }
 
+        if let Some(SubCommand::WhatIf(cmd)) = subcmd {
+            let _enter_guard = rt.enter();
+            *res_clone.borrow_mut() = what_if::what_if(cfg, subnet_id, cmd);
+            return;
+        }
+
         {
             let _enter_guard = rt.enter();
             let player = match (subcmd.as_ref(), target_height) {
@@ -133,9 +154,9 @@ pub fn replay(args: ReplayToolArgs) -> ReplayResult {
                     "Target height cannot be used with any sub-command in subnet-recovery mode."
                 );
                 }
-                (_, target_height) => {
-                    Player::new(cfg, subnet_id).with_replay_target_height(target_height)
-                }
+                (_, target_height) => Player::new(cfg, subnet_id)
+                    .with_replay_target_height(target_height)
+                    .with_trace_dir(trace_dir),
             };
 
             if let Some(SubCommand::GetRecoveryCup(cmd)) = subcmd {
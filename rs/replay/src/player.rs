@@ -2,6 +2,7 @@ use crate::{
     backup,
     backup::{cup_file_name, rename_file},
     ingress::IngressWithPrinter,
+    trace,
     validator::{InvalidArtifact, ReplayValidator},
 };
 use ic_artifact_pool::{
@@ -48,7 +49,7 @@ use ic_registry_transport::{
     deserialize_get_value_response, serialize_get_changes_since_request,
     serialize_get_value_request,
 };
-use ic_state_manager::StateManagerImpl;
+use ic_state_manager::{tree_hash::hash_state, StateManagerImpl};
 use ic_types::{
     batch::{Batch, BatchMessages, BlockmakerMetrics},
     consensus::{
@@ -132,6 +133,10 @@ pub struct Player {
     // The target height until which the state will be replayed.
     // None means finalized height.
     replay_target_height: Option<u64>,
+    // The directory to write execution traces to, if any.
+    trace_dir: Option<PathBuf>,
+    // Whether the state hashes are compared with the ones in the CUPs.
+    verify_state_hashes: bool,
     runtime: Runtime,
 }
 
@@ -381,6 +386,8 @@ impl Player {
             _async_log_guard,
             tmp_dir: None,
             replay_target_height: None,
+            trace_dir: None,
+            verify_state_hashes: true,
             runtime,
         }
     }
@@ -391,6 +398,20 @@ impl Player {
         self
     }
 
+    /// Set the directory to write a trace of every replayed height to. See
+    /// [`crate::trace`] for the contents of the traces.
+    pub fn with_trace_dir(mut self, trace_dir: Option<PathBuf>) -> Self {
+        self.trace_dir = trace_dir;
+        self
+    }
+
+    /// Do not compare the state hashes with the ones in the CUPs, e.g. because
+    /// the initial state was modified on purpose.
+    pub fn without_state_hash_verification(mut self) -> Self {
+        self.verify_state_hashes = false;
+        self
+    }
+
     /// In case a consensus pool was supplied, replay past finalized but
     /// un-executed blocks by delivering ingress messages for execution,
     /// and make a full checkpoint of the latest state when they all finish.
@@ -719,9 +740,79 @@ impl Player {
             last_batch_height - expected_batch_height.decrement()
         );
         println!("Delivered batches up to the height {}", last_batch_height);
+        if let Some(trace_dir) = &self.trace_dir {
+            self.write_traces(
+                pool,
+                expected_batch_height,
+                last_batch_height,
+                replay_target_height,
+                trace_dir,
+            );
+        }
         last_batch_height
     }
 
+    /// Writes the traces of the heights `first..=last` to `trace_dir`.
+    ///
+    /// The state manager keeps all states replayed in memory until
+    /// `remove_states_below` is called, so the traces are computed from the
+    /// states of consecutive heights once all batches have been executed.
+    fn write_traces(
+        &self,
+        pool: &PoolReader<'_>,
+        first: Height,
+        last: Height,
+        replay_target_height: Option<Height>,
+        trace_dir: &Path,
+    ) {
+        if first == Height::from(0) || first > last {
+            return;
+        }
+        while self.state_manager.latest_state_height() < last {
+            std::thread::sleep(WAIT_DURATION);
+        }
+        let get_state = |height| {
+            self.state_manager
+                .get_state_at(height)
+                .unwrap_or_else(|err| panic!("Failed to get state at height {}: {:?}", height, err))
+                .take()
+        };
+
+        let mut before = get_state(first.decrement());
+        let mut tree_before = hash_state(&before);
+        let mut height = first;
+        while height <= last {
+            let after = get_state(height);
+            let tree_after = hash_state(&after);
+            let block = pool.get_finalized_block(height);
+            // Checkpoints are made at summary heights and at the target height.
+            // The page deltas of their states may have been dropped already.
+            let is_checkpoint = block
+                .as_ref()
+                .map_or(true, |block| block.payload.is_summary())
+                || Some(height) == replay_target_height;
+            let trace = trace::trace_height(
+                height,
+                block.as_ref(),
+                &before,
+                &tree_before,
+                &after,
+                &tree_after,
+                is_checkpoint,
+            );
+            trace::write_trace(trace_dir, &trace).unwrap_or_else(|err| panic!("{}", err));
+            before = after;
+            tree_before = tree_after;
+            height = height.increment();
+        }
+        println!(
+            "Wrote traces of heights {} to {} to {}",
+            first,
+            last,
+            trace_dir.display()
+        );
+    }
+
     fn deliver_extra_batch<F: FnMut(&Player, Time) -> Vec<IngressWithPrinter>>(
         &self,
         message_routing: &dyn MessageRouting,
@@ -1197,9 +1288,10 @@ impl Player {
         }
 
         // Verify state hash against the state hash in the CUP
-        if get_state_hash(&*self.state_manager, last_cup.height())
-            .expect("No state hash at a current CUP height found")
-            != last_cup.content.state_hash
+        if self.verify_state_hashes
+            && get_state_hash(&*self.state_manager, last_cup.height())
+                .expect("No state hash at a current CUP height found")
+                != last_cup.content.state_hash
         {
             println!(
                 "The state hash of the CUP at height {:?} differs from the local state's hash",
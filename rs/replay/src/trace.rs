@@ -0,0 +1,430 @@
+//! Per-height execution traces written in `--trace` mode.
+//!
+//! In trace mode, the player delivers one batch at a time and, once the state
+//! at the height of the batch is committed, writes `<height>.json` to the trace
+//! directory. The trace of a height describes the batch in terms of the states
+//! before and after its execution:
+//!
+//! * the ingress messages of the block and their status after execution;
+//! * the number of messages sent to and inducted from each remote subnet;
+//! * every canister that executed messages or whose cycles balance, memory,
+//!   module or certified data changed;
+//! * the changes to the certified state tree as computed by
+//!   [`ic_state_manager::tree_diff`].
+//!
+//! The replicated state does not record the number of instructions executed by
+//! a canister, so the cycles charged for instructions are reported instead.
+
+use ic_crypto_sha2::Sha256;
+use ic_crypto_tree_hash::HashTree;
+use ic_replicated_state::{
+    canister_state::{
+        execution_state::Memory, system_state::CyclesUseCase, WASM_PAGE_SIZE_IN_BYTES,
+    },
+    page_map::PAGE_SIZE,
+    CanisterState, PageIndex, ReplicatedState,
+};
+use ic_state_manager::tree_diff::{diff, PrettyPrintedChanges};
+use ic_types::{
+    consensus::Block,
+    ingress::{IngressState, IngressStatus, WasmResult},
+    CanisterId, Height,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ops::Range,
+    path::Path,
+};
+
+/// The trace of the execution of a single batch.
+#[derive(Deserialize, Serialize)]
+pub struct HeightTrace {
+    pub height: u64,
+    pub batch_time_nanos: u64,
+    pub ingress: Vec<IngressTrace>,
+    pub xnet: Vec<StreamTrace>,
+    pub canisters: Vec<CanisterTrace>,
+    /// Changes to the certified state tree, one path per line.
+    pub state_tree_changes: Vec<String>,
+}
+
+/// An ingress message of the block and its status after execution.
+#[derive(Deserialize, Serialize)]
+pub struct IngressTrace {
+    pub message_id: String,
+    pub sender: String,
+    pub receiver: String,
+    pub method_name: String,
+    pub status: String,
+    /// The reply (hex-encoded) or the reject message, if the message completed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<String>,
+}
+
+/// The messages exchanged with a remote subnet.
+#[derive(Deserialize, Serialize)]
+pub struct StreamTrace {
+    pub subnet_id: String,
+    /// The number of messages from the remote subnet that were inducted.
+    pub inducted: u64,
+    /// The number of messages routed into the stream to the remote subnet.
+    pub sent: u64,
+}
+
+/// A value that changed.
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct Change<T> {
+    pub before: T,
+    pub after: T,
+}
+
+impl<T: PartialEq> Change<T> {
+    fn new(before: T, after: T) -> Option<Self> {
+        (before != after).then_some(Self { before, after })
+    }
+}
+
+/// The changes to a single canister.
+#[derive(Default, Deserialize, Serialize)]
+pub struct CanisterTrace {
+    pub canister_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    /// The number of messages executed.
+    pub executed: u64,
+    /// The cycles charged for executed instructions.
+    pub instruction_cycles: u128,
+    /// The cycles consumed by use case.
+    pub consumed_cycles: BTreeMap<String, u128>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cycles_balance: Option<Change<u128>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub module_hash: Option<Change<Option<String>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wasm_memory_size: Option<Change<u64>>,
+    /// The ranges of Wasm memory pages whose contents changed.
+    pub wasm_memory_pages: Vec<Range<u64>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stable_memory_size: Option<Change<u64>>,
+    /// The ranges of stable memory pages whose contents changed.
+    pub stable_memory_pages: Vec<Range<u64>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub certified_data: Option<Change<String>>,
+    /// The SHA-256 of the new contents of all changed pages, which tells apart
+    /// executions that change the same pages differently.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_digest: Option<String>,
+}
+
+impl CanisterTrace {
+    fn is_empty(&self) -> bool {
+        self.status.is_none()
+            && self.executed == 0
+            && self.consumed_cycles.is_empty()
+            && self.cycles_balance.is_none()
+            && self.module_hash.is_none()
+            && self.wasm_memory_size.is_none()
+            && self.wasm_memory_pages.is_empty()
+            && self.stable_memory_size.is_none()
+            && self.stable_memory_pages.is_empty()
+            && self.certified_data.is_none()
+    }
+}
+
+/// Computes the trace of the execution of `block` from the state `before`
+/// with hash tree `tree_before` to the state `after` with hash tree
+/// `tree_after`.
+///
+/// `after_is_checkpoint` must be set if a checkpoint was made at `height`, in
+/// which case the page deltas of `after` may have been dropped and all pages
+/// of the canister memories are compared.
+pub fn trace_height(
+    height: Height,
+    block: Option<&Block>,
+    before: &ReplicatedState,
+    tree_before: &HashTree,
+    after: &ReplicatedState,
+    tree_after: &HashTree,
+    after_is_checkpoint: bool,
+) -> HeightTrace {
+    HeightTrace {
+        height: height.get(),
+        batch_time_nanos: after.time().as_nanos_since_unix_epoch(),
+        ingress: block
+            .map(|block| trace_ingress(block, after))
+            .unwrap_or_default(),
+        xnet: trace_streams(before, after),
+        canisters: trace_canisters(before, after, after_is_checkpoint),
+        state_tree_changes: PrettyPrintedChanges(&diff(tree_before, tree_after))
+            .to_string()
+            .lines()
+            .map(String::from)
+            .collect(),
+    }
+}
+
+/// Writes `trace` to `<trace_dir>/<height>.json`.
+pub fn write_trace(trace_dir: &Path, trace: &HeightTrace) -> Result<(), String> {
+    let path = trace_dir.join(format!("{}.json", trace.height));
+    let json = serde_json::to_string_pretty(trace)
+        .map_err(|e| format!("failed to serialize trace: {}", e))?;
+    std::fs::write(&path, json).map_err(|e| format!("failed to write {}: {}", path.display(), e))
+}
+
+fn trace_ingress(block: &Block, after: &ReplicatedState) -> Vec<IngressTrace> {
+    if block.payload.is_summary() {
+        return Vec::new();
+    }
+    let payload = &block.payload.as_ref().as_data().batch.ingress;
+    (0..payload.message_count())
+        .filter_map(|index| payload.get(index).ok())
+        .map(|(_, ingress)| {
+            let (status, result) = match after.metadata.ingress_history.get(&ingress.id()) {
+                Some(IngressStatus::Known { state, .. }) => {
+                    let result = match state {
+                        IngressState::Completed(WasmResult::Reply(bytes)) => {
+                            Some(hex::encode(bytes))
+                        }
+                        IngressState::Completed(WasmResult::Reject(message)) => {
+                            Some(message.clone())
+                        }
+                        IngressState::Failed(err) => Some(err.to_string()),
+                        IngressState::Received | IngressState::Processing | IngressState::Done => {
+                            None
+                        }
+                    };
+                    (state.as_str(), result)
+                }
+                Some(IngressStatus::Unknown) | None => ("unknown", None),
+            };
+            IngressTrace {
+                message_id: ingress.id().to_string(),
+                sender: ingress.sender().to_string(),
+                receiver: ingress.canister_id().to_string(),
+                method_name: ingress.method_name(),
+                status: status.to_string(),
+                result,
+            }
+        })
+        .collect()
+}
+
+fn trace_streams(before: &ReplicatedState, after: &ReplicatedState) -> Vec<StreamTrace> {
+    let streams_before = before.metadata.streams().streams();
+    let streams_after = after.metadata.streams().streams();
+    streams_after
+        .iter()
+        .filter_map(|(subnet_id, stream)| {
+            let (signals_end, messages_end) = streams_before
+                .get(subnet_id)
+                .map(|s| (s.signals_end().get(), s.messages_end().get()))
+                .unwrap_or_default();
+            let trace = StreamTrace {
+                subnet_id: subnet_id.to_string(),
+                inducted: stream.signals_end().get().saturating_sub(signals_end),
+                sent: stream.messages_end().get().saturating_sub(messages_end),
+            };
+            (trace.inducted > 0 || trace.sent > 0).then_some(trace)
+        })
+        .collect()
+}
+
+fn trace_canisters(
+    before: &ReplicatedState,
+    after: &ReplicatedState,
+    compare_all_pages: bool,
+) -> Vec<CanisterTrace> {
+    let canister_ids: BTreeSet<&CanisterId> = before
+        .canister_states
+        .keys()
+        .chain(after.canister_states.keys())
+        .collect();
+    canister_ids
+        .into_iter()
+        .map(|canister_id| {
+            trace_canister(
+                canister_id,
+                before.canister_states.get(canister_id),
+                after.canister_states.get(canister_id),
+                compare_all_pages,
+            )
+        })
+        .filter(|trace| !trace.is_empty())
+        .collect()
+}
+
+fn trace_canister(
+    canister_id: &CanisterId,
+    before: Option<&CanisterState>,
+    after: Option<&CanisterState>,
+    compare_all_pages: bool,
+) -> CanisterTrace {
+    let mut trace = CanisterTrace {
+        canister_id: canister_id.to_string(),
+        ..Default::default()
+    };
+    let (before, after) = match (before, after) {
+        (Some(before), Some(after)) => (before, after),
+        (None, Some(_)) => {
+            trace.status = Some("created".to_string());
+            return trace;
+        }
+        (Some(_), None) => {
+            trace.status = Some("deleted".to_string());
+            return trace;
+        }
+        (None, None) => return trace,
+    };
+
+    let metrics_before = &before.system_state.canister_metrics;
+    let metrics_after = &after.system_state.canister_metrics;
+    trace.executed = metrics_after
+        .executed
+        .saturating_sub(metrics_before.executed);
+    for (use_case, consumed) in metrics_after.get_consumed_cycles_by_use_cases() {
+        let consumed_before = metrics_before
+            .get_consumed_cycles_by_use_cases()
+            .get(use_case)
+            .map(|c| c.get())
+            .unwrap_or_default();
+        let delta = consumed.get().saturating_sub(consumed_before);
+        if delta > 0 {
+            trace
+                .consumed_cycles
+                .insert(use_case.as_str().to_string(), delta);
+        }
+        if *use_case == CyclesUseCase::Instructions {
+            trace.instruction_cycles = delta;
+        }
+    }
+    trace.cycles_balance = Change::new(
+        before.system_state.balance().get(),
+        after.system_state.balance().get(),
+    );
+    trace.certified_data = Change::new(
+        hex::encode(&before.system_state.certified_data),
+        hex::encode(&after.system_state.certified_data),
+    );
+
+    let module_hash = |canister: &CanisterState| {
+        canister
+            .execution_state
+            .as_ref()
+            .map(|es| hex::encode(es.wasm_binary.binary.module_hash()))
+    };
+    trace.module_hash = Change::new(module_hash(before), module_hash(after));
+
+    if let (Some(es_before), Some(es_after)) = (&before.execution_state, &after.execution_state) {
+        trace.wasm_memory_size = Change::new(
+            memory_size(&es_before.wasm_memory),
+            memory_size(&es_after.wasm_memory),
+        );
+        trace.wasm_memory_pages = changed_pages(
+            &es_before.wasm_memory,
+            &es_after.wasm_memory,
+            compare_all_pages,
+        );
+        trace.stable_memory_size = Change::new(
+            memory_size(&es_before.stable_memory),
+            memory_size(&es_after.stable_memory),
+        );
+        trace.stable_memory_pages = changed_pages(
+            &es_before.stable_memory,
+            &es_after.stable_memory,
+            compare_all_pages,
+        );
+        if !trace.wasm_memory_pages.is_empty() || !trace.stable_memory_pages.is_empty() {
+            let mut hasher = Sha256::new();
+            hash_pages(&mut hasher, &es_after.wasm_memory, &trace.wasm_memory_pages);
+            hash_pages(
+                &mut hasher,
+                &es_after.stable_memory,
+                &trace.stable_memory_pages,
+            );
+            trace.memory_digest = Some(hex::encode(hasher.finish()));
+        }
+    }
+    trace
+}
+
+fn hash_pages(hasher: &mut Sha256, memory: &Memory, ranges: &[Range<u64>]) {
+    for index in ranges.iter().flat_map(|range| range.clone()) {
+        hasher.write(&index.to_le_bytes());
+        hasher.write(memory.page_map.get_page(PageIndex::new(index)));
+    }
+}
+
+fn memory_size(memory: &Memory) -> u64 {
+    (memory.size.get() * WASM_PAGE_SIZE_IN_BYTES) as u64
+}
+
+/// Returns the ranges of pages whose contents differ between `before` and
+/// `after`.
+///
+/// Unless `compare_all_pages` is set, only the pages in the delta of either
+/// page map are compared. This is sufficient as long as `after` is either
+/// based on the same checkpoint as `before` or on `before` itself.
+fn changed_pages(before: &Memory, after: &Memory, compare_all_pages: bool) -> Vec<Range<u64>> {
+    let indices: BTreeSet<u64> = if compare_all_pages {
+        let num_pages = memory_size(before).max(memory_size(after)) / PAGE_SIZE as u64;
+        (0..num_pages).collect()
+    } else {
+        before
+            .page_map
+            .get_page_delta_indices()
+            .into_iter()
+            .chain(after.page_map.get_page_delta_indices())
+            .map(|index| index.get())
+            .collect()
+    };
+
+    let mut ranges: Vec<Range<u64>> = Vec::new();
+    for index in indices {
+        let page_index = PageIndex::new(index);
+        if before.page_map.get_page(page_index) == after.page_map.get_page(page_index) {
+            continue;
+        }
+        match ranges.last_mut() {
+            Some(range) if range.end == index => range.end = index + 1,
+            _ => ranges.push(index..index + 1),
+        }
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ic_replicated_state::{NumWasmPages, PageMap};
+
+    fn memory_with_pages(pages: &[(u64, u8)]) -> Memory {
+        let mut page_map = PageMap::new_for_testing();
+        let contents: Vec<(PageIndex, [u8; PAGE_SIZE])> = pages
+            .iter()
+            .map(|(index, byte)| (PageIndex::new(*index), [*byte; PAGE_SIZE]))
+            .collect();
+        let updates: Vec<(PageIndex, &[u8; PAGE_SIZE])> = contents
+            .iter()
+            .map(|(index, page)| (*index, page))
+            .collect();
+        page_map.update(&updates);
+        Memory::new(page_map, NumWasmPages::new(1))
+    }
+
+    #[test]
+    fn changed_pages_are_merged_into_ranges() {
+        let before = memory_with_pages(&[(0, 1), (1, 1), (2, 1), (5, 1)]);
+        let after = memory_with_pages(&[(0, 1), (1, 2), (2, 2), (3, 2), (5, 3)]);
+        assert_eq!(changed_pages(&before, &after, false), vec![1..4, 5..6]);
+        assert_eq!(changed_pages(&before, &after, true), vec![1..4, 5..6]);
+    }
+
+    #[test]
+    fn pages_missing_from_either_memory_are_compared() {
+        let before = memory_with_pages(&[(7, 1)]);
+        let after = Memory::new(PageMap::new_for_testing(), NumWasmPages::new(1));
+        assert_eq!(changed_pages(&before, &after, true), vec![7..8]);
+        assert_eq!(changed_pages(&after, &after, true), vec![]);
+    }
+}
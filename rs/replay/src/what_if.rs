@@ -0,0 +1,464 @@
+//! What-if replay of a block range with the Wasm module of a canister
+//! replaced by a patched build.
+//!
+//! The block range is replayed twice from copies of the checkpoint at the start
+//! height: once as it was originally executed and once with the module of the
+//! canister swapped in the checkpoint. Both runs write traces (see
+//! [`crate::trace`]), which are compared height by height afterwards. Every
+//! ingress message whose status or result differs is reported, as well as every
+//! canister and stream whose execution differs, together with the ingress
+//! messages addressed to the canister at that height.
+//!
+//! Since the patched run diverges from the states certified by the subnet, its
+//! state hashes are not verified against the CUPs. Only the module and its hash
+//! are replaced: the exported methods, globals and custom sections recorded in
+//! the checkpoint are kept, so the patched build must be compatible with the
+//! installed one (as a hotfix built from the same code base usually is).
+
+use crate::{
+    cmd::WhatIfCmd,
+    player::{Player, ReplayResult},
+    trace::{CanisterTrace, HeightTrace},
+};
+use ic_config::Config;
+use ic_crypto_sha2::Sha256;
+use ic_protobuf::state::canister_state_bits::v1 as pb_canister_state_bits;
+use ic_state_layout::{CompleteCheckpointLayout, StateLayout, CHECKPOINTS_DIR};
+use ic_types::{CanisterId, Height, ReplicaVersion, SubnetId};
+use prost::Message;
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+};
+
+const STATE_DIR: &str = "ic_state";
+const TRACE_DIR: &str = "trace";
+
+/// Replays the heights `cmd.start_height + 1..=cmd.end_height` with the
+/// original and the patched Wasm module and prints the differences.
+pub fn what_if(cfg: Config, subnet_id: SubnetId, cmd: &WhatIfCmd) -> ReplayResult {
+    assert!(
+        cmd.end_height > cmd.start_height,
+        "The end height must be above the start height."
+    );
+    let canister_id = CanisterId::unchecked_from_principal(cmd.canister_id);
+    let wasm = std::fs::read(&cmd.wasm)
+        .unwrap_or_else(|err| panic!("Failed to read {}: {}", cmd.wasm.display(), err));
+
+    let tmp_dir = tempfile::Builder::new()
+        .prefix("replay_what_if_")
+        .tempdir()
+        .expect("Couldn't create a temporary directory");
+    let start_height = Height::from(cmd.start_height);
+    let checkpoint_name = StateLayout::checkpoint_name(start_height);
+    let checkpoint = cfg
+        .state_manager
+        .state_root()
+        .join(CHECKPOINTS_DIR)
+        .join(&checkpoint_name);
+
+    let original = tmp_dir.path().join("original");
+    let patched = tmp_dir.path().join("patched");
+    for run in [&original, &patched] {
+        let run_checkpoint = run
+            .join(STATE_DIR)
+            .join(CHECKPOINTS_DIR)
+            .join(&checkpoint_name);
+        link_dir(&checkpoint, &run_checkpoint).unwrap_or_else(|err| {
+            panic!(
+                "Failed to copy checkpoint {} to {}: {}",
+                checkpoint.display(),
+                run_checkpoint.display(),
+                err
+            )
+        });
+    }
+    replace_wasm(
+        &patched
+            .join(STATE_DIR)
+            .join(CHECKPOINTS_DIR)
+            .join(&checkpoint_name),
+        start_height,
+        &canister_id,
+        wasm,
+    )
+    .unwrap_or_else(|err| panic!("Failed to replace the Wasm module: {}", err));
+
+    println!(
+        "Replaying heights {} to {} with the original Wasm module of {}",
+        cmd.start_height + 1,
+        cmd.end_height,
+        canister_id
+    );
+    replay_run(&cfg, subnet_id, cmd, &original, true)?;
+    println!(
+        "Replaying heights {} to {} with the Wasm module {}",
+        cmd.start_height + 1,
+        cmd.end_height,
+        cmd.wasm.display()
+    );
+    let state_params = replay_run(&cfg, subnet_id, cmd, &patched, false)?;
+
+    let mut num_differences = 0;
+    for height in cmd.start_height + 1..=cmd.end_height {
+        let (Some(original), Some(patched)) = (
+            read_trace(&original.join(TRACE_DIR), height),
+            read_trace(&patched.join(TRACE_DIR), height),
+        ) else {
+            println!("No traces from height {} on", height);
+            break;
+        };
+        let differences = compare_heights(&original, &patched);
+        if !differences.is_empty() {
+            println!("Height {}:", height);
+            for difference in &differences {
+                println!("  {}", difference);
+            }
+        }
+        num_differences += differences.len();
+    }
+    println!(
+        "Found {} differences between the original and the patched execution",
+        num_differences
+    );
+
+    Ok(state_params)
+}
+
+/// Replays the heights of `cmd` from the state in `<root>/ic_state`, writing
+/// the traces to `<root>/trace`.
+fn replay_run(
+    cfg: &Config,
+    subnet_id: SubnetId,
+    cmd: &WhatIfCmd,
+    root: &Path,
+    verify_state_hashes: bool,
+) -> ReplayResult {
+    let mut cfg = cfg.clone();
+    cfg.state_manager = ic_config::state_manager::Config::new(root.join(STATE_DIR));
+    let trace_dir = root.join(TRACE_DIR);
+    std::fs::create_dir_all(&trace_dir).expect("Couldn't create the trace directory");
+
+    let mut player = Player::new_for_backup(
+        cfg,
+        ReplicaVersion::try_from(cmd.replica_version.as_str())
+            .expect("Couldn't parse the replica version"),
+        &cmd.backup_spool_path,
+        &cmd.registry_local_store_path,
+        subnet_id,
+        cmd.start_height,
+    )
+    .with_replay_target_height(Some(cmd.end_height))
+    .with_trace_dir(Some(trace_dir));
+    if !verify_state_hashes {
+        player = player.without_state_hash_verification();
+    }
+    player.restore(cmd.start_height + 1)
+}
+
+/// Recreates the directory tree `src` at `dst` with hard links to the files of
+/// `src`. Checkpoint files are never modified, so the original checkpoint is
+/// not affected by the replays.
+fn link_dir(src: &Path, dst: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(dst)?;
+    for entry in std::fs::read_dir(src)? {
+        let entry = entry?;
+        let dst = dst.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            link_dir(&entry.path(), &dst)?;
+        } else {
+            std::fs::hard_link(entry.path(), &dst)
+                .or_else(|_| std::fs::copy(entry.path(), &dst).map(|_| ()))?;
+        }
+    }
+    Ok(())
+}
+
+/// Replaces the Wasm module of `canister_id` in the checkpoint at `root` and
+/// updates the module hash in the canister state bits accordingly.
+fn replace_wasm(
+    root: &Path,
+    height: Height,
+    canister_id: &CanisterId,
+    wasm: Vec<u8>,
+) -> Result<(), String> {
+    let layout = CompleteCheckpointLayout::new_untracked(root.to_path_buf(), height)
+        .map_err(|err| format!("failed to create checkpoint layout: {}", err))?;
+    let canister_layout = layout
+        .canister(canister_id)
+        .map_err(|err| format!("failed to open canister {}: {}", canister_id, err))?;
+
+    let mut bits: pb_canister_state_bits::CanisterStateBits = canister_layout
+        .canister()
+        .deserialize()
+        .map_err(|err| format!("failed to read canister {}: {}", canister_id, err))?;
+    bits.execution_state_bits
+        .as_mut()
+        .ok_or_else(|| format!("canister {} has no Wasm module installed", canister_id))?
+        .binary_hash = Some(Sha256::hash(&wasm).to_vec());
+
+    // The files are hard links to the original checkpoint: replace them
+    // instead of writing to them.
+    for (path, bytes) in [
+        (canister_layout.wasm().raw_path().to_path_buf(), wasm),
+        (
+            canister_layout.canister().raw_path().to_path_buf(),
+            bits.encode_to_vec(),
+        ),
+    ] {
+        std::fs::remove_file(&path)
+            .and_then(|_| std::fs::write(&path, bytes))
+            .map_err(|err| format!("failed to write {}: {}", path.display(), err))?;
+    }
+    Ok(())
+}
+
+fn read_trace(trace_dir: &Path, height: u64) -> Option<HeightTrace> {
+    let bytes = std::fs::read(trace_dir.join(format!("{}.json", height))).ok()?;
+    Some(
+        serde_json::from_slice(&bytes)
+            .unwrap_or_else(|err| panic!("Failed to parse trace of height {}: {}", height, err)),
+    )
+}
+
+/// Returns a description of every difference between the traces of the same
+/// height from the original and the patched execution.
+fn compare_heights(original: &HeightTrace, patched: &HeightTrace) -> Vec<String> {
+    let mut differences = Vec::new();
+
+    let patched_ingress: BTreeMap<_, _> = patched
+        .ingress
+        .iter()
+        .map(|ingress| (&ingress.message_id, ingress))
+        .collect();
+    for ingress in &original.ingress {
+        let Some(other) = patched_ingress.get(&ingress.message_id) else {
+            continue;
+        };
+        if (&ingress.status, &ingress.result) != (&other.status, &other.result) {
+            differences.push(format!(
+                "ingress {} ({} -> {}.{}): {} {} -> {} {}",
+                ingress.message_id,
+                ingress.sender,
+                ingress.receiver,
+                ingress.method_name,
+                ingress.status,
+                ingress.result.as_deref().unwrap_or_default(),
+                other.status,
+                other.result.as_deref().unwrap_or_default(),
+            ));
+        }
+    }
+
+    let canisters = |trace: &HeightTrace| -> BTreeMap<String, &CanisterTrace> {
+        trace
+            .canisters
+            .iter()
+            .map(|canister| (canister.canister_id.clone(), canister))
+            .collect()
+    };
+    let original_canisters = canisters(original);
+    let patched_canisters = canisters(patched);
+    let canister_ids: BTreeSet<&String> = original_canisters
+        .keys()
+        .chain(patched_canisters.keys())
+        .collect();
+    let empty = CanisterTrace::default();
+    for canister_id in canister_ids {
+        let fields = compare_canisters(
+            original_canisters.get(canister_id).unwrap_or(&&empty),
+            patched_canisters.get(canister_id).unwrap_or(&&empty),
+        );
+        if fields.is_empty() {
+            continue;
+        }
+        let messages: Vec<&str> = original
+            .ingress
+            .iter()
+            .filter(|ingress| &ingress.receiver == canister_id)
+            .map(|ingress| ingress.message_id.as_str())
+            .collect();
+        differences.push(format!(
+            "canister {}: {} (ingress: [{}])",
+            canister_id,
+            fields.join(", "),
+            messages.join(", ")
+        ));
+    }
+
+    let streams = |trace: &HeightTrace| -> BTreeMap<String, (u64, u64)> {
+        trace
+            .xnet
+            .iter()
+            .map(|stream| (stream.subnet_id.clone(), (stream.inducted, stream.sent)))
+            .collect()
+    };
+    let original_streams = streams(original);
+    let patched_streams = streams(patched);
+    let subnet_ids: BTreeSet<&String> = original_streams
+        .keys()
+        .chain(patched_streams.keys())
+        .collect();
+    for subnet_id in subnet_ids {
+        let (inducted, sent) = original_streams.get(subnet_id).copied().unwrap_or_default();
+        let (other_inducted, other_sent) =
+            patched_streams.get(subnet_id).copied().unwrap_or_default();
+        if (inducted, sent) != (other_inducted, other_sent) {
+            differences.push(format!(
+                "subnet {}: inducted {} -> {}, sent {} -> {}",
+                subnet_id, inducted, other_inducted, sent, other_sent
+            ));
+        }
+    }
+
+    differences
+}
+
+/// Returns a description of every field of the canister traces that differs.
+fn compare_canisters(original: &CanisterTrace, patched: &CanisterTrace) -> Vec<String> {
+    fn compare<T: PartialEq + Serialize>(name: &str, a: &T, b: &T, fields: &mut Vec<String>) {
+        if a != b {
+            fields.push(format!(
+                "{} {} -> {}",
+                name,
+                serde_json::to_string(a).unwrap_or_default(),
+                serde_json::to_string(b).unwrap_or_default()
+            ));
+        }
+    }
+
+    let mut fields = Vec::new();
+    compare("status", &original.status, &patched.status, &mut fields);
+    compare(
+        "executed",
+        &original.executed,
+        &patched.executed,
+        &mut fields,
+    );
+    compare(
+        "consumed_cycles",
+        &original.consumed_cycles,
+        &patched.consumed_cycles,
+        &mut fields,
+    );
+    compare(
+        "cycles_balance",
+        &original.cycles_balance.as_ref().map(|c| c.after),
+        &patched.cycles_balance.as_ref().map(|c| c.after),
+        &mut fields,
+    );
+    compare(
+        "certified_data",
+        &original.certified_data.as_ref().map(|c| &c.after),
+        &patched.certified_data.as_ref().map(|c| &c.after),
+        &mut fields,
+    );
+    compare(
+        "wasm_memory_size",
+        &original.wasm_memory_size.as_ref().map(|c| c.after),
+        &patched.wasm_memory_size.as_ref().map(|c| c.after),
+        &mut fields,
+    );
+    compare(
+        "wasm_memory_pages",
+        &original.wasm_memory_pages,
+        &patched.wasm_memory_pages,
+        &mut fields,
+    );
+    compare(
+        "stable_memory_size",
+        &original.stable_memory_size.as_ref().map(|c| c.after),
+        &patched.stable_memory_size.as_ref().map(|c| c.after),
+        &mut fields,
+    );
+    compare(
+        "stable_memory_pages",
+        &original.stable_memory_pages,
+        &patched.stable_memory_pages,
+        &mut fields,
+    );
+    compare(
+        "memory_digest",
+        &original.memory_digest,
+        &patched.memory_digest,
+        &mut fields,
+    );
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trace::{Change, IngressTrace};
+
+    fn ingress(status: &str, result: &str) -> IngressTrace {
+        IngressTrace {
+            message_id: "m1".to_string(),
+            sender: "user".to_string(),
+            receiver: "canister".to_string(),
+            method_name: "transfer".to_string(),
+            status: status.to_string(),
+            result: Some(result.to_string()),
+        }
+    }
+
+    fn height_trace(ingress: IngressTrace, canister: CanisterTrace) -> HeightTrace {
+        HeightTrace {
+            height: 10,
+            batch_time_nanos: 0,
+            ingress: vec![ingress],
+            xnet: vec![],
+            canisters: vec![canister],
+            state_tree_changes: vec![],
+        }
+    }
+
+    #[test]
+    fn identical_executions_have_no_differences() {
+        let canister = || CanisterTrace {
+            canister_id: "canister".to_string(),
+            executed: 1,
+            ..Default::default()
+        };
+        let original = height_trace(ingress("replied", "4449"), canister());
+        let patched = height_trace(ingress("replied", "4449"), canister());
+        assert!(compare_heights(&original, &patched).is_empty());
+    }
+
+    #[test]
+    fn differing_results_and_cycles_are_reported() {
+        let original = height_trace(
+            ingress("replied", "4449"),
+            CanisterTrace {
+                canister_id: "canister".to_string(),
+                executed: 1,
+                cycles_balance: Some(Change {
+                    before: 100,
+                    after: 90,
+                }),
+                ..Default::default()
+            },
+        );
+        let patched = height_trace(
+            ingress("rejected", "trapped"),
+            CanisterTrace {
+                canister_id: "canister".to_string(),
+                executed: 1,
+                cycles_balance: Some(Change {
+                    before: 100,
+                    after: 80,
+                }),
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            compare_heights(&original, &patched),
+            vec![
+                "ingress m1 (user -> canister.transfer): replied 4449 -> rejected trapped"
+                    .to_string(),
+                "canister canister: cycles_balance 90 -> 80 (ingress: [m1])".to_string(),
+            ]
+        );
+    }
+}
@@ -15,7 +15,10 @@ use ic_replica_tests::assert_reject;
 use ic_test_utilities::assert_utils::assert_balance_equals;
 use ic_test_utilities::universal_canister::management::CanisterUpgradeOptions;
 use ic_test_utilities::universal_canister::{call_args, management, wasm, UNIVERSAL_CANISTER_WASM};
-use ic_types::{ingress::WasmResult, CanisterId, ComputeAllocation, Cycles, NumBytes, PrincipalId};
+use ic_types::{
+    ingress::WasmResult, CanisterId, ComputeAllocation, Cycles, NumBytes, PrincipalId,
+    DEFAULT_CANISTER_LOG_BUFFER_SIZE,
+};
 use maplit::btreeset;
 use std::{collections::BTreeSet, mem::size_of, str::FromStr};
 
@@ -721,6 +724,8 @@ fn can_get_canister_information() {
                 0u128,
                 0u128,
                 Some(DEFAULT_WASM_MEMORY_LIMIT.get()),
+                DEFAULT_CANISTER_LOG_BUFFER_SIZE as u64,
+                vec![],
             )
         );
 
@@ -778,7 +783,9 @@ fn can_get_canister_information() {
                     0u128,
                     0u128,
                     0u128,
-                    Some(DEFAULT_WASM_MEMORY_LIMIT.get())
+                    Some(DEFAULT_WASM_MEMORY_LIMIT.get()),
+                    DEFAULT_CANISTER_LOG_BUFFER_SIZE as u64,
+                    vec![],
                 ),
                 CanisterStatusResultV2::decode(&res).unwrap(),
                 2 * BALANCE_EPSILON,
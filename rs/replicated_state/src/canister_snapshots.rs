@@ -1,8 +1,10 @@
 use crate::{
     canister_state::execution_state::{Global, Memory},
-    canister_state::system_state::wasm_chunk_store::WasmChunkStore,
+    canister_state::system_state::wasm_chunk_store::{WasmChunkHash, WasmChunkStore},
+    page_map::{Buffer, PageAllocatorFileDescriptor},
     CanisterState, NumWasmPages, PageMap,
 };
+use ic_protobuf::{proxy::ProxyDecodeError, state::canister_snapshot_bits::v1 as pb};
 use ic_sys::PAGE_SIZE;
 use ic_types::{CanisterId, NumBytes, SnapshotId, Time};
 use ic_validate_eq::ValidateEq;
@@ -69,6 +71,27 @@ impl CanisterSnapshots {
         snapshot_id
     }
 
+    /// Adds a new snapshot, built from uploaded data rather than taken from
+    /// the canister, in the collection.
+    ///
+    /// Additionally, adds a new item to the `unflushed_changes`. Unlike for
+    /// `push`, this is an `Upload` rather than a `Backup`, as there are no
+    /// canister files to copy into the snapshot.
+    pub fn push_uploaded(
+        &mut self,
+        snapshot_id: SnapshotId,
+        snapshot: Arc<CanisterSnapshot>,
+    ) -> SnapshotId {
+        let canister_id = snapshot.canister_id();
+        self.unflushed_changes
+            .push(SnapshotOperation::Upload(canister_id, snapshot_id));
+        self.memory_usage += snapshot.size();
+        self.snapshots.insert(snapshot_id, snapshot);
+        let snapshot_ids = self.snapshot_ids.entry(canister_id).or_default();
+        snapshot_ids.insert(snapshot_id);
+        snapshot_id
+    }
+
     /// Returns a reference of the canister snapshot identified by `snapshot_id`.
     pub fn get(&self, snapshot_id: SnapshotId) -> Option<&Arc<CanisterSnapshot>> {
         self.snapshots.get(&snapshot_id)
@@ -190,6 +213,16 @@ impl CanisterSnapshots {
             .push(SnapshotOperation::Restore(canister_id, snapshot_id))
     }
 
+    /// Adds a new upload data operation in the unflushed changes, so that the
+    /// data uploaded into the snapshot is flushed. Consecutive uploads into the
+    /// same snapshot are recorded once.
+    pub fn add_upload_data_operation(&mut self, canister_id: CanisterId, snapshot_id: SnapshotId) {
+        let op = SnapshotOperation::UploadData(canister_id, snapshot_id);
+        if self.unflushed_changes.last() != Some(&op) {
+            self.unflushed_changes.push(op);
+        }
+    }
+
     /// Returns true if snapshot ID can be found in the collection.
     pub fn contains(&self, snapshot_id: &SnapshotId) -> bool {
         self.snapshots.contains_key(snapshot_id)
@@ -240,6 +273,18 @@ impl CanisterSnapshots {
         } = self;
     }
 
+    /// Builds the Wasm modules of all snapshots that are being uploaded.
+    ///
+    /// Called before writing the snapshots to a checkpoint, so that the
+    /// in-memory state matches the one loaded from the checkpoint.
+    pub fn build_uploaded_wasm_modules(&mut self) {
+        for snapshot in self.snapshots.values_mut() {
+            if snapshot.is_uploading_wasm_module() {
+                Arc::make_mut(snapshot).build_uploaded_wasm_module();
+            }
+        }
+    }
+
     /// Returns the amount of memory taken by all canister snapshots on
     /// this subnet.
     pub fn memory_taken(&self) -> NumBytes {
@@ -302,9 +347,62 @@ pub struct ExecutionStateSnapshot {
     pub wasm_memory: PageMemory,
 }
 
+/// The Wasm module of a snapshot while it is being uploaded.
+///
+/// Uploaded slices are written in place into a `PageMap`, which only allocates
+/// the pages that are written and is cheap to copy. The module is built, and
+/// hashed, once when it is needed, i.e. when the snapshot is loaded or written
+/// to a checkpoint.
+#[derive(Clone, Eq, PartialEq, Debug)]
+struct WasmModuleUpload {
+    /// The declared size of the module in bytes.
+    size: usize,
+    page_map: PageMap,
+}
+
+/// How a canister snapshot was created.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum SnapshotSource {
+    /// Taken from the canister by `take_canister_snapshot`.
+    TakenFromCanister,
+    /// Created by `upload_canister_snapshot_metadata`. Data can be uploaded
+    /// into the snapshot until it is loaded for the first time.
+    MetadataUpload,
+    /// Created by `upload_canister_snapshot_metadata` and loaded since, so no
+    /// more data can be uploaded into the snapshot.
+    UploadFinalized,
+}
+
+impl From<SnapshotSource> for pb::SnapshotSource {
+    fn from(item: SnapshotSource) -> Self {
+        match item {
+            SnapshotSource::TakenFromCanister => pb::SnapshotSource::TakenFromCanister,
+            SnapshotSource::MetadataUpload => pb::SnapshotSource::MetadataUpload,
+            SnapshotSource::UploadFinalized => pb::SnapshotSource::UploadFinalized,
+        }
+    }
+}
+
+impl TryFrom<pb::SnapshotSource> for SnapshotSource {
+    type Error = ProxyDecodeError;
+    fn try_from(item: pb::SnapshotSource) -> Result<Self, Self::Error> {
+        match item {
+            // Snapshots written before the source was recorded were all
+            // taken from the canister.
+            pb::SnapshotSource::Unspecified | pb::SnapshotSource::TakenFromCanister => {
+                Ok(SnapshotSource::TakenFromCanister)
+            }
+            pb::SnapshotSource::MetadataUpload => Ok(SnapshotSource::MetadataUpload),
+            pb::SnapshotSource::UploadFinalized => Ok(SnapshotSource::UploadFinalized),
+        }
+    }
+}
+
 /// Contains all information related to a canister snapshot.
 #[derive(Clone, Eq, PartialEq, Debug, ValidateEq)]
 pub struct CanisterSnapshot {
+    /// How the snapshot was created.
+    source: SnapshotSource,
     /// Identifies the canister to which this snapshot belongs.
     canister_id: CanisterId,
     /// The timestamp indicating the moment the snapshot was captured.
@@ -320,10 +418,19 @@ pub struct CanisterSnapshot {
     chunk_store: WasmChunkStore,
     #[validate_eq(CompareWithValidateEq)]
     execution_snapshot: ExecutionStateSnapshot,
+    /// The Wasm module being uploaded, if any. Replaces the module of the
+    /// `execution_snapshot` once built.
+    #[validate_eq(Ignore)]
+    wasm_module_upload: Option<WasmModuleUpload>,
+    /// The hashes of the Wasm chunks declared in the metadata of an uploaded
+    /// snapshot. Only these chunks may be uploaded into the chunk store.
+    declared_wasm_chunk_hashes: BTreeSet<WasmChunkHash>,
 }
 
 impl CanisterSnapshot {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
+        source: SnapshotSource,
         canister_id: CanisterId,
         taken_at_timestamp: Time,
         canister_version: u64,
@@ -333,6 +440,7 @@ impl CanisterSnapshot {
         size: NumBytes,
     ) -> CanisterSnapshot {
         Self {
+            source,
             canister_id,
             taken_at_timestamp,
             canister_version,
@@ -340,6 +448,8 @@ impl CanisterSnapshot {
             chunk_store,
             execution_snapshot,
             size,
+            wasm_module_upload: None,
+            declared_wasm_chunk_hashes: BTreeSet::new(),
         }
     }
 
@@ -361,6 +471,7 @@ impl CanisterSnapshot {
         };
 
         Ok(CanisterSnapshot {
+            source: SnapshotSource::TakenFromCanister,
             canister_id,
             taken_at_timestamp,
             canister_version: canister.system_state.canister_version,
@@ -368,9 +479,15 @@ impl CanisterSnapshot {
             chunk_store: canister.system_state.wasm_chunk_store.clone(),
             execution_snapshot,
             size: canister.snapshot_size_bytes(),
+            wasm_module_upload: None,
+            declared_wasm_chunk_hashes: BTreeSet::new(),
         })
     }
 
+    pub fn source(&self) -> SnapshotSource {
+        self.source
+    }
+
     pub fn canister_id(&self) -> CanisterId {
         self.canister_id
     }
@@ -399,7 +516,12 @@ impl CanisterSnapshot {
         &self.execution_snapshot.wasm_memory
     }
 
+    /// Returns the Wasm module of the snapshot.
+    ///
+    /// Must not be called while a module is being uploaded, see
+    /// `build_uploaded_wasm_module()`.
     pub fn canister_module(&self) -> &CanisterModule {
+        debug_assert!(self.wasm_module_upload.is_none());
         &self.execution_snapshot.wasm_binary
     }
 
@@ -423,6 +545,117 @@ impl CanisterSnapshot {
         &mut self.execution_snapshot
     }
 
+    pub fn declared_wasm_chunk_hashes(&self) -> &BTreeSet<WasmChunkHash> {
+        &self.declared_wasm_chunk_hashes
+    }
+
+    pub fn set_declared_wasm_chunk_hashes(&mut self, hashes: BTreeSet<WasmChunkHash>) {
+        self.declared_wasm_chunk_hashes = hashes;
+    }
+
+    /// Returns the size of the Wasm module in bytes, including that of a
+    /// module being uploaded.
+    pub fn wasm_module_size(&self) -> usize {
+        match &self.wasm_module_upload {
+            Some(upload) => upload.size,
+            None => self.execution_snapshot.wasm_binary.len(),
+        }
+    }
+
+    /// Returns true if the Wasm module is being uploaded and not built yet.
+    pub fn is_uploading_wasm_module(&self) -> bool {
+        self.wasm_module_upload.is_some()
+    }
+
+    /// Starts uploading a Wasm module of `size` bytes, which replaces the
+    /// current module once built. Nothing is allocated until data is written.
+    pub fn start_wasm_module_upload(
+        &mut self,
+        size: usize,
+        fd_factory: Arc<dyn PageAllocatorFileDescriptor>,
+    ) {
+        self.wasm_module_upload = Some(WasmModuleUpload {
+            size,
+            page_map: PageMap::new(fd_factory),
+        });
+    }
+
+    /// Writes `data` at `offset` into the Wasm module being uploaded. If the
+    /// module was already built, e.g. when writing a checkpoint, a new upload
+    /// is started from it.
+    ///
+    /// The caller must ensure that the data fits into the module.
+    pub fn write_wasm_module(
+        &mut self,
+        offset: usize,
+        data: &[u8],
+        fd_factory: Arc<dyn PageAllocatorFileDescriptor>,
+    ) {
+        if self.wasm_module_upload.is_none() {
+            let module = self.execution_snapshot.wasm_binary.to_shared_vec();
+            self.start_wasm_module_upload(module.len(), fd_factory);
+            self.write_wasm_module_pages(0, &module);
+        }
+        self.write_wasm_module_pages(offset, data);
+    }
+
+    fn write_wasm_module_pages(&mut self, offset: usize, data: &[u8]) {
+        let upload = self.wasm_module_upload.as_mut().unwrap();
+        debug_assert!(offset + data.len() <= upload.size);
+        let mut buffer = Buffer::new(upload.page_map.clone());
+        buffer.write(data, offset);
+        upload
+            .page_map
+            .update(&buffer.dirty_pages().collect::<Vec<_>>());
+    }
+
+    /// Returns `size` bytes of the Wasm module starting at `offset`.
+    ///
+    /// The caller must ensure that the range is within the module.
+    pub fn read_wasm_module(&self, offset: usize, size: usize) -> Vec<u8> {
+        match &self.wasm_module_upload {
+            Some(upload) => {
+                // Bytes which were not written yet are zero.
+                let mut chunk = vec![0; size];
+                Buffer::new(upload.page_map.clone()).read(&mut chunk, offset);
+                chunk
+            }
+            None => self.execution_snapshot.wasm_binary.as_slice()[offset..offset + size].to_vec(),
+        }
+    }
+
+    /// Builds the Wasm module being uploaded, if any, and makes it the module
+    /// of the snapshot.
+    pub fn build_uploaded_wasm_module(&mut self) {
+        if self.wasm_module_upload.is_some() {
+            self.execution_snapshot.wasm_binary = self.build_wasm_module();
+            self.wasm_module_upload = None;
+        }
+    }
+
+    /// Returns the Wasm module of the snapshot, building the module being
+    /// uploaded, if any, without making it the module of the snapshot.
+    pub fn build_wasm_module(&self) -> CanisterModule {
+        match &self.wasm_module_upload {
+            Some(upload) => {
+                let mut module = vec![0; upload.size];
+                Buffer::new(upload.page_map.clone()).read(&mut module, 0);
+                CanisterModule::new(module)
+            }
+            None => self.execution_snapshot.wasm_binary.clone(),
+        }
+    }
+
+    /// Finalizes a snapshot created from uploaded metadata when it is loaded:
+    /// `wasm_module`, as returned by `build_wasm_module()`, becomes the module
+    /// of the snapshot and no more data can be uploaded into it.
+    pub fn finalize_upload(&mut self, wasm_module: CanisterModule) {
+        debug_assert_eq!(self.source, SnapshotSource::MetadataUpload);
+        self.execution_snapshot.wasm_binary = wasm_module;
+        self.wasm_module_upload = None;
+        self.source = SnapshotSource::UploadFinalized;
+    }
+
     /// Returns the heap delta produced by this snapshot.
     ///
     /// The heap delta includes the delta of the wasm memory, stable memory and
@@ -455,12 +688,15 @@ pub enum SnapshotOperation {
     Delete(SnapshotId),
     Backup(CanisterId, SnapshotId),
     Restore(CanisterId, SnapshotId),
+    Upload(CanisterId, SnapshotId),
+    UploadData(CanisterId, SnapshotId),
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use super::{CanisterSnapshot, CanisterSnapshots, PageMap};
+    use crate::page_map::TestPageAllocatorFileDescriptorImpl;
     use ic_test_utilities_types::ids::canister_test_id;
     use ic_types::time::UNIX_EPOCH;
     use ic_types::NumBytes;
@@ -483,6 +719,7 @@ mod tests {
             },
         };
         let snapshot = CanisterSnapshot::new(
+            SnapshotSource::TakenFromCanister,
             canister_id,
             UNIX_EPOCH,
             0,
@@ -536,6 +773,92 @@ mod tests {
         assert_eq!(snapshot_manager.snapshot_ids.get(&canister_id), None);
     }
 
+    #[test]
+    fn test_push_uploaded_snapshot_records_upload() {
+        let canister_id = canister_test_id(0);
+        let (snapshot_id, snapshot) = fake_canister_snapshot(canister_id, 1);
+        let snapshot_size = snapshot.size();
+        let mut snapshot_manager = CanisterSnapshots::default();
+
+        snapshot_manager.push_uploaded(snapshot_id, Arc::new(snapshot));
+        assert!(snapshot_manager.contains(&snapshot_id));
+        assert_eq!(snapshot_manager.count_by_canister(&canister_id), 1);
+        assert_eq!(snapshot_manager.memory_taken(), snapshot_size);
+        assert_eq!(
+            snapshot_manager.take_unflushed_changes(),
+            vec![SnapshotOperation::Upload(canister_id, snapshot_id)]
+        );
+
+        // Uploading data is recorded once for consecutive uploads.
+        snapshot_manager.add_upload_data_operation(canister_id, snapshot_id);
+        snapshot_manager.add_upload_data_operation(canister_id, snapshot_id);
+        assert_eq!(
+            snapshot_manager.take_unflushed_changes(),
+            vec![SnapshotOperation::UploadData(canister_id, snapshot_id)]
+        );
+
+        // Deleting an uploaded snapshot is recorded like for any other snapshot.
+        snapshot_manager.remove(snapshot_id);
+        assert_eq!(
+            snapshot_manager.take_unflushed_changes(),
+            vec![SnapshotOperation::Delete(snapshot_id)]
+        );
+    }
+
+    #[test]
+    fn test_finalize_upload() {
+        let canister_id = canister_test_id(0);
+        let (_, snapshot) = fake_canister_snapshot(canister_id, 1);
+        let mut snapshot = CanisterSnapshot {
+            source: SnapshotSource::MetadataUpload,
+            ..snapshot
+        };
+        let fd_factory = Arc::new(TestPageAllocatorFileDescriptorImpl::new());
+        snapshot.start_wasm_module_upload(3, fd_factory.clone());
+        snapshot.write_wasm_module(0, &[1, 2, 3], fd_factory);
+
+        // Building the module to load does not change the snapshot.
+        let wasm_module = snapshot.build_wasm_module();
+        assert_eq!(wasm_module.as_slice(), &[1, 2, 3]);
+        assert!(snapshot.is_uploading_wasm_module());
+        assert_eq!(snapshot.source(), SnapshotSource::MetadataUpload);
+
+        snapshot.finalize_upload(wasm_module);
+        assert!(!snapshot.is_uploading_wasm_module());
+        assert_eq!(snapshot.canister_module().as_slice(), &[1, 2, 3]);
+        assert_eq!(snapshot.source(), SnapshotSource::UploadFinalized);
+    }
+
+    #[test]
+    fn test_uploaded_wasm_module_is_built_once_needed() {
+        let canister_id = canister_test_id(0);
+        let (_, mut snapshot) = fake_canister_snapshot(canister_id, 1);
+        let fd_factory = Arc::new(TestPageAllocatorFileDescriptorImpl::new());
+
+        snapshot.start_wasm_module_upload(2 * PAGE_SIZE, fd_factory.clone());
+        assert!(snapshot.is_uploading_wasm_module());
+        assert_eq!(snapshot.wasm_module_size(), 2 * PAGE_SIZE);
+
+        snapshot.write_wasm_module(PAGE_SIZE - 1, &[1, 2], fd_factory.clone());
+        assert_eq!(
+            snapshot.read_wasm_module(PAGE_SIZE - 2, 4),
+            vec![0, 1, 2, 0]
+        );
+
+        snapshot.build_uploaded_wasm_module();
+        assert!(!snapshot.is_uploading_wasm_module());
+        let mut expected = vec![0; 2 * PAGE_SIZE];
+        expected[PAGE_SIZE - 1..PAGE_SIZE + 1].copy_from_slice(&[1, 2]);
+        assert_eq!(snapshot.canister_module().as_slice(), &expected[..]);
+
+        // Writing into a built module starts a new upload from it.
+        snapshot.write_wasm_module(0, &[3], fd_factory);
+        assert!(snapshot.is_uploading_wasm_module());
+        snapshot.build_uploaded_wasm_module();
+        expected[0] = 3;
+        assert_eq!(snapshot.canister_module().as_slice(), &expected[..]);
+    }
+
     #[test]
     fn test_construct_canister_snapshot_ids() {
         let snapshots: BTreeMap<_, _> = [
@@ -162,6 +162,10 @@ impl CanisterState {
         &self.system_state.controllers
     }
 
+    pub fn operators(&self) -> &BTreeSet<PrincipalId> {
+        &self.system_state.operators
+    }
+
     pub fn log_visibility(&self) -> &LogVisibilityV2 {
         &self.system_state.log_visibility
     }
@@ -372,7 +376,8 @@ impl CanisterState {
     /// The amount of memory currently being used by the canister.
     ///
     /// This includes execution memory (heap, stable, globals, Wasm),
-    /// canister history memory, wasm chunk storage and snapshots that
+    /// canister history memory, wasm chunk storage, snapshots and the
+    /// configured canister log buffer above its default size that
     /// belong to this canister.
     ///
     /// This amount is used to periodically charge the canister for the memory
@@ -383,6 +388,7 @@ impl CanisterState {
             + self.canister_history_memory_usage()
             + self.wasm_chunk_store_memory_usage()
             + self.system_state.snapshots_memory_usage
+            + self.canister_log_memory_usage()
     }
 
     /// Returns the amount of execution memory (heap, stable, globals, Wasm)
@@ -414,6 +420,11 @@ impl CanisterState {
         self.system_state.canister_history_memory_usage()
     }
 
+    /// Returns the memory charged for the canister log buffer in bytes.
+    pub fn canister_log_memory_usage(&self) -> NumBytes {
+        self.system_state.canister_log_memory_usage()
+    }
+
     /// Returns the memory usage of the wasm chunk store in bytes.
     pub(super) fn wasm_chunk_store_memory_usage(&self) -> NumBytes {
         self.system_state.wasm_chunk_store.memory_usage()
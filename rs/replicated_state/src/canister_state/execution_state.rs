@@ -1,5 +1,6 @@
 use crate::hash::ic_hashtree_leaf_hash;
 use crate::{canister_state::WASM_PAGE_SIZE_IN_BYTES, num_bytes_try_from, NumWasmPages, PageMap};
+use ic_management_canister_types as ic00;
 use ic_protobuf::{
     proxy::{try_from_option_field, ProxyDecodeError},
     state::canister_state_bits::v1 as pb,
@@ -144,6 +145,30 @@ impl TryFrom<pb::Global> for Global {
     }
 }
 
+impl From<&Global> for ic00::Global {
+    fn from(item: &Global) -> Self {
+        match item {
+            Global::I32(value) => Self::I32(*value),
+            Global::I64(value) => Self::I64(*value),
+            Global::F32(value) => Self::F32(*value),
+            Global::F64(value) => Self::F64(*value),
+            Global::V128(value) => Self::V128(*value),
+        }
+    }
+}
+
+impl From<&ic00::Global> for Global {
+    fn from(item: &ic00::Global) -> Self {
+        match item {
+            ic00::Global::I32(value) => Self::I32(*value),
+            ic00::Global::I64(value) => Self::I64(*value),
+            ic00::Global::F32(value) => Self::F32(*value),
+            ic00::Global::F64(value) => Self::F64(*value),
+            ic00::Global::V128(value) => Self::V128(*value),
+        }
+    }
+}
+
 /// A set of the functions that a Wasm module exports.
 ///
 /// Arc is used to make cheap clones of this during snapshots.
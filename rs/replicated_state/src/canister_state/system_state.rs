@@ -34,8 +34,8 @@ use ic_types::methods::Callback;
 use ic_types::nominal_cycles::NominalCycles;
 use ic_types::time::CoarseTime;
 use ic_types::{
-    CanisterId, CanisterLog, CanisterTimer, Cycles, MemoryAllocation, NumBytes, NumInstructions,
-    PrincipalId, Time,
+    CanisterId, CanisterLog, CanisterTimer, Cycles, EnvironmentVariables, MemoryAllocation,
+    NumBytes, NumInstructions, PrincipalId, Time,
 };
 use ic_validate_eq::ValidateEq;
 use ic_validate_eq_derive::ValidateEq;
@@ -659,6 +659,15 @@ pub struct SystemState {
     /// This amount contributes to the total `memory_usage` of the canister as
     /// reported by `CanisterState::memory_usage`.
     pub snapshots_memory_usage: NumBytes,
+
+    /// Environment variables of the canister, readable through the
+    /// `ic0.env_var_*` system API.
+    pub environment_variables: EnvironmentVariables,
+
+    /// Principals holding the operator role. Operators can start, stop and
+    /// snapshot the canister and read its status, but unlike controllers they
+    /// cannot change its code or settings.
+    pub operators: BTreeSet<PrincipalId>,
 }
 
 /// A wrapper around the different statuses of `OnLowWasmMemory` hook execution.
@@ -1083,6 +1092,8 @@ impl SystemState {
             wasm_memory_limit: None,
             next_snapshot_id: 0,
             snapshots_memory_usage: NumBytes::from(0),
+            environment_variables: Default::default(),
+            operators: Default::default(),
         }
     }
 
@@ -1112,6 +1123,8 @@ impl SystemState {
         wasm_memory_limit: Option<NumBytes>,
         next_snapshot_id: u64,
         snapshots_memory_usage: NumBytes,
+        environment_variables: EnvironmentVariables,
+        operators: BTreeSet<PrincipalId>,
         metrics: &dyn CheckpointLoadingMetrics,
         on_low_wasm_memory_hook_status: OnLowWasmMemoryHookStatus,
     ) -> Self {
@@ -1146,6 +1159,8 @@ impl SystemState {
             wasm_memory_limit,
             next_snapshot_id,
             snapshots_memory_usage,
+            environment_variables,
+            operators,
         };
         system_state.check_invariants().unwrap_or_else(|msg| {
             metrics.observe_broken_soft_invariant(msg);
@@ -1877,6 +1892,16 @@ impl SystemState {
         self.canister_history.get_memory_usage()
     }
 
+    /// Returns the size of the canister log buffer.
+    pub fn log_memory_limit(&self) -> NumBytes {
+        NumBytes::new(self.canister_log.capacity() as u64)
+    }
+
+    /// Returns the memory charged for the canister log buffer.
+    pub fn canister_log_memory_usage(&self) -> NumBytes {
+        NumBytes::new(self.canister_log.memory_usage() as u64)
+    }
+
     /// Sets the (transient) size in bytes of guaranteed responses from this
     /// canister routed into streams and not yet garbage collected.
     pub(super) fn set_stream_guaranteed_responses_size_bytes(&mut self, size_bytes: usize) {
@@ -2540,6 +2565,8 @@ pub mod testing {
             wasm_memory_limit: Default::default(),
             next_snapshot_id: Default::default(),
             snapshots_memory_usage: Default::default(),
+            environment_variables: Default::default(),
+            operators: Default::default(),
         };
     }
 }
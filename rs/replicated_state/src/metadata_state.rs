@@ -364,6 +364,8 @@ pub struct SubnetTopology {
     /// a backup. An additional NNS proposal will be needed to allow the subnet
     /// holding the key as backup to actually produce signatures.
     pub idkg_keys_held: BTreeSet<MasterPublicKeyId>,
+    /// The replica version the subnet is running, as recorded in the registry.
+    pub replica_version: String,
 }
 
 impl From<&SubnetTopology> for pb_metadata::SubnetTopology {
@@ -380,6 +382,7 @@ impl From<&SubnetTopology> for pb_metadata::SubnetTopology {
             subnet_type: i32::from(item.subnet_type),
             subnet_features: Some(pb_subnet::SubnetFeatures::from(item.subnet_features)),
             idkg_keys_held: item.idkg_keys_held.iter().map(|k| k.into()).collect(),
+            replica_version: item.replica_version.clone(),
         }
     }
 }
@@ -409,6 +412,7 @@ impl TryFrom<pb_metadata::SubnetTopology> for SubnetTopology {
                 .map(SubnetFeatures::from)
                 .unwrap_or_default(),
             idkg_keys_held,
+            replica_version: item.replica_version,
         })
     }
 }
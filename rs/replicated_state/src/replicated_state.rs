@@ -617,6 +617,7 @@ impl ReplicatedState {
             wasm_custom_sections_memory_taken,
             canister_history_memory_taken,
             wasm_chunk_store_memory_usage,
+            canister_log_memory_usage,
         ) = self
             .canisters_iter()
             .map(|canister| {
@@ -631,6 +632,7 @@ impl ReplicatedState {
                     canister.wasm_custom_sections_memory_usage(),
                     canister.canister_history_memory_usage(),
                     canister.wasm_chunk_store_memory_usage(),
+                    canister.canister_log_memory_usage(),
                 )
             })
             .reduce(|accum, val| {
@@ -640,6 +642,7 @@ impl ReplicatedState {
                     accum.2 + val.2,
                     accum.3 + val.3,
                     accum.4 + val.4,
+                    accum.5 + val.5,
                 )
             })
             .unwrap_or_default();
@@ -653,6 +656,7 @@ impl ReplicatedState {
             execution: raw_memory_taken
                 + canister_history_memory_taken
                 + wasm_chunk_store_memory_usage
+                + canister_log_memory_usage
                 + canister_snapshots_memory_taken,
             guaranteed_response_messages: guaranteed_response_message_memory_taken,
             wasm_custom_sections: wasm_custom_sections_memory_taken,
@@ -18,6 +18,7 @@ use ic_sns_test_utils::{
         setup_sns_canisters, sns_root_register_dapp_canisters, state_machine_builder_for_sns_tests,
     },
 };
+use ic_types::DEFAULT_CANISTER_LOG_BUFFER_SIZE;
 use lazy_static::lazy_static;
 use tokio::time::Duration;
 
@@ -107,6 +108,8 @@ fn test_manage_dapp_canister_settings_successful() {
             Some(1_000_000_000_000),
             ic_management_canister_types::LogVisibilityV2::Public,
             Some(1_000_000_000),
+            DEFAULT_CANISTER_LOG_BUFFER_SIZE as u64,
+            vec![],
         ),
     );
 
@@ -160,6 +163,8 @@ fn test_manage_dapp_canister_settings_successful() {
             Some(0),
             ic_management_canister_types::LogVisibilityV2::Controllers,
             Some(2_000_000_000),
+            DEFAULT_CANISTER_LOG_BUFFER_SIZE as u64,
+            vec![],
         ),
     );
 }
@@ -237,6 +242,8 @@ fn test_manage_dapp_canister_settings_failure() {
             Some(1_000_000_000_000),
             ic_management_canister_types::LogVisibilityV2::Public,
             Some(1_000_000_000),
+            DEFAULT_CANISTER_LOG_BUFFER_SIZE as u64,
+            vec![],
         ),
     );
 
@@ -310,6 +317,8 @@ fn test_manage_dapp_canister_settings_failure() {
             Some(1_000_000_000_000),
             ic_management_canister_types::LogVisibilityV2::Public,
             Some(1_000_000_000),
+            DEFAULT_CANISTER_LOG_BUFFER_SIZE as u64,
+            vec![],
         ),
     );
 
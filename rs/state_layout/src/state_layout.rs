@@ -12,11 +12,12 @@ use ic_protobuf::{
     },
 };
 use ic_replicated_state::{
+    canister_snapshots::SnapshotSource,
     canister_state::{
         execution_state::{NextScheduledMethod, WasmMetadata},
         system_state::{
-            wasm_chunk_store::WasmChunkStoreMetadata, CanisterHistory, CyclesUseCase,
-            OnLowWasmMemoryHookStatus,
+            wasm_chunk_store::{WasmChunkHash, WasmChunkStoreMetadata},
+            CanisterHistory, CyclesUseCase, OnLowWasmMemoryHookStatus,
         },
     },
     page_map::{Shard, StorageLayout, StorageResult},
@@ -25,8 +26,9 @@ use ic_replicated_state::{
 use ic_sys::{fs::sync_path, mmap::ScopedMmap};
 use ic_types::{
     batch::TotalQueryStats, nominal_cycles::NominalCycles, AccumulatedPriority, CanisterId,
-    CanisterLog, ComputeAllocation, Cycles, ExecutionRound, Height, LongExecutionMode,
-    MemoryAllocation, NumInstructions, PrincipalId, SnapshotId, Time,
+    CanisterLog, ComputeAllocation, Cycles, EnvironmentVariables, ExecutionRound, Height,
+    LongExecutionMode, MemoryAllocation, NumInstructions, PrincipalId, SnapshotId, Time,
+    DEFAULT_CANISTER_LOG_BUFFER_SIZE,
 };
 use ic_utils::thread::maybe_parallel_map;
 use ic_wasm_types::{CanisterModule, WasmHash};
@@ -180,6 +182,8 @@ pub struct CanisterStateBits {
     pub next_snapshot_id: u64,
     pub snapshots_memory_usage: NumBytes,
     pub on_low_wasm_memory_hook_status: OnLowWasmMemoryHookStatus,
+    pub environment_variables: EnvironmentVariables,
+    pub operators: BTreeSet<PrincipalId>,
 }
 
 /// This struct contains bits of the `CanisterSnapshot` that are not already
@@ -208,6 +212,10 @@ pub struct CanisterSnapshotBits {
     pub total_size: NumBytes,
     /// State of the exported Wasm globals.
     pub exported_globals: Vec<Global>,
+    /// The hashes of the Wasm chunks declared for an uploaded snapshot.
+    pub declared_wasm_chunk_hashes: BTreeSet<WasmChunkHash>,
+    /// How the snapshot was created.
+    pub source: SnapshotSource,
 }
 
 #[derive(Clone)]
@@ -2340,6 +2348,7 @@ impl From<CanisterStateBits> for pb_canister_state_bits::CanisterStateBits {
                 .map(|record| record.into())
                 .collect(),
             next_canister_log_record_idx: item.canister_log.next_idx(),
+            log_memory_limit: Some(item.canister_log.capacity() as u64),
             wasm_memory_limit: item.wasm_memory_limit.map(|v| v.get()),
             next_snapshot_id: item.next_snapshot_id,
             snapshots_memory_usage: item.snapshots_memory_usage.get(),
@@ -2349,6 +2358,21 @@ impl From<CanisterStateBits> for pb_canister_state_bits::CanisterStateBits {
                 )
                 .into(),
             ),
+            environment_variables: item
+                .environment_variables
+                .iter()
+                .map(
+                    |(name, value)| pb_canister_state_bits::EnvironmentVariable {
+                        name: name.clone(),
+                        value: value.clone(),
+                    },
+                )
+                .collect(),
+            operators: item
+                .operators
+                .into_iter()
+                .map(|operator| operator.into())
+                .collect(),
         }
     }
 }
@@ -2375,6 +2399,11 @@ impl TryFrom<pb_canister_state_bits::CanisterStateBits> for CanisterStateBits {
             controllers.insert(PrincipalId::try_from(controller)?);
         }
 
+        let mut operators = BTreeSet::new();
+        for operator in value.operators.into_iter() {
+            operators.insert(PrincipalId::try_from(operator)?);
+        }
+
         let cycles_balance =
             try_from_option_field(value.cycles_balance, "CanisterStateBits::cycles_balance")?;
 
@@ -2492,13 +2521,16 @@ impl TryFrom<pb_canister_state_bits::CanisterStateBits> for CanisterStateBits {
                 "CanisterStateBits::log_visibility_v2",
             )
             .unwrap_or_default(),
-            canister_log: CanisterLog::new(
+            canister_log: CanisterLog::new_with_capacity(
                 value.next_canister_log_record_idx,
                 value
                     .canister_log_records
                     .into_iter()
                     .map(|record| record.into())
                     .collect(),
+                value
+                    .log_memory_limit
+                    .map_or(DEFAULT_CANISTER_LOG_BUFFER_SIZE, |limit| limit as usize),
             ),
             wasm_memory_limit: value.wasm_memory_limit.map(NumBytes::from),
             next_snapshot_id: value.next_snapshot_id,
@@ -2508,6 +2540,14 @@ impl TryFrom<pb_canister_state_bits::CanisterStateBits> for CanisterStateBits {
                 "CanisterStateBits::on_low_wasm_memory_hook_status",
             )
             .unwrap_or_default(),
+            environment_variables: EnvironmentVariables::new(
+                value
+                    .environment_variables
+                    .into_iter()
+                    .map(|variable| (variable.name, variable.value))
+                    .collect(),
+            ),
+            operators,
         })
     }
 }
@@ -2596,6 +2636,12 @@ impl From<CanisterSnapshotBits> for pb_canister_snapshot_bits::CanisterSnapshotB
                 .iter()
                 .map(|global| global.into())
                 .collect(),
+            declared_wasm_chunk_hashes: item
+                .declared_wasm_chunk_hashes
+                .iter()
+                .map(|hash| hash.to_vec())
+                .collect(),
+            source: pb_canister_snapshot_bits::SnapshotSource::from(item.source).into(),
         }
     }
 }
@@ -2626,6 +2672,17 @@ impl TryFrom<pb_canister_snapshot_bits::CanisterSnapshotBits> for CanisterSnapsh
             exported_globals.push(global.try_into()?);
         }
 
+        let mut declared_wasm_chunk_hashes = BTreeSet::new();
+        for hash in item.declared_wasm_chunk_hashes.into_iter() {
+            let hash: WasmChunkHash =
+                hash.try_into()
+                    .map_err(|e| ProxyDecodeError::ValueOutOfRange {
+                        typ: "WasmChunkHash",
+                        err: format!("Expected a 32-byte long chunk hash, got {:?}", e),
+                    })?;
+            declared_wasm_chunk_hashes.insert(hash);
+        }
+
         Ok(Self {
             snapshot_id: SnapshotId::from((canister_id, item.snapshot_id)),
             canister_id,
@@ -2642,6 +2699,11 @@ impl TryFrom<pb_canister_snapshot_bits::CanisterSnapshotBits> for CanisterSnapsh
             wasm_memory_size: NumWasmPages::from(item.wasm_memory_size as usize),
             total_size: NumBytes::from(item.total_size),
             exported_globals,
+            declared_wasm_chunk_hashes,
+            source: SnapshotSource::try_from(
+                pb_canister_snapshot_bits::SnapshotSource::try_from(item.source)
+                    .unwrap_or_default(),
+            )?,
         })
     }
 }
@@ -61,6 +61,8 @@ fn default_canister_state_bits() -> CanisterStateBits {
         next_snapshot_id: 0,
         snapshots_memory_usage: NumBytes::from(0),
         on_low_wasm_memory_hook_status: OnLowWasmMemoryHookStatus::default(),
+        environment_variables: EnvironmentVariables::default(),
+        operators: BTreeSet::new(),
     }
 }
 
@@ -188,6 +190,18 @@ fn test_encode_decode_non_empty_history() {
         CanisterChangeOrigin::from_canister(canister_test_id(123).get(), None),
         CanisterChangeDetails::controllers_change(vec![]),
     ));
+    canister_history.add_canister_change(CanisterChange::new(
+        555,
+        7,
+        CanisterChangeOrigin::from_user(user_test_id(42).get()),
+        CanisterChangeDetails::environment_variables_change([3; 32]),
+    ));
+    canister_history.add_canister_change(CanisterChange::new(
+        666,
+        8,
+        CanisterChangeOrigin::from_user(user_test_id(42).get()),
+        CanisterChangeDetails::operators_change(vec![user_test_id(7).get()]),
+    ));
 
     // A canister state with non-empty history.
     let canister_state_bits = CanisterStateBits {
@@ -201,6 +215,42 @@ fn test_encode_decode_non_empty_history() {
     assert_eq!(canister_state_bits.canister_history, canister_history);
 }
 
+#[test]
+fn test_encode_decode_environment_variables() {
+    let environment_variables = EnvironmentVariables::new(BTreeMap::from([
+        ("BACKEND_CANISTER_ID".to_string(), "aaaaa-aa".to_string()),
+        ("NETWORK".to_string(), "staging".to_string()),
+    ]));
+
+    let canister_state_bits = CanisterStateBits {
+        environment_variables: environment_variables.clone(),
+        ..default_canister_state_bits()
+    };
+
+    let pb_bits = pb_canister_state_bits::CanisterStateBits::from(canister_state_bits);
+    let canister_state_bits = CanisterStateBits::try_from(pb_bits).unwrap();
+
+    assert_eq!(
+        canister_state_bits.environment_variables,
+        environment_variables
+    );
+}
+
+#[test]
+fn test_encode_decode_operators() {
+    let operators = BTreeSet::from([user_test_id(1).get(), user_test_id(2).get()]);
+
+    let canister_state_bits = CanisterStateBits {
+        operators: operators.clone(),
+        ..default_canister_state_bits()
+    };
+
+    let pb_bits = pb_canister_state_bits::CanisterStateBits::from(canister_state_bits);
+    let canister_state_bits = CanisterStateBits::try_from(pb_bits).unwrap();
+
+    assert_eq!(canister_state_bits.operators, operators);
+}
+
 #[test]
 fn test_canister_snapshots_decode() {
     let canister_id = canister_test_id(7);
@@ -216,6 +266,8 @@ fn test_canister_snapshots_decode() {
         wasm_memory_size: NumWasmPages::new(10),
         total_size: NumBytes::new(100),
         exported_globals: vec![Global::I32(1), Global::I64(2), Global::F64(0.1)],
+        declared_wasm_chunk_hashes: BTreeSet::from([[1; 32], [2; 32]]),
+        source: SnapshotSource::MetadataUpload,
     };
 
     let pb_bits =
@@ -6,8 +6,8 @@ use ic_btc_consensus::BitcoinPayloadBuilder;
 use ic_config::{
     adapters::AdaptersConfig,
     bitcoin_payload_builder_config::Config as BitcoinPayloadBuilderConfig,
-    execution_environment::Config as HypervisorConfig, flag_status::FlagStatus,
-    state_manager::LsmtConfig, subnet_config::SubnetConfig,
+    embedders::WASM_MAX_SIZE, execution_environment::Config as HypervisorConfig,
+    flag_status::FlagStatus, state_manager::LsmtConfig, subnet_config::SubnetConfig,
 };
 use ic_consensus::{
     consensus::payload_builder::PayloadBuilderImpl,
@@ -20,6 +20,9 @@ use ic_crypto_test_utils_ni_dkg::{
 use ic_crypto_tree_hash::{sparse_labeled_tree_from_paths, Label, Path as LabeledTreePath};
 use ic_crypto_utils_threshold_sig_der::threshold_sig_public_key_to_der;
 use ic_cycles_account_manager::CyclesAccountManager;
+pub use ic_embedders::wasm_utils::coverage::CoverageReport;
+use ic_embedders::wasm_utils::decoding::decode_wasm;
+use ic_embedders::wasmtime_embedder::instruction_profiler::INSTRUCTION_PROFILE_EXTENSION;
 pub use ic_error_types::{ErrorCode, UserError};
 use ic_execution_environment::{ExecutionServices, IngressHistoryReaderImpl};
 use ic_http_endpoints_public::{metrics::HttpHandlerMetrics, IngressWatcher, IngressWatcherHandle};
@@ -53,7 +56,7 @@ pub use ic_management_canister_types::{
     CanisterStatusType, ClearChunkStoreArgs, EcdsaCurve, EcdsaKeyId, HttpHeader, HttpMethod,
     InstallChunkedCodeArgs, LoadCanisterSnapshotArgs, SchnorrAlgorithm, SignWithECDSAReply,
     SignWithSchnorrReply, TakeCanisterSnapshotArgs, UpdateSettingsArgs, UploadChunkArgs,
-    UploadChunkReply,
+    UploadChunkReply, VetKdCurve, VetKdKeyId,
 };
 use ic_messaging::SyncMessageRouting;
 use ic_metrics::MetricsRegistry;
@@ -98,6 +101,7 @@ use ic_replicated_state::{
     canister_state::{system_state::CyclesUseCase, NumWasmPages, WASM_PAGE_SIZE_IN_BYTES},
     metadata_state::subnet_call_context_manager::{SignWithThresholdContext, ThresholdArguments},
     page_map::Buffer,
+    replicated_state::ReplicatedStateMessageRouting,
     CheckpointLoadingMetrics, Memory, PageMap, ReplicatedState,
 };
 use ic_state_layout::{CheckpointLayout, ReadOnly};
@@ -133,7 +137,7 @@ use ic_types::{
     messages::{
         Blob, Certificate, CertificateDelegation, HttpCallContent, HttpCanisterUpdate,
         HttpRequestEnvelope, Payload as MsgPayload, Query, QuerySource, RejectContext,
-        SignedIngress, SignedIngressContent, EXPECTED_MESSAGE_ID_LENGTH,
+        RequestOrResponse, SignedIngress, SignedIngressContent, EXPECTED_MESSAGE_ID_LENGTH,
     },
     signature::ThresholdSignature,
     time::GENESIS,
@@ -165,9 +169,10 @@ pub use ic_error_types::RejectCode;
 use maplit::btreemap;
 use rand::{rngs::StdRng, Rng, SeedableRng};
 use serde::Serialize;
+use sha2::{Digest, Sha512};
 pub use slog::Level;
 use std::{
-    collections::{BTreeMap, BTreeSet},
+    collections::{BTreeMap, BTreeSet, VecDeque},
     convert::TryFrom,
     fmt,
     io::{self, stderr},
@@ -856,6 +861,7 @@ pub struct StateMachine {
     /// A drop guard to gracefully cancel the ingress watcher task.
     _ingress_watcher_drop_guard: tokio_util::sync::DropGuard,
     query_stats_payload_builder: Arc<PocketQueryStatsPayloadBuilderImpl>,
+    instruction_profiling_dir: Option<PathBuf>,
     // This field must be the last one so that the temporary directory is deleted at the very end.
     state_dir: Box<dyn StateMachineStateDir>,
     // DO NOT PUT ANY FIELDS AFTER `state_dir`!!!
@@ -1633,6 +1639,10 @@ impl StateMachine {
         // experience.
         //
         // The API state machine provides is blocking anyway.
+        let instruction_profiling_dir = hypervisor_config
+            .embedders_config
+            .instruction_profiling_dir
+            .clone();
         let execution_services = runtime.block_on(async {
             ExecutionServices::setup_execution(
                 replica_logger.clone(),
@@ -1763,6 +1773,28 @@ impl StateMachine {
                         (public_key, private_key)
                     }
                 },
+                MasterPublicKeyId::VetKd(id) => {
+                    use ic_crypto_internal_bls12_381_vetkd::{G2Affine, Scalar};
+
+                    // We use a fixed seed here so that all subnets in PocketIC share the same keys.
+                    let mut seed = [0; 64];
+                    seed.copy_from_slice(&Sha512::digest(
+                        [&[42; 32][..], id.name.as_bytes()].concat(),
+                    ));
+                    let private_key = Scalar::from_bytes_wide(&seed);
+
+                    let public_key = MasterPublicKey {
+                        algorithm_id: AlgorithmId::ThresBls12_381,
+                        public_key: G2Affine::from(G2Affine::generator() * &private_key)
+                            .serialize()
+                            .to_vec(),
+                    };
+
+                    // vetKD keys are only used to derive public keys, so there
+                    // is no secret key to keep.
+                    idkg_subnet_public_keys.insert(key_id.clone(), public_key);
+                    continue;
+                }
             };
 
             idkg_subnet_secret_keys.insert(key_id.clone(), private_key);
@@ -1849,6 +1881,7 @@ impl StateMachine {
             canister_http_pool,
             canister_http_payload_builder,
             query_stats_payload_builder: pocket_query_stats_payload_builder,
+            instruction_profiling_dir,
         }
     }
 
@@ -3484,6 +3517,56 @@ impl StateMachine {
         canister_state.system_state.canister_log.clone()
     }
 
+    /// Returns the code coverage of the specified canister collected since its
+    /// code was last installed.
+    ///
+    /// Requires coverage instrumentation to be enabled in the embedders config
+    /// of the `StateMachine` (`coverage_instrumentation`) before the canister
+    /// code is installed. Only replicated executions whose state changes are
+    /// committed are counted.
+    pub fn canister_coverage(&self, canister_id: CanisterId) -> Result<CoverageReport, String> {
+        let replicated_state = self.state_manager.get_latest_state().take();
+        let execution_state = replicated_state
+            .canister_state(&canister_id)
+            .ok_or_else(|| format!("Canister {} does not exist", canister_id))?
+            .execution_state
+            .as_ref()
+            .ok_or_else(|| format!("Canister {} has no module", canister_id))?;
+        let wasm = decode_wasm(
+            WASM_MAX_SIZE,
+            Arc::new(execution_state.wasm_binary.binary.as_slice().to_vec()),
+        )
+        .map_err(|err| {
+            format!(
+                "Failed to decode the module of canister {}: {}",
+                canister_id, err
+            )
+        })?;
+        CoverageReport::new(wasm.as_slice(), &execution_state.exported_globals)
+    }
+
+    /// Returns the instruction profile of the last execution of the specified
+    /// canister in the folded stack format understood by flamegraph tools.
+    ///
+    /// Requires instruction profiling to be enabled in the embedders config of
+    /// the `StateMachine` (`instruction_profiling_dir`) before the canister code
+    /// is installed.
+    pub fn instruction_profile(&self, canister_id: CanisterId) -> Result<String, String> {
+        let dir = self
+            .instruction_profiling_dir
+            .as_ref()
+            .ok_or_else(|| "Instruction profiling is not enabled".to_string())?;
+        let path = dir.join(format!("{}.{}", canister_id, INSTRUCTION_PROFILE_EXTENSION));
+        std::fs::read_to_string(&path).map_err(|err| {
+            format!(
+                "Failed to read the instruction profile of canister {} from {}: {}",
+                canister_id,
+                path.display(),
+                err
+            )
+        })
+    }
+
     /// Sets the content of the stable memory for the specified canister.
     ///
     /// If the `data` is not aligned to the Wasm page boundary, this function will extend the stable
@@ -3595,6 +3678,45 @@ impl StateMachine {
         balance
     }
 
+    /// Pushes a message directly into the input queue of its receiver,
+    /// bypassing XNet streams, e.g., a response from a canister that is not
+    /// hosted on any subnet.
+    pub fn push_input(&self, msg: RequestOrResponse) -> Result<(), String> {
+        let (height, mut state) = self.state_manager.take_tip();
+        let mut subnet_available_memory = i64::MAX;
+        let result = state
+            .push_input(msg, &mut subnet_available_memory)
+            .map_err(|(err, _)| err.to_string());
+        self.state_manager.commit_and_certify(
+            state,
+            height.increment(),
+            CertificationScope::Metadata,
+            None,
+        );
+        result
+    }
+
+    /// Garbage collects the messages before `new_begin` from the stream to
+    /// the subnet `remote_subnet_id`, as if that subnet had accepted them.
+    pub fn discard_stream_messages_before(
+        &self,
+        remote_subnet_id: SubnetId,
+        new_begin: StreamIndex,
+    ) {
+        let (height, mut state) = self.state_manager.take_tip();
+        let mut streams = state.take_streams();
+        if let Some(mut stream) = streams.get_mut(&remote_subnet_id) {
+            stream.discard_messages_before(new_begin, &VecDeque::new());
+        }
+        state.put_streams(streams);
+        self.state_manager.commit_and_certify(
+            state,
+            height.increment(),
+            CertificationScope::Metadata,
+            None,
+        );
+    }
+
     /// Returns `sign_with_ecdsa` contexts from internal subnet call context manager.
     pub fn sign_with_ecdsa_contexts(&self) -> BTreeMap<CallbackId, SignWithThresholdContext> {
         let state = self.state_manager.get_latest_state().take();
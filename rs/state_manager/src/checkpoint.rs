@@ -451,6 +451,8 @@ pub fn load_canister_state(
         canister_state_bits.wasm_memory_limit,
         canister_state_bits.next_snapshot_id,
         canister_state_bits.snapshots_memory_usage,
+        canister_state_bits.environment_variables,
+        canister_state_bits.operators,
         metrics,
         canister_state_bits.on_low_wasm_memory_hook_status,
     );
@@ -564,7 +566,8 @@ pub fn load_snapshot<P: ReadPolicy>(
     );
     durations.insert("snapshot_wasm_chunk_store", starting_time.elapsed());
 
-    let canister_snapshot = CanisterSnapshot::new(
+    let mut canister_snapshot = CanisterSnapshot::new(
+        canister_snapshot_bits.source,
         canister_snapshot_bits.canister_id,
         canister_snapshot_bits.taken_at_timestamp,
         canister_snapshot_bits.canister_version,
@@ -573,6 +576,8 @@ pub fn load_snapshot<P: ReadPolicy>(
         execution_snapshot,
         canister_snapshot_bits.total_size,
     );
+    canister_snapshot
+        .set_declared_wasm_chunk_hashes(canister_snapshot_bits.declared_wasm_chunk_hashes);
 
     let metrics = LoadCanisterMetrics { durations };
 
@@ -13,7 +13,7 @@ pub mod tree_hash;
 use crate::{
     manifest::compute_bundled_manifest,
     state_sync::{
-        chunkable::cache::StateSyncCache,
+        chunkable::{cache::StateSyncCache, local_chunks::LocalChunkIndex},
         types::{FileGroupChunks, Manifest, MetaManifest},
     },
     tip::{spawn_tip_thread, HasDowngrade, PageMapToFlush, TipRequest},
@@ -125,6 +125,7 @@ const LABEL_VALUE_REUSED: &str = "reused";
 const LABEL_FETCH: &str = "fetch";
 const LABEL_COPY_FILES: &str = "copy_files";
 const LABEL_COPY_CHUNKS: &str = "copy_chunks";
+const LABEL_COPY_LOCAL_CHUNKS: &str = "copy_local_chunks";
 const LABEL_PREALLOCATE: &str = "preallocate";
 const LABEL_STATE_SYNC_MAKE_CHECKPOINT: &str = "state_sync_make_checkpoint";
 const LABEL_FETCH_META_MANIFEST_CHUNK: &str = "fetch_meta_manifest_chunk";
@@ -541,7 +542,7 @@ impl StateSyncMetrics {
     pub fn new(metrics_registry: &MetricsRegistry) -> Self {
         let size = metrics_registry.int_counter_vec(
             "state_sync_size_bytes_total",
-            "Size of chunks synchronized by different operations ('fetch', 'copy_files', 'copy_chunks', 'preallocate') during all the state sync in bytes.",
+            "Size of chunks synchronized by different operations ('fetch', 'copy_files', 'copy_chunks', 'copy_local_chunks', 'preallocate') during all the state sync in bytes.",
             &["op"],
         );
 
@@ -550,6 +551,7 @@ impl StateSyncMetrics {
             LABEL_FETCH,
             LABEL_COPY_FILES,
             LABEL_COPY_CHUNKS,
+            LABEL_COPY_LOCAL_CHUNKS,
             LABEL_PREALLOCATE,
         ] {
             size.with_label_values(&[*op]);
@@ -582,7 +584,7 @@ impl StateSyncMetrics {
 
         let step_duration = metrics_registry.histogram_vec(
             "state_sync_step_duration_seconds",
-            "Duration of state sync sub-steps in seconds indexed by step ('copy_files', 'copy_chunks', 'fetch', 'state_sync_make_checkpoint')",
+            "Duration of state sync sub-steps in seconds indexed by step ('copy_files', 'copy_chunks', 'copy_local_chunks', 'fetch', 'state_sync_make_checkpoint')",
             // 0.1s, 0.2s, 0.5s, 1s, 2s, 5s, …, 1000s, 2000s, 5000s
             decimal_buckets(-1, 3),
             &["step"],
@@ -592,6 +594,7 @@ impl StateSyncMetrics {
         for step in &[
             LABEL_COPY_FILES,
             LABEL_COPY_CHUNKS,
+            LABEL_COPY_LOCAL_CHUNKS,
             LABEL_FETCH,
             LABEL_STATE_SYNC_MAKE_CHECKPOINT,
         ] {
@@ -1905,6 +1908,21 @@ impl StateManagerImpl {
             })
     }
 
+    /// Adds the chunks of all checkpoints whose manifest has already been
+    /// computed, except the one at `exclude_root`, to `index`.
+    fn index_checkpoint_chunks(&self, index: &mut LocalChunkIndex, exclude_root: Option<&Path>) {
+        let states = self.states.read();
+        for metadata in states.states_metadata.values() {
+            if let (Some(manifest), Some(checkpoint_layout)) =
+                (metadata.manifest(), metadata.checkpoint_layout.as_ref())
+            {
+                if Some(checkpoint_layout.raw_path()) != exclude_root {
+                    index.add_checkpoint(checkpoint_layout, manifest);
+                }
+            }
+        }
+    }
+
     fn compute_certification_metadata(
         metrics: &StateManagerMetrics,
         log: &ReplicaLogger,
@@ -2796,9 +2814,12 @@ fn flush_canister_snapshots_and_page_maps(
     let snapshot_operations = tip_state.canister_snapshots.take_unflushed_changes();
 
     for op in &snapshot_operations {
-        // Only CanisterSnapshots that are new since the last flush will have PageMaps that need to be flushed. They will
-        // have a corresponding Backup in the snapshot operations list.
-        if let SnapshotOperation::Backup(_canister_id, snapshot_id) = op {
+        // Only CanisterSnapshots that are new or were uploaded into since the last flush will have PageMaps that need
+        // to be flushed. They will have a corresponding Backup, Upload or UploadData in the snapshot operations list.
+        if let SnapshotOperation::Backup(_canister_id, snapshot_id)
+        | SnapshotOperation::Upload(_canister_id, snapshot_id)
+        | SnapshotOperation::UploadData(_canister_id, snapshot_id) = op
+        {
             // If we can't find the CanisterSnapshot they must have been already deleted again. Nothing to flush in this case.
             if let Some(canister_snapshot) = tip_state.canister_snapshots.get_mut(*snapshot_id) {
                 let new_snapshot = Arc::make_mut(canister_snapshot);
@@ -3351,6 +3372,9 @@ impl StateManager for StateManagerImpl {
 
         let checkpointed_state = match scope {
             CertificationScope::Full => {
+                // Uploaded snapshot modules are only built when needed, e.g. to be written to
+                // the checkpoint.
+                state.canister_snapshots.build_uploaded_wasm_modules();
                 self.flush_canister_snapshots_and_page_maps(&mut state, height);
                 let CreateCheckpointResult {
                     checkpointed_state,
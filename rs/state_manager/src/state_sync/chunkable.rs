@@ -7,9 +7,10 @@ use crate::{
     },
     state_sync::StateSync,
     StateManagerMetrics, StateSyncMetrics, StateSyncRefs,
-    CRITICAL_ERROR_STATE_SYNC_CORRUPTED_CHUNKS, LABEL_COPY_CHUNKS, LABEL_COPY_FILES, LABEL_FETCH,
-    LABEL_FETCH_MANIFEST_CHUNK, LABEL_FETCH_META_MANIFEST_CHUNK, LABEL_FETCH_STATE_CHUNK,
-    LABEL_PREALLOCATE, LABEL_STATE_SYNC_MAKE_CHECKPOINT,
+    CRITICAL_ERROR_STATE_SYNC_CORRUPTED_CHUNKS, LABEL_COPY_CHUNKS, LABEL_COPY_FILES,
+    LABEL_COPY_LOCAL_CHUNKS, LABEL_FETCH, LABEL_FETCH_MANIFEST_CHUNK,
+    LABEL_FETCH_META_MANIFEST_CHUNK, LABEL_FETCH_STATE_CHUNK, LABEL_PREALLOCATE,
+    LABEL_STATE_SYNC_MAKE_CHECKPOINT,
 };
 use ic_interfaces::p2p::state_sync::{AddChunkError, Chunk, ChunkId, Chunkable};
 use ic_logger::{debug, error, fatal, info, trace, warn, ReplicaLogger};
@@ -26,6 +27,9 @@ use std::{
 };
 
 pub mod cache;
+pub(crate) mod local_chunks;
+
+use local_chunks::LocalChunkIndex;
 
 // If set to true, we validate chunks even in situations where it might not be
 // necessary.
@@ -1020,7 +1024,6 @@ impl IncompleteState {
             let copy_chunks_bytes: u64 =
                 total_bytes - diff_bytes - preallocate_bytes - copy_files_bytes;
 
-            state_sync_size_fetch.inc_by(diff_bytes);
             state_sync_size_preallocate.inc_by(preallocate_bytes);
             state_sync_size_copy_files.inc_by(copy_files_bytes);
             state_sync_size_copy_chunks.inc_by(copy_chunks_bytes);
@@ -1057,6 +1060,16 @@ impl IncompleteState {
                 &mut fetch_chunks,
             );
 
+            let local_bytes = self.copy_local_chunks(
+                &mut thread_pool,
+                manifest_new,
+                &diff_script.fetch_chunks,
+                Some(&root_old),
+                cache.as_deref(),
+                &mut fetch_chunks,
+            );
+            state_sync_size_fetch.inc_by(diff_bytes - local_bytes);
+
             fetch_chunks
         } else {
             info!(
@@ -1069,7 +1082,6 @@ impl IncompleteState {
                 .iter()
                 .map(|i| manifest_new.chunk_table[*i].size_bytes as u64)
                 .sum();
-            state_sync_size_fetch.inc_by(diff_bytes);
             state_sync_size_preallocate.inc_by(total_bytes - diff_bytes);
 
             let zeros_chunks = manifest_new.chunk_table.len() - non_zero_chunks.len();
@@ -1079,11 +1091,82 @@ impl IncompleteState {
                 .remaining
                 .sub(zeros_chunks as i64);
 
-            non_zero_chunks
+            let mut fetch_chunks = non_zero_chunks
                 .iter()
                 .map(|i| *i + FILE_CHUNK_ID_OFFSET)
-                .collect()
+                .collect();
+
+            let local_bytes = self.copy_local_chunks(
+                &mut self.thread_pool.lock().unwrap(),
+                manifest_new,
+                &non_zero_chunks,
+                None,
+                None,
+                &mut fetch_chunks,
+            );
+            state_sync_size_fetch.inc_by(diff_bytes - local_bytes);
+
+            fetch_chunks
+        }
+    }
+
+    /// Copies the chunks `chunks` (indices into the chunk table of
+    /// `manifest_new`) that are available in any local state other than
+    /// `root_old` and removes them from `fetch_chunks`. The local states are
+    /// the state sync cache and all checkpoints with a manifest (see
+    /// [`local_chunks`]).
+    ///
+    /// Returns the number of bytes copied.
+    fn copy_local_chunks(
+        &self,
+        thread_pool: &mut scoped_threadpool::Pool,
+        manifest_new: &Manifest,
+        chunks: &HashSet<usize>,
+        root_old: Option<&Path>,
+        cache_entry: Option<&cache::StateSyncCacheEntry>,
+        fetch_chunks: &mut HashSet<usize>,
+    ) -> u64 {
+        if chunks.is_empty() {
+            return 0;
+        }
+
+        // The index only lives until the chunks have been copied, so that the
+        // checkpoints it refers to can be removed afterwards.
+        let mut index = LocalChunkIndex::new(manifest_new, chunks);
+        if let Some(cache_entry) = cache_entry {
+            if Some(cache_entry.path()) != root_old {
+                index.add_state(
+                    cache_entry.path(),
+                    &cache_entry.manifest,
+                    &cache_entry.missing_chunks,
+                );
+            }
         }
+        self.state_sync
+            .state_manager
+            .index_checkpoint_chunks(&mut index, root_old);
+
+        let copied_chunks = index.copy_chunks(
+            &self.log,
+            &self.metrics.state_sync_metrics,
+            thread_pool,
+            &self.root,
+            manifest_new,
+            chunks,
+        );
+        for ix in &copied_chunks {
+            fetch_chunks.remove(&(*ix + FILE_CHUNK_ID_OFFSET));
+        }
+        let copied_bytes: u64 = copied_chunks
+            .iter()
+            .map(|ix| manifest_new.chunk_table[*ix].size_bytes as u64)
+            .sum();
+        self.metrics
+            .state_sync_metrics
+            .size
+            .with_label_values(&[LABEL_COPY_LOCAL_CHUNKS])
+            .inc_by(copied_bytes);
+        copied_bytes
     }
 }
 
@@ -0,0 +1,198 @@
+//! Reuse of chunks from any state available on the local disk.
+//!
+//! The diff script only copies from a single old state: either the state sync
+//! cache or the latest checkpoint. A node rejoining after a long outage often
+//! holds more checkpoints whose chunks still match chunks of the synced state,
+//! so every chunk that would otherwise be fetched from peers is first looked
+//! up in all of them.
+//!
+//! Only states with a known manifest are indexed, by chunk hash. Computing a
+//! manifest for other states (e.g., backups and diverged checkpoints) would
+//! require hashing them entirely. Every chunk is validated against the new
+//! manifest before it is copied.
+
+use super::*;
+
+#[cfg(test)]
+mod tests;
+
+/// A content-addressed index of the chunks of local states that a state sync
+/// would otherwise fetch from peers.
+///
+/// The index only holds the locations of the wanted chunks, not the manifests
+/// of the indexed states, and is meant to be dropped once the chunks have
+/// been copied.
+pub(crate) struct LocalChunkIndex {
+    /// Hashes of the chunks to look up.
+    wanted: HashSet<[u8; 32]>,
+    /// Paths of the indexed files that contain at least one wanted chunk.
+    files: Vec<PathBuf>,
+    /// Locations of the wanted chunks by chunk hash. A location is a pair of
+    /// an index into `files` and an offset within that file.
+    chunks_by_hash: HashMap<[u8; 32], Vec<(usize, u64)>>,
+    /// Keeps the indexed checkpoints from being removed while the index is
+    /// alive.
+    #[allow(dead_code)]
+    checkpoint_layouts: Vec<CheckpointLayout<ReadOnly>>,
+    /// The number of indexed states.
+    num_states: usize,
+}
+
+impl LocalChunkIndex {
+    /// Creates an empty index for the chunks `chunks` (indices into the chunk
+    /// table of `manifest_new`).
+    pub(crate) fn new(manifest_new: &Manifest, chunks: &HashSet<usize>) -> Self {
+        Self {
+            wanted: chunks
+                .iter()
+                .map(|ix| manifest_new.chunk_table[*ix].hash)
+                .collect(),
+            files: Vec::new(),
+            chunks_by_hash: HashMap::new(),
+            checkpoint_layouts: Vec::new(),
+            num_states: 0,
+        }
+    }
+
+    /// Indexes the wanted chunks of the state at `root` with manifest
+    /// `manifest`, except `missing_chunks` (indices into its chunk table) that
+    /// are not on disk.
+    pub(crate) fn add_state(
+        &mut self,
+        root: &Path,
+        manifest: &Manifest,
+        missing_chunks: &HashSet<usize>,
+    ) {
+        let mut file_ids: HashMap<u32, usize> = HashMap::new();
+        for (chunk_index, chunk) in manifest.chunk_table.iter().enumerate() {
+            if !self.wanted.contains(&chunk.hash) || missing_chunks.contains(&chunk_index) {
+                continue;
+            }
+            let file_id = *file_ids.entry(chunk.file_index).or_insert_with(|| {
+                let relative_path = &manifest.file_table[chunk.file_index as usize].relative_path;
+                self.files.push(root.join(relative_path));
+                self.files.len() - 1
+            });
+            self.chunks_by_hash
+                .entry(chunk.hash)
+                .or_default()
+                .push((file_id, chunk.offset));
+        }
+        self.num_states += 1;
+    }
+
+    /// Indexes the wanted chunks of the checkpoint `checkpoint_layout` with
+    /// manifest `manifest` and keeps the checkpoint until the index is dropped.
+    pub(crate) fn add_checkpoint(
+        &mut self,
+        checkpoint_layout: &CheckpointLayout<ReadOnly>,
+        manifest: &Manifest,
+    ) {
+        self.add_state(checkpoint_layout.raw_path(), manifest, &HashSet::new());
+        self.checkpoint_layouts.push(checkpoint_layout.clone());
+    }
+
+    /// Copies the chunks `chunks` (indices into the chunk table of
+    /// `manifest_new`) that are available in any of the indexed states into
+    /// the state at `root_new`.
+    ///
+    /// Returns the indices of the copied chunks.
+    pub(crate) fn copy_chunks(
+        &self,
+        log: &ReplicaLogger,
+        metrics: &StateSyncMetrics,
+        thread_pool: &mut scoped_threadpool::Pool,
+        root_new: &Path,
+        manifest_new: &Manifest,
+        chunks: &HashSet<usize>,
+    ) -> HashSet<usize> {
+        let _timer = metrics
+            .step_duration
+            .with_label_values(&[LABEL_COPY_LOCAL_CHUNKS])
+            .start_timer();
+
+        // Group chunks by the destination file to lower the cost of opening files.
+        let mut chunk_groups: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+        for ix in chunks {
+            let chunk = &manifest_new.chunk_table[*ix];
+            if self.chunks_by_hash.contains_key(&chunk.hash) {
+                chunk_groups
+                    .entry(chunk.file_index as usize)
+                    .or_default()
+                    .push(*ix);
+            }
+        }
+
+        let copied_chunks = Arc::new(Mutex::new(HashSet::new()));
+
+        thread_pool.scoped(|scope| {
+            for (file_index, chunk_group) in chunk_groups.iter() {
+                let dst_path = root_new.join(&manifest_new.file_table[*file_index].relative_path);
+                let copied_chunks = Arc::clone(&copied_chunks);
+                scope.execute(move || {
+                    let dst = std::fs::OpenOptions::new()
+                        .write(true)
+                        .create(false)
+                        .open(&dst_path)
+                        .unwrap_or_else(|err| {
+                            fatal!(log, "Failed to open file {}: {}", dst_path.display(), err)
+                        });
+
+                    // Failing to open or read a source file is not an error,
+                    // the chunk is then fetched from peers instead.
+                    let mut src_files: HashMap<usize, Option<std::fs::File>> = HashMap::new();
+                    for ix in chunk_group {
+                        let chunk = &manifest_new.chunk_table[*ix];
+                        let mut buf = vec![0; chunk.size_bytes as usize];
+                        let found =
+                            self.chunks_by_hash[&chunk.hash]
+                                .iter()
+                                .any(|(file_id, offset)| {
+                                    let src = src_files.entry(*file_id).or_insert_with(|| {
+                                        std::fs::File::open(&self.files[*file_id]).ok()
+                                    });
+                                    match src {
+                                        Some(src) => {
+                                            src.read_exact_at(&mut buf, *offset).is_ok()
+                                                && crate::manifest::validate_chunk(
+                                                    *ix,
+                                                    &buf,
+                                                    manifest_new,
+                                                )
+                                                .is_ok()
+                                        }
+                                        None => false,
+                                    }
+                                });
+                        if !found {
+                            continue;
+                        }
+
+                        dst.write_all_at(&buf, chunk.offset).unwrap_or_else(|err| {
+                            fatal!(
+                                log,
+                                "Failed to write chunk (offset = {}, size = {}) to file {}: {}",
+                                chunk.offset,
+                                chunk.size_bytes,
+                                dst_path.display(),
+                                err
+                            )
+                        });
+                        metrics.remaining.sub(1);
+                        copied_chunks.lock().unwrap().insert(*ix);
+                    }
+                });
+            }
+        });
+
+        let copied_chunks = std::mem::take(&mut *copied_chunks.lock().unwrap());
+        info!(
+            log,
+            "state sync: copied {} out of {} chunks to fetch from {} local states",
+            copied_chunks.len(),
+            chunks.len(),
+            self.num_states
+        );
+        copied_chunks
+    }
+}
@@ -0,0 +1,108 @@
+use super::*;
+use crate::manifest::{compute_manifest, ManifestMetrics};
+use ic_logger::replica_logger::no_op_logger;
+use ic_metrics::MetricsRegistry;
+use ic_types::state_sync::CURRENT_STATE_SYNC_VERSION;
+use std::fs;
+
+const CHUNK_SIZE: u32 = 1024;
+
+fn manifest_of(root: &Path) -> Manifest {
+    compute_manifest(
+        &mut scoped_threadpool::Pool::new(1),
+        &ManifestMetrics::new(&MetricsRegistry::new()),
+        &no_op_logger(),
+        CURRENT_STATE_SYNC_VERSION,
+        &CheckpointLayout::new_untracked(root.to_path_buf(), Height::new(0)).unwrap(),
+        CHUNK_SIZE,
+        None,
+    )
+    .expect("failed to compute manifest")
+}
+
+fn chunk(byte: u8) -> Vec<u8> {
+    vec![byte; CHUNK_SIZE as usize]
+}
+
+#[test]
+fn copies_chunks_by_hash() {
+    let new = tempfile::TempDir::new().unwrap();
+    fs::write(
+        new.path().join("file"),
+        [chunk(1), chunk(2), chunk(3)].concat(),
+    )
+    .unwrap();
+    let manifest_new = manifest_of(new.path());
+
+    // A checkpoint with a manifest containing the second chunk at a different
+    // path and offset.
+    let checkpoint = tempfile::TempDir::new().unwrap();
+    fs::write(
+        checkpoint.path().join("other_file"),
+        [chunk(4), chunk(2)].concat(),
+    )
+    .unwrap();
+    let manifest_checkpoint = manifest_of(checkpoint.path());
+
+    // Another state containing the third chunk at the same path and offset.
+    let other = tempfile::TempDir::new().unwrap();
+    fs::write(
+        other.path().join("file"),
+        [chunk(5), chunk(5), chunk(3)].concat(),
+    )
+    .unwrap();
+    let manifest_other = manifest_of(other.path());
+
+    let synced = tempfile::TempDir::new().unwrap();
+    IncompleteState::preallocate_layout(&no_op_logger(), synced.path(), &manifest_new);
+
+    let chunks = (0..3).collect();
+    let mut index = LocalChunkIndex::new(&manifest_new, &chunks);
+    index.add_state(checkpoint.path(), &manifest_checkpoint, &HashSet::new());
+    index.add_state(other.path(), &manifest_other, &HashSet::new());
+    let copied_chunks = index.copy_chunks(
+        &no_op_logger(),
+        &StateSyncMetrics::new(&MetricsRegistry::new()),
+        &mut scoped_threadpool::Pool::new(2),
+        synced.path(),
+        &manifest_new,
+        &chunks,
+    );
+
+    assert_eq!(copied_chunks, [1, 2].into_iter().collect());
+    assert_eq!(
+        fs::read(synced.path().join("file")).unwrap(),
+        [chunk(0), chunk(2), chunk(3)].concat()
+    );
+}
+
+#[test]
+fn does_not_copy_missing_chunks() {
+    let new = tempfile::TempDir::new().unwrap();
+    fs::write(new.path().join("file"), chunk(1)).unwrap();
+    let manifest_new = manifest_of(new.path());
+
+    // A state sync cache entry whose only chunk is listed in the manifest but
+    // has not been fetched yet.
+    let cache = tempfile::TempDir::new().unwrap();
+    fs::write(cache.path().join("file"), chunk(1)).unwrap();
+    let manifest_cache = manifest_of(cache.path());
+
+    let synced = tempfile::TempDir::new().unwrap();
+    IncompleteState::preallocate_layout(&no_op_logger(), synced.path(), &manifest_new);
+
+    let chunks = [0].into_iter().collect();
+    let mut index = LocalChunkIndex::new(&manifest_new, &chunks);
+    index.add_state(cache.path(), &manifest_cache, &[0].into_iter().collect());
+    let copied_chunks = index.copy_chunks(
+        &no_op_logger(),
+        &StateSyncMetrics::new(&MetricsRegistry::new()),
+        &mut scoped_threadpool::Pool::new(1),
+        synced.path(),
+        &manifest_new,
+        &chunks,
+    );
+
+    assert!(copied_chunks.is_empty());
+    assert_eq!(fs::read(synced.path().join("file")).unwrap(), chunk(0));
+}
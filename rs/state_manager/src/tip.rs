@@ -490,6 +490,12 @@ fn flush_snapshot_changes<T>(
             SnapshotOperation::Restore(canister_id, snapshot_id) => {
                 restore(log, layout, canister_id, snapshot_id)?;
             }
+            SnapshotOperation::Upload(_canister_id, snapshot_id)
+            | SnapshotOperation::UploadData(_canister_id, snapshot_id) => {
+                // There are no canister files to copy, only create the snapshot directory for
+                // the `PageMap`s to be flushed into. The rest is written at the next checkpoint.
+                layout.snapshot(&snapshot_id)?;
+            }
         }
     }
 
@@ -1075,6 +1081,8 @@ fn serialize_canister_to_tip(
                 .system_state
                 .task_queue
                 .peek_hook_status(),
+            environment_variables: canister_state.system_state.environment_variables.clone(),
+            operators: canister_state.system_state.operators.clone(),
         }
         .into(),
     )?;
@@ -1105,6 +1113,8 @@ fn serialize_snapshot_to_tip(
             wasm_memory_size: canister_snapshot.wasm_memory().size,
             total_size: canister_snapshot.size(),
             exported_globals: canister_snapshot.exported_globals().clone(),
+            declared_wasm_chunk_hashes: canister_snapshot.declared_wasm_chunk_hashes().clone(),
+            source: canister_snapshot.source(),
         }
         .into(),
     )?;
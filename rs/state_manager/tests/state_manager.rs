@@ -24,10 +24,10 @@ use ic_metrics::MetricsRegistry;
 use ic_registry_subnet_features::SubnetFeatures;
 use ic_registry_subnet_type::SubnetType;
 use ic_replicated_state::{
-    canister_snapshots::CanisterSnapshot,
+    canister_snapshots::{CanisterSnapshot, ExecutionStateSnapshot, PageMemory, SnapshotSource},
     canister_state::{execution_state::WasmBinary, system_state::wasm_chunk_store::WasmChunkStore},
     metadata_state::ApiBoundaryNodeEntry,
-    page_map::{PageIndex, Shard, StorageLayout},
+    page_map::{PageIndex, Shard, StorageLayout, TestPageAllocatorFileDescriptorImpl},
     testing::ReplicatedStateTesting,
     ExecutionState, ExportedFunctions, Memory, NetworkTopology, NumWasmPages, PageMap,
     ReplicatedState, Stream, SubnetTopology,
@@ -71,6 +71,7 @@ use ic_types::{
     CanisterId, CryptoHashOfPartialState, CryptoHashOfState, Height, NodeId, NumBytes, PrincipalId,
 };
 use ic_types::{epoch_from_height, QueryStatsEpoch};
+use ic_wasm_types::CanisterModule;
 use maplit::btreemap;
 use nix::sys::time::TimeValLike;
 use nix::sys::{
@@ -4257,6 +4258,7 @@ fn certified_read_can_certify_node_public_keys_since_v12() {
                 subnet_type: SubnetType::System,
                 subnet_features: SubnetFeatures::default(),
                 idkg_keys_held: BTreeSet::new(),
+                replica_version: String::new(),
             },
         );
 
@@ -6463,6 +6465,128 @@ fn can_create_and_restore_snapshot() {
     can_create_and_restore_snapshot_impl(CertificationScope::Full);
 }
 
+#[test]
+fn can_checkpoint_and_reload_uploaded_snapshot() {
+    fn can_checkpoint_and_reload_uploaded_snapshot_impl(certification_scope: CertificationScope) {
+        state_manager_restart_test(|state_manager, restart_fn| {
+            let canister_id = canister_test_id(100);
+            let snapshot_id = SnapshotId::from((canister_id, 0));
+            let fd_factory = Arc::new(TestPageAllocatorFileDescriptorImpl::new());
+
+            // Upload the metadata of a snapshot.
+            let (_height, mut state) = state_manager.take_tip();
+            insert_dummy_canister(&mut state, canister_id);
+            let mut new_snapshot = CanisterSnapshot::new(
+                SnapshotSource::MetadataUpload,
+                canister_id,
+                state.time(),
+                0,
+                vec![],
+                WasmChunkStore::new_for_testing(),
+                ExecutionStateSnapshot {
+                    wasm_binary: CanisterModule::new(vec![]),
+                    exported_globals: vec![],
+                    stable_memory: PageMemory {
+                        page_map: PageMap::new_for_testing(),
+                        size: NumWasmPages::new(1),
+                    },
+                    wasm_memory: PageMemory {
+                        page_map: PageMap::new_for_testing(),
+                        size: NumWasmPages::new(1),
+                    },
+                },
+                NumBytes::new(10),
+            );
+            new_snapshot.start_wasm_module_upload(10, fd_factory.clone());
+            new_snapshot.set_declared_wasm_chunk_hashes(BTreeSet::from([[1; 32]]));
+            state
+                .canister_snapshots
+                .push_uploaded(snapshot_id, Arc::new(new_snapshot));
+            state_manager.commit_and_certify(state, height(1), certification_scope.clone(), None);
+
+            // Upload the data of the snapshot over two rounds, flushing the page maps in between.
+            let (_height, mut state) = state_manager.take_tip();
+            let snapshot = Arc::make_mut(state.canister_snapshots.get_mut(snapshot_id).unwrap());
+            snapshot.write_wasm_module(0, &[1, 2, 3], fd_factory);
+            snapshot
+                .execution_snapshot_mut()
+                .wasm_memory
+                .page_map
+                .update(&[(PageIndex::new(0), &[4u8; PAGE_SIZE])]);
+            state
+                .canister_snapshots
+                .add_upload_data_operation(canister_id, snapshot_id);
+            state_manager.commit_and_certify(state, height(2), certification_scope.clone(), None);
+
+            let (_height, mut state) = state_manager.take_tip();
+            let snapshot = Arc::make_mut(state.canister_snapshots.get_mut(snapshot_id).unwrap());
+            snapshot
+                .execution_snapshot_mut()
+                .stable_memory
+                .page_map
+                .update(&[(PageIndex::new(0), &[5u8; PAGE_SIZE])]);
+            snapshot
+                .execution_snapshot_mut()
+                .wasm_memory
+                .page_map
+                .update(&[(PageIndex::new(1), &[6u8; PAGE_SIZE])]);
+            let chunk_hash = snapshot
+                .chunk_store_mut()
+                .insert_chunk(NumBytes::new(1 << 20), &[7u8; 100])
+                .unwrap();
+            state
+                .canister_snapshots
+                .add_upload_data_operation(canister_id, snapshot_id);
+            state_manager.commit_and_certify(state, height(3), CertificationScope::Full, None);
+            wait_for_checkpoint(&state_manager, height(3));
+
+            // The snapshot is loaded from the checkpoint after a restart.
+            let state_manager = restart_fn(state_manager, Some(height(3)));
+            let (_height, state) = state_manager.take_tip();
+            let snapshot = state.canister_snapshots.get(snapshot_id).unwrap();
+            assert_eq!(snapshot.source(), SnapshotSource::MetadataUpload);
+            assert!(!snapshot.is_uploading_wasm_module());
+            assert_eq!(
+                snapshot.canister_module().as_slice(),
+                &[1, 2, 3, 0, 0, 0, 0, 0, 0, 0]
+            );
+            assert_eq!(
+                snapshot.wasm_memory().page_map.get_page(PageIndex::new(0)),
+                &[4u8; PAGE_SIZE]
+            );
+            assert_eq!(
+                snapshot.wasm_memory().page_map.get_page(PageIndex::new(1)),
+                &[6u8; PAGE_SIZE]
+            );
+            assert_eq!(
+                snapshot
+                    .chunk_store()
+                    .get_chunk_data(&chunk_hash)
+                    .unwrap()
+                    .flatten()
+                    .copied()
+                    .collect::<Vec<_>>(),
+                vec![7u8; 100]
+            );
+            assert_eq!(
+                snapshot
+                    .stable_memory()
+                    .page_map
+                    .get_page(PageIndex::new(0)),
+                &[5u8; PAGE_SIZE]
+            );
+            assert_eq!(
+                snapshot.declared_wasm_chunk_hashes(),
+                &BTreeSet::from([[1; 32]])
+            );
+        });
+    }
+
+    // Upload the data within the same checkpoint interval as the metadata, and in the next one.
+    can_checkpoint_and_reload_uploaded_snapshot_impl(CertificationScope::Metadata);
+    can_checkpoint_and_reload_uploaded_snapshot_impl(CertificationScope::Full);
+}
+
 #[test]
 fn restore_heap_from_snapshot() {
     let env = StateMachineBuilder::new()
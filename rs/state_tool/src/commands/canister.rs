@@ -0,0 +1,437 @@
+//! Lists, exports and diffs individual canisters of checkpoints.
+
+use crate::commands::export_canister::write_memory;
+use ic_replicated_state::{
+    canister_state::WASM_PAGE_SIZE_IN_BYTES,
+    page_map::{PageMap, TestPageAllocatorFileDescriptorImpl},
+    CanisterState, Memory, NumWasmPages,
+};
+use ic_state_layout::{CanisterLayout, CompleteCheckpointLayout, ReadOnly};
+use ic_state_manager::{
+    checkpoint::{load_canister_state, load_snapshot},
+    CheckpointMetrics,
+};
+use ic_sys::PageIndex;
+use ic_types::{CanisterId, Height, PrincipalId};
+use serde::Serialize;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// The name of the file describing the contents of an exported bundle.
+const BUNDLE_MANIFEST_FILE: &str = "manifest.json";
+
+fn open_checkpoint(path: &Path) -> Result<CompleteCheckpointLayout, String> {
+    CompleteCheckpointLayout::new_untracked(path.to_path_buf(), Height::new(0))
+        .map_err(|e| format!("failed to create checkpoint layout: {}", e))
+}
+
+fn canister_layout(
+    cp_layout: &CompleteCheckpointLayout,
+    canister_id: &CanisterId,
+) -> Result<CanisterLayout<ReadOnly>, String> {
+    cp_layout
+        .canister(canister_id)
+        .map_err(|e| format!("failed to create canister layout: {}", e))
+}
+
+/// Loads the state of the canister `canister_id` from the checkpoint
+/// `cp_layout`.
+pub(crate) fn load_canister(
+    cp_layout: &CompleteCheckpointLayout,
+    canister_id: &CanisterId,
+) -> Result<CanisterState, String> {
+    let dummy_metrics_registry = ic_metrics::MetricsRegistry::new();
+    let dummy_metrics = CheckpointMetrics::new(&dummy_metrics_registry, crate::commands::logger());
+    load_canister_state(
+        &canister_layout(cp_layout, canister_id)?,
+        canister_id,
+        cp_layout.height(),
+        Arc::new(TestPageAllocatorFileDescriptorImpl::new()),
+        &dummy_metrics,
+    )
+    .map(|(canister_state, _)| canister_state)
+    .map_err(|e| {
+        format!(
+            "failed to load canister {} from checkpoint at {}: {}",
+            canister_id,
+            cp_layout.raw_path().display(),
+            e
+        )
+    })
+}
+
+fn memory_bytes(size: NumWasmPages) -> usize {
+    size.get() * WASM_PAGE_SIZE_IN_BYTES
+}
+
+/// Returns the sizes of the Wasm and stable memory of the canister in bytes.
+fn memory_sizes(canister_state: &CanisterState) -> (usize, usize) {
+    canister_state
+        .execution_state
+        .as_ref()
+        .map(|es| {
+            (
+                memory_bytes(es.wasm_memory.size),
+                memory_bytes(es.stable_memory.size),
+            )
+        })
+        .unwrap_or_default()
+}
+
+fn format_controllers(canister_state: &CanisterState) -> String {
+    canister_state
+        .system_state
+        .controllers
+        .iter()
+        .map(|c| c.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// `canister list` command entry point: prints a summary of every canister
+/// in the checkpoint at `path`.
+pub fn do_list(path: PathBuf) -> Result<(), String> {
+    let cp_layout = open_checkpoint(&path)?;
+    let canister_ids = cp_layout
+        .canister_ids()
+        .map_err(|e| format!("failed to enumerate canisters: {}", e))?;
+
+    println!(
+        "{:<27}  {:<8}  {:>30}  {:>12}  {:>12}  {:>7}  {:>7}  {:>7}  CONTROLLERS",
+        "CANISTER_ID",
+        "STATUS",
+        "CYCLES",
+        "WASM_MEMORY",
+        "STABLE_MEM",
+        "INGRESS",
+        "INPUT",
+        "OUTPUT"
+    );
+    for canister_id in canister_ids {
+        let canister_state = load_canister(&cp_layout, &canister_id)?;
+        let (wasm_memory, stable_memory) = memory_sizes(&canister_state);
+        let queues = canister_state.system_state.queues();
+        println!(
+            "{:<27}  {:<8}  {:>30}  {:>12}  {:>12}  {:>7}  {:>7}  {:>7}  {}",
+            canister_id.to_string(),
+            canister_state.status().to_string(),
+            canister_state.system_state.balance().get(),
+            wasm_memory,
+            stable_memory,
+            queues.ingress_queue_message_count(),
+            queues.input_queues_message_count(),
+            queues.output_queues_message_count(),
+            format_controllers(&canister_state),
+        );
+    }
+
+    Ok(())
+}
+
+/// The description of an exported canister written to [`BUNDLE_MANIFEST_FILE`].
+#[derive(Serialize)]
+struct BundleManifest {
+    canister_id: String,
+    checkpoint: PathBuf,
+    status: String,
+    canister_version: u64,
+    cycles_balance: u128,
+    controllers: Vec<String>,
+    /// The SHA-256 hash of the Wasm module, if any.
+    wasm_module_hash: Option<String>,
+    /// The pretty-printed `CanisterStateBits` protobuf.
+    system_state_file: String,
+    /// The pretty-printed `CanisterQueues` protobuf.
+    queues_file: String,
+    memory: Option<MemoryFiles>,
+    snapshots: Vec<SnapshotManifest>,
+}
+
+/// The files containing the Wasm module and memories of a canister or of a
+/// snapshot.
+#[derive(Serialize)]
+struct MemoryFiles {
+    wasm_module_file: String,
+    wasm_memory_file: String,
+    wasm_memory_size: u64,
+    stable_memory_file: String,
+    stable_memory_size: u64,
+}
+
+#[derive(Serialize)]
+struct SnapshotManifest {
+    snapshot_id: String,
+    taken_at_timestamp_nanos: u64,
+    canister_version: u64,
+    memory: MemoryFiles,
+}
+
+fn write_file(dir: &Path, name: &str, contents: impl AsRef<[u8]>) -> Result<String, String> {
+    let path = dir.join(name);
+    std::fs::write(&path, contents)
+        .map_err(|e| format!("failed to write {}: {}", path.display(), e))?;
+    Ok(name.to_string())
+}
+
+/// Writes the Wasm module and memories into `dir` relative to `root`. The
+/// memories are written page by page so that they are never held in memory.
+fn write_memories(
+    root: &Path,
+    dir: &str,
+    wasm_module: &[u8],
+    wasm_memory: &Memory,
+    stable_memory: &Memory,
+) -> Result<MemoryFiles, String> {
+    let path = root.join(dir);
+    std::fs::create_dir_all(&path)
+        .map_err(|e| format!("failed to create {}: {}", path.display(), e))?;
+    let wasm_memory_size = write_memory(wasm_memory, &path.join("wasm_memory.bin"))?;
+    let stable_memory_size = write_memory(stable_memory, &path.join("stable_memory.bin"))?;
+    let prefixed = |name: &str| Path::new(dir).join(name).display().to_string();
+    Ok(MemoryFiles {
+        wasm_module_file: prefixed(&write_file(&path, "wasm_module.wasm", wasm_module)?),
+        wasm_memory_file: prefixed("wasm_memory.bin"),
+        wasm_memory_size,
+        stable_memory_file: prefixed("stable_memory.bin"),
+        stable_memory_size,
+    })
+}
+
+/// `canister export` command entry point: writes the Wasm module, memories,
+/// snapshots and system state of the canister `canister_id` in the checkpoint
+/// at `path` into the directory `output`, described by a `manifest.json`.
+pub fn do_export(path: PathBuf, canister_id: PrincipalId, output: PathBuf) -> Result<(), String> {
+    let canister_id = CanisterId::unchecked_from_principal(canister_id);
+    let cp_layout = open_checkpoint(&path)?;
+    let canister_layout = canister_layout(&cp_layout, &canister_id)?;
+    let canister_state = load_canister(&cp_layout, &canister_id)?;
+
+    std::fs::create_dir_all(&output)
+        .map_err(|e| format!("failed to create {}: {}", output.display(), e))?;
+
+    let system_state = canister_layout
+        .canister()
+        .deserialize()
+        .map_err(|e| format!("failed to read canister state bits: {}", e))?;
+    let system_state_file =
+        write_file(&output, "system_state.txt", format!("{:#?}", system_state))?;
+    let queues = canister_layout
+        .queues()
+        .deserialize()
+        .map_err(|e| format!("failed to read canister queues: {}", e))?;
+    let queues_file = write_file(&output, "queues.txt", format!("{:#?}", queues))?;
+
+    let memory = match &canister_state.execution_state {
+        Some(es) => Some(write_memories(
+            &output,
+            "",
+            es.wasm_binary.binary.as_slice(),
+            &es.wasm_memory,
+            &es.stable_memory,
+        )?),
+        None => None,
+    };
+
+    let mut snapshots = vec![];
+    let snapshot_ids = cp_layout
+        .snapshot_ids()
+        .map_err(|e| format!("failed to enumerate snapshots: {}", e))?;
+    for snapshot_id in snapshot_ids
+        .into_iter()
+        .filter(|id| id.get_canister_id() == canister_id)
+    {
+        let snapshot_layout = cp_layout
+            .snapshot(&snapshot_id)
+            .map_err(|e| format!("failed to create snapshot layout: {}", e))?;
+        let (snapshot, _) = load_snapshot(
+            &snapshot_layout,
+            &snapshot_id,
+            cp_layout.height(),
+            Arc::new(TestPageAllocatorFileDescriptorImpl::new()),
+        )
+        .map_err(|e| format!("failed to load snapshot {}: {}", snapshot_id, e))?;
+        let memory = write_memories(
+            &output,
+            &format!("snapshots/{}", hex::encode(snapshot_id.as_slice())),
+            snapshot.canister_module().as_slice(),
+            &Memory::from(snapshot.wasm_memory()),
+            &Memory::from(snapshot.stable_memory()),
+        )?;
+        snapshots.push(SnapshotManifest {
+            snapshot_id: snapshot_id.to_string(),
+            taken_at_timestamp_nanos: snapshot.taken_at_timestamp().as_nanos_since_unix_epoch(),
+            canister_version: snapshot.canister_version(),
+            memory,
+        });
+    }
+
+    let manifest = BundleManifest {
+        canister_id: canister_id.to_string(),
+        checkpoint: path,
+        status: canister_state.status().to_string(),
+        canister_version: canister_state.system_state.canister_version,
+        cycles_balance: canister_state.system_state.balance().get(),
+        controllers: canister_state
+            .system_state
+            .controllers
+            .iter()
+            .map(|c| c.to_string())
+            .collect(),
+        wasm_module_hash: canister_state
+            .execution_state
+            .as_ref()
+            .map(|es| hex::encode(es.wasm_binary.binary.module_hash())),
+        system_state_file,
+        queues_file,
+        memory,
+        snapshots,
+    };
+    let manifest = serde_json::to_string_pretty(&manifest)
+        .map_err(|e| format!("failed to serialize bundle manifest: {}", e))?;
+    write_file(&output, BUNDLE_MANIFEST_FILE, manifest)?;
+
+    println!("Exported canister {} to {}", canister_id, output.display());
+    Ok(())
+}
+
+/// Returns the ranges of indices of the OS pages that differ between `a` and
+/// `b`. Pages beyond the end of a page map are zero.
+fn changed_pages(a: &PageMap, b: &PageMap) -> Vec<Range<u64>> {
+    let num_pages = a.num_host_pages().max(b.num_host_pages()) as u64;
+    let mut ranges: Vec<Range<u64>> = vec![];
+    for i in 0..num_pages {
+        let index = PageIndex::new(i);
+        if a.get_page(index) != b.get_page(index) {
+            match ranges.last_mut() {
+                Some(range) if range.end == i => range.end = i + 1,
+                _ => ranges.push(i..i + 1),
+            }
+        }
+    }
+    ranges
+}
+
+fn print_memory_diff(name: &str, a: Option<&Memory>, b: Option<&Memory>) {
+    let empty = Memory::new_for_testing();
+    let (a, b) = (a.unwrap_or(&empty), b.unwrap_or(&empty));
+    let ranges = changed_pages(&a.page_map, &b.page_map);
+    let num_changed: u64 = ranges.iter().map(|r| r.end - r.start).sum();
+    if a.size != b.size {
+        println!("{}: size {} -> {} Wasm pages", name, a.size, b.size);
+    }
+    if ranges.is_empty() {
+        println!("{}: no changed pages", name);
+        return;
+    }
+    println!(
+        "{}: {} changed pages: {}",
+        name,
+        num_changed,
+        ranges
+            .iter()
+            .map(|r| if r.end - r.start == 1 {
+                r.start.to_string()
+            } else {
+                format!("{}-{}", r.start, r.end - 1)
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+}
+
+fn print_field_diff(name: &str, a: impl ToString, b: impl ToString) {
+    let (a, b) = (a.to_string(), b.to_string());
+    if a != b {
+        println!("{}: {} -> {}", name, a, b);
+    }
+}
+
+/// `canister diff` command entry point: prints the changes of the canister
+/// `canister_id` between the checkpoints at `path_a` and `path_b`.
+pub fn do_diff(path_a: PathBuf, path_b: PathBuf, canister_id: PrincipalId) -> Result<(), String> {
+    let canister_id = CanisterId::unchecked_from_principal(canister_id);
+    let a = load_canister(&open_checkpoint(&path_a)?, &canister_id)?;
+    let b = load_canister(&open_checkpoint(&path_b)?, &canister_id)?;
+
+    print_field_diff("status", a.status(), b.status());
+    print_field_diff(
+        "canister version",
+        a.system_state.canister_version,
+        b.system_state.canister_version,
+    );
+    print_field_diff(
+        "cycles balance",
+        a.system_state.balance().get(),
+        b.system_state.balance().get(),
+    );
+    print_field_diff(
+        "controllers",
+        format_controllers(&a),
+        format_controllers(&b),
+    );
+    let module_hash = |c: &CanisterState| {
+        c.execution_state
+            .as_ref()
+            .map(|es| hex::encode(es.wasm_binary.binary.module_hash()))
+            .unwrap_or_else(|| "none".to_string())
+    };
+    print_field_diff("wasm module hash", module_hash(&a), module_hash(&b));
+
+    let (es_a, es_b) = (a.execution_state.as_ref(), b.execution_state.as_ref());
+    print_memory_diff(
+        "wasm memory",
+        es_a.map(|es| &es.wasm_memory),
+        es_b.map(|es| &es.wasm_memory),
+    );
+    print_memory_diff(
+        "stable memory",
+        es_a.map(|es| &es.stable_memory),
+        es_b.map(|es| &es.stable_memory),
+    );
+    let chunk_store_ranges = changed_pages(
+        a.system_state.wasm_chunk_store.page_map(),
+        b.system_state.wasm_chunk_store.page_map(),
+    );
+    if !chunk_store_ranges.is_empty() {
+        println!(
+            "wasm chunk store: {} changed pages",
+            chunk_store_ranges
+                .iter()
+                .map(|r| r.end - r.start)
+                .sum::<u64>()
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ic_sys::PAGE_SIZE;
+
+    #[test]
+    fn changed_pages_merges_adjacent_pages() {
+        let mut bytes = vec![0; 8 * PAGE_SIZE];
+        let a = PageMap::from(bytes.as_slice());
+        bytes[PAGE_SIZE] = 1;
+        bytes[2 * PAGE_SIZE + 7] = 1;
+        bytes[5 * PAGE_SIZE] = 1;
+        let b = PageMap::from(bytes.as_slice());
+        assert_eq!(changed_pages(&a, &b), vec![1..3, 5..6]);
+        assert_eq!(changed_pages(&a, &a), vec![]);
+    }
+
+    #[test]
+    fn changed_pages_treats_missing_pages_as_zero() {
+        let a = PageMap::from(&[0u8; PAGE_SIZE][..]);
+        let mut bytes = vec![0; 3 * PAGE_SIZE];
+        assert_eq!(changed_pages(&a, &PageMap::from(bytes.as_slice())), vec![]);
+        bytes[2 * PAGE_SIZE] = 1;
+        assert_eq!(
+            changed_pages(&a, &PageMap::from(bytes.as_slice())),
+            vec![2..3]
+        );
+    }
+}
@@ -0,0 +1,171 @@
+//! Exports the state of a single canister from a checkpoint into a directory
+//! that can be used to seed a PocketIC instance.
+
+use candid::CandidType;
+use ic_management_canister_types::{ChunkHash, Global, ReadCanisterSnapshotMetadataResponse};
+use ic_replicated_state::canister_state::{execution_state::Memory, WASM_PAGE_SIZE_IN_BYTES};
+use ic_state_layout::CompleteCheckpointLayout;
+use ic_sys::{PageIndex, PAGE_SIZE};
+use ic_types::{CanisterId, Height, PrincipalId};
+use serde::Deserialize;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// The file containing the Candid encoding of [`ExportedCanister`].
+pub const METADATA_FILE: &str = "metadata.bin";
+/// The file containing the Wasm module.
+pub const WASM_MODULE_FILE: &str = "wasm_module.wasm";
+/// The file containing the Wasm memory.
+pub const WASM_MEMORY_FILE: &str = "wasm_memory.bin";
+/// The file containing the stable memory.
+pub const STABLE_MEMORY_FILE: &str = "stable_memory.bin";
+/// The directory containing one file per chunk of the Wasm chunk store, named
+/// after the hex-encoded hash of the chunk.
+pub const WASM_CHUNKS_DIR: &str = "wasm_chunks";
+
+/// The metadata of a canister exported from a checkpoint.
+///
+/// The directory written by `export_canister` contains the Candid encoding of
+/// this struct in [`METADATA_FILE`] and the Wasm module, memories and Wasm
+/// chunks in separate files, so that none of them has to be held in memory at
+/// once. The snapshot metadata and data follow the format expected by the
+/// `upload_canister_snapshot_metadata` and `upload_canister_snapshot_data`
+/// management canister methods, so that the canister can be recreated by
+/// uploading and loading a snapshot.
+#[derive(Clone, PartialEq, Debug, CandidType, Deserialize)]
+pub struct ExportedCanister {
+    pub canister_id: PrincipalId,
+    pub controllers: Vec<PrincipalId>,
+    pub cycles_balance: u128,
+    pub compute_allocation: u64,
+    pub memory_allocation: u64,
+    pub freezing_threshold: u64,
+    pub reserved_cycles_limit: Option<u128>,
+    pub wasm_memory_limit: Option<u64>,
+    pub snapshot_metadata: ReadCanisterSnapshotMetadataResponse,
+}
+
+fn create_file(path: &Path) -> Result<BufWriter<File>, String> {
+    File::create(path)
+        .map(BufWriter::new)
+        .map_err(|e| format!("failed to create {}: {}", path.display(), e))
+}
+
+fn write_all<'a>(path: &Path, slices: impl IntoIterator<Item = &'a [u8]>) -> Result<(), String> {
+    let mut writer = create_file(path)?;
+    for slice in slices {
+        writer
+            .write_all(slice)
+            .map_err(|e| format!("failed to write {}: {}", path.display(), e))?;
+    }
+    writer
+        .flush()
+        .map_err(|e| format!("failed to write {}: {}", path.display(), e))
+}
+
+/// Writes the contents of `memory` into the file at `path` page by page and
+/// returns the size of the memory in bytes.
+pub(crate) fn write_memory(memory: &Memory, path: &Path) -> Result<u64, String> {
+    let num_pages = memory.size.get() * WASM_PAGE_SIZE_IN_BYTES / PAGE_SIZE;
+    write_all(
+        path,
+        (0..num_pages).map(|i| &memory.page_map.get_page(PageIndex::new(i as u64))[..]),
+    )?;
+    Ok((num_pages * PAGE_SIZE) as u64)
+}
+
+/// Exports the state of the canister `canister_id` in the checkpoint at
+/// `path` into the directory `output`.
+pub fn do_export_canister(
+    path: PathBuf,
+    canister_id: PrincipalId,
+    output: PathBuf,
+) -> Result<(), String> {
+    let canister_id = CanisterId::unchecked_from_principal(canister_id);
+    let cp_layout = CompleteCheckpointLayout::new_untracked(path, Height::new(0))
+        .map_err(|e| format!("failed to create checkpoint layout: {}", e))?;
+    let batch_time_nanos = cp_layout
+        .system_metadata()
+        .deserialize()
+        .map_err(|e| format!("failed to read system metadata: {}", e))?
+        .batch_time_nanos;
+
+    let canister_state = crate::commands::canister::load_canister(&cp_layout, &canister_id)?;
+    let system_state = &canister_state.system_state;
+    let execution_state = canister_state
+        .execution_state
+        .as_ref()
+        .ok_or_else(|| format!("canister {} has no Wasm module installed", canister_id))?;
+
+    let wasm_chunks_dir = output.join(WASM_CHUNKS_DIR);
+    std::fs::create_dir_all(&wasm_chunks_dir)
+        .map_err(|e| format!("failed to create {}: {}", wasm_chunks_dir.display(), e))?;
+
+    let wasm_module = execution_state.wasm_binary.binary.as_slice();
+    write_all(&output.join(WASM_MODULE_FILE), [wasm_module])?;
+    let wasm_memory_size =
+        write_memory(&execution_state.wasm_memory, &output.join(WASM_MEMORY_FILE))?;
+    let stable_memory_size = write_memory(
+        &execution_state.stable_memory,
+        &output.join(STABLE_MEMORY_FILE),
+    )?;
+    let wasm_chunk_store = &system_state.wasm_chunk_store;
+    let chunk_hashes: Vec<_> = wasm_chunk_store.keys().cloned().collect();
+    for hash in &chunk_hashes {
+        let pages = wasm_chunk_store
+            .get_chunk_data(hash)
+            .ok_or_else(|| format!("wasm chunk {} not found", hex::encode(hash)))?;
+        write_all(
+            &wasm_chunks_dir.join(format!("{}.bin", hex::encode(hash))),
+            pages,
+        )?;
+    }
+
+    let exported_canister = ExportedCanister {
+        canister_id: canister_id.get(),
+        controllers: system_state.controllers.iter().cloned().collect(),
+        cycles_balance: system_state.balance().get(),
+        compute_allocation: canister_state
+            .scheduler_state
+            .compute_allocation
+            .as_percent(),
+        memory_allocation: system_state.memory_allocation.bytes().get(),
+        freezing_threshold: system_state.freeze_threshold.get(),
+        reserved_cycles_limit: system_state.reserved_balance_limit().map(|c| c.get()),
+        wasm_memory_limit: system_state.wasm_memory_limit.map(|b| b.get()),
+        snapshot_metadata: ReadCanisterSnapshotMetadataResponse {
+            taken_at_timestamp: batch_time_nanos,
+            canister_version: system_state.canister_version,
+            wasm_module_size: wasm_module.len() as u64,
+            exported_globals: execution_state
+                .exported_globals
+                .iter()
+                .map(Global::from)
+                .collect(),
+            wasm_memory_size,
+            stable_memory_size,
+            wasm_chunk_store: chunk_hashes
+                .iter()
+                .map(|hash| ChunkHash {
+                    hash: hash.to_vec(),
+                })
+                .collect(),
+            certified_data: system_state.certified_data.clone(),
+        },
+    };
+
+    let bytes = candid::encode_one(&exported_canister)
+        .map_err(|e| format!("failed to encode canister {}: {}", canister_id, e))?;
+    write_all(&output.join(METADATA_FILE), [bytes.as_slice()])?;
+
+    println!(
+        "Exported canister {} ({} bytes of Wasm memory, {} bytes of stable memory) to {}",
+        canister_id,
+        wasm_memory_size,
+        stable_memory_size,
+        output.display()
+    );
+
+    Ok(())
+}
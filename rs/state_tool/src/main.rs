@@ -76,6 +76,25 @@ enum Opt {
         file: PathBuf,
     },
 
+    /// Exports the state of a single canister from a checkpoint into a
+    /// directory that can be loaded into a PocketIC instance.
+    #[clap(name = "export_canister")]
+    ExportCanister {
+        /// Path to a checkpoint.
+        #[clap(long = "state")]
+        path: PathBuf,
+        /// ID of the canister to export.
+        #[clap(long = "canister_id")]
+        canister_id: PrincipalId,
+        /// Path to the directory to write the exported canister state to.
+        #[clap(long = "output")]
+        output: PathBuf,
+    },
+
+    /// Inspects, exports and compares single canisters of checkpoints.
+    #[clap(name = "canister", subcommand)]
+    Canister(CanisterOpt),
+
     /// Converts textual principal representation to hex.
     #[clap(name = "canister_id_to_hex")]
     CanisterIdToHex {
@@ -149,6 +168,47 @@ enum Opt {
     },
 }
 
+/// Subcommands of `state_tool canister`.
+#[derive(Debug, clap::Subcommand)]
+enum CanisterOpt {
+    /// Lists the canisters of a checkpoint with their memory sizes, cycles,
+    /// queue depths and controllers.
+    #[clap(name = "list")]
+    List {
+        /// Path to a checkpoint.
+        #[clap(long = "state")]
+        path: PathBuf,
+    },
+
+    /// Exports the Wasm module, memories, snapshots and system state of a
+    /// canister into a directory, described by a `manifest.json` file.
+    #[clap(name = "export")]
+    Export {
+        /// Path to a checkpoint.
+        #[clap(long = "state")]
+        path: PathBuf,
+        /// ID of the canister to export.
+        #[clap(long = "canister_id")]
+        canister_id: PrincipalId,
+        /// Path to the directory to write the bundle to.
+        #[clap(long = "output")]
+        output: PathBuf,
+    },
+
+    /// Shows the changed memory pages and system state of a canister between
+    /// two checkpoints.
+    #[clap(name = "diff")]
+    Diff {
+        /// Path to the first checkpoint.
+        path_a: PathBuf,
+        /// Path to the checkpoint to compare against.
+        path_b: PathBuf,
+        /// ID of the canister to compare.
+        #[clap(long = "canister_id")]
+        canister_id: PrincipalId,
+    },
+}
+
 fn main() {
     let opt = Parser::parse();
     let result = match opt {
@@ -163,6 +223,22 @@ fn main() {
         Opt::VerifyManifest { file } => commands::verify_manifest::do_verify_manifest(&file),
         Opt::ListStates { config } => commands::list::do_list(config),
         Opt::Decode { file } => commands::decode::do_decode(file),
+        Opt::ExportCanister {
+            path,
+            canister_id,
+            output,
+        } => commands::export_canister::do_export_canister(path, canister_id, output),
+        Opt::Canister(CanisterOpt::List { path }) => commands::canister::do_list(path),
+        Opt::Canister(CanisterOpt::Export {
+            path,
+            canister_id,
+            output,
+        }) => commands::canister::do_export(path, canister_id, output),
+        Opt::Canister(CanisterOpt::Diff {
+            path_a,
+            path_b,
+            canister_id,
+        }) => commands::canister::do_diff(path_a, path_b, canister_id),
         Opt::CanisterIdToHex { canister_id } => {
             commands::convert_ids::do_canister_id_to_hex(canister_id)
         }
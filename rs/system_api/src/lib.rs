@@ -11,6 +11,9 @@ use ic_interfaces::execution_environment::{
     TrapCode::{self, CyclesAmountTooBigFor64Bit},
 };
 use ic_logger::{error, ReplicaLogger};
+use ic_management_canister_types::{
+    EcdsaCurve, EcdsaKeyId, MasterPublicKeyId, SchnorrAlgorithm, SchnorrKeyId,
+};
 use ic_registry_subnet_type::SubnetType;
 use ic_replicated_state::{
     canister_state::WASM_PAGE_SIZE_IN_BYTES, memory_required_to_push_request, Memory, NumWasmPages,
@@ -43,7 +46,7 @@ mod stable_memory;
 
 pub const MULTIPLIER_MAX_SIZE_LOCAL_SUBNET: u64 = 5;
 const MAX_NON_REPLICATED_QUERY_REPLY_SIZE: NumBytes = NumBytes::new(3 << 20);
-const CERTIFIED_DATA_MAX_LENGTH: usize = 32;
+pub const CERTIFIED_DATA_MAX_LENGTH: usize = 32;
 
 // Enables tracing of system calls for local debugging.
 const TRACE_SYSCALLS: bool = false;
@@ -54,6 +57,11 @@ const WASM_NATIVE_STABLE_MEMORY_ERROR: &str = "Stable memory cannot be accessed
 
 const MAX_32_BIT_STABLE_MEMORY_IN_PAGES: u64 = 64 * 1024; // 4GiB
 
+/// Result codes of the `ic0.cost_sign_with_*` system calls.
+const COST_SIGN_WITH_SUCCESS: u32 = 0;
+const COST_SIGN_WITH_UNKNOWN_CURVE_OR_ALGORITHM: u32 = 1;
+const COST_SIGN_WITH_UNKNOWN_KEY: u32 = 2;
+
 /// Upper bound on `timeout` when using calls with
 /// best-effort responses represented in seconds.
 pub const MAX_CALL_TIMEOUT_SECONDS: u32 = 300;
@@ -1626,6 +1634,87 @@ impl SystemApiImpl {
             }
         }
     }
+
+    /// Shared implementation of the `ic0.cost_sign_with_*` system calls.
+    ///
+    /// `key_id` builds the threshold key ID from the key name read from the
+    /// heap and returns `None` if the curve or algorithm is unknown.
+    fn ic0_cost_sign_with_threshold_helper(
+        &self,
+        method_name: &str,
+        src: usize,
+        size: usize,
+        key_id: impl FnOnce(String) -> Option<MasterPublicKeyId>,
+        dst: usize,
+        heap: &mut [u8],
+    ) -> HypervisorResult<u32> {
+        let name = valid_subslice(method_name, src, size, heap)?.to_vec();
+        // A key name that is not valid UTF-8 cannot belong to any key.
+        let Ok(name) = String::from_utf8(name) else {
+            return Ok(COST_SIGN_WITH_UNKNOWN_KEY);
+        };
+        let Some(key_id) = key_id(name) else {
+            return Ok(COST_SIGN_WITH_UNKNOWN_CURVE_OR_ALGORITHM);
+        };
+        match self
+            .sandbox_safe_system_state
+            .sign_with_threshold_cost(&key_id)
+        {
+            Some(cost) => {
+                copy_cycles_to_heap(cost, dst, heap, method_name)?;
+                Ok(COST_SIGN_WITH_SUCCESS)
+            }
+            None => Ok(COST_SIGN_WITH_UNKNOWN_KEY),
+        }
+    }
+
+    /// Returns the name of the environment variable at `index`.
+    fn env_var_name(&self, method_name: &str, index: usize) -> HypervisorResult<&str> {
+        let environment_variables = self.sandbox_safe_system_state.environment_variables();
+        environment_variables
+            .name_at(index)
+            .ok_or_else(|| HypervisorError::UserContractViolation {
+                error: format!(
+                    "{} failed because index {} is out of range for {} environment variables.",
+                    method_name,
+                    index,
+                    environment_variables.len()
+                ),
+                suggestion: "Use ic0.env_var_count to get the number of environment variables."
+                    .to_string(),
+                doc_link: "".to_string(),
+            })
+    }
+
+    /// Returns the value of the environment variable whose name is read from
+    /// the heap at `src`/`size`.
+    fn env_var_value(
+        &self,
+        method_name: &str,
+        src: usize,
+        size: usize,
+        heap: &[u8],
+    ) -> HypervisorResult<&str> {
+        let name = valid_subslice(method_name, src, size, heap)?;
+        let name =
+            std::str::from_utf8(name).map_err(|_| HypervisorError::ToolchainContractViolation {
+                error: format!(
+                    "{} failed because the environment variable name is not valid UTF-8.",
+                    method_name
+                ),
+            })?;
+        self.sandbox_safe_system_state
+            .environment_variables()
+            .get(name)
+            .ok_or_else(|| HypervisorError::UserContractViolation {
+                error: format!(
+                    "{} failed because there is no environment variable named {}.",
+                    method_name, name
+                ),
+                suggestion: "Check the environment variables in the canister settings.".to_string(),
+                doc_link: "".to_string(),
+            })
+    }
 }
 
 impl SystemApi for SystemApiImpl {
@@ -2148,6 +2237,68 @@ impl SystemApi for SystemApiImpl {
         result
     }
 
+    fn ic0_subnet_self_size(&self) -> HypervisorResult<usize> {
+        let result = match &self.api_type {
+            ApiType::Start { .. } => Err(self.error_for("ic0_subnet_self_size")),
+            ApiType::Init { .. }
+            | ApiType::SystemTask { .. }
+            | ApiType::Cleanup { .. }
+            | ApiType::Update { .. }
+            | ApiType::ReplicatedQuery { .. }
+            | ApiType::NonReplicatedQuery { .. }
+            | ApiType::ReplyCallback { .. }
+            | ApiType::RejectCallback { .. }
+            | ApiType::PreUpgrade { .. }
+            | ApiType::InspectMessage { .. } => Ok(self
+                .sandbox_safe_system_state
+                .subnet_id()
+                .get_ref()
+                .as_slice()
+                .len()),
+        };
+        trace_syscall!(self, SubnetSelfSize, result);
+        result
+    }
+
+    fn ic0_subnet_self_copy(
+        &mut self,
+        dst: usize,
+        offset: usize,
+        size: usize,
+        heap: &mut [u8],
+    ) -> HypervisorResult<()> {
+        let result = match &self.api_type {
+            ApiType::Start { .. } => Err(self.error_for("ic0_subnet_self_copy")),
+            ApiType::Init { .. }
+            | ApiType::SystemTask { .. }
+            | ApiType::Cleanup { .. }
+            | ApiType::Update { .. }
+            | ApiType::ReplicatedQuery { .. }
+            | ApiType::NonReplicatedQuery { .. }
+            | ApiType::PreUpgrade { .. }
+            | ApiType::ReplyCallback { .. }
+            | ApiType::RejectCallback { .. }
+            | ApiType::InspectMessage { .. } => {
+                valid_subslice("ic0.subnet_self_copy heap", dst, size, heap)?;
+                let subnet_id = self.sandbox_safe_system_state.subnet_id();
+                let id_bytes = subnet_id.get_ref().as_slice();
+                let slice = valid_subslice("ic0.subnet_self_copy id", offset, size, id_bytes)?;
+                deterministic_copy_from_slice(&mut heap[dst..dst + size], slice);
+                Ok(())
+            }
+        };
+        trace_syscall!(
+            self,
+            SubnetSelfCopy,
+            result,
+            dst,
+            offset,
+            size,
+            summarize(heap, dst, size)
+        );
+        result
+    }
+
     fn ic0_call_new(
         &mut self,
         callee_src: usize,
@@ -3414,6 +3565,236 @@ impl SystemApi for SystemApiImpl {
         trace_syscall!(self, CyclesBurn128, result, amount);
         result
     }
+
+    fn ic0_cost_call(
+        &self,
+        method_name_size: u64,
+        payload_size: u64,
+        dst: usize,
+        heap: &mut [u8],
+    ) -> HypervisorResult<()> {
+        let payload_size = NumBytes::from(method_name_size.saturating_add(payload_size));
+        let cost = self.sandbox_safe_system_state.call_cost(payload_size);
+        let result = copy_cycles_to_heap(cost, dst, heap, "ic0_cost_call");
+        trace_syscall!(
+            self,
+            CostCall,
+            result,
+            method_name_size,
+            payload_size,
+            dst,
+            summarize(heap, dst, 16)
+        );
+        result
+    }
+
+    fn ic0_cost_create_canister(&self, dst: usize, heap: &mut [u8]) -> HypervisorResult<()> {
+        let cost = self.sandbox_safe_system_state.create_canister_cost();
+        let result = copy_cycles_to_heap(cost, dst, heap, "ic0_cost_create_canister");
+        trace_syscall!(
+            self,
+            CostCreateCanister,
+            result,
+            dst,
+            summarize(heap, dst, 16)
+        );
+        result
+    }
+
+    fn ic0_cost_http_request(
+        &self,
+        request_size: u64,
+        max_res_bytes: u64,
+        dst: usize,
+        heap: &mut [u8],
+    ) -> HypervisorResult<()> {
+        let cost = self.sandbox_safe_system_state.http_request_cost(
+            NumBytes::from(request_size),
+            Some(NumBytes::from(max_res_bytes)),
+        );
+        let result = copy_cycles_to_heap(cost, dst, heap, "ic0_cost_http_request");
+        trace_syscall!(
+            self,
+            CostHttpRequest,
+            result,
+            request_size,
+            max_res_bytes,
+            dst,
+            summarize(heap, dst, 16)
+        );
+        result
+    }
+
+    fn ic0_cost_sign_with_ecdsa(
+        &self,
+        src: usize,
+        size: usize,
+        curve: u32,
+        dst: usize,
+        heap: &mut [u8],
+    ) -> HypervisorResult<u32> {
+        let result = self.ic0_cost_sign_with_threshold_helper(
+            "ic0_cost_sign_with_ecdsa",
+            src,
+            size,
+            |name| {
+                let curve = match curve {
+                    0 => EcdsaCurve::Secp256k1,
+                    _ => return None,
+                };
+                Some(MasterPublicKeyId::Ecdsa(EcdsaKeyId { curve, name }))
+            },
+            dst,
+            heap,
+        );
+        trace_syscall!(
+            self,
+            CostSignWithEcdsa,
+            result,
+            src,
+            size,
+            curve,
+            dst,
+            summarize(heap, dst, 16)
+        );
+        result
+    }
+
+    fn ic0_cost_sign_with_schnorr(
+        &self,
+        src: usize,
+        size: usize,
+        algorithm: u32,
+        dst: usize,
+        heap: &mut [u8],
+    ) -> HypervisorResult<u32> {
+        let result = self.ic0_cost_sign_with_threshold_helper(
+            "ic0_cost_sign_with_schnorr",
+            src,
+            size,
+            |name| {
+                let algorithm = match algorithm {
+                    0 => SchnorrAlgorithm::Bip340Secp256k1,
+                    1 => SchnorrAlgorithm::Ed25519,
+                    _ => return None,
+                };
+                Some(MasterPublicKeyId::Schnorr(SchnorrKeyId { algorithm, name }))
+            },
+            dst,
+            heap,
+        );
+        trace_syscall!(
+            self,
+            CostSignWithSchnorr,
+            result,
+            src,
+            size,
+            algorithm,
+            dst,
+            summarize(heap, dst, 16)
+        );
+        result
+    }
+
+    fn ic0_env_var_count(&self) -> HypervisorResult<usize> {
+        let result = Ok(self.sandbox_safe_system_state.environment_variables().len());
+        trace_syscall!(self, EnvVarCount, result);
+        result
+    }
+
+    fn ic0_env_var_name_size(&self, index: usize) -> HypervisorResult<usize> {
+        let result = self
+            .env_var_name("ic0_env_var_name_size", index)
+            .map(str::len);
+        trace_syscall!(self, EnvVarNameSize, result, index);
+        result
+    }
+
+    fn ic0_env_var_name_copy(
+        &self,
+        index: usize,
+        dst: usize,
+        offset: usize,
+        size: usize,
+        heap: &mut [u8],
+    ) -> HypervisorResult<()> {
+        let result = self
+            .env_var_name("ic0_env_var_name_copy", index)
+            .and_then(|name| {
+                valid_subslice("ic0.env_var_name_copy heap", dst, size, heap)?;
+                let name_subslice =
+                    valid_subslice("ic0.env_var_name_copy name", offset, size, name.as_bytes())?;
+                deterministic_copy_from_slice(&mut heap[dst..dst + size], name_subslice);
+                Ok(())
+            });
+        trace_syscall!(
+            self,
+            EnvVarNameCopy,
+            result,
+            index,
+            dst,
+            offset,
+            size,
+            summarize(heap, dst, size)
+        );
+        result
+    }
+
+    fn ic0_env_var_value_size(
+        &self,
+        name_src: usize,
+        name_size: usize,
+        heap: &[u8],
+    ) -> HypervisorResult<usize> {
+        let result = self
+            .env_var_value("ic0_env_var_value_size", name_src, name_size, heap)
+            .map(str::len);
+        trace_syscall!(
+            self,
+            EnvVarValueSize,
+            result,
+            name_src,
+            name_size,
+            summarize(heap, name_src, name_size)
+        );
+        result
+    }
+
+    fn ic0_env_var_value_copy(
+        &self,
+        name_src: usize,
+        name_size: usize,
+        dst: usize,
+        offset: usize,
+        size: usize,
+        heap: &mut [u8],
+    ) -> HypervisorResult<()> {
+        let result = self
+            .env_var_value("ic0_env_var_value_copy", name_src, name_size, heap)
+            .and_then(|value| {
+                valid_subslice("ic0.env_var_value_copy heap", dst, size, heap)?;
+                let value_subslice = valid_subslice(
+                    "ic0.env_var_value_copy value",
+                    offset,
+                    size,
+                    value.as_bytes(),
+                )?;
+                deterministic_copy_from_slice(&mut heap[dst..dst + size], value_subslice);
+                Ok(())
+            });
+        trace_syscall!(
+            self,
+            EnvVarValueCopy,
+            result,
+            name_src,
+            name_size,
+            dst,
+            offset,
+            size,
+            summarize(heap, dst, size)
+        );
+        result
+    }
 }
 
 /// The default implementation of the `OutOfInstructionHandler` trait.
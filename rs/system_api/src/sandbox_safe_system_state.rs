@@ -11,7 +11,7 @@ use ic_limits::{LOG_CANISTER_OPERATION_CYCLES_THRESHOLD, SMALL_APP_SUBNET_MAX_SI
 use ic_logger::{info, ReplicaLogger};
 use ic_management_canister_types::{
     CanisterStatusType, CreateCanisterArgs, InstallChunkedCodeArgs, InstallCodeArgsV2,
-    LoadCanisterSnapshotArgs, Method as Ic00Method, Payload,
+    LoadCanisterSnapshotArgs, MasterPublicKeyId, Method as Ic00Method, Payload,
     ProvisionalCreateCanisterWithCyclesArgs, UninstallCodeArgs, UpdateSettingsArgs, IC_00,
 };
 use ic_nns_constants::CYCLES_MINTING_CANISTER_ID;
@@ -23,7 +23,8 @@ use ic_types::{
     messages::{CallContextId, CallbackId, RejectContext, Request, RequestMetadata, NO_DEADLINE},
     methods::Callback,
     time::CoarseTime,
-    CanisterLog, CanisterTimer, ComputeAllocation, Cycles, MemoryAllocation, NumInstructions, Time,
+    CanisterLog, CanisterTimer, ComputeAllocation, Cycles, EnvironmentVariables, MemoryAllocation,
+    NumInstructions, Time,
 };
 use ic_wasm_types::WasmEngineError;
 use serde::{Deserialize, Serialize};
@@ -246,6 +247,7 @@ impl SystemStateChanges {
             | Ok(Ic00Method::ComputeInitialIDkgDealings)
             | Ok(Ic00Method::SchnorrPublicKey)
             | Ok(Ic00Method::SignWithSchnorr)
+            | Ok(Ic00Method::VetKdPublicKey)
             | Ok(Ic00Method::ProvisionalTopUpCanister)
             | Ok(Ic00Method::BitcoinSendTransactionInternal)
             | Ok(Ic00Method::BitcoinGetSuccessors)
@@ -255,13 +257,18 @@ impl SystemStateChanges {
             | Ok(Ic00Method::BitcoinSendTransaction)
             | Ok(Ic00Method::BitcoinGetCurrentFeePercentiles)
             | Ok(Ic00Method::NodeMetricsHistory)
+            | Ok(Ic00Method::SubnetInfo)
             | Ok(Ic00Method::FetchCanisterLogs)
             | Ok(Ic00Method::UploadChunk)
             | Ok(Ic00Method::StoredChunks)
             | Ok(Ic00Method::ClearChunkStore)
             | Ok(Ic00Method::TakeCanisterSnapshot)
             | Ok(Ic00Method::ListCanisterSnapshots)
-            | Ok(Ic00Method::DeleteCanisterSnapshot) => Ok(None),
+            | Ok(Ic00Method::DeleteCanisterSnapshot)
+            | Ok(Ic00Method::ReadCanisterSnapshotMetadata)
+            | Ok(Ic00Method::ReadCanisterSnapshotData)
+            | Ok(Ic00Method::UploadCanisterSnapshotMetadata)
+            | Ok(Ic00Method::UploadCanisterSnapshotData) => Ok(None),
             Err(_) => Err(UserError::new(
                 ErrorCode::CanisterMethodNotFound,
                 format!("Management canister has no method '{}'", msg.method_name),
@@ -555,6 +562,12 @@ pub struct SandboxSafeSystemState {
     pub(super) status: CanisterStatusView,
     pub(super) subnet_type: SubnetType,
     pub(super) subnet_size: usize,
+    /// Sizes of the subnets that threshold signing requests for the given
+    /// key are routed to. Used to price signatures before making them.
+    idkg_signing_subnet_sizes: BTreeMap<MasterPublicKeyId, usize>,
+    /// Whether the canister is running on the NNS subnet, whose canisters
+    /// are not charged for threshold signatures.
+    is_nns_subnet: bool,
     dirty_page_overhead: NumInstructions,
     freeze_threshold: NumSeconds,
     memory_allocation: MemoryAllocation,
@@ -578,6 +591,7 @@ pub struct SandboxSafeSystemState {
     controllers: BTreeSet<PrincipalId>,
     pub(super) request_metadata: RequestMetadata,
     caller: Option<PrincipalId>,
+    environment_variables: EnvironmentVariables,
 }
 
 impl SandboxSafeSystemState {
@@ -603,6 +617,8 @@ impl SandboxSafeSystemState {
         ic00_available_request_slots: usize,
         ic00_aliases: BTreeSet<CanisterId>,
         subnet_size: usize,
+        idkg_signing_subnet_sizes: BTreeMap<MasterPublicKeyId, usize>,
+        is_nns_subnet: bool,
         dirty_page_overhead: NumInstructions,
         global_timer: CanisterTimer,
         canister_version: u64,
@@ -610,12 +626,16 @@ impl SandboxSafeSystemState {
         request_metadata: RequestMetadata,
         caller: Option<PrincipalId>,
         next_canister_log_record_idx: u64,
+        log_memory_limit: NumBytes,
+        environment_variables: EnvironmentVariables,
     ) -> Self {
         Self {
             canister_id,
             status,
             subnet_type: cycles_account_manager.subnet_type(),
             subnet_size,
+            idkg_signing_subnet_sizes,
+            is_nns_subnet,
             dirty_page_overhead,
             freeze_threshold,
             memory_allocation,
@@ -623,7 +643,11 @@ impl SandboxSafeSystemState {
             compute_allocation,
             system_state_changes: SystemStateChanges {
                 // Start indexing new batch of canister log records from the given index.
-                canister_log: CanisterLog::new_with_next_index(next_canister_log_record_idx),
+                canister_log: CanisterLog::new_with_capacity(
+                    next_canister_log_record_idx,
+                    vec![],
+                    log_memory_limit.get() as usize,
+                ),
                 call_context_balance_taken: call_context_id
                     .map(|call_context_id| (call_context_id, Cycles::zero())),
                 ..SystemStateChanges::default()
@@ -643,6 +667,7 @@ impl SandboxSafeSystemState {
             controllers,
             request_metadata,
             caller,
+            environment_variables,
         }
     }
 
@@ -698,6 +723,18 @@ impl SandboxSafeSystemState {
         let subnet_size = network_topology
             .get_subnet_size(&cycles_account_manager.get_subnet_id())
             .unwrap_or(SMALL_APP_SUBNET_MAX_SIZE);
+        // Signing requests are routed to the first subnet enabled to sign
+        // with the key, so that is the subnet whose size determines the fee.
+        let idkg_signing_subnet_sizes = network_topology
+            .idkg_signing_subnets
+            .iter()
+            .filter_map(|(key_id, subnets)| {
+                let size = network_topology.get_subnet_size(subnets.first()?)?;
+                Some((key_id.clone(), size))
+            })
+            .collect();
+        let is_nns_subnet =
+            cycles_account_manager.get_subnet_id() == network_topology.nns_subnet_id;
 
         Self::new_internal(
             system_state.canister_id,
@@ -720,6 +757,8 @@ impl SandboxSafeSystemState {
             ic00_available_request_slots,
             ic00_aliases,
             subnet_size,
+            idkg_signing_subnet_sizes,
+            is_nns_subnet,
             dirty_page_overhead,
             system_state.global_timer,
             system_state.canister_version,
@@ -727,6 +766,8 @@ impl SandboxSafeSystemState {
             request_metadata,
             caller,
             system_state.canister_log.next_idx(),
+            system_state.log_memory_limit(),
+            system_state.environment_variables.clone(),
         )
     }
 
@@ -734,6 +775,11 @@ impl SandboxSafeSystemState {
         self.canister_id
     }
 
+    /// Returns the ID of the subnet the canister is running on.
+    pub fn subnet_id(&self) -> SubnetId {
+        self.cycles_account_manager.get_subnet_id()
+    }
+
     pub fn global_timer(&self) -> CanisterTimer {
         self.global_timer
     }
@@ -873,6 +919,57 @@ impl SandboxSafeSystemState {
         burned_cycles
     }
 
+    /// Returns the amount of cycles charged for sending a request with a
+    /// payload of the given size, including the prepayments for the
+    /// response.
+    pub(super) fn call_cost(&self, payload_size: NumBytes) -> Cycles {
+        self.cycles_account_manager
+            .xnet_call_total_fee(payload_size, self.subnet_size)
+            + self.prepayment_for_response_transmission()
+            + self.prepayment_for_response_execution()
+    }
+
+    /// Returns the amount of cycles required to create a canister.
+    pub(super) fn create_canister_cost(&self) -> Cycles {
+        self.cycles_account_manager
+            .canister_creation_fee(self.subnet_size)
+    }
+
+    /// Returns the amount of cycles required for an HTTP outcall with the
+    /// given request size and maximum response size.
+    pub(super) fn http_request_cost(
+        &self,
+        request_size: NumBytes,
+        max_response_bytes: Option<NumBytes>,
+    ) -> Cycles {
+        self.cycles_account_manager.http_request_fee(
+            request_size,
+            max_response_bytes,
+            self.subnet_size,
+        )
+    }
+
+    /// Returns the amount of cycles required for a threshold signature with
+    /// the given key, or `None` if no subnet is enabled to sign with it.
+    /// Canisters on the NNS subnet are not charged for signatures.
+    pub(super) fn sign_with_threshold_cost(&self, key_id: &MasterPublicKeyId) -> Option<Cycles> {
+        let subnet_size = *self.idkg_signing_subnet_sizes.get(key_id)?;
+        let fee = match key_id {
+            MasterPublicKeyId::Ecdsa(_) => {
+                self.cycles_account_manager.ecdsa_signature_fee(subnet_size)
+            }
+            MasterPublicKeyId::Schnorr(_) => self
+                .cycles_account_manager
+                .schnorr_signature_fee(subnet_size),
+            // vetKD keys cannot be used to sign.
+            MasterPublicKeyId::VetKd(_) => return None,
+        };
+        if self.is_nns_subnet {
+            return Some(Cycles::zero());
+        }
+        Some(fee)
+    }
+
     pub(super) fn refund_cycles(&mut self, cycles: Cycles) {
         let mut new_balance = self.cycles_balance();
         new_balance += cycles;
@@ -1032,6 +1129,10 @@ impl SandboxSafeSystemState {
         self.controllers.contains(principal_id)
     }
 
+    pub fn environment_variables(&self) -> &EnvironmentVariables {
+        &self.environment_variables
+    }
+
     /// Checks the cycles balance against the freezing threshold with the new
     /// memory usage if that's needed for the given API type.
     ///
@@ -1328,14 +1429,20 @@ mod tests {
     use ic_config::subnet_config::{CyclesAccountManagerConfig, SchedulerConfig};
     use ic_cycles_account_manager::CyclesAccountManager;
     use ic_limits::SMALL_APP_SUBNET_MAX_SIZE;
+    use ic_management_canister_types::{EcdsaCurve, EcdsaKeyId, MasterPublicKeyId};
     use ic_registry_subnet_type::SubnetType;
-    use ic_replicated_state::{canister_state::system_state::CyclesUseCase, SystemState};
-    use ic_test_utilities_types::ids::{canister_test_id, subnet_test_id, user_test_id};
+    use ic_replicated_state::{
+        canister_state::system_state::CyclesUseCase, metadata_state::SubnetTopology,
+        NetworkTopology, SystemState,
+    };
+    use ic_test_utilities_types::ids::{
+        canister_test_id, node_test_id, subnet_test_id, user_test_id,
+    };
     use ic_types::{
         messages::{RequestMetadata, NO_DEADLINE},
         time::CoarseTime,
-        CanisterTimer, ComputeAllocation, Cycles, MemoryAllocation, NumBytes, NumInstructions,
-        Time,
+        CanisterTimer, ComputeAllocation, Cycles, EnvironmentVariables, MemoryAllocation, NumBytes,
+        NumInstructions, SubnetId, Time, DEFAULT_CANISTER_LOG_BUFFER_SIZE,
     };
 
     use crate::{
@@ -1407,6 +1514,72 @@ mod tests {
         assert_eq!(initial_cycles_balance + added, system_state.balance());
     }
 
+    fn sign_with_ecdsa_cost_on_subnet(own_subnet_id: SubnetId) -> Option<Cycles> {
+        let key_id = MasterPublicKeyId::Ecdsa(EcdsaKeyId {
+            curve: EcdsaCurve::Secp256k1,
+            name: "key_1".to_string(),
+        });
+        let signing_subnet_id = subnet_test_id(2);
+        let network_topology = NetworkTopology {
+            subnets: BTreeMap::from([(
+                signing_subnet_id,
+                SubnetTopology {
+                    nodes: (0..13).map(node_test_id).collect(),
+                    ..SubnetTopology::default()
+                },
+            )]),
+            nns_subnet_id: subnet_test_id(1),
+            idkg_signing_subnets: BTreeMap::from([(key_id.clone(), vec![signing_subnet_id])]),
+            ..NetworkTopology::default()
+        };
+        let system_state = SystemState::new_running_for_testing(
+            canister_test_id(0),
+            user_test_id(1).get(),
+            Cycles::new(1_000_000_000),
+            NumSeconds::from(100_000),
+        );
+        let sandbox_state = SandboxSafeSystemState::new(
+            &system_state,
+            CyclesAccountManager::new(
+                NumInstructions::from(1_000_000_000),
+                SubnetType::Application,
+                own_subnet_id,
+                CyclesAccountManagerConfig::application_subnet(),
+            ),
+            &network_topology,
+            SchedulerConfig::application_subnet().dirty_page_overhead,
+            ComputeAllocation::default(),
+            RequestMetadata::new(0, Time::from_nanos_since_unix_epoch(0)),
+            None,
+            None,
+        );
+        sandbox_state.sign_with_threshold_cost(&key_id)
+    }
+
+    #[test]
+    fn test_sign_with_threshold_cost_is_zero_on_nns_subnet() {
+        let cycles_account_manager = CyclesAccountManager::new(
+            NumInstructions::from(1_000_000_000),
+            SubnetType::Application,
+            subnet_test_id(0),
+            CyclesAccountManagerConfig::application_subnet(),
+        );
+        // The fee is scaled by the size of the signing subnet.
+        assert_eq!(
+            sign_with_ecdsa_cost_on_subnet(subnet_test_id(0)),
+            Some(cycles_account_manager.ecdsa_signature_fee(13))
+        );
+        assert_ne!(
+            sign_with_ecdsa_cost_on_subnet(subnet_test_id(0)),
+            Some(Cycles::zero())
+        );
+        // Canisters on the NNS subnet are not charged.
+        assert_eq!(
+            sign_with_ecdsa_cost_on_subnet(subnet_test_id(1)),
+            Some(Cycles::zero())
+        );
+    }
+
     fn helper_msg_deadline(call_context_deadline: Option<CoarseTime>) -> CoarseTime {
         let sandbox_state = SandboxSafeSystemState::new_internal(
             canister_test_id(0),
@@ -1432,6 +1605,8 @@ mod tests {
             0,
             BTreeSet::new(),
             SMALL_APP_SUBNET_MAX_SIZE,
+            BTreeMap::new(),
+            false,
             SchedulerConfig::application_subnet().dirty_page_overhead,
             CanisterTimer::Inactive,
             0,
@@ -1439,6 +1614,8 @@ mod tests {
             RequestMetadata::new(0, Time::from_nanos_since_unix_epoch(0)),
             None,
             0,
+            NumBytes::new(DEFAULT_CANISTER_LOG_BUFFER_SIZE as u64),
+            EnvironmentVariables::default(),
         );
         sandbox_state.msg_deadline()
     }
@@ -1479,6 +1656,8 @@ mod tests {
             0,
             BTreeSet::new(),
             SMALL_APP_SUBNET_MAX_SIZE,
+            BTreeMap::new(),
+            false,
             SchedulerConfig::application_subnet().dirty_page_overhead,
             CanisterTimer::Inactive,
             0,
@@ -1486,6 +1665,8 @@ mod tests {
             RequestMetadata::new(0, Time::from_nanos_since_unix_epoch(0)),
             None,
             0,
+            NumBytes::new(DEFAULT_CANISTER_LOG_BUFFER_SIZE as u64),
+            EnvironmentVariables::default(),
         )
     }
 
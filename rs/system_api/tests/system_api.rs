@@ -32,8 +32,8 @@ use ic_types::{
     methods::{Callback, WasmClosure},
     time,
     time::UNIX_EPOCH,
-    CanisterTimer, CountBytes, Cycles, NumInstructions, PrincipalId, Time,
-    MAX_ALLOWED_CANISTER_LOG_BUFFER_SIZE,
+    CanisterTimer, CountBytes, Cycles, EnvironmentVariables, NumInstructions, PrincipalId, Time,
+    DEFAULT_CANISTER_LOG_BUFFER_SIZE,
 };
 use maplit::btreemap;
 use more_asserts::assert_le;
@@ -59,6 +59,17 @@ fn get_system_state_with_cycles(cycles_amount: Cycles) -> SystemState {
     )
 }
 
+fn get_system_state_with_environment_variables(variables: &[(&str, &str)]) -> SystemState {
+    let mut system_state = get_system_state();
+    system_state.environment_variables = EnvironmentVariables::new(
+        variables
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect(),
+    );
+    system_state
+}
+
 fn assert_api_supported<T>(res: HypervisorResult<T>) {
     res.unwrap();
 }
@@ -260,6 +271,8 @@ fn is_supported(api_type: SystemApiCallId, context: &str) -> bool {
         SystemApiCallId::CyclesBurn128 => vec!["I", "G", "U", "Ry", "Rt", "C", "T"],
         SystemApiCallId::CanisterSelfSize => vec!["*"],
         SystemApiCallId::CanisterSelfCopy => vec!["*"],
+        SystemApiCallId::SubnetSelfSize => vec!["*"],
+        SystemApiCallId::SubnetSelfCopy => vec!["*"],
         SystemApiCallId::CanisterCycleBalance => vec!["*"],
         SystemApiCallId::CanisterCycleBalance128 => vec!["*"],
         SystemApiCallId::CanisterStatus => vec!["*"],
@@ -293,7 +306,17 @@ fn is_supported(api_type: SystemApiCallId, context: &str) -> bool {
         SystemApiCallId::InReplicatedExecution => vec!["*", "s"],
         SystemApiCallId::DebugPrint => vec!["*", "s"],
         SystemApiCallId::Trap => vec!["*", "s"],
-        SystemApiCallId::MintCycles => vec!["U", "Ry", "Rt", "T"]
+        SystemApiCallId::MintCycles => vec!["U", "Ry", "Rt", "T"],
+        SystemApiCallId::CostCall => vec!["*", "s"],
+        SystemApiCallId::CostCreateCanister => vec!["*", "s"],
+        SystemApiCallId::CostHttpRequest => vec!["*", "s"],
+        SystemApiCallId::CostSignWithEcdsa => vec!["*", "s"],
+        SystemApiCallId::CostSignWithSchnorr => vec!["*", "s"],
+        SystemApiCallId::EnvVarCount => vec!["*", "s"],
+        SystemApiCallId::EnvVarNameSize => vec!["*", "s"],
+        SystemApiCallId::EnvVarNameCopy => vec!["*", "s"],
+        SystemApiCallId::EnvVarValueSize => vec!["*", "s"],
+        SystemApiCallId::EnvVarValueCopy => vec!["*", "s"],
     };
     // the semantics of "*" is to cover all modes except for "s"
     matrix.get(&api_type).unwrap().contains(&context)
@@ -775,6 +798,126 @@ fn api_availability_test(
                 context,
             );
         }
+        SystemApiCallId::SubnetSelfSize => {
+            assert_api_availability(
+                |api| api.ic0_subnet_self_size(),
+                api_type,
+                &system_state,
+                cycles_account_manager,
+                api_type_enum,
+                context,
+            );
+        }
+        SystemApiCallId::SubnetSelfCopy => {
+            assert_api_availability(
+                |mut api| api.ic0_subnet_self_copy(0, 0, 0, &mut [42; 128]),
+                api_type,
+                &system_state,
+                cycles_account_manager,
+                api_type_enum,
+                context,
+            );
+        }
+        SystemApiCallId::CostCall => {
+            assert_api_availability(
+                |api| api.ic0_cost_call(0, 0, 0, &mut [42; 128]),
+                api_type,
+                &system_state,
+                cycles_account_manager,
+                api_type_enum,
+                context,
+            );
+        }
+        SystemApiCallId::CostCreateCanister => {
+            assert_api_availability(
+                |api| api.ic0_cost_create_canister(0, &mut [42; 128]),
+                api_type,
+                &system_state,
+                cycles_account_manager,
+                api_type_enum,
+                context,
+            );
+        }
+        SystemApiCallId::CostHttpRequest => {
+            assert_api_availability(
+                |api| api.ic0_cost_http_request(0, 0, 0, &mut [42; 128]),
+                api_type,
+                &system_state,
+                cycles_account_manager,
+                api_type_enum,
+                context,
+            );
+        }
+        SystemApiCallId::CostSignWithEcdsa => {
+            assert_api_availability(
+                |api| api.ic0_cost_sign_with_ecdsa(0, 0, 0, 0, &mut [42; 128]),
+                api_type,
+                &system_state,
+                cycles_account_manager,
+                api_type_enum,
+                context,
+            );
+        }
+        SystemApiCallId::CostSignWithSchnorr => {
+            assert_api_availability(
+                |api| api.ic0_cost_sign_with_schnorr(0, 0, 0, 0, &mut [42; 128]),
+                api_type,
+                &system_state,
+                cycles_account_manager,
+                api_type_enum,
+                context,
+            );
+        }
+        SystemApiCallId::EnvVarCount => {
+            assert_api_availability(
+                |api| api.ic0_env_var_count(),
+                api_type,
+                &system_state,
+                cycles_account_manager,
+                api_type_enum,
+                context,
+            );
+        }
+        SystemApiCallId::EnvVarNameSize => {
+            assert_api_availability(
+                |api| api.ic0_env_var_name_size(0),
+                api_type,
+                &get_system_state_with_environment_variables(&[("NAME", "VALUE")]),
+                cycles_account_manager,
+                api_type_enum,
+                context,
+            );
+        }
+        SystemApiCallId::EnvVarNameCopy => {
+            assert_api_availability(
+                |api| api.ic0_env_var_name_copy(0, 0, 0, 0, &mut [42; 128]),
+                api_type,
+                &get_system_state_with_environment_variables(&[("NAME", "VALUE")]),
+                cycles_account_manager,
+                api_type_enum,
+                context,
+            );
+        }
+        SystemApiCallId::EnvVarValueSize => {
+            assert_api_availability(
+                |api| api.ic0_env_var_value_size(0, 4, b"NAME"),
+                api_type,
+                &get_system_state_with_environment_variables(&[("NAME", "VALUE")]),
+                cycles_account_manager,
+                api_type_enum,
+                context,
+            );
+        }
+        SystemApiCallId::EnvVarValueCopy => {
+            assert_api_availability(
+                |api| api.ic0_env_var_value_copy(0, 4, 0, 0, 0, &mut b"NAME".to_vec()),
+                api_type,
+                &get_system_state_with_environment_variables(&[("NAME", "VALUE")]),
+                cycles_account_manager,
+                api_type_enum,
+                context,
+            );
+        }
         // stable API is tested separately
         SystemApiCallId::StableGrow
         | SystemApiCallId::StableRead
@@ -1943,6 +2086,158 @@ fn test_ic0_cycles_burn() {
     assert_eq!(Cycles::new(0), Cycles::from(&heap));
 }
 
+#[test]
+fn test_ic0_subnet_self_copy() {
+    let subnet_id = subnet_test_id(7);
+    let mut api = get_system_api(
+        ApiTypeBuilder::build_update_api(),
+        &SystemStateBuilder::default().build(),
+        CyclesAccountManagerBuilder::new()
+            .with_subnet_id(subnet_id)
+            .build(),
+    );
+    let size = api.ic0_subnet_self_size().unwrap();
+    assert_eq!(size, subnet_id.get_ref().as_slice().len());
+
+    let mut heap = vec![0; size];
+    api.ic0_subnet_self_copy(0, 0, size, &mut heap).unwrap();
+    assert_eq!(heap, subnet_id.get_ref().as_slice());
+}
+
+#[test]
+fn test_ic0_cost_create_canister_and_http_request() {
+    let cycles_account_manager = CyclesAccountManagerBuilder::new().build();
+    let api = get_system_api(
+        ApiTypeBuilder::build_update_api(),
+        &SystemStateBuilder::default().build(),
+        cycles_account_manager,
+    );
+
+    let mut heap = vec![0; 16];
+    api.ic0_cost_create_canister(0, &mut heap).unwrap();
+    assert_eq!(
+        Cycles::from(&heap),
+        cycles_account_manager.canister_creation_fee(SMALL_APP_SUBNET_MAX_SIZE)
+    );
+
+    let mut heap = vec![0; 16];
+    api.ic0_cost_http_request(1_000, 2_000, 0, &mut heap)
+        .unwrap();
+    assert_eq!(
+        Cycles::from(&heap),
+        cycles_account_manager.http_request_fee(
+            NumBytes::from(1_000),
+            Some(NumBytes::from(2_000)),
+            SMALL_APP_SUBNET_MAX_SIZE
+        )
+    );
+}
+
+#[test]
+fn test_ic0_cost_call_matches_amount_charged_by_call_perform() {
+    let cycles_amount = Cycles::from(1_000_000_000_000u128);
+    let cycles_account_manager = CyclesAccountManagerBuilder::new()
+        .with_max_num_instructions(NumInstructions::from(1 << 30))
+        .build();
+    let mut api = get_system_api(
+        ApiTypeBuilder::build_update_api(),
+        &get_system_state_with_cycles(cycles_amount),
+        cycles_account_manager,
+    );
+
+    let method_name = b"method";
+    let payload = [1u8; 100];
+    let mut heap = vec![0; 16];
+    api.ic0_cost_call(method_name.len() as u64, payload.len() as u64, 0, &mut heap)
+        .unwrap();
+    let cost = Cycles::from(&heap);
+
+    // The callee ID occupies the first 10 bytes of the heap.
+    let mut heap = vec![0; 1024];
+    heap[10..10 + method_name.len()].copy_from_slice(method_name);
+    heap[100..200].copy_from_slice(&payload);
+    api.ic0_call_new(0, 10, 10, method_name.len(), 0, 0, 0, 0, &heap)
+        .unwrap();
+    api.ic0_call_data_append(100, payload.len(), &heap).unwrap();
+    assert_eq!(api.ic0_call_perform().unwrap(), 0);
+
+    assert_eq!(
+        Cycles::from(api.ic0_canister_cycle_balance().unwrap()),
+        cycles_amount - cost
+    );
+}
+
+#[test]
+fn test_ic0_env_var_accessors() {
+    let api = get_system_api(
+        ApiTypeBuilder::build_update_api(),
+        &get_system_state_with_environment_variables(&[
+            ("NETWORK", "staging"),
+            ("BACKEND", "rrkah-fqaaa-aaaaa-aaaaq-cai"),
+        ]),
+        CyclesAccountManagerBuilder::new().build(),
+    );
+    assert_eq!(api.ic0_env_var_count(), Ok(2));
+
+    // Environment variables are ordered by name.
+    let mut heap = vec![0; 64];
+    assert_eq!(api.ic0_env_var_name_size(1), Ok(7));
+    api.ic0_env_var_name_copy(1, 0, 0, 7, &mut heap).unwrap();
+    assert_eq!(&heap[..7], b"NETWORK");
+
+    // Look up the value by the name copied to the heap above.
+    assert_eq!(api.ic0_env_var_value_size(0, 7, &heap), Ok(7));
+    api.ic0_env_var_value_copy(0, 7, 32, 0, 7, &mut heap)
+        .unwrap();
+    assert_eq!(&heap[32..39], b"staging");
+
+    // Unknown indices and names trap.
+    assert!(matches!(
+        api.ic0_env_var_name_size(2),
+        Err(HypervisorError::UserContractViolation { .. })
+    ));
+    heap[..7].copy_from_slice(b"UNKNOWN");
+    assert!(matches!(
+        api.ic0_env_var_value_size(0, 7, &heap),
+        Err(HypervisorError::UserContractViolation { .. })
+    ));
+}
+
+#[test]
+fn test_ic0_cost_sign_with_unknown_curve_or_key() {
+    let api = get_system_api(
+        ApiTypeBuilder::build_update_api(),
+        &SystemStateBuilder::default().build(),
+        CyclesAccountManagerBuilder::new().build(),
+    );
+    let key_name = b"key_1";
+    let mut heap = vec![0; 64];
+    heap[..key_name.len()].copy_from_slice(key_name);
+
+    // Unknown curve or algorithm.
+    assert_eq!(
+        api.ic0_cost_sign_with_ecdsa(0, key_name.len(), 1, 32, &mut heap),
+        Ok(1)
+    );
+    assert_eq!(
+        api.ic0_cost_sign_with_schnorr(0, key_name.len(), 2, 32, &mut heap),
+        Ok(1)
+    );
+
+    // Known curve and algorithm, but no subnet is enabled to sign with the key.
+    assert_eq!(
+        api.ic0_cost_sign_with_ecdsa(0, key_name.len(), 0, 32, &mut heap),
+        Ok(2)
+    );
+    assert_eq!(
+        api.ic0_cost_sign_with_schnorr(0, key_name.len(), 1, 32, &mut heap),
+        Ok(2)
+    );
+
+    // Nothing is copied to the heap on failure.
+    assert_eq!(&heap[32..48], &[0; 16]);
+}
+
 #[test]
 fn test_save_log_message_adds_canister_log_records() {
     let messages: Vec<Vec<_>> = vec![
@@ -2015,7 +2310,7 @@ fn test_save_log_message_invalid_message_offset() {
 
 #[test]
 fn test_save_log_message_trims_long_message() {
-    let long_message_size = 2 * MAX_ALLOWED_CANISTER_LOG_BUFFER_SIZE;
+    let long_message_size = 2 * DEFAULT_CANISTER_LOG_BUFFER_SIZE;
     let mut api = get_system_api(
         ApiTypeBuilder::build_update_api(),
         &SystemStateBuilder::default().build(),
@@ -2028,13 +2323,13 @@ fn test_save_log_message_trims_long_message() {
     // Expect added log record with the content trimmed to the allowed size.
     let records = api.canister_log().records();
     assert_eq!(records.len(), initial_records_number + 1);
-    assert!(records.back().unwrap().content.len() <= MAX_ALLOWED_CANISTER_LOG_BUFFER_SIZE);
+    assert!(records.back().unwrap().content.len() <= DEFAULT_CANISTER_LOG_BUFFER_SIZE);
 }
 
 #[test]
 fn test_save_log_message_keeps_total_log_size_limited() {
     let messages_number = 10;
-    let long_message_size = 2 * MAX_ALLOWED_CANISTER_LOG_BUFFER_SIZE;
+    let long_message_size = 2 * DEFAULT_CANISTER_LOG_BUFFER_SIZE;
     let mut api = get_system_api(
         ApiTypeBuilder::build_update_api(),
         &SystemStateBuilder::default().build(),
@@ -2049,5 +2344,5 @@ fn test_save_log_message_keeps_total_log_size_limited() {
     // Expect only one log record to be kept, staying within the size limit.
     let log = api.canister_log();
     assert_eq!(log.records().len(), initial_records_number + 1);
-    assert_le!(log.used_space(), MAX_ALLOWED_CANISTER_LOG_BUFFER_SIZE);
+    assert_le!(log.used_space(), DEFAULT_CANISTER_LOG_BUFFER_SIZE);
 }
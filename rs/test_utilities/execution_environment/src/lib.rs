@@ -107,6 +107,7 @@ pub fn generate_subnets(
                 subnet_type,
                 subnet_features: SubnetFeatures::default(),
                 idkg_keys_held: BTreeSet::new(),
+                replica_version: String::new(),
             },
         );
     }
@@ -412,6 +413,18 @@ impl ExecutionTest {
         )
     }
 
+    pub fn non_replicated_http_request_fee(
+        &self,
+        request_size: NumBytes,
+        response_size_limit: Option<NumBytes>,
+    ) -> Cycles {
+        self.cycles_account_manager.non_replicated_http_request_fee(
+            request_size,
+            response_size_limit,
+            self.subnet_size(),
+        )
+    }
+
     pub fn reduced_wasm_compilation_fee(&self, wasm: &[u8]) -> Cycles {
         let cost = wasm_compilation_cost(wasm);
         self.cycles_account_manager()
@@ -2149,6 +2162,13 @@ impl ExecutionTestBuilder {
                         public_key: b"cdcdcdcd".to_vec(),
                     },
                 ),
+                MasterPublicKeyId::VetKd(_) => (
+                    key_id,
+                    MasterPublicKey {
+                        algorithm_id: AlgorithmId::ThresBls12_381,
+                        public_key: b"efefefef".to_vec(),
+                    },
+                ),
             })
             .collect();
 
@@ -152,6 +152,7 @@ impl ReplicatedStateBuilder {
                 subnet_type: self.subnet_type,
                 subnet_features: self.subnet_features,
                 idkg_keys_held: BTreeSet::new(),
+                replica_version: String::new(),
             },
         );
 
@@ -245,10 +245,12 @@ fn test(env: TestEnv, binary_version: String, target_version: String) {
         replay_period_secs: 30,
         thread_id: 0,
         disable_cold_storage: false,
+        retention: Default::default(),
     };
     let cold_storage = Some(ColdStorage {
         cold_storage_dir: cold_storage_dir.clone(),
         versions_hot: 1,
+        object_storage: None,
     });
     let config = Config {
         push_metrics: false,
@@ -263,6 +265,8 @@ fn test(env: TestEnv, binary_version: String, target_version: String) {
         hot_disk_resource_threshold_percentage: 75,
         cold_disk_resource_threshold_percentage: 95,
         slack_token: "NO_TOKEN_IN_TESTING".to_string(),
+        notification_sinks: vec![],
+        notification_throttling: Default::default(),
         cold_storage,
         blacklisted_nodes: None,
         subnets: vec![subnet],
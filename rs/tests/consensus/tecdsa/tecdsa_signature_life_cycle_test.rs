@@ -215,6 +215,7 @@ fn test(env: TestEnv) {
                 let method_name = match key_id {
                     MasterPublicKeyId::Ecdsa(_) => "sign_with_ecdsa",
                     MasterPublicKeyId::Schnorr(_) => "sign_with_schnorr",
+                    MasterPublicKeyId::VetKd(_) => panic!("vetKD keys are not supported"),
                 };
                 if let Err(sig_err) = sig_result {
                     assert_eq!(
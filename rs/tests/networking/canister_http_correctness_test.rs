@@ -72,6 +72,7 @@ pub fn test(env: TestEnv) {
                         url: format!("http://[{webserver_ipv6}]:20443"),
                         headers: BoundedHttpHeaders::new(vec![]),
                         method: HttpMethod::GET,
+                        is_replicated: None,
                         body: Some("".as_bytes().to_vec()),
                         transform: Some(TransformContext {
                             function: TransformFunc(candid::Func {
@@ -99,6 +100,7 @@ pub fn test(env: TestEnv) {
                         url: format!("https://[{webserver_ipv6}]:20443"),
                         headers: BoundedHttpHeaders::new(vec![]),
                         method: HttpMethod::GET,
+                        is_replicated: None,
                         body: Some("".as_bytes().to_vec()),
                         transform: Some(TransformContext {
                             function: TransformFunc(candid::Func {
@@ -133,6 +135,7 @@ pub fn test(env: TestEnv) {
                         url: format!("https://[{webserver_ipv6}]:20443"),
                         headers: BoundedHttpHeaders::new(vec![]),
                         method: HttpMethod::GET,
+                        is_replicated: None,
                         body: Some("".as_bytes().to_vec()),
                         transform: Some(TransformContext {
                             function: TransformFunc(candid::Func {
@@ -167,6 +170,7 @@ pub fn test(env: TestEnv) {
                         url: format!("http://[{webserver_ipv6}]:20443"),
                         headers: BoundedHttpHeaders::new(vec![]),
                         method: HttpMethod::GET,
+                        is_replicated: None,
                         body: Some("".as_bytes().to_vec()),
                         transform: Some(TransformContext {
                             function: TransformFunc(candid::Func {
@@ -189,6 +193,7 @@ pub fn test(env: TestEnv) {
             url: format!("https://[{webserver_ipv6}]:20443"),
             headers: BoundedHttpHeaders::new(vec![]),
             method: HttpMethod::GET,
+            is_replicated: None,
             body: Some("".as_bytes().to_vec()),
             transform: Some(TransformContext {
                 function: TransformFunc(candid::Func {
@@ -239,6 +244,7 @@ pub fn test(env: TestEnv) {
             url: format!("https://[{webserver_ipv6}]:20443"),
             headers: BoundedHttpHeaders::new(vec![]),
             method: HttpMethod::GET,
+            is_replicated: None,
             body: Some("".as_bytes().to_vec()),
             transform: Some(TransformContext {
                 function: TransformFunc(candid::Func {
@@ -294,6 +300,7 @@ pub fn test(env: TestEnv) {
                         url: format!("https://[{webserver_ipv6}]:20443"),
                         headers: BoundedHttpHeaders::new(vec![]),
                         method: HttpMethod::GET,
+                        is_replicated: None,
                         body: Some("".as_bytes().to_vec()),
                         transform: Some(TransformContext {
                             function: TransformFunc(candid::Func {
@@ -321,6 +328,7 @@ pub fn test(env: TestEnv) {
                         url: format!("https://[{webserver_ipv6}]:20443"),
                         headers: BoundedHttpHeaders::new(vec![]),
                         method: HttpMethod::GET,
+                        is_replicated: None,
                         body: Some("".as_bytes().to_vec()),
                         transform: Some(TransformContext {
                             function: TransformFunc(candid::Func {
@@ -348,6 +356,7 @@ pub fn test(env: TestEnv) {
                         url: format!("https://[{webserver_ipv6}]:20443"),
                         headers: BoundedHttpHeaders::new(vec![]),
                         method: HttpMethod::GET,
+                        is_replicated: None,
                         body: Some("".as_bytes().to_vec()),
                         transform: Some(TransformContext {
                             function: TransformFunc(candid::Func {
@@ -378,6 +387,7 @@ pub fn test(env: TestEnv) {
                             value: "application/x-www-form-urlencoded".to_string(),
                         }]),
                         method: HttpMethod::POST,
+                        is_replicated: None,
                         body: Some("satoshi=me".as_bytes().to_vec()),
                         transform: Some(TransformContext {
                             function: TransformFunc(candid::Func {
@@ -405,6 +415,7 @@ pub fn test(env: TestEnv) {
                         url: format!("https://[{webserver_ipv6}]:20443/bytes/100000"),
                         headers: BoundedHttpHeaders::new(vec![]),
                         method: HttpMethod::GET,
+                        is_replicated: None,
                         body: Some("".as_bytes().to_vec()),
                         transform: Some(TransformContext {
                             function: TransformFunc(candid::Func {
@@ -433,6 +444,7 @@ pub fn test(env: TestEnv) {
                         url: format!("https://[{webserver_ipv6}]:20443/delay/40"),
                         headers: BoundedHttpHeaders::new(vec![]),
                         method: HttpMethod::GET,
+                        is_replicated: None,
                         body: Some("".as_bytes().to_vec()),
                         transform: Some(TransformContext {
                             function: TransformFunc(candid::Func {
@@ -460,6 +472,7 @@ pub fn test(env: TestEnv) {
                         url: format!("https://[{webserver_ipv6}]:20443/redirect/10"),
                         headers: BoundedHttpHeaders::new(vec![]),
                         method: HttpMethod::GET,
+                        is_replicated: None,
                         body: Some("".as_bytes().to_vec()),
                         transform: Some(TransformContext {
                             function: TransformFunc(candid::Func {
@@ -487,6 +500,7 @@ pub fn test(env: TestEnv) {
                         url: format!("https://[{}]:9090", node.get_ip_addr()),
                         headers: BoundedHttpHeaders::new(vec![]),
                         method: HttpMethod::GET,
+                        is_replicated: None,
                         body: Some("".as_bytes().to_vec()),
                         transform: Some(TransformContext {
                             function: TransformFunc(candid::Func {
@@ -155,6 +155,7 @@ pub fn test(env: TestEnv) {
                             url: format!("https://[{webserver_ipv6}]:20443/anything/{n}"),
                             headers: BoundedHttpHeaders::new(vec![]),
                             method: HttpMethod::GET,
+                            is_replicated: None,
                             body: Some("".as_bytes().to_vec()),
                             transform: Some(TransformContext {
                                 function: TransformFunc(candid::Func {
@@ -93,6 +93,7 @@ async fn test_proxy_canister(proxy_canister: &Canister<'_>, url: String, logger:
                                 context: context.as_bytes().to_vec(),
                             }),
                             method: HttpMethod::GET,
+                            is_replicated: None,
                             max_response_bytes: None,
                         },
                         cycles: 500_000_000_000,
@@ -56,6 +56,7 @@ pub fn test(env: TestEnv) {
                 url: url_to_succeed.clone(),
                 headers: BoundedHttpHeaders::new(vec![]),
                 method: HttpMethod::GET,
+                is_replicated: None,
                 body: Some("".as_bytes().to_vec()),
                 transform: Some(TransformContext {
                     function: TransformFunc(candid::Func {
@@ -64,12 +64,13 @@ pub type BoundedHttpHeaders = BoundedVec<
 //     url : text;
 //     max_response_bytes: opt nat64;
 //     headers : vec http_header;
-//     method : variant { get; head; post };
+//     method : variant { get; head; post; put; patch; delete };
 //     body : opt blob;
 //     transform : opt record {
 //       function : func (record {response : http_response; context : blob}) -> (http_response) query;
 //       context : blob;
 //     };
+//     is_replicated : opt bool;
 //   })`
 #[derive(Clone, PartialEq, Debug, CandidType, Deserialize)]
 pub struct CanisterHttpRequestArgs {
@@ -80,6 +81,10 @@ pub struct CanisterHttpRequestArgs {
     pub body: Option<Vec<u8>>,
     pub method: HttpMethod,
     pub transform: Option<TransformContext>,
+    /// If set to `Some(false)`, the request is made by a single node of the
+    /// subnet instead of all of them, and its response is delivered without
+    /// consensus on its content. Defaults to a fully replicated request.
+    pub is_replicated: Option<bool>,
 }
 
 impl Payload<'_> for CanisterHttpRequestArgs {}
@@ -92,6 +97,12 @@ impl CanisterHttpRequestArgs {
             .as_ref()
             .map(|transform_context| PrincipalId::from(transform_context.function.0.principal))
     }
+
+    /// Returns true, unless the request explicitly asks to be made by a
+    /// single node only.
+    pub fn is_replicated(&self) -> bool {
+        self.is_replicated.unwrap_or(true)
+    }
 }
 
 #[test]
@@ -113,6 +124,7 @@ fn test_http_headers_max_number() {
             headers,
             body: None,
             method: HttpMethod::GET,
+            is_replicated: None,
             transform: None,
         };
 
@@ -166,6 +178,7 @@ fn test_http_headers_max_total_size() {
             headers,
             body: None,
             method: HttpMethod::GET,
+            is_replicated: None,
             transform: None,
         };
 
@@ -213,6 +226,7 @@ fn test_http_headers_max_element_size() {
             headers,
             body: None,
             method: HttpMethod::GET,
+            is_replicated: None,
             transform: None,
         };
 
@@ -290,6 +304,12 @@ pub enum HttpMethod {
     POST,
     #[serde(rename = "head")]
     HEAD,
+    #[serde(rename = "put")]
+    PUT,
+    #[serde(rename = "patch")]
+    PATCH,
+    #[serde(rename = "delete")]
+    DELETE,
 }
 
 /// Represents the response for a canister http request.
@@ -28,6 +28,7 @@ use ic_protobuf::types::v1::{
     WasmMemoryPersistence as WasmMemoryPersistenceProto,
 };
 use ic_protobuf::{proxy::ProxyDecodeError, registry::crypto::v1 as pb_registry_crypto};
+use ic_registry_subnet_type::SubnetType;
 use num_traits::cast::ToPrimitive;
 pub use provisional::{ProvisionalCreateCanisterWithCyclesArgs, ProvisionalTopUpCanisterArgs};
 use serde::Serialize;
@@ -39,6 +40,9 @@ use strum_macros::{Display, EnumIter, EnumString};
 /// The id of the management canister.
 pub const IC_00: CanisterId = CanisterId::ic_00();
 pub const MAX_CONTROLLERS: usize = 10;
+/// The maximum number of bytes transferred by a single
+/// `read_canister_snapshot_data` or `upload_canister_snapshot_data` call.
+pub const MAX_SNAPSHOT_DATA_SLICE_SIZE_BYTES: u64 = 2_000_000;
 const WASM_HASH_LENGTH: usize = 32;
 /// The maximum length of a BIP32 derivation path
 ///
@@ -87,6 +91,10 @@ pub enum Method {
     SchnorrPublicKey,
     SignWithSchnorr,
 
+    // vetKD interface.
+    #[strum(serialize = "vetkd_public_key")]
+    VetKdPublicKey,
+
     // Bitcoin Interface.
     BitcoinGetBalance,
     BitcoinGetUtxos,
@@ -99,6 +107,8 @@ pub enum Method {
 
     NodeMetricsHistory,
 
+    SubnetInfo,
+
     FetchCanisterLogs,
 
     // These methods are only available on test IC instances where there is a
@@ -116,6 +126,12 @@ pub enum Method {
     LoadCanisterSnapshot,
     ListCanisterSnapshots,
     DeleteCanisterSnapshot,
+
+    // Support for downloading and uploading canister snapshots.
+    ReadCanisterSnapshotMetadata,
+    ReadCanisterSnapshotData,
+    UploadCanisterSnapshotMetadata,
+    UploadCanisterSnapshotData,
 }
 
 fn candid_error_to_user_error(err: candid::Error) -> UserError {
@@ -290,6 +306,40 @@ impl CanisterControllersChangeRecord {
     }
 }
 
+/// `CandidType` for `CanisterOperatorsChangeRecord`
+/// ```text
+/// record {
+///   operators : vec principal;
+/// }
+/// ```
+#[derive(Clone, Eq, PartialEq, Debug, CandidType, Deserialize)]
+pub struct CanisterOperatorsChangeRecord {
+    operators: Vec<PrincipalId>,
+}
+
+impl CanisterOperatorsChangeRecord {
+    pub fn operators(&self) -> &[PrincipalId] {
+        &self.operators
+    }
+}
+
+/// `CandidType` for `CanisterEnvironmentVariablesChangeRecord`
+/// ```text
+/// record {
+///   environment_variables_hash : blob;
+/// }
+/// ```
+#[derive(Clone, Eq, PartialEq, Debug, CandidType, Deserialize)]
+pub struct CanisterEnvironmentVariablesChangeRecord {
+    environment_variables_hash: [u8; 32],
+}
+
+impl CanisterEnvironmentVariablesChangeRecord {
+    pub fn environment_variables_hash(&self) -> [u8; 32] {
+        self.environment_variables_hash
+    }
+}
+
 /// `CandidType` for `CanisterLoadSnapshotRecord`
 /// ```text
 /// record {
@@ -349,6 +399,12 @@ impl CanisterLoadSnapshotRecord {
 ///     snapshot_id: blob;
 ///     taken_at_timestamp: nat64;
 ///   };
+///   environment_variables_change : record {
+///     environment_variables_hash : blob;
+///   };
+///   operators_change : record {
+///     operators : vec principal;
+///   };
 /// }
 /// ```
 #[derive(Clone, Eq, PartialEq, Debug, CandidType, Deserialize)]
@@ -363,6 +419,10 @@ pub enum CanisterChangeDetails {
     CanisterControllersChange(CanisterControllersChangeRecord),
     #[serde(rename = "load_snapshot")]
     CanisterLoadSnapshot(CanisterLoadSnapshotRecord),
+    #[serde(rename = "environment_variables_change")]
+    CanisterEnvironmentVariablesChange(CanisterEnvironmentVariablesChangeRecord),
+    #[serde(rename = "operators_change")]
+    CanisterOperatorsChange(CanisterOperatorsChangeRecord),
 }
 
 impl CanisterChangeDetails {
@@ -397,6 +457,20 @@ impl CanisterChangeDetails {
             taken_at_timestamp,
         })
     }
+
+    pub fn environment_variables_change(
+        environment_variables_hash: [u8; 32],
+    ) -> CanisterChangeDetails {
+        CanisterChangeDetails::CanisterEnvironmentVariablesChange(
+            CanisterEnvironmentVariablesChangeRecord {
+                environment_variables_hash,
+            },
+        )
+    }
+
+    pub fn operators_change(operators: Vec<PrincipalId>) -> CanisterChangeDetails {
+        CanisterChangeDetails::CanisterOperatorsChange(CanisterOperatorsChangeRecord { operators })
+    }
 }
 
 /// Every canister change (canister creation, code uninstallation, code deployment, or controllers change) consists of
@@ -415,6 +489,10 @@ impl CanisterChangeDetails {
 ///
 /// Controllers changes are described by the full new set of the canister controllers after the change.
 ///
+/// Environment variables changes are described by the SHA-256 hash of the new set of environment variables.
+///
+/// Operators changes are described by the full new set of the canister operators after the change.
+///
 /// `CandidType` for `CanisterChange`
 /// ```text
 /// record {
@@ -449,8 +527,9 @@ impl CanisterChange {
 
     /// Returns the number of bytes to represent a canister change in memory.
     /// The vector of controllers in `CanisterCreation` and `CanisterControllersChange`
-    /// is counted separately because the controllers are stored on heap
-    /// and thus not accounted for in `size_of::<CanisterChange>()`.
+    /// (and of operators in `CanisterOperatorsChange`) is counted separately because
+    /// the principals are stored on heap and thus not accounted for in
+    /// `size_of::<CanisterChange>()`.
     pub fn count_bytes(&self) -> NumBytes {
         let controllers_memory_size = match &self.details {
             CanisterChangeDetails::CanisterCreation(canister_creation) => {
@@ -459,9 +538,13 @@ impl CanisterChange {
             CanisterChangeDetails::CanisterControllersChange(canister_controllers_change) => {
                 std::mem::size_of_val(canister_controllers_change.controllers())
             }
+            CanisterChangeDetails::CanisterOperatorsChange(canister_operators_change) => {
+                std::mem::size_of_val(canister_operators_change.operators())
+            }
             CanisterChangeDetails::CanisterCodeDeployment(_)
             | CanisterChangeDetails::CanisterCodeUninstall
-            | CanisterChangeDetails::CanisterLoadSnapshot(_) => 0,
+            | CanisterChangeDetails::CanisterLoadSnapshot(_)
+            | CanisterChangeDetails::CanisterEnvironmentVariablesChange(_) => 0,
         };
         NumBytes::from((size_of::<CanisterChange>() + controllers_memory_size) as u64)
     }
@@ -650,6 +733,26 @@ impl From<&CanisterChangeDetails> for pb_canister_state_bits::canister_change::C
                     },
                 )
             }
+            CanisterChangeDetails::CanisterEnvironmentVariablesChange(
+                canister_environment_variables_change,
+            ) => pb_canister_state_bits::canister_change::ChangeDetails::CanisterEnvironmentVariablesChange(
+                pb_canister_state_bits::CanisterEnvironmentVariablesChange {
+                    environment_variables_hash: canister_environment_variables_change
+                        .environment_variables_hash
+                        .to_vec(),
+                },
+            ),
+            CanisterChangeDetails::CanisterOperatorsChange(canister_operators_change) => {
+                pb_canister_state_bits::canister_change::ChangeDetails::CanisterOperatorsChange(
+                    pb_canister_state_bits::CanisterOperatorsChange {
+                        operators: canister_operators_change
+                            .operators
+                            .iter()
+                            .map(|c| (*c).into())
+                            .collect::<Vec<ic_protobuf::types::v1::PrincipalId>>(),
+                    },
+                )
+            }
         }
     }
 }
@@ -714,6 +817,20 @@ impl TryFrom<pb_canister_state_bits::canister_change::ChangeDetails> for Caniste
                 canister_load_snapshot.snapshot_id,
                 canister_load_snapshot.taken_at_timestamp,
             )),
+            pb_canister_state_bits::canister_change::ChangeDetails::CanisterEnvironmentVariablesChange(
+                canister_environment_variables_change,
+            ) => Ok(CanisterChangeDetails::environment_variables_change(
+                try_decode_hash(canister_environment_variables_change.environment_variables_hash)?,
+            )),
+            pb_canister_state_bits::canister_change::ChangeDetails::CanisterOperatorsChange(
+                canister_operators_change,
+            ) => Ok(CanisterChangeDetails::operators_change(
+                canister_operators_change
+                    .operators
+                    .into_iter()
+                    .map(TryInto::try_into)
+                    .collect::<Result<Vec<PrincipalId>, _>>()?,
+            )),
         }
     }
 }
@@ -868,6 +985,8 @@ impl TryFrom<pb_canister_state_bits::LogVisibilityV2> for LogVisibilityV2 {
 ///     reserved_cycles_limit: nat;
 ///     log_visibility: log_visibility;
 ///     wasm_memory_limit: nat;
+///     log_memory_limit: nat;
+///     operators: vec principal;
 /// })`
 #[derive(Clone, Eq, PartialEq, Debug, CandidType, Deserialize)]
 pub struct DefiniteCanisterSettingsArgs {
@@ -879,9 +998,12 @@ pub struct DefiniteCanisterSettingsArgs {
     reserved_cycles_limit: candid::Nat,
     log_visibility: LogVisibilityV2,
     wasm_memory_limit: candid::Nat,
+    log_memory_limit: candid::Nat,
+    operators: Vec<PrincipalId>,
 }
 
 impl DefiniteCanisterSettingsArgs {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         controller: PrincipalId,
         controllers: Vec<PrincipalId>,
@@ -891,6 +1013,8 @@ impl DefiniteCanisterSettingsArgs {
         reserved_cycles_limit: Option<u128>,
         log_visibility: LogVisibilityV2,
         wasm_memory_limit: Option<u64>,
+        log_memory_limit: u64,
+        operators: Vec<PrincipalId>,
     ) -> Self {
         let memory_allocation = candid::Nat::from(memory_allocation.unwrap_or(0));
         let reserved_cycles_limit = candid::Nat::from(reserved_cycles_limit.unwrap_or(0));
@@ -904,6 +1028,8 @@ impl DefiniteCanisterSettingsArgs {
             reserved_cycles_limit,
             log_visibility,
             wasm_memory_limit,
+            log_memory_limit: candid::Nat::from(log_memory_limit),
+            operators,
         }
     }
 
@@ -911,6 +1037,10 @@ impl DefiniteCanisterSettingsArgs {
         self.controllers.clone()
     }
 
+    pub fn operators(&self) -> Vec<PrincipalId> {
+        self.operators.clone()
+    }
+
     pub fn reserved_cycles_limit(&self) -> candid::Nat {
         self.reserved_cycles_limit.clone()
     }
@@ -922,6 +1052,10 @@ impl DefiniteCanisterSettingsArgs {
     pub fn wasm_memory_limit(&self) -> candid::Nat {
         self.wasm_memory_limit.clone()
     }
+
+    pub fn log_memory_limit(&self) -> candid::Nat {
+        self.log_memory_limit.clone()
+    }
 }
 
 impl Payload<'_> for DefiniteCanisterSettingsArgs {}
@@ -1045,6 +1179,8 @@ impl CanisterStatusResultV2 {
         query_ingress_payload_size: u128,
         query_egress_payload_size: u128,
         wasm_memory_limit: Option<u64>,
+        log_memory_limit: u64,
+        operators: Vec<PrincipalId>,
     ) -> Self {
         Self {
             status,
@@ -1064,6 +1200,8 @@ impl CanisterStatusResultV2 {
                 reserved_cycles_limit,
                 log_visibility,
                 wasm_memory_limit,
+                log_memory_limit,
+                operators,
             ),
             freezing_threshold: candid::Nat::from(freezing_threshold),
             idle_cycles_burned_per_day: candid::Nat::from(idle_cycles_burned_per_day),
@@ -1093,6 +1231,10 @@ impl CanisterStatusResultV2 {
         self.settings.controllers()
     }
 
+    pub fn operators(&self) -> Vec<PrincipalId> {
+        self.settings.operators()
+    }
+
     pub fn memory_size(&self) -> NumBytes {
         NumBytes::from(self.memory_size.0.to_u64().unwrap())
     }
@@ -1697,12 +1839,38 @@ pub type BoundedControllers =
 
 impl Payload<'_> for BoundedControllers {}
 
+/// Maximum number of operators allowed in a request.
+const MAX_ALLOWED_OPERATORS_COUNT: usize = 10;
+
+/// Principals holding the operator role of a canister. Unlike controllers,
+/// operators can only start, stop, top up and take snapshots of a canister
+/// and read its status; they cannot change its code or settings.
+pub type BoundedOperators =
+    BoundedVec<MAX_ALLOWED_OPERATORS_COUNT, UNBOUNDED, UNBOUNDED, PrincipalId>;
+
+impl Payload<'_> for BoundedOperators {}
+
 impl DataSize for PrincipalId {
     fn data_size(&self) -> usize {
         self.as_slice().data_size()
     }
 }
 
+/// A single canister environment variable.
+///
+/// `CandidType` for `EnvironmentVariable`
+/// ```text
+/// record {
+///   name : text;
+///   value : text;
+/// }
+/// ```
+#[derive(Clone, Eq, PartialEq, Debug, CandidType, Deserialize, Serialize)]
+pub struct EnvironmentVariable {
+    pub name: String,
+    pub value: String,
+}
+
 /// Struct used for encoding/decoding
 /// `(record {
 ///     controllers: opt vec principal;
@@ -1713,6 +1881,9 @@ impl DataSize for PrincipalId {
 ///     log_visibility : opt log_visibility;
 ///     wasm_memory_limit: opt nat;
 ///     wasm_memory_threshold: opt nat;
+///     log_memory_limit: opt nat;
+///     environment_variables: opt vec environment_variable;
+///     operators: opt vec principal;
 /// })`
 #[derive(Clone, Eq, PartialEq, Debug, Default, CandidType, Deserialize)]
 pub struct CanisterSettingsArgs {
@@ -1724,6 +1895,9 @@ pub struct CanisterSettingsArgs {
     pub log_visibility: Option<LogVisibilityV2>,
     pub wasm_memory_limit: Option<candid::Nat>,
     pub wasm_memory_threshold: Option<candid::Nat>,
+    pub log_memory_limit: Option<candid::Nat>,
+    pub environment_variables: Option<Vec<EnvironmentVariable>>,
+    pub operators: Option<BoundedOperators>,
 }
 
 impl Payload<'_> for CanisterSettingsArgs {}
@@ -1741,6 +1915,9 @@ impl CanisterSettingsArgs {
             log_visibility: None,
             wasm_memory_limit: None,
             wasm_memory_threshold: None,
+            log_memory_limit: None,
+            environment_variables: None,
+            operators: None,
         }
     }
 }
@@ -1755,6 +1932,9 @@ pub struct CanisterSettingsArgsBuilder {
     log_visibility: Option<LogVisibilityV2>,
     wasm_memory_limit: Option<candid::Nat>,
     wasm_memory_threshold: Option<candid::Nat>,
+    log_memory_limit: Option<candid::Nat>,
+    environment_variables: Option<Vec<EnvironmentVariable>>,
+    operators: Option<Vec<PrincipalId>>,
 }
 
 #[allow(dead_code)]
@@ -1773,6 +1953,9 @@ impl CanisterSettingsArgsBuilder {
             log_visibility: self.log_visibility,
             wasm_memory_limit: self.wasm_memory_limit,
             wasm_memory_threshold: self.wasm_memory_threshold,
+            log_memory_limit: self.log_memory_limit,
+            environment_variables: self.environment_variables,
+            operators: self.operators.map(BoundedOperators::new),
         }
     }
 
@@ -1857,6 +2040,33 @@ impl CanisterSettingsArgsBuilder {
             ..self
         }
     }
+
+    /// Sets the size of the canister log buffer in bytes.
+    pub fn with_log_memory_limit(self, log_memory_limit: u64) -> Self {
+        Self {
+            log_memory_limit: Some(candid::Nat::from(log_memory_limit)),
+            ..self
+        }
+    }
+
+    /// Sets the environment variables, replacing any previously set ones.
+    pub fn with_environment_variables(
+        self,
+        environment_variables: Vec<EnvironmentVariable>,
+    ) -> Self {
+        Self {
+            environment_variables: Some(environment_variables),
+            ..self
+        }
+    }
+
+    /// Sets the operators, replacing any previously set ones.
+    pub fn with_operators(self, operators: Vec<PrincipalId>) -> Self {
+        Self {
+            operators: Some(operators),
+            ..self
+        }
+    }
 }
 
 /// Struct used for encoding/decoding
@@ -2251,10 +2461,130 @@ impl FromStr for SchnorrKeyId {
     }
 }
 
+/// Types of curves that can be used for vetKD key derivation.
+/// ```text
+/// (variant { bls12_381_g2 })
+/// ```
+#[derive(
+    Copy,
+    Clone,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Hash,
+    Debug,
+    CandidType,
+    Deserialize,
+    EnumIter,
+    Serialize,
+)]
+pub enum VetKdCurve {
+    #[serde(rename = "bls12_381_g2")]
+    #[allow(non_camel_case_types)]
+    Bls12_381_G2,
+}
+
+impl From<&VetKdCurve> for pb_registry_crypto::VetKdCurve {
+    fn from(item: &VetKdCurve) -> Self {
+        match item {
+            VetKdCurve::Bls12_381_G2 => pb_registry_crypto::VetKdCurve::Bls12381G2,
+        }
+    }
+}
+
+impl TryFrom<pb_registry_crypto::VetKdCurve> for VetKdCurve {
+    type Error = ProxyDecodeError;
+
+    fn try_from(item: pb_registry_crypto::VetKdCurve) -> Result<Self, Self::Error> {
+        match item {
+            pb_registry_crypto::VetKdCurve::Bls12381G2 => Ok(VetKdCurve::Bls12_381_G2),
+            pb_registry_crypto::VetKdCurve::Unspecified => Err(ProxyDecodeError::ValueOutOfRange {
+                typ: "VetKdCurve",
+                err: format!("Unable to convert {:?} to a VetKdCurve", item),
+            }),
+        }
+    }
+}
+
+impl std::fmt::Display for VetKdCurve {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl FromStr for VetKdCurve {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "bls12_381_g2" => Ok(Self::Bls12_381_G2),
+            _ => Err(format!("{} is not a recognized vetKD curve", s)),
+        }
+    }
+}
+
+/// Unique identifier for a key that can be used for vetKD key derivation. The
+/// name is just an identifier, but it may be used to convey some information
+/// about the key (e.g. that the key is meant to be used for testing purposes).
+/// ```text
+/// (record { curve: vetkd_curve; name: text})
+/// ```
+#[derive(
+    Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, CandidType, Deserialize, Serialize,
+)]
+pub struct VetKdKeyId {
+    pub curve: VetKdCurve,
+    pub name: String,
+}
+
+impl From<&VetKdKeyId> for pb_registry_crypto::VetKdKeyId {
+    fn from(item: &VetKdKeyId) -> Self {
+        Self {
+            curve: pb_registry_crypto::VetKdCurve::from(&item.curve) as i32,
+            name: item.name.clone(),
+        }
+    }
+}
+
+impl TryFrom<pb_registry_crypto::VetKdKeyId> for VetKdKeyId {
+    type Error = ProxyDecodeError;
+    fn try_from(item: pb_registry_crypto::VetKdKeyId) -> Result<Self, Self::Error> {
+        let pb_registry_crypto::VetKdKeyId { curve, name } = item;
+        let curve =
+            VetKdCurve::try_from(pb_registry_crypto::VetKdCurve::try_from(curve).map_err(
+                |_| ProxyDecodeError::ValueOutOfRange {
+                    typ: "VetKdKeyId",
+                    err: format!("Unable to convert {} to a VetKdCurve", curve),
+                },
+            )?)?;
+        Ok(Self { curve, name })
+    }
+}
+
+impl std::fmt::Display for VetKdKeyId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.curve, self.name)
+    }
+}
+
+impl FromStr for VetKdKeyId {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (curve, name) = s
+            .split_once(':')
+            .ok_or_else(|| format!("vetKD key id {} does not contain a ':'", s))?;
+        Ok(VetKdKeyId {
+            curve: curve.parse::<VetKdCurve>()?,
+            name: name.to_string(),
+        })
+    }
+}
+
 /// Unique identifier for a key that can be used for one of the signature schemes
 /// supported on the IC.
 /// ```text
-/// (variant { EcdsaKeyId; SchnorrKeyId })
+/// (variant { EcdsaKeyId; SchnorrKeyId; VetKdKeyId })
 /// ```
 #[derive(
     Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, CandidType, Deserialize, Serialize,
@@ -2262,6 +2592,19 @@ impl FromStr for SchnorrKeyId {
 pub enum MasterPublicKeyId {
     Ecdsa(EcdsaKeyId),
     Schnorr(SchnorrKeyId),
+    VetKd(VetKdKeyId),
+}
+
+impl MasterPublicKeyId {
+    /// Returns true if the key is generated and used through the IDKG protocol
+    /// (i.e. it requires pre-signatures), as opposed to vetKD keys which are
+    /// derived from NiDKG transcripts.
+    pub fn is_idkg_key(&self) -> bool {
+        match self {
+            Self::Ecdsa(_) | Self::Schnorr(_) => true,
+            Self::VetKd(_) => false,
+        }
+    }
 }
 
 impl From<&MasterPublicKeyId> for pb_registry_crypto::MasterPublicKeyId {
@@ -2270,6 +2613,7 @@ impl From<&MasterPublicKeyId> for pb_registry_crypto::MasterPublicKeyId {
         let key_id_pb = match item {
             MasterPublicKeyId::Schnorr(schnorr_key_id) => KeyId::Schnorr(schnorr_key_id.into()),
             MasterPublicKeyId::Ecdsa(ecdsa_key_id) => KeyId::Ecdsa(ecdsa_key_id.into()),
+            MasterPublicKeyId::VetKd(vetkd_key_id) => KeyId::Vetkd(vetkd_key_id.into()),
         };
         Self {
             key_id: Some(key_id_pb),
@@ -2289,6 +2633,7 @@ impl TryFrom<pb_registry_crypto::MasterPublicKeyId> for MasterPublicKeyId {
                 MasterPublicKeyId::Schnorr(schnorr_key_id.try_into()?)
             }
             KeyId::Ecdsa(ecdsa_key_id) => MasterPublicKeyId::Ecdsa(ecdsa_key_id.try_into()?),
+            KeyId::Vetkd(vetkd_key_id) => MasterPublicKeyId::VetKd(vetkd_key_id.try_into()?),
         };
         Ok(master_public_key_id)
     }
@@ -2305,6 +2650,10 @@ impl std::fmt::Display for MasterPublicKeyId {
                 write!(f, "schnorr:")?;
                 schnorr_key_id.fmt(f)
             }
+            Self::VetKd(vetkd_key_id) => {
+                write!(f, "vetkd:")?;
+                vetkd_key_id.fmt(f)
+            }
         }
     }
 }
@@ -2318,6 +2667,7 @@ impl FromStr for MasterPublicKeyId {
         match scheme.to_lowercase().as_str() {
             "ecdsa" => Ok(Self::Ecdsa(EcdsaKeyId::from_str(key_id)?)),
             "schnorr" => Ok(Self::Schnorr(SchnorrKeyId::from_str(key_id)?)),
+            "vetkd" => Ok(Self::VetKd(VetKdKeyId::from_str(key_id)?)),
             _ => Err(format!(
                 "Scheme {} in master public key id {} is not supported.",
                 scheme, s
@@ -2522,6 +2872,37 @@ pub struct SchnorrPublicKeyResponse {
 
 impl Payload<'_> for SchnorrPublicKeyResponse {}
 
+/// Represents the argument of the vetkd_public_key API.
+/// ```text
+/// (record {
+///   canister_id : opt canister_id;
+///   derivation_path : vec blob;
+///   key_id : vetkd_key_id;
+/// })
+/// ```
+#[derive(Eq, PartialEq, Debug, CandidType, Deserialize)]
+pub struct VetKdPublicKeyArgs {
+    pub canister_id: Option<CanisterId>,
+    pub derivation_path: DerivationPath,
+    pub key_id: VetKdKeyId,
+}
+
+impl Payload<'_> for VetKdPublicKeyArgs {}
+
+/// Represents the response of the vetkd_public_key API.
+/// ```text
+/// (record {
+///   public_key : blob;
+/// })
+/// ```
+#[derive(Debug, CandidType, Deserialize)]
+pub struct VetKdPublicKeyResult {
+    #[serde(with = "serde_bytes")]
+    pub public_key: Vec<u8>,
+}
+
+impl Payload<'_> for VetKdPublicKeyResult {}
+
 /// Struct used to return the xnet initial dealings.
 #[derive(Debug)]
 pub struct ComputeInitialIDkgDealingsResponse {
@@ -2633,15 +3014,96 @@ pub struct NodeMetricsHistoryResponse {
 
 impl Payload<'_> for NodeMetricsHistoryResponse {}
 
+/// `CandidType` for `SubnetInfoArgs`
+/// ```text
+/// record {
+///     subnet_id: principal;
+/// }
+/// ```
+#[derive(Clone, Debug, Default, CandidType, Deserialize)]
+pub struct SubnetInfoArgs {
+    pub subnet_id: PrincipalId,
+}
+
+impl Payload<'_> for SubnetInfoArgs {}
+
+/// `CandidType` for `SubnetInfoResponse`
+///
+/// `key_ids` are the chain keys the subnet has signing enabled for.
+/// ```text
+/// record {
+///     subnet_type: variant { application; system; verified_application };
+///     subnet_size: nat64;
+///     replica_version: text;
+///     key_ids: vec master_public_key_id;
+/// }
+/// ```
+#[derive(Clone, Debug, Default, CandidType, Deserialize, PartialEq, Eq)]
+pub struct SubnetInfoResponse {
+    pub subnet_type: SubnetType,
+    pub subnet_size: u64,
+    pub replica_version: String,
+    pub key_ids: Vec<MasterPublicKeyId>,
+}
+
+impl Payload<'_> for SubnetInfoResponse {}
+
+/// `CandidType` for `FetchCanisterLogsRange`
+/// ```text
+/// record {
+///     start: nat64;
+///     end: nat64;
+/// }
+/// ```
+/// The range includes `start` and excludes `end`.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, CandidType, Deserialize)]
+pub struct FetchCanisterLogsRange {
+    pub start: u64,
+    pub end: u64,
+}
+
+impl FetchCanisterLogsRange {
+    pub fn contains(&self, value: u64) -> bool {
+        self.start <= value && value < self.end
+    }
+}
+
+/// `CandidType` for `FetchCanisterLogsFilter`
+/// ```text
+/// variant {
+///     by_idx: fetch_canister_logs_range;
+///     by_timestamp_nanos: fetch_canister_logs_range;
+/// }
+/// ```
+#[derive(Copy, Clone, Eq, PartialEq, Debug, CandidType, Deserialize)]
+pub enum FetchCanisterLogsFilter {
+    #[serde(rename = "by_idx")]
+    ByIdx(FetchCanisterLogsRange),
+    #[serde(rename = "by_timestamp_nanos")]
+    ByTimestampNanos(FetchCanisterLogsRange),
+}
+
+impl FetchCanisterLogsFilter {
+    /// Returns true if the record passes the filter.
+    pub fn matches(&self, record: &CanisterLogRecord) -> bool {
+        match self {
+            Self::ByIdx(range) => range.contains(record.idx),
+            Self::ByTimestampNanos(range) => range.contains(record.timestamp_nanos),
+        }
+    }
+}
+
 /// `CandidType` for `FetchCanisterLogsRequest`
 /// ```text
 /// record {
 ///     canister_id: principal;
+///     filter: opt fetch_canister_logs_filter;
 /// }
 /// ```
 #[derive(Clone, Debug, Default, CandidType, Deserialize)]
 pub struct FetchCanisterLogsRequest {
     pub canister_id: PrincipalId,
+    pub filter: Option<FetchCanisterLogsFilter>,
 }
 
 impl Payload<'_> for FetchCanisterLogsRequest {}
@@ -2650,6 +3112,15 @@ impl FetchCanisterLogsRequest {
     pub fn new(canister_id: CanisterId) -> Self {
         Self {
             canister_id: canister_id.into(),
+            filter: None,
+        }
+    }
+
+    /// Only returns the log records that pass the given filter.
+    pub fn with_filter(self, filter: FetchCanisterLogsFilter) -> Self {
+        Self {
+            filter: Some(filter),
+            ..self
         }
     }
 
@@ -2692,6 +3163,20 @@ fn test_canister_log_record_data_size() {
     assert_eq!(record.data_size(), 8 + 8 + 24 + 3);
 }
 
+#[test]
+fn test_fetch_canister_logs_filter() {
+    let record = CanisterLogRecord {
+        idx: 5,
+        timestamp_nanos: 200,
+        content: vec![],
+    };
+    let range = |start, end| FetchCanisterLogsRange { start, end };
+    assert!(FetchCanisterLogsFilter::ByIdx(range(5, 6)).matches(&record));
+    assert!(!FetchCanisterLogsFilter::ByIdx(range(0, 5)).matches(&record));
+    assert!(FetchCanisterLogsFilter::ByTimestampNanos(range(100, 201)).matches(&record));
+    assert!(!FetchCanisterLogsFilter::ByTimestampNanos(range(201, 300)).matches(&record));
+}
+
 impl From<&CanisterLogRecord> for pb_canister_state_bits::CanisterLogRecord {
     fn from(item: &CanisterLogRecord) -> Self {
         Self {
@@ -3173,6 +3658,324 @@ impl ListCanisterSnapshotArgs {
 
 impl Payload<'_> for ListCanisterSnapshotArgs {}
 
+fn validate_snapshot_id(snapshot_id: &[u8]) -> Result<(), UserError> {
+    SnapshotId::try_from(&snapshot_id.to_vec())
+        .map(|_| ())
+        .map_err(|err| {
+            UserError::new(
+                ErrorCode::InvalidManagementPayload,
+                format!("Payload deserialization error: {err:?}"),
+            )
+        })
+}
+
+/// Candid type representing a Wasm global exported by a canister snapshot.
+/// `(variant {
+///     i32: int32;
+///     i64: int64;
+///     f32: float32;
+///     f64: float64;
+///     v128: nat;
+/// })`
+#[derive(Copy, Clone, PartialEq, Debug, CandidType, Deserialize)]
+pub enum Global {
+    #[serde(rename = "i32")]
+    I32(i32),
+    #[serde(rename = "i64")]
+    I64(i64),
+    #[serde(rename = "f32")]
+    F32(f32),
+    #[serde(rename = "f64")]
+    F64(f64),
+    #[serde(rename = "v128")]
+    V128(u128),
+}
+
+/// Struct used for encoding/decoding
+/// `(record {
+///     canister_id: principal;
+///     snapshot_id: blob;
+/// })`
+#[derive(Clone, Eq, PartialEq, Debug, Default, CandidType, Deserialize)]
+pub struct ReadCanisterSnapshotMetadataArgs {
+    canister_id: PrincipalId,
+    #[serde(with = "serde_bytes")]
+    snapshot_id: Vec<u8>,
+}
+
+impl ReadCanisterSnapshotMetadataArgs {
+    pub fn new(canister_id: CanisterId, snapshot_id: SnapshotId) -> Self {
+        Self {
+            canister_id: canister_id.get(),
+            snapshot_id: snapshot_id.to_vec(),
+        }
+    }
+
+    pub fn get_canister_id(&self) -> CanisterId {
+        CanisterId::unchecked_from_principal(self.canister_id)
+    }
+
+    pub fn get_snapshot_id(&self) -> SnapshotId {
+        SnapshotId::try_from(&self.snapshot_id).unwrap()
+    }
+}
+
+impl<'a> Payload<'a> for ReadCanisterSnapshotMetadataArgs {
+    fn decode(blob: &'a [u8]) -> Result<Self, UserError> {
+        let args = Decode!([decoder_config()]; blob, Self).map_err(candid_error_to_user_error)?;
+        // Verify that snapshot ID has the correct format.
+        validate_snapshot_id(&args.snapshot_id)?;
+        Ok(args)
+    }
+}
+
+/// Struct to be returned when reading the metadata of a canister snapshot.
+/// The same fields are expected by `upload_canister_snapshot_metadata`,
+/// so that a downloaded snapshot can be uploaded again as is.
+/// `(record {
+///     taken_at_timestamp: nat64;
+///     canister_version: nat64;
+///     wasm_module_size: nat64;
+///     exported_globals: vec global;
+///     wasm_memory_size: nat64;
+///     stable_memory_size: nat64;
+///     wasm_chunk_store: vec record { hash: blob };
+///     certified_data: blob;
+/// })`
+#[derive(Clone, PartialEq, Debug, Default, CandidType, Deserialize)]
+pub struct ReadCanisterSnapshotMetadataResponse {
+    pub taken_at_timestamp: u64,
+    pub canister_version: u64,
+    pub wasm_module_size: u64,
+    pub exported_globals: Vec<Global>,
+    pub wasm_memory_size: u64,
+    pub stable_memory_size: u64,
+    pub wasm_chunk_store: Vec<ChunkHash>,
+    #[serde(with = "serde_bytes")]
+    pub certified_data: Vec<u8>,
+}
+
+impl Payload<'_> for ReadCanisterSnapshotMetadataResponse {}
+
+/// Identifies the part of a canister snapshot to read.
+/// `(variant {
+///     wasm_module: record { offset: nat64; size: nat64 };
+///     main_memory: record { offset: nat64; size: nat64 };
+///     stable_memory: record { offset: nat64; size: nat64 };
+///     wasm_chunk: record { hash: blob };
+/// })`
+#[derive(Clone, Eq, PartialEq, Debug, CandidType, Deserialize)]
+pub enum CanisterSnapshotDataKind {
+    #[serde(rename = "wasm_module")]
+    WasmModule { offset: u64, size: u64 },
+    #[serde(rename = "main_memory")]
+    MainMemory { offset: u64, size: u64 },
+    #[serde(rename = "stable_memory")]
+    StableMemory { offset: u64, size: u64 },
+    #[serde(rename = "wasm_chunk")]
+    WasmChunk {
+        #[serde(with = "serde_bytes")]
+        hash: Vec<u8>,
+    },
+}
+
+/// Struct used for encoding/decoding
+/// `(record {
+///     canister_id: principal;
+///     snapshot_id: blob;
+///     kind: canister_snapshot_data_kind;
+/// })`
+#[derive(Clone, Eq, PartialEq, Debug, CandidType, Deserialize)]
+pub struct ReadCanisterSnapshotDataArgs {
+    canister_id: PrincipalId,
+    #[serde(with = "serde_bytes")]
+    snapshot_id: Vec<u8>,
+    pub kind: CanisterSnapshotDataKind,
+}
+
+impl ReadCanisterSnapshotDataArgs {
+    pub fn new(
+        canister_id: CanisterId,
+        snapshot_id: SnapshotId,
+        kind: CanisterSnapshotDataKind,
+    ) -> Self {
+        Self {
+            canister_id: canister_id.get(),
+            snapshot_id: snapshot_id.to_vec(),
+            kind,
+        }
+    }
+
+    pub fn get_canister_id(&self) -> CanisterId {
+        CanisterId::unchecked_from_principal(self.canister_id)
+    }
+
+    pub fn get_snapshot_id(&self) -> SnapshotId {
+        SnapshotId::try_from(&self.snapshot_id).unwrap()
+    }
+}
+
+impl<'a> Payload<'a> for ReadCanisterSnapshotDataArgs {
+    fn decode(blob: &'a [u8]) -> Result<Self, UserError> {
+        let args = Decode!([decoder_config()]; blob, Self).map_err(candid_error_to_user_error)?;
+        // Verify that snapshot ID has the correct format.
+        validate_snapshot_id(&args.snapshot_id)?;
+        Ok(args)
+    }
+}
+
+/// Struct to be returned when reading data from a canister snapshot.
+/// `(record {
+///     chunk: blob;
+/// })`
+#[derive(Clone, Eq, PartialEq, Debug, Default, CandidType, Deserialize)]
+pub struct ReadCanisterSnapshotDataResponse {
+    #[serde(with = "serde_bytes")]
+    pub chunk: Vec<u8>,
+}
+
+impl Payload<'_> for ReadCanisterSnapshotDataResponse {}
+
+/// Struct used for encoding/decoding
+/// `(record {
+///     canister_id: principal;
+///     replace_snapshot: opt blob;
+///     wasm_module_size: nat64;
+///     exported_globals: vec global;
+///     wasm_memory_size: nat64;
+///     stable_memory_size: nat64;
+///     wasm_chunk_store: vec record { hash: blob };
+///     certified_data: blob;
+/// })`
+#[derive(Clone, PartialEq, Debug, Default, CandidType, Deserialize)]
+pub struct UploadCanisterSnapshotMetadataArgs {
+    pub canister_id: PrincipalId,
+    pub replace_snapshot: Option<serde_bytes::ByteBuf>,
+    pub wasm_module_size: u64,
+    pub exported_globals: Vec<Global>,
+    pub wasm_memory_size: u64,
+    pub stable_memory_size: u64,
+    pub wasm_chunk_store: Vec<ChunkHash>,
+    #[serde(with = "serde_bytes")]
+    pub certified_data: Vec<u8>,
+}
+
+impl UploadCanisterSnapshotMetadataArgs {
+    pub fn get_canister_id(&self) -> CanisterId {
+        CanisterId::unchecked_from_principal(self.canister_id)
+    }
+
+    pub fn replace_snapshot(&self) -> Option<SnapshotId> {
+        self.replace_snapshot
+            .as_ref()
+            .map(|bytes| SnapshotId::try_from(&bytes.clone().into_vec()).unwrap())
+    }
+}
+
+impl<'a> Payload<'a> for UploadCanisterSnapshotMetadataArgs {
+    fn decode(blob: &'a [u8]) -> Result<Self, UserError> {
+        let args = Decode!([decoder_config()]; blob, Self).map_err(candid_error_to_user_error)?;
+        // Verify that snapshot ID has the correct format.
+        if let Some(replace_snapshot) = &args.replace_snapshot {
+            validate_snapshot_id(replace_snapshot)?;
+        }
+        Ok(args)
+    }
+}
+
+/// Struct to be returned when uploading the metadata of a canister snapshot.
+/// `(record {
+///     snapshot_id: blob;
+/// })`
+#[derive(Clone, Eq, PartialEq, Debug, Default, CandidType, Deserialize)]
+pub struct UploadCanisterSnapshotMetadataResponse {
+    #[serde(with = "serde_bytes")]
+    pub snapshot_id: Vec<u8>,
+}
+
+impl Payload<'_> for UploadCanisterSnapshotMetadataResponse {}
+
+impl UploadCanisterSnapshotMetadataResponse {
+    pub fn new(snapshot_id: &SnapshotId) -> Self {
+        Self {
+            snapshot_id: snapshot_id.to_vec(),
+        }
+    }
+
+    pub fn get_snapshot_id(&self) -> SnapshotId {
+        SnapshotId::try_from(&self.snapshot_id).unwrap()
+    }
+}
+
+/// Identifies where uploaded data is written to in a canister snapshot.
+/// `(variant {
+///     wasm_module: record { offset: nat64 };
+///     main_memory: record { offset: nat64 };
+///     stable_memory: record { offset: nat64 };
+///     wasm_chunk;
+/// })`
+#[derive(Clone, Eq, PartialEq, Debug, CandidType, Deserialize)]
+pub enum CanisterSnapshotDataOffset {
+    #[serde(rename = "wasm_module")]
+    WasmModule { offset: u64 },
+    #[serde(rename = "main_memory")]
+    MainMemory { offset: u64 },
+    #[serde(rename = "stable_memory")]
+    StableMemory { offset: u64 },
+    #[serde(rename = "wasm_chunk")]
+    WasmChunk,
+}
+
+/// Struct used for encoding/decoding
+/// `(record {
+///     canister_id: principal;
+///     snapshot_id: blob;
+///     kind: canister_snapshot_data_offset;
+///     chunk: blob;
+/// })`
+#[derive(Clone, Eq, PartialEq, Debug, CandidType, Deserialize)]
+pub struct UploadCanisterSnapshotDataArgs {
+    canister_id: PrincipalId,
+    #[serde(with = "serde_bytes")]
+    snapshot_id: Vec<u8>,
+    pub kind: CanisterSnapshotDataOffset,
+    #[serde(with = "serde_bytes")]
+    pub chunk: Vec<u8>,
+}
+
+impl UploadCanisterSnapshotDataArgs {
+    pub fn new(
+        canister_id: CanisterId,
+        snapshot_id: SnapshotId,
+        kind: CanisterSnapshotDataOffset,
+        chunk: Vec<u8>,
+    ) -> Self {
+        Self {
+            canister_id: canister_id.get(),
+            snapshot_id: snapshot_id.to_vec(),
+            kind,
+            chunk,
+        }
+    }
+
+    pub fn get_canister_id(&self) -> CanisterId {
+        CanisterId::unchecked_from_principal(self.canister_id)
+    }
+
+    pub fn get_snapshot_id(&self) -> SnapshotId {
+        SnapshotId::try_from(&self.snapshot_id).unwrap()
+    }
+}
+
+impl<'a> Payload<'a> for UploadCanisterSnapshotDataArgs {
+    fn decode(blob: &'a [u8]) -> Result<Self, UserError> {
+        let args = Decode!([decoder_config()]; blob, Self).map_err(candid_error_to_user_error)?;
+        // Verify that snapshot ID has the correct format.
+        validate_snapshot_id(&args.snapshot_id)?;
+        Ok(args)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -3346,6 +4149,26 @@ mod tests {
         }
     }
 
+    #[test]
+    fn vetkd_curve_round_trip() {
+        for curve in VetKdCurve::iter() {
+            assert_eq!(format!("{}", curve).parse::<VetKdCurve>().unwrap(), curve);
+        }
+    }
+
+    #[test]
+    fn vetkd_key_id_round_trip() {
+        for curve in VetKdCurve::iter() {
+            for name in ["bls12_381_g2", "", "other_key", "other key", "other:key"] {
+                let key = VetKdKeyId {
+                    curve,
+                    name: name.to_string(),
+                };
+                assert_eq!(format!("{}", key).parse::<VetKdKeyId>().unwrap(), key);
+            }
+        }
+    }
+
     #[test]
     fn master_public_key_id_round_trip() {
         for algorithm in SchnorrAlgorithm::iter() {
@@ -3373,6 +4196,19 @@ mod tests {
                 );
             }
         }
+
+        for curve in VetKdCurve::iter() {
+            for name in ["bls12_381_g2", "", "other_key", "other key", "other:key"] {
+                let key = MasterPublicKeyId::VetKd(VetKdKeyId {
+                    curve,
+                    name: name.to_string(),
+                });
+                assert_eq!(
+                    format!("{}", key).parse::<MasterPublicKeyId>().unwrap(),
+                    key
+                );
+            }
+        }
     }
 
     #[test]
@@ -41,12 +41,18 @@
 //! The blockmaker indicates, which requests have timed out, i.e. the blocktime of the latest finalized block is higher than
 //! the timestamp of a request plus the timeout interval. This condition is verifiable by the other nodes in the network.
 //! Once a timeout has made it into a finalized block, the request is answered with an error message.
+//!
+//! A request may also be non-replicated (see [`Replication`]), in which case only a single designated node
+//! makes the request in 1a. The share of that node alone proves the response, so it is included without waiting for
+//! other shares in 3b and no divergence can be detected in 4b. Since only the designated node holds the content,
+//! the response is included once that node is the block maker.
 use crate::{
     artifact::{CanisterHttpResponseId, IdentifiableArtifact, PbArtifact},
     crypto::{CryptoHashOf, Signed},
     messages::{CallbackId, RejectContext, Request},
+    node_id_into_protobuf, node_id_try_from_option,
     signature::*,
-    CanisterId, CountBytes, RegistryVersion, Time,
+    CanisterId, CountBytes, NodeId, RegistryVersion, Time,
 };
 use ic_base_types::{NumBytes, PrincipalId};
 use ic_error_types::{ErrorCode, RejectCode, UserError};
@@ -114,6 +120,20 @@ impl From<TransformContext> for Transform {
     }
 }
 
+/// Specifies which nodes of the subnet make a canister http request.
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug, Default, Deserialize, Serialize)]
+pub enum Replication {
+    /// All nodes make the request and the response is only delivered once
+    /// enough of them agree on its content.
+    #[default]
+    FullyReplicated,
+    /// Only the given node makes the request and its signed response is
+    /// delivered as is. This is cheaper, but the canister has to trust the
+    /// single node, so it is meant for idempotent requests or responses the
+    /// canister can verify itself (e.g. signed by the server).
+    NonReplicated(NodeId),
+}
+
 #[derive(Clone, Eq, PartialEq, Hash, Debug, Deserialize, Serialize)]
 pub struct CanisterHttpRequestContext {
     pub request: Request,
@@ -125,6 +145,7 @@ pub struct CanisterHttpRequestContext {
     pub http_method: CanisterHttpMethod,
     pub transform: Option<Transform>,
     pub time: Time,
+    pub replication: Replication,
 }
 
 impl From<&CanisterHttpRequestContext> for pb_metadata::CanisterHttpRequestContext {
@@ -155,6 +176,10 @@ impl From<&CanisterHttpRequestContext> for pb_metadata::CanisterHttpRequestConte
                 .map(|transform| transform.context.clone()),
             http_method: pb_metadata::HttpMethod::from(&context.http_method).into(),
             time: context.time.as_nanos_since_unix_epoch(),
+            non_replicated_node: match context.replication {
+                Replication::FullyReplicated => None,
+                Replication::NonReplicated(node_id) => Some(node_id_into_protobuf(node_id)),
+            },
         }
     }
 }
@@ -213,6 +238,10 @@ impl TryFrom<pb_metadata::CanisterHttpRequestContext> for CanisterHttpRequestCon
                 .try_into()?,
             transform,
             time: Time::from_nanos_since_unix_epoch(context.time),
+            replication: match context.non_replicated_node {
+                None => Replication::FullyReplicated,
+                node_id => Replication::NonReplicated(node_id_try_from_option(node_id)?),
+            },
         })
     }
 }
@@ -332,9 +361,15 @@ impl TryFrom<(Time, &Request, CanisterHttpRequestArgs)> for CanisterHttpRequestC
                 HttpMethod::GET => CanisterHttpMethod::GET,
                 HttpMethod::POST => CanisterHttpMethod::POST,
                 HttpMethod::HEAD => CanisterHttpMethod::HEAD,
+                HttpMethod::PUT => CanisterHttpMethod::PUT,
+                HttpMethod::PATCH => CanisterHttpMethod::PATCH,
+                HttpMethod::DELETE => CanisterHttpMethod::DELETE,
             },
             transform: args.transform.map(From::from),
             time,
+            // The node making a non-replicated request is picked by execution,
+            // which has access to the subnet topology.
+            replication: Replication::FullyReplicated,
         })
     }
 }
@@ -531,6 +566,9 @@ pub enum CanisterHttpMethod {
     GET = 1,
     POST = 2,
     HEAD = 3,
+    PUT = 4,
+    PATCH = 5,
+    DELETE = 6,
 }
 
 impl From<&CanisterHttpMethod> for pb_metadata::HttpMethod {
@@ -539,6 +577,9 @@ impl From<&CanisterHttpMethod> for pb_metadata::HttpMethod {
             CanisterHttpMethod::GET => pb_metadata::HttpMethod::Get,
             CanisterHttpMethod::POST => pb_metadata::HttpMethod::Post,
             CanisterHttpMethod::HEAD => pb_metadata::HttpMethod::Head,
+            CanisterHttpMethod::PUT => pb_metadata::HttpMethod::Put,
+            CanisterHttpMethod::PATCH => pb_metadata::HttpMethod::Patch,
+            CanisterHttpMethod::DELETE => pb_metadata::HttpMethod::Delete,
         }
     }
 }
@@ -551,6 +592,9 @@ impl TryFrom<pb_metadata::HttpMethod> for CanisterHttpMethod {
             pb_metadata::HttpMethod::Get => Ok(CanisterHttpMethod::GET),
             pb_metadata::HttpMethod::Post => Ok(CanisterHttpMethod::POST),
             pb_metadata::HttpMethod::Head => Ok(CanisterHttpMethod::HEAD),
+            pb_metadata::HttpMethod::Put => Ok(CanisterHttpMethod::PUT),
+            pb_metadata::HttpMethod::Patch => Ok(CanisterHttpMethod::PATCH),
+            pb_metadata::HttpMethod::Delete => Ok(CanisterHttpMethod::DELETE),
             pb_metadata::HttpMethod::Unspecified => Err(ProxyDecodeError::ValueOutOfRange {
                 typ: "ic_protobuf::state::system_metadata::v1::HttpMethod",
                 err: "Unspecified HttpMethod".to_string(),
@@ -676,6 +720,7 @@ mod tests {
                 deadline: NO_DEADLINE,
             },
             time: UNIX_EPOCH,
+            replication: Replication::FullyReplicated,
         };
 
         let expected_size = context.url.len()
@@ -718,6 +763,7 @@ mod tests {
                 deadline: NO_DEADLINE,
             },
             time: UNIX_EPOCH,
+            replication: Replication::FullyReplicated,
         };
 
         let expected_size = context.url.len()
@@ -730,6 +776,40 @@ mod tests {
         );
     }
 
+    #[test]
+    fn canister_http_request_context_proto_round_trip() {
+        for replication in [
+            Replication::FullyReplicated,
+            Replication::NonReplicated(NodeId::from(PrincipalId::new_node_test_id(1))),
+        ] {
+            let initial = CanisterHttpRequestContext {
+                url: "https://example.com".to_string(),
+                headers: vec![],
+                body: None,
+                max_response_bytes: Some(NumBytes::from(1024)),
+                http_method: CanisterHttpMethod::GET,
+                transform: None,
+                request: Request {
+                    receiver: CanisterId::ic_00(),
+                    sender: CanisterId::ic_00(),
+                    sender_reply_callback: CallbackId::from(3),
+                    payment: Cycles::new(10),
+                    method_name: "http_request".to_string(),
+                    method_payload: Vec::new(),
+                    metadata: None,
+                    deadline: NO_DEADLINE,
+                },
+                time: UNIX_EPOCH,
+                replication,
+            };
+
+            let encoded = pb_metadata::CanisterHttpRequestContext::from(&initial);
+            let round_trip = CanisterHttpRequestContext::try_from(encoded).unwrap();
+
+            assert_eq!(initial, round_trip);
+        }
+    }
+
     #[test]
     fn canister_http_method_proto_round_trip() {
         for initial in CanisterHttpMethod::iter() {
@@ -748,7 +828,7 @@ mod tests {
             CanisterHttpMethod::iter()
                 .map(|x| x as i32)
                 .collect::<Vec<i32>>(),
-            [1, 2, 3]
+            [1, 2, 3, 4, 5, 6]
         );
     }
 }
@@ -5,35 +5,53 @@ use ic_validate_eq_derive::ValidateEq;
 use serde::Serialize;
 use std::collections::VecDeque;
 
-/// The maximum allowed size of a canister log buffer.
-pub const MAX_ALLOWED_CANISTER_LOG_BUFFER_SIZE: usize = 4 * 1024;
-
-fn truncate_content(mut record: CanisterLogRecord) -> CanisterLogRecord {
-    let max_content_size =
-        MAX_ALLOWED_CANISTER_LOG_BUFFER_SIZE - std::mem::size_of::<CanisterLogRecord>();
+/// The default size of a canister log buffer.
+///
+/// This is also the smallest buffer a canister can configure and the amount
+/// of log memory that is not charged for.
+pub const DEFAULT_CANISTER_LOG_BUFFER_SIZE: usize = 4 * 1024;
+
+/// The maximum size of a canister log buffer that can be configured via the
+/// `log_memory_limit` canister setting.
+pub const MAX_CANISTER_LOG_BUFFER_SIZE: usize = 2 * 1024 * 1024;
+
+fn truncate_content(mut record: CanisterLogRecord, capacity: usize) -> CanisterLogRecord {
+    let max_content_size = capacity.saturating_sub(std::mem::size_of::<CanisterLogRecord>());
     record.content.truncate(max_content_size);
     record
 }
 
 // Helper struct to hold canister log records and keep track of the used space.
 // This is needed to avoid iterating over all records to calculate the used space.
-#[derive(Clone, Eq, PartialEq, Debug, Default, Deserialize, Serialize, ValidateEq)]
+#[derive(Clone, Eq, PartialEq, Debug, Deserialize, Serialize, ValidateEq)]
 struct Records {
     #[validate_eq(Ignore)]
     records: VecDeque<CanisterLogRecord>,
     used_space: usize,
+    capacity: usize,
+}
+
+impl Default for Records {
+    fn default() -> Self {
+        Self {
+            records: Default::default(),
+            used_space: 0,
+            capacity: DEFAULT_CANISTER_LOG_BUFFER_SIZE,
+        }
+    }
 }
 
 impl Records {
-    fn from(records: Vec<CanisterLogRecord>) -> Self {
+    fn from(records: Vec<CanisterLogRecord>, capacity: usize) -> Self {
         let records: Vec<_> = records
             .into_iter()
-            .map(truncate_content) // Apply size limit to each record's content.
+            .map(|r| truncate_content(r, capacity)) // Apply size limit to each record's content.
             .collect();
         let used_space = records.iter().map(|r| r.data_size()).sum();
         let mut result = Self {
             records: records.into(),
             used_space,
+            capacity,
         };
         // Make sure the buffer is within limit.
         result.make_free_space_within_limit(0);
@@ -82,7 +100,13 @@ impl Records {
     }
 
     fn capacity(&self) -> usize {
-        MAX_ALLOWED_CANISTER_LOG_BUFFER_SIZE
+        self.capacity
+    }
+
+    fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        // Drop the oldest records if the buffer shrank.
+        self.make_free_space_within_limit(0);
     }
 
     fn make_free_space_within_limit(&mut self, new_data_size: usize) {
@@ -109,9 +133,18 @@ pub struct CanisterLog {
 impl CanisterLog {
     /// Creates a new `CanisterLog` with the given next index and records.
     pub fn new(next_idx: u64, records: Vec<CanisterLogRecord>) -> Self {
+        Self::new_with_capacity(next_idx, records, DEFAULT_CANISTER_LOG_BUFFER_SIZE)
+    }
+
+    /// Creates a new `CanisterLog` with the given next index, records and buffer capacity.
+    pub fn new_with_capacity(
+        next_idx: u64,
+        records: Vec<CanisterLogRecord>,
+        capacity: usize,
+    ) -> Self {
         Self {
             next_idx,
-            records: Records::from(records),
+            records: Records::from(records, capacity),
         }
     }
 
@@ -143,6 +176,23 @@ impl CanisterLog {
         self.records.capacity()
     }
 
+    /// Changes the size of the canister log buffer, dropping the oldest
+    /// records if they no longer fit.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.records.set_capacity(capacity);
+    }
+
+    /// Returns the memory usage charged for the canister log buffer.
+    pub fn memory_usage(&self) -> usize {
+        Self::memory_usage_for_capacity(self.capacity())
+    }
+
+    /// Returns the memory usage charged for a canister log buffer of the
+    /// given capacity. Only the part above the default buffer size is charged.
+    pub fn memory_usage_for_capacity(capacity: usize) -> usize {
+        capacity.saturating_sub(DEFAULT_CANISTER_LOG_BUFFER_SIZE)
+    }
+
     /// Returns the used space in the canister log buffer.
     pub fn used_space(&self) -> usize {
         self.records.used_space()
@@ -157,11 +207,15 @@ impl CanisterLog {
     /// Adds a new log record.
     pub fn add_record(&mut self, timestamp_nanos: u64, content: Vec<u8>) {
         // Add record and update the next index.
-        self.records.push_back(truncate_content(CanisterLogRecord {
-            idx: self.next_idx,
-            timestamp_nanos,
-            content,
-        }));
+        let capacity = self.capacity();
+        self.records.push_back(truncate_content(
+            CanisterLogRecord {
+                idx: self.next_idx,
+                timestamp_nanos,
+                content,
+            },
+            capacity,
+        ));
         self.next_idx += 1;
     }
 
@@ -343,4 +397,35 @@ mod tests {
             ]))
         );
     }
+
+    #[test]
+    fn test_canister_log_with_capacity_applies_memory_limit() {
+        let capacity = 4 * TEST_MAX_ALLOWED_SIZE;
+        let mut log = CanisterLog::new_with_capacity(0, vec![], capacity);
+        for _ in 0..5 {
+            log.add_record(100, vec![b'a'; TEST_MAX_ALLOWED_SIZE]);
+        }
+        // Assert the bigger buffer keeps more than one record within its limit.
+        assert_eq!(log.records().len(), 3);
+        assert!(log.used_space() <= capacity);
+        assert_eq!(log.capacity(), capacity);
+        assert_eq!(log.memory_usage(), capacity - TEST_MAX_ALLOWED_SIZE);
+    }
+
+    #[test]
+    fn test_canister_log_set_capacity_drops_oldest_records() {
+        let mut log = CanisterLog::new_with_capacity(0, vec![], 4 * TEST_MAX_ALLOWED_SIZE);
+        log.add_record(100, b"record #0".to_vec());
+        log.add_record(101, BIGGER_THAN_LIMIT_MESSAGE.to_vec());
+        log.add_record(102, b"record #2".to_vec());
+        // Act.
+        log.set_capacity(TEST_MAX_ALLOWED_SIZE);
+        // Assert only the newest record survived and nothing is charged.
+        assert_eq!(
+            log.records(),
+            &VecDeque::from(canister_log_records(&[(2, 102, b"record #2")]))
+        );
+        assert_eq!(log.capacity(), TEST_MAX_ALLOWED_SIZE);
+        assert_eq!(log.memory_usage(), 0);
+    }
 }
@@ -0,0 +1,110 @@
+use ic_crypto_sha2::Sha256;
+use ic_management_canister_types::EnvironmentVariable;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// The maximum number of environment variables a canister can have.
+pub const MAX_ENVIRONMENT_VARIABLES_COUNT: usize = 20;
+
+/// The maximum length in bytes of an environment variable name.
+pub const MAX_ENVIRONMENT_VARIABLE_NAME_LENGTH: usize = 128;
+
+/// The maximum length in bytes of an environment variable value.
+pub const MAX_ENVIRONMENT_VARIABLE_VALUE_LENGTH: usize = 128;
+
+/// The environment variables of a canister, kept sorted by name so that
+/// index-based access through the system API is deterministic.
+#[derive(Clone, Eq, PartialEq, Debug, Default, Deserialize, Serialize)]
+pub struct EnvironmentVariables(BTreeMap<String, String>);
+
+impl EnvironmentVariables {
+    pub fn new(environment_variables: BTreeMap<String, String>) -> Self {
+        Self(environment_variables)
+    }
+
+    /// Returns the number of environment variables.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the name of the environment variable at the given index in
+    /// the name order.
+    pub fn name_at(&self, index: usize) -> Option<&str> {
+        self.0.keys().nth(index).map(String::as_str)
+    }
+
+    /// Returns the value of the environment variable with the given name.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.0.get(name).map(String::as_str)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &String)> {
+        self.0.iter()
+    }
+
+    /// Returns the SHA-256 hash of the environment variables, which is
+    /// recorded in the canister history instead of the values themselves.
+    ///
+    /// Every name and value is prefixed with its length so that different
+    /// sets of variables cannot produce the same input to the hash.
+    pub fn hash(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        for (name, value) in self.0.iter() {
+            hasher.write(&(name.len() as u64).to_be_bytes());
+            hasher.write(name.as_bytes());
+            hasher.write(&(value.len() as u64).to_be_bytes());
+            hasher.write(value.as_bytes());
+        }
+        hasher.finish()
+    }
+}
+
+impl From<&EnvironmentVariables> for Vec<EnvironmentVariable> {
+    fn from(environment_variables: &EnvironmentVariables) -> Self {
+        environment_variables
+            .iter()
+            .map(|(name, value)| EnvironmentVariable {
+                name: name.clone(),
+                value: value.clone(),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn environment_variables(pairs: &[(&str, &str)]) -> EnvironmentVariables {
+        EnvironmentVariables::new(
+            pairs
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_environment_variables_are_sorted_by_name() {
+        let env = environment_variables(&[("B", "2"), ("A", "1"), ("C", "3")]);
+        assert_eq!(env.len(), 3);
+        assert_eq!(env.name_at(0), Some("A"));
+        assert_eq!(env.name_at(2), Some("C"));
+        assert_eq!(env.name_at(3), None);
+        assert_eq!(env.get("B"), Some("2"));
+        assert_eq!(env.get("D"), None);
+    }
+
+    #[test]
+    fn test_environment_variables_hash_is_unambiguous() {
+        let a = environment_variables(&[("AB", "C")]);
+        let b = environment_variables(&[("A", "BC")]);
+        assert_ne!(a.hash(), b.hash());
+        assert_eq!(a.hash(), environment_variables(&[("AB", "C")]).hash());
+        assert_ne!(EnvironmentVariables::default().hash(), a.hash());
+    }
+}
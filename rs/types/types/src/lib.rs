@@ -69,6 +69,7 @@ pub mod canister_http;
 pub mod canister_log;
 pub mod consensus;
 pub mod crypto;
+pub mod environment_variables;
 pub mod funds;
 pub mod hostos_version;
 pub mod ingress;
@@ -88,7 +89,13 @@ pub mod xnet;
 #[cfg(test)]
 pub mod exhaustive;
 
-pub use crate::canister_log::{CanisterLog, MAX_ALLOWED_CANISTER_LOG_BUFFER_SIZE};
+pub use crate::canister_log::{
+    CanisterLog, DEFAULT_CANISTER_LOG_BUFFER_SIZE, MAX_CANISTER_LOG_BUFFER_SIZE,
+};
+pub use crate::environment_variables::{
+    EnvironmentVariables, MAX_ENVIRONMENT_VARIABLES_COUNT, MAX_ENVIRONMENT_VARIABLE_NAME_LENGTH,
+    MAX_ENVIRONMENT_VARIABLE_VALUE_LENGTH,
+};
 pub use crate::replica_version::ReplicaVersion;
 pub use crate::time::Time;
 pub use funds::*;